@@ -0,0 +1,82 @@
+//! Benchmarks for the parts of the game engine that run on every tick or that
+//! scale with the size of the level.  Run with `cargo bench --features bench`.
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use ratsnake::bench::{generate_maze, generate_obstacles, Engine};
+use std::hint::black_box;
+
+const SEED: u64 = 0x0123_4567_89AB_CDEF;
+
+fn advance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("advance");
+    let fresh = Engine::new(SEED);
+    group.bench_function("new_game", |b| {
+        b.iter_batched(
+            || fresh.clone(),
+            |mut engine| engine.advance(),
+            BatchSize::SmallInput,
+        );
+    });
+    let full = Engine::nearly_full(16, SEED);
+    group.bench_function("nearly_full", |b| {
+        b.iter_batched(
+            || full.clone(),
+            |mut engine| engine.advance(),
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    let fresh = Engine::new(SEED);
+    group.bench_function("new_game", |b| b.iter(|| black_box(fresh.render_all())));
+    let full = Engine::nearly_full(16, SEED);
+    group.bench_function("nearly_full", |b| b.iter(|| black_box(full.render_all())));
+    // Draw the first frame so that the next one only redraws what changed
+    black_box(full.render());
+    group.bench_function("nearly_full_after_tick", |b| {
+        b.iter_batched(
+            || {
+                let mut engine = full.clone();
+                engine.advance();
+                engine
+            },
+            |engine| black_box(engine.render()),
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+fn place_fruit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("place_fruit");
+    for free in [1, 16, 256] {
+        let engine = Engine::nearly_full(free, SEED);
+        group.bench_with_input(BenchmarkId::from_parameter(free), &engine, |b, engine| {
+            b.iter_batched(
+                || engine.clone(),
+                |mut engine| black_box(engine.place_fruit()),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn level_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("level_generation");
+    for (width, height) in [(76, 19), (200, 100), (500, 200)] {
+        let id = format!("{width}x{height}");
+        group.bench_with_input(BenchmarkId::new("obstacles", &id), &(), |b, ()| {
+            b.iter(|| generate_obstacles(black_box(width), black_box(height), SEED));
+        });
+        group.bench_with_input(BenchmarkId::new("maze", &id), &(), |b, ()| {
+            b.iter(|| generate_maze(black_box(width), black_box(height), SEED));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, advance, render, place_fruit, level_generation);
+criterion_main!(benches);
@@ -0,0 +1,287 @@
+//! The about screen, showing the program version and the files that the
+//! program is reading & writing so that problems with saving & loading data
+//! can be tracked down
+use crate::app::Screen;
+use crate::command::Command;
+use crate::consts;
+use crate::i18n::{tr, Msg};
+use crate::input::EventSource;
+use crate::migrations::SaveFile;
+use crate::palette::{Palette, PaletteAction, PaletteEntry};
+use crate::util::{data_dir, get_display_area, Globals};
+use crossterm::event::Event;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+    Frame,
+};
+use std::path::PathBuf;
+
+/// The about screen
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct About {
+    /// The facts shown on the screen as pairs of labels & values, determined
+    /// when the screen is opened
+    facts: Vec<(Msg, String)>,
+
+    /// Global data (options & high scores)
+    globals: Globals,
+}
+
+impl About {
+    /// Create a new about screen from the given globals, resolving the paths
+    /// of the files in use from the configuration
+    pub(crate) fn new(globals: Globals) -> Self {
+        let config = &globals.config;
+        let facts = vec![
+            (Msg::Version, String::from(env!("CARGO_PKG_VERSION"))),
+            (
+                Msg::ConfigFile,
+                describe_path::<()>(Ok(config.path.clone())),
+            ),
+            (
+                Msg::OptionsFile,
+                describe_path(config.save_file_path(SaveFile::Options)),
+            ),
+            (
+                Msg::HighScoresFile,
+                describe_path(config.save_file_path(SaveFile::HighScores)),
+            ),
+            (
+                Msg::HistoryFile,
+                describe_path(config.save_file_path(SaveFile::History)),
+            ),
+            (
+                Msg::SessionFile,
+                describe_path(config.save_file_path(SaveFile::Session)),
+            ),
+            (
+                Msg::FavoritesFile,
+                describe_path(config.save_file_path(SaveFile::Favorites)),
+            ),
+            (
+                Msg::ScreenshotsDir,
+                describe_path(config.screenshots_dir().map(Some)),
+            ),
+            (Msg::DataDir, describe_path(data_dir().map(Some).ok_or(()))),
+            (
+                Msg::IgnoreErrors,
+                String::from(tr(if config.ignore_errors() {
+                    Msg::Yes
+                } else {
+                    Msg::No
+                })),
+            ),
+        ];
+        About { facts, globals }
+    }
+
+    /// Return a mutable reference to the screen's global data
+    pub(crate) fn globals_mut(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+
+    /// Draw the about screen on the given frame
+    pub(crate) fn draw(&self, frame: &mut Frame<'_>) {
+        frame.render_widget(self, frame.area());
+    }
+
+    /// Receive & handle the next input event from `events`.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn process_input(
+        &self,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<Option<Screen>> {
+        Ok(self.handle_event(events.read()?))
+    }
+
+    /// Return a command palette listing the actions available from the about
+    /// screen
+    pub(crate) fn palette(&self) -> Palette {
+        let mut entries = vec![PaletteEntry::new(PaletteAction::MainMenu)];
+        if !self.globals.kiosk {
+            entries.push(PaletteEntry::new(PaletteAction::Quit));
+        }
+        Palette::new(entries)
+    }
+
+    /// Carry out an action chosen from the command palette.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn run_palette_action(&self, action: PaletteAction) -> Option<Screen> {
+        match action {
+            PaletteAction::MainMenu => Some(self.main_menu()),
+            PaletteAction::Quit => Some(Screen::Quit),
+            PaletteAction::Play
+            | PaletteAction::Toggle(_)
+            | PaletteAction::SetSeed(_)
+            | PaletteAction::HighScores
+            | PaletteAction::Progress
+            | PaletteAction::About
+            | PaletteAction::Favorites
+            | PaletteAction::Levels
+            | PaletteAction::AddFavorite
+            | PaletteAction::Resume
+            | PaletteAction::Restart => None,
+        }
+    }
+
+    /// Handle the given input event.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    fn handle_event(&self, event: Event) -> Option<Screen> {
+        match Command::from_key_event(event.as_key_press_event()?)? {
+            Command::Quit | Command::Q => Some(Screen::Quit),
+            Command::Esc | Command::M => Some(self.main_menu()),
+            _ => None,
+        }
+    }
+
+    /// Return a main menu screen to switch to
+    fn main_menu(&self) -> Screen {
+        Screen::Main(crate::menu::MainMenu::new(self.globals.clone()))
+    }
+}
+
+impl Widget for &About {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        let direction = self.globals.config.layout_direction;
+        let [title_area, facts_area, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .spacing(1)
+        .areas(display);
+
+        direction
+            .align_start(Line::raw(tr(Msg::AboutTitle)))
+            .style(consts::SCORE_BAR_STYLE)
+            .render(title_area, buf);
+
+        // Each fact takes up two lines, the label followed by the value, so
+        // that long paths have the full width of the screen
+        let lines = self.facts.iter().flat_map(|(label, value)| {
+            [
+                direction.align_start(Line::styled(tr(*label), consts::ABOUT_LABEL_STYLE)),
+                direction.align_start(Line::raw(value.as_str())),
+            ]
+        });
+        for (line, row) in lines.zip(facts_area.rows()) {
+            line.render(row, buf);
+        }
+
+        direction
+            .align_start(Line::from_iter([
+                Span::raw(format!("{} (", tr(Msg::MainMenu))),
+                Span::styled("Esc", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::Quit))),
+                Span::styled("q", consts::KEY_STYLE),
+                Span::raw(")"),
+            ]))
+            .render(help_area, buf);
+    }
+}
+
+/// Return a description of the path of a file or directory for display on
+/// the about screen: the path itself (noting if nothing exists there yet),
+/// "(none)" if the file is disabled, or a note that the path could not be
+/// determined
+fn describe_path<E>(path: Result<Option<PathBuf>, E>) -> String {
+    match path {
+        Ok(Some(p)) if p.exists() => p.display().to_string(),
+        Ok(Some(p)) => format!("{} {}", p.display(), tr(Msg::PathMissing)),
+        Ok(None) => String::from(tr(Msg::PathNone)),
+        Err(_) => String::from(tr(Msg::PathUnknown)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyCode;
+    use tempfile::TempDir;
+
+    #[test]
+    fn resolved_paths() {
+        let tmpdir = TempDir::new().unwrap();
+        let cfgfile = tmpdir.path().join("config.toml");
+        let history = tmpdir.path().join("history.jsonl");
+        std::fs::write(&history, "").unwrap();
+        let about = About::new(Globals::in_tempdir(
+            &tmpdir,
+            "[files]\noptions-file = false\nignore-errors = true\n",
+        ));
+        let fact = |label: Msg| {
+            about
+                .facts
+                .iter()
+                .find(|&&(l, _)| l == label)
+                .map(|(_, value)| value.as_str())
+                .unwrap()
+        };
+        assert_eq!(fact(Msg::ConfigFile), cfgfile.display().to_string());
+        assert_eq!(fact(Msg::OptionsFile), "(none)");
+        assert_eq!(
+            fact(Msg::HighScoresFile),
+            format!(
+                "{} (does not exist yet)",
+                tmpdir
+                    .path()
+                    .join("highscores")
+                    .join(crate::highscores::HighScores::ARCADE_FILE_NAME)
+                    .display()
+            )
+        );
+        assert_eq!(fact(Msg::HistoryFile), history.display().to_string());
+        assert_eq!(fact(Msg::IgnoreErrors), "Yes");
+    }
+
+    #[test]
+    fn render() {
+        let about = About {
+            facts: vec![
+                (Msg::Version, String::from("1.2.3")),
+                (
+                    Msg::ConfigFile,
+                    String::from("/home/luser/.config/ratsnake/config.toml"),
+                ),
+                (Msg::OptionsFile, String::from("(none)")),
+                (
+                    Msg::HighScoresFile,
+                    String::from(
+                        "/home/luser/.local/share/ratsnake/highscores/arcade.json (does not exist yet)",
+                    ),
+                ),
+                (Msg::IgnoreErrors, String::from("No")),
+            ],
+            globals: Globals::default(),
+        };
+        let buffer = crate::snapshot::render(&about);
+        crate::snapshot::assert_snapshot("about/render", &buffer);
+    }
+
+    #[test]
+    fn keys() {
+        let about = About {
+            facts: Vec::new(),
+            globals: Globals::default(),
+        };
+        assert!(about.handle_event(Event::Key(KeyCode::Up.into())).is_none());
+        assert!(matches!(
+            about.handle_event(Event::Key(KeyCode::Esc.into())),
+            Some(Screen::Main(_))
+        ));
+        assert!(matches!(
+            about.handle_event(Event::Key(KeyCode::Char('q').into())),
+            Some(Screen::Quit)
+        ));
+    }
+}
@@ -1,61 +1,453 @@
-use crate::game::Game;
+use crate::about::About;
+#[cfg(feature = "broadcast")]
+use crate::broadcast::Broadcaster;
+use crate::command::Command;
+use crate::config::{Config, ConfigWatcher};
+use crate::consts;
+use crate::diagnostics::Diagnostics;
+use crate::game::{Game, SplitGame};
+use crate::history::GameRecord;
+use crate::i18n::{tr_args, Msg};
+use crate::input::{EventSource, TerminalEvents, TrackedEvents};
+use crate::kiosk::Passphrase;
 use crate::menu::MainMenu;
-use ratatui::{backend::Backend, Terminal};
+use crate::options;
+use crate::palette::{self, Palette, PaletteAction, PaletteOutcome};
+use crate::progress::Progress;
+use crate::recovery::CorruptFile;
+use crate::scoreboard::Scoreboard;
+use crate::screenshot::save_screenshot;
+use crate::session::{Heartbeat, ScreenName, SessionFile};
+use crate::terminal;
+use crate::theme;
+use crate::util::{error_chain, get_display_area, Globals, SaveError};
+use crate::warning::Warning;
+use ratatui::{
+    backend::Backend,
+    buffer::Buffer,
+    layout::Rect,
+    text::Line,
+    widgets::{Clear, Widget},
+    Terminal,
+};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
 
 /// The `ratsnake` application, the top-level struct for the program
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub(crate) struct App {
     /// What screen are we currently displaying?
     screen: Screen,
+
+    /// Where input events are read from
+    events: TrackedEvents,
+
+    /// A watcher for changes to the configuration file, if the configuration
+    /// should be reloaded when the file changes
+    watcher: Option<ConfigWatcher>,
+
+    /// Timing measurements for the main loop & the overlay that shows them
+    diagnostics: Diagnostics,
+
+    /// The command palette, if it is open
+    palette: Option<Palette>,
+
+    /// In kiosk mode, the key sequence that must be typed in order to quit.
+    /// While this is set, requests from screens to quit are ignored.
+    passphrase: Option<Passphrase>,
+
+    /// Whether mouse capture is currently enabled in the terminal, as set by
+    /// the `mouse-steering` setting
+    mouse_capture: bool,
+
+    /// Whether the terminal should be cleared before the next frame is drawn
+    /// so that the whole screen is redrawn from scratch, getting rid of any
+    /// stray output from other programs
+    clear_pending: bool,
+
+    /// Whether a screenshot of the next frame drawn should be saved
+    screenshot_pending: bool,
+
+    /// A message shown at the bottom of the display for a limited time, such
+    /// as the result of saving a screenshot
+    notice: Option<Notice>,
+
+    /// The file to which heartbeats are written so that the next session can
+    /// tell whether this one ended abruptly, if enabled
+    session: Option<SessionFile>,
+
+    /// A record of the last game played, taken from the final screen when
+    /// the application quits
+    last_game: Option<GameRecord>,
+
+    /// A handle for serving the state of the game to WebSocket clients, if
+    /// broadcasting is enabled
+    #[cfg(feature = "broadcast")]
+    broadcaster: Option<Broadcaster>,
 }
 
 impl App {
-    /// Create a new `App` from the given [`Globals`][crate::util::Globals]
-    /// that shows the main menu.
-    pub(crate) fn new(globals: crate::util::Globals) -> App {
+    /// Create a new `App` from the given [`Globals`] that shows the main
+    /// menu.  If `watcher` is non-`None`, the configuration will be reloaded
+    /// whenever the watched file changes.
+    pub(crate) fn new(globals: Globals, watcher: Option<ConfigWatcher>) -> App {
+        let mouse_capture = globals.config.game.mouse_steering;
         let screen = Screen::Main(MainMenu::new(globals));
-        App { screen }
+        App {
+            screen,
+            events: TrackedEvents::new(TerminalEvents),
+            watcher,
+            diagnostics: Diagnostics::default(),
+            palette: None,
+            passphrase: None,
+            mouse_capture,
+            clear_pending: false,
+            screenshot_pending: false,
+            notice: None,
+            session: None,
+            last_game: None,
+            #[cfg(feature = "broadcast")]
+            broadcaster: None,
+        }
+    }
+
+    /// Have the main menu ask the user what to do about each of the given
+    /// data files that could not be parsed at startup
+    pub(crate) fn with_corrupt_files(mut self, files: Vec<CorruptFile>) -> App {
+        if let Screen::Main(ref mut menu) = self.screen {
+            menu.recover(files);
+        }
+        self
     }
 
-    /// Run the application on the given terminal
-    pub(crate) fn run<B: Backend>(mut self, mut terminal: Terminal<B>) -> std::io::Result<()> {
+    /// Have the main menu show the given warnings about problems encountered
+    /// at startup
+    pub(crate) fn with_startup_warnings(mut self, warnings: Vec<Warning>) -> App {
+        if let Screen::Main(ref mut menu) = self.screen {
+            menu.warn(warnings);
+        }
+        self
+    }
+
+    /// Have the main menu note that the given errors were ignored while
+    /// loading data files at startup, offering to show their details
+    pub(crate) fn with_ignored_errors(mut self, errors: Vec<Warning>) -> App {
+        if let Screen::Main(ref mut menu) = self.screen {
+            menu.note_ignored(errors);
+        }
+        self
+    }
+
+    /// Run in kiosk mode, in which the application can only be quit by typing
+    /// `passphrase`
+    pub(crate) fn with_kiosk(mut self, passphrase: Passphrase) -> App {
+        self.passphrase = Some(passphrase);
+        self
+    }
+
+    /// Periodically record the state of the session in `session`, deleting
+    /// the file when the application quits
+    pub(crate) fn with_session(mut self, session: SessionFile) -> App {
+        self.session = Some(session);
+        self
+    }
+
+    /// Serve the state of each game played to WebSocket clients via
+    /// `broadcaster`
+    #[cfg(feature = "broadcast")]
+    pub(crate) fn with_broadcaster(mut self, broadcaster: Broadcaster) -> App {
+        self.broadcaster = Some(broadcaster);
+        self
+    }
+
+    /// Read input events from `events` instead of the terminal
+    pub(crate) fn with_events<E: EventSource + 'static>(mut self, events: E) -> App {
+        self.events = TrackedEvents::new(events);
+        self
+    }
+
+    /// Run the application on the given terminal.
+    ///
+    /// Returns a record of the last game played, if any.  If the application
+    /// quit in the middle of a game, the record's cause is
+    /// [`EndCause::Interrupted`][crate::history::EndCause::Interrupted].
+    pub(crate) fn run<B: Backend>(
+        mut self,
+        mut terminal: Terminal<B>,
+    ) -> std::io::Result<Option<GameRecord>> {
         while !self.quitting() {
-            self.draw(&mut terminal)?;
-            self.process_input()?;
+            self.step(&mut terminal)?;
+        }
+        if let Some(session) = self.session.take() {
+            session.end()?;
+        }
+        Ok(self.last_game)
+    }
+
+    /// Perform one iteration of the main loop: draw the current screen on
+    /// the terminal and then receive & handle the next input event or lack
+    /// thereof
+    fn step<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> std::io::Result<()> {
+        if let Some(config) = self.watcher.as_mut().and_then(ConfigWatcher::poll) {
+            self.reload_config(config)?;
+        }
+        if std::mem::take(&mut self.clear_pending) {
+            terminal.clear()?;
+        }
+        if self.screenshot_pending {
+            // Keep the old message out of the screenshot
+            self.notice = None;
+        }
+        let start = Instant::now();
+        let frame = self.draw(terminal)?;
+        let elapsed = start.elapsed();
+        self.diagnostics.record_draw(Instant::now(), elapsed);
+        if let Some(buf) = frame {
+            self.screenshot_pending = false;
+            self.screenshot(&buf);
+        }
+        // If drawing blocked for a long time, the player likely couldn't see
+        // the game in the meantime
+        if elapsed >= consts::STALL_THRESHOLD {
+            if let Screen::Game(ref mut game) = self.screen {
+                game.handle_stall();
+            }
+        }
+        let before = self.tick_timing();
+        self.process_input()?;
+        self.measure_input(before);
+        if let Some(session) = self.session.as_mut() {
+            // A missed heartbeat only matters if the program then dies, so
+            // there's no need to interrupt the user about it
+            let _ = session.beat(self.screen.heartbeat());
+        }
+        #[cfg(feature = "broadcast")]
+        if let (Some(broadcaster), Screen::Game(ref game)) =
+            (self.broadcaster.as_mut(), &self.screen)
+        {
+            broadcaster.send(game.snapshot());
         }
         Ok(())
     }
 
-    /// Draw the current screen on the terminal
-    fn draw<B: Backend>(&self, terminal: &mut Terminal<B>) -> std::io::Result<()> {
-        match self.screen {
-            Screen::Main(ref menu) => {
-                terminal.draw(|frame| menu.draw(frame))?;
+    /// Replace the current screen's configuration with `config`, turning
+    /// mouse capture on or off if the `mouse-steering` setting changed.
+    /// Gamepad settings are left as-is, as they only take effect at startup.
+    fn reload_config(&mut self, config: Config) -> std::io::Result<()> {
+        options::set_max_fruits(config.game.max_fruits);
+        if config.game.mouse_steering != self.mouse_capture {
+            terminal::set_mouse_capture(config.game.mouse_steering)?;
+            self.mouse_capture = config.game.mouse_steering;
+        }
+        if let Some(globals) = self.screen.globals_mut() {
+            globals.config = Config {
+                gamepad: globals.config.gamepad.clone(),
+                ..config
+            };
+        }
+        Ok(())
+    }
+
+    /// Draw the current screen on the terminal, along with the theme's
+    /// backdrop (if any), the command palette (if it is open), the
+    /// diagnostics overlay (if it is enabled), and the current notice (if
+    /// any).
+    ///
+    /// If a screenshot is pending, returns the contents of the frame that was
+    /// drawn; otherwise (or if there is no screen to draw), returns `None`.
+    fn draw<B: Backend>(&self, terminal: &mut Terminal<B>) -> std::io::Result<Option<Buffer>> {
+        let now = Instant::now();
+        let overlay = |frame: &mut ratatui::Frame<'_>| {
+            if let Some(backdrop) = theme::backdrop() {
+                frame.render_widget(backdrop, frame.area());
+            }
+            if let Some(ref palette) = self.palette {
+                frame.render_widget(palette, frame.area());
+            }
+            if self.diagnostics.enabled() {
+                frame.render_widget(&self.diagnostics, frame.area());
             }
-            Screen::Game(ref game) => {
-                terminal.draw(|frame| game.draw(frame))?;
+            if let Some(notice) = self.notice.as_ref().filter(|n| n.expires > now) {
+                frame.render_widget(notice, frame.area());
             }
-            Screen::Quit => (),
+        };
+        let completed = match self.screen {
+            Screen::Main(ref menu) => terminal.draw(|frame| {
+                menu.draw(frame);
+                overlay(frame);
+            })?,
+            Screen::Game(ref game) => terminal.draw(|frame| {
+                game.draw(frame);
+                overlay(frame);
+            })?,
+            Screen::Split(ref game) => terminal.draw(|frame| {
+                game.draw(frame);
+                overlay(frame);
+            })?,
+            Screen::HighScores(ref board) => terminal.draw(|frame| {
+                board.draw(frame);
+                overlay(frame);
+            })?,
+            Screen::Progress(ref progress) => terminal.draw(|frame| {
+                progress.draw(frame);
+                overlay(frame);
+            })?,
+            Screen::About(ref about) => terminal.draw(|frame| {
+                about.draw(frame);
+                overlay(frame);
+            })?,
+            Screen::Quit => return Ok(None),
+        };
+        Ok(self.screenshot_pending.then(|| completed.buffer.clone()))
+    }
+
+    /// Save `buf` as a screenshot in the screenshots directory and show a
+    /// notice reporting the result
+    fn screenshot(&mut self, buf: &Buffer) {
+        let Some(config) = self.screen.globals_mut().map(|g| &g.config) else {
+            return;
+        };
+        let r = config
+            .screenshots_dir()
+            .map_err(|_| SaveError::no_path("screenshot"))
+            .and_then(|dir| save_screenshot(buf, &dir, config.files.ansi_screenshots));
+        let text = match r {
+            Ok(path) => tr_args(Msg::ScreenshotSaved, &[("path", &path.display())]),
+            Err(e) => error_chain(&e),
+        };
+        self.notice = Some(Notice {
+            text,
+            expires: Instant::now() + consts::NOTICE_DURATION,
+        });
+    }
+
+    /// Receive & handle the next input event or lack thereof.  While the
+    /// command palette is open, it receives all input instead of the current
+    /// screen.
+    fn process_input(&mut self) -> std::io::Result<()> {
+        if let Some(ref mut palette) = self.palette {
+            let next = match palette.handle_event(self.events.read()?) {
+                Some(PaletteOutcome::Run(action)) => {
+                    self.palette = None;
+                    self.screen.run_palette_action(action)
+                }
+                Some(PaletteOutcome::Close) => {
+                    self.palette = None;
+                    None
+                }
+                None => None,
+            };
+            self.switch_to(next);
+            return Ok(());
         }
+        let next = match self.screen {
+            Screen::Main(ref mut menu) => menu.process_input(&mut self.events)?,
+            Screen::Game(ref mut game) => game.process_input(&mut self.events)?,
+            Screen::Split(ref mut game) => game.process_input(&mut self.events)?,
+            Screen::HighScores(ref mut board) => board.process_input(&mut self.events)?,
+            Screen::Progress(ref mut progress) => progress.process_input(&mut self.events)?,
+            Screen::About(ref about) => about.process_input(&mut self.events)?,
+            Screen::Quit => None,
+        };
+        self.switch_to(next);
         Ok(())
     }
 
-    /// Receive & handle the next input event or lack thereof
-    fn process_input(&mut self) -> std::io::Result<()> {
+    /// Switch to the screen returned by the current screen or the command
+    /// palette in response to input, if any.  When leaving a game, its end is
+    /// recorded in the game history.
+    fn switch_to(&mut self, next: Option<Screen>) {
+        match next {
+            // In kiosk mode, only the passphrase can quit the application
+            Some(Screen::Quit) if self.passphrase.is_some() => (),
+            Some(Screen::Quit) => self.quit(),
+            Some(screen) => {
+                if let Screen::Game(ref mut game) = self.screen {
+                    game.record_end();
+                }
+                self.screen = screen;
+            }
+            None => (),
+        }
+    }
+
+    /// If the current screen is a game whose snake is being moved by a timer,
+    /// return the number of ticks that have passed and the current time
+    /// between ticks
+    fn tick_timing(&self) -> Option<(u32, Duration)> {
         match self.screen {
-            Screen::Main(ref mut menu) => {
-                if let Some(screen) = menu.process_input()? {
-                    self.screen = screen;
+            Screen::Game(ref game) => game.tick_timing(),
+            _ => None,
+        }
+    }
+
+    /// Update the diagnostics after a call to
+    /// [`process_input()`][App::process_input], given the value of
+    /// [`tick_timing()`][App::tick_timing] from before the call.  If an input
+    /// event was read from [`App::events`] during the call, the time taken to
+    /// handle it is recorded.  If the event was the key for toggling the
+    /// diagnostics overlay, the overlay is toggled; if it was the screenshot
+    /// key, a screenshot of the next frame is saved; and if it was
+    /// <kbd>Ctrl</kbd>+<kbd>L</kbd>, the terminal is cleared before the next
+    /// frame.  If it was a key for opening the command palette and the
+    /// current screen can be interrupted, the palette is opened.  In kiosk
+    /// mode, if the event completed the passphrase, the application quits.
+    fn measure_input(&mut self, before: Option<(u32, Duration)>) {
+        let now = Instant::now();
+        if let Some((read_at, event)) = self.events.take_last_read() {
+            self.diagnostics
+                .record_event(now, now.saturating_duration_since(read_at));
+            if let (Some(passphrase), Some(ev)) =
+                (self.passphrase.as_mut(), event.as_key_press_event())
+            {
+                if passphrase.feed(ev) {
+                    self.quit();
                 }
             }
-            Screen::Game(ref mut game) => {
-                if let Some(screen) = game.process_input()? {
-                    self.screen = screen;
+            if event
+                .as_key_press_event()
+                .is_some_and(|ev| Command::from_key_event(ev) == Some(Command::Redraw))
+            {
+                self.clear_pending = true;
+            }
+            if self.palette.is_none()
+                && event
+                    .as_key_press_event()
+                    .is_some_and(palette::opens_palette)
+            {
+                self.palette = self.screen.palette();
+            }
+            if let (Some((diagnostics, screenshot)), Some(ev)) = (
+                self.screen
+                    .globals_mut()
+                    .map(|g| (g.config.controls.diagnostics, g.config.controls.screenshot)),
+                event.as_key_press_event(),
+            ) {
+                if diagnostics.matches(ev) {
+                    self.diagnostics.toggle();
+                }
+                if screenshot.matches(ev) {
+                    self.screenshot_pending = true;
                 }
             }
-            Screen::Quit => (),
         }
-        Ok(())
+        match (before, self.tick_timing()) {
+            (Some((ticks, period)), Some((after, _))) if after == ticks.saturating_add(1) => {
+                self.diagnostics.record_tick(now, period);
+            }
+            (Some((ticks, _)), Some((after, _))) if after == ticks => (),
+            _ => self.diagnostics.interrupt_ticks(),
+        }
+    }
+
+    /// Switch to [`Screen::Quit`], keeping a record of the last game played
+    /// on the current screen
+    fn quit(&mut self) {
+        if let Screen::Game(ref mut game) = self.screen {
+            game.record_end();
+        }
+        self.last_game = self.screen.last_game();
+        self.screen = Screen::Quit;
     }
 
     /// Should the application terminate?
@@ -64,6 +456,37 @@ impl App {
     }
 }
 
+/// A short message shown centered at the bottom of the display, on top of
+/// whatever screen is current, until a given time
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Notice {
+    /// The text of the message
+    text: String,
+
+    /// When the message should stop being shown
+    expires: Instant,
+}
+
+impl Widget for &Notice {
+    /// Draw the notice on the bottom row of the display.  `area` should be
+    /// the area of the entire frame.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        let text = format!(" {} ", self.text);
+        let width = u16::try_from(text.width())
+            .unwrap_or(u16::MAX)
+            .min(display.width);
+        let notice_area = Rect {
+            x: display.x + (display.width - width) / 2,
+            y: display.bottom().saturating_sub(1),
+            width,
+            height: 1,
+        };
+        Clear.render(notice_area, buf);
+        Line::styled(text, consts::NOTICE_STYLE).render(notice_area, buf);
+    }
+}
+
 /// An enum of the application's top-level screens.
 ///
 /// Screen values implement the following *de facto* trait:
@@ -73,9 +496,20 @@ impl App {
 ///     /// Draw the screen on the given frame
 ///     fn draw(&self, frame: &mut Frame<'_>);
 ///
-///     /// Handle the next input event or lack thereof.  Return `Some(screen)`
-///     /// if the application should switch to a new screen.
-///     fn process_input(&mut self) -> std::io::Result<Option<Screen>>;
+///     /// Handle the next input event from `events` or lack thereof.  Return
+///     /// `Some(screen)` if the application should switch to a new screen.
+///     fn process_input(
+///         &mut self,
+///         events: &mut dyn EventSource,
+///     ) -> std::io::Result<Option<Screen>>;
+///
+///     /// Return a command palette listing the actions that the screen
+///     /// offers, or `None` if the screen can't be interrupted right now
+///     fn palette(&mut self) -> Option<Palette>;
+///
+///     /// Carry out an action chosen from the command palette.  Return
+///     /// `Some(screen)` if the application should switch to a new screen.
+///     fn run_palette_action(&mut self, action: PaletteAction) -> Option<Screen>;
 /// }
 /// ```
 #[derive(Clone, Debug)]
@@ -84,8 +518,409 @@ pub(crate) enum Screen {
     Main(MainMenu),
 
     /// The gameplay screen
-    Game(Game),
+    Game(Box<Game>),
+
+    /// The gameplay screen for a two-player game on a split board
+    Split(Box<SplitGame>),
+
+    /// The high scores screen
+    HighScores(Scoreboard),
+
+    /// The progress screen, summarizing the game history by week or month
+    Progress(Progress),
+
+    /// The about screen, showing the version & the files in use
+    About(About),
 
     /// A pseudo-screen used to indicate that the application should terminate
     Quit,
 }
+
+impl Screen {
+    /// Return a record of the last game played: the game on this screen if
+    /// it's in progress, or else the last game that finished
+    fn last_game(&mut self) -> Option<GameRecord> {
+        if let Screen::Game(game) = self {
+            if let Some(record) = game.interrupted_record() {
+                return Some(record);
+            }
+        }
+        self.globals_mut()?.last_game.take()
+    }
+
+    /// Return a heartbeat describing the screen and, if it's a game in
+    /// progress, the game
+    fn heartbeat(&self) -> Heartbeat {
+        let (screen, game) = match self {
+            Screen::Main(_) | Screen::Quit => (ScreenName::Menu, None),
+            Screen::Game(game) => (ScreenName::Game, game.interrupted_record()),
+            Screen::Split(_) => (ScreenName::Game, None),
+            Screen::HighScores(_) => (ScreenName::HighScores, None),
+            Screen::Progress(_) => (ScreenName::Progress, None),
+            Screen::About(_) => (ScreenName::About, None),
+        };
+        Heartbeat {
+            timestamp: jiff::Timestamp::now(),
+            screen,
+            game,
+        }
+    }
+
+    /// Return a command palette listing the actions that the screen offers,
+    /// or `None` if the screen can't be interrupted right now
+    fn palette(&mut self) -> Option<Palette> {
+        match self {
+            Screen::Main(menu) => menu.palette(),
+            Screen::Game(game) => game.palette(),
+            Screen::Split(game) => Some(game.palette()),
+            Screen::HighScores(board) => board.palette(),
+            Screen::Progress(progress) => progress.palette(),
+            Screen::About(about) => Some(about.palette()),
+            Screen::Quit => None,
+        }
+    }
+
+    /// Carry out an action chosen from the command palette.  Returns
+    /// `Some(screen)` if the application should switch to a new screen.
+    fn run_palette_action(&mut self, action: PaletteAction) -> Option<Screen> {
+        match self {
+            Screen::Main(menu) => menu.run_palette_action(action),
+            Screen::Game(game) => game.run_palette_action(action),
+            Screen::Split(game) => game.run_palette_action(action),
+            Screen::HighScores(board) => board.run_palette_action(action),
+            Screen::Progress(progress) => progress.run_palette_action(action),
+            Screen::About(about) => about.run_palette_action(action),
+            Screen::Quit => None,
+        }
+    }
+
+    /// Return a mutable reference to the screen's global data, if it has any
+    fn globals_mut(&mut self) -> Option<&mut Globals> {
+        match self {
+            Screen::Main(menu) => Some(menu.globals_mut()),
+            Screen::Game(game) => Some(game.globals_mut()),
+            Screen::Split(game) => Some(game.globals_mut()),
+            Screen::HighScores(board) => Some(board.globals_mut()),
+            Screen::Progress(progress) => Some(progress.globals_mut()),
+            Screen::About(about) => Some(about.globals_mut()),
+            Screen::Quit => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::TickSource;
+    use crate::input::ScriptedEvents;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use std::io::ErrorKind;
+
+    fn key(c: char) -> Event {
+        Event::Key(KeyCode::Char(c).into())
+    }
+
+    #[test]
+    fn play_until_game_over() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let history_file = tmpdir.path().join("history.jsonl");
+        let globals = Globals {
+            tick_source: TickSource::Step,
+            ..Globals::in_tempdir(&tmpdir, "[game]\ngame-over-hold-ms = 0\n")
+        };
+        // Start a game, step the snake forwards until it runs into a wall
+        // (further steps are ignored once the game is over), and then return
+        // to the main menu
+        let steps = 100;
+        let script = std::iter::once(key('p'))
+            .chain(std::iter::repeat_n(key('.'), steps))
+            .chain(std::iter::once(key('m')));
+        let mut app = App::new(globals, None).with_events(ScriptedEvents::new(script));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        app.step(&mut terminal).unwrap();
+        assert!(matches!(app.screen, Screen::Game(_)));
+        for _ in 0..steps {
+            app.step(&mut terminal).unwrap();
+        }
+        assert!(matches!(app.screen, Screen::Game(_)));
+        app.step(&mut terminal).unwrap();
+        assert!(matches!(app.screen, Screen::Main(_)));
+        // The last frame drawn was that of the finished game
+        let buffer = terminal.backend().buffer();
+        let screen = buffer
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+        assert!(screen.contains("GAME OVER"));
+        assert_eq!(
+            std::fs::read_to_string(&history_file)
+                .unwrap()
+                .lines()
+                .count(),
+            1
+        );
+        let e = app.step(&mut terminal).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedEof);
+        assert!(!app.quitting());
+    }
+
+    #[test]
+    fn kiosk_quit_keeps_game_unrecorded() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let history_file = tmpdir.path().join("history.jsonl");
+        let globals = Globals {
+            tick_source: TickSource::Step,
+            kiosk: true,
+            ..Globals::in_tempdir(
+                &tmpdir,
+                "[controls]\nrewinds = 1\n\n[game]\ngame-over-hold-ms = 60000\n",
+            )
+        };
+        let passphrase = Passphrase::new(vec!["ctrl-x".parse().unwrap()]).unwrap();
+        let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        // Start a game, press the quit keys mid-game, step the snake forwards
+        // until it runs into a wall, and then press the quit keys again while
+        // the game over screen is held
+        let steps = 100;
+        let script = [key('p'), key('q'), ctrl_c]
+            .into_iter()
+            .chain(std::iter::repeat_n(key('.'), steps))
+            .chain([key('q'), ctrl_c]);
+        let mut app = App::new(globals, None)
+            .with_kiosk(passphrase)
+            .with_events(ScriptedEvents::new(script));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        for _ in 0..(steps + 5) {
+            app.step(&mut terminal).unwrap();
+            assert!(matches!(app.screen, Screen::Game(_)));
+            assert!(!history_file.exists());
+        }
+        let Screen::Game(ref game) = app.screen else {
+            panic!("App left the game");
+        };
+        assert!(game.can_rewind());
+    }
+
+    #[test]
+    fn last_game() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let globals = Globals {
+            tick_source: TickSource::Step,
+            ..Globals::in_tempdir(
+                &tmpdir,
+                "[files]\noptions-file = false\n\n[game]\ngame-over-hold-ms = 0\n",
+            )
+        };
+        let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        // Quitting without playing a game
+        let app = App::new(globals.clone(), None).with_events(ScriptedEvents::new([key('q')]));
+        let last = app.run(Terminal::new(TestBackend::new(80, 24)).unwrap());
+        assert_eq!(last.unwrap(), None);
+        // Quitting in the middle of a game
+        let app = App::new(globals.clone(), None).with_events(ScriptedEvents::new([
+            key('p'),
+            key('.'),
+            ctrl_c,
+        ]));
+        let last = app
+            .run(Terminal::new(TestBackend::new(80, 24)).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(last.cause, crate::history::EndCause::Interrupted);
+        // Quitting from the main menu after the snake runs into a wall
+        let script = std::iter::once(key('p'))
+            .chain(std::iter::repeat_n(key('.'), 100))
+            .chain([key('m'), ctrl_c]);
+        let app = App::new(globals, None).with_events(ScriptedEvents::new(script));
+        let last = app
+            .run(Terminal::new(TestBackend::new(80, 24)).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(last.cause, crate::history::EndCause::Wall);
+    }
+
+    #[test]
+    fn session_heartbeat() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let session_file = tmpdir.path().join("session.json");
+        let globals = Globals {
+            tick_source: TickSource::Step,
+            ..Globals::in_tempdir(&tmpdir, "")
+        };
+        let mut app = App::new(globals.clone(), None)
+            .with_session(SessionFile::new(session_file.clone()))
+            .with_events(ScriptedEvents::new([key('p')]));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        app.step(&mut terminal).unwrap();
+        assert!(matches!(app.screen, Screen::Game(_)));
+        let heartbeat = SessionFile::new(session_file.clone())
+            .previous()
+            .unwrap()
+            .unwrap();
+        assert_eq!(heartbeat.screen, ScreenName::Game);
+        let record = heartbeat.game.unwrap();
+        assert_eq!(record.score, 0);
+        assert_eq!(record.cause, crate::history::EndCause::Interrupted);
+        // Quitting normally deletes the session file
+        let app = App::new(globals, None)
+            .with_session(SessionFile::new(session_file.clone()))
+            .with_events(ScriptedEvents::new([key('q')]));
+        app.run(Terminal::new(TestBackend::new(80, 24)).unwrap())
+            .unwrap();
+        assert!(!session_file.exists());
+    }
+
+    #[test]
+    fn redraw() {
+        let ctrl_l = Event::Key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL));
+        let script = [ctrl_l, key('u')];
+        let mut app = App::new(Globals::default(), None).with_events(ScriptedEvents::new(script));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        app.step(&mut terminal).unwrap();
+        assert!(app.clear_pending);
+        // Simulate another program writing to the screen
+        let mut stray = ratatui::buffer::Cell::default();
+        stray.set_symbol("X");
+        terminal
+            .backend_mut()
+            .draw(std::iter::once((0, 0, &stray)))
+            .unwrap();
+        assert_eq!(terminal.backend().buffer()[(0, 0)].symbol(), "X");
+        app.step(&mut terminal).unwrap();
+        assert!(!app.clear_pending);
+        assert_eq!(terminal.backend().buffer()[(0, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn input_timing() {
+        let script = [Event::Key(KeyCode::F(3).into()), key('j')];
+        let mut app = App::new(Globals::default(), None).with_events(ScriptedEvents::new(script));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        app.step(&mut terminal).unwrap();
+        assert!(app.diagnostics.enabled());
+        assert_eq!(app.diagnostics.event_samples(), 1);
+        assert!(app.events.take_last_read().is_none());
+        app.step(&mut terminal).unwrap();
+        assert_eq!(app.diagnostics.event_samples(), 2);
+        assert!(app.events.take_last_read().is_none());
+    }
+
+    #[test]
+    fn reload_mouse_steering() {
+        let mut app = App::new(Globals::default(), None);
+        assert!(!app.mouse_capture);
+        let config = Config::parse("[game]\nmouse-steering = true\n").unwrap();
+        app.reload_config(config).unwrap();
+        assert!(app.mouse_capture);
+        assert!(app.screen.globals_mut().unwrap().config.game.mouse_steering);
+        app.reload_config(Config::default()).unwrap();
+        assert!(!app.mouse_capture);
+    }
+
+    #[test]
+    fn screenshot() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let shotdir = tmpdir.path().join("screenshots");
+        let globals = Globals::in_tempdir(&tmpdir, "");
+        let f12 = Event::Key(KeyCode::F(12).into());
+        let script = [f12, key('o'), key('u')];
+        let mut app = App::new(globals, None).with_events(ScriptedEvents::new(script));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        assert!(app.draw(&mut terminal).unwrap().is_none());
+        app.step(&mut terminal).unwrap();
+        assert!(app.screenshot_pending);
+        assert!(app.draw(&mut terminal).unwrap().is_some());
+        app.step(&mut terminal).unwrap();
+        assert!(!app.screenshot_pending);
+        assert!(matches!(app.screen, Screen::HighScores(_)));
+        let entries = std::fs::read_dir(&shotdir)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        let path = entries[0].path();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("Play"));
+        assert_eq!(content.lines().count(), 24);
+        let text = format!("Saved screenshot to {}", path.display());
+        assert_eq!(app.notice.as_ref().map(|n| &n.text), Some(&text));
+        app.step(&mut terminal).unwrap();
+        let bottom = (0..80)
+            .map(|x| terminal.backend().buffer()[(x, 23)].symbol())
+            .collect::<String>();
+        assert!(bottom.contains(" Saved screenshot to "), "{bottom:?}");
+    }
+
+    #[test]
+    fn command_palette() {
+        let script = [
+            key(':'),
+            key('h'),
+            key('i'),
+            Event::Key(KeyCode::Enter.into()),
+            Event::Key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL)),
+            Event::Key(KeyCode::Esc.into()),
+        ];
+        let mut app = App::new(Globals::default(), None).with_events(ScriptedEvents::new(script));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        app.step(&mut terminal).unwrap();
+        assert!(app.palette.is_some());
+        app.step(&mut terminal).unwrap();
+        app.step(&mut terminal).unwrap();
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect::<String>();
+        assert!(screen.contains("COMMANDS"));
+        assert!(screen.contains("High Scores"));
+        assert!(!screen.contains("Toggle Wraparound"));
+        app.step(&mut terminal).unwrap();
+        assert!(app.palette.is_none());
+        assert!(matches!(app.screen, Screen::HighScores(_)));
+        app.step(&mut terminal).unwrap();
+        assert!(app.palette.is_some());
+        app.step(&mut terminal).unwrap();
+        assert!(app.palette.is_none());
+        assert!(matches!(app.screen, Screen::HighScores(_)));
+    }
+
+    #[test]
+    fn kiosk_passphrase() {
+        let globals = Globals {
+            kiosk: true,
+            ..Globals::default()
+        };
+        let passphrase = Passphrase::new(vec![
+            "ctrl-x".parse().unwrap(),
+            "b".parse().unwrap(),
+            "y".parse().unwrap(),
+        ])
+        .unwrap();
+        let ctrl = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+        let script = [
+            key('q'),
+            ctrl('c'),
+            ctrl('x'),
+            key('b'),
+            key('q'),
+            ctrl('x'),
+            key('b'),
+            key('y'),
+        ];
+        let mut app = App::new(globals, None)
+            .with_kiosk(passphrase)
+            .with_events(ScriptedEvents::new(script));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        for _ in 0..7 {
+            app.step(&mut terminal).unwrap();
+            assert!(matches!(app.screen, Screen::Main(_)));
+        }
+        app.step(&mut terminal).unwrap();
+        assert!(app.quitting());
+    }
+}
@@ -0,0 +1,492 @@
+//! Backing up all of the program's data to a single archive and restoring it
+//! again, e.g., when moving to a new machine.
+//!
+//! A backup is a gzipped tarball containing whichever of the configuration
+//! file, options file, high scores file, game history file, and favorite
+//! boards file exist, each stored under a fixed name regardless of where it
+//! was found.  When restoring, every file in the archive is checked for
+//! validity before anything on disk is overwritten, and the files are written
+//! to the locations given by the restored configuration (or, if the backup
+//! doesn't include one, by the current configuration).
+use crate::config::{Config, ConfigError};
+use crate::favorites::Favorites;
+use crate::highscores::HighScores;
+use crate::history::GameRecord;
+use crate::migrations::{self, SaveFile};
+use crate::options::SavedOptions;
+use crate::util::{temp_path, EnumExt, LoadError, SaveError};
+use enum_map::{Enum, EnumMap};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// An enum of the files included in backups
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+pub(crate) enum DataFile {
+    Config,
+    Options,
+    HighScores,
+    History,
+    Favorites,
+}
+
+impl DataFile {
+    /// Return the name under which the file is stored in a backup
+    fn archive_name(self) -> &'static str {
+        match self {
+            DataFile::Config => "config.toml",
+            DataFile::Options => "options.json",
+            DataFile::HighScores => "highscores/arcade.json",
+            DataFile::History => "history.jsonl",
+            DataFile::Favorites => "favorites.json",
+        }
+    }
+
+    /// Return a description of the file for use in summaries & error
+    /// messages
+    pub(crate) fn desc(self) -> &'static str {
+        match self.save_file() {
+            Some(file) => file.desc(),
+            None => "configuration",
+        }
+    }
+
+    /// Return the kind of save file that this is, if it's not the
+    /// configuration file
+    fn save_file(self) -> Option<SaveFile> {
+        match self {
+            DataFile::Config => None,
+            DataFile::Options => Some(SaveFile::Options),
+            DataFile::HighScores => Some(SaveFile::HighScores),
+            DataFile::History => Some(SaveFile::History),
+            DataFile::Favorites => Some(SaveFile::Favorites),
+        }
+    }
+
+    /// Return the path at which the file is stored according to `config`, or
+    /// `None` if it is the options file and saving of options is disabled
+    fn path(self, config: &Config, config_path: &Path) -> Result<Option<PathBuf>, SaveError> {
+        match self.save_file() {
+            Some(file) => config.save_file_path(file),
+            None => Ok(Some(config_path.to_owned())),
+        }
+    }
+
+    /// Check that `content` is a valid instance of the file
+    fn validate(self, content: &[u8]) -> Result<(), InvalidFile> {
+        match self {
+            DataFile::Config => {
+                Config::parse(std::str::from_utf8(content)?)?;
+            }
+            DataFile::Options => {
+                SavedOptions::from_json(content)?;
+            }
+            DataFile::HighScores => {
+                HighScores::from_json(content)?;
+            }
+            DataFile::History => {
+                for ln in std::str::from_utf8(content)?.lines() {
+                    if !ln.trim().is_empty() {
+                        migrations::from_json::<GameRecord>(SaveFile::History, ln.as_bytes())?;
+                    }
+                }
+            }
+            DataFile::Favorites => {
+                migrations::from_json::<Favorites>(SaveFile::Favorites, content)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write a backup of all existing data files to `archive`, using the
+/// configuration `config`, read from `config_path`, to locate the files.
+/// Returns the files that were included and where they were read from.
+pub(crate) fn backup(
+    archive: &Path,
+    config: &Config,
+    config_path: &Path,
+) -> Result<Vec<(DataFile, PathBuf)>, BackupError> {
+    let mut contents = Vec::new();
+    for file in DataFile::iter() {
+        let Some(path) = file.path(config, config_path)? else {
+            continue;
+        };
+        match fs_err::read(&path) {
+            Ok(content) => contents.push((file, path, content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(BackupError::Read(file.desc(), e)),
+        }
+    }
+    let fp = fs_err::File::create(archive).map_err(BackupError::WriteArchive)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(fp, Compression::default()));
+    let mtime = jiff::Timestamp::now().as_second().try_into().unwrap_or(0);
+    for (file, _, content) in &contents {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(u64::try_from(content.len()).unwrap_or(u64::MAX));
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        builder
+            .append_data(&mut header, file.archive_name(), content.as_slice())
+            .map_err(BackupError::WriteArchive)?;
+    }
+    builder
+        .into_inner()
+        .and_then(GzEncoder::finish)
+        .map_err(BackupError::WriteArchive)?;
+    Ok(contents
+        .into_iter()
+        .map(|(file, path, _)| (file, path))
+        .collect())
+}
+
+/// Restore the data files in the backup `archive`, writing the configuration
+/// file to `config_path` and the other files to the locations given by the
+/// restored configuration or, if the backup doesn't contain one, by
+/// `current`.  Returns the files that were restored and where they were
+/// written; a file whose location is `None` was skipped because saving of
+/// options is disabled.
+///
+/// Nothing is written unless every file in the backup is valid.  Each file is
+/// first written to a temporary file beside its destination, and the
+/// temporary files are only renamed into place once all of them have been
+/// written.  While they are renamed, the files they replace are moved aside,
+/// and if any rename fails, the files already restored are removed and the
+/// originals are put back.  Thus, a failure partway through (e.g., due to a
+/// full disk) doesn't leave a mix of restored and unrestored files behind.
+pub(crate) fn restore(
+    archive: &Path,
+    current: &Config,
+    config_path: &Path,
+) -> Result<Vec<(DataFile, Option<PathBuf>)>, BackupError> {
+    let contents = read_archive(archive)?;
+    for (file, content) in &contents {
+        if let Some(content) = content {
+            file.validate(content)
+                .map_err(|e| BackupError::Invalid(file.desc(), e))?;
+        }
+    }
+    let restored_config = contents[DataFile::Config]
+        .as_deref()
+        .and_then(|src| std::str::from_utf8(src).ok())
+        .and_then(|src| Config::parse(src).ok());
+    let config = restored_config.as_ref().unwrap_or(current);
+    let mut targets = Vec::new();
+    for (file, content) in contents {
+        if let Some(content) = content {
+            targets.push((file, file.path(config, config_path)?, content));
+        }
+    }
+    let mut staged = Vec::new();
+    let r = targets.iter().try_for_each(|(file, path, content)| {
+        let Some(p) = path else {
+            return Ok(());
+        };
+        let tmp = temp_path(p);
+        staged.push(tmp.clone());
+        if let Some(parent) = p.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs_err::create_dir_all(parent).map_err(|e| BackupError::Write(file.desc(), e))?;
+        }
+        fs_err::write(&tmp, content).map_err(|e| BackupError::Write(file.desc(), e))?;
+        Ok(())
+    });
+    // Each destination paired with where its original file was moved, if it
+    // existed
+    let mut replaced = Vec::new();
+    let r = r.and_then(|()| {
+        targets.iter().try_for_each(|(file, path, _)| {
+            let Some(p) = path else {
+                return Ok(());
+            };
+            let aside = aside_path(p);
+            let original = match fs_err::rename(p, &aside) {
+                Ok(()) => Some(aside),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => return Err(BackupError::Write(file.desc(), e)),
+            };
+            replaced.push((p, original));
+            fs_err::rename(temp_path(p), p).map_err(|e| BackupError::Write(file.desc(), e))
+        })
+    });
+    if let Err(e) = r {
+        for (p, original) in replaced.into_iter().rev() {
+            let _ = match original {
+                Some(aside) => fs_err::rename(aside, p),
+                None => fs_err::remove_file(p),
+            };
+        }
+        for tmp in staged {
+            let _ = fs_err::remove_file(tmp);
+        }
+        return Err(e);
+    }
+    for aside in replaced.into_iter().filter_map(|(_, original)| original) {
+        let _ = fs_err::remove_file(aside);
+    }
+    Ok(targets
+        .into_iter()
+        .map(|(file, path, _)| (file, path))
+        .collect())
+}
+
+/// Return the path to which the file at `path` is moved while it is being
+/// replaced by a restored file
+fn aside_path(path: &Path) -> PathBuf {
+    let mut p = path.as_os_str().to_owned();
+    p.push(".orig");
+    PathBuf::from(p)
+}
+
+/// Read the contents of each data file in the backup `archive`
+fn read_archive(archive: &Path) -> Result<EnumMap<DataFile, Option<Vec<u8>>>, BackupError> {
+    let fp = fs_err::File::open(archive).map_err(BackupError::ReadArchive)?;
+    let mut tarball = tar::Archive::new(GzDecoder::new(fp));
+    let mut contents = EnumMap::<DataFile, Option<Vec<u8>>>::default();
+    for entry in tarball.entries().map_err(BackupError::ReadArchive)? {
+        let mut entry = entry.map_err(BackupError::ReadArchive)?;
+        let name = entry
+            .path()
+            .map_err(BackupError::ReadArchive)?
+            .to_string_lossy()
+            .into_owned();
+        let file = DataFile::iter()
+            .find(|f| f.archive_name() == name)
+            .filter(|_| entry.header().entry_type().is_file())
+            .ok_or(BackupError::UnknownEntry(name))?;
+        if contents[file].is_some() {
+            return Err(BackupError::Duplicate(file.desc()));
+        }
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .map_err(BackupError::ReadArchive)?;
+        contents[file] = Some(content);
+    }
+    Ok(contents)
+}
+
+/// An error creating or restoring a backup
+#[derive(Debug, Error)]
+pub(crate) enum BackupError {
+    #[error(transparent)]
+    NoPath(#[from] SaveError),
+    #[error("failed to read {0} file")]
+    Read(&'static str, #[source] io::Error),
+    #[error("failed to write backup archive")]
+    WriteArchive(#[source] io::Error),
+    #[error("failed to read backup archive")]
+    ReadArchive(#[source] io::Error),
+    #[error("backup archive contains unexpected entry {0:?}")]
+    UnknownEntry(String),
+    #[error("backup archive contains more than one {0} file")]
+    Duplicate(&'static str),
+    #[error("backup archive contains invalid {0} file")]
+    Invalid(&'static str, #[source] InvalidFile),
+    #[error("failed to restore {0} file")]
+    Write(&'static str, #[source] io::Error),
+}
+
+/// An error validating a file in a backup
+#[derive(Debug, Error)]
+pub(crate) enum InvalidFile {
+    #[error("file is not valid UTF-8")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Load(#[from] LoadError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+    use crate::util::Globals;
+    use tempfile::TempDir;
+
+    /// Create a configuration file in `dir` that stores all data files in
+    /// `dir` and return it and its path
+    fn setup(dir: &TempDir) -> (Config, PathBuf) {
+        let config = Globals::in_tempdir(dir, "").config;
+        (config, dir.path().join("config.toml"))
+    }
+
+    /// Write a gzipped tarball to `path` containing the given entries
+    fn write_archive(path: &Path, entries: &[(&str, &str)]) {
+        let fp = fs_err::File::create(path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(fp, Compression::default()));
+        for &(name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(u64::try_from(content.len()).unwrap());
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn roundtrip() {
+        let tmpdir = TempDir::new().unwrap();
+        let (config, config_path) = setup(&tmpdir);
+        let options = Options {
+            wraparound: true,
+            ..Options::default()
+        };
+        let options_path = tmpdir.path().join("options.json");
+        SavedOptions::from(options).save(&options_path).unwrap();
+        let scores_path = tmpdir
+            .path()
+            .join("highscores")
+            .join(HighScores::ARCADE_FILE_NAME);
+        config.save_high_scores(&HighScores::default()).unwrap();
+        let archive = tmpdir.path().join("backup.tar.gz");
+        let backed_up = backup(&archive, &config, &config_path).unwrap();
+        assert_eq!(
+            backed_up,
+            [
+                (DataFile::Config, config_path.clone()),
+                (DataFile::Options, options_path.clone()),
+                (DataFile::HighScores, scores_path.clone()),
+            ]
+        );
+        let config_src = fs_err::read_to_string(&config_path).unwrap();
+        let options_src = fs_err::read_to_string(&options_path).unwrap();
+        let scores_src = fs_err::read_to_string(&scores_path).unwrap();
+        fs_err::remove_file(&config_path).unwrap();
+        fs_err::remove_file(&options_path).unwrap();
+        fs_err::remove_dir_all(tmpdir.path().join("highscores")).unwrap();
+        let restored = restore(&archive, &Config::default(), &config_path).unwrap();
+        assert_eq!(
+            restored,
+            [
+                (DataFile::Config, Some(config_path.clone())),
+                (DataFile::Options, Some(options_path.clone())),
+                (DataFile::HighScores, Some(scores_path.clone())),
+            ]
+        );
+        assert_eq!(fs_err::read_to_string(&config_path).unwrap(), config_src);
+        assert_eq!(fs_err::read_to_string(&options_path).unwrap(), options_src);
+        assert_eq!(fs_err::read_to_string(&scores_path).unwrap(), scores_src);
+        assert!(!tmpdir.path().join("history.jsonl").exists());
+    }
+
+    #[test]
+    fn restore_invalid() {
+        let tmpdir = TempDir::new().unwrap();
+        let (config, config_path) = setup(&tmpdir);
+        let options_path = tmpdir.path().join("options.json");
+        SavedOptions::default().save(&options_path).unwrap();
+        let options_src = fs_err::read_to_string(&options_path).unwrap();
+        let archive = tmpdir.path().join("backup.tar.gz");
+        write_archive(
+            &archive,
+            &[
+                ("options.json", &options_src),
+                ("highscores/arcade.json", "not JSON"),
+            ],
+        );
+        let e = restore(&archive, &config, &config_path).unwrap_err();
+        assert!(matches!(e, BackupError::Invalid("high scores", _)));
+        // Nothing was overwritten
+        assert_eq!(fs_err::read_to_string(&options_path).unwrap(), options_src);
+    }
+
+    #[test]
+    fn restore_unknown_entry() {
+        let tmpdir = TempDir::new().unwrap();
+        let (config, config_path) = setup(&tmpdir);
+        let archive = tmpdir.path().join("backup.tar.gz");
+        write_archive(&archive, &[("notes.txt", "")]);
+        let e = restore(&archive, &config, &config_path).unwrap_err();
+        assert!(matches!(e, BackupError::UnknownEntry(ref name) if name == "notes.txt"));
+    }
+
+    #[test]
+    fn restore_write_failure() {
+        let tmpdir = TempDir::new().unwrap();
+        let (config, config_path) = setup(&tmpdir);
+        let options_path = tmpdir.path().join("options.json");
+        SavedOptions::default().save(&options_path).unwrap();
+        let options_src = fs_err::read_to_string(&options_path).unwrap();
+        let restored_src = fs_err::read_to_string(&options_path)
+            .unwrap()
+            .replace("false", "true");
+        assert_ne!(restored_src, options_src);
+        config.save_high_scores(&HighScores::default()).unwrap();
+        let scores_src = fs_err::read_to_string(
+            tmpdir
+                .path()
+                .join("highscores")
+                .join(HighScores::ARCADE_FILE_NAME),
+        )
+        .unwrap();
+        // Block the creation of the high scores directory
+        fs_err::remove_dir_all(tmpdir.path().join("highscores")).unwrap();
+        fs_err::write(tmpdir.path().join("highscores"), "").unwrap();
+        let archive = tmpdir.path().join("backup.tar.gz");
+        write_archive(
+            &archive,
+            &[
+                ("options.json", &restored_src),
+                ("highscores/arcade.json", &scores_src),
+            ],
+        );
+        let e = restore(&archive, &config, &config_path).unwrap_err();
+        assert!(matches!(e, BackupError::Write("high scores", _)));
+        // The options file was not replaced, and no temporary files remain
+        assert_eq!(fs_err::read_to_string(&options_path).unwrap(), options_src);
+        let mut names = fs_err::read_dir(tmpdir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            ["backup.tar.gz", "config.toml", "highscores", "options.json"]
+        );
+    }
+
+    #[test]
+    fn restore_rename_failure() {
+        let tmpdir = TempDir::new().unwrap();
+        let (config, config_path) = setup(&tmpdir);
+        let options_path = tmpdir.path().join("options.json");
+        SavedOptions::default().save(&options_path).unwrap();
+        let options_src = fs_err::read_to_string(&options_path).unwrap();
+        let restored_src = options_src.replace("false", "true");
+        assert_ne!(restored_src, options_src);
+        let history_path = tmpdir.path().join("history.jsonl");
+        fs_err::write(&history_path, "").unwrap();
+        // Block moving the history file aside, which happens after the options
+        // file has been renamed into place
+        fs_err::create_dir(aside_path(&history_path)).unwrap();
+        let archive = tmpdir.path().join("backup.tar.gz");
+        write_archive(
+            &archive,
+            &[("options.json", &restored_src), ("history.jsonl", "")],
+        );
+        let e = restore(&archive, &config, &config_path).unwrap_err();
+        assert!(matches!(e, BackupError::Write("game history", _)));
+        // The original options file was put back, and no temporary files
+        // remain
+        assert_eq!(fs_err::read_to_string(&options_path).unwrap(), options_src);
+        let mut names = fs_err::read_dir(tmpdir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "backup.tar.gz",
+                "config.toml",
+                "history.jsonl",
+                "history.jsonl.orig",
+                "options.json"
+            ]
+        );
+    }
+}
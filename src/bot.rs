@@ -0,0 +1,219 @@
+//! Steering the snake with an external "bot" program.
+//!
+//! When `ratsnake` is run with `--bot <command>`, the command is run in a
+//! shell with its stdin & stdout connected to pipes.  On each tick of a game,
+//! a [`Snapshot`] of the game is written to the bot's stdin as a single line
+//! of JSON, and the bot must reply within
+//! [`BOT_TIMEOUT`][consts::BOT_TIMEOUT] by writing a line to its stdout
+//! containing one of `north`, `east`, `south`, or `west` (to turn the snake in
+//! that direction) or an empty line (to keep going straight).  Bots that take
+//! too long to reply, reply with anything else, or exit are killed, and the
+//! game is forfeited.
+use crate::consts;
+use crate::direction::Direction;
+use crate::game::Snapshot;
+use std::cell::RefCell;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Stdio};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use thiserror::Error;
+
+/// A handle to an external program that steers the snake.
+///
+/// The program is started the first time it is asked for a move and is kept
+/// running from one game to the next, so bots can tell that a new game has
+/// begun by [`Snapshot::tick`] going back to zero.  If the program misbehaves,
+/// it is killed, and a fresh instance is started for the next game.
+///
+/// Clones of a `Bot` share the same program instance.
+#[derive(Clone, Debug)]
+pub(crate) struct Bot(Rc<RefCell<BotState>>);
+
+impl Bot {
+    /// Create a `Bot` that runs the shell command `command`
+    pub(crate) fn new(command: String) -> Bot {
+        Bot(Rc::new(RefCell::new(BotState {
+            command,
+            process: None,
+        })))
+    }
+
+    /// Send `snapshot` to the bot and wait for it to reply with a move.
+    /// Returns `Some(direction)` if the bot wants the snake to move in
+    /// `direction`, or `None` if the snake should keep going straight.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the bot could not be started, if communicating with it
+    /// failed, if it did not reply in time, or if its reply was invalid.  In
+    /// all of these cases, the bot is killed.
+    pub(crate) fn steer(&self, snapshot: &Snapshot) -> Result<Option<Direction>, BotError> {
+        let mut state = self.0.borrow_mut();
+        let r = state.steer(snapshot);
+        if r.is_err() {
+            state.process = None;
+        }
+        r
+    }
+}
+
+impl PartialEq for Bot {
+    fn eq(&self, other: &Bot) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Bot {}
+
+/// The shared state behind a [`Bot`]
+#[derive(Debug)]
+struct BotState {
+    /// The shell command for running the bot
+    command: String,
+
+    /// The running bot program, if it has been started and has not been
+    /// killed
+    process: Option<BotProcess>,
+}
+
+impl BotState {
+    fn steer(&mut self, snapshot: &Snapshot) -> Result<Option<Direction>, BotError> {
+        let process = match self.process {
+            Some(ref mut p) => p,
+            None => self.process.insert(BotProcess::spawn(&self.command)?),
+        };
+        let mut line = serde_json::to_string(snapshot).map_err(BotError::Serialize)?;
+        line.push('\n');
+        process
+            .stdin
+            .write_all(line.as_bytes())
+            .and_then(|()| process.stdin.flush())
+            .map_err(BotError::Write)?;
+        let reply = match process.lines.recv_timeout(consts::BOT_TIMEOUT) {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(e)) => return Err(BotError::Read(e)),
+            Err(RecvTimeoutError::Timeout) => return Err(BotError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => return Err(BotError::Exited),
+        };
+        parse_reply(&reply)
+    }
+}
+
+/// A running bot program
+#[derive(Debug)]
+struct BotProcess {
+    /// The child process
+    child: Child,
+
+    /// The process's standard input
+    stdin: ChildStdin,
+
+    /// Lines read from the process's standard output by a background thread
+    lines: Receiver<io::Result<String>>,
+}
+
+impl BotProcess {
+    /// Start running the shell command `command`
+    fn spawn(command: &str) -> Result<BotProcess, BotError> {
+        let mut child = shell(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // Anything the bot writes to stderr would garble the display
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(BotError::Spawn)?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            unreachable!("Child process should have piped stdin and stdout");
+        };
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(BotProcess {
+            child,
+            stdin,
+            lines,
+        })
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Return a [`std::process::Command`] for running `command` in the system
+/// shell
+fn shell(command: &str) -> std::process::Command {
+    if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// Parse a line of output from a bot into a move
+fn parse_reply(reply: &str) -> Result<Option<Direction>, BotError> {
+    match reply.trim().to_ascii_lowercase().as_str() {
+        "" => Ok(None),
+        "north" => Ok(Some(Direction::North)),
+        "east" => Ok(Some(Direction::East)),
+        "south" => Ok(Some(Direction::South)),
+        "west" => Ok(Some(Direction::West)),
+        _ => Err(BotError::BadReply(reply.to_owned())),
+    }
+}
+
+/// Error returned by [`Bot::steer()`]
+#[derive(Debug, Error)]
+pub(crate) enum BotError {
+    #[error("Failed to start bot")]
+    Spawn(#[source] io::Error),
+    #[error("Failed to serialize game state for bot")]
+    Serialize(#[source] serde_json::Error),
+    #[error("Failed to send game state to bot")]
+    Write(#[source] io::Error),
+    #[error("Failed to read move from bot")]
+    Read(#[source] io::Error),
+    #[error("Bot forfeited by taking too long to move")]
+    Timeout,
+    #[error("Bot forfeited by exiting")]
+    Exited,
+    #[error("Bot forfeited by making an invalid move: {0:?}")]
+    BadReply(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", None)]
+    #[case("north", Some(Direction::North))]
+    #[case("East\r", Some(Direction::East))]
+    #[case(" SOUTH ", Some(Direction::South))]
+    #[case("west", Some(Direction::West))]
+    fn test_parse_reply(#[case] reply: &str, #[case] r: Option<Direction>) {
+        assert_eq!(parse_reply(reply).unwrap(), r);
+    }
+
+    #[test]
+    fn test_parse_bad_reply() {
+        assert!(matches!(
+            parse_reply("up"),
+            Err(BotError::BadReply(s)) if s == "up"
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+//! Serving the live state of the game over WebSocket (if the `broadcast`
+//! feature is enabled).
+//!
+//! When `ratsnake` is run with `--broadcast <port>`, a background thread
+//! listens for WebSocket connections on `127.0.0.1:<port>` and sends each
+//! client a JSON [`Snapshot`] of the game whenever it changes.  This is
+//! intended for building browser-based overlays for streaming.
+use crate::game::Snapshot;
+use std::io;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// How often the background thread checks for new connections while no
+/// snapshots are being sent
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait for a client to complete the WebSocket handshake or to
+/// accept a message before giving up on it
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A handle for sending snapshots of the game to the background thread that
+/// serves them to WebSocket clients
+#[derive(Clone, Debug)]
+pub(crate) struct Broadcaster {
+    /// The sending end of the channel to the background thread
+    sender: Sender<String>,
+
+    /// The snapshot most recently sent, used to avoid sending duplicates
+    last: Option<Snapshot>,
+}
+
+impl Broadcaster {
+    /// Start listening for WebSocket connections on the given port of the
+    /// loopback interface
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if binding to the port failed.
+    pub(crate) fn start(port: u16) -> io::Result<Broadcaster> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name(String::from("broadcast"))
+            .spawn(move || serve(&listener, &receiver))?;
+        Ok(Broadcaster { sender, last: None })
+    }
+
+    /// Send `snapshot` to all connected clients, unless it is the same as the
+    /// last snapshot sent
+    pub(crate) fn send(&mut self, snapshot: Snapshot) {
+        if self.last.as_ref() == Some(&snapshot) {
+            return;
+        }
+        if let Ok(msg) = serde_json::to_string(&snapshot) {
+            // If the background thread has died, there's nothing to be done
+            // about it.
+            let _ = self.sender.send(msg);
+        }
+        self.last = Some(snapshot);
+    }
+}
+
+/// Accept connections on `listener` and send every message received on
+/// `messages` to all connected clients.  Returns once the sending end of
+/// `messages` is dropped.
+fn serve(listener: &TcpListener, messages: &Receiver<String>) {
+    let mut clients: Vec<WebSocket<TcpStream>> = Vec::new();
+    let mut latest: Option<String> = None;
+    loop {
+        match messages.recv_timeout(ACCEPT_INTERVAL) {
+            Ok(msg) => {
+                clients.retain_mut(|ws| ws.send(Message::text(msg.as_str())).is_ok());
+                latest = Some(msg);
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        while let Ok((stream, _)) = listener.accept() {
+            if let Some(mut ws) = handshake(stream) {
+                // Bring new clients up to date immediately
+                if latest
+                    .as_ref()
+                    .is_none_or(|msg| ws.send(Message::text(msg.as_str())).is_ok())
+                {
+                    clients.push(ws);
+                }
+            }
+        }
+    }
+}
+
+/// Perform the WebSocket handshake on a newly-accepted connection.  Returns
+/// `None` if anything goes wrong.
+fn handshake(stream: TcpStream) -> Option<WebSocket<TcpStream>> {
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    tungstenite::accept(stream).ok()
+}
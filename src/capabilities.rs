@@ -0,0 +1,86 @@
+//! Detection of optional features supported by the terminal.
+//!
+//! Capabilities are detected once at startup and stored in
+//! [`Globals`][crate::util::Globals] so that widgets can make use of richer
+//! features when they are available while falling back to plainer rendering
+//! otherwise.  Detection can be overridden via the configuration file.
+use serde::Deserialize;
+
+/// The optional features supported by the terminal
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Capabilities {
+    /// Can the terminal display 24-bit colors?
+    pub(crate) true_color: bool,
+}
+
+impl Capabilities {
+    /// Detect the capabilities of the terminal from the environment
+    pub(crate) fn detect() -> Capabilities {
+        Capabilities::from_colorterm(std::env::var("COLORTERM").ok().as_deref())
+    }
+
+    /// Determine the capabilities of the terminal from the value of the
+    /// `COLORTERM` environment variable, if set
+    fn from_colorterm(colorterm: Option<&str>) -> Capabilities {
+        Capabilities {
+            true_color: colorterm.is_some_and(|v| {
+                v.eq_ignore_ascii_case("truecolor") || v.eq_ignore_ascii_case("24bit")
+            }),
+        }
+    }
+}
+
+/// A configuration setting for whether to make use of a terminal capability
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Support {
+    /// Use the capability if it was detected
+    #[default]
+    Auto,
+
+    /// Always use the capability
+    Always,
+
+    /// Never use the capability
+    Never,
+}
+
+impl Support {
+    /// Decide whether to use a capability, given whether it was detected
+    pub(crate) fn resolve(self, detected: bool) -> bool {
+        match self {
+            Support::Auto => detected,
+            Support::Always => true,
+            Support::Never => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(None, false)]
+    #[case(Some(""), false)]
+    #[case(Some("truecolor"), true)]
+    #[case(Some("24bit"), true)]
+    #[case(Some("TrueColor"), true)]
+    #[case(Some("yes"), false)]
+    fn true_color(#[case] colorterm: Option<&str>, #[case] supported: bool) {
+        assert_eq!(
+            Capabilities::from_colorterm(colorterm).true_color,
+            supported
+        );
+    }
+
+    #[rstest]
+    #[case(Support::Auto, false, false)]
+    #[case(Support::Auto, true, true)]
+    #[case(Support::Always, false, true)]
+    #[case(Support::Never, true, false)]
+    fn resolve(#[case] support: Support, #[case] detected: bool, #[case] used: bool) {
+        assert_eq!(support.resolve(detected), used);
+    }
+}
@@ -1,8 +1,14 @@
+use crate::consts;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::text::{Line, Span};
+use serde::{de::Deserializer, Deserialize};
+use std::time::Instant;
+use thiserror::Error;
 
 /// An enum of input commands, (mostly) abstracted away from the key codes that
 /// produce them
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub(crate) enum Command {
     /// Quit the program (Ctrl-C)
     Quit,
@@ -28,14 +34,40 @@ pub(crate) enum Command {
     Prev,
     /// User pressed the Escape key
     Esc,
+    /// Delete the current item (Delete, Backspace)
+    Delete,
+    /// Redo the last undone change (Ctrl-R)
+    Redo,
+    /// Save changes (Ctrl-S)
+    Save,
+    /// Clear & redraw the whole terminal (Ctrl-L)
+    Redraw,
+    /// User pressed the `c` key
+    C,
+    /// User pressed the `e` key
+    E,
+    /// User pressed the `f` key
+    F,
+    /// User pressed the `i` key
+    I,
     /// User pressed the `m` key
     M,
+    /// User pressed the `n` key
+    N,
+    /// User pressed the `o` key
+    O,
     /// User pressed the `p` key
     P,
     /// User pressed the `q` key
     Q,
     /// User pressed the `r` key
     R,
+    /// User pressed the `u` key
+    U,
+    /// User pressed the `y` key
+    Y,
+    /// User pressed the `z` key
+    Z,
 }
 
 impl Command {
@@ -43,6 +75,9 @@ impl Command {
     pub(crate) fn from_key_event(ev: KeyEvent) -> Option<Command> {
         match (ev.modifiers, ev.code) {
             (KeyModifiers::CONTROL, KeyCode::Char('c')) => Some(Command::Quit),
+            (KeyModifiers::CONTROL, KeyCode::Char('r')) => Some(Command::Redo),
+            (KeyModifiers::CONTROL, KeyCode::Char('s')) => Some(Command::Save),
+            (KeyModifiers::CONTROL, KeyCode::Char('l')) => Some(Command::Redraw),
             (KeyModifiers::NONE, KeyCode::Char('w' | 'k' | '8') | KeyCode::Up) => Some(Command::Up),
             (KeyModifiers::NONE, KeyCode::Char('s' | 'j' | '2') | KeyCode::Down) => {
                 Some(Command::Down)
@@ -60,11 +95,383 @@ impl Command {
             (_, KeyCode::Tab) => Some(Command::Next),
             (_, KeyCode::BackTab) => Some(Command::Prev),
             (_, KeyCode::Esc) => Some(Command::Esc),
+            (_, KeyCode::Delete | KeyCode::Backspace) => Some(Command::Delete),
+            (KeyModifiers::NONE, KeyCode::Char('c')) => Some(Command::C),
+            (KeyModifiers::NONE, KeyCode::Char('e')) => Some(Command::E),
+            (KeyModifiers::NONE, KeyCode::Char('f')) => Some(Command::F),
+            (KeyModifiers::NONE, KeyCode::Char('i')) => Some(Command::I),
             (KeyModifiers::NONE, KeyCode::Char('m')) => Some(Command::M),
+            (KeyModifiers::NONE, KeyCode::Char('n')) => Some(Command::N),
+            (KeyModifiers::NONE, KeyCode::Char('o')) => Some(Command::O),
             (KeyModifiers::NONE, KeyCode::Char('p')) => Some(Command::P),
             (KeyModifiers::NONE, KeyCode::Char('q')) => Some(Command::Q),
             (KeyModifiers::NONE, KeyCode::Char('r')) => Some(Command::R),
+            (KeyModifiers::NONE, KeyCode::Char('u')) => Some(Command::U),
+            (KeyModifiers::NONE, KeyCode::Char('y')) => Some(Command::Y),
+            (KeyModifiers::NONE, KeyCode::Char('z')) => Some(Command::Z),
             _ => None,
         }
     }
+
+    /// Return a key event that produces this `Command`.  This is used to feed
+    /// commands from non-keyboard input sources through the same code paths
+    /// as key presses.
+    #[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+    pub(crate) fn key_event(self) -> KeyEvent {
+        let (code, modifiers) = match self {
+            Command::Quit => (KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Command::Up => (KeyCode::Up, KeyModifiers::NONE),
+            Command::Down => (KeyCode::Down, KeyModifiers::NONE),
+            Command::Left => (KeyCode::Left, KeyModifiers::NONE),
+            Command::Right => (KeyCode::Right, KeyModifiers::NONE),
+            Command::Enter => (KeyCode::Enter, KeyModifiers::NONE),
+            Command::Space => (KeyCode::Char(' '), KeyModifiers::NONE),
+            Command::Home => (KeyCode::Home, KeyModifiers::NONE),
+            Command::End => (KeyCode::End, KeyModifiers::NONE),
+            Command::Next => (KeyCode::Tab, KeyModifiers::NONE),
+            Command::Prev => (KeyCode::BackTab, KeyModifiers::SHIFT),
+            Command::Esc => (KeyCode::Esc, KeyModifiers::NONE),
+            Command::Delete => (KeyCode::Delete, KeyModifiers::NONE),
+            Command::Redo => (KeyCode::Char('r'), KeyModifiers::CONTROL),
+            Command::Save => (KeyCode::Char('s'), KeyModifiers::CONTROL),
+            Command::Redraw => (KeyCode::Char('l'), KeyModifiers::CONTROL),
+            Command::C => (KeyCode::Char('c'), KeyModifiers::NONE),
+            Command::E => (KeyCode::Char('e'), KeyModifiers::NONE),
+            Command::F => (KeyCode::Char('f'), KeyModifiers::NONE),
+            Command::I => (KeyCode::Char('i'), KeyModifiers::NONE),
+            Command::M => (KeyCode::Char('m'), KeyModifiers::NONE),
+            Command::N => (KeyCode::Char('n'), KeyModifiers::NONE),
+            Command::O => (KeyCode::Char('o'), KeyModifiers::NONE),
+            Command::P => (KeyCode::Char('p'), KeyModifiers::NONE),
+            Command::Q => (KeyCode::Char('q'), KeyModifiers::NONE),
+            Command::R => (KeyCode::Char('r'), KeyModifiers::NONE),
+            Command::U => (KeyCode::Char('u'), KeyModifiers::NONE),
+            Command::Y => (KeyCode::Char('y'), KeyModifiers::NONE),
+            Command::Z => (KeyCode::Char('z'), KeyModifiers::NONE),
+        };
+        KeyEvent::new(code, modifiers)
+    }
+}
+
+/// A sequence of two keys that produces a command when the keys are typed in
+/// quick succession
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Chord {
+    keys: [char; 2],
+    command: Command,
+}
+
+/// The chords recognized by [`ChordReader`]
+const CHORDS: [Chord; 2] = [
+    // `g g`: Jump to the top of the list
+    Chord {
+        keys: ['g', 'g'],
+        command: Command::Home,
+    },
+    // `Z Z`: Quit
+    Chord {
+        keys: ['Z', 'Z'],
+        command: Command::Quit,
+    },
+];
+
+/// A converter of key events to commands that, in addition to the keys
+/// recognized by [`Command::from_key_event()`], recognizes two-key chords like
+/// `g g`.  The first key of a chord produces no command on its own; if the
+/// second key is not pressed within
+/// [`CHORD_TIMEOUT`][consts::CHORD_TIMEOUT], the chord is abandoned.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct ChordReader {
+    /// The first key of a chord that has been pressed, if any, along with
+    /// when it was pressed
+    pending: Option<(char, Instant)>,
+}
+
+impl ChordReader {
+    /// Return the `Command`, if any, for the given key event pressed at time
+    /// `now`
+    pub(crate) fn command(&mut self, ev: KeyEvent, now: Instant) -> Option<Command> {
+        let key = chord_key(ev);
+        if let Some((first, _)) = self
+            .pending
+            .take()
+            .filter(|&(_, t)| now < t + consts::CHORD_TIMEOUT)
+        {
+            if let Some(chord) = key.and_then(|k| CHORDS.iter().find(|c| c.keys == [first, k])) {
+                return Some(chord.command);
+            }
+        }
+        if let Some(k) = key.filter(|&k| CHORDS.iter().any(|c| c.keys[0] == k)) {
+            self.pending = Some((k, now));
+            return None;
+        }
+        Command::from_key_event(ev)
+    }
+
+    /// If a chord has been started, return the time at which it will be
+    /// abandoned
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.pending.map(|(_, t)| t + consts::CHORD_TIMEOUT)
+    }
+
+    /// Abandon any chord that has been started
+    pub(crate) fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    /// If a chord has been started, return a line showing the key pressed so
+    /// far, for displaying as an indicator that another key is expected
+    pub(crate) fn indicator(&self) -> Option<Line<'static>> {
+        self.pending.map(|(k, _)| {
+            Line::from_iter([
+                Span::styled(String::from(k), consts::KEY_STYLE),
+                Span::raw("…"),
+            ])
+        })
+    }
+}
+
+/// If `ev` is a press of a character key with no modifiers other than Shift,
+/// return the character
+fn chord_key(ev: KeyEvent) -> Option<char> {
+    match ev.code {
+        KeyCode::Char(c) if (ev.modifiers - KeyModifiers::SHIFT).is_empty() => Some(c),
+        _ => None,
+    }
+}
+
+/// A user-configurable key combination, written in configuration files as
+/// zero or more modifiers (`ctrl`, `alt`, `shift`) followed by a key name, all
+/// separated by hyphens, e.g., `"ctrl-r"` or `"f5"`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct KeyBinding {
+    modifiers: KeyModifiers,
+    code: KeyCode,
+}
+
+impl KeyBinding {
+    /// Create a new `KeyBinding` for the given key and modifiers
+    pub(crate) const fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        KeyBinding { modifiers, code }
+    }
+
+    /// Return a key event for pressing this key combination
+    pub(crate) fn key_event(self) -> KeyEvent {
+        KeyEvent::new(self.code, self.modifiers)
+    }
+
+    /// Returns `true` if the given key event was produced by this key
+    /// combination.  Letters are compared case-insensitively, as terminals
+    /// report Shift+letter as an uppercase letter.
+    pub(crate) fn matches(&self, ev: KeyEvent) -> bool {
+        let code = match ev.code {
+            KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        code == self.code && ev.modifiers == self.modifiers
+    }
+}
+
+impl std::str::FromStr for KeyBinding {
+    type Err = ParseKeyBindingError;
+
+    fn from_str(s: &str) -> Result<KeyBinding, ParseKeyBindingError> {
+        let (mods, key) = if s == "-" {
+            (None, s)
+        } else if let Some(mods) = s.strip_suffix("--") {
+            // Allow binding the hyphen key itself, e.g., "ctrl--"
+            (Some(mods), "-")
+        } else {
+            match s.rsplit_once('-') {
+                Some((mods, key)) => (Some(mods), key),
+                None => (None, s),
+            }
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods.into_iter().flat_map(|m| m.split('-')) {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(ParseKeyBindingError::Modifier(m.to_owned())),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c.to_ascii_lowercase()),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                k => match k.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(ParseKeyBindingError::Key(key.to_owned())),
+                },
+            },
+        };
+        Ok(KeyBinding { modifiers, code })
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBinding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<KeyBinding>().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub(crate) enum ParseKeyBindingError {
+    #[error("unknown key modifier {0:?}")]
+    Modifier(String),
+    #[error("unknown key name {0:?}")]
+    Key(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    mod chords {
+        use super::*;
+        use std::time::Duration;
+
+        fn key(c: char) -> KeyEvent {
+            if c.is_ascii_uppercase() {
+                KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT)
+            } else {
+                KeyEvent::from(KeyCode::Char(c))
+            }
+        }
+
+        #[test]
+        fn complete() {
+            let mut reader = ChordReader::default();
+            let now = Instant::now();
+            assert_eq!(reader.command(key('g'), now), None);
+            assert_eq!(reader.deadline(), Some(now + consts::CHORD_TIMEOUT));
+            assert!(reader.indicator().is_some());
+            assert_eq!(
+                reader.command(key('g'), now + Duration::from_millis(200)),
+                Some(Command::Home)
+            );
+            assert_eq!(reader.deadline(), None);
+            assert_eq!(reader.indicator(), None);
+            assert_eq!(reader.command(key('Z'), now), None);
+            assert_eq!(reader.command(key('Z'), now), Some(Command::Quit));
+        }
+
+        #[test]
+        fn interrupted() {
+            let mut reader = ChordReader::default();
+            let now = Instant::now();
+            assert_eq!(reader.command(key('g'), now), None);
+            // A key that doesn't complete the chord is handled normally
+            assert_eq!(reader.command(key('j'), now), Some(Command::Down));
+            assert_eq!(reader.command(key('g'), now), None);
+            // ... including the first key of a different chord
+            assert_eq!(reader.command(key('Z'), now), None);
+            assert_eq!(reader.command(key('g'), now), None);
+            assert_eq!(reader.command(key('g'), now), Some(Command::Home));
+            // Lowercase z is not part of a chord
+            assert_eq!(reader.command(key('z'), now), Some(Command::Z));
+        }
+
+        #[test]
+        fn timeout() {
+            let mut reader = ChordReader::default();
+            let now = Instant::now();
+            assert_eq!(reader.command(key('g'), now), None);
+            // A late second key starts a new chord instead
+            let later = now + consts::CHORD_TIMEOUT;
+            assert_eq!(reader.command(key('g'), later), None);
+            assert_eq!(reader.command(key('g'), later), Some(Command::Home));
+            assert_eq!(reader.command(key('g'), later), None);
+            reader.cancel();
+            assert_eq!(reader.command(key('g'), later), None);
+            assert_eq!(reader.deadline(), Some(later + consts::CHORD_TIMEOUT));
+        }
+    }
+
+    #[rstest]
+    #[case("ctrl-r", KeyModifiers::CONTROL, KeyCode::Char('r'))]
+    #[case("Ctrl-R", KeyModifiers::CONTROL, KeyCode::Char('r'))]
+    #[case("f5", KeyModifiers::NONE, KeyCode::F(5))]
+    #[case("alt-shift-space", KeyModifiers::ALT | KeyModifiers::SHIFT, KeyCode::Char(' '))]
+    #[case("ctrl--", KeyModifiers::CONTROL, KeyCode::Char('-'))]
+    #[case("-", KeyModifiers::NONE, KeyCode::Char('-'))]
+    #[case("backspace", KeyModifiers::NONE, KeyCode::Backspace)]
+    fn parse_key_binding(#[case] s: &str, #[case] modifiers: KeyModifiers, #[case] code: KeyCode) {
+        assert_eq!(
+            s.parse::<KeyBinding>(),
+            Ok(KeyBinding::new(code, modifiers))
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("ctrl-")]
+    #[case("hyper-r")]
+    #[case("f25")]
+    #[case("ctrl-rr")]
+    fn parse_bad_key_binding(#[case] s: &str) {
+        assert!(s.parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn match_shifted_letter() {
+        let kb = "shift-r".parse::<KeyBinding>().unwrap();
+        assert!(kb.matches(KeyEvent::new(KeyCode::Char('R'), KeyModifiers::SHIFT)));
+        assert!(!kb.matches(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn key_event_roundtrip() {
+        for cmd in [
+            Command::Quit,
+            Command::Up,
+            Command::Down,
+            Command::Left,
+            Command::Right,
+            Command::Enter,
+            Command::Space,
+            Command::Home,
+            Command::End,
+            Command::Next,
+            Command::Prev,
+            Command::Esc,
+            Command::Delete,
+            Command::Redo,
+            Command::Save,
+            Command::Redraw,
+            Command::C,
+            Command::E,
+            Command::F,
+            Command::I,
+            Command::M,
+            Command::N,
+            Command::O,
+            Command::P,
+            Command::Q,
+            Command::R,
+            Command::U,
+            Command::Y,
+            Command::Z,
+        ] {
+            assert_eq!(Command::from_key_event(cmd.key_event()), Some(cmd));
+        }
+    }
 }
@@ -1,16 +1,27 @@
+use crate::capabilities::Support;
+use crate::command::{Command, KeyBinding};
 use crate::consts;
 use crate::direction::Direction;
+use crate::favorites::Favorites;
 use crate::highscores::HighScores;
-use crate::options::Options;
-use crate::util::{expanduser, LoadError, NoHomeError, SaveError};
+use crate::history::GameRecord;
+use crate::migrations::SaveFile;
+use crate::options::{Options, ReplacedValues, SavedOptions};
+use crate::session::SessionFile;
+use crate::util::{
+    data_dir, expanduser, CellAspect, LayoutDirection, LoadError, NoHomeError, SaveError,
+};
+use rand::TryRngCore;
 use ratatui::style::Style;
 use serde::{
     de::{Deserializer, Unexpected},
-    Deserialize,
+    Deserialize, Serialize, Serializer,
 };
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 use thiserror::Error;
 use unicode_properties::{GeneralCategoryGroup, UnicodeGeneralCategory};
 use unicode_segmentation::UnicodeSegmentation;
@@ -20,6 +31,25 @@ use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 #[derive(Clone, Deserialize, Debug, Default, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct Config {
+    /// The language in which to display the interface, if not English.  This
+    /// only takes effect at startup.
+    #[serde(default)]
+    pub(crate) lang: Option<String>,
+
+    /// The direction in which to lay out the interface
+    #[serde(default, rename = "layout-direction")]
+    pub(crate) layout_direction: LayoutDirection,
+
+    /// The shape of terminal cells, if known, used to adjust the shapes of
+    /// levels and the snake's vertical speed
+    #[serde(default, rename = "cell-aspect")]
+    pub(crate) cell_aspect: Option<CellAspect>,
+
+    /// Whether to use 24-bit colors, which by default are only used if the
+    /// terminal advertises support for them
+    #[serde(default, rename = "true-color")]
+    pub(crate) true_color: Support,
+
     /// Default options when no options file is present
     #[serde(default)]
     pub(crate) options: Options,
@@ -31,6 +61,42 @@ pub(crate) struct Config {
     /// Game glyph settings
     #[serde(default)]
     pub(crate) glyphs: GlyphConfig,
+
+    /// Gamepad input settings
+    #[serde(default)]
+    pub(crate) gamepad: GamepadConfig,
+
+    /// Keyboard control settings
+    #[serde(default)]
+    pub(crate) controls: ControlsConfig,
+
+    /// Gameplay behavior settings
+    #[serde(default)]
+    pub(crate) game: GameConfig,
+
+    /// Kiosk mode settings
+    #[serde(default)]
+    pub(crate) kiosk: KioskConfig,
+
+    /// Interface appearance settings
+    #[serde(default)]
+    pub(crate) theme: ThemeConfig,
+
+    /// Named sets of glyphs, any one of which can be selected with
+    /// `glyphs.skin`
+    #[serde(default)]
+    pub(crate) skins: HashMap<String, Skin>,
+
+    /// The unknown keys found in `skins`, which are ignored but reported to
+    /// the user at startup
+    #[serde(skip)]
+    pub(crate) unknown_skin_keys: Vec<UnknownSkinKey>,
+
+    /// The path to the file from which the configuration was loaded, if any.
+    /// This is set even if the file did not exist and the default
+    /// configuration was used in its place.
+    #[serde(skip)]
+    pub(crate) path: Option<PathBuf>,
 }
 
 impl Config {
@@ -52,11 +118,41 @@ impl Config {
         let content = match fs_err::read_to_string(path) {
             Ok(content) => content,
             Err(e) if e.kind() == std::io::ErrorKind::NotFound && allow_missing => {
-                return Ok(Config::default())
+                return Ok(Config {
+                    path: Some(path.to_owned()),
+                    ..Config::default()
+                })
             }
             Err(e) => return Err(ConfigError::Read(e)),
         };
-        toml::from_str(&content).map_err(Into::into)
+        let mut config = Config::parse(&content)?;
+        config.path = Some(path.to_owned());
+        Ok(config)
+    }
+
+    /// Parse the contents of a configuration file
+    pub(crate) fn parse(content: &str) -> Result<Config, ConfigError> {
+        let mut config = toml::from_str::<Config>(content)?;
+        if let Some(name) = config.glyphs.skin.take() {
+            config.glyphs = select_skin(content, &name)?;
+            config.glyphs.skin = Some(name);
+        }
+        config.unknown_skin_keys = config
+            .skins
+            .iter()
+            .flat_map(|(name, skin)| {
+                skin.unknown
+                    .iter()
+                    .map(|key| UnknownSkinKey::new(content, name, key))
+            })
+            .collect();
+        config
+            .unknown_skin_keys
+            .sort_unstable_by_key(|k| (k.line, k.column));
+        if !(1..=consts::FRUIT_CAPACITY).contains(&config.game.max_fruits) {
+            return Err(ConfigError::MaxFruits(config.game.max_fruits));
+        }
+        Ok(config)
     }
 
     /// Return the filepath at which gameplay options should be stored: the
@@ -74,34 +170,59 @@ impl Config {
         }
     }
 
+    /// Returns `true` if gameplay options are saved to a file
+    pub(crate) fn saves_options(&self) -> bool {
+        !matches!(self.files.options_file, OptionsFile::Off)
+    }
+
     /// Load gameplay options from a file, if enabled.  If the file does not
-    /// exist, `self.options` is returned.
-    pub(crate) fn load_options(&self) -> Result<Options, LoadError> {
+    /// exist or its "Use config defaults" toggle is on, `self.options` is
+    /// returned as the options.  If an error occurs and `ignore_errors` is
+    /// set, `self.options` is returned along with the error.
+    pub(crate) fn load_options(&self) -> Result<Loaded<SavedOptions>, LoadError> {
         let r = match self.options_file() {
-            Ok(Some(p)) => Options::load(&p),
+            Ok(Some(p)) => SavedOptions::load(&p),
             Ok(None) => Ok(None),
-            Err(_) if self.files.ignore_errors => Ok(None),
             Err(_) => Err(LoadError::no_path("options")),
         };
         match r {
-            Ok(Some(opts)) => Ok(opts),
-            Ok(None) => Ok(self.options),
-            Err(_) if self.files.ignore_errors => Ok(self.options),
+            Ok(Some(loaded)) if loaded.value.use_config_defaults => Ok(Loaded {
+                value: SavedOptions {
+                    options: self.options,
+                    ..loaded.value
+                },
+                ignored: loaded.ignored,
+                repaired: loaded.repaired,
+            }),
+            Ok(Some(loaded)) => Ok(loaded),
+            Ok(None) => Ok(Loaded::new(SavedOptions::from(self.options))),
+            Err(e) if self.files.ignore_errors && !e.is_too_new() => Ok(Loaded {
+                value: SavedOptions::from(self.options),
+                ignored: Some(e),
+                repaired: None,
+            }),
             Err(e) => Err(e),
         }
     }
 
-    /// Save the given gameplay options to a file, if enabled.
-    pub(crate) fn save_options(&self, options: Options) -> Result<(), SaveError> {
-        match self.options_file() {
-            Ok(Some(p)) => {
-                let r = options.save(&p);
-                if r.is_err() && self.files.ignore_errors {
-                    Ok(())
-                } else {
-                    r
-                }
+    /// Save the given gameplay options to a file, if enabled.  If the "Use
+    /// config defaults" toggle is on, the options from the configuration are
+    /// saved in place of the given ones so that changes made in the options
+    /// menu are not kept.
+    pub(crate) fn save_options(&self, saved: SavedOptions) -> Result<(), SaveError> {
+        let saved = if saved.use_config_defaults {
+            SavedOptions {
+                options: self.options,
+                ..saved
             }
+        } else {
+            saved
+        };
+        match self.options_file() {
+            Ok(Some(p)) => match saved.save(&p) {
+                Err(e) if self.files.ignore_errors && !e.is_too_new() => Ok(()),
+                r => r,
+            },
             Ok(None) => Ok(()),
             Err(_) if self.files.ignore_errors => Ok(()),
             Err(_) => Err(SaveError::no_path("options")),
@@ -119,27 +240,144 @@ impl Config {
     }
 
     /// Load high scores from a file.  If the file does not exist, an empty
-    /// `HighScores` value is returned.
-    pub(crate) fn load_high_scores(&self) -> Result<HighScores, LoadError> {
-        match self.high_scores_file() {
-            Ok(p) => {
-                let r = HighScores::load(&p);
-                if r.is_err() && self.files.ignore_errors {
-                    Ok(HighScores::default())
-                } else {
-                    r
-                }
-            }
-            Err(_) if self.files.ignore_errors => Ok(HighScores::default()),
-            Err(_) => Err(LoadError::no_path("high scores")),
+    /// `HighScores` value is returned.  If an error occurs and
+    /// `ignore_errors` is set, an empty `HighScores` value is returned along
+    /// with the error.
+    pub(crate) fn load_high_scores(&self) -> Result<Loaded<HighScores>, LoadError> {
+        let r = self
+            .high_scores_file()
+            .map_err(|_| LoadError::no_path("high scores"))
+            .and_then(|p| HighScores::load(&p));
+        match r {
+            Ok(scores) => Ok(Loaded::new(scores)),
+            Err(e) if self.files.ignore_errors && !e.is_too_new() => Ok(Loaded {
+                value: HighScores::default(),
+                ignored: Some(e),
+                repaired: None,
+            }),
+            Err(e) => Err(e),
         }
     }
 
     /// Save the given high scores to a file
     pub(crate) fn save_high_scores(&self, scores: &HighScores) -> Result<(), SaveError> {
         match self.high_scores_file() {
+            Ok(p) => match scores.save(&p) {
+                Err(e) if self.files.ignore_errors && !e.is_too_new() => Ok(()),
+                r => r,
+            },
+            Err(_) if self.files.ignore_errors => Ok(()),
+            Err(_) => Err(SaveError::no_path("high scores")),
+        }
+    }
+
+    /// Return the path at which the given kind of save file is stored, or
+    /// `None` if it is the options file and saving of options is disabled
+    pub(crate) fn save_file_path(&self, file: SaveFile) -> Result<Option<PathBuf>, SaveError> {
+        match file {
+            SaveFile::Options => match self.options_file() {
+                Ok(p) => Ok(p.map(Cow::into_owned)),
+                Err(_) => Err(SaveError::no_path(file.desc())),
+            },
+            SaveFile::HighScores => self
+                .high_scores_file()
+                .map(Some)
+                .map_err(|_| SaveError::no_path(file.desc())),
+            SaveFile::History => self
+                .history_file()
+                .map(|p| Some(p.into_owned()))
+                .map_err(|_| SaveError::no_path(file.desc())),
+            SaveFile::Session => self
+                .session_file()
+                .map(Some)
+                .map_err(|_| SaveError::no_path(file.desc())),
+            SaveFile::Favorites => self
+                .favorites_file()
+                .map(Some)
+                .map_err(|_| SaveError::no_path(file.desc())),
+        }
+    }
+
+    /// Move the options or high scores file aside by renaming it to end in
+    /// `.corrupt` (replacing any previous such backup), so that the program
+    /// can start afresh without it.  Returns the path of the backup, or `None`
+    /// if there was no file to move.
+    pub(crate) fn reset_file(&self, file: SaveFile) -> Result<Option<PathBuf>, SaveError> {
+        let Some(path) = self.save_file_path(file)? else {
+            return Ok(None);
+        };
+        let mut backup = path.clone().into_os_string();
+        backup.push(".corrupt");
+        let backup = PathBuf::from(backup);
+        match fs_err::rename(&path, &backup) {
+            Ok(()) => Ok(Some(backup)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(SaveError::backup(file.desc(), e)),
+        }
+    }
+
+    /// Return the path to the game history file: the path given in the
+    /// configuration or, if that is not set, the default path
+    fn history_file(&self) -> Result<Cow<'_, Path>, NoHomeError> {
+        match self.files.history_file {
+            Some(ref path) => Ok(Cow::from(path)),
+            None => GameRecord::default_path().map(Cow::from).ok_or(NoHomeError),
+        }
+    }
+
+    /// Append a record of a completed game to the game history file
+    pub(crate) fn append_history(&self, record: &GameRecord) -> Result<(), SaveError> {
+        let r = self
+            .history_file()
+            .map_err(|_| SaveError::no_path("game history"))
+            .and_then(|p| record.append(&p));
+        if r.is_err() && self.files.ignore_errors {
+            Ok(())
+        } else {
+            r
+        }
+    }
+
+    /// Load all records of completed games from the game history file
+    pub(crate) fn load_history(&self) -> Result<Vec<GameRecord>, LoadError> {
+        let p = self
+            .history_file()
+            .map_err(|_| LoadError::no_path("game history"))?;
+        GameRecord::load_all(&p)
+    }
+
+    /// Return the path to the session file: the path given in the
+    /// configuration or, if that is not set, the default path
+    pub(crate) fn session_file(&self) -> Result<PathBuf, NoHomeError> {
+        match self.files.session_file {
+            Some(ref path) => Ok(path.clone()),
+            None => SessionFile::default_path().ok_or(NoHomeError),
+        }
+    }
+
+    /// Return the path to the favorite boards file: the path given in the
+    /// configuration or, if that is not set, the default path
+    fn favorites_file(&self) -> Result<PathBuf, NoHomeError> {
+        match self.files.favorites_file {
+            Some(ref path) => Ok(path.clone()),
+            None => Favorites::default_path().ok_or(NoHomeError),
+        }
+    }
+
+    /// Load the favorite boards from a file.  If the file does not exist, an
+    /// empty `Favorites` value is returned.
+    pub(crate) fn load_favorites(&self) -> Result<Favorites, LoadError> {
+        let p = self
+            .favorites_file()
+            .map_err(|_| LoadError::no_path("favorites"))?;
+        Favorites::load(&p)
+    }
+
+    /// Save the given favorite boards to a file
+    pub(crate) fn save_favorites(&self, favorites: &Favorites) -> Result<(), SaveError> {
+        match self.favorites_file() {
             Ok(p) => {
-                let r = scores.save(&p);
+                let r = favorites.save(&p);
                 if r.is_err() && self.files.ignore_errors {
                     Ok(())
                 } else {
@@ -147,11 +385,147 @@ impl Config {
                 }
             }
             Err(_) if self.files.ignore_errors => Ok(()),
-            Err(_) => Err(SaveError::no_path("high scores")),
+            Err(_) => Err(SaveError::no_path("favorites")),
+        }
+    }
+
+    /// Return whether errors that occur while saving & loading data files
+    /// are ignored
+    pub(crate) fn ignore_errors(&self) -> bool {
+        self.files.ignore_errors
+    }
+
+    /// Return the directory in which screenshots should be saved: the
+    /// directory given in the configuration or, if that is not set, a
+    /// `screenshots` directory inside the local data directory.
+    pub(crate) fn screenshots_dir(&self) -> Result<PathBuf, NoHomeError> {
+        match self.files.screenshots_dir {
+            Some(ref path) => Ok(path.clone()),
+            None => data_dir().map(|p| p.join("screenshots")).ok_or(NoHomeError),
+        }
+    }
+
+    /// Return the directory in which level packs are stored: the directory
+    /// given in the configuration or, if that is not set, a `levels`
+    /// directory inside the local data directory.
+    pub(crate) fn levels_dir(&self) -> Result<PathBuf, NoHomeError> {
+        match self.files.levels_dir {
+            Some(ref path) => Ok(path.clone()),
+            None => data_dir().map(|p| p.join("levels")).ok_or(NoHomeError),
+        }
+    }
+}
+
+/// A value loaded from a data file, along with the error that occurred while
+/// loading it, if any, that was ignored because of the `ignore-errors`
+/// setting, and any values in the file that were repaired
+#[derive(Debug)]
+pub(crate) struct Loaded<T> {
+    /// The loaded value, or a default value if an error was ignored
+    pub(crate) value: T,
+
+    /// The error that was ignored, if any
+    pub(crate) ignored: Option<LoadError>,
+
+    /// The values in the file that could not be understood and were replaced
+    /// in `value`, if any.  Only the options file is repaired in this way.
+    pub(crate) repaired: Option<ReplacedValues>,
+}
+
+impl<T> Loaded<T> {
+    /// Wrap a value that was loaded without any errors or repairs
+    pub(crate) fn new(value: T) -> Loaded<T> {
+        Loaded {
+            value,
+            ignored: None,
+            repaired: None,
+        }
+    }
+}
+
+/// Watches a configuration file for modifications so that the configuration
+/// can be reloaded while the program is running
+#[derive(Clone, Debug)]
+pub(crate) struct ConfigWatcher {
+    /// The path to the configuration file
+    path: PathBuf,
+
+    /// Whether a missing configuration file should be treated as an empty
+    /// configuration
+    allow_missing: bool,
+
+    /// The modification time of the file as of the last check, or `None` if
+    /// it could not be determined (e.g., because the file did not exist)
+    mtime: Option<SystemTime>,
+
+    /// When the file was last checked
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    /// Start watching the configuration file at `path`.  `allow_missing` has
+    /// the same meaning as for [`Config::load()`].
+    pub(crate) fn new(path: PathBuf, allow_missing: bool) -> ConfigWatcher {
+        let mtime = modification_time(&path);
+        ConfigWatcher {
+            path,
+            allow_missing,
+            mtime,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// If at least [`consts::CONFIG_POLL_INTERVAL`] has passed since the last
+    /// check and the file's modification time has changed since then, reload
+    /// the configuration and return it.
+    ///
+    /// If the modified file cannot be read or parsed (e.g., because the user
+    /// saved it while in the middle of editing), `None` is returned, and the
+    /// file will be tried again once it is next modified.
+    pub(crate) fn poll(&mut self) -> Option<Config> {
+        if self.last_check.elapsed() < consts::CONFIG_POLL_INTERVAL {
+            return None;
         }
+        self.last_check = Instant::now();
+        let mtime = modification_time(&self.path);
+        if mtime == self.mtime {
+            return None;
+        }
+        self.mtime = mtime;
+        Config::load(&self.path, self.allow_missing).ok()
     }
 }
 
+/// Return the glyph configuration formed by laying the `[glyphs]` table of
+/// the configuration file contents `content` over the skin `name` from the
+/// `[skins]` table, so that glyphs set directly in `[glyphs]` take precedence
+/// over those in the skin.
+///
+/// This should only be called on contents that have already been successfully
+/// deserialized into a [`Config`].  Unknown keys in the skin are discarded, as
+/// they have already been recorded in [`Config::unknown_skin_keys`].
+fn select_skin(content: &str, name: &str) -> Result<GlyphConfig, ConfigError> {
+    let mut table = toml::from_str::<toml::Table>(content)?;
+    let Some(toml::Value::Table(mut glyphs)) = table
+        .get_mut("skins")
+        .and_then(|skins| skins.as_table_mut())
+        .and_then(|skins| skins.remove(name))
+    else {
+        return Err(ConfigError::UnknownSkin(name.to_owned()));
+    };
+    if let Some(toml::Value::Table(overrides)) = table.remove("glyphs") {
+        glyphs.extend(overrides.into_iter().filter(|(key, _)| key != "skin"));
+    }
+    glyphs.retain(|key, _| GlyphConfig::SKIN_KEYS.contains(&key));
+    toml::Value::Table(glyphs).try_into().map_err(Into::into)
+}
+
+/// Return the modification time of the file at `path`, or `None` if it cannot
+/// be determined
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs_err::metadata(path).and_then(|md| md.modified()).ok()
+}
+
 #[derive(Clone, Deserialize, Debug, Default, Eq, PartialEq)]
 #[serde(try_from = "RawFileConfig")]
 pub(crate) struct FileConfig {
@@ -163,6 +537,26 @@ pub(crate) struct FileConfig {
     // level high scores here.
     high_scores_dir: Option<PathBuf>,
 
+    /// Path to the directory in which screenshots should be saved
+    screenshots_dir: Option<PathBuf>,
+
+    /// Path to the file to which a record of each completed game should be
+    /// appended
+    history_file: Option<PathBuf>,
+
+    /// Path to the file in which the state of the running session is
+    /// periodically recorded
+    session_file: Option<PathBuf>,
+
+    /// Path to the file in which the player's favorite boards are stored
+    favorites_file: Option<PathBuf>,
+
+    /// Path to the directory in which level packs are stored
+    levels_dir: Option<PathBuf>,
+
+    /// Whether to include ANSI escape sequences for styling in screenshots
+    pub(crate) ansi_screenshots: bool,
+
     /// Whether to ignore errors that occur while saving & loading options &
     /// high-score files.
     ignore_errors: bool,
@@ -173,6 +567,12 @@ pub(crate) struct FileConfig {
 struct RawFileConfig {
     options_file: OptionsFile<String>,
     high_scores_dir: Option<String>,
+    screenshots_dir: Option<String>,
+    history_file: Option<String>,
+    session_file: Option<String>,
+    favorites_file: Option<String>,
+    levels_dir: Option<String>,
+    ansi_screenshots: bool,
     ignore_errors: bool,
 }
 
@@ -187,6 +587,20 @@ impl TryFrom<RawFileConfig> for FileConfig {
                 .as_deref()
                 .map(expanduser)
                 .transpose()?,
+            screenshots_dir: value
+                .screenshots_dir
+                .as_deref()
+                .map(expanduser)
+                .transpose()?,
+            history_file: value.history_file.as_deref().map(expanduser).transpose()?,
+            session_file: value.session_file.as_deref().map(expanduser).transpose()?,
+            favorites_file: value
+                .favorites_file
+                .as_deref()
+                .map(expanduser)
+                .transpose()?,
+            levels_dir: value.levels_dir.as_deref().map(expanduser).transpose()?,
+            ansi_screenshots: value.ansi_screenshots,
             ignore_errors: value.ignore_errors,
         })
     }
@@ -260,22 +674,58 @@ impl<'de> Deserialize<'de> for OptionsFile<String> {
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
 pub(crate) struct GlyphConfig {
-    pub(crate) snake_head: SnakeHeadConfig,
+    /// The name of the skin in the `[skins]` table that the glyphs are taken
+    /// from, if any
+    pub(crate) skin: Option<String>,
+
+    pub(crate) snake_head: DirectionalGlyph,
     pub(crate) snake_body: Glyph,
+    pub(crate) snake_grow: Glyph,
     pub(crate) fruit: Glyph,
     pub(crate) obstacle: Glyph,
+    pub(crate) plow: Glyph,
+    pub(crate) speed_up: Glyph,
+    pub(crate) slow_down: Glyph,
+    pub(crate) star: Glyph,
+    pub(crate) hydra_fruit: Glyph,
+    pub(crate) mud: Glyph,
+    pub(crate) ice: Glyph,
+    pub(crate) gate: DirectionalGlyph,
+    pub(crate) forbidden: Glyph,
     pub(crate) collision: Glyph,
+    pub(crate) eat: CellStyle,
+    pub(crate) spawn: CellStyle,
+    pub(crate) ghost_head: Option<CellStyle>,
+    pub(crate) background: Option<Glyph>,
 }
 
 impl Default for GlyphConfig {
     fn default() -> GlyphConfig {
         GlyphConfig {
-            snake_head: SnakeHeadConfig::default(),
+            skin: None,
+            snake_head: DirectionalGlyph {
+                symbol: DirectionalSymbol::Split {
+                    north: Symbol::try_from(consts::SNAKE_HEAD_NORTH_SYMBOL)
+                        .expect("SNAKE_HEAD_NORTH_SYMBOL should be a valid Symbol"),
+                    south: Symbol::try_from(consts::SNAKE_HEAD_SOUTH_SYMBOL)
+                        .expect("SNAKE_HEAD_SOUTH_SYMBOL should be a valid Symbol"),
+                    east: Symbol::try_from(consts::SNAKE_HEAD_EAST_SYMBOL)
+                        .expect("SNAKE_HEAD_EAST_SYMBOL should be a valid Symbol"),
+                    west: Symbol::try_from(consts::SNAKE_HEAD_WEST_SYMBOL)
+                        .expect("SNAKE_HEAD_WEST_SYMBOL should be a valid Symbol"),
+                },
+                style: consts::SNAKE_STYLE,
+            },
             snake_body: Glyph {
                 symbol: Symbol::try_from(consts::SNAKE_BODY_SYMBOL)
                     .expect("SNAKE_BODY_SYMBOL should be a valid Symbol"),
                 style: consts::SNAKE_STYLE,
             },
+            snake_grow: Glyph {
+                symbol: Symbol::try_from(consts::SNAKE_GROW_SYMBOL)
+                    .expect("SNAKE_GROW_SYMBOL should be a valid Symbol"),
+                style: consts::SNAKE_STYLE,
+            },
             fruit: Glyph {
                 symbol: Symbol::try_from(consts::FRUIT_SYMBOL)
                     .expect("FRUIT_SYMBOL should be a valid Symbol"),
@@ -286,11 +736,172 @@ impl Default for GlyphConfig {
                     .expect("OBSTACLE_SYMBOL should be a valid Symbol"),
                 style: consts::OBSTACLE_STYLE,
             },
+            plow: Glyph {
+                symbol: Symbol::try_from(consts::PLOW_SYMBOL)
+                    .expect("PLOW_SYMBOL should be a valid Symbol"),
+                style: consts::PLOW_STYLE,
+            },
+            speed_up: Glyph {
+                symbol: Symbol::try_from(consts::SPEED_UP_SYMBOL)
+                    .expect("SPEED_UP_SYMBOL should be a valid Symbol"),
+                style: consts::SPEED_UP_STYLE,
+            },
+            slow_down: Glyph {
+                symbol: Symbol::try_from(consts::SLOW_DOWN_SYMBOL)
+                    .expect("SLOW_DOWN_SYMBOL should be a valid Symbol"),
+                style: consts::SLOW_DOWN_STYLE,
+            },
+            star: Glyph {
+                symbol: Symbol::try_from(consts::STAR_SYMBOL)
+                    .expect("STAR_SYMBOL should be a valid Symbol"),
+                style: consts::STAR_STYLE,
+            },
+            hydra_fruit: Glyph {
+                symbol: Symbol::try_from(consts::HYDRA_FRUIT_SYMBOL)
+                    .expect("HYDRA_FRUIT_SYMBOL should be a valid Symbol"),
+                style: consts::HYDRA_FRUIT_STYLE,
+            },
+            mud: Glyph {
+                symbol: Symbol::try_from(consts::MUD_SYMBOL)
+                    .expect("MUD_SYMBOL should be a valid Symbol"),
+                style: consts::MUD_STYLE,
+            },
+            ice: Glyph {
+                symbol: Symbol::try_from(consts::ICE_SYMBOL)
+                    .expect("ICE_SYMBOL should be a valid Symbol"),
+                style: consts::ICE_STYLE,
+            },
+            gate: DirectionalGlyph {
+                symbol: DirectionalSymbol::Split {
+                    north: Symbol::try_from(consts::GATE_NORTH_SYMBOL)
+                        .expect("GATE_NORTH_SYMBOL should be a valid Symbol"),
+                    south: Symbol::try_from(consts::GATE_SOUTH_SYMBOL)
+                        .expect("GATE_SOUTH_SYMBOL should be a valid Symbol"),
+                    east: Symbol::try_from(consts::GATE_EAST_SYMBOL)
+                        .expect("GATE_EAST_SYMBOL should be a valid Symbol"),
+                    west: Symbol::try_from(consts::GATE_WEST_SYMBOL)
+                        .expect("GATE_WEST_SYMBOL should be a valid Symbol"),
+                },
+                style: consts::GATE_STYLE,
+            },
+            forbidden: Glyph {
+                symbol: Symbol::try_from(consts::FORBIDDEN_SYMBOL)
+                    .expect("FORBIDDEN_SYMBOL should be a valid Symbol"),
+                style: consts::FORBIDDEN_STYLE,
+            },
             collision: Glyph {
                 symbol: Symbol::try_from(consts::COLLISION_SYMBOL)
                     .expect("COLLISION_SYMBOL should be a valid Symbol"),
                 style: consts::COLLISION_STYLE,
             },
+            eat: CellStyle {
+                style: consts::EAT_STYLE,
+            },
+            spawn: CellStyle {
+                style: consts::SPAWN_STYLE,
+            },
+            ghost_head: None,
+            background: None,
+        }
+    }
+}
+
+impl GlyphConfig {
+    /// The keys that can be set in a `[skins.<name>]` table: all of the keys
+    /// of `[glyphs]` other than `skin`
+    const SKIN_KEYS: &'static [&'static str] = &[
+        "snake-head",
+        "snake-body",
+        "snake-grow",
+        "fruit",
+        "obstacle",
+        "plow",
+        "speed-up",
+        "slow-down",
+        "star",
+        "hydra-fruit",
+        "mud",
+        "ice",
+        "gate",
+        "forbidden",
+        "collision",
+        "eat",
+        "spawn",
+        "ghost-head",
+        "background",
+    ];
+}
+
+/// A named set of glyphs from the `[skins]` table.  Unlike in `[glyphs]`,
+/// unknown keys in a skin are not an error; they are ignored and reported as
+/// warnings instead, so that a skin written for a newer version of the
+/// program (or containing a typo) doesn't stop the configuration from loading.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Skin {
+    pub(crate) glyphs: GlyphConfig,
+
+    /// The unknown keys in the skin, with their locations in the
+    /// configuration file
+    unknown: Vec<toml::Spanned<String>>,
+}
+
+impl<'de> Deserialize<'de> for Skin {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Skin;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a table of glyphs")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut glyphs = toml::Table::new();
+                let mut unknown = Vec::new();
+                while let Some(key) = map.next_key::<toml::Spanned<String>>()? {
+                    if GlyphConfig::SKIN_KEYS.contains(&key.get_ref().as_str()) {
+                        glyphs.insert(key.into_inner(), map.next_value()?);
+                    } else {
+                        map.next_value::<serde::de::IgnoredAny>()?;
+                        unknown.push(key);
+                    }
+                }
+                let glyphs = toml::Value::Table(glyphs)
+                    .try_into()
+                    .map_err(|e: toml::de::Error| serde::de::Error::custom(e.message()))?;
+                Ok(Skin { glyphs, unknown })
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+/// An unknown key in a `[skins.<name>]` table, which is ignored
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("ignoring unknown key {key:?} in [skins.{skin}] at line {line}, column {column} of the configuration file")]
+pub(crate) struct UnknownSkinKey {
+    skin: String,
+    key: String,
+    line: usize,
+    column: usize,
+}
+
+impl UnknownSkinKey {
+    /// Construct an `UnknownSkinKey` for the key `key` in the skin `skin`,
+    /// locating the key within the configuration file contents `content`
+    fn new(content: &str, skin: &str, key: &toml::Spanned<String>) -> UnknownSkinKey {
+        let before = content.get(..key.span().start).unwrap_or(content);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        UnknownSkinKey {
+            skin: skin.to_owned(),
+            key: key.get_ref().clone(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
         }
     }
 }
@@ -304,6 +915,14 @@ pub(crate) struct Glyph {
     pub(crate) style: Style,
 }
 
+/// A style that is applied to a cell without changing its symbol
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CellStyle {
+    #[serde(default, with = "parse_style::serde::ratatui::style")]
+    pub(crate) style: Style,
+}
+
 /// A single non-control grapheme that occupies exactly one display column
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct Symbol(String);
@@ -372,36 +991,20 @@ impl<'de> Deserialize<'de> for Symbol {
     }
 }
 
+/// A glyph whose symbol can depend on a direction, such as the one in which
+/// the snake's head is moving
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
-pub(crate) struct SnakeHeadConfig {
-    pub(crate) symbol: SnakeHeadSymbol,
+pub(crate) struct DirectionalGlyph {
+    pub(crate) symbol: DirectionalSymbol,
 
     #[serde(default, with = "parse_style::serde::ratatui::style")]
     pub(crate) style: Style,
 }
 
-impl Default for SnakeHeadConfig {
-    fn default() -> SnakeHeadConfig {
-        SnakeHeadConfig {
-            symbol: SnakeHeadSymbol::Split {
-                north: Symbol::try_from(consts::SNAKE_HEAD_NORTH_SYMBOL)
-                    .expect("SNAKE_HEAD_NORTH_SYMBOL should be a valid Symbol"),
-                south: Symbol::try_from(consts::SNAKE_HEAD_SOUTH_SYMBOL)
-                    .expect("SNAKE_HEAD_SOUTH_SYMBOL should be a valid Symbol"),
-                east: Symbol::try_from(consts::SNAKE_HEAD_EAST_SYMBOL)
-                    .expect("SNAKE_HEAD_EAST_SYMBOL should be a valid Symbol"),
-                west: Symbol::try_from(consts::SNAKE_HEAD_WEST_SYMBOL)
-                    .expect("SNAKE_HEAD_WEST_SYMBOL should be a valid Symbol"),
-            },
-            style: consts::SNAKE_STYLE,
-        }
-    }
-}
-
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 #[serde(deny_unknown_fields, untagged)]
-pub(crate) enum SnakeHeadSymbol {
+pub(crate) enum DirectionalSymbol {
     All(Symbol),
     Split {
         north: Symbol,
@@ -411,14 +1014,409 @@ pub(crate) enum SnakeHeadSymbol {
     },
 }
 
-impl SnakeHeadSymbol {
+impl DirectionalSymbol {
     pub(crate) fn for_direction(&self, dir: Direction) -> &Symbol {
         match (self, dir) {
-            (SnakeHeadSymbol::All(sym), _) => sym,
-            (SnakeHeadSymbol::Split { north, .. }, Direction::North) => north,
-            (SnakeHeadSymbol::Split { south, .. }, Direction::South) => south,
-            (SnakeHeadSymbol::Split { east, .. }, Direction::East) => east,
-            (SnakeHeadSymbol::Split { west, .. }, Direction::West) => west,
+            (DirectionalSymbol::All(sym), _) => sym,
+            (DirectionalSymbol::Split { north, .. }, Direction::North) => north,
+            (DirectionalSymbol::Split { south, .. }, Direction::South) => south,
+            (DirectionalSymbol::Split { east, .. }, Direction::East) => east,
+            (DirectionalSymbol::Split { west, .. }, Direction::West) => west,
+        }
+    }
+}
+
+/// Keyboard control settings
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct ControlsConfig {
+    /// The key for immediately restarting a game in progress
+    pub(crate) restart: KeyBinding,
+
+    /// Whether to ask for confirmation before restarting a game in progress
+    pub(crate) confirm_restart: bool,
+
+    /// The key for toggling slow motion during a game
+    pub(crate) slow_motion: KeyBinding,
+
+    /// The key for saving a screenshot of the current screen
+    pub(crate) screenshot: KeyBinding,
+
+    /// The key for toggling the frame timing diagnostics overlay
+    pub(crate) diagnostics: KeyBinding,
+
+    /// The number of times per game that the player may rewind the game after
+    /// the snake dies
+    pub(crate) rewinds: u32,
+}
+
+impl Default for ControlsConfig {
+    fn default() -> ControlsConfig {
+        ControlsConfig {
+            restart: consts::RESTART_KEY,
+            confirm_restart: true,
+            slow_motion: consts::SLOW_MOTION_KEY,
+            screenshot: consts::SCREENSHOT_KEY,
+            diagnostics: consts::DIAGNOSTICS_KEY,
+            rewinds: 0,
+        }
+    }
+}
+
+/// Settings for how a game responds to changes in the terminal and to input
+/// after it ends, and for how large levels are scrolled
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct GameConfig {
+    /// Whether to pause the game when the terminal loses focus
+    pub(crate) pause_on_focus_loss: bool,
+
+    /// Whether to pause the game when the terminal is resized
+    pub(crate) pause_on_resize: bool,
+
+    /// Whether to pause the game when the terminal stops accepting output for
+    /// a while, e.g., because an SSH connection froze
+    pub(crate) pause_on_stall: bool,
+
+    /// For how many milliseconds after a game ends to ignore input, so that
+    /// keys pressed just before the end don't choose a game-over option
+    pub(crate) game_over_hold_ms: u64,
+
+    /// Whether to color the snake's head when the cell directly ahead of it
+    /// would kill it (or nearly would)
+    pub(crate) collision_warning: bool,
+
+    /// How the view of a level too large to show at once follows the snake
+    pub(crate) camera: CameraMode,
+
+    /// Whether to ask for the player's name after a new high score so that
+    /// it can be shown on the high scores screen
+    pub(crate) ask_name: bool,
+
+    /// For how many minutes a paused or finished game can go without input
+    /// before the application returns to the main menu; 0 means never
+    pub(crate) idle_timeout_mins: u64,
+
+    /// Whether to show an arrow in the score bar pointing toward the fruit
+    /// nearest to the snake's head
+    pub(crate) fruit_arrow: bool,
+
+    /// Whether to show in the score bar how much of the level the snake
+    /// fills
+    pub(crate) fill_meter: bool,
+
+    /// Whether to show the name of the level and the seed it was generated
+    /// from on the level's top border
+    pub(crate) border_title: bool,
+
+    /// Whether to hide the score & high score in the score bar until the game
+    /// is over
+    pub(crate) hide_score: bool,
+
+    /// Whether to save a new high score to disk as soon as the old one is
+    /// surpassed (and periodically as it rises) rather than only when the
+    /// game ends
+    pub(crate) live_high_score: bool,
+
+    /// Whether to show hints for pausing & quitting under the level at the
+    /// start of a game and while paused
+    pub(crate) key_hints: bool,
+
+    /// Whether clicking on a cell of the level steers the snake towards it
+    /// (experimental)
+    pub(crate) mouse_steering: bool,
+
+    /// Where the seeds for new games' random-number generators come from
+    pub(crate) rng: RngMode,
+
+    /// Whether to show a tip about avoiding crashes after the snake crashes
+    /// several times in a short span
+    pub(crate) crash_tips: bool,
+
+    /// The largest value that the Fruits option can be set to, from 1 to
+    /// [`FRUIT_CAPACITY`][consts::FRUIT_CAPACITY]
+    pub(crate) max_fruits: usize,
+}
+
+impl GameConfig {
+    /// Return how long after a game ends to ignore input
+    pub(crate) fn game_over_hold(&self) -> Duration {
+        Duration::from_millis(self.game_over_hold_ms)
+    }
+
+    /// Return how long a paused or finished game can go without input before
+    /// returning to the main menu.  A zero duration means never.
+    pub(crate) fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_mins.saturating_mul(60))
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            pause_on_focus_loss: true,
+            pause_on_resize: false,
+            pause_on_stall: true,
+            game_over_hold_ms: 500,
+            collision_warning: false,
+            camera: CameraMode::default(),
+            ask_name: false,
+            idle_timeout_mins: 0,
+            fruit_arrow: false,
+            fill_meter: false,
+            border_title: true,
+            hide_score: false,
+            live_high_score: false,
+            key_hints: true,
+            mouse_steering: false,
+            rng: RngMode::Thread,
+            crash_tips: false,
+            max_fruits: consts::DEFAULT_MAX_FRUITS,
+        }
+    }
+}
+
+/// An enum of the ways that the view of a level too large to show at once can
+/// follow the snake
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CameraMode {
+    /// The view only scrolls when the snake's head comes near its edges
+    #[default]
+    DeadZone,
+
+    /// The view scrolls on every move to keep the snake's head in the center
+    Centered,
+}
+
+/// An enum of the sources of the seeds for new games' random-number
+/// generators, written in configuration files as `"thread"`, `"os"`, or
+/// `"seeded:<u64>"`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum RngMode {
+    /// Draw each game's seed from the thread-local random-number generator
+    #[default]
+    Thread,
+
+    /// Draw each game's seed directly from the operating system's source of
+    /// randomness
+    Os,
+
+    /// Use the same seed for every game, so that every game with the same
+    /// options gets the same level & fruit placements
+    Seeded(u64),
+}
+
+impl RngMode {
+    /// Return a seed for a new game's random-number generator
+    pub(crate) fn seed(self) -> u64 {
+        match self {
+            RngMode::Thread => rand::random(),
+            // If the OS fails to provide randomness, the game can still be
+            // played with a seed from the thread-local generator
+            RngMode::Os => rand::rngs::OsRng
+                .try_next_u64()
+                .unwrap_or_else(|_| rand::random()),
+            RngMode::Seeded(seed) => seed,
+        }
+    }
+}
+
+impl fmt::Display for RngMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RngMode::Thread => write!(f, "thread"),
+            RngMode::Os => write!(f, "os"),
+            RngMode::Seeded(seed) => write!(f, "seeded:{seed}"),
+        }
+    }
+}
+
+impl std::str::FromStr for RngMode {
+    type Err = ParseRngModeError;
+
+    fn from_str(s: &str) -> Result<RngMode, ParseRngModeError> {
+        match s {
+            "thread" => Ok(RngMode::Thread),
+            "os" => Ok(RngMode::Os),
+            _ => s
+                .strip_prefix("seeded:")
+                .and_then(|seed| seed.trim().parse::<u64>().ok())
+                .map(RngMode::Seeded)
+                .ok_or(ParseRngModeError),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RngMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<RngMode>().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for RngMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+#[error("invalid RNG mode: expected \"thread\", \"os\", or \"seeded:<u64>\"")]
+pub(crate) struct ParseRngModeError;
+
+/// Interface appearance settings.  These only take effect at startup.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ThemeConfig {
+    /// How to indicate the currently-selected item in menus, lists, and rows
+    /// of buttons.  An empty list is treated the same as the default.
+    pub(crate) selection: Vec<SelectionIndicator>,
+
+    /// The symbol & style with which to fill the parts of the terminal
+    /// outside of the area that the interface is drawn in
+    pub(crate) backdrop: Option<Glyph>,
+
+    /// The style to apply to the score bar once the score has overtaken the
+    /// high score during a game
+    #[serde(with = "parse_style::serde::ratatui::style")]
+    pub(crate) record: Style,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> ThemeConfig {
+        ThemeConfig {
+            selection: vec![SelectionIndicator::Underline],
+            backdrop: None,
+            record: consts::RECORD_STYLE,
+        }
+    }
+}
+
+/// An enum of the ways of indicating the currently-selected item
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SelectionIndicator {
+    /// Underline the item
+    Underline,
+
+    /// Draw the item in bold
+    Bold,
+
+    /// Draw the item in reverse video
+    Reverse,
+
+    /// Surround a selected button with pointers
+    Brackets,
+}
+
+/// Settings for kiosk mode.  These are only used if `ratsnake` was run with
+/// `--kiosk`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct KioskConfig {
+    /// The sequence of keys that must be typed to quit the application
+    pub(crate) passphrase: Vec<KeyBinding>,
+}
+
+/// Gamepad input settings.  These are only used if `ratsnake` was built with
+/// the `gamepad` feature.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct GamepadConfig {
+    /// Whether to read input from gamepads
+    pub(crate) enabled: bool,
+
+    /// The commands that gamepad buttons are mapped to.  Buttons not present
+    /// in the configuration file keep their default mappings.
+    #[serde(deserialize_with = "deserialize_buttons")]
+    pub(crate) buttons: HashMap<GamepadButton, Command>,
+}
+
+impl GamepadConfig {
+    /// Return the default mapping of gamepad buttons to commands
+    fn default_buttons() -> HashMap<GamepadButton, Command> {
+        HashMap::from([
+            (GamepadButton::DpadUp, Command::Up),
+            (GamepadButton::DpadDown, Command::Down),
+            (GamepadButton::DpadLeft, Command::Left),
+            (GamepadButton::DpadRight, Command::Right),
+            (GamepadButton::South, Command::Enter),
+            (GamepadButton::East, Command::Esc),
+            (GamepadButton::West, Command::Space),
+            (GamepadButton::Start, Command::Esc),
+        ])
+    }
+}
+
+impl Default for GamepadConfig {
+    fn default() -> GamepadConfig {
+        GamepadConfig {
+            enabled: true,
+            buttons: GamepadConfig::default_buttons(),
+        }
+    }
+}
+
+/// Deserialize a table of button mappings and layer it on top of the default
+/// mappings
+fn deserialize_buttons<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<GamepadButton, Command>, D::Error> {
+    let mut buttons = GamepadConfig::default_buttons();
+    buttons.extend(HashMap::<GamepadButton, Command>::deserialize(
+        deserializer,
+    )?);
+    Ok(buttons)
+}
+
+/// The gamepad buttons that can be mapped to commands
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadButton {
+    /// Convert a `gilrs` button to a `GamepadButton`.  Returns `None` for
+    /// buttons that cannot be mapped.
+    pub(crate) fn from_gilrs(button: gilrs::Button) -> Option<GamepadButton> {
+        match button {
+            gilrs::Button::South => Some(GamepadButton::South),
+            gilrs::Button::East => Some(GamepadButton::East),
+            gilrs::Button::North => Some(GamepadButton::North),
+            gilrs::Button::West => Some(GamepadButton::West),
+            gilrs::Button::LeftTrigger => Some(GamepadButton::LeftTrigger),
+            gilrs::Button::LeftTrigger2 => Some(GamepadButton::LeftTrigger2),
+            gilrs::Button::RightTrigger => Some(GamepadButton::RightTrigger),
+            gilrs::Button::RightTrigger2 => Some(GamepadButton::RightTrigger2),
+            gilrs::Button::Select => Some(GamepadButton::Select),
+            gilrs::Button::Start => Some(GamepadButton::Start),
+            gilrs::Button::Mode => Some(GamepadButton::Mode),
+            gilrs::Button::LeftThumb => Some(GamepadButton::LeftThumb),
+            gilrs::Button::RightThumb => Some(GamepadButton::RightThumb),
+            gilrs::Button::DPadUp => Some(GamepadButton::DpadUp),
+            gilrs::Button::DPadDown => Some(GamepadButton::DpadDown),
+            gilrs::Button::DPadLeft => Some(GamepadButton::DpadLeft),
+            gilrs::Button::DPadRight => Some(GamepadButton::DpadRight),
+            _ => None,
         }
     }
 }
@@ -431,6 +1429,10 @@ pub(crate) enum ConfigError {
     Read(#[from] std::io::Error),
     #[error("failed to parse configuration file")]
     Parse(#[from] toml::de::Error),
+    #[error("glyphs.skin is set to {0:?}, but there is no [skins.{0}] table")]
+    UnknownSkin(String),
+    #[error("game.max-fruits must be between 1 and {cap}, but it is set to {0}", cap = consts::FRUIT_CAPACITY)]
+    MaxFruits(usize),
 }
 
 #[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
@@ -446,8 +1448,86 @@ pub(crate) enum ParseSymbolError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::Globals;
     use rstest::rstest;
 
+    mod watcher {
+        use super::*;
+        use std::time::Duration;
+        use tempfile::TempDir;
+
+        /// Write `content` to `path` and set the file's modification time to
+        /// `secs` seconds in the future, so that the change is detected even
+        /// on filesystems with coarse timestamps
+        fn write(path: &Path, content: &str, secs: u64) {
+            std::fs::write(path, content).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() + Duration::from_secs(secs))
+                .unwrap();
+        }
+
+        /// Make the watcher act as though its last check was long enough ago
+        /// that it can check again
+        fn rewind(watcher: &mut ConfigWatcher) {
+            watcher.last_check = Instant::now()
+                .checked_sub(consts::CONFIG_POLL_INTERVAL)
+                .unwrap();
+        }
+
+        #[test]
+        fn reload_on_change() {
+            let tmpdir = TempDir::new().unwrap();
+            let path = tmpdir.path().join("config.toml");
+            write(&path, "[glyphs.fruit]\nsymbol = \"@\"\n", 10);
+            let mut watcher = ConfigWatcher::new(path.clone(), false);
+            rewind(&mut watcher);
+            assert_eq!(watcher.poll(), None);
+
+            write(&path, "[glyphs.fruit]\nsymbol = \"%\"\n", 20);
+            rewind(&mut watcher);
+            let cfg = watcher.poll().unwrap();
+            assert_eq!(cfg.glyphs.fruit.symbol, "%".parse::<Symbol>().unwrap());
+            assert_eq!(watcher.poll(), None);
+
+            // Checks are rate-limited
+            write(&path, "[glyphs.fruit]\nsymbol = \"&\"\n", 30);
+            assert_eq!(watcher.poll(), None);
+            rewind(&mut watcher);
+            assert!(watcher.poll().is_some());
+
+            // Invalid files are skipped until they're next modified
+            write(&path, "[glyphs.fruit\n", 40);
+            rewind(&mut watcher);
+            assert_eq!(watcher.poll(), None);
+            rewind(&mut watcher);
+            assert_eq!(watcher.poll(), None);
+        }
+
+        #[test]
+        fn missing_file() {
+            let tmpdir = TempDir::new().unwrap();
+            let path = tmpdir.path().join("config.toml");
+            let mut watcher = ConfigWatcher::new(path.clone(), true);
+            rewind(&mut watcher);
+            assert_eq!(watcher.poll(), None);
+            write(&path, "[controls]\nconfirm-restart = false\n", 10);
+            rewind(&mut watcher);
+            assert!(!watcher.poll().unwrap().controls.confirm_restart);
+            std::fs::remove_file(&path).unwrap();
+            rewind(&mut watcher);
+            assert_eq!(
+                watcher.poll(),
+                Some(Config {
+                    path: Some(path),
+                    ..Config::default()
+                })
+            );
+        }
+    }
+
     mod deser {
         use super::*;
         use ratatui::style::{Color, Modifier};
@@ -474,6 +1554,7 @@ mod tests {
                         )),
                         ..FileConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -497,6 +1578,7 @@ mod tests {
                         options_file: OptionsFile::Default,
                         ..FileConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -515,6 +1597,7 @@ mod tests {
                         options_file: OptionsFile::Default,
                         ..FileConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -533,6 +1616,7 @@ mod tests {
                         options_file: OptionsFile::Off,
                         ..FileConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -548,12 +1632,13 @@ mod tests {
                 cfg,
                 Config {
                     glyphs: GlyphConfig {
-                        snake_head: SnakeHeadConfig {
-                            symbol: SnakeHeadSymbol::All("@".parse::<Symbol>().unwrap()),
+                        snake_head: DirectionalGlyph {
+                            symbol: DirectionalSymbol::All("@".parse::<Symbol>().unwrap()),
                             style: Style::new(),
                         },
                         ..GlyphConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -572,12 +1657,13 @@ mod tests {
                 cfg,
                 Config {
                     glyphs: GlyphConfig {
-                        snake_head: SnakeHeadConfig {
-                            symbol: SnakeHeadSymbol::All("@".parse::<Symbol>().unwrap()),
+                        snake_head: DirectionalGlyph {
+                            symbol: DirectionalSymbol::All("@".parse::<Symbol>().unwrap()),
                             style: Style::new().fg(Color::Indexed(1)),
                         },
                         ..GlyphConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -599,8 +1685,8 @@ mod tests {
                 cfg,
                 Config {
                     glyphs: GlyphConfig {
-                        snake_head: SnakeHeadConfig {
-                            symbol: SnakeHeadSymbol::Split {
+                        snake_head: DirectionalGlyph {
+                            symbol: DirectionalSymbol::Split {
                                 north: "↑".parse::<Symbol>().unwrap(),
                                 south: "↓".parse::<Symbol>().unwrap(),
                                 east: "→".parse::<Symbol>().unwrap(),
@@ -610,6 +1696,7 @@ mod tests {
                         },
                         ..GlyphConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -632,8 +1719,8 @@ mod tests {
                 cfg,
                 Config {
                     glyphs: GlyphConfig {
-                        snake_head: SnakeHeadConfig {
-                            symbol: SnakeHeadSymbol::Split {
+                        snake_head: DirectionalGlyph {
+                            symbol: DirectionalSymbol::Split {
                                 north: "↑".parse::<Symbol>().unwrap(),
                                 south: "↓".parse::<Symbol>().unwrap(),
                                 east: "→".parse::<Symbol>().unwrap(),
@@ -646,6 +1733,7 @@ mod tests {
                         },
                         ..GlyphConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -666,6 +1754,7 @@ mod tests {
                         },
                         ..GlyphConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
@@ -692,10 +1781,94 @@ mod tests {
                         },
                         ..GlyphConfig::default()
                     },
+                    path: Some(tmp.path().to_owned()),
                     ..Config::default()
                 }
             );
         }
+
+        #[test]
+        fn background() {
+            let tmp = NamedTempFile::new().unwrap();
+            std::fs::write(
+                tmp.path(),
+                "[glyphs.background]\nsymbol = \"·\"\nstyle = \"dim\"",
+            )
+            .unwrap();
+            let cfg = Config::load(tmp.path(), false).unwrap();
+            assert_eq!(
+                cfg,
+                Config {
+                    glyphs: GlyphConfig {
+                        background: Some(Glyph {
+                            symbol: "·".parse::<Symbol>().unwrap(),
+                            style: Style::new().add_modifier(Modifier::DIM),
+                        }),
+                        ..GlyphConfig::default()
+                    },
+                    path: Some(tmp.path().to_owned()),
+                    ..Config::default()
+                }
+            );
+        }
+
+        #[test]
+        fn ghost_head() {
+            let tmp = NamedTempFile::new().unwrap();
+            std::fs::write(tmp.path(), "[glyphs.ghost-head]\nstyle = \"dim\"").unwrap();
+            let cfg = Config::load(tmp.path(), false).unwrap();
+            assert_eq!(
+                cfg,
+                Config {
+                    glyphs: GlyphConfig {
+                        ghost_head: Some(CellStyle {
+                            style: Style::new().add_modifier(Modifier::DIM),
+                        }),
+                        ..GlyphConfig::default()
+                    },
+                    path: Some(tmp.path().to_owned()),
+                    ..Config::default()
+                }
+            );
+        }
+
+        #[test]
+        fn eat_style() {
+            let tmp = NamedTempFile::new().unwrap();
+            std::fs::write(tmp.path(), "[glyphs.eat]\nstyle = \"italic blue\"").unwrap();
+            let cfg = Config::load(tmp.path(), false).unwrap();
+            assert_eq!(
+                cfg,
+                Config {
+                    glyphs: GlyphConfig {
+                        eat: CellStyle {
+                            style: Style::new()
+                                .fg(Color::Indexed(4))
+                                .add_modifier(Modifier::ITALIC),
+                        },
+                        ..GlyphConfig::default()
+                    },
+                    path: Some(tmp.path().to_owned()),
+                    ..Config::default()
+                }
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("thread", Some(RngMode::Thread))]
+    #[case("os", Some(RngMode::Os))]
+    #[case("seeded:42", Some(RngMode::Seeded(42)))]
+    #[case("seeded:18446744073709551615", Some(RngMode::Seeded(u64::MAX)))]
+    #[case("seeded:", None)]
+    #[case("seeded:-1", None)]
+    #[case("seeded", None)]
+    #[case("Thread", None)]
+    fn test_parse_rng_mode(#[case] s: &str, #[case] mode: Option<RngMode>) {
+        assert_eq!(s.parse::<RngMode>().ok(), mode);
+        if let Some(mode) = mode {
+            assert_eq!(mode.to_string(), s);
+        }
     }
 
     #[rstest]
@@ -752,6 +1925,290 @@ mod tests {
         assert_eq!(Symbol::try_from(ch).is_ok(), ok);
     }
 
+    #[test]
+    fn controls() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            "[controls]\nrestart = \"f5\"\nconfirm-restart = false\nscreenshot = \"ctrl-s\"\nrewinds = 2\n",
+        )
+        .unwrap();
+        let cfg = Config::load(tmp.path(), false).unwrap();
+        assert_eq!(
+            cfg.controls,
+            ControlsConfig {
+                restart: KeyBinding::new(
+                    crossterm::event::KeyCode::F(5),
+                    crossterm::event::KeyModifiers::NONE
+                ),
+                confirm_restart: false,
+                slow_motion: consts::SLOW_MOTION_KEY,
+                screenshot: KeyBinding::new(
+                    crossterm::event::KeyCode::Char('s'),
+                    crossterm::event::KeyModifiers::CONTROL
+                ),
+                diagnostics: consts::DIAGNOSTICS_KEY,
+                rewinds: 2,
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("1:2", Some((1, 2)))]
+    #[case(" 10 : 21 ", Some((10, 21)))]
+    #[case("1:1", Some((1, 1)))]
+    #[case("1:5", None)]
+    #[case("3:1", None)]
+    #[case("0:2", None)]
+    #[case("1/2", None)]
+    #[case("0.5", None)]
+    fn cell_aspect(#[case] value: &str, #[case] aspect: Option<(u16, u16)>) {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), format!("cell-aspect = {value:?}\n")).unwrap();
+        let cfg = Config::load(tmp.path(), false);
+        match aspect {
+            Some((w, h)) => assert_eq!(cfg.unwrap().cell_aspect, CellAspect::new(w, h)),
+            None => assert!(cfg.is_err()),
+        }
+    }
+
+    #[test]
+    fn true_color() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), "true-color = \"never\"\n").unwrap();
+        let cfg = Config::load(tmp.path(), false).unwrap();
+        assert_eq!(cfg.true_color, Support::Never);
+        std::fs::write(tmp.path(), "true-color = true\n").unwrap();
+        assert!(Config::load(tmp.path(), false).is_err());
+    }
+
+    #[test]
+    fn theme() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            "[theme]\nselection = [\"reverse\", \"brackets\"]\n\n[theme.backdrop]\nsymbol = \"░\"\n",
+        )
+        .unwrap();
+        let cfg = Config::load(tmp.path(), false).unwrap();
+        assert_eq!(
+            cfg.theme.selection,
+            [SelectionIndicator::Reverse, SelectionIndicator::Brackets]
+        );
+        assert_eq!(
+            cfg.theme.backdrop,
+            Some(Glyph {
+                symbol: "░".parse::<Symbol>().unwrap(),
+                style: Style::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn kiosk() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), "[kiosk]\npassphrase = [\"ctrl-x\", \"q\"]\n").unwrap();
+        let cfg = Config::load(tmp.path(), false).unwrap();
+        assert_eq!(
+            cfg.kiosk.passphrase,
+            [
+                KeyBinding::new(
+                    crossterm::event::KeyCode::Char('x'),
+                    crossterm::event::KeyModifiers::CONTROL
+                ),
+                KeyBinding::new(
+                    crossterm::event::KeyCode::Char('q'),
+                    crossterm::event::KeyModifiers::NONE
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn game() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            "[game]\npause-on-focus-loss = false\npause-on-resize = true\npause-on-stall = false\ngame-over-hold-ms = 250\ncollision-warning = true\ncamera = \"centered\"\nask-name = true\nidle-timeout-mins = 5\nfruit-arrow = true\nfill-meter = true\nborder-title = false\nhide-score = true\nlive-high-score = true\nkey-hints = false\nmouse-steering = true\nrng = \"seeded:42\"\ncrash-tips = true\nmax-fruits = 25\n",
+        )
+        .unwrap();
+        let cfg = Config::load(tmp.path(), false).unwrap();
+        assert_eq!(
+            cfg.game,
+            GameConfig {
+                pause_on_focus_loss: false,
+                pause_on_resize: true,
+                pause_on_stall: false,
+                game_over_hold_ms: 250,
+                collision_warning: true,
+                camera: CameraMode::Centered,
+                ask_name: true,
+                idle_timeout_mins: 5,
+                fruit_arrow: true,
+                fill_meter: true,
+                border_title: false,
+                hide_score: true,
+                live_high_score: true,
+                key_hints: false,
+                mouse_steering: true,
+                rng: RngMode::Seeded(42),
+                crash_tips: true,
+                max_fruits: 25,
+            }
+        );
+        assert_eq!(cfg.game.idle_timeout(), Duration::from_secs(300));
+        assert_eq!(cfg.game.game_over_hold(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn gamepad_buttons() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            "[gamepad.buttons]\nsouth = \"space\"\nnorth = \"r\"\n",
+        )
+        .unwrap();
+        let cfg = Config::load(tmp.path(), false).unwrap();
+        assert!(cfg.gamepad.enabled);
+        let buttons = cfg.gamepad.buttons;
+        assert_eq!(buttons.get(&GamepadButton::South), Some(&Command::Space));
+        assert_eq!(buttons.get(&GamepadButton::North), Some(&Command::R));
+        assert_eq!(buttons.get(&GamepadButton::DpadUp), Some(&Command::Up));
+    }
+
+    #[test]
+    fn skin() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            concat!(
+                "[glyphs]\n",
+                "skin = \"retro\"\n",
+                "obstacle = { symbol = \"%\" }\n",
+                "\n",
+                "[skins.retro]\n",
+                "fruit = { symbol = \"@\" }\n",
+                "obstacle = { symbol = \"#\" }\n",
+                "\n",
+                "[skins.other]\n",
+                "fruit = { symbol = \"*\" }\n",
+            ),
+        )
+        .unwrap();
+        let cfg = Config::load(tmp.path(), false).unwrap();
+        assert_eq!(cfg.glyphs.skin.as_deref(), Some("retro"));
+        assert_eq!(cfg.glyphs.fruit.symbol.as_ref(), "@");
+        // Glyphs set in `[glyphs]` take precedence over the skin
+        assert_eq!(cfg.glyphs.obstacle.symbol.as_ref(), "%");
+        // Glyphs not set in either fall back to the defaults
+        assert_eq!(cfg.glyphs.star, GlyphConfig::default().star);
+        assert_eq!(cfg.skins.len(), 2);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(50)]
+    fn invalid_max_fruits(#[case] max: usize) {
+        let e = Config::parse(&format!("[game]\nmax-fruits = {max}\n")).unwrap_err();
+        assert!(matches!(e, ConfigError::MaxFruits(n) if n == max));
+        assert_eq!(
+            e.to_string(),
+            format!("game.max-fruits must be between 1 and 49, but it is set to {max}")
+        );
+    }
+
+    #[test]
+    fn unknown_skin() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            "[glyphs]\nskin = \"retro\"\n\n[skins.modern]\nfruit = { symbol = \"@\" }\n",
+        )
+        .unwrap();
+        let e = Config::load(tmp.path(), false).unwrap_err();
+        assert!(matches!(e, ConfigError::UnknownSkin(ref name) if name == "retro"));
+        assert_eq!(
+            e.to_string(),
+            "glyphs.skin is set to \"retro\", but there is no [skins.retro] table"
+        );
+    }
+
+    #[test]
+    fn unknown_key_in_skin() {
+        let cfg = Config::parse(concat!(
+            "[glyphs]\n",
+            "skin = \"retro\"\n",
+            "\n",
+            "[skins.retro]\n",
+            "fruit = { symbol = \"@\" }\n",
+            "friut = { symbol = \"*\" }\n",
+            "\n",
+            "[skins.other]\n",
+            "  skin = \"retro\"\n",
+        ))
+        .unwrap();
+        assert_eq!(cfg.glyphs.fruit.symbol.as_ref(), "@");
+        assert_eq!(cfg.glyphs.skin.as_deref(), Some("retro"));
+        assert_eq!(
+            cfg.unknown_skin_keys
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "ignoring unknown key \"friut\" in [skins.retro] at line 6, column 1 of the configuration file",
+                "ignoring unknown key \"skin\" in [skins.other] at line 9, column 3 of the configuration file",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_glyph_in_skin() {
+        let e = Config::parse("[skins.retro]\nfruit = { symbol = \"@@\" }\n").unwrap_err();
+        assert!(matches!(e, ConfigError::Parse(_)));
+    }
+
+    #[test]
+    fn skin_keys() {
+        // Every key of `[glyphs]` other than `skin` can be set in a skin
+        let ConfigError::Parse(e) = Config::parse("[glyphs]\nfriut = 1\n").unwrap_err() else {
+            panic!("unknown key in [glyphs] was not a parse error");
+        };
+        let expected = e
+            .message()
+            .split_once("expected one of ")
+            .unwrap()
+            .1
+            .split(", ")
+            .map(|key| key.trim_matches('`'))
+            .filter(|&key| key != "skin")
+            .collect::<Vec<_>>();
+        assert_eq!(GlyphConfig::SKIN_KEYS, expected);
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn ignore_load_errors(#[case] ignore: bool) {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let cfg =
+            Globals::in_tempdir(&tmpdir, &format!("[files]\nignore-errors = {ignore}\n")).config;
+        let scores_dir = tmpdir.path().join("highscores");
+        std::fs::write(tmpdir.path().join("options.json"), "{not json").unwrap();
+        std::fs::create_dir(&scores_dir).unwrap();
+        std::fs::write(scores_dir.join(HighScores::ARCADE_FILE_NAME), "42").unwrap();
+        if ignore {
+            let options = cfg.load_options().unwrap();
+            assert_eq!(options.value, SavedOptions::from(cfg.options));
+            assert!(options.ignored.is_some_and(|e| e.is_corrupt()));
+            let scores = cfg.load_high_scores().unwrap();
+            assert_eq!(scores.value, HighScores::default());
+            assert!(scores.ignored.is_some_and(|e| e.is_corrupt()));
+        } else {
+            assert!(cfg.load_options().is_err());
+            assert!(cfg.load_high_scores().is_err());
+        }
+    }
+
     #[test]
     fn test_default_glyph_config() {
         GlyphConfig::default();
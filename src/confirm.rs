@@ -0,0 +1,178 @@
+use crate::command::Command;
+use crate::consts;
+use crate::i18n::{tr, Msg};
+use crate::popup::PopupFrame;
+use crate::theme;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::Widget,
+};
+use std::borrow::Cow;
+
+/// A widget for asking the user to confirm a destructive action in a pop-up
+/// window
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Confirm {
+    /// The question to display, wrapped to fit in the pop-up
+    lines: Vec<String>,
+
+    /// Is the "Yes" button currently selected?  The "No" button is selected
+    /// by default so that a stray Enter does not trigger the action.
+    yes_selected: bool,
+}
+
+impl Confirm {
+    /// The width of the text area
+    const TEXT_WIDTH: u16 = 36;
+
+    /// The width of the widget
+    const WIDTH: u16 = Self::TEXT_WIDTH + 4;
+
+    /// Create a new `Confirm` pop-up displaying the given question
+    pub(crate) fn new(question: &str) -> Confirm {
+        let opts = textwrap::Options::new(usize::from(Confirm::TEXT_WIDTH)).break_words(true);
+        let lines = textwrap::wrap(question, opts)
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect();
+        Confirm {
+            lines,
+            yes_selected: false,
+        }
+    }
+
+    /// Process an input command.  Returns `Some` if the user answers the
+    /// question or quits the application.
+    pub(crate) fn handle_command(&mut self, cmd: Command) -> Option<ConfirmOutcome> {
+        match cmd {
+            Command::Y => return Some(ConfirmOutcome::Yes),
+            Command::N | Command::Esc => return Some(ConfirmOutcome::No),
+            Command::Quit => return Some(ConfirmOutcome::Quit),
+            Command::Enter => {
+                if self.yes_selected {
+                    return Some(ConfirmOutcome::Yes);
+                } else {
+                    return Some(ConfirmOutcome::No);
+                }
+            }
+            Command::Left | Command::Home => self.yes_selected = true,
+            Command::Right | Command::End => self.yes_selected = false,
+            Command::Next | Command::Prev => self.yes_selected = !self.yes_selected,
+            _ => (),
+        }
+        None
+    }
+}
+
+/// An enum of the ways that the user can finish working with a `Confirm`
+/// pop-up
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ConfirmOutcome {
+    /// The user confirmed the action
+    Yes,
+
+    /// The user declined or cancelled the action
+    No,
+
+    /// The user quit the application
+    Quit,
+}
+
+impl Widget for &Confirm {
+    /// Render a `Confirm` in the given area of `buf`.
+    ///
+    /// As with [`Warning`][crate::warning::Warning], `area` should be the area
+    /// of the entire display within which the pop-up will be rendered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [text_area, buttons_area] = PopupFrame {
+            title: tr(Msg::ConfirmTitle),
+            width: Confirm::WIDTH,
+            text_height: u16::try_from(self.lines.len()).unwrap_or(u16::MAX),
+            footer: true,
+        }
+        .render(area, buf);
+        Text::from_iter(self.lines.iter().map(String::as_str)).render(text_area, buf);
+        let (yes_style, no_style) = if self.yes_selected {
+            (theme::selection_style(), Style::new())
+        } else {
+            (Style::new(), theme::selection_style())
+        };
+        let [yes_before, yes_after] = theme::button_markers(self.yes_selected);
+        let [no_before, no_after] = theme::button_markers(!self.yes_selected);
+        Line::from_iter([
+            Span::raw(yes_before),
+            Span::styled(format!("[{} (", tr(Msg::Yes)), yes_style),
+            Span::styled("y", consts::KEY_STYLE.patch(yes_style)),
+            Span::styled(")]", yes_style),
+            Span::raw(yes_after),
+            Span::raw("   "),
+            Span::raw(no_before),
+            Span::styled(format!("[{} (", tr(Msg::No)), no_style),
+            Span::styled("n", consts::KEY_STYLE.patch(no_style)),
+            Span::styled(")]", no_style),
+            Span::raw(no_after),
+        ])
+        .centered()
+        .render(buttons_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let confirm = Confirm::new("Delete all high scores?");
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        confirm.render(area, &mut buffer);
+        let mut expected = Buffer::with_lines([
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "                    ┌────────────── CONFIRM ───────────────┐                    ",
+            "                    │ Delete all high scores?              │                    ",
+            "                    │                                      │                    ",
+            "                    │         [Yes (y)]   [No (n)]         │                    ",
+            "                    └──────────────────────────────────────┘                    ",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+        ]);
+        expected.set_style(Rect::new(36, 13, 1, 1), consts::KEY_STYLE);
+        expected.set_style(Rect::new(42, 13, 8, 1), theme::selection_style());
+        expected.set_style(Rect::new(47, 13, 1, 1), consts::KEY_STYLE);
+        pretty_assertions::assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn enter_defaults_to_no() {
+        let mut confirm = Confirm::new("Really?");
+        assert_eq!(
+            confirm.handle_command(Command::Enter),
+            Some(ConfirmOutcome::No)
+        );
+        assert_eq!(confirm.handle_command(Command::Left), None);
+        assert_eq!(
+            confirm.handle_command(Command::Enter),
+            Some(ConfirmOutcome::Yes)
+        );
+    }
+}
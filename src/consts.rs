@@ -1,4 +1,6 @@
 //! Assorted constants & hard-coded configuration
+use crate::command::KeyBinding;
+use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
     layout::Size,
     style::{Color, Modifier, Style},
@@ -8,6 +10,31 @@ use std::time::Duration;
 /// Time between movements of the snake
 pub(crate) const TICK_PERIOD: Duration = Duration::from_millis(200);
 
+/// Time between movements of the snake while slow motion is active
+pub(crate) const SLOW_MOTION_TICK_PERIOD: Duration = Duration::from_millis(400);
+
+/// Number of ticks for which the death animation (flashing & shaking the
+/// level) plays before the game-over message is shown
+pub(crate) const DEATH_ANIMATION_TICKS: u8 = 4;
+
+/// Number of ticks to count down before play resumes after a pop-up that
+/// interrupted the game is dismissed
+pub(crate) const RESUME_COUNTDOWN_TICKS: u8 = 15;
+
+/// Number of ticks for which the victory animation (cycling the colors of the
+/// snake) plays after the snake fills the level
+pub(crate) const VICTORY_ANIMATION_TICKS: u8 = 12;
+
+/// The colors through which the snake cycles during the victory animation
+pub(crate) const VICTORY_COLORS: [Color; 6] = [
+    Color::LightRed,
+    Color::LightYellow,
+    Color::LightGreen,
+    Color::LightCyan,
+    Color::LightBlue,
+    Color::LightMagenta,
+];
+
 /// Draw everything inside a rectangle of this size in the center of the
 /// terminal window.
 ///
@@ -17,8 +44,155 @@ pub(crate) const DISPLAY_SIZE: Size = Size {
     height: 24,
 };
 
-/// The maximum number of fruits that can be present on a level at one time
-pub(crate) const MAX_FRUITS: usize = 10;
+/// Default key for restarting a game in progress
+pub(crate) const RESTART_KEY: KeyBinding =
+    KeyBinding::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+
+/// Default key for toggling slow motion during a game
+pub(crate) const SLOW_MOTION_KEY: KeyBinding =
+    KeyBinding::new(KeyCode::Char('z'), KeyModifiers::NONE);
+
+/// Default key for saving a screenshot of the screen
+pub(crate) const SCREENSHOT_KEY: KeyBinding = KeyBinding::new(KeyCode::F(12), KeyModifiers::NONE);
+
+/// Default key for toggling the frame timing diagnostics overlay
+pub(crate) const DIAGNOSTICS_KEY: KeyBinding = KeyBinding::new(KeyCode::F(3), KeyModifiers::NONE);
+
+/// How far back the frame timing diagnostics overlay shows measurements for
+pub(crate) const DIAGNOSTICS_WINDOW: Duration = Duration::from_secs(5);
+
+/// How many high scores are kept for each combination of options
+pub(crate) const HIGH_SCORES_PER_OPTIONS: usize = 10;
+
+/// The minimum time between saves of a high score that is still being set,
+/// when the `live-high-score` setting is enabled
+pub(crate) const LIVE_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The longest gap between two presses of the same key for the second to be
+/// treated as an autorepeat of a key held down, when ignoring a steering key
+/// that is still held down after the game has been paused
+pub(crate) const KEY_REPEAT_WINDOW: Duration = Duration::from_millis(150);
+
+/// The minimum time between heartbeats written to the session file
+pub(crate) const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long drawing a frame must take before the terminal is considered to
+/// have stalled
+pub(crate) const STALL_THRESHOLD: Duration = Duration::from_secs(3);
+
+/// How long to wait for a bot to reply with its next move before it forfeits
+pub(crate) const BOT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The number of crashes within [`HINT_WINDOW`] after which a tip is shown,
+/// when the `crash-tips` setting is enabled
+pub(crate) const HINT_DEATHS: usize = 3;
+
+/// The span of time within which [`HINT_DEATHS`] crashes prompt a tip
+pub(crate) const HINT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Key for advancing the snake by one tick when in step mode
+pub(crate) const STEP_KEY: KeyBinding = KeyBinding::new(KeyCode::Char('.'), KeyModifiers::NONE);
+
+/// Minimum time between checks of the configuration file for changes
+pub(crate) const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The time within which the second key of a two-key chord (e.g., `g g`) must
+/// be pressed
+pub(crate) const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long each tip is shown on the main menu before moving on to the next
+pub(crate) const MENU_TIP_PERIOD: Duration = Duration::from_secs(6);
+
+/// Number of ticks' worth of game states kept for rewinding the game after the
+/// snake dies (about three seconds at normal speed)
+pub(crate) const REWIND_TICKS: usize = 15;
+
+/// Number of ticks in each player's turn in co-op relay mode (15 seconds at
+/// normal speed)
+pub(crate) const RELAY_TURN_TICKS: u16 = 75;
+
+/// Number of ticks before the end of a player's turn in co-op relay mode at
+/// which to start counting down to the hand-off
+pub(crate) const RELAY_WARNING_TICKS: u16 = 15;
+
+/// Number of ticks for which the snake flashes after control passes to the
+/// other player in co-op relay mode
+pub(crate) const HANDOFF_ANIMATION_TICKS: u8 = 6;
+
+/// The highest adaptive difficulty level; the lowest is its negation
+pub(crate) const ADAPTIVE_MAX_LEVEL: i8 = 4;
+
+/// Eating a fruit within this many ticks of the previous one counts towards
+/// raising the adaptive difficulty level
+pub(crate) const ADAPTIVE_FAST_FRUIT_TICKS: u32 = 25;
+
+/// Taking at least this many ticks to eat a fruit counts towards lowering the
+/// adaptive difficulty level
+pub(crate) const ADAPTIVE_SLOW_FRUIT_TICKS: u32 = 75;
+
+/// How many more fast fruits than slow ones (or vice versa) it takes to change
+/// the adaptive difficulty level
+pub(crate) const ADAPTIVE_MOMENTUM: i8 = 3;
+
+/// The fraction of the normal time between ticks by which each adaptive
+/// difficulty level speeds up (or, if negative, slows down) the snake
+pub(crate) const ADAPTIVE_SPEED_STEP: f64 = 0.08;
+
+/// At adaptive difficulty level 1, the number of fruits to be eaten between
+/// placements of new obstacles; each level above 1 places them one fruit
+/// sooner
+pub(crate) const ADAPTIVE_OBSTACLE_FRUITS: u32 = 4;
+
+/// New obstacles placed due to adaptive difficulty are placed more than this
+/// many cells away from the head of each snake
+pub(crate) const ADAPTIVE_OBSTACLE_CLEARANCE: u32 = 3;
+
+/// Number of ticks for which a notification message is shown under the level
+pub(crate) const TOAST_TICKS: u8 = 15;
+
+/// How long a notice (such as the result of saving a screenshot) is shown at
+/// the bottom of the display
+pub(crate) const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+/// Number of ticks for which a newly-spawned fruit blinks
+pub(crate) const SPAWN_HIGHLIGHT_TICKS: u8 = 6;
+
+/// The default maximum number of fruits that can be present on a level at one
+/// time, i.e., the default value of `game.max-fruits`
+pub(crate) const DEFAULT_MAX_FRUITS: usize = 10;
+
+/// The smallest size that can be given for a custom level size
+pub(crate) const MIN_CUSTOM_LEVEL_SIZE: Size = Size {
+    width: 10,
+    height: 5,
+};
+
+/// The largest value allowed for `game.max-fruits`: the number of cells not
+/// occupied by the snake at the start of a game on the smallest possible
+/// level
+pub(crate) const FRUIT_CAPACITY: usize =
+    (MIN_CUSTOM_LEVEL_SIZE.width as usize) * (MIN_CUSTOM_LEVEL_SIZE.height as usize) - 1;
+
+/// The largest size that can be given for a custom level size
+pub(crate) const MAX_CUSTOM_LEVEL_SIZE: Size = Size {
+    width: 500,
+    height: 200,
+};
+
+/// The largest portion of a level that is shown on screen at once.  Levels
+/// larger than this are shown through a window that scrolls to follow the
+/// snake.
+pub(crate) const VIEWPORT_SIZE: Size = Size {
+    width: 76,
+    height: 19,
+};
+
+/// With the dead-zone camera, how close the snake's head can come to each
+/// edge of the view before the view scrolls
+pub(crate) const CAMERA_DEAD_ZONE_MARGIN: Size = Size {
+    width: 20,
+    height: 5,
+};
 
 /// Maximum snake length before any fruits have been eaten
 pub(crate) const INITIAL_SNAKE_LENGTH: usize = 3;
@@ -26,6 +200,29 @@ pub(crate) const INITIAL_SNAKE_LENGTH: usize = 3;
 /// How many cells the snake's length should increase by upon eating a fruit
 pub(crate) const SNAKE_GROWTH: usize = 3;
 
+/// When the "Combos" option is enabled, eating a fruit within this many ticks
+/// of the previous one increases the score multiplier
+pub(crate) const COMBO_WINDOW_TICKS: u32 = 15;
+
+/// When the "Power-ups" option is enabled and no power-up is present in the
+/// level, the probability of placing one each time a fruit is eaten
+pub(crate) const POWER_UP_PROBABILITY: f64 = 0.2;
+
+/// When the "Hydra" option is enabled and no hydra fruit is present in the
+/// level, the probability of placing one each time a fruit is eaten
+pub(crate) const HYDRA_FRUIT_PROBABILITY: f64 = 0.1;
+
+/// Number of ticks for which the speed-up power-up lasts (about ten seconds
+/// at normal speed)
+pub(crate) const SPEED_UP_TICKS: u32 = 75;
+
+/// Number of ticks for which the slow-down power-up lasts (about ten seconds
+/// at normal speed)
+pub(crate) const SLOW_DOWN_TICKS: u32 = 33;
+
+/// Number of ticks for which the invincibility star power-up lasts
+pub(crate) const STAR_TICKS: u32 = 40;
+
 /// Glyph for the snake's head when it is moving north/up
 pub(crate) const SNAKE_HEAD_NORTH_SYMBOL: char = 'v';
 
@@ -41,12 +238,51 @@ pub(crate) const SNAKE_HEAD_WEST_SYMBOL: char = '>';
 /// Glyph for the parts of the snake's body
 pub(crate) const SNAKE_BODY_SYMBOL: char = '⚬';
 
+/// Glyph for the tail of the snake on ticks in which it grows
+pub(crate) const SNAKE_GROW_SYMBOL: char = '·';
+
 /// Glyph for the fruit
 pub(crate) const FRUIT_SYMBOL: char = '●';
 
 /// Glyph for obstacles
 pub(crate) const OBSTACLE_SYMBOL: char = '█';
 
+/// Glyph for the power-up that lets the snake plow through an obstacle
+pub(crate) const PLOW_SYMBOL: char = '♦';
+
+/// Glyph for the power-up that temporarily speeds up the snake
+pub(crate) const SPEED_UP_SYMBOL: char = '»';
+
+/// Glyph for the power-up that temporarily slows down the snake
+pub(crate) const SLOW_DOWN_SYMBOL: char = '«';
+
+/// Glyph for the power-up that makes the snake temporarily invincible
+pub(crate) const STAR_SYMBOL: char = '★';
+
+/// Glyph for the fruit that spawns an extra snake in hydra mode
+pub(crate) const HYDRA_FRUIT_SYMBOL: char = '♣';
+
+/// Glyph for mud terrain tiles
+pub(crate) const MUD_SYMBOL: char = '▒';
+
+/// Glyph for ice terrain tiles
+pub(crate) const ICE_SYMBOL: char = '░';
+
+/// Glyph for the cells of a custom level that the snake must not touch
+pub(crate) const FORBIDDEN_SYMBOL: char = '╳';
+
+/// Glyph for one-way gates that can only be entered moving north/up
+pub(crate) const GATE_NORTH_SYMBOL: char = '↑';
+
+/// Glyph for one-way gates that can only be entered moving south/down
+pub(crate) const GATE_SOUTH_SYMBOL: char = '↓';
+
+/// Glyph for one-way gates that can only be entered moving east/right
+pub(crate) const GATE_EAST_SYMBOL: char = '→';
+
+/// Glyph for one-way gates that can only be entered moving west/left
+pub(crate) const GATE_WEST_SYMBOL: char = '←';
+
 /// Glyph for the snake's head when it's collided with an obstacle or wall
 pub(crate) const COLLISION_SYMBOL: char = '×';
 
@@ -56,30 +292,161 @@ pub(crate) const SNAKE_STYLE: Style = Style::new().fg(Color::Green).add_modifier
 /// Style for the fruit
 pub(crate) const FRUIT_STYLE: Style = Style::new().fg(Color::LightRed);
 
+/// The 24-bit color at the left end of the gradient used for the logo on
+/// terminals that support true color
+pub(crate) const LOGO_GRADIENT_START: [u8; 3] = [255, 85, 85];
+
+/// The 24-bit color at the right end of the gradient used for the logo on
+/// terminals that support true color
+pub(crate) const LOGO_GRADIENT_END: [u8; 3] = [0, 205, 0];
+
 /// Style for obstacles
 pub(crate) const OBSTACLE_STYLE: Style = Style::new().fg(Color::Gray);
 
+/// Style for [`MUD_SYMBOL`]
+pub(crate) const MUD_STYLE: Style = Style::new().fg(Color::Yellow);
+
+/// Style for [`ICE_SYMBOL`]
+pub(crate) const ICE_STYLE: Style = Style::new().fg(Color::Cyan);
+
+/// Style for [`FORBIDDEN_SYMBOL`]
+pub(crate) const FORBIDDEN_STYLE: Style = Style::new().fg(Color::Red);
+
+/// Style for one-way gates
+pub(crate) const GATE_STYLE: Style = Style::new().fg(Color::White).add_modifier(Modifier::BOLD);
+
+/// Style for [`PLOW_SYMBOL`]
+pub(crate) const PLOW_STYLE: Style = Style::new()
+    .fg(Color::LightCyan)
+    .add_modifier(Modifier::BOLD);
+
+/// Style for [`SPEED_UP_SYMBOL`]
+pub(crate) const SPEED_UP_STYLE: Style = Style::new()
+    .fg(Color::LightYellow)
+    .add_modifier(Modifier::BOLD);
+
+/// Style for [`SLOW_DOWN_SYMBOL`]
+pub(crate) const SLOW_DOWN_STYLE: Style = Style::new()
+    .fg(Color::LightBlue)
+    .add_modifier(Modifier::BOLD);
+
+/// Style for [`STAR_SYMBOL`].  This style is also applied to the snake on
+/// every other tick while it is invincible.
+pub(crate) const STAR_STYLE: Style = Style::new()
+    .fg(Color::LightMagenta)
+    .add_modifier(Modifier::BOLD);
+
+/// Style for [`HYDRA_FRUIT_SYMBOL`]
+pub(crate) const HYDRA_FRUIT_STYLE: Style = Style::new()
+    .fg(Color::LightGreen)
+    .add_modifier(Modifier::BOLD);
+
 /// Style for [`COLLISION_SYMBOL`]
 pub(crate) const COLLISION_STYLE: Style = Style::new()
     .fg(Color::LightRed)
     .add_modifier(Modifier::REVERSED);
 
+/// Style briefly applied to the snake's head when it eats a fruit
+pub(crate) const EAT_STYLE: Style = Style::new()
+    .fg(Color::LightYellow)
+    .add_modifier(Modifier::BOLD.union(Modifier::REVERSED));
+
+/// Style applied to a newly-spawned fruit on every other tick for
+/// [`SPAWN_HIGHLIGHT_TICKS`] ticks
+pub(crate) const SPAWN_STYLE: Style = Style::new()
+    .fg(Color::LightGreen)
+    .add_modifier(Modifier::BOLD.union(Modifier::REVERSED));
+
+/// Style applied to the snake's head (when collision warnings are enabled)
+/// when moving forwards would kill the snake
+pub(crate) const DANGER_STYLE: Style = Style::new().fg(Color::LightRed);
+
+/// Style applied to the snake's head (when collision warnings are enabled)
+/// when it is about to move into the cell being vacated by the end of its tail
+pub(crate) const CLOSE_CALL_STYLE: Style = Style::new().fg(Color::LightYellow);
+
+/// Style for the pace indicator in the score bar when racing a high score and
+/// scoring at least as fast as it
+pub(crate) const PACE_AHEAD_STYLE: Style = Style::new().fg(Color::LightGreen);
+
+/// Style for the pace indicator in the score bar when racing a high score and
+/// scoring more slowly than it
+pub(crate) const PACE_BEHIND_STYLE: Style = Style::new().fg(Color::LightRed);
+
+/// Style for the adaptive difficulty level in the score bar
+pub(crate) const ADAPTIVE_STYLE: Style = Style::new().add_modifier(Modifier::DIM);
+
 /// Style for key codes shown in the interface
 pub(crate) const KEY_STYLE: Style = Style::new().fg(Color::Yellow);
 
 /// Style for the score bar at the top of the game screen
 pub(crate) const SCORE_BAR_STYLE: Style = Style::new().add_modifier(Modifier::REVERSED);
 
-/// Style for the currently-selected menu item
-pub(crate) const MENU_SELECTION_STYLE: Style = Style::new().add_modifier(Modifier::UNDERLINED);
+/// Style for notices shown at the bottom of the display
+pub(crate) const NOTICE_STYLE: Style = Style::new().add_modifier(Modifier::REVERSED);
+
+/// Default style applied on top of [`SCORE_BAR_STYLE`] once the score has
+/// overtaken the high score
+pub(crate) const RECORD_STYLE: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+
+/// Style for the labels of the facts listed on the about screen
+pub(crate) const ABOUT_LABEL_STYLE: Style = Style::new().add_modifier(Modifier::BOLD);
+
+/// Style for the tips shown on the main menu
+pub(crate) const TIP_STYLE: Style = Style::new().add_modifier(Modifier::DIM);
+
+/// For how many ticks at the start of a game to show the key hints under the
+/// level
+pub(crate) const KEY_HINT_TICKS: u32 = 25;
+
+/// For how many of the last of [`KEY_HINT_TICKS`] ticks to draw the key hints
+/// dimmed
+pub(crate) const KEY_HINT_FADE_TICKS: u32 = 5;
+
+/// Text shown in place of the score & high score while they are hidden
+pub(crate) const HIDDEN_SCORE: &str = "???";
+
+/// Style for the text & empty space in a text field
+pub(crate) const TEXT_INPUT_STYLE: Style = Style::new().add_modifier(Modifier::UNDERLINED);
+
+/// Style for the cursor in a text field
+pub(crate) const TEXT_CURSOR_STYLE: Style = Style::new().add_modifier(Modifier::REVERSED);
+
+/// The maximum display width of a player name recorded with a high score
+pub(crate) const PLAYER_NAME_MAX_WIDTH: u16 = 8;
+
+/// The maximum display width of the name of a favorite board
+pub(crate) const FAVORITE_NAME_MAX_WIDTH: u16 = 20;
 
 /// Probability of placing an obstacle in a given cell
 pub(crate) const OBSTACLE_PROBABILITY: f64 = 0.03;
 
-/// When creating a level with random obstacles, remove any obstacles behind
-/// the snake's head this many cells backwards.
+/// Probability of centering a patch of mud or ice on a given cell when
+/// generating terrain
+pub(crate) const TERRAIN_PATCH_PROBABILITY: f64 = 0.01;
+
+/// Probability of placing a one-way gate in a given cell when generating
+/// terrain
+pub(crate) const GATE_PROBABILITY: f64 = 0.003;
+
+/// The minimum width of the corridors in a maze level
+pub(crate) const MAZE_CORRIDOR_WIDTH: u16 = 2;
+
+/// When creating a level with random obstacles or terrain, remove any
+/// obstacles & terrain behind the snake's head this many cells backwards.
 pub(crate) const BACKWARDS_CLEARANCE: usize = 3;
 
-/// When creating a level with random obstacles, remove any obstacles in front
-/// of the snake's head this many cells forwards.
+/// When creating a level with random obstacles or terrain, remove any
+/// obstacles & terrain in front of the snake's head this many cells forwards.
 pub(crate) const FORWARDS_CLEARANCE: usize = 7;
+
+/// Levels with more than this many cells have their obstacles or maze walls
+/// generated in a background thread while a "Generating level" message is
+/// shown
+pub(crate) const BACKGROUND_GENERATION_CELLS: u32 = 20_000;
+
+/// The frames of the spinner shown while a level is being generated
+pub(crate) const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// Time between frames of the level generation spinner
+pub(crate) const SPINNER_PERIOD: Duration = Duration::from_millis(100);
@@ -0,0 +1,224 @@
+//! Measuring how long each pass through the application's main loop takes,
+//! for diagnosing performance problems on slow terminals.
+//!
+//! The measurements for the last [`DIAGNOSTICS_WINDOW`][consts::DIAGNOSTICS_WINDOW]
+//! are shown as sparklines in an overlay that can be toggled with
+//! <kbd>F3</kbd> (configurable).
+use crate::consts;
+use crate::i18n::{tr, Msg};
+use crate::util::get_display_area;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::Style,
+    text::Span,
+    widgets::{
+        block::{Block, Padding},
+        Clear, Sparkline, Widget,
+    },
+};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The total width of the diagnostics overlay, including borders
+const OVERLAY_WIDTH: u16 = 40;
+
+/// The width of the labels in the diagnostics overlay
+const LABEL_WIDTH: u16 = 6;
+
+/// The width of the maximum values shown in the diagnostics overlay
+const VALUE_WIDTH: u16 = 9;
+
+/// Timing measurements for the main loop, along with whether to display them
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Diagnostics {
+    /// Is the overlay currently shown?
+    enabled: bool,
+
+    /// How far the time between each pair of consecutive game ticks differed
+    /// from the expected tick period
+    jitter: Samples,
+
+    /// How long each frame took to draw
+    draws: Samples,
+
+    /// How long each input event took to be handled after it was read
+    events: Samples,
+
+    /// When the most recent game tick happened, if the game has been ticking
+    /// uninterrupted since then
+    last_tick: Option<Instant>,
+}
+
+impl Diagnostics {
+    /// Show the overlay if it is hidden, or hide it if it is shown
+    pub(crate) fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Is the overlay currently shown?
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Record that drawing a frame finished at `now` after taking `elapsed`
+    pub(crate) fn record_draw(&mut self, now: Instant, elapsed: Duration) {
+        self.draws.push(now, elapsed);
+    }
+
+    /// Record that handling an input event finished at `now`, `elapsed` after
+    /// the event was read
+    pub(crate) fn record_event(&mut self, now: Instant, elapsed: Duration) {
+        self.events.push(now, elapsed);
+    }
+
+    /// Return the number of input events currently measured
+    #[cfg(test)]
+    pub(crate) fn event_samples(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Record that a game tick happened at `now`, at which point the game was
+    /// meant to be ticking once every `period`
+    pub(crate) fn record_tick(&mut self, now: Instant, period: Duration) {
+        if let Some(last) = self.last_tick {
+            let interval = now.saturating_duration_since(last);
+            self.jitter.push(now, interval.abs_diff(period));
+        }
+        self.last_tick = Some(now);
+    }
+
+    /// Record that the game has stopped ticking (e.g., because it was paused
+    /// or restarted), so that the time until the next tick is not counted as
+    /// jitter
+    pub(crate) fn interrupt_ticks(&mut self) {
+        self.last_tick = None;
+    }
+}
+
+impl Widget for &Diagnostics {
+    /*
+     * ┌──────────── DIAGNOSTICS ─────────────┐
+     * │ jitter ▁▁▂▁▁▁▃▁▁▁▁▁▁▁▁▁▂▁▁▁   1.2ms  │
+     * │ draw   ▂▂▂▃▂▂▂▂▂▂▂▂▂▂▂▂▂▂▂▂ 350.0µs  │
+     * │ input  ▁▁▁▁▁▁▁█▁▁            4.1ms  │
+     * └──────────────────────────────────────┘
+     */
+
+    /// Draw the overlay in the top-right corner of the display, just below
+    /// the score bar.  `area` should be the area of the entire frame.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        let block_area = Rect {
+            x: display.right().saturating_sub(OVERLAY_WIDTH),
+            y: display.y.saturating_add(1),
+            width: OVERLAY_WIDTH,
+            height: 5,
+        }
+        .intersection(display);
+        let block = Block::bordered()
+            .title(format!(" {} ", tr(Msg::DiagnosticsTitle)))
+            .title_alignment(Alignment::Center)
+            .padding(Padding::horizontal(1))
+            .style(Style::reset());
+        let inner = block.inner(block_area);
+        Clear.render(block_area, buf);
+        block.render(block_area, buf);
+        let rows = [
+            (tr(Msg::Jitter), &self.jitter),
+            (tr(Msg::Draw), &self.draws),
+            (tr(Msg::Input), &self.events),
+        ];
+        for ((label, samples), row) in rows.into_iter().zip(inner.rows()) {
+            let [label_area, graph_area, value_area] = Layout::horizontal([
+                Constraint::Length(LABEL_WIDTH),
+                Constraint::Fill(1),
+                Constraint::Length(VALUE_WIDTH),
+            ])
+            .spacing(1)
+            .areas(row);
+            Span::raw(label).render(label_area, buf);
+            let recent = samples.len().saturating_sub(usize::from(graph_area.width));
+            Sparkline::default()
+                .data(samples.micros().skip(recent))
+                .render(graph_area, buf);
+            if let Some(max) = samples.max() {
+                Span::raw(format!("{max:.1?}"))
+                    .into_right_aligned_line()
+                    .render(value_area, buf);
+            }
+        }
+    }
+}
+
+/// A series of timing measurements, each paired with the time at which it was
+/// taken, covering the last [`DIAGNOSTICS_WINDOW`][consts::DIAGNOSTICS_WINDOW]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Samples(VecDeque<(Instant, Duration)>);
+
+impl Samples {
+    /// Add a measurement taken at `now`, discarding any measurements that are
+    /// now too old
+    fn push(&mut self, now: Instant, value: Duration) {
+        while self.0.front().is_some_and(|&(when, _)| {
+            now.saturating_duration_since(when) > consts::DIAGNOSTICS_WINDOW
+        }) {
+            let _ = self.0.pop_front();
+        }
+        self.0.push_back((now, value));
+    }
+
+    /// Return the number of measurements
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return the largest measurement, if any
+    fn max(&self) -> Option<Duration> {
+        self.0.iter().map(|&(_, value)| value).max()
+    }
+
+    /// Return an iterator over the measurements in microseconds, from oldest
+    /// to newest
+    fn micros(&self) -> impl Iterator<Item = u64> + '_ {
+        self.0
+            .iter()
+            .map(|&(_, value)| u64::try_from(value.as_micros()).unwrap_or(u64::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_expire() {
+        let start = Instant::now();
+        let mut samples = Samples::default();
+        samples.push(start, Duration::from_millis(7));
+        samples.push(start + Duration::from_secs(1), Duration::from_millis(2));
+        assert_eq!(samples.max(), Some(Duration::from_millis(7)));
+        samples.push(
+            start + consts::DIAGNOSTICS_WINDOW + Duration::from_millis(500),
+            Duration::from_millis(3),
+        );
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples.max(), Some(Duration::from_millis(3)));
+        assert_eq!(samples.micros().collect::<Vec<_>>(), [2000, 3000]);
+    }
+
+    #[test]
+    fn tick_jitter() {
+        let start = Instant::now();
+        let period = Duration::from_millis(200);
+        let mut diag = Diagnostics::default();
+        diag.record_tick(start, period);
+        assert_eq!(diag.jitter.len(), 0);
+        diag.record_tick(start + Duration::from_millis(210), period);
+        diag.record_tick(start + Duration::from_millis(405), period);
+        assert_eq!(diag.jitter.micros().collect::<Vec<_>>(), [10000, 5000]);
+        diag.interrupt_ticks();
+        diag.record_tick(start + Duration::from_secs(3), period);
+        assert_eq!(diag.jitter.len(), 2);
+    }
+}
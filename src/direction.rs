@@ -1,8 +1,10 @@
 use crate::util::Bounds;
 use ratatui::layout::Position;
+use serde::Serialize;
 
 /// An enum of the directions in which the snake can move
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub(super) enum Direction {
     North,
     East,
@@ -34,6 +36,11 @@ impl Direction {
         Some(Position { x, y })
     }
 
+    /// Is this direction north or south?
+    pub(super) fn is_vertical(self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+
     /// Return the direction going in the opposite way from this direction
     pub(super) fn reverse(self) -> Direction {
         match self {
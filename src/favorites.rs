@@ -0,0 +1,137 @@
+//! Boards that the player has bookmarked under a name so that they can be
+//! replayed later.
+//!
+//! A favorite board records the seed for the random-number generator along
+//! with the gameplay options, which together determine the level layout and
+//! the sequence of fruit placements.
+use crate::migrations::{self, SaveFile};
+use crate::options::Options;
+use crate::util::{data_dir, LoadError, SaveError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The player's collection of favorite boards, in the order they were added
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct Favorites {
+    boards: Vec<Favorite>,
+}
+
+impl Favorites {
+    /// Return the default filepath used for storing favorite boards
+    pub(crate) fn default_path() -> Option<PathBuf> {
+        data_dir().map(|p| p.join("favorites.json"))
+    }
+
+    /// Read favorite boards from a file on disk.  If the file does not exist,
+    /// an empty `Favorites` value is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file could not be read or if the file's contents
+    /// could not be deserialized.
+    pub(crate) fn load(path: &Path) -> Result<Favorites, LoadError> {
+        match fs_err::read(path) {
+            Ok(src) => migrations::from_json(SaveFile::Favorites, &src),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Favorites::default()),
+            Err(e) => Err(LoadError::read("favorites", e)),
+        }
+    }
+
+    /// Write the favorite boards to the file at `path`, replacing its
+    /// contents
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if creating the file's parent directories failed, if
+    /// serializing the favorites failed, or if writing the file failed.
+    pub(crate) fn save(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs_err::create_dir_all(parent).map_err(|e| SaveError::mkdir("favorites", e))?;
+        }
+        let mut src = migrations::to_json(SaveFile::Favorites, self)?;
+        src.push('\n');
+        fs_err::write(path, &src).map_err(|e| SaveError::write("favorites", e))?;
+        Ok(())
+    }
+
+    /// Return the favorite boards
+    pub(crate) fn boards(&self) -> &[Favorite] {
+        &self.boards
+    }
+
+    /// Add a favorite board.  If there is already a board with the same name,
+    /// it is replaced.
+    pub(crate) fn add(&mut self, fav: Favorite) {
+        if let Some(existing) = self.boards.iter_mut().find(|f| f.name == fav.name) {
+            *existing = fav;
+        } else {
+            self.boards.push(fav);
+        }
+    }
+
+    /// Remove & return the board at index `i`, if there is one
+    pub(crate) fn remove(&mut self, i: usize) -> Option<Favorite> {
+        (i < self.boards.len()).then(|| self.boards.remove(i))
+    }
+}
+
+/// A bookmarked board
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct Favorite {
+    /// The name that the player gave the board
+    pub(crate) name: String,
+
+    /// The seed for the random-number generator used to generate the level &
+    /// place fruits
+    pub(crate) seed: u64,
+
+    /// The gameplay options the board was played with
+    pub(crate) options: Options,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn favorite(name: &str, seed: u64) -> Favorite {
+        Favorite {
+            name: name.to_owned(),
+            seed,
+            options: Options::default(),
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("data").join("favorites.json");
+        assert_eq!(Favorites::load(&path).unwrap(), Favorites::default());
+        let mut favs = Favorites::default();
+        favs.add(favorite("Spiral", 42));
+        favs.add(Favorite {
+            options: Options {
+                wraparound: true,
+                ..Options::default()
+            },
+            ..favorite("Donut", 1337)
+        });
+        favs.save(&path).unwrap();
+        assert_eq!(Favorites::load(&path).unwrap(), favs);
+    }
+
+    #[test]
+    fn add_replaces_same_name() {
+        let mut favs = Favorites::default();
+        favs.add(favorite("Spiral", 42));
+        favs.add(favorite("Donut", 1337));
+        favs.add(favorite("Spiral", 23));
+        assert_eq!(
+            favs.boards(),
+            [favorite("Spiral", 23), favorite("Donut", 1337)]
+        );
+        assert_eq!(favs.remove(1), Some(favorite("Donut", 1337)));
+        assert_eq!(favs.remove(1), None);
+        assert_eq!(favs.boards(), [favorite("Spiral", 23)]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Entry points into the game engine for the Criterion benchmarks in
+//! `benches/`.  This module is only compiled when the `bench` feature is
+//! enabled.
+use super::levels::LevelMap;
+use super::Game;
+use crate::direction::Direction;
+use crate::options::{LevelSize, Options, StartPosition};
+use crate::util::{Bounds, Globals};
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect, Size},
+    widgets::Widget,
+};
+use std::collections::HashSet;
+
+/// A game in progress on a large level, using a seeded RNG
+#[derive(Clone, Debug)]
+pub struct Engine(Game<StdRng>);
+
+impl Engine {
+    /// Start a new game on a large level with wraparound, so that the snake
+    /// can advance indefinitely without dying
+    pub fn new(seed: u64) -> Engine {
+        let globals = Globals {
+            options: Options {
+                wraparound: true,
+                level_size: LevelSize::Large,
+                ..Options::default()
+            },
+            ..Globals::default()
+        };
+        Engine(Game::new_with_rng(globals, StdRng::seed_from_u64(seed)))
+    }
+
+    /// Start a game on a large level without wraparound in which the snake
+    /// winds back & forth across the rows, filling every cell except for the
+    /// last `free` cells along its path (`free` is raised to at least 1).
+    /// The snake's head faces the first free cell, and a single fruit is
+    /// placed in one of the free cells.
+    pub fn nearly_full(free: usize, seed: u64) -> Engine {
+        let globals = Globals {
+            options: Options {
+                level_size: LevelSize::Large,
+                ..Options::default()
+            },
+            ..Globals::default()
+        };
+        let mut game = Game::new_with_rng(globals, StdRng::seed_from_u64(seed));
+        let bounds = game.map.bounds();
+        let Size { width, height } = bounds.size();
+        let path = (0..height)
+            .flat_map(|y| {
+                let row = (0..width).map(move |x| Position::new(x, y));
+                if y % 2 == 0 {
+                    row.collect::<Vec<_>>()
+                } else {
+                    row.rev().collect()
+                }
+            })
+            .collect::<Vec<_>>();
+        let head_index = path.len().saturating_sub(free.max(1)).saturating_sub(1);
+        let head = path[head_index];
+        let direction = match path.get(head_index + 1) {
+            Some(next) if next.y > head.y => Direction::South,
+            Some(next) if next.x < head.x => Direction::West,
+            _ => Direction::East,
+        };
+        let mut snake = game.map.new_snake();
+        snake.head = head;
+        snake.set_body(path[..head_index].iter().copied());
+        snake.max_len = snake.body().len();
+        snake.direction = direction;
+        game.map = LevelMap::new(bounds);
+        game.snake = snake;
+        game.fruits = HashSet::new();
+        game.place_fruit();
+        Engine(game)
+    }
+
+    /// Advance the game by one tick
+    pub fn advance(&mut self) {
+        self.0.advance();
+    }
+
+    /// Draw the game on a fresh buffer the size of the display, as is done
+    /// for every frame, and return the buffer.  Only the cells of the level
+    /// that changed since the previous call are drawn again.
+    pub fn render(&self) -> Buffer {
+        let area = Rect::from((Position::ORIGIN, crate::consts::DISPLAY_SIZE));
+        let mut buf = Buffer::empty(area);
+        self.0.render(area, &mut buf);
+        buf
+    }
+
+    /// Like [`Engine::render()`], but draw every cell of the level again, as
+    /// is done for the first frame
+    pub fn render_all(&self) -> Buffer {
+        self.0.layer.borrow_mut().invalidate();
+        self.render()
+    }
+
+    /// Place a fruit at a random empty position in the level.  Returns
+    /// `false` if there were no empty positions.
+    pub fn place_fruit(&mut self) -> bool {
+        self.0.place_fruit().is_some()
+    }
+}
+
+/// Generate random obstacles for an open level of the given size, returning
+/// the number of obstacles
+pub fn generate_obstacles(width: u16, height: u16, seed: u64) -> usize {
+    let mut map = LevelMap::new(Bounds::new(Size { width, height }, false));
+    map.set_obstacles(StdRng::seed_from_u64(seed));
+    map.obstacles().len()
+}
+
+/// Generate a maze for a level of the given size, returning the number of
+/// wall cells
+pub fn generate_maze(width: u16, height: u16, seed: u64) -> usize {
+    let mut map = LevelMap::new(Bounds::new(Size { width, height }, false));
+    map.set_maze(StartPosition::Center, StdRng::seed_from_u64(seed));
+    map.obstacles().len()
+}
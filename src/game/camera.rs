@@ -0,0 +1,189 @@
+use crate::config::CameraMode;
+use crate::consts;
+use ratatui::layout::{Position, Rect, Size};
+
+/// The window through which a level larger than
+/// [`VIEWPORT_SIZE`][consts::VIEWPORT_SIZE] is shown, which scrolls to follow
+/// the snake's head
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct Camera {
+    /// How the view follows the snake's head
+    mode: CameraMode,
+
+    /// The portion of the level currently shown, in level coordinates
+    view: Rect,
+}
+
+impl Camera {
+    /// Create a camera for a level of size `level` with the view centered on
+    /// `head` as far as the level's edges allow
+    pub(super) fn new(mode: CameraMode, level: Size, head: Position) -> Camera {
+        let size = Size {
+            width: level.width.min(consts::VIEWPORT_SIZE.width),
+            height: level.height.min(consts::VIEWPORT_SIZE.height),
+        };
+        let mut camera = Camera {
+            mode: CameraMode::Centered,
+            view: Rect::from((Position::ORIGIN, size)),
+        };
+        camera.follow(level, head);
+        camera.mode = mode;
+        camera
+    }
+
+    /// Return the portion of the level currently shown, in level coordinates
+    pub(super) fn view(&self) -> Rect {
+        self.view
+    }
+
+    /// Scroll the view as needed after the snake's head has moved to `head`.
+    /// The view never extends past the edges of the level.
+    pub(super) fn follow(&mut self, level: Size, head: Position) {
+        let margin = consts::CAMERA_DEAD_ZONE_MARGIN;
+        self.view.x = self.follow_axis(
+            self.view.x,
+            self.view.width,
+            level.width,
+            head.x,
+            margin.width,
+        );
+        self.view.y = self.follow_axis(
+            self.view.y,
+            self.view.height,
+            level.height,
+            head.y,
+            margin.height,
+        );
+    }
+
+    /// Compute the new start of the view along one axis, given the view's
+    /// current start & length, the level's length, the head's coordinate, and
+    /// the dead-zone margin
+    fn follow_axis(&self, start: u16, span: u16, len: u16, pos: u16, margin: u16) -> u16 {
+        let start = match self.mode {
+            CameraMode::Centered => pos.saturating_sub(span / 2),
+            CameraMode::DeadZone => {
+                // Keep the dead zone at least one cell wide
+                let margin = margin.min(span.saturating_sub(1) / 2);
+                if pos < start.saturating_add(margin) {
+                    pos.saturating_sub(margin)
+                } else if pos >= start.saturating_add(span).saturating_sub(margin) {
+                    pos.saturating_add(margin)
+                        .saturating_add(1)
+                        .saturating_sub(span)
+                } else {
+                    start
+                }
+            }
+        };
+        start.min(len.saturating_sub(span))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const LEVEL: Size = Size {
+        width: 200,
+        height: 100,
+    };
+
+    #[test]
+    fn starts_centered() {
+        for mode in [CameraMode::Centered, CameraMode::DeadZone] {
+            let camera = Camera::new(mode, LEVEL, Position::new(100, 50));
+            assert_eq!(camera.view(), Rect::new(62, 41, 76, 19));
+            let camera = Camera::new(mode, LEVEL, Position::new(3, 97));
+            assert_eq!(camera.view(), Rect::new(0, 81, 76, 19));
+        }
+    }
+
+    #[test]
+    fn small_level() {
+        let level = Size::new(20, 10);
+        for mode in [CameraMode::Centered, CameraMode::DeadZone] {
+            let mut camera = Camera::new(mode, level, Position::new(10, 5));
+            assert_eq!(camera.view(), Rect::new(0, 0, 20, 10));
+            camera.follow(level, Position::new(19, 9));
+            assert_eq!(camera.view(), Rect::new(0, 0, 20, 10));
+        }
+    }
+
+    #[test]
+    fn centered_scrolls_every_move() {
+        let mut camera = Camera::new(CameraMode::Centered, LEVEL, Position::new(100, 50));
+        camera.follow(LEVEL, Position::new(101, 50));
+        assert_eq!(camera.view(), Rect::new(63, 41, 76, 19));
+        camera.follow(LEVEL, Position::new(101, 49));
+        assert_eq!(camera.view(), Rect::new(63, 40, 76, 19));
+    }
+
+    #[test]
+    fn dead_zone_holds_still() {
+        let mut camera = Camera::new(CameraMode::DeadZone, LEVEL, Position::new(100, 50));
+        let start = camera.view();
+        let margin = consts::CAMERA_DEAD_ZONE_MARGIN;
+        // Moving east, the view stays put until the head is within the
+        // margin of the right edge
+        let last_still = start.right() - margin.width - 1;
+        for x in 101..=last_still {
+            camera.follow(LEVEL, Position::new(x, 50));
+            assert_eq!(camera.view(), start);
+        }
+        camera.follow(LEVEL, Position::new(last_still + 1, 50));
+        assert_eq!(
+            camera.view(),
+            Rect {
+                x: start.x + 1,
+                ..start
+            }
+        );
+        // Turning around doesn't scroll the view back
+        camera.follow(LEVEL, Position::new(last_still + 1, 49));
+        camera.follow(LEVEL, Position::new(last_still, 49));
+        assert_eq!(
+            camera.view(),
+            Rect {
+                x: start.x + 1,
+                ..start
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(CameraMode::Centered)]
+    #[case(CameraMode::DeadZone)]
+    fn head_stays_in_view(#[case] mode: CameraMode) {
+        // Spiral outwards from the center of the level, then jump across it
+        // as though wrapping around
+        let mut head = Position::new(100, 50);
+        let mut camera = Camera::new(mode, LEVEL, head);
+        let mut path = Vec::new();
+        for (i, leg) in (1..90).enumerate() {
+            for _ in 0..leg {
+                match i % 4 {
+                    0 => head.x = (head.x + 1).min(LEVEL.width - 1),
+                    1 => head.y = (head.y + 1).min(LEVEL.height - 1),
+                    2 => head.x = head.x.saturating_sub(1),
+                    _ => head.y = head.y.saturating_sub(1),
+                }
+                path.push(head);
+            }
+        }
+        path.extend([
+            Position::new(0, 50),
+            Position::new(199, 50),
+            Position::new(199, 0),
+            Position::new(199, 99),
+        ]);
+        for head in path {
+            camera.follow(LEVEL, head);
+            let view = camera.view();
+            assert!(view.contains(head), "{head} not in {view}");
+            assert!(view.right() <= LEVEL.width && view.bottom() <= LEVEL.height);
+            assert_eq!(view.as_size(), consts::VIEWPORT_SIZE);
+        }
+    }
+}
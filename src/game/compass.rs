@@ -0,0 +1,81 @@
+use crate::util::Bounds;
+use ratatui::layout::Position;
+
+/// Return an arrow pointing from `head` toward the nearest of `fruits`, or
+/// `None` if there are no fruits.  Ties between equally-near fruits are
+/// broken by preferring the topmost, then leftmost fruit.
+pub(super) fn fruit_arrow<'a, I>(bounds: Bounds, head: Position, fruits: I) -> Option<&'static str>
+where
+    I: IntoIterator<Item = &'a Position>,
+{
+    let &nearest = fruits
+        .into_iter()
+        .min_by_key(|&&f| (bounds.distance(head, f), f.y, f.x))?;
+    let (dx, dy) = bounds.offset(head, nearest);
+    Some(arrow(dx, dy))
+}
+
+/// Return the one of the eight compass arrows closest in direction to the
+/// vector `(dx, dy)`, where positive `dy` points down the screen
+fn arrow(dx: i32, dy: i32) -> &'static str {
+    // A component counts if it's at least ~tan(22.5°) of the other one.
+    let horiz = dx.abs() * 5 > dy.abs() * 2;
+    let vert = dy.abs() * 5 > dx.abs() * 2;
+    match (horiz.then_some(dx.signum()), vert.then_some(dy.signum())) {
+        (Some(1), Some(-1)) => "↗",
+        (Some(1), Some(1)) => "↘",
+        (Some(-1), Some(1)) => "↙",
+        (Some(-1), Some(-1)) => "↖",
+        (Some(1), _) => "→",
+        (Some(-1), _) => "←",
+        (_, Some(1)) => "↓",
+        (_, Some(-1)) => "↑",
+        _ => "·",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(5, 0, "→")]
+    #[case(5, 1, "→")]
+    #[case(5, 3, "↘")]
+    #[case(-4, 4, "↙")]
+    #[case(-1, -7, "↑")]
+    #[case(0, 2, "↓")]
+    #[case(-9, 0, "←")]
+    #[case(3, -2, "↗")]
+    #[case(-6, -5, "↖")]
+    #[case(0, 0, "·")]
+    fn arrows(#[case] dx: i32, #[case] dy: i32, #[case] expected: &str) {
+        assert_eq!(arrow(dx, dy), expected);
+    }
+
+    #[test]
+    fn nearest_fruit() {
+        let bounds = Bounds {
+            width: 40,
+            height: 20,
+            wrap: false,
+        };
+        let head = Position::new(10, 10);
+        let fruits = [Position::new(30, 10), Position::new(10, 4)];
+        assert_eq!(fruit_arrow(bounds, head, &fruits), Some("↑"));
+        assert_eq!(fruit_arrow(bounds, head, &[]), None);
+    }
+
+    #[test]
+    fn nearest_fruit_wraparound() {
+        let bounds = Bounds {
+            width: 40,
+            height: 20,
+            wrap: true,
+        };
+        let head = Position::new(2, 10);
+        let fruits = [Position::new(37, 11), Position::new(2, 1)];
+        assert_eq!(fruit_arrow(bounds, head, &fruits), Some("←"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Adaptive difficulty, which speeds up or slows down the snake and adds
+//! obstacles to levels according to how well the player has been doing
+use crate::consts;
+use std::fmt;
+use std::time::Duration;
+
+/// The player's adaptive difficulty level, carried over from game to game
+/// when the Adaptive option is set.
+///
+/// Eating several fruits in a row quickly raises the level by one, taking a
+/// long time to eat several fruits in a row lowers it by one, and crashing
+/// lowers it by one straight away.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Difficulty {
+    /// The difficulty level, from `-consts::ADAPTIVE_MAX_LEVEL` (easiest) to
+    /// `consts::ADAPTIVE_MAX_LEVEL` (hardest), with 0 being the normal
+    /// difficulty
+    level: i8,
+
+    /// The number of quickly-eaten fruits minus the number of slowly-eaten
+    /// fruits since the level last changed
+    momentum: i8,
+}
+
+impl Difficulty {
+    /// Record that a fruit was eaten `ticks` ticks after the previous one (or
+    /// after the start of the game)
+    pub(super) fn record_fruit(&mut self, ticks: u32) {
+        if ticks <= consts::ADAPTIVE_FAST_FRUIT_TICKS {
+            self.momentum = self.momentum.saturating_add(1);
+        } else if ticks >= consts::ADAPTIVE_SLOW_FRUIT_TICKS {
+            self.momentum = self.momentum.saturating_sub(1);
+        }
+        if self.momentum >= consts::ADAPTIVE_MOMENTUM {
+            self.shift(1);
+        } else if self.momentum <= -consts::ADAPTIVE_MOMENTUM {
+            self.shift(-1);
+        }
+    }
+
+    /// Record that the snake crashed
+    pub(super) fn record_death(&mut self) {
+        self.shift(-1);
+    }
+
+    /// Change the level by `delta`, staying within bounds, and start
+    /// counting momentum afresh
+    fn shift(&mut self, delta: i8) {
+        self.level = self
+            .level
+            .saturating_add(delta)
+            .clamp(-consts::ADAPTIVE_MAX_LEVEL, consts::ADAPTIVE_MAX_LEVEL);
+        self.momentum = 0;
+    }
+
+    /// Shorten or lengthen the time between ticks according to the level
+    pub(super) fn adjust_period(&self, period: Duration) -> Duration {
+        period.mul_f64(f64::from(self.level).mul_add(-consts::ADAPTIVE_SPEED_STEP, 1.0))
+    }
+
+    /// Return the number of fruits to be eaten between placements of new
+    /// obstacles, or `None` if the level is too low for new obstacles to be
+    /// placed
+    pub(super) fn obstacle_interval(&self) -> Option<u32> {
+        let level = u32::try_from(self.level).ok().filter(|&lv| lv > 0)?;
+        Some(
+            consts::ADAPTIVE_OBSTACLE_FRUITS
+                .saturating_add(1)
+                .saturating_sub(level)
+                .max(1),
+        )
+    }
+}
+
+impl fmt::Display for Difficulty {
+    /// Display the level with an explicit sign if it's nonzero
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.level == 0 {
+            write!(f, "0")
+        } else {
+            write!(f, "{:+}", self.level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adapt() {
+        let mut diff = Difficulty::default();
+        assert_eq!(diff.to_string(), "0");
+        assert_eq!(diff.obstacle_interval(), None);
+        for _ in 1..consts::ADAPTIVE_MOMENTUM {
+            diff.record_fruit(consts::ADAPTIVE_FAST_FRUIT_TICKS);
+        }
+        // Fruits eaten at an ordinary pace don't change anything
+        diff.record_fruit(consts::ADAPTIVE_FAST_FRUIT_TICKS + 1);
+        assert_eq!(diff.level, 0);
+        diff.record_fruit(1);
+        assert_eq!(diff.level, 1);
+        assert_eq!(diff.to_string(), "+1");
+        assert_eq!(
+            diff.obstacle_interval(),
+            Some(consts::ADAPTIVE_OBSTACLE_FRUITS)
+        );
+        assert!(diff.adjust_period(consts::TICK_PERIOD) < consts::TICK_PERIOD);
+        diff.record_death();
+        diff.record_death();
+        assert_eq!(diff.to_string(), "-1");
+        assert_eq!(diff.obstacle_interval(), None);
+        assert!(diff.adjust_period(consts::TICK_PERIOD) > consts::TICK_PERIOD);
+        for _ in 0..consts::ADAPTIVE_MOMENTUM {
+            diff.record_fruit(consts::ADAPTIVE_SLOW_FRUIT_TICKS);
+        }
+        assert_eq!(diff.level, -2);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut diff = Difficulty::default();
+        for _ in 0..100 {
+            diff.record_fruit(0);
+        }
+        assert_eq!(diff.level, consts::ADAPTIVE_MAX_LEVEL);
+        assert_eq!(diff.obstacle_interval(), Some(1));
+        for _ in 0..100 {
+            diff.record_death();
+        }
+        assert_eq!(diff.level, -consts::ADAPTIVE_MAX_LEVEL);
+        assert!(!diff.adjust_period(consts::TICK_PERIOD).is_zero());
+    }
+}
@@ -0,0 +1,76 @@
+//! Tips shown to players who keep dying the same way, when the `crash-tips`
+//! setting is enabled
+use crate::consts;
+use crate::history::EndCause;
+use crate::i18n::Msg;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// A record of when the snake recently crashed, kept across games for
+/// deciding when to show a tip
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct DeathLog(VecDeque<Instant>);
+
+impl DeathLog {
+    /// Record a crash that happened at `when` and return `true` if it was
+    /// the [`consts::HINT_DEATHS`]-th crash within [`consts::HINT_WINDOW`], in
+    /// which case the log is cleared so that the next tip takes just as many
+    /// crashes.
+    pub(crate) fn record(&mut self, when: Instant) -> bool {
+        self.0
+            .retain(|&t| when.saturating_duration_since(t) < consts::HINT_WINDOW);
+        self.0.push_back(when);
+        if self.0.len() >= consts::HINT_DEATHS {
+            self.0.clear();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Return the tip to show after repeatedly crashing into `cause`, if there
+/// is one
+pub(super) fn hint_for(cause: EndCause) -> Option<Msg> {
+    match cause {
+        EndCause::Wall => Some(Msg::HintWall),
+        EndCause::Body => Some(Msg::HintBody),
+        EndCause::Obstacle => Some(Msg::HintObstacle),
+        EndCause::Forfeit
+        | EndCause::BoardFull
+        | EndCause::Perfect
+        | EndCause::Forbidden
+        | EndCause::TimeUp
+        | EndCause::Objective
+        | EndCause::Interrupted => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn third_death_within_window() {
+        let start = Instant::now();
+        let mut log = DeathLog::default();
+        assert!(!log.record(start));
+        assert!(!log.record(start + Duration::from_secs(20)));
+        assert!(log.record(start + Duration::from_secs(40)));
+        // The log starts over after a tip
+        assert!(!log.record(start + Duration::from_secs(41)));
+        assert!(!log.record(start + Duration::from_secs(42)));
+        assert!(log.record(start + Duration::from_secs(43)));
+    }
+
+    #[test]
+    fn old_deaths_expire() {
+        let start = Instant::now();
+        let mut log = DeathLog::default();
+        assert!(!log.record(start));
+        assert!(!log.record(start + Duration::from_secs(30)));
+        assert!(!log.record(start + Duration::from_secs(61)));
+        assert!(log.record(start + Duration::from_secs(62)));
+    }
+}
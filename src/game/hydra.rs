@@ -0,0 +1,84 @@
+use super::snake::Snake;
+use crate::direction::Direction;
+use enum_map::Enum;
+use ratatui::layout::{Position, Size};
+
+/// An extra snake spawned by eating a hydra fruit, which moves as a mirror
+/// image of the player's snake
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct Hydra {
+    /// How the snake's movements are mirrored from those of the player's
+    /// snake
+    pub(super) mirror: Mirror,
+
+    /// The snake itself
+    pub(super) snake: Snake,
+}
+
+/// The ways in which an extra snake can mirror the player's snake.  Each
+/// extra snake in a game uses a different mirroring, and so there can be at
+/// most one extra snake per variant.
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+pub(super) enum Mirror {
+    /// Mirrored across the vertical center line of the level, so that east &
+    /// west are swapped
+    Horizontal,
+
+    /// Mirrored across the horizontal center line of the level, so that north
+    /// & south are swapped
+    Vertical,
+
+    /// Mirrored across both center lines, so that every direction is reversed
+    Both,
+}
+
+impl Mirror {
+    /// Return the direction in which a mirrored snake moves when the player's
+    /// snake moves in `direction`
+    pub(super) fn direction(self, direction: Direction) -> Direction {
+        match (self, direction) {
+            (Mirror::Horizontal, Direction::East | Direction::West)
+            | (Mirror::Vertical, Direction::North | Direction::South)
+            | (Mirror::Both, _) => direction.reverse(),
+            _ => direction,
+        }
+    }
+
+    /// Return the position in a level of size `level` that mirrors `pos`
+    pub(super) fn position(self, pos: Position, level: Size) -> Position {
+        let flip_x = || level.width.saturating_sub(1).saturating_sub(pos.x);
+        let flip_y = || level.height.saturating_sub(1).saturating_sub(pos.y);
+        match self {
+            Mirror::Horizontal => Position::new(flip_x(), pos.y),
+            Mirror::Vertical => Position::new(pos.x, flip_y()),
+            Mirror::Both => Position::new(flip_x(), flip_y()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Mirror::Horizontal, Direction::North, Direction::North)]
+    #[case(Mirror::Horizontal, Direction::East, Direction::West)]
+    #[case(Mirror::Horizontal, Direction::West, Direction::East)]
+    #[case(Mirror::Vertical, Direction::North, Direction::South)]
+    #[case(Mirror::Vertical, Direction::East, Direction::East)]
+    #[case(Mirror::Vertical, Direction::South, Direction::North)]
+    #[case(Mirror::Both, Direction::North, Direction::South)]
+    #[case(Mirror::Both, Direction::West, Direction::East)]
+    fn mirror_direction(#[case] mirror: Mirror, #[case] d: Direction, #[case] r: Direction) {
+        assert_eq!(mirror.direction(d), r);
+    }
+
+    #[rstest]
+    #[case(Mirror::Horizontal, Position::new(2, 3))]
+    #[case(Mirror::Vertical, Position::new(7, 6))]
+    #[case(Mirror::Both, Position::new(2, 6))]
+    fn mirror_position(#[case] mirror: Mirror, #[case] r: Position) {
+        assert_eq!(mirror.position(Position::new(7, 3), Size::new(10, 10)), r);
+    }
+}
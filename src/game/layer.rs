@@ -0,0 +1,136 @@
+use crate::config::GlyphConfig;
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect},
+};
+
+/// The drawn contents of the cells of the level — the snakes' bodies, fruits,
+/// obstacles, terrain, etc., but not the snakes' heads — kept between frames
+/// so that only the cells that changed since the previous frame need to be
+/// drawn again
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct LevelLayer {
+    /// The part of the level that `cells` covers
+    view: Rect,
+
+    /// The glyphs that `cells` were drawn with
+    glyphs: Option<GlyphConfig>,
+
+    /// The cells in `view`, row by row, or `None` for cells in which nothing
+    /// is drawn
+    cells: Vec<Option<Cell>>,
+
+    /// The state of the game when `cells` was last brought up to date, or
+    /// `None` if every cell needs to be drawn again
+    seen: Option<Seen>,
+}
+
+impl LevelLayer {
+    /// Discard the drawn cells so that they are all drawn again on the next
+    /// frame.  This must be called whenever the level changes in a way not
+    /// captured by [`Seen`].
+    pub(super) fn invalidate(&mut self) {
+        self.seen = None;
+    }
+
+    /// Bring the cells up to date for a frame in which the part of the level
+    /// in `view` is drawn with `glyphs` and the state of the game is `seen`.
+    /// `draw` is called to obtain the contents of each cell that may have
+    /// changed since the last frame; if nothing about the game has changed
+    /// beyond what `seen` records, only the cells marked in either the
+    /// previous or current `seen` are drawn, along with any cells that
+    /// scrolled into view.
+    pub(super) fn update<F>(&mut self, view: Rect, glyphs: &GlyphConfig, seen: Seen, mut draw: F)
+    where
+        F: FnMut(Position) -> Option<Cell>,
+    {
+        let incremental = self.view.as_size() == view.as_size()
+            && self.glyphs.as_ref() == Some(glyphs)
+            && self.seen.as_ref().is_some_and(|prev| prev.continues(&seen));
+        if incremental {
+            if self.view != view {
+                let cells = view
+                    .positions()
+                    .map(|pos| match self.index(pos) {
+                        Some(i) => self.cells[i].take(),
+                        None => draw(pos),
+                    })
+                    .collect();
+                self.cells = cells;
+                self.view = view;
+            }
+            let prev = self.seen.take().map(|prev| prev.marks).unwrap_or_default();
+            for pos in prev.into_iter().chain(seen.marks.iter().copied()) {
+                if let Some(i) = self.index(pos) {
+                    self.cells[i] = draw(pos);
+                }
+            }
+        } else {
+            self.view = view;
+            if self.glyphs.as_ref() != Some(glyphs) {
+                self.glyphs = Some(glyphs.clone());
+            }
+            self.cells = view.positions().map(draw).collect();
+        }
+        self.seen = Some(seen);
+    }
+
+    /// Copy the drawn cells into `buf`, with the top-left corner of the view
+    /// at the top-left corner of `area`.  Cells that fall outside of `area`
+    /// are not copied.
+    pub(super) fn render(&self, area: Rect, buf: &mut Buffer) {
+        let width = self.view.width.min(area.width);
+        let height = self.view.height.min(area.height);
+        for y in 0..height {
+            for x in 0..width {
+                let i = usize::from(y) * usize::from(self.view.width) + usize::from(x);
+                if let Some(Some(cell)) = self.cells.get(i) {
+                    if let Some(dest) = buf.cell_mut((area.x + x, area.y + y)) {
+                        dest.clone_from(cell);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the index into `cells` of the cell at `pos`, if it is in view
+    fn index(&self, pos: Position) -> Option<usize> {
+        self.view.contains(pos).then(|| {
+            usize::from(pos.y - self.view.y) * usize::from(self.view.width)
+                + usize::from(pos.x - self.view.x)
+        })
+    }
+}
+
+/// The parts of the state of a game that determine which cells of a
+/// [`LevelLayer`] need to be drawn again on the next frame
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct Seen {
+    /// The number of ticks that had passed in the game
+    pub(super) ticks: u32,
+
+    /// The number of snakes
+    pub(super) snakes: usize,
+
+    /// Whether the snakes were drawn in the style of the star power-up
+    pub(super) blink: bool,
+
+    /// The cells whose contents may change by the next tick: the snakes'
+    /// heads and tails and the positions of fruits, power-ups, and other
+    /// short-lived things.  When the snakes advance by one cell, the only
+    /// cells of their bodies that change are where their heads and tails
+    /// were.
+    pub(super) marks: Vec<Position>,
+}
+
+impl Seen {
+    /// Can a frame showing the game as recorded in `next` be drawn by only
+    /// redrawing the cells marked in `self` and `next`?  This is the case if
+    /// at most one tick has passed, no snakes have been added, and the snakes
+    /// haven't started or stopped blinking.
+    fn continues(&self, next: &Seen) -> bool {
+        (next.ticks == self.ticks || Some(next.ticks) == self.ticks.checked_add(1))
+            && next.snakes == self.snakes
+            && next.blink == self.blink
+    }
+}
@@ -1,13 +1,15 @@
 use super::snake::Snake;
 use crate::consts;
 use crate::direction::Direction;
+use crate::options::{LevelLayout, StartPosition};
 use crate::util::Bounds;
 use rand::{
     distr::{Bernoulli, Distribution},
+    seq::{IndexedRandom, IteratorRandom, SliceRandom},
     Rng,
 };
 use ratatui::layout::{Position, Size};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// A map of a game level
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -18,6 +20,9 @@ pub(super) struct LevelMap {
     /// The locations of any obstacles in the level
     obstacles: HashSet<Position>,
 
+    /// The locations & kinds of any special terrain tiles in the level
+    terrain: HashMap<Position, Terrain>,
+
     /// The snake's starting position and direction
     snake_start: (Position, Direction),
 }
@@ -30,10 +35,79 @@ impl LevelMap {
         LevelMap {
             bounds,
             obstacles: HashSet::new(),
+            terrain: HashMap::new(),
             snake_start: (snake_head, Direction::North),
         }
     }
 
+    /// Create a level with the given bounds & layout, placing the snake's
+    /// starting position according to `start` and populating the level with
+    /// random obstacles (if `obstacles` is true and the layout is open) or
+    /// maze walls and, if `terrain` is true, patches of mud & ice using the
+    /// given RNG
+    pub(super) fn generate<R: Rng>(
+        bounds: Bounds,
+        layout: LevelLayout,
+        obstacles: bool,
+        terrain: bool,
+        start: StartPosition,
+        mut rng: R,
+    ) -> LevelMap {
+        let mut map = LevelMap::new(bounds);
+        match layout {
+            LevelLayout::Open => {
+                map.set_start(start, &mut rng);
+                if obstacles {
+                    map.set_obstacles(&mut rng);
+                }
+            }
+            LevelLayout::Maze => map.set_maze(start, &mut rng),
+        }
+        if terrain {
+            map.set_terrain(rng);
+        }
+        map
+    }
+
+    /// Set the snake's starting position & direction in a level without maze
+    /// walls.  A random start is chosen using the given RNG from among the
+    /// positions that leave at least
+    /// [`FORWARDS_CLEARANCE`][consts::FORWARDS_CLEARANCE] cells in front of
+    /// the snake inside the level.
+    pub(super) fn set_start<R: Rng>(&mut self, start: StartPosition, mut rng: R) {
+        match start {
+            StartPosition::Center => (),
+            StartPosition::Corner => {
+                let head = Position::new(
+                    1.min(self.bounds.width.saturating_sub(1)),
+                    1.min(self.bounds.height.saturating_sub(1)),
+                );
+                self.snake_start = (head, Direction::East);
+            }
+            StartPosition::Random => {
+                let dirs = [
+                    Direction::North,
+                    Direction::East,
+                    Direction::South,
+                    Direction::West,
+                ];
+                let Some(&dir) = dirs.choose(&mut rng) else {
+                    return;
+                };
+                let bounds = self.bounds;
+                let roomy = bounds.positions().filter(|&pos| {
+                    std::iter::successors(Some(pos), |&p| dir.advance(p, bounds))
+                        .take(consts::FORWARDS_CLEARANCE)
+                        .count()
+                        == consts::FORWARDS_CLEARANCE
+                });
+                if let Some(head) = roomy.choose(&mut rng) {
+                    self.snake_start = (head, dir);
+                }
+            }
+        }
+    }
+
     /// Populate the level with randomly-generated obstacles using the given
     /// RNG.  Any previously-generated obstacles are discarded.
     pub(super) fn set_obstacles<R: Rng>(&mut self, rng: R) {
@@ -45,18 +119,169 @@ impl LevelMap {
                 .zip(dist.sample_iter(rng))
                 .filter_map(|(pos, f)| f.then_some(pos)),
         );
-        let (snake_head, snake_dir) = self.snake_start;
-        for pos in std::iter::successors(Some(snake_head), |&p| snake_dir.advance(p, self.bounds))
-            .take(consts::FORWARDS_CLEARANCE)
-        {
+        for pos in self.start_clearance() {
             self.obstacles.remove(&pos);
         }
+    }
+
+    /// Scatter patches of mud & ice and single one-way gates around the level
+    /// using the given RNG.  Each patch is a plus shape of a single kind of
+    /// terrain centered on a randomly-chosen cell, and all terrain is kept
+    /// off of obstacles and out of the snake's way at the start of the level.
+    /// Any previously-generated terrain is discarded.
+    pub(super) fn set_terrain<R: Rng>(&mut self, mut rng: R) {
+        let dist = Bernoulli::new(consts::TERRAIN_PATCH_PROBABILITY)
+            .expect("TERRAIN_PATCH_PROBABILITY should be between 0 and 1");
+        let gate_dist = Bernoulli::new(consts::GATE_PROBABILITY)
+            .expect("GATE_PROBABILITY should be between 0 and 1");
+        let centers = self
+            .bounds
+            .positions()
+            .filter(|_| dist.sample(&mut rng))
+            .collect::<Vec<_>>();
+        self.terrain.clear();
+        for center in centers {
+            let kind = if rng.random_bool(0.5) {
+                Terrain::Mud
+            } else {
+                Terrain::Ice
+            };
+            for pos in std::iter::once(center).chain(
+                [
+                    Direction::North,
+                    Direction::East,
+                    Direction::South,
+                    Direction::West,
+                ]
+                .into_iter()
+                .filter_map(|d| d.advance(center, self.bounds)),
+            ) {
+                if !self.obstacles.contains(&pos) {
+                    self.terrain.insert(pos, kind);
+                }
+            }
+        }
+        let gates = self
+            .bounds
+            .positions()
+            .filter(|pos| !self.obstacles.contains(pos) && !self.terrain.contains_key(pos))
+            .filter(|_| gate_dist.sample(&mut rng))
+            .collect::<Vec<_>>();
+        for pos in gates {
+            let dirs = [
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ];
+            if let Some(&dir) = dirs.choose(&mut rng) {
+                self.terrain.insert(pos, Terrain::Gate(dir));
+            }
+        }
+        for pos in self.start_clearance() {
+            self.terrain.remove(&pos);
+        }
+    }
+
+    /// Return the cells in front of & behind the snake's starting position
+    /// that are kept clear of randomly-placed obstacles & terrain, as set by
+    /// [`FORWARDS_CLEARANCE`][consts::FORWARDS_CLEARANCE] and
+    /// [`BACKWARDS_CLEARANCE`][consts::BACKWARDS_CLEARANCE]
+    fn start_clearance(&self) -> Vec<Position> {
+        let (snake_head, snake_dir) = self.snake_start;
         let rid_ekans = snake_dir.reverse();
-        for pos in std::iter::successors(Some(snake_head), |&p| rid_ekans.advance(p, self.bounds))
-            .take(consts::BACKWARDS_CLEARANCE)
-        {
-            self.obstacles.remove(&pos);
+        std::iter::successors(Some(snake_head), |&p| snake_dir.advance(p, self.bounds))
+            .take(consts::FORWARDS_CLEARANCE)
+            .chain(
+                std::iter::successors(Some(snake_head), |&p| rid_ekans.advance(p, self.bounds))
+                    .take(consts::BACKWARDS_CLEARANCE),
+            )
+            .collect()
+    }
+
+    /// Fill the level with the walls of a randomly-generated perfect maze
+    /// using the given RNG.  Any previously-generated obstacles are
+    /// discarded.
+    ///
+    /// The maze is generated with the recursive backtracker algorithm on a
+    /// grid of "rooms" that are scaled to fill the level, with all corridors
+    /// at least [`MAZE_CORRIDOR_WIDTH`][consts::MAZE_CORRIDOR_WIDTH] cells
+    /// wide, and so every open cell in the level is reachable from every
+    /// other.  The snake's starting position is moved to the top-left corner
+    /// of the room selected by `start` (the room nearest the center of the
+    /// level, the top-left room, or a random room), facing along one of that
+    /// room's passages.
+    pub(super) fn set_maze<R: Rng>(&mut self, start: StartPosition, mut rng: R) {
+        let columns = maze_spans(self.bounds.width);
+        let rows = maze_spans(self.bounds.height);
+        if columns.is_empty() || rows.is_empty() {
+            self.obstacles.clear();
+            return;
         }
+        let mut open = HashSet::new();
+        let mut carve = |xs: Span, ys: Span| {
+            for x in xs.0..xs.1 {
+                for y in ys.0..ys.1 {
+                    open.insert(Position::new(x, y));
+                }
+            }
+        };
+        let start = match start {
+            StartPosition::Center => (columns.len() / 2, rows.len() / 2),
+            StartPosition::Corner => (0, 0),
+            StartPosition::Random => (
+                rng.random_range(0..columns.len()),
+                rng.random_range(0..rows.len()),
+            ),
+        };
+        let mut visited = HashSet::from([start]);
+        let mut stack = vec![start];
+        let mut start_dir = None;
+        while let Some(&(i, j)) = stack.last() {
+            carve(columns[i], rows[j]);
+            let mut neighbors = [
+                (Direction::North, j.checked_sub(1).map(|j2| (i, j2))),
+                (
+                    Direction::East,
+                    Some((i + 1, j)).filter(|&(i2, _)| i2 < columns.len()),
+                ),
+                (
+                    Direction::South,
+                    Some((i, j + 1)).filter(|&(_, j2)| j2 < rows.len()),
+                ),
+                (Direction::West, i.checked_sub(1).map(|i2| (i2, j))),
+            ]
+            .into_iter()
+            .filter_map(|(d, cell)| cell.filter(|c| !visited.contains(c)).map(|c| (d, c)))
+            .collect::<Vec<_>>();
+            neighbors.shuffle(&mut rng);
+            let Some(&(dir, (i2, j2))) = neighbors.first() else {
+                stack.pop();
+                continue;
+            };
+            // Knock down the wall between the two rooms
+            match dir {
+                Direction::North => carve(columns[i], (rows[j2].1, rows[j].0)),
+                Direction::South => carve(columns[i], (rows[j].1, rows[j2].0)),
+                Direction::East => carve((columns[i].1, columns[i2].0), rows[j]),
+                Direction::West => carve((columns[i2].1, columns[i].0), rows[j]),
+            }
+            if (i, j) == start && start_dir.is_none() {
+                start_dir = Some(dir);
+            }
+            visited.insert((i2, j2));
+            stack.push((i2, j2));
+        }
+        self.obstacles = self
+            .bounds
+            .positions()
+            .filter(|p| !open.contains(p))
+            .collect();
+        let (i, j) = start;
+        self.snake_start = (
+            Position::new(columns[i].0, rows[j].0),
+            start_dir.unwrap_or(Direction::North),
+        );
     }
 
     /// Return a new `Snake` value with this level's starting location &
@@ -76,6 +301,89 @@ impl LevelMap {
         &self.obstacles
     }
 
+    /// Remove the obstacle at `pos`, if any
+    pub(super) fn remove_obstacle(&mut self, pos: Position) {
+        self.obstacles.remove(&pos);
+    }
+
+    /// Place an obstacle at `pos`, covering up any terrain there
+    pub(super) fn add_obstacle(&mut self, pos: Position) {
+        self.obstacles.insert(pos);
+        self.terrain.remove(&pos);
+    }
+
+    /// Return the locations & kinds of any terrain tiles in the level
+    pub(super) fn terrain(&self) -> &HashMap<Position, Terrain> {
+        &self.terrain
+    }
+
+    /// Place a terrain tile of the given kind at `pos`
+    #[cfg(test)]
+    pub(super) fn add_terrain(&mut self, pos: Position, kind: Terrain) {
+        self.terrain.insert(pos, kind);
+    }
+
+    /// Return the kind of terrain at `pos`, if any
+    pub(super) fn terrain_at(&self, pos: Position) -> Option<Terrain> {
+        self.terrain.get(&pos).copied()
+    }
+
+    /// Return the locations & directions of any one-way gates in the level
+    pub(super) fn gates(&self) -> impl Iterator<Item = (Position, Direction)> + '_ {
+        self.terrain.iter().filter_map(|(&pos, &t)| match t {
+            Terrain::Gate(dir) => Some((pos, dir)),
+            Terrain::Mud | Terrain::Ice => None,
+        })
+    }
+
+    /// Returns `true` unless there is a one-way gate at `pos` that can't be
+    /// entered by moving in `direction`.  Obstacles are not taken into
+    /// account.
+    pub(super) fn can_enter(&self, pos: Position, direction: Direction) -> bool {
+        match self.terrain_at(pos) {
+            Some(Terrain::Gate(dir)) => dir == direction,
+            Some(Terrain::Mud | Terrain::Ice) | None => true,
+        }
+    }
+
+    /// Return the cells that can be reached from the snake's starting position
+    /// without passing through an obstacle or the wrong way through a gate
+    pub(super) fn reachable(&self) -> HashSet<Position> {
+        let (head, _) = self.snake_start;
+        let mut seen = HashSet::from([head]);
+        let mut queue = VecDeque::from([head]);
+        while let Some(p) = queue.pop_front() {
+            for d in [
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ] {
+                if let Some(q) = d.advance(p, self.bounds) {
+                    if !self.obstacles.contains(&q) && self.can_enter(q, d) && seen.insert(q) {
+                        queue.push_back(q);
+                    }
+                }
+            }
+        }
+        seen
+    }
+
+    /// Returns `true` if `pos` lies on the edge of the level or is
+    /// orthogonally adjacent to an obstacle
+    pub(super) fn is_near_wall(&self, pos: Position) -> bool {
+        self.bounds.is_edge(pos)
+            || [
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ]
+            .into_iter()
+            .filter_map(|d| d.advance(pos, self.bounds))
+            .any(|p| self.obstacles.contains(&p))
+    }
+
     /// Return the level's size
     pub(super) fn size(&self) -> Size {
         self.bounds.size()
@@ -86,3 +394,168 @@ impl LevelMap {
         self.bounds.wrap
     }
 }
+
+/// A kind of special terrain tile that affects the snake's movement while its
+/// head is on it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Terrain {
+    /// The snake is held in place on every other tick
+    Mud,
+
+    /// The snake slides straight ahead, unable to turn
+    Ice,
+
+    /// A one-way gate that can only be entered by moving in the given
+    /// direction; entering it any other way is a collision
+    Gate(Direction),
+}
+
+/// A half-open range of coordinates along one axis of a level
+type Span = (u16, u16);
+
+/// Divide a level dimension of size `length` into the spans occupied by maze
+/// rooms, separated by one-cell-thick walls.  Each room is
+/// [`MAZE_CORRIDOR_WIDTH`][consts::MAZE_CORRIDOR_WIDTH] cells wide, except
+/// that any leftover space is given to the last room.
+fn maze_spans(length: u16) -> Vec<Span> {
+    let pitch = consts::MAZE_CORRIDOR_WIDTH + 1;
+    let qty = length.saturating_add(1) / pitch;
+    (0..qty)
+        .map(|k| {
+            let start = k * pitch;
+            let end = if k + 1 == qty {
+                length
+            } else {
+                start + consts::MAZE_CORRIDOR_WIDTH
+            };
+            (start, end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::LevelSize;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(StartPosition::Center, Some((Position::new(38, 9), Direction::North)))]
+    #[case(StartPosition::Corner, Some((Position::new(1, 1), Direction::East)))]
+    #[case(StartPosition::Random, None)]
+    fn start_clearance(
+        #[case] start: StartPosition,
+        #[case] expected: Option<(Position, Direction)>,
+    ) {
+        let bounds = Bounds::new(LevelSize::Large.as_size(None), false);
+        for seed in 0..20 {
+            let map = LevelMap::generate(
+                bounds,
+                LevelLayout::Open,
+                true,
+                true,
+                start,
+                ChaCha12Rng::seed_from_u64(seed),
+            );
+            let (head, dir) = map.snake_start;
+            if let Some(expected) = expected {
+                assert_eq!((head, dir), expected);
+            }
+            let ahead = std::iter::successors(Some(head), |&p| dir.advance(p, bounds))
+                .take(consts::FORWARDS_CLEARANCE)
+                .collect::<Vec<_>>();
+            assert_eq!(ahead.len(), consts::FORWARDS_CLEARANCE);
+            assert!(ahead.iter().all(|p| !map.obstacles().contains(p)));
+            assert!(ahead.iter().all(|p| !map.terrain().contains_key(p)));
+        }
+    }
+
+    #[test]
+    fn terrain_avoids_obstacles() {
+        let bounds = Bounds::new(LevelSize::Large.as_size(None), false);
+        let map = LevelMap::generate(
+            bounds,
+            LevelLayout::Maze,
+            false,
+            true,
+            StartPosition::Center,
+            ChaCha12Rng::seed_from_u64(0x0123456789ABCDEF),
+        );
+        assert!(map.terrain().values().any(|&t| t == Terrain::Mud));
+        assert!(map.terrain().values().any(|&t| t == Terrain::Ice));
+        assert!(map.terrain().keys().all(|p| !map.obstacles().contains(p)));
+        let mut map = map;
+        let (&pos, _) = map.terrain().iter().next().unwrap();
+        map.add_obstacle(pos);
+        assert_eq!(map.terrain_at(pos), None);
+    }
+
+    #[rstest]
+    #[case(8, vec![(0, 2), (3, 5), (6, 8)])]
+    #[case(12, vec![(0, 2), (3, 5), (6, 8), (9, 12)])]
+    #[case(1, Vec::new())]
+    fn test_maze_spans(#[case] length: u16, #[case] spans: Vec<Span>) {
+        assert_eq!(maze_spans(length), spans);
+    }
+
+    #[test]
+    fn reachable_excludes_enclosed_cells() {
+        let bounds = Bounds::new(Size::new(10, 5), false);
+        let mut map = LevelMap::new(bounds);
+        map.obstacles = HashSet::from([Position::new(1, 0), Position::new(0, 1)]);
+        let reachable = map.reachable();
+        assert_eq!(reachable.len(), 47);
+        assert!(!reachable.contains(&Position::ORIGIN));
+        assert!(!reachable.contains(&Position::new(1, 0)));
+        assert!(reachable.contains(&Position::new(1, 1)));
+    }
+
+    #[test]
+    fn reachable_through_gates() {
+        let bounds = Bounds::new(Size::new(10, 5), false);
+        let mut map = LevelMap::new(bounds);
+        // The top-left corner can only be reached through the gate, and only
+        // if it faces north
+        map.obstacles = HashSet::from([Position::new(1, 0)]);
+        map.add_terrain(Position::new(0, 1), Terrain::Gate(Direction::South));
+        let reachable = map.reachable();
+        assert!(!reachable.contains(&Position::new(0, 1)));
+        assert!(!reachable.contains(&Position::ORIGIN));
+        map.add_terrain(Position::new(0, 1), Terrain::Gate(Direction::North));
+        let reachable = map.reachable();
+        assert!(reachable.contains(&Position::new(0, 1)));
+        assert!(reachable.contains(&Position::ORIGIN));
+        assert_eq!(map.gates().count(), 1);
+    }
+
+    #[rstest]
+    #[case(LevelSize::Small, false, StartPosition::Center)]
+    #[case(LevelSize::Medium, false, StartPosition::Center)]
+    #[case(LevelSize::Large, false, StartPosition::Center)]
+    #[case(LevelSize::Large, true, StartPosition::Center)]
+    #[case(LevelSize::Medium, false, StartPosition::Corner)]
+    #[case(LevelSize::Medium, false, StartPosition::Random)]
+    fn maze_is_connected(
+        #[case] size: LevelSize,
+        #[case] wrap: bool,
+        #[case] start: StartPosition,
+    ) {
+        let bounds = Bounds::new(size.as_size(None), wrap);
+        let mut map = LevelMap::new(bounds);
+        map.set_maze(start, ChaCha12Rng::seed_from_u64(0x0123456789ABCDEF));
+        assert!(!map.obstacles().is_empty());
+        let (head, dir) = map.snake_start;
+        assert!(!map.obstacles().contains(&head));
+        let ahead = dir.advance(head, bounds).unwrap();
+        assert!(!map.obstacles().contains(&ahead));
+        // Check that every open cell is reachable from the snake's starting
+        // position
+        let open = bounds
+            .positions()
+            .filter(|p| !map.obstacles().contains(p))
+            .count();
+        assert_eq!(map.reachable().len(), open);
+    }
+}
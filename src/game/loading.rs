@@ -0,0 +1,111 @@
+use super::levels::LevelMap;
+use crate::consts;
+use crate::i18n::{tr, Msg};
+use crate::options::{LevelLayout, StartPosition};
+use crate::util::{center_rect, Bounds};
+use rand::rngs::StdRng;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Size},
+    style::Style,
+    text::Line,
+    widgets::{Block, Clear, Widget},
+};
+use std::sync::{
+    mpsc::{self, Receiver, TryRecvError},
+    Arc, Mutex,
+};
+use unicode_width::UnicodeWidthStr;
+
+/// A level whose obstacles or maze walls are being generated in a background
+/// thread, along with the state of the spinner shown in the meantime
+#[derive(Clone, Debug)]
+pub(super) struct LevelLoader {
+    /// The level's bounds, for falling back to an empty level if generation
+    /// fails
+    bounds: Bounds,
+
+    /// The channel on which the finished level will be sent
+    receiver: Arc<Mutex<Receiver<LevelMap>>>,
+
+    /// The index of the current frame of the spinner
+    frame: usize,
+}
+
+impl LevelLoader {
+    /// Start generating a level with the given bounds, layout, & snake
+    /// starting position in a background thread using the given RNG
+    pub(super) fn spawn(
+        bounds: Bounds,
+        layout: LevelLayout,
+        obstacles: bool,
+        terrain: bool,
+        start: StartPosition,
+        rng: StdRng,
+    ) -> LevelLoader {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // If the game was abandoned while the level was being generated,
+            // there's no one left to receive it
+            let _ = sender.send(LevelMap::generate(
+                bounds, layout, obstacles, terrain, start, rng,
+            ));
+        });
+        LevelLoader {
+            bounds,
+            receiver: Arc::new(Mutex::new(receiver)),
+            frame: 0,
+        }
+    }
+
+    /// Return the generated level if it is ready.  If the background thread
+    /// died without producing a level, a level without any obstacles is
+    /// returned instead so that the game can still proceed.
+    pub(super) fn try_finish(&self) -> Option<LevelMap> {
+        let Ok(receiver) = self.receiver.lock() else {
+            return Some(LevelMap::new(self.bounds));
+        };
+        match receiver.try_recv() {
+            Ok(map) => Some(map),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(LevelMap::new(self.bounds)),
+        }
+    }
+
+    /// Advance the spinner to its next frame
+    pub(super) fn spin(&mut self) {
+        self.frame = (self.frame + 1) % consts::SPINNER_FRAMES.len();
+    }
+}
+
+impl PartialEq for LevelLoader {
+    fn eq(&self, other: &LevelLoader) -> bool {
+        self.bounds == other.bounds
+            && Arc::ptr_eq(&self.receiver, &other.receiver)
+            && self.frame == other.frame
+    }
+}
+
+impl Eq for LevelLoader {}
+
+impl Widget for &LevelLoader {
+    /// Render a small pop-up containing the spinner and a "Generating level"
+    /// message in the center of `area`, which should be the area of the
+    /// entire display
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let text = format!(
+            "{} {}",
+            consts::SPINNER_FRAMES[self.frame],
+            tr(Msg::GeneratingLevel)
+        );
+        let width = u16::try_from(text.width())
+            .unwrap_or(u16::MAX)
+            .saturating_add(4);
+        let block_area = center_rect(area, Size { width, height: 3 });
+        let block = Block::bordered().style(Style::reset());
+        let inner = block.inner(block_area);
+        Clear.render(block_area, buf);
+        block.render(block_area, buf);
+        Line::from(text).centered().render(inner, buf);
+    }
+}
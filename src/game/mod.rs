@@ -1,55 +1,172 @@
+#[cfg(feature = "bench")]
+pub mod bench;
+mod camera;
+mod compass;
+mod difficulty;
+mod hints;
+mod hydra;
+mod layer;
 mod levels;
+mod loading;
+mod name_prompt;
+mod objectives;
+mod pace;
+mod pathfind;
 mod paused;
+mod powerups;
+mod relay;
+mod rewind;
 mod snake;
-use self::levels::LevelMap;
+mod snapshot;
+mod split;
+mod stats;
+use self::camera::Camera;
+pub(crate) use self::difficulty::Difficulty;
+pub(crate) use self::hints::DeathLog;
+use self::hydra::{Hydra, Mirror};
+use self::layer::{LevelLayer, Seen};
+use self::levels::{LevelMap, Terrain};
+use self::loading::LevelLoader;
+use self::name_prompt::{NameOutcome, NamePrompt};
+use self::objectives::Verdict;
+use self::pace::fmt_tenths;
+pub(crate) use self::pace::Rival;
 use self::paused::{PauseOpt, Paused};
+use self::powerups::{PowerUp, PowerUpKind};
+use self::relay::Relay;
+use self::rewind::{Checkpoint, History};
 use self::snake::Snake;
+pub(crate) use self::snapshot::Snapshot;
+use self::snapshot::{GateSnapshot, PowerUpSnapshot, SnakeSnapshot, SnapshotState};
+pub(crate) use self::split::SplitGame;
+use self::stats::GameStats;
 use crate::app::Screen;
+use crate::bot::BotError;
 use crate::command::Command;
+use crate::config::RngMode;
+use crate::confirm::{Confirm, ConfirmOutcome};
 use crate::consts;
 use crate::direction::Direction;
-use crate::util::{center_rect, get_display_area, Globals};
+use crate::favorites::Favorite;
+use crate::highscores::HighScore;
+use crate::history::{EndCause, GameRecord};
+use crate::i18n::{self, tr, tr_args, Msg};
+use crate::input::EventSource;
+use crate::level::CustomLevel;
+use crate::options::{Adjustable, LevelLayout, OptKey, Options, SpawnStrategy};
+use crate::palette::{Palette, PaletteAction, PaletteEntry};
+use crate::theme;
+use crate::util::{center_rect, error_chain, get_display_area, Bounds, EnumExt, Globals};
 use crate::warning::{Warning, WarningOutcome};
-use crossterm::event::{poll, read, Event};
-use rand::{seq::IteratorRandom, Rng};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use enum_map::Enum;
+use rand::{
+    rngs::StdRng,
+    seq::{IndexedRandom, IteratorRandom},
+    Rng, SeedableRng,
+};
 use ratatui::{
-    buffer::Buffer,
-    layout::{Constraint, Layout, Margin, Position, Rect, Size},
-    style::Style,
+    buffer::{Buffer, Cell},
+    layout::{Constraint, Layout, Margin, Offset, Position, Rect, Size},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Widget},
     Frame,
 };
 use std::collections::HashSet;
 use std::num::NonZeroU32;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
 
 /// Snake game screen
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub(crate) struct Game<R = rand::rngs::ThreadRng> {
+pub(crate) struct Game<R = StdRng> {
     /// The random-number generator used for generating obstacles and fruit
     rng: R,
 
-    /// The current score, equal to the number of fruits eaten
+    /// The seed that `rng` was created from, if known.  Only games with a
+    /// known seed are recorded in the game history.
+    seed: Option<u64>,
+
+    /// The `rng` setting in effect when the game was created
+    rng_mode: RngMode,
+
+    /// The current score.  Each fruit eaten is worth `combo` points.
     score: u32,
 
+    /// The current score multiplier.  This is always 1 unless the "Combos"
+    /// option is enabled.
+    combo: u32,
+
+    /// The number of ticks remaining in which eating a fruit will increase
+    /// `combo`.  When this reaches zero, `combo` is reset to 1.
+    combo_ticks_left: u32,
+
     /// The current high score for the current gameplay options.
     ///
     /// If `score` exceeds this value, `high_score` is not updated.
     high_score: Option<NonZeroU32>,
 
+    /// The high score that the player is racing against, if any
+    rival: Option<Rival>,
+
     /// The state of the snake itself
     snake: Snake,
 
+    /// The extra snakes spawned by eating hydra fruits, which mirror the
+    /// player's snake
+    hydras: Vec<Hydra>,
+
     /// The positions of the fruits in the level
     fruits: HashSet<Position>,
 
+    /// The position of the hydra fruit in the level, if any
+    hydra_fruit: Option<Position>,
+
+    /// The power-ups present in the level
+    power_ups: Vec<PowerUp>,
+
     /// The state that the game is currently in
     state: GameState,
 
     /// The map of the game level
     map: LevelMap,
 
+    /// The custom level that the game is being played on, if any, whose
+    /// objectives the game is evaluated against
+    level: Option<CustomLevel>,
+
+    /// The cells of the level that can be reached from the snake's starting
+    /// position, for detecting when the snake has filled them all
+    reachable: HashSet<Position>,
+
+    /// The window through which the level is shown
+    camera: Camera,
+
+    /// The area of the screen in which the level was most recently drawn,
+    /// for working out which cell a mouse click landed on
+    level_area: std::cell::Cell<Rect>,
+
+    /// The contents of the level as drawn on the most recent frame, so that
+    /// only the cells that have changed since need to be drawn on the next
+    layer: std::cell::RefCell<LevelLayer>,
+
+    /// The cell that the snake is heading towards after it was clicked on
+    /// with mouse steering enabled, if any
+    mouse_target: Option<Position>,
+
+    /// The most recent key press that steered the snake and when it (or an
+    /// autorepeat of it) was last received
+    turn_key: Option<(KeyEvent, Instant)>,
+
+    /// Has a key press steered the snake since the last tick?
+    turned_since_tick: bool,
+
+    /// Was the snake held in place by mud on the last tick?
+    mired: bool,
+
     /// Global data (options & high scores)
     globals: Globals,
 
@@ -57,107 +174,960 @@ pub(crate) struct Game<R = rand::rngs::ThreadRng> {
     /// next value will be calculated on the next call to
     /// [`Game::process_input()`]
     next_tick: Option<Instant>,
+
+    /// The time of the most recent input event or movement of the snake, from
+    /// which the idle timeout is measured
+    last_activity: Instant,
+
+    /// Is slow motion currently active?
+    slow_motion: bool,
+
+    /// Has slow motion been active at any point during the game?
+    used_slow_motion: bool,
+
+    /// Short-lived visual effects currently applied to cells of the level
+    effects: Vec<CellEffect>,
+
+    /// Statistics about the game so far
+    stats: GameStats,
+
+    /// In co-op relay mode, which player is steering the snake
+    relay: Option<Relay>,
+
+    /// The tick on which the last fruit was eaten (or 0 if none has been
+    /// eaten yet), for measuring the player's pace for adaptive difficulty
+    last_fruit_tick: u32,
+
+    /// The number of fruits eaten since adaptive difficulty last placed a new
+    /// obstacle
+    fruits_since_obstacle: u32,
+
+    /// A message currently displayed under the level, if any
+    toast: Option<Toast>,
+
+    /// Has the score overtaken the high score that was in place when the game
+    /// started?
+    record_pace: bool,
+
+    /// When the high score was last saved mid-game due to the
+    /// `live-high-score` setting, if ever
+    last_live_save: Option<Instant>,
+
+    /// An error that occurred while saving the high score mid-game, to be
+    /// shown once the current move is over (or, if the game ended on that
+    /// move, along with the game-over screen)
+    deferred_warning: Option<Warning>,
+
+    /// Has saving the high score mid-game failed?  If so, no further mid-game
+    /// saves are attempted, though the high score is still saved when the
+    /// game ends.
+    live_save_failed: bool,
+
+    /// The entry that the game has added to the high scores, if any.  Later
+    /// saves replace this entry rather than adding another.
+    saved_score: Option<HighScore>,
+
+    /// The number of ticks that have passed since the game started
+    ticks: u32,
+
+    /// The states of the game over the last few ticks, for rewinding after
+    /// the snake dies.  This is only recorded while `rewinds_left` is nonzero.
+    history: History,
+
+    /// The number of times remaining that the game can be rewound
+    rewinds_left: u32,
+
+    /// Has the game been rewound at any point?
+    used_rewind: bool,
+
+    /// A record of the game's end that has not yet been written to the game
+    /// history because the game could still be rewound, undoing the end
+    unrecorded: Option<GameRecord>,
+
+    /// The pop-up asking for a name under which to save the board as a
+    /// favorite, if it is being shown
+    favorite_prompt: Option<NamePrompt>,
+
+    /// Is this one player's game in a split-board match?  If so, crashing
+    /// costs the player a point and starts the snake over from its starting
+    /// position instead of ending the game.
+    respawn: bool,
 }
 
-impl Game<rand::rngs::ThreadRng> {
-    /// Create a new game from the given globals using the thread RNG
+impl Game<StdRng> {
+    /// Create a new game from the given globals using an RNG seeded as
+    /// directed by the `rng` setting
     pub(crate) fn new(globals: Globals) -> Self {
-        Game::new_with_rng(globals, rand::rng())
+        let seed = globals.config.game.rng.seed();
+        let mut game = Game::new_with_rng(globals, StdRng::seed_from_u64(seed));
+        game.seed = Some(seed);
+        game
+    }
+
+    /// Create a new game from the given globals using an RNG seeded with
+    /// `seed` regardless of the `rng` setting, as when replaying a favorite
+    /// board
+    pub(crate) fn new_with_seed(globals: Globals, seed: u64) -> Self {
+        let mut game = Game::new_with_rng(globals, StdRng::seed_from_u64(seed));
+        game.seed = Some(seed);
+        game.rng_mode = RngMode::Seeded(seed);
+        game
+    }
+
+    /// Create a new game played on the custom level `level`.  As the level
+    /// isn't described by the gameplay options, such games are not eligible
+    /// for high scores and are not recorded in the game history.
+    pub(crate) fn for_level(globals: Globals, level: CustomLevel) -> Self {
+        let rng = StdRng::seed_from_u64(globals.config.game.rng.seed());
+        let mut game = Game::blank(globals, level.bounds, rng);
+        game.high_score = None;
+        let mut map = LevelMap::new(level.bounds);
+        for &pos in &level.obstacles {
+            map.add_obstacle(pos);
+        }
+        game.level = Some(level);
+        game.start(map);
+        game
     }
 }
 
 impl<R: Rng> Game<R> {
     /// Create a new game from the given globals using the given RNG
     pub(crate) fn new_with_rng(globals: Globals, mut rng: R) -> Game<R> {
-        let mut map = LevelMap::new(globals.options.level_bounds());
-        if globals.options.obstacles {
-            map.set_obstacles(&mut rng);
+        let bounds = globals.options.level_bounds(globals.config.cell_aspect);
+        let layout = globals.options.layout;
+        let obstacles = globals.options.obstacles;
+        let terrain = globals.options.terrain;
+        let start = globals.options.start;
+        let cells = u32::from(bounds.width) * u32::from(bounds.height);
+        // Huge levels are generated in the background so that the interface
+        // doesn't freeze.  The background thread gets its own RNG seeded from
+        // the game's, so a game's seed still determines its level.
+        let loader = (cells > consts::BACKGROUND_GENERATION_CELLS
+            && (layout == LevelLayout::Maze || obstacles || terrain))
+            .then(|| {
+                LevelLoader::spawn(
+                    bounds,
+                    layout,
+                    obstacles,
+                    terrain,
+                    start,
+                    StdRng::from_rng(&mut rng),
+                )
+            });
+        // Until the real level is ready, the game is set up on an empty one
+        let mut game = Game::blank(globals, bounds, rng);
+        if let Some(loader) = loader {
+            game.state = GameState::Loading(loader);
+        } else {
+            let map = LevelMap::generate(bounds, layout, obstacles, terrain, start, &mut game.rng);
+            game.start(map);
         }
+        game
+    }
+
+    /// Create one player's game in a split-board match, played on `map` with
+    /// the given RNG.  Such games are not eligible for high scores, are not
+    /// recorded in the game history, and cannot be rewound.
+    fn for_split(globals: Globals, map: LevelMap, rng: R) -> Game<R> {
+        let mut game = Game::blank(globals, map.bounds(), rng);
+        game.high_score = None;
+        game.rewinds_left = 0;
+        game.respawn = true;
+        game.start(map);
+        game
+    }
+
+    /// Create a game with the given globals & RNG on an empty level with the
+    /// given bounds.  The game is not ready to play until
+    /// [`Game::start()`] is called with the real level.
+    fn blank(globals: Globals, bounds: Bounds, rng: R) -> Game<R> {
+        let map = LevelMap::new(bounds);
         let snake = map.new_snake();
-        let fruit_qty = globals.options.fruits.get();
+        let camera = Camera::new(globals.config.game.camera, map.size(), snake.head());
         let high_score = globals.high_scores.get(globals.options);
-        let mut game = Game {
+        let rewinds_left = globals.config.controls.rewinds;
+        let relay = globals.relay;
+        Game {
             rng,
+            seed: None,
+            rng_mode: globals.config.game.rng,
             score: 0,
+            combo: 1,
+            combo_ticks_left: 0,
             high_score,
+            rival: None,
             snake,
+            hydras: Vec::new(),
             fruits: HashSet::new(),
+            hydra_fruit: None,
+            power_ups: Vec::new(),
             state: GameState::Running,
+            reachable: map.reachable(),
             map,
+            level: None,
+            camera,
+            level_area: std::cell::Cell::new(Rect::ZERO),
+            layer: std::cell::RefCell::default(),
+            mouse_target: None,
+            turn_key: None,
+            turned_since_tick: false,
+            mired: false,
             globals,
             next_tick: None,
-        };
-        for _ in 0..fruit_qty {
-            game.place_fruit();
+            last_activity: Instant::now(),
+            slow_motion: false,
+            used_slow_motion: false,
+            effects: Vec::new(),
+            stats: if relay {
+                GameStats::for_relay()
+            } else {
+                GameStats::default()
+            },
+            relay: relay.then(Relay::new),
+            last_fruit_tick: 0,
+            fruits_since_obstacle: 0,
+            toast: None,
+            record_pace: false,
+            last_live_save: None,
+            deferred_warning: None,
+            live_save_failed: false,
+            saved_score: None,
+            ticks: 0,
+            history: History::default(),
+            rewinds_left,
+            used_rewind: false,
+            unrecorded: None,
+            favorite_prompt: None,
+            respawn: false,
         }
-        game
     }
 
-    /// Receive & handle the next input event.  If the game is currently
-    /// running and no event is received before [`Game::next_tick`] passes, the
-    /// snake advances and the method returns.
+    /// Put the snake at the starting position of the generated level `map`,
+    /// place the initial fruits, and set the game running
+    fn start(&mut self, map: LevelMap) {
+        self.map = map;
+        self.layer.get_mut().invalidate();
+        self.reachable = self.map.reachable();
+        self.snake = self.map.new_snake();
+        self.camera = Camera::new(
+            self.globals.config.game.camera,
+            self.map.size(),
+            self.snake.head(),
+        );
+        for _ in 0..self.globals.options.fruits.get() {
+            self.place_fruit();
+        }
+        self.state = GameState::Running;
+        self.next_tick = None;
+        self.last_activity = Instant::now();
+    }
+
+    /// Receive & handle the next input event from `events`.  If the game is
+    /// currently running and no event is received before [`Game::next_tick`]
+    /// passes, the snake advances and the method returns.  If the game is
+    /// paused or over and no event is received before the configured idle
+    /// timeout passes, the application returns to the main menu.
     ///
     /// Returns `Some(screen)` if the application should switch to a different
-    /// screen or quit.
-    pub(crate) fn process_input(&mut self) -> std::io::Result<Option<Screen>> {
-        if self.running() {
+    /// screen or quit.  If it does so, [`Game::record_end()`] should be called
+    /// first.
+    pub(crate) fn process_input(
+        &mut self,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<Option<Screen>> {
+        if let GameState::Loading(ref loader) = self.state {
+            if let Some(map) = loader.try_finish() {
+                self.start(map);
+                return Ok(None);
+            }
+            if events.poll(consts::SPINNER_PERIOD)? {
+                let event = self.read_event(events)?;
+                return Ok(self.handle_event(event));
+            }
+            if let GameState::Loading(ref mut loader) = self.state {
+                loader.spin();
+            }
+            return Ok(None);
+        }
+        if (self.running() || self.animating()) && self.globals.tick_source == TickSource::Timer {
+            let period = self.move_period();
             let when = *self
                 .next_tick
-                .get_or_insert_with(|| Instant::now() + consts::TICK_PERIOD);
+                .get_or_insert_with(|| Instant::now() + period);
             let wait = when.saturating_duration_since(Instant::now());
-            if wait.is_zero() || !poll(wait)? {
+            if wait.is_zero() || !events.poll(wait)? {
                 self.advance();
                 self.next_tick = None;
+                self.last_activity = Instant::now();
                 Ok(None)
             } else {
-                Ok(self.handle_event(read()?))
+                let event = self.read_event(events)?;
+                Ok(self.handle_event(event))
             }
         } else {
-            Ok(self.handle_event(read()?))
+            if let Some(deadline) = self.idle_deadline() {
+                let wait = deadline.saturating_duration_since(Instant::now());
+                if wait.is_zero() || !events.poll(wait)? {
+                    return Ok(Some(Screen::Main(crate::menu::MainMenu::new(
+                        self.globals.clone(),
+                    ))));
+                }
+            }
+            let event = self.read_event(events)?;
+            if self.is_step(&event) {
+                self.advance();
+                Ok(None)
+            } else {
+                Ok(self.handle_event(event))
+            }
+        }
+    }
+
+    /// Read the next input event from `events`, noting the time for the
+    /// purposes of the idle timeout
+    fn read_event(&mut self, events: &mut dyn EventSource) -> std::io::Result<Event> {
+        let event = events.read()?;
+        self.last_activity = Instant::now();
+        Ok(event)
+    }
+
+    /// If the game is paused or over and an idle timeout is configured,
+    /// return the time at which the game will be abandoned for the main menu
+    /// if no input is received
+    fn idle_deadline(&self) -> Option<Instant> {
+        let timeout = self.globals.config.game.idle_timeout();
+        if timeout.is_zero() {
+            return None;
+        }
+        match self.state {
+            GameState::Paused(_)
+            | GameState::ConfirmRestart(_)
+            | GameState::Interrupted(_)
+            | GameState::Dead(_)
+            | GameState::Exhausted(_) => Some(self.last_activity + timeout),
+            GameState::Loading(_)
+            | GameState::Running
+            | GameState::Resuming { .. }
+            | GameState::Dying { .. }
+            | GameState::Celebrating { .. } => None,
         }
     }
 
     /// Move the snake forwards and respond to any fruits or obstacles it came
     /// into contact with
     fn advance(&mut self) {
-        if !self.running() {
+        if let GameState::Dying {
+            ref mut ticks_left,
+            ref mut post_mortem,
+        } = self.state
+        {
+            *ticks_left = ticks_left.saturating_sub(1);
+            if *ticks_left == 0 {
+                let post_mortem = std::mem::take(post_mortem);
+                self.state = GameState::Dead(self.hold(post_mortem));
+            }
+            return;
+        }
+        if let GameState::Celebrating {
+            ref mut ticks_left,
+            ref mut post_mortem,
+        } = self.state
+        {
+            *ticks_left = ticks_left.saturating_sub(1);
+            if *ticks_left == 0 {
+                let post_mortem = std::mem::take(post_mortem);
+                self.state = GameState::Exhausted(self.hold(post_mortem));
+            }
             return;
         }
-        if !self.snake.advance(self.map.bounds()) {
-            self.state = GameState::Dead(self.finalize_score());
+        if let GameState::Resuming { ref mut ticks_left } = self.state {
+            *ticks_left = ticks_left.saturating_sub(1);
+            if *ticks_left == 0 {
+                self.state = GameState::Running;
+            }
             return;
         }
-        if self.fruits.remove(&self.snake.head()) {
-            self.score += 1;
-            self.snake.grow();
-            self.place_fruit();
-        } else if self.snake.body().contains(&self.snake.head())
-            || self.map.obstacles().contains(&self.snake.head())
-        {
-            self.state = GameState::Dead(self.finalize_score());
+        if !self.running() {
+            return;
+        }
+        self.turned_since_tick = false;
+        if self.rewinds_left > 0 {
+            self.history.push(self.checkpoint());
+        }
+        self.effects.retain_mut(|eff| {
+            eff.ticks_left = eff.ticks_left.saturating_sub(1);
+            eff.ticks_left > 0
+        });
+        self.snake.power_ups.retain_mut(|pu| match pu.ticks_left {
+            Some(ref mut ticks) => {
+                *ticks = ticks.saturating_sub(1);
+                *ticks > 0
+            }
+            None => true,
+        });
+        if let Some(relay) = self.relay.as_mut() {
+            relay.tick();
+        }
+        if let Some(toast) = self.toast.as_mut() {
+            toast.ticks_left = toast.ticks_left.saturating_sub(1);
+            if toast.ticks_left == 0 {
+                self.toast = None;
+            }
+        }
+        if self.combo_ticks_left > 0 {
+            self.combo_ticks_left -= 1;
+            if self.combo_ticks_left == 0 {
+                self.combo = 1;
+            }
+        }
+        if let Some(bot) = self.globals.bot.clone() {
+            match bot.steer(&self.snapshot()) {
+                Ok(Some(direction)) => self.steer(direction),
+                Ok(None) => (),
+                Err(e) => {
+                    self.forfeit(e);
+                    return;
+                }
+            }
+        } else {
+            self.follow_mouse_target();
+        }
+        self.ticks = self.ticks.saturating_add(1);
+        // Mud holds the snakes in place on every other tick that the player's
+        // snake spends in it; the player's snake sets the pace for the others
+        if self.map.terrain_at(self.snake.head()) == Some(Terrain::Mud) {
+            self.mired = !self.mired;
+            if self.mired {
+                return;
+            }
+        } else {
+            self.mired = false;
+        }
+        // Move all of the snakes before checking for collisions so that the
+        // snakes can run into each other's new positions
+        let bounds = self.map.bounds();
+        let sliding = self.snakes().map(|sn| self.on_ice(sn)).collect::<Vec<_>>();
+        let mut old_tails = Vec::with_capacity(self.hydras.len() + 1);
+        for i in 0..=self.hydras.len() {
+            let Some(snake) = self.snake_mut(i) else {
+                break;
+            };
+            let old_len = snake.body().len();
+            old_tails.push(snake.body().front().copied());
+            let moved = if sliding.get(i) == Some(&true) {
+                snake.slide(bounds)
+            } else {
+                snake.advance(bounds)
+            };
+            if !moved {
+                self.crash(EndCause::Wall, i);
+                return;
+            }
+            // Only show the grow-in glyph for growth caused by eating, not for
+            // the snake's initial emergence
+            let grown_tail = if snake.body().len() > old_len.max(consts::INITIAL_SNAKE_LENGTH) {
+                snake.body().front().copied()
+            } else {
+                None
+            };
+            if let Some(tail) = grown_tail {
+                self.effects.push(CellEffect::new(tail, EffectKind::GrowIn));
+            }
+        }
+        self.camera.follow(self.map.size(), self.snake.head());
+        self.stats.record_move(
+            self.tick_period(),
+            self.snake.heading(),
+            self.snake.body().len() + 1,
+        );
+        self.stats.record_fill(self.fill_percent());
+        for (i, old_tail) in old_tails.into_iter().enumerate() {
+            let Some(head) = self.snake_mut(i).map(|snake| snake.head()) else {
+                break;
+            };
+            if let Some(j) = self.power_ups.iter().position(|pu| pu.pos == head) {
+                // Power-ups eaten by any snake take effect on the player's
+                // snake, which sets the pace for the others
+                let pu = self.power_ups.swap_remove(j);
+                self.snake.gain(pu.kind);
+                self.effects
+                    .push(CellEffect::new(head, EffectKind::EatFlash));
+            } else if self.fruits.remove(&head) {
+                if self.globals.options.combos {
+                    if self.combo_ticks_left > 0 {
+                        self.combo = self.combo.saturating_add(1);
+                    }
+                    self.combo_ticks_left = consts::COMBO_WINDOW_TICKS;
+                }
+                self.score = self.score.saturating_add(self.combo);
+                self.check_record_pace();
+                self.save_live_high_score();
+                self.stats.record_fruit();
+                if let Some(relay) = self.relay {
+                    self.stats.record_player_fruit(relay.player());
+                }
+                if self.globals.options.adaptive {
+                    self.adapt_to_fruit();
+                }
+                if let Some(snake) = self.snake_mut(i) {
+                    snake.grow();
+                }
+                if let Some(pos) = self.place_fruit() {
+                    self.effects.push(CellEffect::new(pos, EffectKind::Spawn));
+                }
+                if self.globals.options.power_ups
+                    && self.power_ups.is_empty()
+                    && self.rng.random_bool(consts::POWER_UP_PROBABILITY)
+                {
+                    let kinds = PowerUpKind::iter().collect::<Vec<_>>();
+                    if let Ok(&kind) = kinds.choose_weighted(&mut self.rng, |k| k.weight()) {
+                        self.place_power_up(kind);
+                    }
+                }
+                if self.globals.options.hydra
+                    && self.hydra_fruit.is_none()
+                    && self.hydras.len() < Mirror::LENGTH
+                    && self.rng.random_bool(consts::HYDRA_FRUIT_PROBABILITY)
+                {
+                    self.hydra_fruit = self.random_free_cell();
+                }
+                self.effects
+                    .push(CellEffect::new(head, EffectKind::EatFlash));
+            } else if self.hydra_fruit == Some(head) {
+                self.hydra_fruit = None;
+                self.spawn_hydra();
+                self.effects
+                    .push(CellEffect::new(head, EffectKind::EatFlash));
+            } else if self.forbidden(head) {
+                // Not even invincibility lets the snakes break the rules
+                self.crash(EndCause::Forbidden, i);
+                return;
+            } else if self.snake.invincible_ticks().is_some() {
+                // The snakes pass harmlessly through everything
+            } else if self.map.obstacles().contains(&head) && self.snake.consume(PowerUpKind::Plow)
+            {
+                self.map.remove_obstacle(head);
+                self.reachable = self.map.reachable();
+            } else if self.map.obstacles().contains(&head)
+                || self
+                    .snakes()
+                    .nth(i)
+                    .is_some_and(|sn| !self.map.can_enter(head, sn.heading()))
+            {
+                // If any snake collides with something (including going the
+                // wrong way through a gate), they all die together
+                self.crash(EndCause::Obstacle, i);
+                return;
+            } else if self.collides(i, head)
+                || (self.globals.options.strict_collisions && old_tail == Some(head))
+            {
+                self.crash(EndCause::Body, i);
+                return;
+            }
         }
-        if self.fruits.is_empty() {
-            self.state = GameState::Exhausted(self.finalize_score());
+        let goal = self.level.as_ref().and_then(|level| {
+            objectives::check(&level.objectives, self.score, self.stats.elapsed())
+        });
+        let perfect = self.fills_reachable();
+        if goal == Some(EndCause::TimeUp) {
+            self.die(EndCause::TimeUp);
+        } else if let Some(cause) = goal {
+            self.state = GameState::Celebrating {
+                ticks_left: consts::VICTORY_ANIMATION_TICKS,
+                post_mortem: self.finalize_score(cause),
+            };
+        } else if self.fruits.is_empty() || perfect {
+            let mut post_mortem = self.finalize_score(if perfect {
+                EndCause::Perfect
+            } else {
+                EndCause::BoardFull
+            });
+            post_mortem.perfect = perfect;
+            self.state = GameState::Celebrating {
+                ticks_left: consts::VICTORY_ANIMATION_TICKS,
+                post_mortem,
+            };
+        } else if let Some(warning) = self.deferred_warning.take() {
+            self.state = GameState::Interrupted(warning);
+        }
+    }
+
+    /// Do the snakes between them occupy every cell of the level that can be
+    /// reached from the snake's starting position?
+    fn fills_reachable(&self) -> bool {
+        let length = self.snakes().map(|sn| sn.body().len() + 1).sum::<usize>();
+        if length < self.reachable.len() {
+            return false;
+        }
+        let mut cells = HashSet::with_capacity(length);
+        for snake in self.snakes() {
+            cells.insert(snake.head());
+            cells.extend(snake.body().iter().copied());
         }
+        self.reachable.is_subset(&cells)
     }
 
     /// Place a fruit at a randomly-selected empty position in the level, if
-    /// any
-    fn place_fruit(&mut self) {
+    /// any, and return the position
+    fn place_fruit(&mut self) -> Option<Position> {
+        let occupied = self.occupied();
+        let free = self
+            .map
+            .bounds()
+            .positions()
+            .filter(move |p| !occupied.contains(p));
+        let bounds = self.map.bounds();
+        let head = self.snake.head();
+        let pos = match self.globals.options.spawn {
+            SpawnStrategy::Random => free.choose(&mut self.rng),
+            SpawnStrategy::FarFromSnake => {
+                let candidates = free.collect::<Vec<_>>();
+                let farthest = candidates.iter().map(|&p| bounds.distance(head, p)).max();
+                candidates
+                    .into_iter()
+                    .filter(|&p| Some(bounds.distance(head, p)) == farthest)
+                    .choose(&mut self.rng)
+            }
+            SpawnStrategy::NearWalls => {
+                let candidates = free.collect::<Vec<_>>();
+                let near_walls = candidates
+                    .iter()
+                    .copied()
+                    .filter(|&p| self.map.is_near_wall(p))
+                    .choose(&mut self.rng);
+                // Fall back to uniform placement once the cells next to walls
+                // are all taken
+                near_walls.or_else(|| candidates.into_iter().choose(&mut self.rng))
+            }
+            SpawnStrategy::Spread => {
+                let candidates = free.collect::<Vec<_>>();
+                let vacant = bounds
+                    .regions(self.globals.options.fruits.get())
+                    .into_iter()
+                    .filter(|r| !self.fruits.iter().any(|&f| r.contains(f)))
+                    .collect::<Vec<_>>();
+                let spread = candidates
+                    .iter()
+                    .copied()
+                    .filter(|&p| vacant.iter().any(|r| r.contains(p)))
+                    .choose(&mut self.rng);
+                // Fall back to uniform placement if the vacant regions are
+                // full
+                spread.or_else(|| candidates.into_iter().choose(&mut self.rng))
+            }
+        };
+        self.fruits.extend(pos);
+        pos
+    }
+
+    /// Update the adaptive difficulty after a fruit is eaten, placing a new
+    /// obstacle if the difficulty calls for one and the Obstacles option is
+    /// set
+    fn adapt_to_fruit(&mut self) {
+        let difficulty = &mut self.globals.difficulty;
+        difficulty.record_fruit(self.ticks.saturating_sub(self.last_fruit_tick));
+        self.last_fruit_tick = self.ticks;
+        self.fruits_since_obstacle = self.fruits_since_obstacle.saturating_add(1);
+        if self.globals.options.obstacles
+            && difficulty
+                .obstacle_interval()
+                .is_some_and(|n| self.fruits_since_obstacle >= n)
+        {
+            self.fruits_since_obstacle = 0;
+            self.place_obstacle();
+        }
+    }
+
+    /// Place a new obstacle in a random unoccupied cell that isn't near the
+    /// head of any snake, if there is one
+    fn place_obstacle(&mut self) {
+        let occupied = self.occupied();
+        let bounds = self.map.bounds();
+        let heads = self.snakes().map(Snake::head).collect::<Vec<_>>();
+        let pos = bounds
+            .positions()
+            .filter(|p| !occupied.contains(p))
+            .filter(|&p| {
+                heads
+                    .iter()
+                    .all(|&h| bounds.distance(h, p) > consts::ADAPTIVE_OBSTACLE_CLEARANCE)
+            })
+            .choose(&mut self.rng);
+        if let Some(pos) = pos {
+            self.map.add_obstacle(pos);
+            self.reachable = self.map.reachable();
+            self.layer.get_mut().invalidate();
+        }
+    }
+
+    /// Place a power-up of the given kind at a randomly-selected empty
+    /// position in the level, if any
+    fn place_power_up(&mut self, kind: PowerUpKind) {
+        if let Some(pos) = self.random_free_cell() {
+            self.power_ups.push(PowerUp { pos, kind });
+        }
+    }
+
+    /// Return a randomly-selected empty position in the level, if any
+    fn random_free_cell(&mut self) -> Option<Position> {
+        let occupied = self.occupied();
+        self.map
+            .bounds()
+            .positions()
+            .filter(|p| !occupied.contains(p))
+            .choose(&mut self.rng)
+    }
+
+    /// Spawn an extra snake that mirrors the player's snake, using the first
+    /// kind of mirroring not already in use.  The new snake starts out at the
+    /// mirror image of the player's snake's head, or at a random empty
+    /// position if that cell is occupied.
+    fn spawn_hydra(&mut self) {
+        let Some(mirror) = Mirror::iter().find(|&m| self.hydras.iter().all(|h| h.mirror != m))
+        else {
+            return;
+        };
+        let mirrored = mirror.position(self.snake.head(), self.map.size());
+        let pos = if self.occupied().contains(&mirrored) {
+            self.random_free_cell()
+        } else {
+            Some(mirrored)
+        };
+        if let Some(pos) = pos {
+            let snake = Snake::new(pos, mirror.direction(self.snake.direction));
+            self.hydras.push(Hydra { mirror, snake });
+        }
+    }
+
+    /// Return a record of the current state of the game for rewinding to
+    /// later
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            score: self.score,
+            combo: self.combo,
+            combo_ticks_left: self.combo_ticks_left,
+            snake: self.snake.clone(),
+            hydras: self.hydras.clone(),
+            fruits: self.fruits.clone(),
+            hydra_fruit: self.hydra_fruit,
+            power_ups: self.power_ups.clone(),
+            map: self.map.clone(),
+            stats: self.stats.clone(),
+            ticks: self.ticks,
+        }
+    }
+
+    /// Return the positions of all cells in the level that are occupied by
+    /// the snakes, fruits, power-ups, obstacles, gates, or forbidden cells
+    fn occupied(&self) -> HashSet<Position> {
         let mut occupied = &self.fruits | self.map.obstacles();
-        occupied.insert(self.snake.head());
-        occupied.extend(self.snake.body().iter().copied());
-        self.fruits.extend(
-            self.map
-                .bounds()
-                .positions()
-                .filter(move |p| !occupied.contains(p))
-                .choose(&mut self.rng),
-        );
+        if let Some(ref level) = self.level {
+            occupied.extend(&level.objectives.forbidden);
+        }
+        occupied.extend(self.map.gates().map(|(pos, _)| pos));
+        occupied.extend(self.hydra_fruit);
+        occupied.extend(self.power_ups.iter().map(|pu| pu.pos));
+        for snake in self.snakes() {
+            occupied.insert(snake.head());
+            occupied.extend(snake.body().iter().copied());
+        }
+        occupied
     }
 }
 
 impl<R> Game<R> {
+    /// Is `pos` one of the cells that the custom level's objectives forbid the
+    /// snake from touching?
+    fn forbidden(&self, pos: Position) -> bool {
+        self.level
+            .as_ref()
+            .is_some_and(|level| level.objectives.forbidden.contains(&pos))
+    }
+
+    /// Return an iterator over all of the snakes in the game, starting with
+    /// the player's snake and followed by any extra snakes
+    fn snakes(&self) -> impl Iterator<Item = &Snake> {
+        std::iter::once(&self.snake).chain(self.hydras.iter().map(|h| &h.snake))
+    }
+
+    /// Is `snake`'s head on ice, so that it will keep moving the way it last
+    /// moved on the next tick rather than turning?
+    fn on_ice(&self, snake: &Snake) -> bool {
+        self.map.terrain_at(snake.head()) == Some(Terrain::Ice)
+    }
+
+    /// Return the direction in which the player's snake will move on the
+    /// next tick
+    fn next_direction(&self) -> Direction {
+        if self.on_ice(&self.snake) {
+            self.snake.heading()
+        } else {
+            self.snake.direction
+        }
+    }
+
+    /// Return the percentage of the cells of the level that can be reached
+    /// from the snake's starting position that the snakes between them
+    /// occupy, rounded down
+    fn fill_percent(&self) -> u8 {
+        let length = self.snakes().map(|sn| sn.body().len() + 1).sum::<usize>();
+        let percent = length
+            .saturating_mul(100)
+            .checked_div(self.reachable.len())
+            .unwrap_or_default();
+        u8::try_from(percent.min(100)).unwrap_or(100)
+    }
+
+    /// Return a mutable reference to the `i`-th snake in the order of
+    /// [`Game::snakes()`], if there is one
+    fn snake_mut(&mut self, i: usize) -> Option<&mut Snake> {
+        match i.checked_sub(1) {
+            None => Some(&mut self.snake),
+            Some(j) => self.hydras.get_mut(j).map(|h| &mut h.snake),
+        }
+    }
+
+    /// Returns `true` if `head`, the position of the head of the `i`-th
+    /// snake, is on the body of any snake or on the head of another snake
+    fn collides(&self, i: usize, head: Position) -> bool {
+        self.snakes()
+            .enumerate()
+            .any(|(j, snake)| snake.body_contains(head) || (j != i && snake.head() == head))
+    }
+
+    /// If the snake is heading towards a cell that was clicked on, turn it
+    /// onto the next leg of the shortest route there.  The destination is
+    /// forgotten once it has been reached or if no route to it is open.
+    fn follow_mouse_target(&mut self) {
+        let Some(target) = self.mouse_target else {
+            return;
+        };
+        let mut blocked = self.map.obstacles().clone();
+        blocked.extend(self.map.gates().map(|(pos, _)| pos));
+        for snake in self.snakes() {
+            blocked.insert(snake.head());
+            blocked.extend(snake.body().iter().copied());
+        }
+        match pathfind::first_step(self.map.bounds(), self.snake.head(), target, &blocked) {
+            Some(direction) => self.steer(direction),
+            None => self.mouse_target = None,
+        }
+    }
+
+    /// Turn the player's snake to face `direction`, turning any extra snakes
+    /// to face the mirror images of `direction`
+    fn steer(&mut self, direction: Direction) {
+        self.snake.turn(direction);
+        for hydra in &mut self.hydras {
+            hydra.snake.turn(hydra.mirror.direction(direction));
+        }
+    }
+
+    /// Return a mutable reference to the screen's global data
+    pub(crate) fn globals_mut(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+
+    /// If the snake is currently being moved by a timer, return the number of
+    /// ticks that have passed so far and the current time between ticks;
+    /// otherwise, return `None`
+    pub(crate) fn tick_timing(&self) -> Option<(u32, Duration)> {
+        (self.running() && self.globals.tick_source == TickSource::Timer)
+            .then(|| (self.ticks, self.tick_period()))
+    }
+
+    /// Return a description of the current state of the game for sending to a
+    /// bot or broadcast clients
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        let bounds = self.map.bounds();
+        let mut fruits = self
+            .fruits
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+        fruits.sort_unstable();
+        // Forbidden cells are as deadly as obstacles, so bots are shown them
+        // as such
+        let mut obstacles = self
+            .map
+            .obstacles()
+            .iter()
+            .chain(
+                self.level
+                    .iter()
+                    .flat_map(|level| &level.objectives.forbidden),
+            )
+            .copied()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+        obstacles.sort_unstable();
+        let terrain = |kind| {
+            let mut tiles = self
+                .map
+                .terrain()
+                .iter()
+                .filter(|&(_, &t)| t == kind)
+                .map(|(&pos, _)| pos.into())
+                .collect::<Vec<_>>();
+            tiles.sort_unstable();
+            tiles
+        };
+        Snapshot {
+            tick: self.ticks,
+            width: bounds.width,
+            height: bounds.height,
+            wraparound: bounds.wrap,
+            state: match self.state {
+                GameState::Running => SnapshotState::Running,
+                // The snake isn't moving while the level is being generated
+                GameState::Loading(_)
+                | GameState::Paused(_)
+                | GameState::ConfirmRestart(_)
+                | GameState::Interrupted(_)
+                | GameState::Resuming { .. } => SnapshotState::Paused,
+                GameState::Dying { .. }
+                | GameState::Dead(_)
+                | GameState::Celebrating { .. }
+                | GameState::Exhausted(_) => SnapshotState::Over,
+            },
+            score: self.score,
+            high_score: self.high_score.map(NonZeroU32::get),
+            snake: SnakeSnapshot::from(&self.snake),
+            hydras: self
+                .hydras
+                .iter()
+                .map(|h| SnakeSnapshot::from(&h.snake))
+                .collect(),
+            fruits,
+            hydra_fruit: self.hydra_fruit.map(Into::into),
+            obstacles,
+            mud: terrain(Terrain::Mud),
+            ice: terrain(Terrain::Ice),
+            gates: {
+                let mut gates = self
+                    .map
+                    .gates()
+                    .map(|(pos, direction)| GateSnapshot {
+                        pos: pos.into(),
+                        direction,
+                    })
+                    .collect::<Vec<_>>();
+                gates.sort_unstable_by_key(|g| g.pos);
+                gates
+            },
+            power_ups: self
+                .power_ups
+                .iter()
+                .map(|pu| PowerUpSnapshot {
+                    pos: pu.pos.into(),
+                    kind: pu.kind,
+                })
+                .collect(),
+        }
+    }
+
     /// Draw the game on the given frame
     pub(crate) fn draw(&self, frame: &mut Frame<'_>) {
         frame.render_widget(self, frame.area());
@@ -168,17 +1138,57 @@ impl<R> Game<R> {
     /// Returns `Some(screen)` if the application should switch to a different
     /// screen or quit.
     fn handle_event(&mut self, event: Event) -> Option<Screen> {
+        if let Some(ref mut prompt) = self.favorite_prompt {
+            match prompt.handle_key(event.as_key_press_event()?)? {
+                NameOutcome::Save(name) => {
+                    self.favorite_prompt = None;
+                    self.add_favorite(name);
+                }
+                NameOutcome::Skip => self.favorite_prompt = None,
+                NameOutcome::Quit => return Some(Screen::Quit),
+            }
+            return None;
+        }
+        if event.as_key_press_event().is_some() && !self.running() {
+            // Toasts only expire on their own while the game is running
+            self.toast = None;
+        }
+        if matches!(self.state, GameState::Paused(_)) && self.held_turn_key(&event) {
+            return None;
+        }
         match self.state {
             GameState::Running => {
-                if event == Event::FocusLost {
+                if self.pauses_for(&event) {
                     self.pause();
+                } else if let Event::Mouse(mouse) = event {
+                    self.click(mouse);
                 } else {
-                    match Command::from_key_event(event.as_key_press_event()?)? {
+                    let ev = event.as_key_press_event()?;
+                    if self.globals.config.controls.restart.matches(ev) {
+                        if self.globals.config.controls.confirm_restart {
+                            self.state =
+                                GameState::ConfirmRestart(Confirm::new(tr(Msg::ConfirmRestart)));
+                        } else {
+                            return Some(self.restart());
+                        }
+                        return None;
+                    }
+                    if self.globals.config.controls.slow_motion.matches(ev) {
+                        self.slow_motion = !self.slow_motion;
+                        self.used_slow_motion |= self.slow_motion;
+                        return None;
+                    }
+                    // When a bot is steering, the directional keys do nothing,
+                    // and in relay mode, only the keys of the player whose
+                    // turn it is do anything
+                    let steering = self.globals.bot.is_none()
+                        && self.relay.is_none_or(|relay| relay.steers(ev));
+                    match Command::from_key_event(ev)? {
                         Command::Quit => return Some(Screen::Quit),
-                        Command::Up => self.snake.turn(Direction::North),
-                        Command::Left => self.snake.turn(Direction::West),
-                        Command::Down => self.snake.turn(Direction::South),
-                        Command::Right => self.snake.turn(Direction::East),
+                        Command::Up if steering => self.steer_by_key(Direction::North, ev),
+                        Command::Left if steering => self.steer_by_key(Direction::West, ev),
+                        Command::Down if steering => self.steer_by_key(Direction::South, ev),
+                        Command::Right if steering => self.steer_by_key(Direction::East, ev),
                         Command::Esc => self.pause(),
                         _ => (),
                     }
@@ -186,7 +1196,8 @@ impl<R> Game<R> {
             }
             GameState::Paused(ref mut paused) => match paused.handle_event(event)? {
                 PauseOpt::Resume => self.state = GameState::Running,
-                PauseOpt::Restart => return Some(Screen::Game(Game::new(self.globals.clone()))),
+                PauseOpt::Restart => return Some(self.restart()),
+                PauseOpt::Favorite => self.favorite_prompt = Some(NamePrompt::favorite()),
                 PauseOpt::MainMenu => {
                     return Some(Screen::Main(crate::menu::MainMenu::new(
                         self.globals.clone(),
@@ -194,21 +1205,113 @@ impl<R> Game<R> {
                 }
                 PauseOpt::Quit => return Some(Screen::Quit),
             },
+            GameState::Interrupted(ref mut warning) => {
+                match warning.handle_key(event.as_key_press_event()?)? {
+                    WarningOutcome::Dismissed => {
+                        self.state = GameState::Resuming {
+                            ticks_left: consts::RESUME_COUNTDOWN_TICKS,
+                        };
+                    }
+                    WarningOutcome::Quit => return Some(Screen::Quit),
+                }
+            }
+            GameState::Resuming { .. } => {
+                if self.pauses_for(&event) {
+                    self.pause();
+                } else {
+                    match Command::from_key_event(event.as_key_press_event()?)? {
+                        Command::Quit => return Some(Screen::Quit),
+                        Command::Esc => self.pause(),
+                        _ => (),
+                    }
+                }
+            }
+            GameState::ConfirmRestart(ref mut confirm) => {
+                let cmd = Command::from_key_event(event.as_key_press_event()?)?;
+                match confirm.handle_command(cmd)? {
+                    ConfirmOutcome::Yes => return Some(self.restart()),
+                    ConfirmOutcome::No => self.state = GameState::Running,
+                    ConfirmOutcome::Quit => return Some(Screen::Quit),
+                }
+            }
+            GameState::Loading(_) | GameState::Dying { .. } | GameState::Celebrating { .. } => {
+                if Command::from_key_event(event.as_key_press_event()?)? == Command::Quit {
+                    return Some(Screen::Quit);
+                }
+            }
             GameState::Dead(PostMortem {
-                ref mut warning, ..
+                ref mut warning,
+                ref mut name_prompt,
+                held_until,
+                ..
             })
             | GameState::Exhausted(PostMortem {
-                ref mut warning, ..
+                ref mut warning,
+                ref mut name_prompt,
+                held_until,
+                ..
             }) => {
-                let cmd = Command::from_key_event(event.as_key_press_event()?)?;
-                if let Some(wrn) = warning {
-                    match wrn.handle_command(cmd)? {
+                let ev = event.as_key_press_event()?;
+                if held_until.is_some_and(|t| Instant::now() < t) {
+                    // Keys pressed in the moments before the game ended are
+                    // ignored, except for the interrupt key
+                    if Command::from_key_event(ev)? == Command::Quit {
+                        return Some(Screen::Quit);
+                    }
+                } else if let Some(wrn) = warning {
+                    match wrn.handle_key(ev)? {
                         WarningOutcome::Dismissed => *warning = None,
                         WarningOutcome::Quit => return Some(Screen::Quit),
                     }
+                } else if let Some(prompt) = name_prompt {
+                    match prompt.handle_key(ev)? {
+                        NameOutcome::Save(name) => {
+                            *name_prompt = None;
+                            if let Some(ref mut hs) = self.saved_score {
+                                self.globals.high_scores.set_name(
+                                    self.globals.options,
+                                    hs,
+                                    name.clone(),
+                                );
+                                hs.name = Some(name);
+                            }
+                            *warning = self
+                                .globals
+                                .config
+                                .save_high_scores(&self.globals.high_scores)
+                                .err()
+                                .map(Warning::from);
+                        }
+                        NameOutcome::Skip => *name_prompt = None,
+                        NameOutcome::Quit => return Some(Screen::Quit),
+                    }
+                } else if self.globals.kiosk {
+                    // Kiosk games always end with a return to the main menu so
+                    // that the next player starts from there
+                    return Some(Screen::Main(crate::menu::MainMenu::new(
+                        self.globals.clone(),
+                    )));
+                } else if self.globals.config.controls.restart.matches(ev) {
+                    return Some(self.restart());
+                } else if let Some(options) = quick_option(self.globals.options, ev) {
+                    // As in the main menu, the changed options are saved
+                    // before a game is started with them
+                    self.globals.options = options;
+                    match self
+                        .globals
+                        .config
+                        .save_options(self.globals.saved_options())
+                    {
+                        Ok(()) => return Some(self.restart_reconfigured()),
+                        Err(e) => *warning = Some(Warning::from(e)),
+                    }
                 } else {
-                    match Command::from_key_event(event.as_key_press_event()?)? {
-                        Command::R => return Some(Screen::Game(Game::new(self.globals.clone()))),
+                    match Command::from_key_event(ev)? {
+                        Command::R => return Some(self.restart()),
+                        Command::Z if self.can_rewind() => self.rewind(),
+                        Command::F if self.can_favorite() => {
+                            self.favorite_prompt = Some(NamePrompt::favorite());
+                        }
                         Command::M => {
                             return Some(Screen::Main(crate::menu::MainMenu::new(
                                 self.globals.clone(),
@@ -223,609 +1326,4083 @@ impl<R> Game<R> {
         None
     }
 
-    /// Check for a new high score and, if there is one, update the high scores
-    /// and write them to disk.
-    ///
-    /// Any errors that occur while updating the high score file are converted
-    /// into a [`Warning`] for display.
-    fn finalize_score(&mut self) -> PostMortem {
-        if let Some(score) = self.new_high_score() {
-            self.globals.high_scores.set(self.globals.options, score);
-            let warning = self
-                .globals
-                .config
-                .save_high_scores(&self.globals.high_scores)
-                .err()
-                .map(Warning::from);
-            PostMortem {
-                new_high_score: true,
-                warning,
-            }
-        } else {
-            PostMortem {
-                new_high_score: false,
-                warning: None,
-            }
+    /// Turn the snake in response to the directional key press `ev`,
+    /// abandoning any route to a clicked-on cell.  If the same key has
+    /// already steered the snake since the last tick, the press is an
+    /// autorepeat that would change nothing, and it is dropped.
+    fn steer_by_key(&mut self, direction: Direction, ev: KeyEvent) {
+        let repeat = self.turn_key.is_some_and(|(key, _)| key == ev);
+        self.turn_key = Some((ev, Instant::now()));
+        if repeat && self.turned_since_tick {
+            return;
         }
+        self.turned_since_tick = true;
+        self.mouse_target = None;
+        self.steer(direction);
     }
 
-    /// If the score exceeds the current high score, return the new high score.
-    fn new_high_score(&self) -> Option<NonZeroU32> {
-        NonZeroU32::new(self.score).filter(|&score| self.high_score.is_none_or(|hs| hs < score))
+    /// Is `event` an autorepeat of the key that last steered the snake,
+    /// arriving while the game is paused?  Such repeats come from the key
+    /// still being held down when the game was paused, and they are ignored
+    /// so that they don't move the selection in the pause menu.
+    fn held_turn_key(&mut self, event: &Event) -> bool {
+        let Some(ev) = event.as_key_press_event() else {
+            return false;
+        };
+        let Some((key, last)) = self.turn_key else {
+            return false;
+        };
+        let now = Instant::now();
+        if key == ev && now.saturating_duration_since(last) < consts::KEY_REPEAT_WINDOW {
+            self.turn_key = Some((key, now));
+            true
+        } else {
+            self.turn_key = None;
+            false
+        }
     }
 
-    /// Is the game currently running (and not paused or over?)
-    fn running(&self) -> bool {
-        self.state == GameState::Running
+    /// Handle a mouse event received while the game is running.  If mouse
+    /// steering is enabled and no bot is steering, a left click on a cell of
+    /// the level makes the snake head towards that cell.
+    fn click(&mut self, mouse: MouseEvent) {
+        if !self.globals.config.game.mouse_steering
+            || self.globals.bot.is_some()
+            || mouse.kind != MouseEventKind::Down(MouseButton::Left)
+        {
+            return;
+        }
+        let area = self.level_area.get();
+        let Some(x) = mouse.column.checked_sub(area.x) else {
+            return;
+        };
+        let Some(y) = mouse.row.checked_sub(area.y) else {
+            return;
+        };
+        if x >= area.width || y >= area.height {
+            return;
+        }
+        let view = self.viewport();
+        if let (Some(x), Some(y)) = (view.x.checked_add(x), view.y.checked_add(y)) {
+            self.mouse_target = Some(Position { x, y });
+        }
     }
 
-    /// Pause the game
-    fn pause(&mut self) {
-        self.state = GameState::Paused(Paused::new());
+    /// End the game due to a collision, starting the death animation
+    fn die(&mut self, cause: EndCause) {
+        let post_mortem = self.finalize_score(cause);
+        self.state = GameState::Dying {
+            ticks_left: consts::DEATH_ANIMATION_TICKS,
+            post_mortem,
+        };
     }
-}
 
-impl<R> Widget for &Game<R> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let display = get_display_area(area);
-        let [score_area, block_area, msg1_area, msg2_area] = Layout::vertical([
-            Constraint::Length(1),
-            Constraint::Fill(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ])
-        .areas(display);
+    /// End the game due to the `i`-th snake (in the order of
+    /// [`Game::snakes()`]) running into `cause`, recording where it crashed.
+    /// In a split-board match, the player loses a point and the snake starts
+    /// over instead (see [`Game::respawn`]).
+    fn crash(&mut self, cause: EndCause, i: usize) {
+        if self.respawn {
+            self.snake = self.map.new_snake();
+            self.hydras.clear();
+            self.mouse_target = None;
+            self.score = self.score.saturating_sub(1);
+            self.combo = 1;
+            self.combo_ticks_left = 0;
+            self.layer.get_mut().invalidate();
+            return;
+        }
+        let crash = self.snakes().nth(i).map(|snake| Crash {
+            cause,
+            head: snake.head(),
+            direction: snake.heading(),
+        });
+        self.die(cause);
+        if let GameState::Dying {
+            ref mut post_mortem,
+            ..
+        } = self.state
+        {
+            post_mortem.crash = crash;
+        }
+        if self.globals.options.adaptive {
+            self.globals.difficulty.record_death();
+        }
+        if self.globals.config.game.crash_tips
+            && self.globals.bot.is_none()
+            && self.globals.deaths.record(Instant::now())
+        {
+            if let Some(msg) = hints::hint_for(cause) {
+                self.show_toast(String::from(tr(msg)));
+            }
+        }
+    }
 
-        Line::styled(format!(" Score: {}", self.score), consts::SCORE_BAR_STYLE)
-            .render(score_area, buf);
+    /// End the game because the bot steering the snake failed to make a valid
+    /// move in time, reporting the failure in a [`Warning`]
+    fn forfeit(&mut self, e: BotError) {
+        self.die(EndCause::Forfeit);
+        if let GameState::Dying {
+            ref mut post_mortem,
+            ..
+        } = self.state
+        {
+            post_mortem.warning = Some(Warning::from(e));
+        }
+    }
+
+    /// Set the time until which input is ignored after the game ends, based
+    /// on the configured hold time
+    fn hold(&self, mut post_mortem: PostMortem) -> PostMortem {
+        let hold = self.globals.config.game.game_over_hold();
+        if !hold.is_zero() {
+            post_mortem.held_until = Some(Instant::now() + hold);
+        }
+        post_mortem
+    }
 
-        let hs_str = match self.high_score {
-            Some(hs) => format!("High Score: {hs} "),
-            None => String::from("High Score: - "),
+    /// Record the game, which ended due to `cause`, in the game history & as
+    /// the last game played, and check for a new high score and, if there is
+    /// one, update the high scores and write them to disk.  Scores achieved
+    /// by bots or in relay games are not recorded as high scores.
+    ///
+    /// Any errors that occur while updating the history or high score file
+    /// are converted into a [`Warning`] for display.
+    fn finalize_score(&mut self, cause: EndCause) -> PostMortem {
+        let record = self.history_record(cause);
+        let history_warning = if self.rewinds_left > 0 && !self.history.is_empty() {
+            // Only the final end of a game is recorded, so hold off until
+            // the player either rewinds or leaves the game
+            self.unrecorded.clone_from(&record);
+            None
+        } else {
+            record
+                .as_ref()
+                .and_then(|record| self.globals.config.append_history(record).err())
+                .map(Warning::from)
         };
-        Line::styled(hs_str, consts::SCORE_BAR_STYLE)
-            .right_aligned()
-            .render(score_area, buf);
+        self.globals.last_game = record;
+        let mut post_mortem = self.update_high_score();
+        post_mortem.warning = post_mortem.warning.or(history_warning);
+        post_mortem.verdict = self
+            .level
+            .as_ref()
+            .map(|level| Verdict::of(&level.objectives, cause, self.score));
+        post_mortem
+    }
 
-        let mut block_size = self.map.size();
-        block_size.width = block_size.width.saturating_add(2);
-        block_size.height = block_size.height.saturating_add(2);
-        let block_area = center_rect(block_area, block_size);
-        if self.map.wrap() {
-            DottedBorder.render(block_area, buf);
+    /// Write the record of the game's end to the game history if it was held
+    /// back while the game could be rewound.  Once the record is written, the
+    /// game can no longer be rewound.
+    ///
+    /// This is called by the application when it leaves the game, so there's
+    /// nowhere to report a failure to write the record, and it is ignored.
+    pub(crate) fn record_end(&mut self) {
+        if let Some(record) = self.unrecorded.take() {
+            let _ = self.globals.config.append_history(&record);
+            self.rewinds_left = 0;
+        }
+    }
+
+    /// Return a record of the game for the game history as it would be if the
+    /// game ended now due to `cause`.  Returns `None` if the game's seed is
+    /// unknown, as for games created with a caller-supplied RNG, which are not
+    /// recorded.
+    fn history_record(&self, cause: EndCause) -> Option<GameRecord> {
+        self.seed.map(|seed| GameRecord {
+            timestamp: jiff::Timestamp::now(),
+            options: self.globals.options,
+            seed: Some(seed),
+            rng: Some(self.rng_mode),
+            score: self.score,
+            duration_ms: u64::try_from(self.stats.elapsed().as_millis()).unwrap_or(u64::MAX),
+            cause,
+            peak_fill: Some(self.stats.peak_fill()),
+        })
+    }
+
+    /// If the game is in progress, or if it has ended but the record of its
+    /// end has not yet been written to the game history, return a record of
+    /// it for the game history in case the program exits without cleaning up
+    pub(crate) fn interrupted_record(&self) -> Option<GameRecord> {
+        match self.state {
+            GameState::Running
+            | GameState::Paused(_)
+            | GameState::ConfirmRestart(_)
+            | GameState::Interrupted(_)
+            | GameState::Resuming { .. } => self.history_record(EndCause::Interrupted),
+            GameState::Loading(_)
+            | GameState::Dying { .. }
+            | GameState::Dead(_)
+            | GameState::Celebrating { .. }
+            | GameState::Exhausted(_) => self.unrecorded.clone(),
+        }
+    }
+
+    /// Check whether the score ranks among the high scores for the game's
+    /// options and, if it does, update the high scores and write them to disk.
+    /// If the configuration asks for players' names, the returned `PostMortem`
+    /// will prompt for one.
+    ///
+    /// If an earlier attempt to save the high score mid-game failed, its error
+    /// is reported here unless saving at the end of the game also failed.
+    fn update_high_score(&mut self) -> PostMortem {
+        let mut post_mortem = if let Some(score) = self.ranked_score() {
+            PostMortem {
+                new_high_score: self.new_high_score().is_some(),
+                warning: self.save_high_score(score),
+                name_prompt: (self.globals.config.game.ask_name && self.saved_score.is_some())
+                    .then(NamePrompt::new),
+                held_until: None,
+                crash: None,
+                perfect: false,
+                verdict: None,
+            }
         } else {
-            Block::bordered().render(block_area, buf);
+            PostMortem::default()
+        };
+        let deferred = self.deferred_warning.take();
+        post_mortem.warning = post_mortem.warning.or(deferred);
+        post_mortem
+    }
+
+    /// If the `live-high-score` setting is enabled and the score exceeds the
+    /// high score, save it as the new high score, but not more often than
+    /// every [`consts::LIVE_SAVE_INTERVAL`].  Errors are held onto until the
+    /// game ends.
+    fn save_live_high_score(&mut self) {
+        if !self.globals.config.game.live_high_score
+            || self.live_save_failed
+            || self
+                .last_live_save
+                .is_some_and(|t| t.elapsed() < consts::LIVE_SAVE_INTERVAL)
+        {
+            return;
+        }
+        if let Some(score) = self.new_high_score() {
+            self.last_live_save = Some(Instant::now());
+            if let Some(warning) = self.save_high_score(score) {
+                self.live_save_failed = true;
+                self.deferred_warning = Some(warning);
+            }
+        }
+    }
+
+    /// Record `score` among the high scores for the game's options, replacing
+    /// any score saved earlier in the game, and write the high scores to disk,
+    /// returning a [`Warning`] on failure
+    fn save_high_score(&mut self, score: NonZeroU32) -> Option<Warning> {
+        let hs = HighScore {
+            score,
+            slow_motion: self.used_slow_motion,
+            achieved: Some(jiff::Timestamp::now()),
+            duration_ms: Some(u64::try_from(self.stats.elapsed().as_millis()).unwrap_or(u64::MAX)),
+            name: None,
+        };
+        if let Some(old) = self.saved_score.take() {
+            self.globals.high_scores.remove(self.globals.options, &old);
+        }
+        if self
+            .globals
+            .high_scores
+            .insert(self.globals.options, hs.clone())
+        {
+            self.saved_score = Some(hs);
+        }
+        self.globals
+            .config
+            .save_high_scores(&self.globals.high_scores)
+            .err()
+            .map(Warning::from)
+    }
+
+    /// If the score exceeds the current high score and the game is eligible
+    /// for high scores, return the new high score.
+    fn new_high_score(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.score)
+            .filter(|&score| self.high_score.is_none_or(|hs| hs < score))
+            .filter(|_| self.eligible_for_high_score())
+    }
+
+    /// If the score would rank among the high scores for the game's options
+    /// (or has already been saved among them) and the game is eligible for
+    /// high scores, return the score.
+    fn ranked_score(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.score)
+            .filter(|&score| {
+                self.saved_score.is_some()
+                    || self
+                        .globals
+                        .high_scores
+                        .qualifies(self.globals.options, score)
+            })
+            .filter(|_| self.eligible_for_high_score())
+    }
+
+    /// If the score has just overtaken the high score, turn the score bar to
+    /// its record style and announce it (unless the score is hidden)
+    fn check_record_pace(&mut self) {
+        if !self.record_pace
+            && self.high_score.is_some_and(|hs| self.score > hs.get())
+            && self.eligible_for_high_score()
+        {
+            self.record_pace = true;
+            if !self.score_hidden() {
+                self.show_toast(String::from(tr(Msg::NewRecordPace)));
+            }
+        }
+    }
+
+    /// Can the game set a new high score?  This is false if the snake was
+    /// steered by a bot, if the game is a co-op relay, if the game was
+    /// rewound, if the game is in step mode, or if the game is on a custom
+    /// level.
+    fn eligible_for_high_score(&self) -> bool {
+        self.globals.bot.is_none()
+            && self.level.is_none()
+            && self.relay.is_none()
+            && !self.used_rewind
+            && !self.respawn
+            && self.globals.tick_source == TickSource::Timer
+    }
+
+    /// Can the game currently be rewound?
+    pub(crate) fn can_rewind(&self) -> bool {
+        matches!(self.state, GameState::Dead(_))
+            && self.rewinds_left > 0
+            && !self.history.is_empty()
+    }
+
+    /// Restore the game to its state a few seconds ago and pause it.  This
+    /// prevents any further high scores from being recorded for the game.
+    fn rewind(&mut self) {
+        let Some(checkpoint) = self.history.take_oldest() else {
+            return;
+        };
+        let Checkpoint {
+            score,
+            combo,
+            combo_ticks_left,
+            snake,
+            hydras,
+            fruits,
+            hydra_fruit,
+            power_ups,
+            map,
+            stats,
+            ticks,
+        } = checkpoint;
+        self.score = score;
+        self.combo = combo;
+        self.combo_ticks_left = combo_ticks_left;
+        self.snake = snake;
+        self.hydras = hydras;
+        self.fruits = fruits;
+        self.hydra_fruit = hydra_fruit;
+        self.power_ups = power_ups;
+        self.map = map;
+        self.reachable = self.map.reachable();
+        self.mouse_target = None;
+        self.stats = stats;
+        self.ticks = ticks;
+        self.camera.follow(self.map.size(), self.snake.head());
+        self.effects.clear();
+        self.next_tick = None;
+        self.rewinds_left -= 1;
+        self.used_rewind = true;
+        // The death being undone is not recorded in the game history
+        self.unrecorded = None;
+        self.pause();
+    }
+
+    /// Return a screen containing a fresh game with the same options (and on
+    /// the same custom level, if any)
+    fn restart(&self) -> Screen {
+        let mut game = self.fresh_game();
+        game.rival = self.rival;
+        Screen::Game(Box::new(game))
+    }
+
+    /// Return a new game with the same globals, played on the same custom
+    /// level as this game if it is on one
+    fn fresh_game(&self) -> Game {
+        match self.level {
+            Some(ref level) => Game::for_level(self.globals.clone(), level.clone()),
+            None => Game::new(self.globals.clone()),
+        }
+    }
+
+    /// Return a screen containing a fresh game using options that were just
+    /// changed from the game-over screen.  As the options differ from those
+    /// of the finished game, any race against a high score is not carried
+    /// over, and the new game announces the high score for its options.
+    fn restart_reconfigured(&self) -> Screen {
+        let mut game = self.fresh_game();
+        game.announce_high_score();
+        Screen::Game(Box::new(game))
+    }
+
+    /// Is the game currently running (and not paused or over?)
+    fn running(&self) -> bool {
+        self.state == GameState::Running
+    }
+
+    /// Return the details of the collision that ended the game, if it ended
+    /// in one
+    fn crash_report(&self) -> Option<Crash> {
+        match self.state {
+            GameState::Dying {
+                ref post_mortem, ..
+            }
+            | GameState::Dead(ref post_mortem) => post_mortem.crash,
+            _ => None,
         }
+    }
+
+    /// Should the game be paused in response to `event`, a change in the
+    /// terminal rather than a key press?
+    fn pauses_for(&self, event: &Event) -> bool {
+        let cfg = &self.globals.config.game;
+        match event {
+            Event::FocusLost => cfg.pause_on_focus_loss,
+            Event::Resize(..) => cfg.pause_on_resize,
+            _ => false,
+        }
+    }
 
+    /// Return the portion of the level that is shown on screen, in level
+    /// coordinates
+    fn viewport(&self) -> Rect {
+        self.camera.view()
+    }
+
+    /// Draw the part `view` of the level, with the snakes' heads, into
+    /// `level_area`.  If a snake crashed into the border, the border around
+    /// `level_area` is marked as well.
+    fn render_level(&self, view: Rect, level_area: Rect, buf: &mut Buffer) {
         let glyphs = &self.globals.config.glyphs;
-        let level_area = block_area.inner(Margin::new(1, 1));
+        self.level_area.set(level_area);
+        // The ghost shows where the snake's head will be after the next tick
+        let ghost = glyphs
+            .ghost_head
+            .as_ref()
+            .filter(|_| self.running())
+            .and_then(|_| {
+                let direction = self.next_direction();
+                direction
+                    .advance(self.snake.head(), self.map.bounds())
+                    .map(|next| (next, direction))
+            });
+        // While invincible, the snake blinks between its normal style and
+        // that of the star power-up
+        let blink = self
+            .snake
+            .invincible_ticks()
+            .is_some_and(|ticks| ticks % 2 == 1);
+        {
+            let mut layer = self.layer.borrow_mut();
+            layer.update(view, glyphs, self.seen(ghost, blink), |pos| {
+                self.level_cell(pos, ghost, blink)
+            });
+            layer.render(level_area, buf);
+        }
         let mut level = Canvas {
             area: level_area,
+            origin: view.as_position(),
             buf,
         };
-        for &p in self.snake.body() {
-            level.draw_cell(p, &glyphs.snake_body.symbol, glyphs.snake_body.style);
-        }
-        for &pos in &self.fruits {
-            level.draw_cell(pos, &glyphs.fruit.symbol, glyphs.fruit.style);
-        }
-        for &pos in self.map.obstacles() {
-            level.draw_cell(pos, &glyphs.obstacle.symbol, glyphs.obstacle.style);
-        }
-        // Draw the head last so that, if it's a collision, we overwrite
-        // whatever it's colliding with
-        if matches!(self.state, GameState::Dead(_)) {
-            level.draw_cell(
-                self.snake.head(),
-                &glyphs.collision.symbol,
-                glyphs.collision.style,
-            );
+        // Draw the heads last so that, if one is a collision, we overwrite
+        // whatever it's colliding with.  As all of the snakes die together,
+        // every head is drawn as a collision — unless a snake ran into the
+        // border, in which case the collision is drawn on the border instead.
+        if matches!(self.state, GameState::Dead(_) | GameState::Dying { .. }) {
+            let wall_crash = self.crash_report().filter(|c| c.cause == EndCause::Wall);
+            for snake in self.snakes() {
+                if wall_crash.is_some() {
+                    level.draw_cell(
+                        snake.head(),
+                        glyphs.snake_head.symbol.for_direction(snake.direction),
+                        glyphs.snake_head.style,
+                    );
+                } else {
+                    level.draw_cell(
+                        snake.head(),
+                        &glyphs.collision.symbol,
+                        glyphs.collision.style,
+                    );
+                }
+            }
+            if let Some(crash) = wall_crash {
+                let side = border_side(level_area, crash.direction);
+                buf.set_style(side, glyphs.collision.style);
+                let x = crash
+                    .head
+                    .x
+                    .checked_sub(view.x)
+                    .and_then(|x| level_area.x.checked_add(x));
+                let y = crash
+                    .head
+                    .y
+                    .checked_sub(view.y)
+                    .and_then(|y| level_area.y.checked_add(y));
+                let hit = if crash.direction.is_vertical() {
+                    x.map(|x| Position::new(x, side.y))
+                } else {
+                    y.map(|y| Position::new(side.x, y))
+                };
+                if let Some(cell) = hit
+                    .filter(|&pos| side.contains(pos))
+                    .and_then(|pos| buf.cell_mut(pos))
+                {
+                    cell.set_symbol(glyphs.collision.symbol.as_ref());
+                    cell.set_style(Style::reset().patch(glyphs.collision.style));
+                }
+            }
         } else {
+            for hydra in &self.hydras {
+                let head = hydra.snake.head();
+                level.draw_cell(
+                    head,
+                    glyphs
+                        .snake_head
+                        .symbol
+                        .for_direction(hydra.snake.direction),
+                    glyphs.snake_head.style,
+                );
+                if blink {
+                    level.style_cell(head, glyphs.star.style);
+                }
+            }
+            let hazard = if self.globals.config.game.collision_warning && self.running() {
+                self.hazard_ahead()
+            } else {
+                None
+            };
+            let head_style = match hazard {
+                Some(Hazard::Fatal) => glyphs.snake_head.style.patch(consts::DANGER_STYLE),
+                Some(Hazard::CloseCall) => glyphs.snake_head.style.patch(consts::CLOSE_CALL_STYLE),
+                None => glyphs.snake_head.style,
+            };
             level.draw_cell(
                 self.snake.head(),
                 glyphs.snake_head.symbol.for_direction(self.snake.direction),
-                glyphs.snake_head.style,
+                head_style,
             );
+            if blink {
+                level.style_cell(self.snake.head(), glyphs.star.style);
+            }
+            for eff in &self.effects {
+                if eff.kind == EffectKind::EatFlash && self.snakes().any(|sn| sn.head() == eff.pos)
+                {
+                    level.style_cell(eff.pos, glyphs.eat.style);
+                }
+            }
+            if let Some(ticks_left) = self.relay.and_then(|relay| relay.handoff()) {
+                // Flash the snake to show that it has changed hands
+                if ticks_left % 2 == 0 {
+                    for snake in self.snakes() {
+                        for pos in std::iter::once(snake.head()).chain(snake.body().iter().copied())
+                        {
+                            level.style_cell(pos, Style::new().add_modifier(Modifier::REVERSED));
+                        }
+                    }
+                }
+            }
+            if let GameState::Celebrating { ticks_left, .. } = self.state {
+                // Cycle each segment of the snake through the victory colors,
+                // offset from its neighbors so that the colors ripple along it
+                for snake in self.snakes() {
+                    let cells =
+                        std::iter::once(snake.head()).chain(snake.body().iter().rev().copied());
+                    for (i, pos) in cells.enumerate() {
+                        let color = consts::VICTORY_COLORS
+                            [(i + usize::from(ticks_left)) % consts::VICTORY_COLORS.len()];
+                        level.style_cell(pos, Style::new().fg(color).add_modifier(Modifier::BOLD));
+                    }
+                }
+            }
         }
+    }
 
-        match self.state {
-            GameState::Running => (),
-            GameState::Paused(paused) => {
-                let pause_area = center_rect(
-                    display,
-                    Size {
-                        width: Paused::WIDTH,
-                        height: Paused::HEIGHT,
-                    },
-                );
-                paused.render(pause_area, buf);
+    /// Return a record of the cells of the level that may change by the next
+    /// tick, for drawing only those cells of the [`LevelLayer`] again.
+    /// `ghost` and `blink` are as for [`Game::level_cell()`].
+    fn seen(&self, ghost: Option<(Position, Direction)>, blink: bool) -> Seen {
+        let mut marks = Vec::new();
+        for snake in self.snakes() {
+            marks.push(snake.head());
+            marks.extend(snake.body().front());
+        }
+        marks.extend(&self.fruits);
+        marks.extend(self.power_ups.iter().map(|pu| pu.pos));
+        marks.extend(self.hydra_fruit);
+        marks.extend(self.effects.iter().map(|eff| eff.pos));
+        marks.extend(ghost.map(|(pos, _)| pos));
+        Seen {
+            ticks: self.ticks,
+            snakes: self.hydras.len() + 1,
+            blink,
+            marks,
+        }
+    }
+
+    /// Return the cell to draw at `pos` in the [`LevelLayer`], or `None` if
+    /// there is nothing there.  The snakes' heads are not included, as they
+    /// are drawn on top of the layer.
+    ///
+    /// `ghost` is the position & direction of the snake's head after the next
+    /// tick, if the ghost head is to be shown there, and `blink` is `true` if
+    /// the snakes are to be drawn in the style of the star power-up.
+    fn level_cell(
+        &self,
+        pos: Position,
+        ghost: Option<(Position, Direction)>,
+        blink: bool,
+    ) -> Option<Cell> {
+        let glyphs = &self.globals.config.glyphs;
+        // Things are listed from the bottom up, with whatever is drawn last
+        // hiding the rest
+        let mut drawn: Option<(&str, Style)> = None;
+        if let Some(ref bg) = glyphs.background {
+            drawn = Some((bg.symbol.as_ref(), bg.style));
+        }
+        match self.map.terrain_at(pos) {
+            Some(Terrain::Mud) => drawn = Some((glyphs.mud.symbol.as_ref(), glyphs.mud.style)),
+            Some(Terrain::Ice) => drawn = Some((glyphs.ice.symbol.as_ref(), glyphs.ice.style)),
+            Some(Terrain::Gate(dir)) => {
+                drawn = Some((
+                    glyphs.gate.symbol.for_direction(dir).as_ref(),
+                    glyphs.gate.style,
+                ));
             }
-            GameState::Dead(ref pm) | GameState::Exhausted(ref pm) => {
-                Span::from(if pm.new_high_score {
-                    " — GAME OVER — NEW HIGH SCORE! —"
-                } else {
-                    " — GAME OVER —"
-                })
-                .render(msg1_area, buf);
-                Line::from_iter([
-                    Span::raw(" Choose One: Restart ("),
-                    Span::styled("r", consts::KEY_STYLE),
-                    Span::raw(") — Main Menu ("),
-                    Span::styled("m", consts::KEY_STYLE),
-                    Span::raw(") — Quit ("),
-                    Span::styled("q", consts::KEY_STYLE),
-                    Span::raw(")"),
-                ])
-                .render(msg2_area, buf);
-                if let Some(ref warning) = pm.warning {
-                    warning.render(display, buf);
-                }
+            None => (),
+        }
+        if self.forbidden(pos) {
+            drawn = Some((glyphs.forbidden.symbol.as_ref(), glyphs.forbidden.style));
+        }
+        // The ghost goes beneath everything else so that it doesn't hide
+        // whatever the snake is about to run into
+        if let (Some((ghost_pos, direction)), Some(ghost_glyph)) = (ghost, &glyphs.ghost_head) {
+            if ghost_pos == pos {
+                drawn = Some((
+                    glyphs.snake_head.symbol.for_direction(direction).as_ref(),
+                    ghost_glyph.style,
+                ));
             }
         }
+        if self.snakes().any(|sn| sn.body_contains(pos)) {
+            let style = if blink {
+                glyphs.star.style
+            } else {
+                glyphs.snake_body.style
+            };
+            drawn = Some((glyphs.snake_body.symbol.as_ref(), style));
+        }
+        if self
+            .effects
+            .iter()
+            .any(|eff| eff.kind == EffectKind::GrowIn && eff.pos == pos)
+        {
+            drawn = Some((glyphs.snake_grow.symbol.as_ref(), glyphs.snake_grow.style));
+        }
+        if self.fruits.contains(&pos) {
+            let spawning = self.effects.iter().any(|eff| {
+                eff.kind == EffectKind::Spawn && eff.ticks_left % 2 == 0 && eff.pos == pos
+            });
+            let style = if spawning {
+                glyphs.spawn.style
+            } else {
+                glyphs.fruit.style
+            };
+            drawn = Some((glyphs.fruit.symbol.as_ref(), style));
+        }
+        if self.map.obstacles().contains(&pos) {
+            drawn = Some((glyphs.obstacle.symbol.as_ref(), glyphs.obstacle.style));
+        }
+        if let Some(pu) = self.power_ups.iter().rev().find(|pu| pu.pos == pos) {
+            let glyph = pu.kind.glyph(glyphs);
+            drawn = Some((glyph.symbol.as_ref(), glyph.style));
+        }
+        if self.hydra_fruit == Some(pos) {
+            drawn = Some((glyphs.hydra_fruit.symbol.as_ref(), glyphs.hydra_fruit.style));
+        }
+        drawn.map(|(symbol, style)| {
+            let mut cell = Cell::default();
+            cell.set_symbol(symbol);
+            cell.set_style(Style::reset().patch(style));
+            cell
+        })
     }
-}
 
-/// A portion of a [`Buffer`] that provides methods for drawing individual
-/// cells using coordinates relative to the top-left corner of `area`
-#[derive(Debug, Eq, PartialEq)]
-struct Canvas<'a> {
-    area: Rect,
-    buf: &'a mut Buffer,
-}
+    /// Return the title to draw on the level's top border, padded with a space
+    /// on each side, if one fits in `width` columns.  If the seed that the
+    /// level was generated from is known, it is included, abbreviated if
+    /// necessary.  Custom levels are titled with their names.
+    fn border_title(&self, width: u16) -> Option<String> {
+        let mut candidates = Vec::new();
+        if let Some(ref level) = self.level {
+            candidates.push(level.name.clone());
+        } else {
+            if let Some(seed) = self.seed {
+                let hex = format!("{seed:016X}");
+                candidates.push(tr_args(Msg::ArcadeWithSeed, &[("seed", &hex)]));
+                let short = format!("{}…", hex.get(..4).unwrap_or_default());
+                candidates.push(tr_args(Msg::ArcadeWithSeed, &[("seed", &short)]));
+            }
+            candidates.push(String::from(tr(Msg::Arcade)));
+        }
+        candidates
+            .into_iter()
+            .map(|title| format!(" {title} "))
+            .find(|title| title.width() <= usize::from(width))
+    }
 
-impl Canvas<'_> {
-    /// Set the cell at `pos` to `symbol`
-    fn draw_char(&mut self, pos: Position, symbol: char) {
-        let Some(x) = self.area.x.checked_add(pos.x) else {
-            return;
+    /// Return the time between movements of the snake at the current speed
+    fn tick_period(&self) -> Duration {
+        let base = if self.slow_motion {
+            consts::SLOW_MOTION_TICK_PERIOD
+        } else {
+            consts::TICK_PERIOD
         };
-        let Some(y) = self.area.y.checked_add(pos.y) else {
-            return;
+        let base = if self.globals.options.adaptive {
+            self.globals.difficulty.adjust_period(base)
+        } else {
+            base
         };
-        if let Some(cell) = self.buf.cell_mut((x, y)) {
-            cell.set_char(symbol);
-            cell.set_style(Style::reset());
+        self.snake
+            .power_ups
+            .iter()
+            .fold(base, |period, pu| pu.kind.adjust_period(period))
+    }
+
+    /// Return the time until the snake's next move.  If the configuration
+    /// gives the shape of terminal cells, vertical moves take proportionally
+    /// longer (or shorter) than horizontal ones so that the snake covers the
+    /// same distance on screen in the same time in every direction.
+    fn move_period(&self) -> Duration {
+        let period = self.tick_period();
+        match self.globals.config.cell_aspect {
+            Some(aspect) if self.snake.direction.is_vertical() => aspect.vertical_period(period),
+            _ => period,
         }
     }
 
-    /// Set the cell at `pos` to `symbol` with the given style
-    fn draw_cell<S: AsRef<str>>(&mut self, pos: Position, symbol: S, style: Style) {
-        let Some(x) = self.area.x.checked_add(pos.x) else {
-            return;
-        };
-        let Some(y) = self.area.y.checked_add(pos.y) else {
-            return;
+    /// Determine what will happen to the snake if it moves into the cell
+    /// directly ahead of its head on the next tick, or `None` if it will come
+    /// to no harm
+    fn hazard_ahead(&self) -> Option<Hazard> {
+        let Some(next) = self
+            .next_direction()
+            .advance(self.snake.head(), self.map.bounds())
+        else {
+            return Some(Hazard::Fatal);
         };
-        if let Some(cell) = self.buf.cell_mut((x, y)) {
-            cell.set_symbol(symbol.as_ref());
-            cell.set_style(Style::reset().patch(style));
+        if self.forbidden(next) {
+            Some(Hazard::Fatal)
+        } else if self.snake.invincible_ticks().is_some() {
+            None
+        } else if !self.map.can_enter(next, self.next_direction()) {
+            Some(Hazard::Fatal)
+        } else if self.map.obstacles().contains(&next) {
+            let plowing = self
+                .snake
+                .power_ups
+                .iter()
+                .any(|pu| pu.kind == PowerUpKind::Plow);
+            (!plowing).then_some(Hazard::Fatal)
+        } else if self.snake.body().front() == Some(&next) {
+            // The end of the tail only moves out of the way if the snake
+            // isn't still growing
+            if self.globals.options.strict_collisions
+                || self.snake.body().len() < self.snake.max_len
+            {
+                Some(Hazard::Fatal)
+            } else {
+                Some(Hazard::CloseCall)
+            }
+        } else if self.snake.body_contains(next) {
+            Some(Hazard::Fatal)
+        } else {
+            None
         }
     }
-}
 
-/// A widget for drawing a border made of dots around the edge of an area.
-///
-/// Like [`Block::bordered()`], but with different characters.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-struct DottedBorder;
+    /// Is the death animation currently playing?
+    fn dying(&self) -> bool {
+        matches!(self.state, GameState::Dying { .. })
+    }
 
-impl Widget for DottedBorder {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        if area.is_empty() {
-            return;
-        }
-        let size = area.as_size();
-        let max_x = size.width.saturating_sub(1);
-        let max_y = size.height.saturating_sub(1);
-        let mut canvas = Canvas { area, buf };
-        canvas.draw_char(Position::ORIGIN, '·');
-        canvas.draw_char(Position::new(max_x, 0), '·');
-        canvas.draw_char(Position::new(max_x, max_y), '·');
-        canvas.draw_char(Position::new(0, max_y), '·');
-        for x in 1..max_x {
-            canvas.draw_char(Position::new(x, 0), '⋯');
-            canvas.draw_char(Position::new(x, max_y), '⋯');
+    /// Return the line of key hints to show under the level, if any: for the
+    /// first few seconds of a game, how to pause & quit, and while paused, how
+    /// to resume & quit.  In kiosk mode, the quit key is left out.
+    fn key_hints(&self) -> Option<Line<'static>> {
+        if !self.globals.config.game.key_hints {
+            return None;
         }
-        for y in 1..max_y {
-            canvas.draw_char(Position::new(0, y), '⋮');
-            canvas.draw_char(Position::new(max_x, y), '⋮');
+        let (action, quit_key, style) = match self.state {
+            GameState::Running if self.ticks < consts::KEY_HINT_TICKS => {
+                let style = if consts::KEY_HINT_TICKS - self.ticks <= consts::KEY_HINT_FADE_TICKS {
+                    Style::new().add_modifier(Modifier::DIM)
+                } else {
+                    Style::new()
+                };
+                (tr(Msg::Pause), "Ctrl-C", style)
+            }
+            GameState::Paused(_) => (tr(Msg::Resume), "q", Style::new()),
+            _ => return None,
+        };
+        let mut hints = Line::from_iter([
+            Span::raw(format!("{action} (")),
+            Span::styled("Esc", consts::KEY_STYLE),
+        ]);
+        if !self.globals.kiosk {
+            hints.extend([
+                Span::raw(format!(") — {} (", tr(Msg::Quit))),
+                Span::styled(quit_key, consts::KEY_STYLE),
+            ]);
         }
+        hints.push_span(Span::raw(")"));
+        Some(hints.style(style))
     }
-}
 
-/// An enum of the states that a game can be in
-#[derive(Clone, Debug, Eq, PartialEq)]
-enum GameState {
-    /// The game is currently running
-    Running,
+    /// Should the score & high score be hidden from the score bar?  If the
+    /// `hide-score` setting is enabled, they are only shown once the game is
+    /// over.
+    fn score_hidden(&self) -> bool {
+        self.globals.config.game.hide_score
+            && !matches!(
+                self.state,
+                GameState::Dying { .. }
+                    | GameState::Dead(_)
+                    | GameState::Celebrating { .. }
+                    | GameState::Exhausted(_)
+            )
+    }
 
-    /// The game is currently paused
-    Paused(Paused),
+    /// Is the death or victory animation or the countdown to resuming play
+    /// currently playing?
+    fn animating(&self) -> bool {
+        matches!(
+            self.state,
+            GameState::Dying { .. } | GameState::Celebrating { .. } | GameState::Resuming { .. }
+        )
+    }
 
-    /// The game ended due to the snake colliding with something
-    Dead(PostMortem),
+    /// Is `event` a press of the step key while the game is in step mode and
+    /// the snake is able to move?
+    fn is_step(&self, event: &Event) -> bool {
+        self.globals.tick_source == TickSource::Step
+            && (self.running() || self.dying() || matches!(self.state, GameState::Resuming { .. }))
+            && event
+                .as_key_press_event()
+                .is_some_and(|ev| consts::STEP_KEY.matches(ev))
+    }
 
-    /// The snake has filled the board and there are no more spaces to place
-    /// fruits in
-    Exhausted(PostMortem),
+    /// Can the current board be saved as a favorite?  This requires the
+    /// board's seed to be known, and it isn't offered in kiosk mode.
+    fn can_favorite(&self) -> bool {
+        self.seed.is_some() && !self.globals.kiosk
+    }
+
+    /// Save the current board to the player's favorites under the given name
+    /// and show a message reporting the result
+    fn add_favorite(&mut self, name: String) {
+        let Some(seed) = self.seed else {
+            return;
+        };
+        let config = &self.globals.config;
+        let favorite = Favorite {
+            name,
+            seed,
+            options: self.globals.options,
+        };
+        let text = match config.load_favorites() {
+            Ok(mut favorites) => {
+                let text = tr_args(Msg::FavoriteSaved, &[("name", &favorite.name)]);
+                favorites.add(favorite);
+                match config.save_favorites(&favorites) {
+                    Ok(()) => text,
+                    Err(e) => error_chain(&e),
+                }
+            }
+            Err(e) => error_chain(&e),
+        };
+        self.show_toast(text);
+    }
+
+    /// Show a message stating whether a high score has been recorded for the
+    /// game's options yet and, if so, what it is.  Nothing is shown if the
+    /// high score is hidden by the `hide-score` setting or if the game can't
+    /// set a high score.
+    pub(crate) fn announce_high_score(&mut self) {
+        if self.score_hidden() || !self.eligible_for_high_score() {
+            return;
+        }
+        let text = match self.globals.high_scores.get(self.globals.options) {
+            Some(score) => tr_args(
+                Msg::HighScoreToBeat,
+                &[("score", &i18n::number(score.get()))],
+            ),
+            None => String::from(tr(Msg::NoHighScoreYet)),
+        };
+        self.show_toast(text);
+    }
+
+    /// Race against the given high score, showing how the game's scoring pace
+    /// compares to it in the score bar
+    pub(crate) fn race(&mut self, rival: Rival) {
+        self.rival = Some(rival);
+    }
+
+    /// Show the given message under the level for a limited time
+    fn show_toast(&mut self, text: String) {
+        self.toast = Some(Toast {
+            text,
+            ticks_left: consts::TOAST_TICKS,
+        });
+    }
+
+    /// Respond to the terminal having stalled while drawing the game by
+    /// pausing it, if it's running and the configuration allows
+    pub(crate) fn handle_stall(&mut self) {
+        if self.running() && self.globals.config.game.pause_on_stall {
+            self.pause();
+        }
+    }
+
+    /// Return a command palette listing the actions available in the game's
+    /// current state, pausing the game first if it's in progress.  Returns
+    /// `None` if the game is in a state that the palette can't interrupt
+    /// (e.g., a pop-up is being shown).
+    pub(crate) fn palette(&mut self) -> Option<Palette> {
+        if matches!(self.state, GameState::Running | GameState::Resuming { .. }) {
+            self.pause();
+        }
+        if self.favorite_prompt.is_some() {
+            return None;
+        }
+        let actions: &[PaletteAction] = match self.state {
+            GameState::Paused(_) if self.globals.kiosk => &[
+                PaletteAction::Resume,
+                PaletteAction::Restart,
+                PaletteAction::MainMenu,
+            ],
+            GameState::Paused(_) => &[
+                PaletteAction::Resume,
+                PaletteAction::Restart,
+                PaletteAction::AddFavorite,
+                PaletteAction::MainMenu,
+                PaletteAction::Quit,
+            ],
+            GameState::Dead(PostMortem {
+                warning: None,
+                name_prompt: None,
+                held_until,
+                ..
+            })
+            | GameState::Exhausted(PostMortem {
+                warning: None,
+                name_prompt: None,
+                held_until,
+                ..
+            }) if held_until.is_none_or(|t| Instant::now() >= t) => {
+                if self.globals.kiosk {
+                    &[PaletteAction::MainMenu]
+                } else {
+                    &[
+                        PaletteAction::Restart,
+                        PaletteAction::AddFavorite,
+                        PaletteAction::MainMenu,
+                        PaletteAction::Quit,
+                    ]
+                }
+            }
+            _ => return None,
+        };
+        Some(Palette::new(
+            actions
+                .iter()
+                .copied()
+                .filter(|&action| action != PaletteAction::AddFavorite || self.can_favorite())
+                .map(PaletteEntry::new)
+                .collect(),
+        ))
+    }
+
+    /// Carry out an action chosen from the command palette.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.  If it does so, [`Game::record_end()`] should be called
+    /// first.
+    pub(crate) fn run_palette_action(&mut self, action: PaletteAction) -> Option<Screen> {
+        match action {
+            PaletteAction::Resume => {
+                if matches!(self.state, GameState::Paused(_)) {
+                    self.state = GameState::Running;
+                }
+                None
+            }
+            PaletteAction::Restart => Some(self.restart()),
+            PaletteAction::AddFavorite => {
+                if self.can_favorite() {
+                    self.favorite_prompt = Some(NamePrompt::favorite());
+                }
+                None
+            }
+            PaletteAction::MainMenu => Some(Screen::Main(crate::menu::MainMenu::new(
+                self.globals.clone(),
+            ))),
+            PaletteAction::Quit => Some(Screen::Quit),
+            PaletteAction::Play
+            | PaletteAction::Toggle(_)
+            | PaletteAction::SetSeed(_)
+            | PaletteAction::HighScores
+            | PaletteAction::Progress
+            | PaletteAction::About
+            | PaletteAction::Favorites
+            | PaletteAction::Levels => None,
+        }
+    }
+
+    /// Pause the game
+    fn pause(&mut self) {
+        self.state = GameState::Paused(if self.can_favorite() {
+            Paused::with_favorite()
+        } else {
+            Paused::new()
+        });
+    }
 }
 
-/// End-of-game report
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct PostMortem {
-    /// True if a new high score was set
-    new_high_score: bool,
+impl<R> Widget for &Game<R> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        let [score_area, main_area, msg1_area, msg2_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(display);
 
-    /// A warning to display about an error, if any, that occurred while
-    /// updating the high score file
-    warning: Option<Warning>,
+        let direction = self.globals.config.layout_direction;
+        let hidden = self.score_hidden();
+        let bar_style = if self.record_pace && !hidden {
+            consts::SCORE_BAR_STYLE.patch(theme::record_style())
+        } else {
+            consts::SCORE_BAR_STYLE
+        };
+        let score = if hidden {
+            String::from(consts::HIDDEN_SCORE)
+        } else {
+            i18n::number(self.score)
+        };
+        let score_str = if self.combo > 1 && !hidden {
+            let args: [(&str, &dyn std::fmt::Display); 2] =
+                [("score", &score), ("combo", &self.combo)];
+            tr_args(Msg::ScoreWithCombo, &args)
+        } else {
+            tr_args(Msg::Score, &[("score", &score)])
+        };
+        // The score followed by a badge for each active power-up
+        let mut items = Vec::new();
+        if self.globals.config.game.fruit_arrow {
+            if let Some(arrow) =
+                compass::fruit_arrow(self.map.bounds(), self.snake.head(), &self.fruits)
+            {
+                items.push(vec![Span::styled(
+                    arrow,
+                    self.globals.config.glyphs.fruit.style,
+                )]);
+            }
+        }
+        items.push(vec![Span::raw(score_str)]);
+        if let Some(ref level) = self.level {
+            items.extend(
+                objectives::progress(&level.objectives, &score, self.stats.elapsed())
+                    .into_iter()
+                    .map(|item| vec![Span::raw(item)]),
+            );
+        }
+        if self.globals.config.game.fill_meter {
+            items.push(vec![Span::raw(tr_args(
+                Msg::Fill,
+                &[("percent", &self.fill_percent())],
+            ))]);
+        }
+        if self.globals.options.adaptive {
+            items.push(vec![Span::styled(
+                tr_args(Msg::AdaptiveLevel, &[("level", &self.globals.difficulty)]),
+                consts::ADAPTIVE_STYLE,
+            )]);
+        }
+        if let Some(relay) = self.relay {
+            items.push(vec![Span::raw(tr_args(
+                Msg::RelayPlayer,
+                &[("player", &relay.player_number())],
+            ))]);
+        }
+        if let Some(rival) = self.rival.filter(|_| !hidden) {
+            let pace = rival.pace(self.score, self.stats.elapsed());
+            let args: [(&str, &dyn std::fmt::Display); 2] = [
+                ("rate", &fmt_tenths(pace.rate)),
+                ("target", &fmt_tenths(pace.target)),
+            ];
+            let style = if pace.ahead {
+                consts::PACE_AHEAD_STYLE
+            } else {
+                consts::PACE_BEHIND_STYLE
+            };
+            items.push(vec![Span::styled(tr_args(Msg::Pace, &args), style)]);
+        }
+        // On levels too large to show at once, show where the snake is
+        if self.viewport().as_size() != self.map.size() {
+            let head = self.snake.head();
+            let args: [(&str, &dyn std::fmt::Display); 2] = [("x", &head.x), ("y", &head.y)];
+            items.push(vec![Span::raw(tr_args(Msg::Position, &args))]);
+        }
+        let mut shown = Vec::new();
+        for pu in &self.snake.power_ups {
+            if shown.contains(&pu.kind) {
+                continue;
+            }
+            shown.push(pu.kind);
+            let glyph = pu.kind.glyph(&self.globals.config.glyphs);
+            // Timed effects don't stack, so there's only ever one of each
+            // kind to show a countdown for
+            let text = if let Some(ticks) = pu.ticks_left {
+                let left = self.tick_period().saturating_mul(ticks);
+                format!("{} {}s", pu.kind.label(), left.as_secs_f64().ceil())
+            } else {
+                let qty = self
+                    .snake
+                    .power_ups
+                    .iter()
+                    .filter(|p| p.kind == pu.kind)
+                    .count();
+                if qty > 1 {
+                    format!("{}×{qty}", pu.kind.label())
+                } else {
+                    pu.kind.label().to_owned()
+                }
+            };
+            items.push(vec![
+                Span::styled(glyph.symbol.as_ref(), glyph.style),
+                Span::raw(" "),
+                Span::raw(text),
+            ]);
+        }
+        let mut score_line = Line::default().style(bar_style);
+        for (i, item) in direction.arrange(items).into_iter().enumerate() {
+            if i > 0 {
+                score_line.push_span(Span::raw("  "));
+            }
+            score_line.extend(item);
+        }
+        direction.align_start(score_line).render(score_area, buf);
+
+        let mode = match (self.slow_motion, self.globals.tick_source) {
+            (false, TickSource::Timer) => None,
+            (true, TickSource::Timer) => Some(String::from(tr(Msg::SlowMotion))),
+            (false, TickSource::Step) => Some(String::from(tr(Msg::StepMode))),
+            (true, TickSource::Step) => {
+                Some(format!("{} · {}", tr(Msg::SlowMotion), tr(Msg::StepMode)))
+            }
+        };
+        if let Some(mode) = mode {
+            Line::styled(mode, bar_style)
+                .centered()
+                .render(score_area, buf);
+        }
+
+        let hs = if hidden {
+            String::from(consts::HIDDEN_SCORE)
+        } else {
+            self.high_score
+                .map_or_else(|| String::from("-"), |hs| i18n::number(hs.get()))
+        };
+        let hs_str = tr_args(Msg::HighScore, &[("score", &hs)]);
+        direction
+            .align_end(Line::styled(hs_str, bar_style))
+            .render(score_area, buf);
+
+        let view = self.viewport();
+        let mut block_size = view.as_size();
+        block_size.width = block_size.width.saturating_add(2);
+        block_size.height = block_size.height.saturating_add(2);
+        let mut block_area = center_rect(main_area, block_size);
+        if let GameState::Dying { ticks_left, .. } = self.state {
+            // Shake the level back & forth by one cell, staying in bounds
+            let dx = if ticks_left % 2 == 0 { 1 } else { -1 };
+            let shaken = block_area.offset(Offset { x: dx, y: 0 });
+            if area.intersection(shaken) == shaken {
+                block_area = shaken;
+            }
+        }
+        if self.map.wrap() {
+            DottedBorder.render(block_area, buf);
+        } else {
+            Block::bordered().render(block_area, buf);
+        }
+        if self.globals.config.game.border_title {
+            // Leave the corners and one cell of border on either side
+            let title_area = Rect {
+                x: block_area.x.saturating_add(2),
+                width: block_area.width.saturating_sub(4),
+                height: 1,
+                ..block_area
+            };
+            if let Some(title) = self.border_title(title_area.width) {
+                Line::raw(title).render(title_area, buf);
+            }
+        }
+        ScrollMarkers {
+            view,
+            level: self.map.size(),
+        }
+        .render(block_area, buf);
+
+        let glyphs = &self.globals.config.glyphs;
+        let level_area = block_area.inner(Margin::new(1, 1));
+        self.render_level(view, level_area, buf);
+
+        if let Some(ref toast) = self.toast {
+            let toast_area = Rect {
+                y: block_area.bottom().saturating_sub(1),
+                height: 1,
+                ..block_area
+            }
+            .inner(Margin::new(1, 0));
+            Line::from(format!(" {} ", toast.text))
+                .centered()
+                .render(toast_area, buf);
+        }
+
+        if let Some(hints) = self.key_hints() {
+            direction.align_start(hints).render(msg2_area, buf);
+        }
+
+        match self.state {
+            GameState::Running => {
+                if let Some(relay) = self.relay {
+                    let msg = if relay.handoff().is_some() {
+                        Some(tr_args(
+                            Msg::YourTurn,
+                            &[("player", &relay.player_number())],
+                        ))
+                    } else {
+                        relay.countdown().map(|ticks| {
+                            let left = self.tick_period().saturating_mul(u32::from(ticks));
+                            let secs = left.as_secs_f64().ceil();
+                            let args: [(&str, &dyn std::fmt::Display); 2] =
+                                [("player", &relay.next_player_number()), ("secs", &secs)];
+                            tr_args(Msg::HandoffIn, &args)
+                        })
+                    };
+                    if let Some(msg) = msg {
+                        direction
+                            .align_start(Line::from(msg))
+                            .render(msg1_area, buf);
+                    }
+                }
+            }
+            GameState::Loading(ref loader) => loader.render(display, buf),
+            GameState::Dying { ticks_left, .. } => {
+                if ticks_left % 2 == 0 {
+                    buf.set_style(level_area, glyphs.collision.style);
+                }
+            }
+            // The pause menu is hidden while the favorite prompt is shown over
+            // it
+            GameState::Paused(_) if self.favorite_prompt.is_some() => (),
+            GameState::Paused(paused) => {
+                let pause_area = center_rect(
+                    display,
+                    Size {
+                        width: Paused::WIDTH,
+                        height: paused.height(),
+                    },
+                );
+                paused.render(pause_area, buf);
+            }
+            GameState::ConfirmRestart(ref confirm) => confirm.render(display, buf),
+            GameState::Interrupted(ref warning) => warning.render(display, buf),
+            GameState::Resuming { ticks_left } => {
+                let left = self.tick_period().saturating_mul(u32::from(ticks_left));
+                let secs = left.as_secs_f64().ceil();
+                direction
+                    .align_start(Line::from(tr_args(Msg::ResumingIn, &[("secs", &secs)])))
+                    .render(msg1_area, buf);
+            }
+            GameState::Celebrating {
+                ref post_mortem, ..
+            } => {
+                let headline = post_mortem
+                    .verdict
+                    .map_or_else(|| tr(Msg::YouWin), Verdict::headline);
+                direction
+                    .align_start(Line::from(format!("— {headline} —")))
+                    .render(msg1_area, buf);
+            }
+            GameState::Dead(ref pm) | GameState::Exhausted(ref pm) => {
+                let mut headline = vec![if let Some(verdict) = pm.verdict {
+                    verdict.headline()
+                } else if matches!(self.state, GameState::Exhausted(_)) {
+                    tr(Msg::YouWin)
+                } else {
+                    tr(Msg::GameOver)
+                }];
+                if pm.perfect {
+                    headline.push(tr(Msg::PerfectGame));
+                }
+                if pm.new_high_score {
+                    headline.push(tr(Msg::NewHighScore));
+                }
+                direction
+                    .align_start(Line::from(format!("— {} —", headline.join(" — "))))
+                    .render(msg1_area, buf);
+                let choices = if self.globals.kiosk {
+                    Line::from(tr(Msg::AnyKeyMainMenu))
+                } else {
+                    let mut choices = Line::from_iter([
+                        Span::raw(format!("{} {} (", tr(Msg::ChooseOne), tr(Msg::Restart))),
+                        Span::styled("r", consts::KEY_STYLE),
+                    ]);
+                    if self.can_rewind() {
+                        choices.push_span(Span::raw(format!(") — {} (", tr(Msg::Rewind))));
+                        choices.push_span(Span::styled("z", consts::KEY_STYLE));
+                    }
+                    if self.can_favorite() {
+                        choices.push_span(Span::raw(format!(") — {} (", tr(Msg::Favorite))));
+                        choices.push_span(Span::styled("f", consts::KEY_STYLE));
+                    }
+                    choices.extend([
+                        Span::raw(format!(") — {} (", tr(Msg::MainMenu))),
+                        Span::styled("m", consts::KEY_STYLE),
+                        Span::raw(format!(") — {} (", tr(Msg::Quit))),
+                        Span::styled("q", consts::KEY_STYLE),
+                        Span::raw(")"),
+                    ]);
+                    choices
+                };
+                direction.align_start(choices).render(msg2_area, buf);
+                // Draw the statistics in whichever part of the screen above
+                // or below the snake's head is taller so as not to hide the
+                // cause of death
+                let head_y = level_area
+                    .y
+                    .saturating_add(self.snake.head().y.saturating_sub(view.y));
+                let above = Rect {
+                    height: head_y.saturating_sub(main_area.y),
+                    ..main_area
+                };
+                let below = Rect {
+                    y: head_y.saturating_add(1),
+                    height: main_area.bottom().saturating_sub(head_y.saturating_add(1)),
+                    ..main_area
+                };
+                let stats_area = if above.height > below.height {
+                    above
+                } else {
+                    below
+                };
+                if let Some(ref prompt) = pm.name_prompt {
+                    prompt.render(display, buf);
+                } else {
+                    self.stats.render(stats_area, buf);
+                }
+                if let Some(ref warning) = pm.warning {
+                    warning.render(display, buf);
+                }
+            }
+        }
+        if let Some(ref prompt) = self.favorite_prompt {
+            prompt.render(display, buf);
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::options::LevelSize;
-    use crossterm::event::KeyCode;
-    use rand::SeedableRng;
-    use rand_chacha::ChaCha12Rng;
-    use rstest::rstest;
-    use std::collections::VecDeque;
+/// If `ev` is one of the keys for changing an option on the game-over
+/// screen, return `options` with the change applied.  Returns `None` for
+/// other keys and for changes that would go out of range.
+///
+/// - `w` toggles wraparound
+/// - `+` (or `=`) and `-` increase & decrease the number of fruits
+fn quick_option(mut options: Options, ev: KeyEvent) -> Option<Options> {
+    if ev
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        return None;
+    }
+    let before = options;
+    match ev.code {
+        KeyCode::Char('w') => options.modify(OptKey::Wraparound, Adjustable::toggle),
+        KeyCode::Char('+' | '=') => options.modify(OptKey::Fruits, Adjustable::increase),
+        KeyCode::Char('-') => options.modify(OptKey::Fruits, Adjustable::decrease),
+        _ => return None,
+    }
+    (options != before).then_some(options)
+}
+
+/// A portion of a [`Buffer`] that provides methods for drawing individual
+/// cells using coordinates relative to `origin`, which is drawn at the
+/// top-left corner of `area`.  Cells that fall outside of `area` are not
+/// drawn.
+#[derive(Debug, Eq, PartialEq)]
+struct Canvas<'a> {
+    area: Rect,
+    origin: Position,
+    buf: &'a mut Buffer,
+}
+
+impl Canvas<'_> {
+    /// Return the cell of the buffer at which `pos` is drawn, if it is inside
+    /// `area`
+    fn cell_mut(&mut self, pos: Position) -> Option<&mut Cell> {
+        let x = pos.x.checked_sub(self.origin.x)?;
+        let y = pos.y.checked_sub(self.origin.y)?;
+        if x >= self.area.width || y >= self.area.height {
+            return None;
+        }
+        self.buf
+            .cell_mut((self.area.x.checked_add(x)?, self.area.y.checked_add(y)?))
+    }
+
+    /// Set the cell at `pos` to `symbol`
+    fn draw_char(&mut self, pos: Position, symbol: char) {
+        if let Some(cell) = self.cell_mut(pos) {
+            cell.set_char(symbol);
+            cell.set_style(Style::reset());
+        }
+    }
+
+    /// Set the style of the cell at `pos` without changing its symbol
+    fn style_cell(&mut self, pos: Position, style: Style) {
+        if let Some(cell) = self.cell_mut(pos) {
+            cell.set_style(Style::reset().patch(style));
+        }
+    }
+
+    /// Set the cell at `pos` to `symbol` with the given style
+    fn draw_cell<S: AsRef<str>>(&mut self, pos: Position, symbol: S, style: Style) {
+        if let Some(cell) = self.cell_mut(pos) {
+            cell.set_symbol(symbol.as_ref());
+            cell.set_style(Style::reset().patch(style));
+        }
+    }
+}
+
+/// Return the row or column of the border around `level_area` that a snake
+/// moving in `direction` runs into, not including the corners
+fn border_side(level_area: Rect, direction: Direction) -> Rect {
+    match direction {
+        Direction::North => Rect {
+            y: level_area.y.saturating_sub(1),
+            height: 1,
+            ..level_area
+        },
+        Direction::South => Rect {
+            y: level_area.bottom(),
+            height: 1,
+            ..level_area
+        },
+        Direction::West => Rect {
+            x: level_area.x.saturating_sub(1),
+            width: 1,
+            ..level_area
+        },
+        Direction::East => Rect {
+            x: level_area.right(),
+            width: 1,
+            ..level_area
+        },
+    }
+}
+
+/// A widget for drawing a border made of dots around the edge of an area.
+///
+/// Like [`Block::bordered()`], but with different characters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct DottedBorder;
+
+impl Widget for DottedBorder {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let size = area.as_size();
+        let max_x = size.width.saturating_sub(1);
+        let max_y = size.height.saturating_sub(1);
+        let mut canvas = Canvas {
+            area,
+            origin: Position::ORIGIN,
+            buf,
+        };
+        canvas.draw_char(Position::ORIGIN, '·');
+        canvas.draw_char(Position::new(max_x, 0), '·');
+        canvas.draw_char(Position::new(max_x, max_y), '·');
+        canvas.draw_char(Position::new(0, max_y), '·');
+        for x in 1..max_x {
+            canvas.draw_char(Position::new(x, 0), '⋯');
+            canvas.draw_char(Position::new(x, max_y), '⋯');
+        }
+        for y in 1..max_y {
+            canvas.draw_char(Position::new(0, y), '⋮');
+            canvas.draw_char(Position::new(max_x, y), '⋮');
+        }
+    }
+}
+
+/// A widget for marking the sides of a level's border beyond which more of
+/// the level lies outside of the viewport.  Each such side gets an arrow in
+/// its middle pointing away from the level.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct ScrollMarkers {
+    /// The portion of the level shown on screen
+    view: Rect,
+
+    /// The size of the whole level
+    level: Size,
+}
+
+impl Widget for ScrollMarkers {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let size = area.as_size();
+        let mid_x = size.width / 2;
+        let mid_y = size.height / 2;
+        let max_x = size.width.saturating_sub(1);
+        let max_y = size.height.saturating_sub(1);
+        let mut canvas = Canvas {
+            area,
+            origin: Position::ORIGIN,
+            buf,
+        };
+        if self.view.y > 0 {
+            canvas.draw_char(Position::new(mid_x, 0), '▲');
+        }
+        if self.view.bottom() < self.level.height {
+            canvas.draw_char(Position::new(mid_x, max_y), '▼');
+        }
+        if self.view.x > 0 {
+            canvas.draw_char(Position::new(0, mid_y), '◀');
+        }
+        if self.view.right() < self.level.width {
+            canvas.draw_char(Position::new(max_x, mid_y), '▶');
+        }
+    }
+}
+
+/// An enum of the ways that the snake can be made to advance
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum TickSource {
+    /// The snake advances at regular intervals
+    #[default]
+    Timer,
+
+    /// The snake only advances when the step key is pressed.  Games in this
+    /// mode cannot set high scores.
+    Step,
+}
+
+/// An enum of the states that a game can be in
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum GameState {
+    /// The level is being generated in a background thread
+    Loading(LevelLoader),
+
+    /// The game is currently running
+    Running,
+
+    /// The game is currently paused
+    Paused(Paused),
+
+    /// The user pressed the restart key during play and is being asked to
+    /// confirm
+    ConfirmRestart(Confirm),
+
+    /// A problem arose during play, and the game is paused behind a pop-up
+    /// describing it until the pop-up is dismissed
+    Interrupted(Warning),
+
+    /// The game is counting down to resuming play after an interruption.
+    /// Once `ticks_left` reaches zero, the game enters the `Running` state.
+    Resuming { ticks_left: u8 },
+
+    /// The snake has collided with something, and the death animation is
+    /// playing.  Once `ticks_left` reaches zero, the game enters the `Dead`
+    /// state.
+    Dying {
+        ticks_left: u8,
+        post_mortem: PostMortem,
+    },
+
+    /// The game ended due to the snake colliding with something
+    Dead(PostMortem),
+
+    /// The snake has filled the board, and the victory animation is playing.
+    /// Once `ticks_left` reaches zero, the game enters the `Exhausted` state.
+    Celebrating {
+        ticks_left: u8,
+        post_mortem: PostMortem,
+    },
+
+    /// The snake has filled the board and there are no more spaces to place
+    /// fruits in
+    Exhausted(PostMortem),
+}
+
+/// A visual effect applied to a single cell of the level for a limited number
+/// of ticks
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct CellEffect {
+    /// The position of the affected cell
+    pos: Position,
+
+    /// The type of effect
+    kind: EffectKind,
+
+    /// The number of ticks remaining before the effect disappears
+    ticks_left: u8,
+}
+
+impl CellEffect {
+    /// Create a new effect of the given kind at `pos` that lasts for the
+    /// kind's duration
+    fn new(pos: Position, kind: EffectKind) -> CellEffect {
+        CellEffect {
+            pos,
+            kind,
+            ticks_left: kind.duration(),
+        }
+    }
+}
+
+/// An enum of the types of [`CellEffect`]s
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum EffectKind {
+    /// Flash the snake's head after it eats a fruit
+    EatFlash,
+
+    /// Draw the tail of the snake with a special glyph while it grows
+    GrowIn,
+
+    /// Make a newly-spawned fruit blink so that it's easier to spot
+    Spawn,
+}
+
+impl EffectKind {
+    /// Return the number of ticks for which an effect of this kind lasts
+    fn duration(self) -> u8 {
+        match self {
+            EffectKind::EatFlash | EffectKind::GrowIn => 1,
+            EffectKind::Spawn => consts::SPAWN_HIGHLIGHT_TICKS,
+        }
+    }
+}
+
+/// The dangers that the snake can face on its next move, as shown by
+/// collision warnings
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Hazard {
+    /// The snake will die
+    Fatal,
+
+    /// The snake will move into the cell that the end of its tail is leaving
+    CloseCall,
+}
+
+/// A short message displayed on the bottom border of the level for a limited
+/// number of ticks
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Toast {
+    /// The text of the message
+    text: String,
+
+    /// The number of ticks remaining before the message disappears
+    ticks_left: u8,
+}
+
+/// End-of-game report
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct PostMortem {
+    /// True if a new high score was set
+    new_high_score: bool,
+
+    /// A warning to display about an error, if any, that occurred while
+    /// updating the high score file
+    warning: Option<Warning>,
+
+    /// A prompt for the name of the player who set the new high score, shown
+    /// once any warning has been dismissed
+    name_prompt: Option<NamePrompt>,
+
+    /// Input received before this time is ignored, so that keys pressed just
+    /// before the game ended don't restart or quit it
+    held_until: Option<Instant>,
+
+    /// Where & how the snake died, if the game ended in a collision
+    crash: Option<Crash>,
+
+    /// True if the game ended with the snake filling every cell that it could
+    /// reach
+    perfect: bool,
+
+    /// How the game fared against the objectives of its custom level, if it
+    /// was played on one
+    verdict: Option<Verdict>,
+}
+
+/// The details of a collision that ended a game
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Crash {
+    /// What the snake ran into
+    cause: EndCause,
+
+    /// The position of the head of the snake that collided with something
+    head: Position,
+
+    /// The direction in which that snake was moving
+    direction: Direction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::powerups::ActivePowerUp;
+    use super::*;
+    use crate::config::CameraMode;
+    use crate::highscores::HighScores;
+    use crate::input::ScriptedEvents;
+    use crate::options::{FruitQty, LevelSize, Options};
+    use crate::util::{Bounds, LayoutDirection};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+    use ratatui::style::Color;
+    use rstest::rstest;
+
+    const RNG_SEED: u64 = 0x0123456789ABCDEF;
+
+    #[test]
+    fn new_game() {
+        let game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/new_game", &buffer);
+    }
+
+    #[test]
+    fn score_bar_right_to_left() {
+        let mut globals = Globals::default();
+        globals.config.layout_direction = LayoutDirection::RightToLeft;
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let mut expected = Buffer::with_lines([
+            " High Score: -                                                         Score: 0 ",
+        ]);
+        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
+        pretty_assertions::assert_eq!(buffer.content[..80], expected.content);
+    }
+
+    #[test]
+    fn fruit_arrow() {
+        let mut globals = Globals::default();
+        globals.config.game.fruit_arrow = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(10, 10);
+        game.fruits = HashSet::from([Position::new(30, 2), Position::new(2, 11)]);
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..13).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert_eq!(score_bar, " ←  Score: 0 ");
+        game.fruits.clear();
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..10).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert_eq!(score_bar, " Score: 0 ");
+    }
+
+    #[test]
+    fn border_title() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert_eq!(game.border_title(76).as_deref(), Some(" Arcade "));
+        game.seed = Some(0x3F2A_0123_4567_89AB);
+        assert_eq!(
+            game.border_title(76).as_deref(),
+            Some(" Arcade #3F2A0123456789AB ")
+        );
+        assert_eq!(game.border_title(20).as_deref(), Some(" Arcade #3F2A… "));
+        assert_eq!(game.border_title(10).as_deref(), Some(" Arcade "));
+        assert_eq!(game.border_title(6), None);
+    }
+
+    /// Process input for `game` with no events until the level finishes
+    /// generating
+    fn finish_loading(game: &mut Game<ChaCha12Rng>) {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while matches!(game.state, GameState::Loading(_)) {
+            assert!(Instant::now() < deadline, "level generation took too long");
+            assert!(game
+                .process_input(&mut ScriptedEvents::new([]))
+                .unwrap()
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn background_generation() {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::custom(500, 200).unwrap();
+        globals.options.obstacles = true;
+        let mut game = Game::new_with_rng(globals.clone(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert!(matches!(game.state, GameState::Loading(_)));
+        assert!(game.fruits.is_empty());
+        assert_eq!(game.tick_timing(), None);
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let middle = (0..80)
+            .map(|x| buffer[(x, 12)].symbol())
+            .collect::<String>();
+        assert!(middle.contains("| Generating level…"), "{middle:?}");
+        finish_loading(&mut game);
+        assert!(game.running());
+        assert!(!game.map.obstacles().is_empty());
+        assert!(!game.map.obstacles().contains(&game.snake.head()));
+        assert_eq!(game.fruits.len(), globals.options.fruits.get());
+        // The level is still determined by the RNG seed
+        let mut game2 = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        finish_loading(&mut game2);
+        assert_eq!(game.map, game2.map);
+        assert_eq!(game.fruits, game2.fruits);
+    }
+
+    #[test]
+    fn quit_while_loading() {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::custom(500, 200).unwrap();
+        globals.options.layout = LevelLayout::Maze;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert!(matches!(game.state, GameState::Loading(_)));
+        let mut events = ScriptedEvents::new([Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        ))]);
+        let mut screen = None;
+        while screen.is_none() && matches!(game.state, GameState::Loading(_)) {
+            screen = game.process_input(&mut events).unwrap();
+        }
+        // If the maze finished generating before the key was read, the key
+        // quits the running game instead
+        if screen.is_none() {
+            screen = game.process_input(&mut events).unwrap();
+        }
+        assert!(matches!(screen, Some(Screen::Quit)));
+    }
+
+    #[test]
+    fn race_pace() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.race(
+            Rival::new(&HighScore {
+                score: NonZeroU32::new(30).unwrap(),
+                slow_motion: false,
+                achieved: None,
+                duration_ms: Some(120_000),
+                name: None,
+            })
+            .unwrap(),
+        );
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..36).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert_eq!(score_bar, " Score: 0  Pace 0.0/15.0 per min    ");
+        assert_eq!(buffer[(11, 0)].fg, consts::PACE_AHEAD_STYLE.fg.unwrap());
+        game.score = 10;
+        game.stats
+            .record_move(Duration::from_secs(60), Direction::North, 3);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..36).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert_eq!(score_bar, " Score: 10  Pace 10.0/15.0 per min  ");
+        assert_eq!(buffer[(12, 0)].fg, consts::PACE_BEHIND_STYLE.fg.unwrap());
+    }
+
+    #[test]
+    fn spawn_spread() {
+        let mut globals = Globals::default();
+        globals.options.fruits = FruitQty::new(5).unwrap();
+        globals.options.spawn = SpawnStrategy::Spread;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let regions = game.map.bounds().regions(5);
+        assert_eq!(regions.len(), 5);
+        let one_per_region = |game: &Game<ChaCha12Rng>| {
+            regions
+                .iter()
+                .all(|r| game.fruits.iter().filter(|&&f| r.contains(f)).count() == 1)
+        };
+        assert_eq!(game.fruits.len(), 5);
+        assert!(one_per_region(&game));
+        let &eaten = game.fruits.iter().next().unwrap();
+        game.fruits.remove(&eaten);
+        game.place_fruit();
+        assert_eq!(game.fruits.len(), 5);
+        assert!(one_per_region(&game));
+    }
+
+    #[test]
+    fn spawn_far_from_snake() {
+        let mut globals = Globals::default();
+        globals.options.spawn = SpawnStrategy::FarFromSnake;
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let bounds = game.map.bounds();
+        let head = game.snake.head();
+        let farthest = bounds
+            .positions()
+            .map(|p| bounds.distance(head, p))
+            .max()
+            .unwrap();
+        assert_eq!(game.fruits.len(), 1);
+        assert!(game
+            .fruits
+            .iter()
+            .all(|&p| bounds.distance(head, p) == farthest));
+    }
+
+    #[test]
+    fn spawn_near_walls() {
+        let mut globals = Globals::default();
+        globals.options.obstacles = true;
+        globals.options.fruits = FruitQty::new(10).unwrap();
+        globals.options.spawn = SpawnStrategy::NearWalls;
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert_eq!(game.fruits.len(), 10);
+        assert!(game.fruits.iter().all(|&p| game.map.is_near_wall(p)));
+    }
+
+    #[test]
+    fn new_wraparound_game() {
+        let mut globals = Globals::default();
+        globals.options.wraparound = true;
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/new_wraparound_game", &buffer);
+    }
+
+    #[test]
+    fn new_game_with_background() {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::Small;
+        globals.config.glyphs.background = Some(crate::config::Glyph {
+            symbol: "·".parse().unwrap(),
+            style: Style::new().add_modifier(Modifier::DIM),
+        });
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/new_game_with_background", &buffer);
+    }
+
+    /// Test that drawing only the cells of the level that changed since the
+    /// previous frame gives the same result as drawing every cell, over many
+    /// games with randomly-steered snakes.  Frames are sometimes skipped so
+    /// that both ways of bringing the level up to date get used.
+    #[rstest]
+    #[case(LevelSize::Small, true)]
+    #[case(LevelSize::Custom { width: 120, height: 40 }, false)]
+    fn incremental_rendering(#[case] level_size: LevelSize, #[case] wraparound: bool) {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let mut globals = Globals {
+            options: Options {
+                wraparound,
+                obstacles: true,
+                fruits: FruitQty::new(5).unwrap(),
+                level_size,
+                power_ups: true,
+                hydra: true,
+                adaptive: true,
+                terrain: true,
+                ..Options::default()
+            },
+            ..Globals::in_tempdir(&tmpdir, "")
+        };
+        globals.config.glyphs.background = Some(crate::config::Glyph {
+            symbol: "·".parse().unwrap(),
+            style: Style::new().add_modifier(Modifier::DIM),
+        });
+        globals.config.glyphs.ghost_head = Some(crate::config::CellStyle {
+            style: Style::new().add_modifier(Modifier::DIM),
+        });
+        let mut rng = ChaCha12Rng::seed_from_u64(RNG_SEED);
+        let area = Rect::new(0, 0, 80, 24);
+        let mut game = Game::new_with_rng(globals.clone(), ChaCha12Rng::seed_from_u64(0));
+        for i in 1..300 {
+            if !game.running() && !matches!(game.state, GameState::Dying { .. }) {
+                game = Game::new_with_rng(globals.clone(), ChaCha12Rng::seed_from_u64(i));
+            }
+            if rng.random_bool(0.2) {
+                let direction = [
+                    Direction::North,
+                    Direction::South,
+                    Direction::East,
+                    Direction::West,
+                ]
+                .choose(&mut rng)
+                .copied()
+                .unwrap();
+                game.steer(direction);
+            }
+            game.advance();
+            if rng.random_bool(0.1) {
+                continue;
+            }
+            let mut incremental = Buffer::empty(area);
+            game.render(area, &mut incremental);
+            let full_game = game.clone();
+            full_game.layer.borrow_mut().invalidate();
+            let mut full = Buffer::empty(area);
+            full_game.render(area, &mut full);
+            pretty_assertions::assert_eq!(incremental, full, "frame {i}");
+        }
+    }
+
+    #[test]
+    fn seeded_rng() {
+        let mut globals = Globals::default();
+        globals.config.game.rng = RngMode::Seeded(42);
+        let game1 = Game::new(globals.clone());
+        let game2 = Game::new(globals);
+        assert_eq!(game1.seed, Some(42));
+        assert_eq!(game1.rng_mode, RngMode::Seeded(42));
+        assert_eq!(game1.fruits, game2.fruits);
+    }
+
+    #[test]
+    fn new_game_with_high_score() {
+        let mut globals = Globals::default();
+        globals
+            .high_scores
+            .insert(globals.options, NonZeroU32::new(42).unwrap().into());
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/new_game_with_high_score", &buffer);
+    }
+
+    #[rstest]
+    #[case(false, true)]
+    #[case(true, false)]
+    fn move_into_tail_tip(#[case] strict: bool, #[case] survives: bool) {
+        let mut globals = Globals::default();
+        globals.options.strict_collisions = strict;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(31, 7);
+        game.snake.set_body([
+            Position::new(31, 6),
+            Position::new(30, 6),
+            Position::new(30, 7),
+        ]);
+        game.snake.max_len = 3;
+        game.snake.direction = Direction::North;
+        game.advance();
+        assert_eq!(game.snake.head, Position::new(31, 6));
+        assert_eq!(game.running(), survives);
+        assert_eq!(game.dying(), !survives);
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn plow_through_obstacle(#[case] plow: bool) {
+        let mut globals = Globals::default();
+        globals.options.obstacles = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let target = game
+            .map
+            .obstacles()
+            .iter()
+            .copied()
+            .find(|p| {
+                p.x > 0
+                    && !game.map.obstacles().contains(&Position::new(p.x - 1, p.y))
+                    && !game.fruits.contains(&Position::new(p.x - 1, p.y))
+            })
+            .unwrap();
+        game.snake.head = Position::new(target.x - 1, target.y);
+        game.snake.set_body([]);
+        game.snake.direction = Direction::East;
+        if plow {
+            game.snake.gain(PowerUpKind::Plow);
+        }
+        game.advance();
+        assert_eq!(game.snake.head, target);
+        assert_eq!(game.running(), plow);
+        assert_eq!(game.map.obstacles().contains(&target), !plow);
+        assert!(game.snake.power_ups.is_empty());
+    }
+
+    #[rstest]
+    #[case(false, Position::new(0, 6), None)]
+    #[case(true, Position::new(0, 6), Some((77, 8)))]
+    #[case(false, Position::new(10, 6), Some((11, 8)))]
+    fn ghost_head(
+        #[case] wraparound: bool,
+        #[case] head: Position,
+        #[case] ghost: Option<(u16, u16)>,
+    ) {
+        let mut globals = Globals::default();
+        globals.options.wraparound = wraparound;
+        globals.config.glyphs.ghost_head = Some(crate::config::CellStyle {
+            style: Style::new().add_modifier(Modifier::DIM),
+        });
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(40, 15)]);
+        game.snake.head = head;
+        game.snake.set_body([]);
+        game.snake.direction = Direction::West;
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let ghosts = buffer
+            .content()
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.modifier.contains(Modifier::DIM))
+            .map(|(i, cell)| {
+                let (x, y) = buffer.pos_of(i);
+                assert_eq!(cell.symbol(), ">");
+                (x, y)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ghosts, Vec::from_iter(ghost));
+    }
+
+    #[test]
+    fn star_invincibility() {
+        let mut globals = Globals::default();
+        globals.options.obstacles = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let height = game.map.bounds().height;
+        let target = game
+            .map
+            .obstacles()
+            .iter()
+            .copied()
+            .filter(|p| {
+                p.x > 2
+                    && p.y + 2 < height
+                    && !game.map.obstacles().contains(&Position::new(p.x - 1, p.y))
+            })
+            .min_by_key(|p| (p.y, p.x))
+            .unwrap();
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(target.x - 1, target.y);
+        game.snake.set_body([Position::new(target.x - 2, target.y)]);
+        game.snake.direction = Direction::East;
+        game.snake.gain(PowerUpKind::Star);
+        game.snake.gain(PowerUpKind::Plow);
+        game.advance();
+        assert_eq!(game.snake.head, target);
+        assert!(game.running());
+        // The obstacle is left intact, and the plow is not used up
+        assert!(game.map.obstacles().contains(&target));
+        assert_eq!(game.snake.invincible_ticks(), Some(consts::STAR_TICKS - 1));
+        assert_eq!(game.snake.power_ups.len(), 2);
+
+        // The snake blinks on alternate ticks
+        let area = Rect::new(0, 0, 80, 24);
+        let head_cell = (target.x + 2, target.y + 2);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[head_cell].fg, Color::LightMagenta);
+        // Move into the snake's own body
+        let mut body = game.snake.body().clone();
+        body.push_back(Position::new(target.x, target.y + 1));
+        game.snake.set_body(body);
+        game.snake.direction = Direction::South;
+        game.advance();
+        assert!(game.running());
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[(target.x + 2, target.y + 3)].fg, Color::Green);
+
+        // Once the star wears off, collisions are fatal again
+        game.snake.power_ups[0].ticks_left = Some(1);
+        let mut body = game.snake.body().clone();
+        body.push_back(Position::new(target.x, target.y + 2));
+        game.snake.set_body(body);
+        game.advance();
+        assert_eq!(game.snake.invincible_ticks(), None);
+        assert!(game.dying());
+    }
+
+    #[test]
+    fn cell_aspect() {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::Large;
+        globals.config.cell_aspect = crate::util::CellAspect::new(1, 1);
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert_eq!(game.map.size(), Size::new(38, 19));
+        game.snake.direction = Direction::East;
+        assert_eq!(game.move_period(), consts::TICK_PERIOD);
+        game.snake.direction = Direction::North;
+        assert_eq!(game.move_period(), consts::TICK_PERIOD);
+        game.globals.config.cell_aspect = crate::util::CellAspect::new(1, 2);
+        assert_eq!(game.move_period(), consts::TICK_PERIOD * 2);
+        game.snake.direction = Direction::West;
+        assert_eq!(game.move_period(), consts::TICK_PERIOD);
+    }
+
+    #[test]
+    fn timed_power_ups() {
+        let mut globals = Globals::default();
+        globals.options.wraparound = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.direction = Direction::East;
+        game.snake.gain(PowerUpKind::SpeedUp);
+        assert_eq!(game.tick_period(), Duration::from_nanos(133_333_333));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..22).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert_eq!(score_bar, " Score: 0  » Fast 10s ");
+        for _ in 1..consts::SPEED_UP_TICKS {
+            game.advance();
+        }
+        // Eating another speed-up restarts the timer rather than stacking
+        game.snake.gain(PowerUpKind::SpeedUp);
+        assert_eq!(
+            game.snake.power_ups,
+            [ActivePowerUp::new(PowerUpKind::SpeedUp)]
+        );
+        game.snake.gain(PowerUpKind::SlowDown);
+        assert_eq!(game.tick_period(), Duration::from_nanos(199_999_999));
+        for _ in 0..consts::SLOW_DOWN_TICKS {
+            game.advance();
+        }
+        assert_eq!(
+            game.snake.power_ups,
+            [ActivePowerUp {
+                kind: PowerUpKind::SpeedUp,
+                ticks_left: Some(consts::SPEED_UP_TICKS - consts::SLOW_DOWN_TICKS),
+            }]
+        );
+        for _ in consts::SLOW_DOWN_TICKS..consts::SPEED_UP_TICKS {
+            game.advance();
+        }
+        assert!(game.running());
+        assert!(game.snake.power_ups.is_empty());
+        assert_eq!(game.tick_period(), consts::TICK_PERIOD);
+    }
+
+    #[test]
+    fn eat_power_up() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.direction = Direction::East;
+        game.power_ups = vec![PowerUp {
+            pos: Position::new(31, 6),
+            kind: PowerUpKind::Plow,
+        }];
+        game.snake.gain(PowerUpKind::Plow);
+        game.advance();
+        assert!(game.power_ups.is_empty());
+        assert_eq!(
+            game.snake.power_ups,
+            [
+                ActivePowerUp::new(PowerUpKind::Plow),
+                ActivePowerUp::new(PowerUpKind::Plow)
+            ]
+        );
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..20).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert_eq!(score_bar, " Score: 0  ♦ Plow×2 ");
+        assert_eq!(buffer[(11, 0)].fg, Color::LightCyan);
+    }
+
+    #[test]
+    fn fill_meter() {
+        let mut globals = Globals::default();
+        globals.config.game.fill_meter = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.direction = Direction::East;
+        game.snake
+            .set_body([Position::new(28, 6), Position::new(29, 6)]);
+        game.snake.max_len = 2;
+        game.reachable = (25..35).map(|x| Position::new(x, 6)).collect();
+        assert_eq!(game.fill_percent(), 30);
+        game.advance();
+        assert_eq!(game.stats.peak_fill(), 30);
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..20).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert_eq!(score_bar, " Score: 0  Fill: 30%");
+    }
+
+    #[test]
+    fn mud() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.direction = Direction::East;
+        game.map.add_terrain(Position::new(31, 6), Terrain::Mud);
+        game.map.add_terrain(Position::new(32, 6), Terrain::Mud);
+        let mut heads = Vec::new();
+        for _ in 0..6 {
+            game.advance();
+            heads.push(game.snake.head().x);
+        }
+        // The snake spends two ticks on each mud tile
+        assert_eq!(heads, [31, 31, 32, 32, 33, 34]);
+        assert_eq!(game.ticks, 6);
+    }
+
+    #[test]
+    fn ice() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.direction = Direction::East;
+        game.snake.turn(Direction::East);
+        game.advance();
+        game.map.add_terrain(Position::new(32, 6), Terrain::Ice);
+        game.advance();
+        assert_eq!(game.snake.head(), Position::new(32, 6));
+        // A turn made on ice only takes effect once the snake slides off
+        game.steer(Direction::South);
+        assert_eq!(game.next_direction(), Direction::East);
+        game.advance();
+        assert_eq!(game.snake.head(), Position::new(33, 6));
+        game.advance();
+        assert_eq!(game.snake.head(), Position::new(33, 7));
+    }
+
+    #[rstest]
+    #[case(Direction::East, true)]
+    #[case(Direction::North, false)]
+    fn one_way_gate(#[case] gate: Direction, #[case] passable: bool) {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.direction = Direction::East;
+        game.map
+            .add_terrain(Position::new(31, 6), Terrain::Gate(gate));
+        assert_eq!(game.hazard_ahead().is_none(), passable);
+        game.advance();
+        assert_eq!(game.snake.head(), Position::new(31, 6));
+        assert_eq!(game.running(), passable);
+    }
+
+    #[test]
+    fn self_collision() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.score = 3;
+        game.snake.head = Position::new(30, 6);
+        game.snake.set_body([
+            Position::new(30, 6),
+            Position::new(31, 6),
+            Position::new(32, 6),
+            Position::new(33, 6),
+            Position::new(33, 7),
+            Position::new(33, 8),
+            Position::new(33, 9),
+            Position::new(32, 9),
+            Position::new(31, 9),
+            Position::new(30, 9),
+            Position::new(30, 8),
+            Position::new(30, 7),
+        ]);
+        game.snake.max_len = 12;
+        game.snake.direction = Direction::North;
+        game.state = GameState::Dead(PostMortem {
+            new_high_score: false,
+            warning: None,
+            name_prompt: None,
+            held_until: None,
+            crash: None,
+            perfect: false,
+            verdict: None,
+        });
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/self_collision", &buffer);
+    }
+
+    fn live_high_score_game(tmpdir: &tempfile::TempDir) -> Game<ChaCha12Rng> {
+        let globals = Globals::in_tempdir(tmpdir, "[game]\nlive-high-score = true\n");
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.high_score = NonZeroU32::new(1);
+        game.snake.head = Position::new(10, 10);
+        game.snake.direction = Direction::East;
+        game
+    }
+
+    fn eat_ahead(game: &mut Game<ChaCha12Rng>) {
+        game.fruits = HashSet::from([Position::new(game.snake.head.x + 1, 10)]);
+        game.advance();
+        assert!(game.running());
+    }
+
+    #[test]
+    fn live_high_score() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let scoredir = tmpdir.path().join("highscores");
+        let scorefile = scoredir.join(HighScores::ARCADE_FILE_NAME);
+        let mut game = live_high_score_game(&tmpdir);
+        let options = game.globals.options;
+        let saved = || {
+            HighScores::load(&scorefile)
+                .unwrap()
+                .get(options)
+                .map(NonZeroU32::get)
+        };
+        eat_ahead(&mut game);
+        assert!(!scorefile.exists());
+        eat_ahead(&mut game);
+        assert_eq!(saved(), Some(2));
+        // Further saves wait until the save interval has passed
+        eat_ahead(&mut game);
+        assert_eq!(saved(), Some(2));
+        game.last_live_save = game
+            .last_live_save
+            .and_then(|t| t.checked_sub(consts::LIVE_SAVE_INTERVAL));
+        eat_ahead(&mut game);
+        assert_eq!(saved(), Some(4));
+        assert_eq!(game.deferred_warning, None);
+    }
+
+    #[test]
+    fn live_high_score_error() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        // The high scores "directory" is a file, so saving will fail
+        let scoredir = tmpdir.path().join("highscores");
+        std::fs::write(&scoredir, "").unwrap();
+        let mut game = live_high_score_game(&tmpdir);
+        eat_ahead(&mut game);
+        game.fruits = HashSet::from([Position::new(game.snake.head.x + 1, 10)]);
+        game.advance();
+        // The game is paused behind the warning until it's dismissed, after
+        // which it counts down to resuming
+        assert!(matches!(game.state, GameState::Interrupted(_)));
+        game.advance();
+        assert_eq!(game.score, 2);
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Enter.into()))
+            .is_none());
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let message = (0..80)
+            .map(|x| buffer[(x, 22)].symbol())
+            .collect::<String>();
+        assert_eq!(message.trim(), "Resuming in 3…");
+        for _ in 0..consts::RESUME_COUNTDOWN_TICKS {
+            assert!(!game.running());
+            game.advance();
+        }
+        assert!(game.running());
+        // No further mid-game saves are attempted
+        game.last_live_save = None;
+        eat_ahead(&mut game);
+        assert_eq!(game.deferred_warning, None);
+        let post_mortem = game.finalize_score(EndCause::Wall);
+        assert!(post_mortem.new_high_score);
+        assert!(post_mortem.warning.is_some());
+    }
+
+    #[test]
+    fn record_pace() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.high_score = NonZeroU32::new(1);
+        game.snake.head = Position::new(10, 10);
+        game.snake.direction = Direction::East;
+        let area = Rect::new(0, 0, 80, 24);
+        for _ in 0..2 {
+            game.fruits = HashSet::from([Position::new(game.snake.head.x + 1, 10)]);
+            game.advance();
+            assert!(game.running());
+        }
+        assert_eq!(game.score, 2);
+        assert!(game.record_pace);
+        assert_eq!(
+            game.toast.as_ref().map(|t| t.text.as_str()),
+            Some("New record pace!")
+        );
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let cell = &buffer[(0, 0)];
+        assert_eq!(cell.fg, Color::Yellow);
+        assert!(cell.modifier.contains(Modifier::BOLD | Modifier::REVERSED));
+        // The announcement is only made once per game
+        game.toast = None;
+        game.fruits = HashSet::from([Position::new(game.snake.head.x + 1, 10)]);
+        game.advance();
+        assert_eq!(game.score, 3);
+        assert_eq!(game.toast, None);
+    }
+
+    #[test]
+    fn hide_score() {
+        let mut globals = Globals::default();
+        globals.config.game.hide_score = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.score = 42;
+        game.high_score = NonZeroU32::new(100);
+        let area = Rect::new(0, 0, 80, 24);
+        let score_bar = |game: &Game<ChaCha12Rng>| {
+            let mut buffer = Buffer::empty(area);
+            game.render(area, &mut buffer);
+            (0..80).map(|x| buffer[(x, 0)].symbol()).collect::<String>()
+        };
+        let bar = score_bar(&game);
+        assert!(bar.starts_with(" Score: ??? "), "{bar:?}");
+        assert!(bar.ends_with(" High Score: ??? "), "{bar:?}");
+        game.state = GameState::Dead(PostMortem {
+            new_high_score: false,
+            warning: None,
+            name_prompt: None,
+            held_until: None,
+            crash: None,
+            perfect: false,
+            verdict: None,
+        });
+        let bar = score_bar(&game);
+        assert!(bar.starts_with(" Score: 42 "), "{bar:?}");
+        assert!(bar.ends_with(" High Score: 100 "), "{bar:?}");
+    }
+
+    #[test]
+    fn wall_collision() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(0, 5);
+        game.snake.direction = Direction::West;
+        game.advance();
+        assert!(game.dying());
+        assert_eq!(
+            game.crash_report(),
+            Some(Crash {
+                cause: EndCause::Wall,
+                head: Position::new(0, 5),
+                direction: Direction::West,
+            })
+        );
+        while game.dying() {
+            game.advance();
+        }
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/wall_collision", &buffer);
+        // The collision is drawn on the left border, which is styled to
+        // match, while the head keeps its usual glyph
+        let glyphs = &game.globals.config.glyphs;
+        assert_eq!(buffer[(1, 7)].symbol(), glyphs.collision.symbol.as_ref());
+        assert_eq!(buffer[(1, 2)].fg, Color::LightRed);
+        assert_eq!(buffer[(2, 7)].symbol(), ">");
+    }
+
+    #[test]
+    fn self_collision_new_high_score() {
+        let mut globals = Globals::default();
+        globals
+            .high_scores
+            .insert(globals.options, NonZeroU32::new(2).unwrap().into());
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.score = 3;
+        game.snake.head = Position::new(30, 6);
+        game.snake.set_body([
+            Position::new(30, 6),
+            Position::new(31, 6),
+            Position::new(32, 6),
+            Position::new(33, 6),
+            Position::new(33, 7),
+            Position::new(33, 8),
+            Position::new(33, 9),
+            Position::new(32, 9),
+            Position::new(31, 9),
+            Position::new(30, 9),
+            Position::new(30, 8),
+            Position::new(30, 7),
+        ]);
+        game.snake.max_len = 12;
+        game.snake.direction = Direction::North;
+        game.state = GameState::Dead(PostMortem {
+            new_high_score: true,
+            warning: None,
+            name_prompt: None,
+            held_until: None,
+            crash: None,
+            perfect: false,
+            verdict: None,
+        });
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/self_collision_new_high_score", &buffer);
+    }
+
+    #[test]
+    fn new_medium_game() {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::Medium;
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/new_medium_game", &buffer);
+    }
+
+    #[test]
+    fn scrolled_viewport() {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::custom(200, 100).unwrap();
+        globals.options.obstacles = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        // The level starts out centered on the snake in the middle of the
+        // level
+        assert_eq!(game.viewport(), Rect::new(62, 41, 76, 19));
+        game.snake.head = Position::new(3, 97);
+        game.snake
+            .set_body([Position::new(1, 97), Position::new(2, 97)]);
+        game.snake.direction = Direction::East;
+        game.fruits = HashSet::from([Position::new(10, 95), Position::new(100, 50)]);
+        game.camera.follow(game.map.size(), game.snake.head());
+        // The view stops at the edges of the level
+        assert_eq!(game.viewport(), Rect::new(0, 81, 76, 19));
+        let buffer = crate::snapshot::render(&game);
+        crate::snapshot::assert_snapshot("game/scrolled_viewport", &buffer);
+    }
+
+    #[rstest]
+    #[case(CameraMode::Centered, false)]
+    #[case(CameraMode::Centered, true)]
+    #[case(CameraMode::DeadZone, false)]
+    #[case(CameraMode::DeadZone, true)]
+    fn head_stays_in_viewport(#[case] camera: CameraMode, #[case] wraparound: bool) {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::custom(200, 100).unwrap();
+        globals.options.wraparound = wraparound;
+        globals.config.game.camera = camera;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let mut rng = ChaCha12Rng::seed_from_u64(RNG_SEED);
+        let mut ticks = 0;
+        while game.running() && ticks < 2000 {
+            // Wander the level at random, avoiding walls & doubling back
+            let dirs = [
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ];
+            let reverse = game.snake.direction.reverse();
+            if rng.random_ratio(1, 8) {
+                game.snake.turn(dirs[rng.random_range(0..4)]);
+                if game.snake.direction == reverse {
+                    game.snake.turn(reverse.reverse());
+                }
+            }
+            for dir in dirs {
+                if game.hazard_ahead() != Some(Hazard::Fatal) {
+                    break;
+                }
+                if dir != reverse {
+                    game.snake.turn(dir);
+                }
+            }
+            game.advance();
+            let view = game.viewport();
+            assert!(
+                view.contains(game.snake.head()),
+                "head at {} outside of view {view} after {ticks} ticks",
+                game.snake.head()
+            );
+            ticks += 1;
+        }
+        assert!(ticks > 500, "snake died after only {ticks} ticks");
+    }
+
+    #[test]
+    fn paused() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        assert!(game.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/paused", &buffer);
+    }
+
+    #[test]
+    fn restart_confirm() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert!(game.handle_event(Event::Key(ctrl_r)).is_none());
+        assert!(matches!(game.state, GameState::ConfirmRestart(_)));
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('n').into()))
+            .is_none());
+        assert_eq!(game.state, GameState::Running);
+        assert!(game.handle_event(Event::Key(ctrl_r)).is_none());
+        assert!(matches!(
+            game.handle_event(Event::Key(KeyCode::Char('y').into())),
+            Some(Screen::Game(_))
+        ));
+    }
+
+    #[test]
+    fn game_over_hold() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(0, 5);
+        game.snake.direction = Direction::West;
+        game.advance();
+        for _ in 0..consts::DEATH_ANIMATION_TICKS {
+            game.advance();
+        }
+        assert!(matches!(game.state, GameState::Dead(_)));
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('r').into()))
+            .is_none());
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('q').into()))
+            .is_none());
+        assert!(matches!(game.state, GameState::Dead(_)));
+        if let GameState::Dead(ref mut post_mortem) = game.state {
+            post_mortem.held_until = Some(Instant::now());
+        }
+        assert!(matches!(
+            game.handle_event(Event::Key(KeyCode::Char('r').into())),
+            Some(Screen::Game(_))
+        ));
+    }
+
+    #[test]
+    fn game_over_hold_allows_interrupt() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.state = GameState::Dead(game.hold(PostMortem::default()));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(matches!(
+            game.handle_event(Event::Key(ctrl_c)),
+            Some(Screen::Quit)
+        ));
+    }
+
+    #[test]
+    fn record_history() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let globals = Globals::in_tempdir(&tmpdir, "");
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.seed = Some(RNG_SEED);
+        game.score = 3;
+        game.snake.head = Position::new(0, 5);
+        game.snake.direction = Direction::West;
+        game.advance();
+        assert!(game.dying());
+        let records = game.globals.config.load_history().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].options, game.globals.options);
+        assert_eq!(records[0].seed, Some(RNG_SEED));
+        assert_eq!(records[0].score, 3);
+        assert_eq!(records[0].cause, EndCause::Wall);
+    }
+
+    #[test]
+    fn slow_motion_flags_high_score() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let globals = Globals::in_tempdir(&tmpdir, "");
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('z').into()))
+            .is_none());
+        assert!(game.slow_motion);
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('z').into()))
+            .is_none());
+        assert!(!game.slow_motion);
+        assert!(game.used_slow_motion);
+        game.score = 5;
+        let pm = game.finalize_score(EndCause::Body);
+        assert!(pm.new_high_score);
+        assert_eq!(pm.warning, None);
+        let ranked = game.globals.high_scores.ranked();
+        assert_eq!(ranked.len(), 1);
+        let (opts, ref hs) = ranked[0];
+        assert_eq!(opts, game.globals.options);
+        assert_eq!(hs.score, NonZeroU32::new(5).unwrap());
+        assert!(hs.slow_motion);
+        assert!(hs.achieved.is_some());
+    }
+
+    #[test]
+    fn name_prompt_after_high_score() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let globals =
+            Globals::in_tempdir(&tmpdir, "[game]\nask-name = true\ngame-over-hold-ms = 0\n");
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.score = 5;
+        game.state = GameState::Dead(game.finalize_score(EndCause::Body));
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/name_prompt", &buffer);
+        // Keys that normally choose a game-over option are typed instead
+        for c in "Quinn".chars() {
+            assert!(game
+                .handle_event(Event::Key(KeyCode::Char(c).into()))
+                .is_none());
+        }
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Enter.into()))
+            .is_none());
+        let GameState::Dead(ref pm) = game.state else {
+            panic!("Game should still be over");
+        };
+        assert_eq!(pm.name_prompt, None);
+        assert_eq!(pm.warning, None);
+        let saved = game.globals.config.load_high_scores().unwrap().value;
+        let ranked = saved.ranked();
+        assert_eq!(ranked.len(), 1);
+        let (_, ref hs) = ranked[0];
+        assert_eq!(hs.score, NonZeroU32::new(5).unwrap());
+        assert_eq!(hs.name.as_deref(), Some("Quinn"));
+    }
+
+    #[test]
+    fn runner_up_score_is_ranked() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let mut globals = Globals::in_tempdir(&tmpdir, "[game]\ngame-over-hold-ms = 0\n");
+        globals
+            .high_scores
+            .insert(globals.options, NonZeroU32::new(10).unwrap().into());
+        let mut game = Game::new_with_rng(globals.clone(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.score = 5;
+        let pm = game.finalize_score(EndCause::Body);
+        assert!(!pm.new_high_score);
+        assert_eq!(pm.warning, None);
+        let saved = game.globals.config.load_high_scores().unwrap().value;
+        let scores = saved
+            .ranked()
+            .into_iter()
+            .map(|(_, hs)| hs.score.get())
+            .collect::<Vec<_>>();
+        assert_eq!(scores, [10, 5]);
+        // Once the table is full, lower scores are not recorded
+        for _ in 1..consts::HIGH_SCORES_PER_OPTIONS {
+            globals
+                .high_scores
+                .insert(globals.options, NonZeroU32::new(20).unwrap().into());
+        }
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.score = 5;
+        let _ = game.finalize_score(EndCause::Body);
+        assert_eq!(game.saved_score, None);
+        assert_eq!(
+            game.globals.high_scores.ranked().len(),
+            consts::HIGH_SCORES_PER_OPTIONS
+        );
+    }
+
+    #[rstest]
+    #[case(GameState::Paused(Paused::new()))]
+    #[case(GameState::Dead(PostMortem::default()))]
+    fn idle_timeout(#[case] state: GameState) {
+        let mut globals = Globals::default();
+        globals.config.game.idle_timeout_mins = 1;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.state = state;
+        // Input within the timeout is handled as usual
+        let mut events = ScriptedEvents::new([Event::Key(KeyCode::Char('x').into())]);
+        assert!(game.process_input(&mut events).unwrap().is_none());
+        // Once the timeout has passed, the game is abandoned
+        game.last_activity -= Duration::from_secs(61);
+        let mut events = ScriptedEvents::new([Event::Key(KeyCode::Char('x').into())]);
+        assert!(matches!(
+            game.process_input(&mut events),
+            Ok(Some(Screen::Main(_)))
+        ));
+        // The idle timeout does not apply to games in progress
+        game.state = GameState::Running;
+        game.globals.tick_source = TickSource::Step;
+        let e = game
+            .process_input(&mut ScriptedEvents::new([]))
+            .unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn no_idle_timeout_by_default() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.state = GameState::Dead(PostMortem::default());
+        game.last_activity -= Duration::from_secs(3600);
+        let e = game
+            .process_input(&mut ScriptedEvents::new([]))
+            .unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn kiosk_game_over() {
+        let mut globals = Globals {
+            kiosk: true,
+            ..Globals::default()
+        };
+        globals.config.game.game_over_hold_ms = 0;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.state = GameState::Dead(PostMortem::default());
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let last_line = (0..80)
+            .map(|x| buffer[(x, 23)].symbol())
+            .collect::<String>();
+        assert_eq!(last_line.trim(), "Press any key to return to the main menu");
+        assert!(matches!(
+            game.handle_event(Event::Key(KeyCode::Char('r').into())),
+            Some(Screen::Main(_))
+        ));
+    }
+
+    #[test]
+    fn eat_effects() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(10, 5);
+        game.snake.set_body([
+            Position::new(7, 5),
+            Position::new(8, 5),
+            Position::new(9, 5),
+        ]);
+        game.snake.direction = Direction::East;
+        game.fruits = HashSet::from([Position::new(11, 5)]);
+        game.advance();
+        assert_eq!(game.score, 1);
+        let &spawned = game.fruits.iter().next().unwrap();
+        assert_eq!(
+            game.effects,
+            [
+                CellEffect::new(spawned, EffectKind::Spawn),
+                CellEffect::new(Position::new(11, 5), EffectKind::EatFlash)
+            ]
+        );
+        game.advance();
+        assert_eq!(
+            game.effects,
+            [
+                CellEffect {
+                    pos: spawned,
+                    kind: EffectKind::Spawn,
+                    ticks_left: consts::SPAWN_HIGHLIGHT_TICKS - 1,
+                },
+                CellEffect::new(Position::new(8, 5), EffectKind::GrowIn)
+            ]
+        );
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[(10, 7)].symbol(), "·");
+        assert_eq!(buffer[(11, 7)].symbol(), "⚬");
+        assert_eq!(buffer[(14, 7)].symbol(), "<");
+        game.advance();
+        game.advance();
+        game.advance();
+        assert!(game.effects.iter().all(|eff| eff.kind == EffectKind::Spawn));
+        for _ in 4..consts::SPAWN_HIGHLIGHT_TICKS {
+            game.advance();
+        }
+        assert_eq!(game.effects, []);
+    }
+
+    #[test]
+    fn hazard_ahead() {
+        let mut globals = Globals::default();
+        globals.options.obstacles = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let target = game
+            .map
+            .obstacles()
+            .iter()
+            .copied()
+            .find(|p| p.x > 0 && !game.map.obstacles().contains(&Position::new(p.x - 1, p.y)))
+            .unwrap();
+        game.snake.head = Position::new(target.x - 1, target.y);
+        game.snake.set_body([]);
+        game.snake.direction = Direction::East;
+        assert_eq!(game.hazard_ahead(), Some(Hazard::Fatal));
+        game.snake.gain(PowerUpKind::Plow);
+        assert_eq!(game.hazard_ahead(), None);
+        game.snake.power_ups.clear();
+        game.snake.gain(PowerUpKind::Star);
+        assert_eq!(game.hazard_ahead(), None);
+        // Not even a star saves the snake from the level border
+        game.snake.head = Position::new(game.map.bounds().width - 1, target.y);
+        assert_eq!(game.hazard_ahead(), Some(Hazard::Fatal));
+    }
+
+    #[test]
+    fn hazard_ahead_tail() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits.clear();
+        // The snake is curled up with its head just behind the end of its
+        // tail
+        game.snake.head = Position::new(10, 5);
+        game.snake.set_body([
+            Position::new(11, 5),
+            Position::new(11, 6),
+            Position::new(10, 6),
+            Position::new(9, 6),
+            Position::new(9, 5),
+        ]);
+        game.snake.max_len = 5;
+        game.snake.direction = Direction::East;
+        assert_eq!(game.hazard_ahead(), Some(Hazard::CloseCall));
+        game.snake.direction = Direction::South;
+        assert_eq!(game.hazard_ahead(), Some(Hazard::Fatal));
+        game.snake.direction = Direction::East;
+        game.snake.grow();
+        assert_eq!(game.hazard_ahead(), Some(Hazard::Fatal));
+        game.snake.max_len = 5;
+        game.globals.options.strict_collisions = true;
+        assert_eq!(game.hazard_ahead(), Some(Hazard::Fatal));
+    }
+
+    #[test]
+    fn collision_warning_style() {
+        let mut globals = Globals::default();
+        globals.config.game.collision_warning = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let area = Rect::new(0, 0, 80, 24);
+        let head = game.snake.head();
+        let cell = (head.x + 2, head.y + 2);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[cell].fg, Color::Green);
+        game.snake.head = Position::new(head.x, 0);
+        game.snake.direction = Direction::North;
+        let cell = (head.x + 2, 2);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[cell].fg, Color::LightRed);
+        game.globals.config.game.collision_warning = false;
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[cell].fg, Color::Green);
+    }
+
+    #[test]
+    fn spawn_highlight() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(11, 5)]);
+        game.snake.head = Position::new(10, 5);
+        game.snake.direction = Direction::East;
+        game.advance();
+        let &spawned = game.fruits.iter().next().unwrap();
+        let cell = (spawned.x + 2, spawned.y + 2);
+        let area = Rect::new(0, 0, 80, 24);
+        for i in 0..consts::SPAWN_HIGHLIGHT_TICKS {
+            let mut buffer = Buffer::empty(area);
+            game.render(area, &mut buffer);
+            let highlighted = buffer[cell].modifier.contains(Modifier::REVERSED);
+            assert_eq!(highlighted, i % 2 == 0, "tick {i}");
+            game.advance();
+        }
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[cell].symbol(), "●");
+        assert!(!buffer[cell].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn combo_multiplier() {
+        let mut globals = Globals::default();
+        globals.options.combos = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(10, 5);
+        game.snake.set_body([
+            Position::new(7, 5),
+            Position::new(8, 5),
+            Position::new(9, 5),
+        ]);
+        game.snake.direction = Direction::East;
+        game.fruits = HashSet::from([Position::new(11, 5), Position::new(13, 5)]);
+        game.advance();
+        assert_eq!(game.score, 1);
+        assert_eq!(game.combo, 1);
+        game.advance();
+        game.fruits = HashSet::from([Position::new(13, 5), Position::new(0, 0)]);
+        game.advance();
+        assert_eq!(game.score, 3);
+        assert_eq!(game.combo, 2);
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        let score_bar = (0..80).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert!(score_bar.starts_with(" Score: 3 (×2) "));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        for _ in 0..consts::COMBO_WINDOW_TICKS {
+            game.advance();
+        }
+        assert!(game.running());
+        assert_eq!(game.combo, 1);
+        assert_eq!(game.score, 3);
+    }
+
+    #[test]
+    fn no_combos_by_default() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(10, 5);
+        game.snake.set_body([
+            Position::new(7, 5),
+            Position::new(8, 5),
+            Position::new(9, 5),
+        ]);
+        game.snake.direction = Direction::East;
+        game.fruits = HashSet::from([Position::new(11, 5), Position::new(12, 5)]);
+        game.advance();
+        game.fruits = HashSet::from([Position::new(12, 5), Position::new(0, 0)]);
+        game.advance();
+        assert_eq!(game.score, 2);
+        assert_eq!(game.combo, 1);
+    }
+
+    #[test]
+    fn death_animation() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(0, 5);
+        game.snake
+            .set_body([Position::new(2, 5), Position::new(1, 5)]);
+        game.snake.direction = Direction::West;
+        game.advance();
+        assert_eq!(
+            game.state,
+            GameState::Dying {
+                ticks_left: consts::DEATH_ANIMATION_TICKS,
+                post_mortem: PostMortem {
+                    crash: Some(Crash {
+                        cause: EndCause::Wall,
+                        head: Position::new(0, 5),
+                        direction: Direction::West,
+                    }),
+                    ..PostMortem::default()
+                },
+            }
+        );
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        // The level is shifted one cell to the right and flashed
+        assert_eq!(buffer[(2, 1)].symbol(), "┌");
+        assert_eq!(buffer[(2, 7)].symbol(), "×");
+        assert_eq!(buffer[(40, 12)].fg, Color::LightRed);
+        assert_eq!(buffer[(40, 12)].modifier, Modifier::REVERSED);
+        assert_eq!(buffer[(1, 22)].symbol(), " ");
+        game.advance();
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        // The level is shifted one cell to the left and not flashed
+        assert_eq!(buffer[(0, 1)].symbol(), "┌");
+        assert_eq!(buffer[(0, 7)].symbol(), "×");
+        assert_eq!(buffer[(40, 12)].fg, Color::Reset);
+        assert_eq!(buffer[(40, 12)].modifier, Modifier::empty());
+        for _ in 1..consts::DEATH_ANIMATION_TICKS {
+            game.advance();
+        }
+        let GameState::Dead(ref post_mortem) = game.state else {
+            panic!("Game not over after death animation: {:?}", game.state);
+        };
+        assert!(!post_mortem.new_high_score);
+        assert_eq!(post_mortem.warning, None);
+        assert!(post_mortem.held_until.is_some());
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_eq!(buffer[(1, 1)].symbol(), "┌");
+        assert_eq!(buffer[(1, 7)].symbol(), "×");
+        assert_eq!(buffer[(1, 22)].symbol(), "—");
+    }
+
+    #[test]
+    fn perfect_game() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        // Pretend that the rest of the level is walled off
+        game.reachable = HashSet::from([
+            Position::new(0, 0),
+            Position::new(1, 0),
+            Position::new(2, 0),
+        ]);
+        game.snake.head = Position::new(1, 0);
+        game.snake.direction = Direction::East;
+        game.snake.set_body([Position::new(0, 0)]);
+        game.snake.max_len = 1000;
+        game.fruits = HashSet::from([Position::new(10, 10)]);
+        game.advance();
+        let GameState::Celebrating {
+            ref post_mortem, ..
+        } = game.state
+        else {
+            panic!("Game did not end: {:?}", game.state);
+        };
+        assert!(post_mortem.perfect);
+    }
+
+    #[test]
+    fn relay_handoff() {
+        let mut globals = Globals {
+            relay: true,
+            ..Globals::default()
+        };
+        globals.options.wraparound = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        // Relay games don't compete with single-player games for high scores
+        assert!(!game.eligible_for_high_score());
+        let area = Rect::new(0, 0, 80, 24);
+        let row = |game: &Game<ChaCha12Rng>, y| {
+            let mut buffer = Buffer::empty(area);
+            game.render(area, &mut buffer);
+            (0..80).map(|x| buffer[(x, y)].symbol()).collect::<String>()
+        };
+        assert!(row(&game, 0).contains("Player 1"));
+        // Only the steering keys of the player whose turn it is work
+        let key = |c| Event::Key(KeyCode::Char(c).into());
+        assert_eq!(game.snake.direction, Direction::North);
+        assert!(game.handle_event(key('a')).is_none());
+        assert_eq!(game.snake.direction, Direction::North);
+        assert!(game.handle_event(key('h')).is_none());
+        assert_eq!(game.snake.direction, Direction::West);
+        for _ in 0..consts::RELAY_TURN_TICKS - consts::RELAY_WARNING_TICKS {
+            game.advance();
+        }
+        assert!(game.running());
+        assert_eq!(row(&game, 22).trim(), "Player 2 takes over in 3…");
+        for _ in 0..consts::RELAY_WARNING_TICKS {
+            game.advance();
+        }
+        assert!(game.running());
+        assert!(row(&game, 0).contains("Player 2"));
+        assert_eq!(row(&game, 22).trim(), "Player 2, your turn!");
+        assert!(game.handle_event(key('k')).is_none());
+        assert_eq!(game.snake.direction, Direction::West);
+        assert!(game.handle_event(key('w')).is_none());
+        assert_eq!(game.snake.direction, Direction::North);
+        let fruit = game
+            .snake
+            .direction
+            .advance(game.snake.head(), game.map.bounds());
+        game.fruits.extend(fruit);
+        game.advance();
+        let mut buffer = Buffer::empty(area);
+        game.stats.render(area, &mut buffer);
+        let tally = (0..24)
+            .map(|y| (0..80).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .find(|line| line.contains("Player 2 Fruits"));
+        assert!(tally.is_some_and(|line| line.trim().ends_with(" 1 │")));
+    }
+
+    #[test]
+    fn hint_after_repeated_crashes() {
+        let mut globals = Globals::default();
+        globals.config.game.crash_tips = true;
+        for i in 0..3 {
+            let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+            game.snake.head = Position::new(0, 0);
+            game.snake.direction = Direction::West;
+            game.advance();
+            assert!(game.dying());
+            let toast = game.toast.as_ref().map(|t| t.text.as_str());
+            if i < 2 {
+                assert_eq!(toast, None);
+            } else {
+                assert_eq!(toast, Some(tr(Msg::HintWall)));
+            }
+            globals = game.globals;
+        }
+    }
 
-    const RNG_SEED: u64 = 0x0123456789ABCDEF;
+    #[test]
+    fn palette_pauses_game() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert!(game.running());
+        assert!(game.palette().is_some());
+        assert_eq!(game.state, GameState::Paused(Paused::new()));
+        assert!(game.run_palette_action(PaletteAction::Resume).is_none());
+        assert!(game.running());
+        game.state = GameState::ConfirmRestart(Confirm::new("Restart?"));
+        assert!(game.palette().is_none());
+    }
 
     #[test]
-    fn new_game() {
-        let game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+    fn adaptive_difficulty() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let mut globals = Globals::in_tempdir(&tmpdir, "");
+        globals.options.adaptive = true;
+        globals.options.obstacles = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.map = LevelMap::new(game.map.bounds());
+        game.snake.head = Position::new(10, 10);
+        game.snake.direction = Direction::East;
+        for x in 11..15 {
+            game.fruits = HashSet::from([Position::new(x, 10)]);
+            game.advance();
+            assert!(game.running());
+        }
+        assert!(game.tick_period() < consts::TICK_PERIOD);
+        let obstacles = game.map.obstacles();
+        assert_eq!(obstacles.len(), 1);
+        assert!(obstacles
+            .iter()
+            .all(|&p| game.map.bounds().distance(p, game.snake.head)
+                > consts::ADAPTIVE_OBSTACLE_CLEARANCE));
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         game.render(area, &mut buffer);
-        let mut expected = Buffer::with_lines([
-            " Score: 0                                                         High Score: - ",
-            " ┌────────────────────────────────────────────────────────────────────────────┐ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                          ●                                                 │ ",
-            " │                                      v                                     │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " └────────────────────────────────────────────────────────────────────────────┘ ",
-            "",
-            "",
-        ]);
-        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
-        expected.set_style(Rect::new(40, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(28, 10, 1, 1), consts::FRUIT_STYLE);
-        pretty_assertions::assert_eq!(buffer, expected);
+        let score_bar = (0..80).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert!(score_bar.contains("Lv +1"));
+        // Crashing makes the next game easier
+        game.snake.direction = Direction::North;
+        game.snake.head = Position::new(10, 0);
+        game.advance();
+        assert!(game.dying());
+        let game = Game::new_with_rng(game.globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert_eq!(game.tick_period(), consts::TICK_PERIOD);
     }
 
     #[test]
-    fn new_wraparound_game() {
-        let mut globals = Globals::default();
-        globals.options.wraparound = true;
-        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+    fn victory() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let mut globals = Globals::in_tempdir(&tmpdir, "");
+        globals.options.level_size = LevelSize::Small;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.high_score = NonZeroU32::new(100);
+        let head = Position::new(0, 0);
+        let fruit = Position::new(1, 0);
+        game.snake.head = head;
+        game.snake.direction = Direction::East;
+        game.snake.set_body(
+            game.map
+                .bounds()
+                .positions()
+                .filter(|&p| p != head && p != fruit),
+        );
+        game.snake.max_len = 1000;
+        game.fruits = HashSet::from([fruit]);
+        game.advance();
+        assert!(matches!(
+            game.state,
+            GameState::Celebrating {
+                ticks_left: consts::VICTORY_ANIMATION_TICKS,
+                ..
+            }
+        ));
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         game.render(area, &mut buffer);
-        let mut expected = Buffer::with_lines([
-            " Score: 0                                                         High Score: - ",
-            " ·⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯· ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                          ●                                                 ⋮ ",
-            " ⋮                                      v                                     ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ⋮                                                                            ⋮ ",
-            " ·⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯⋯· ",
-            "",
-            "",
-        ]);
-        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
-        expected.set_style(Rect::new(40, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(28, 10, 1, 1), consts::FRUIT_STYLE);
-        pretty_assertions::assert_eq!(buffer, expected);
+        let banner = (1..13)
+            .map(|x| buffer[(x, 22)].symbol())
+            .collect::<String>();
+        assert_eq!(banner, "— YOU WIN! —");
+        let head_color = buffer[(22, 8)].fg;
+        assert!(consts::VICTORY_COLORS.contains(&head_color));
+        game.advance();
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        assert_ne!(buffer[(22, 8)].fg, head_color);
+        for _ in 1..consts::VICTORY_ANIMATION_TICKS {
+            game.advance();
+        }
+        let GameState::Exhausted(ref post_mortem) = game.state else {
+            panic!("Game not over after victory animation: {:?}", game.state);
+        };
+        assert!(post_mortem.held_until.is_some());
+        let mut buffer = Buffer::empty(area);
+        game.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("game/victory", &buffer);
+    }
+
+    /// Start a game on an open 20×10 custom level with the given objectives
+    /// and with a single fruit out of the snake's way
+    fn level_game(objectives: crate::level::Objectives, wrap: bool) -> Game {
+        let level = CustomLevel {
+            name: String::from("Proving Ground"),
+            bounds: Bounds {
+                width: 20,
+                height: 10,
+                wrap,
+            },
+            obstacles: HashSet::from([Position::new(3, 3)]),
+            objectives,
+        };
+        let mut game = Game::for_level(Globals::default(), level);
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game
     }
 
     #[test]
-    fn new_game_with_high_score() {
-        let mut globals = Globals::default();
-        globals
-            .high_scores
-            .set(globals.options, NonZeroU32::new(42).unwrap());
-        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+    fn level_target_score() {
+        let mut game = level_game(
+            crate::level::Objectives {
+                target_score: NonZeroU32::new(1),
+                ..crate::level::Objectives::default()
+            },
+            false,
+        );
+        assert_eq!(game.high_score, None);
+        assert!(game.map.obstacles().contains(&Position::new(3, 3)));
+        game.fruits.insert(Position::new(10, 4));
+        game.advance();
+        let GameState::Celebrating {
+            ref post_mortem, ..
+        } = game.state
+        else {
+            panic!("Game not won after reaching target: {:?}", game.state);
+        };
+        assert_eq!(post_mortem.verdict, Some(Verdict::Complete));
+        assert!(!post_mortem.new_high_score);
+        assert_eq!(game.globals.last_game, None);
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         game.render(area, &mut buffer);
-        let mut expected = Buffer::with_lines([
-            " Score: 0                                                        High Score: 42 ",
-            " ┌────────────────────────────────────────────────────────────────────────────┐ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                          ●                                                 │ ",
-            " │                                      v                                     │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " └────────────────────────────────────────────────────────────────────────────┘ ",
-            "",
-            "",
-        ]);
-        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
-        expected.set_style(Rect::new(40, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(28, 10, 1, 1), consts::FRUIT_STYLE);
-        pretty_assertions::assert_eq!(buffer, expected);
+        let banner = (1..25)
+            .map(|x| buffer[(x, 22)].symbol())
+            .collect::<String>();
+        assert_eq!(banner, "— OBJECTIVES COMPLETE! —");
     }
 
     #[test]
-    fn self_collision() {
-        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
-        game.score = 3;
-        game.snake.head = Position::new(30, 6);
-        game.snake.body = VecDeque::from([
-            Position::new(30, 6),
-            Position::new(31, 6),
-            Position::new(32, 6),
-            Position::new(33, 6),
-            Position::new(33, 7),
-            Position::new(33, 8),
-            Position::new(33, 9),
-            Position::new(32, 9),
-            Position::new(31, 9),
-            Position::new(30, 9),
-            Position::new(30, 8),
-            Position::new(30, 7),
-        ]);
-        game.snake.max_len = 12;
-        game.snake.direction = Direction::North;
-        game.state = GameState::Dead(PostMortem {
-            new_high_score: false,
-            warning: None,
-        });
+    fn level_forbidden_cell() {
+        let mut game = level_game(
+            crate::level::Objectives {
+                forbidden: HashSet::from([Position::new(10, 4)]),
+                ..crate::level::Objectives::default()
+            },
+            false,
+        );
+        assert_eq!(game.hazard_ahead(), Some(Hazard::Fatal));
+        // Fruits are never placed on forbidden cells
+        assert!(game.occupied().contains(&Position::new(10, 4)));
+        game.snake.gain(PowerUpKind::Star);
+        game.advance();
+        let GameState::Dying {
+            ref post_mortem, ..
+        } = game.state
+        else {
+            panic!("Snake survived touching a forbidden cell: {:?}", game.state);
+        };
+        assert_eq!(post_mortem.verdict, Some(Verdict::Failed));
+        assert_eq!(
+            post_mortem.crash.map(|c| c.cause),
+            Some(EndCause::Forbidden)
+        );
+    }
+
+    #[rstest]
+    #[case(NonZeroU32::new(5), Verdict::TimeUp)]
+    #[case(None, Verdict::Complete)]
+    fn level_time_limit(#[case] target_score: Option<NonZeroU32>, #[case] verdict: Verdict) {
+        let mut game = level_game(
+            crate::level::Objectives {
+                target_score,
+                time_limit: Some(Duration::from_secs(1)),
+                ..crate::level::Objectives::default()
+            },
+            true,
+        );
+        let mut moves = 0;
+        while game.running() {
+            game.advance();
+            moves += 1;
+            assert!(moves < 100, "time limit never ran out");
+        }
+        assert!(game.stats.elapsed() >= Duration::from_secs(1));
+        let post_mortem = match game.state {
+            GameState::Dying {
+                ref post_mortem, ..
+            }
+            | GameState::Celebrating {
+                ref post_mortem, ..
+            } => post_mortem,
+            ref state => panic!("Unexpected state after time limit: {state:?}"),
+        };
+        assert_eq!(post_mortem.verdict, Some(verdict));
+    }
+
+    #[test]
+    fn level_progress() {
+        let mut game = level_game(
+            crate::level::Objectives {
+                target_score: NonZeroU32::new(25),
+                time_limit: Some(Duration::from_secs(90)),
+                forbidden: HashSet::from([
+                    Position::new(14, 2),
+                    Position::new(15, 2),
+                    Position::new(16, 2),
+                ]),
+            },
+            false,
+        );
+        game.score = 7;
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         game.render(area, &mut buffer);
-        let mut expected = Buffer::with_lines([
-            " Score: 3                                                         High Score: - ",
-            " ┌────────────────────────────────────────────────────────────────────────────┐ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                              ×⚬⚬⚬                                          │ ",
-            " │                              ⚬  ⚬                                          │ ",
-            " │                          ●   ⚬  ⚬                                          │ ",
-            " │                              ⚬⚬⚬⚬                                          │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " └────────────────────────────────────────────────────────────────────────────┘ ",
-            " — GAME OVER —",
-            " Choose One: Restart (r) — Main Menu (m) — Quit (q)",
-        ]);
-        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
-        expected.set_style(Rect::new(32, 8, 1, 1), consts::COLLISION_STYLE);
-        expected.set_style(Rect::new(33, 8, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(34, 8, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 8, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 9, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 10, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(34, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(33, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(32, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(32, 10, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(32, 9, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(28, 10, 1, 1), consts::FRUIT_STYLE);
-        expected.set_style(Rect::new(22, 23, 1, 1), consts::KEY_STYLE);
-        expected.set_style(Rect::new(38, 23, 1, 1), consts::KEY_STYLE);
-        expected.set_style(Rect::new(49, 23, 1, 1), consts::KEY_STYLE);
-        pretty_assertions::assert_eq!(buffer, expected);
+        crate::snapshot::assert_snapshot("game/level_progress", &buffer);
     }
 
     #[test]
-    fn self_collision_new_high_score() {
+    fn debounce_turn_keys() {
+        let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let down = Event::Key(KeyCode::Down.into());
+        assert!(game.handle_event(down).is_none());
+        assert_eq!(game.snake.direction, Direction::South);
+        // An autorepeat before the next tick is dropped instead of steering
+        // the snake again
+        game.snake.direction = Direction::East;
+        assert!(game.handle_event(down).is_none());
+        assert_eq!(game.snake.direction, Direction::East);
+        game.advance();
+        assert!(game.handle_event(down).is_none());
+        assert_eq!(game.snake.direction, Direction::South);
+        // Autorepeats of the held key don't move the pause menu selection
+        assert!(game.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
+        assert!(game.handle_event(down).is_none());
+        assert_eq!(game.state, GameState::Paused(Paused::new()));
+        // Once the autorepeat window has passed since the last repeat, the key
+        // counts as a new press.  Rather than waiting, pretend that the last
+        // repeat was received that long ago.
+        let (key, last) = game.turn_key.unwrap();
+        let last = last.checked_sub(consts::KEY_REPEAT_WINDOW).unwrap();
+        game.turn_key = Some((key, last));
+        assert!(game.handle_event(down).is_none());
+        assert_ne!(game.state, GameState::Paused(Paused::new()));
+    }
+
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    fn mouse_steering(#[case] enabled: bool) {
         let mut globals = Globals::default();
-        globals
-            .high_scores
-            .set(globals.options, NonZeroU32::new(2).unwrap());
+        globals.config.game.mouse_steering = enabled;
         let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
-        game.score = 3;
-        game.snake.head = Position::new(30, 6);
-        game.snake.body = VecDeque::from([
-            Position::new(30, 6),
-            Position::new(31, 6),
-            Position::new(32, 6),
-            Position::new(33, 6),
-            Position::new(33, 7),
-            Position::new(33, 8),
-            Position::new(33, 9),
-            Position::new(32, 9),
-            Position::new(31, 9),
-            Position::new(30, 9),
-            Position::new(30, 8),
-            Position::new(30, 7),
-        ]);
-        game.snake.max_len = 12;
-        game.snake.direction = Direction::North;
-        game.state = GameState::Dead(PostMortem {
-            new_high_score: true,
-            warning: None,
-        });
+        game.snake.head = Position::new(5, 5);
+        game.snake.direction = Direction::East;
+        game.snake.set_body([Position::new(4, 5)]);
+        game.fruits = HashSet::from([Position::new(20, 15)]);
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         game.render(area, &mut buffer);
-        let mut expected = Buffer::with_lines([
-            " Score: 3                                                         High Score: 2 ",
-            " ┌────────────────────────────────────────────────────────────────────────────┐ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                              ×⚬⚬⚬                                          │ ",
-            " │                              ⚬  ⚬                                          │ ",
-            " │                          ●   ⚬  ⚬                                          │ ",
-            " │                              ⚬⚬⚬⚬                                          │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " └────────────────────────────────────────────────────────────────────────────┘ ",
-            " — GAME OVER — NEW HIGH SCORE! —",
-            " Choose One: Restart (r) — Main Menu (m) — Quit (q)",
-        ]);
-        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
-        expected.set_style(Rect::new(32, 8, 1, 1), consts::COLLISION_STYLE);
-        expected.set_style(Rect::new(33, 8, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(34, 8, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 8, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 9, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 10, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(35, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(34, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(33, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(32, 11, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(32, 10, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(32, 9, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(28, 10, 1, 1), consts::FRUIT_STYLE);
-        expected.set_style(Rect::new(22, 23, 1, 1), consts::KEY_STYLE);
-        expected.set_style(Rect::new(38, 23, 1, 1), consts::KEY_STYLE);
-        expected.set_style(Rect::new(49, 23, 1, 1), consts::KEY_STYLE);
-        pretty_assertions::assert_eq!(buffer, expected);
+        let level_area = game.level_area.get();
+        let view = game.viewport();
+        let click = |x: u16, y: u16| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: level_area.x + x - view.x,
+                row: level_area.y + y - view.y,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        assert!(game.handle_event(click(5, 8)).is_none());
+        for _ in 0..3 {
+            game.advance();
+        }
+        if enabled {
+            assert_eq!(game.snake.head(), Position::new(5, 8));
+            game.advance();
+            assert_eq!(game.mouse_target, None);
+            assert_eq!(game.snake.head(), Position::new(5, 9));
+        } else {
+            assert_eq!(game.snake.head(), Position::new(8, 5));
+        }
+        // Pressing a direction key cancels the route
+        assert!(game.handle_event(click(30, 2)).is_none());
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Right.into()))
+            .is_none());
+        game.advance();
+        assert_eq!(game.mouse_target, None);
+        assert_eq!(game.snake.direction, Direction::East);
     }
 
     #[test]
-    fn new_medium_game() {
-        let mut globals = Globals::default();
-        globals.options.level_size = LevelSize::Medium;
-        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+    fn step_mode() {
+        let globals = Globals {
+            tick_source: TickSource::Step,
+            ..Globals::default()
+        };
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let dot = Event::Key(KeyCode::Char('.').into());
+        assert!(game.is_step(&dot));
+        assert!(!game.is_step(&Event::Key(KeyCode::Char(',').into())));
+        game.score = 5;
+        assert_eq!(game.new_high_score(), None);
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         game.render(area, &mut buffer);
-        let mut expected = Buffer::with_lines([
-            " Score: 0                                                         High Score: - ",
-            "",
-            "",
-            "",
-            "",
-            "             ┌─────────────────────────────────────────────────────┐            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                          v                          │            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                                                     │            ",
-            "             │                                                    ●│            ",
-            "             └─────────────────────────────────────────────────────┘            ",
-            "",
-            "",
-            "",
-            "",
-            "",
-        ]);
-        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
-        expected.set_style(Rect::new(40, 12, 1, 1), consts::SNAKE_STYLE);
-        expected.set_style(Rect::new(66, 17, 1, 1), consts::FRUIT_STYLE);
-        pretty_assertions::assert_eq!(buffer, expected);
+        let score_bar = (0..80).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert!(score_bar.contains("Step Mode"));
+        game.pause();
+        assert!(!game.is_step(&dot));
+    }
+
+    #[rstest]
+    #[case(Event::FocusLost, true, false, true)]
+    #[case(Event::FocusLost, false, true, false)]
+    #[case(Event::Resize(100, 30), true, false, false)]
+    #[case(Event::Resize(100, 30), false, true, true)]
+    fn pause_on_terminal_event(
+        #[case] event: Event,
+        #[case] pause_on_focus_loss: bool,
+        #[case] pause_on_resize: bool,
+        #[case] pauses: bool,
+    ) {
+        let mut globals = Globals::default();
+        globals.config.game.pause_on_focus_loss = pause_on_focus_loss;
+        globals.config.game.pause_on_resize = pause_on_resize;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert!(game.handle_event(event).is_none());
+        assert_eq!(matches!(game.state, GameState::Paused(_)), pauses);
     }
 
     #[test]
-    fn paused() {
+    fn key_hints() {
+        let text = |hints: Line<'_>| hints.to_string();
         let mut game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let hints = game.key_hints().unwrap();
+        assert_eq!(hints.style, Style::new());
+        assert_eq!(text(hints), "Pause (Esc) — Quit (Ctrl-C)");
+        game.ticks = consts::KEY_HINT_TICKS - 1;
+        let hints = game.key_hints().unwrap();
+        assert_eq!(hints.style, Style::new().add_modifier(Modifier::DIM));
+        game.ticks = consts::KEY_HINT_TICKS;
+        assert_eq!(game.key_hints(), None);
+        // Hints reappear when the game is paused
+        game.pause();
+        assert_eq!(text(game.key_hints().unwrap()), "Resume (Esc) — Quit (q)");
+        game.globals.kiosk = true;
+        assert_eq!(text(game.key_hints().unwrap()), "Resume (Esc)");
+        game.globals.config.game.key_hints = false;
+        assert_eq!(game.key_hints(), None);
+    }
+
+    #[rstest]
+    #[case(true, true)]
+    #[case(false, false)]
+    fn pause_for_stall(#[case] pause_on_stall: bool, #[case] pauses: bool) {
+        let mut globals = Globals::default();
+        globals.config.game.pause_on_stall = pause_on_stall;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.handle_stall();
+        assert_eq!(matches!(game.state, GameState::Paused(_)), pauses);
+    }
+
+    #[test]
+    fn no_step_with_timer() {
+        let game = Game::new_with_rng(Globals::default(), ChaCha12Rng::seed_from_u64(RNG_SEED));
+        assert!(!game.is_step(&Event::Key(KeyCode::Char('.').into())));
+    }
+
+    #[test]
+    fn rewind_after_death() {
+        let mut globals = Globals::default();
+        globals.config.controls.rewinds = 1;
+        globals.config.game.game_over_hold_ms = 0;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.snake.head = Position::new(2, 5);
+        game.snake.direction = Direction::West;
+        game.advance();
+        let start = game.snake.head();
+        game.advance();
+        game.advance();
+        assert!(game.dying());
+        for _ in 0..consts::DEATH_ANIMATION_TICKS {
+            game.advance();
+        }
+        assert!(game.can_rewind());
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('z').into()))
+            .is_none());
+        assert!(matches!(game.state, GameState::Paused(_)));
+        assert_eq!(game.snake.head(), Position::new(2, 5));
+        assert_eq!(game.rewinds_left, 0);
+        game.state = GameState::Running;
+        game.advance();
+        assert_eq!(game.snake.head(), start);
+        game.score = 5;
+        assert_eq!(game.new_high_score(), None);
+        game.advance();
+        game.advance();
+        for _ in 0..consts::DEATH_ANIMATION_TICKS {
+            game.advance();
+        }
+        assert!(matches!(game.state, GameState::Dead(_)));
+        assert!(!game.can_rewind());
+    }
+
+    #[test]
+    fn rewind_records_final_death() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let history_file = tmpdir.path().join("history.jsonl");
+        let globals = Globals::in_tempdir(
+            &tmpdir,
+            "[controls]\nrewinds = 1\n\n[game]\ngame-over-hold-ms = 0\n",
+        );
+        let history_len =
+            || std::fs::read_to_string(&history_file).map_or(0, |s| s.lines().count());
+        let die = |game: &mut Game| {
+            game.snake.head = Position::new(0, 5);
+            game.snake.direction = Direction::West;
+            game.advance();
+            for _ in 0..consts::DEATH_ANIMATION_TICKS {
+                game.advance();
+            }
+            assert!(matches!(game.state, GameState::Dead(_)));
+        };
+        let mut game = Game::new(globals.clone());
+        game.advance();
+        die(&mut game);
+        assert_eq!(history_len(), 0);
+        assert_eq!(
+            game.interrupted_record().map(|r| r.cause),
+            Some(EndCause::Wall)
+        );
+        game.rewind();
+        assert_eq!(
+            game.interrupted_record().map(|r| r.cause),
+            Some(EndCause::Interrupted)
+        );
+        game.state = GameState::Running;
+        die(&mut game);
+        assert_eq!(history_len(), 1);
+        assert_eq!(game.interrupted_record(), None);
+        // Leaving a game that could still be rewound records its end
+        let mut game = Game::new(globals);
+        game.advance();
+        die(&mut game);
+        assert_eq!(history_len(), 1);
+        game.record_end();
+        assert_eq!(history_len(), 2);
+        assert!(!game.can_rewind());
+    }
+
+    #[rstest]
+    #[case(None, "New category: no high score yet")]
+    #[case(Some(42), "High score to beat: 42")]
+    fn announce_high_score(#[case] high_score: Option<u32>, #[case] text: &str) {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::Small;
+        if let Some(score) = high_score {
+            globals
+                .high_scores
+                .insert(globals.options, NonZeroU32::new(score).unwrap().into());
+        }
+        // A high score for other options doesn't count
+        globals.high_scores.insert(
+            Options {
+                wraparound: true,
+                ..globals.options
+            },
+            NonZeroU32::new(100).unwrap().into(),
+        );
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.announce_high_score();
+        assert_eq!(game.toast.as_ref().map(|t| t.text.as_str()), Some(text));
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
-        assert!(game.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
         game.render(area, &mut buffer);
-        let mut expected = Buffer::with_lines([
-            " Score: 0                                                         High Score: - ",
-            " ┌────────────────────────────────────────────────────────────────────────────┐ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                             ┌──── PAUSED ─────┐                            │ ",
-            " │                          ●  │ » Resume (Esc)  │                            │ ",
-            " │                             │   Restart (r)   │                            │ ",
-            " │                             │   Main Menu (m) │                            │ ",
-            " │                             │   Quit (q)      │                            │ ",
-            " │                             └─────────────────┘                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " │                                                                            │ ",
-            " └────────────────────────────────────────────────────────────────────────────┘ ",
-            "",
-            "",
-        ]);
-        expected.set_style(Rect::new(0, 0, 80, 1), consts::SCORE_BAR_STYLE);
-        expected.set_style(Rect::new(28, 10, 1, 1), consts::FRUIT_STYLE);
-        expected.set_style(Rect::new(43, 10, 3, 1), consts::KEY_STYLE);
-        expected.set_style(Rect::new(33, 10, 15, 1), consts::MENU_SELECTION_STYLE);
-        expected.set_style(Rect::new(44, 11, 1, 1), consts::KEY_STYLE);
-        expected.set_style(Rect::new(46, 12, 1, 1), consts::KEY_STYLE);
-        expected.set_style(Rect::new(41, 13, 1, 1), consts::KEY_STYLE);
-        pretty_assertions::assert_eq!(buffer, expected);
+        let bottom = (0..80)
+            .map(|x| buffer[(x, 16)].symbol())
+            .collect::<String>();
+        assert!(bottom.contains(&format!(" {text} ")), "{bottom:?}");
+    }
+
+    #[test]
+    fn announce_hidden_high_score() {
+        let mut globals = Globals::default();
+        globals.config.game.hide_score = true;
+        globals
+            .high_scores
+            .insert(globals.options, NonZeroU32::new(42).unwrap().into());
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.announce_high_score();
+        assert!(game.toast.is_none());
+    }
+
+    #[test]
+    fn favorite_from_pause_menu() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let favfile = tmpdir.path().join("favorites.json");
+        let globals = Globals::in_tempdir(&tmpdir, "");
+        let options = globals.options;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        // Boards can't be saved without a known seed
+        assert!(game.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
+        assert_eq!(game.state, GameState::Paused(Paused::new()));
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('f').into()))
+            .is_none());
+        assert_eq!(game.favorite_prompt, None);
+        assert!(game.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
+        assert!(game.running());
+
+        game.seed = Some(RNG_SEED);
+        assert!(game.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
+        assert_eq!(game.state, GameState::Paused(Paused::with_favorite()));
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('f').into()))
+            .is_none());
+        assert_eq!(game.favorite_prompt, Some(NamePrompt::favorite()));
+        for c in "Spiral".chars() {
+            assert!(game
+                .handle_event(Event::Key(KeyCode::Char(c).into()))
+                .is_none());
+        }
+        let buffer = crate::snapshot::render(&game);
+        crate::snapshot::assert_snapshot("game/favorite_prompt", &buffer);
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Enter.into()))
+            .is_none());
+        assert_eq!(game.favorite_prompt, None);
+        assert_eq!(game.state, GameState::Paused(Paused::with_favorite()));
+        assert_eq!(
+            game.toast.as_ref().map(|t| t.text.as_str()),
+            Some("Saved board as \"Spiral\"")
+        );
+        let favorites = crate::favorites::Favorites::load(&favfile).unwrap();
+        assert_eq!(
+            favorites.boards(),
+            [Favorite {
+                name: String::from("Spiral"),
+                seed: RNG_SEED,
+                options,
+            }]
+        );
+    }
+
+    #[test]
+    fn restart_no_confirm() {
+        let mut globals = Globals::default();
+        globals.config.controls.confirm_restart = false;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert!(matches!(
+            game.handle_event(Event::Key(ctrl_r)),
+            Some(Screen::Game(_))
+        ));
+    }
+
+    #[test]
+    fn quick_option_keys() {
+        let options = Options::default();
+        let wrapped = quick_option(options, KeyEvent::from(KeyCode::Char('w'))).unwrap();
+        assert!(wrapped.wraparound);
+        assert_eq!(
+            quick_option(wrapped, KeyEvent::from(KeyCode::Char('w'))),
+            Some(options)
+        );
+        let more = quick_option(options, KeyEvent::from(KeyCode::Char('+'))).unwrap();
+        assert_eq!(more.fruits.get(), 2);
+        assert_eq!(
+            quick_option(more, KeyEvent::from(KeyCode::Char('-'))),
+            Some(options)
+        );
+        // Fewer than one fruit isn't possible
+        assert_eq!(
+            quick_option(options, KeyEvent::from(KeyCode::Char('-'))),
+            None
+        );
+        assert_eq!(
+            quick_option(
+                options,
+                KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL)
+            ),
+            None
+        );
+        assert_eq!(
+            quick_option(options, KeyEvent::from(KeyCode::Char('x'))),
+            None
+        );
+    }
+
+    #[test]
+    fn quick_option_restart() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let globals = Globals::in_tempdir(&tmpdir, "");
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.state = GameState::Dead(PostMortem::default());
+        let Some(Screen::Game(new_game)) = game.handle_event(Event::Key(KeyCode::Char('w').into()))
+        else {
+            panic!("Game did not restart");
+        };
+        assert!(new_game.globals.options.wraparound);
+        assert!(new_game.toast.is_some());
+        let saved = game.globals.config.load_options().unwrap().value;
+        assert!(saved.options.wraparound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bot_steers_snake() {
+        let globals = Globals {
+            bot: Some(crate::bot::Bot::new(String::from(
+                "while read -r line; do echo west; done",
+            ))),
+            ..Globals::default()
+        };
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let head = game.snake.head();
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Down.into()))
+            .is_none());
+        assert_eq!(game.snake.direction, Direction::North);
+        game.advance();
+        assert!(game.running());
+        assert_eq!(game.snake.direction, Direction::West);
+        assert_eq!(game.snake.head(), Position::new(head.x - 1, head.y));
+        assert_eq!(game.snapshot().tick, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bot_forfeits() {
+        let globals = Globals {
+            bot: Some(crate::bot::Bot::new(String::from("read -r line; echo up"))),
+            ..Globals::default()
+        };
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.score = 5;
+        game.advance();
+        let GameState::Dying {
+            ref post_mortem, ..
+        } = game.state
+        else {
+            panic!("Game should be over after bot forfeits");
+        };
+        assert!(!post_mortem.new_high_score);
+        assert!(post_mortem.warning.is_some());
+    }
+
+    #[test]
+    fn hydra_mirrors_player() {
+        let mut globals = Globals::default();
+        globals.options.hydra = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.set_body([]);
+        game.snake.direction = Direction::East;
+        game.hydra_fruit = Some(Position::new(31, 6));
+        game.advance();
+        assert_eq!(game.hydra_fruit, None);
+        assert_eq!(game.hydras.len(), 1);
+        assert_eq!(game.hydras[0].mirror, Mirror::Horizontal);
+        assert_eq!(game.hydras[0].snake.head(), Position::new(44, 6));
+        assert_eq!(game.hydras[0].snake.direction, Direction::West);
+        for dir in [
+            Direction::North,
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::South,
+            Direction::South,
+        ] {
+            game.steer(dir);
+            game.advance();
+            assert!(game.running());
+            assert_eq!(
+                game.hydras[0].snake.head(),
+                Mirror::Horizontal.position(game.snake.head(), game.map.size())
+            );
+        }
+        let value = serde_json::to_value(game.snapshot()).unwrap();
+        assert_eq!(
+            value["hydras"][0]["head"],
+            serde_json::json!({"x": 43, "y": 7})
+        );
+        assert_eq!(value["hydras"][0]["direction"], "south");
+        game.hydra_fruit = Some(Position::new(60, 12));
+        let buffer = crate::snapshot::render(&game);
+        crate::snapshot::assert_snapshot("game/hydra", &buffer);
+    }
+
+    #[test]
+    fn hydras_collide() {
+        let mut globals = Globals::default();
+        globals.options.hydra = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(37, 6);
+        game.snake.set_body([Position::new(36, 6)]);
+        game.snake.direction = Direction::East;
+        game.hydras = vec![Hydra {
+            mirror: Mirror::Horizontal,
+            snake: {
+                let mut snake = Snake::new(Position::new(38, 6), Direction::West);
+                snake.set_body([Position::new(39, 6)]);
+                snake
+            },
+        }];
+        // The heads swap places, each running into the other snake's neck
+        game.advance();
+        assert!(game.dying());
+        let buffer = crate::snapshot::render(&game);
+        let collisions = buffer
+            .content()
+            .iter()
+            .filter(|c| c.symbol() == "×")
+            .count();
+        assert_eq!(collisions, 2);
+    }
+
+    #[test]
+    fn hydra_hits_wall() {
+        let mut globals = Globals::default();
+        globals.options.hydra = true;
+        let mut game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        game.fruits = HashSet::from([Position::new(0, 0)]);
+        game.snake.head = Position::new(30, 6);
+        game.snake.direction = Direction::East;
+        game.hydras = vec![Hydra {
+            mirror: Mirror::Vertical,
+            snake: Snake::new(Position::new(30, 18), Direction::South),
+        }];
+        game.advance();
+        assert!(game.dying());
+        assert_eq!(game.snake.head(), Position::new(31, 6));
+    }
+
+    #[test]
+    fn snapshot_json() {
+        let mut globals = Globals::default();
+        globals.options.level_size = LevelSize::Small;
+        let game = Game::new_with_rng(globals, ChaCha12Rng::seed_from_u64(RNG_SEED));
+        let value = serde_json::to_value(game.snapshot()).unwrap();
+        assert_eq!(value["tick"], 0);
+        assert_eq!(value["width"], 38);
+        assert_eq!(value["height"], 8);
+        assert_eq!(value["wraparound"], false);
+        assert_eq!(value["state"], "running");
+        assert_eq!(value["high_score"], serde_json::Value::Null);
+        assert_eq!(value["snake"]["direction"], "north");
+        assert_eq!(value["snake"]["body"], serde_json::json!([]));
+        assert_eq!(value["fruits"].as_array().unwrap().len(), 1);
+        assert!(value["fruits"][0]["x"].is_u64());
+        assert!(value["fruits"][0]["y"].is_u64());
+        assert_eq!(value["mud"], serde_json::json!([]));
+        assert_eq!(value["ice"], serde_json::json!([]));
+        assert_eq!(value["gates"], serde_json::json!([]));
     }
 
     #[rstest]
@@ -0,0 +1,160 @@
+use crate::command::Command;
+use crate::consts;
+use crate::i18n::{tr, Msg};
+use crate::popup::PopupFrame;
+use crate::textinput::TextInput;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+/// A pop-up asking for a name: either the name of the player who achieved a
+/// new high score or a name under which to save the current board as a
+/// favorite
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct NamePrompt {
+    /// The title drawn on the pop-up's border
+    title: Msg,
+
+    /// The text shown above the input field
+    label: Msg,
+
+    input: TextInput,
+}
+
+impl NamePrompt {
+    /// The width of the widget
+    const WIDTH: u16 = 30;
+
+    /// Create a prompt for the name of the player who achieved a new high
+    /// score
+    pub(super) fn new() -> NamePrompt {
+        NamePrompt {
+            title: Msg::NamePromptTitle,
+            label: Msg::EnterName,
+            input: TextInput::new(consts::PLAYER_NAME_MAX_WIDTH),
+        }
+    }
+
+    /// Create a prompt for the name under which to save the current board as
+    /// a favorite
+    pub(super) fn favorite() -> NamePrompt {
+        NamePrompt {
+            title: Msg::FavoritePromptTitle,
+            label: Msg::NameThisBoard,
+            input: TextInput::new(consts::FAVORITE_NAME_MAX_WIDTH),
+        }
+    }
+
+    /// Process a key press.  Returns `Some` if the user submits or skips the
+    /// prompt or quits the application.
+    pub(super) fn handle_key(&mut self, ev: KeyEvent) -> Option<NameOutcome> {
+        if Command::from_key_event(ev) == Some(Command::Quit) {
+            return Some(NameOutcome::Quit);
+        }
+        match ev.code {
+            KeyCode::Enter => {
+                let name = self.input.value().trim();
+                if name.is_empty() {
+                    Some(NameOutcome::Skip)
+                } else {
+                    Some(NameOutcome::Save(name.to_owned()))
+                }
+            }
+            KeyCode::Esc => Some(NameOutcome::Skip),
+            _ => {
+                self.input.handle_key(ev);
+                None
+            }
+        }
+    }
+}
+
+/// An enum of the ways that the user can finish working with a `NamePrompt`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum NameOutcome {
+    /// The user entered a name to record with the high score or favorite
+    Save(String),
+
+    /// The user declined to enter a name
+    Skip,
+
+    /// The user quit the application
+    Quit,
+}
+
+impl Widget for &NamePrompt {
+    /// Render a `NamePrompt` in the given area of `buf`.
+    ///
+    /// As with [`Warning`][crate::warning::Warning], `area` should be the area
+    /// of the entire display within which the pop-up will be rendered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [text_area, footer_area] = PopupFrame {
+            title: tr(self.title),
+            width: NamePrompt::WIDTH,
+            text_height: 2,
+            footer: true,
+        }
+        .render(area, buf);
+        let [label_area, input_area] =
+            Layout::vertical([Constraint::Length(1); 2]).areas(text_area);
+        Line::from(tr(self.label))
+            .centered()
+            .render(label_area, buf);
+        let [input_area] = Layout::horizontal([Constraint::Length(self.input.width())])
+            .flex(Flex::Center)
+            .areas(input_area);
+        self.input.render(input_area, buf);
+        Line::from_iter([
+            Span::raw(format!("{} (", tr(Msg::Save))),
+            Span::styled("Enter", consts::KEY_STYLE),
+            Span::raw(format!(") — {} (", tr(Msg::Skip))),
+            Span::styled("Esc", consts::KEY_STYLE),
+            Span::raw(")"),
+        ])
+        .centered()
+        .render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn press(prompt: &mut NamePrompt, code: KeyCode) -> Option<NameOutcome> {
+        prompt.handle_key(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn save_name() {
+        let mut prompt = NamePrompt::new();
+        for c in " qrz ".chars() {
+            assert_eq!(press(&mut prompt, KeyCode::Char(c)), None);
+        }
+        assert_eq!(
+            press(&mut prompt, KeyCode::Enter),
+            Some(NameOutcome::Save(String::from("qrz")))
+        );
+    }
+
+    #[test]
+    fn skip() {
+        let mut prompt = NamePrompt::new();
+        assert_eq!(press(&mut prompt, KeyCode::Enter), Some(NameOutcome::Skip));
+        assert_eq!(press(&mut prompt, KeyCode::Char('a')), None);
+        assert_eq!(press(&mut prompt, KeyCode::Esc), Some(NameOutcome::Skip));
+    }
+
+    #[test]
+    fn quit() {
+        let mut prompt = NamePrompt::new();
+        assert_eq!(
+            prompt.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(NameOutcome::Quit)
+        );
+    }
+}
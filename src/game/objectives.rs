@@ -0,0 +1,147 @@
+//! Evaluating the objectives of custom levels
+use crate::history::EndCause;
+use crate::i18n::{self, tr, tr_args, Msg};
+use crate::level::Objectives;
+use std::time::Duration;
+
+/// How a game on a custom level turned out with respect to its objectives
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Verdict {
+    /// The objectives were met
+    Complete,
+
+    /// The snake died (or filled the level) before the objectives were met
+    Failed,
+
+    /// The time limit ran out before the target score was reached
+    TimeUp,
+}
+
+impl Verdict {
+    /// Return the verdict on a game with the given objectives that ended due
+    /// to `cause` with the given score
+    pub(super) fn of(objectives: &Objectives, cause: EndCause, score: u32) -> Verdict {
+        match cause {
+            EndCause::Objective => Verdict::Complete,
+            EndCause::TimeUp => Verdict::TimeUp,
+            EndCause::BoardFull | EndCause::Perfect
+                if objectives.target_score.is_none_or(|t| score >= t.get()) =>
+            {
+                Verdict::Complete
+            }
+            _ => Verdict::Failed,
+        }
+    }
+
+    /// Return the headline to show on the game-over screen
+    pub(super) fn headline(self) -> &'static str {
+        match self {
+            Verdict::Complete => tr(Msg::ObjectivesComplete),
+            Verdict::Failed => tr(Msg::ObjectivesFailed),
+            Verdict::TimeUp => tr(Msg::TimeUp),
+        }
+    }
+}
+
+/// Determine whether a game with the given objectives should end now that
+/// the player has the given score after `elapsed` time of play.  Returns
+/// [`EndCause::Objective`] if the objectives have been met or
+/// [`EndCause::TimeUp`] if the time limit has run out before the target score
+/// was reached.  Surviving until the time limit runs out meets the objectives
+/// if there is no target score.
+pub(super) fn check(objectives: &Objectives, score: u32, elapsed: Duration) -> Option<EndCause> {
+    if objectives.target_score.is_some_and(|t| score >= t.get()) {
+        Some(EndCause::Objective)
+    } else if objectives.time_limit.is_some_and(|limit| elapsed >= limit) {
+        Some(if objectives.target_score.is_some() {
+            EndCause::TimeUp
+        } else {
+            EndCause::Objective
+        })
+    } else {
+        None
+    }
+}
+
+/// Return the items to show in the score bar for the player's progress
+/// towards the objectives: the score (as formatted for display) against the
+/// target score and the time remaining, for whichever of these the objectives
+/// include
+pub(super) fn progress(objectives: &Objectives, score: &str, elapsed: Duration) -> Vec<String> {
+    let mut items = Vec::new();
+    if let Some(target) = objectives.target_score {
+        let target = i18n::number(target.get());
+        let args: [(&str, &dyn std::fmt::Display); 2] = [("score", &score), ("target", &target)];
+        items.push(tr_args(Msg::Goal, &args));
+    }
+    if let Some(limit) = objectives.time_limit {
+        let secs = limit.saturating_sub(elapsed).as_secs_f64().ceil();
+        items.push(tr_args(Msg::TimeLeft, &[("secs", &secs)]));
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    fn objectives(target: Option<u32>, limit: Option<u64>) -> Objectives {
+        Objectives {
+            target_score: target.and_then(NonZeroU32::new),
+            time_limit: limit.map(Duration::from_secs),
+            ..Objectives::default()
+        }
+    }
+
+    #[test]
+    fn check_target_score() {
+        let obj = objectives(Some(10), Some(60));
+        assert_eq!(check(&obj, 9, Duration::from_secs(59)), None);
+        assert_eq!(
+            check(&obj, 10, Duration::from_secs(59)),
+            Some(EndCause::Objective)
+        );
+        assert_eq!(
+            check(&obj, 9, Duration::from_secs(60)),
+            Some(EndCause::TimeUp)
+        );
+    }
+
+    #[test]
+    fn check_survival() {
+        let obj = objectives(None, Some(30));
+        assert_eq!(check(&obj, 0, Duration::from_secs(29)), None);
+        assert_eq!(
+            check(&obj, 0, Duration::from_secs(30)),
+            Some(EndCause::Objective)
+        );
+        assert_eq!(check(&Objectives::default(), 100, Duration::MAX), None);
+    }
+
+    #[test]
+    fn verdict() {
+        let obj = objectives(Some(10), None);
+        assert_eq!(
+            Verdict::of(&obj, EndCause::Objective, 10),
+            Verdict::Complete
+        );
+        assert_eq!(Verdict::of(&obj, EndCause::TimeUp, 3), Verdict::TimeUp);
+        assert_eq!(Verdict::of(&obj, EndCause::Forbidden, 3), Verdict::Failed);
+        assert_eq!(Verdict::of(&obj, EndCause::BoardFull, 3), Verdict::Failed);
+        assert_eq!(
+            Verdict::of(&Objectives::default(), EndCause::Perfect, 3),
+            Verdict::Complete
+        );
+    }
+
+    #[test]
+    fn progress_items() {
+        let obj = objectives(Some(10), Some(60));
+        assert_eq!(
+            progress(&obj, "4", Duration::from_millis(15_500)),
+            ["Goal: 4/10", "Time: 45s"]
+        );
+        assert!(progress(&Objectives::default(), "4", Duration::ZERO).is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::highscores::HighScore;
+use crate::i18n;
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+/// A previously-achieved high score that the player is racing against
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Rival {
+    /// The score to beat
+    score: NonZeroU32,
+
+    /// How long the game in which the score was achieved was played for
+    duration: Duration,
+}
+
+impl Rival {
+    /// Create a `Rival` for racing against the given high score.  Returns
+    /// `None` if the duration of the high score's game was not recorded.
+    pub(crate) fn new(high_score: &HighScore) -> Option<Rival> {
+        high_score
+            .duration()
+            .filter(|d| !d.is_zero())
+            .map(|duration| Rival {
+                score: high_score.score,
+                duration,
+            })
+    }
+
+    /// Compare a score of `score` after playing for `elapsed` to the pace of
+    /// the rival score
+    pub(super) fn pace(&self, score: u32, elapsed: Duration) -> Pace {
+        // Compare score / elapsed to rival / duration by cross-multiplying
+        let ours = u128::from(score) * self.duration.as_millis();
+        let theirs = u128::from(self.score.get()) * elapsed.as_millis();
+        Pace {
+            rate: per_minute(score, elapsed),
+            target: per_minute(self.score.get(), self.duration),
+            ahead: ours >= theirs,
+        }
+    }
+}
+
+/// How a game in progress compares to the [`Rival`] it is racing against
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct Pace {
+    /// The points scored per minute so far, in tenths of a point
+    pub(super) rate: u64,
+
+    /// The points per minute scored by the rival, in tenths of a point
+    pub(super) target: u64,
+
+    /// Is the game scoring at least as fast as the rival?
+    pub(super) ahead: bool,
+}
+
+/// Return the number of points per minute, in tenths of a point, of scoring
+/// `score` points in `elapsed`.  If `elapsed` is zero, the result is zero.
+fn per_minute(score: u32, elapsed: Duration) -> u64 {
+    (u128::from(score) * 600_000)
+        .checked_div(elapsed.as_millis())
+        .map_or(0, |r| u64::try_from(r).unwrap_or(u64::MAX))
+}
+
+/// Format a quantity in tenths with one decimal place
+pub(super) fn fmt_tenths(n: u64) -> String {
+    format!(
+        "{}{}{}",
+        i18n::number(n / 10),
+        i18n::decimal_separator(),
+        n % 10
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rival() -> Rival {
+        Rival::new(&HighScore {
+            score: NonZeroU32::new(30).unwrap(),
+            slow_motion: false,
+            achieved: None,
+            duration_ms: Some(120_000),
+            name: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn no_duration() {
+        let hs = HighScore::from(NonZeroU32::new(30).unwrap());
+        assert_eq!(Rival::new(&hs), None);
+    }
+
+    #[test]
+    fn ahead() {
+        let pace = rival().pace(8, Duration::from_secs(30));
+        assert_eq!(
+            pace,
+            Pace {
+                rate: 160,
+                target: 150,
+                ahead: true
+            }
+        );
+        assert_eq!(fmt_tenths(pace.rate), "16.0");
+    }
+
+    #[test]
+    fn behind() {
+        let pace = rival().pace(10, Duration::from_secs(45));
+        assert_eq!(
+            pace,
+            Pace {
+                rate: 133,
+                target: 150,
+                ahead: false
+            }
+        );
+        assert_eq!(fmt_tenths(pace.rate), "13.3");
+    }
+
+    #[test]
+    fn start_of_game() {
+        let pace = rival().pace(0, Duration::ZERO);
+        assert_eq!(pace.rate, 0);
+        assert!(pace.ahead);
+    }
+}
@@ -0,0 +1,89 @@
+//! Route planning for steering the snake towards a cell chosen with the mouse
+use crate::direction::Direction;
+use crate::util::Bounds;
+use ratatui::layout::Position;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Return the direction of the first move along a shortest route from `start`
+/// to `goal` within `bounds` that does not pass through any cell in
+/// `blocked`.  Returns `None` if `start` equals `goal` or if there is no such
+/// route.
+pub(super) fn first_step(
+    bounds: Bounds,
+    start: Position,
+    goal: Position,
+    blocked: &HashSet<Position>,
+) -> Option<Direction> {
+    // Maps each cell visited so far to the first move of the route to it
+    let mut firsts = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(p) = queue.pop_front() {
+        if p == goal {
+            return firsts.get(&p).copied().flatten();
+        }
+        let first = firsts.get(&p).copied().flatten();
+        for d in [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ] {
+            if let Some(q) = d.advance(p, bounds) {
+                if !blocked.contains(&q) && !firsts.contains_key(&q) {
+                    firsts.insert(q, Some(first.unwrap_or(d)));
+                    queue.push_back(q);
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Size;
+
+    #[test]
+    fn straight_line() {
+        let bounds = Bounds::new(Size::new(10, 10), false);
+        let step = first_step(
+            bounds,
+            Position::new(2, 5),
+            Position::new(7, 5),
+            &HashSet::new(),
+        );
+        assert_eq!(step, Some(Direction::East));
+        let step = first_step(
+            bounds,
+            Position::new(2, 5),
+            Position::new(2, 5),
+            &HashSet::new(),
+        );
+        assert_eq!(step, None);
+    }
+
+    #[test]
+    fn around_wall() {
+        let bounds = Bounds::new(Size::new(5, 5), false);
+        // A wall across the middle row, open only at the right end
+        let blocked = (0..4).map(|x| Position::new(x, 2)).collect::<HashSet<_>>();
+        let step = first_step(bounds, Position::new(1, 1), Position::new(1, 3), &blocked);
+        assert_eq!(step, Some(Direction::East));
+        let blocked = (0..5).map(|x| Position::new(x, 2)).collect::<HashSet<_>>();
+        let step = first_step(bounds, Position::new(1, 1), Position::new(1, 3), &blocked);
+        assert_eq!(step, None);
+    }
+
+    #[test]
+    fn wraparound() {
+        let bounds = Bounds::new(Size::new(10, 10), true);
+        let step = first_step(
+            bounds,
+            Position::new(1, 5),
+            Position::new(8, 5),
+            &HashSet::new(),
+        );
+        assert_eq!(step, Some(Direction::West));
+    }
+}
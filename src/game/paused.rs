@@ -1,5 +1,7 @@
 use crate::command::Command;
 use crate::consts;
+use crate::i18n::{tr, Msg};
+use crate::theme;
 use crate::util::EnumExt;
 use crossterm::event::Event;
 use enum_map::Enum;
@@ -19,13 +21,13 @@ use ratatui::{
 pub(super) struct Paused {
     /// The currently-selected item in the pause menu
     selection: PauseOpt,
+
+    /// Whether the menu includes the option to add the board to the
+    /// player's favorites
+    favorite: bool,
 }
 
 impl Paused {
-    /// The height that should be used for the `Rect` passed to
-    /// `Paused::render()`
-    pub(super) const HEIGHT: u16 = 6;
-
     /// The width that should be used for the `Rect` passed to
     /// `Paused::render()`
     pub(super) const WIDTH: u16 = 19;
@@ -34,32 +36,58 @@ impl Paused {
     pub(super) fn new() -> Paused {
         Paused {
             selection: PauseOpt::min(),
+            favorite: false,
+        }
+    }
+
+    /// Create a new `Paused` whose menu also offers to add the board to the
+    /// player's favorites
+    pub(super) fn with_favorite() -> Paused {
+        Paused {
+            favorite: true,
+            ..Paused::new()
         }
     }
 
+    /// The height that should be used for the `Rect` passed to
+    /// `Paused::render()`
+    pub(super) fn height(self) -> u16 {
+        let opts = u16::try_from(self.options().count()).unwrap_or(u16::MAX);
+        opts.saturating_add(2)
+    }
+
+    /// Return an iterator over the options shown in the menu
+    fn options(self) -> impl Iterator<Item = PauseOpt> {
+        PauseOpt::iter().filter(move |&opt| self.favorite || opt != PauseOpt::Favorite)
+    }
+
     /// Handle an input event.  Returns `Some` if the user made a choice.
     pub(super) fn handle_event(&mut self, event: Event) -> Option<PauseOpt> {
-        match Command::from_key_event(event.as_key_press_event()?)? {
+        let opts = self.options().collect::<Vec<_>>();
+        let i = opts
+            .iter()
+            .position(|&opt| opt == self.selection)
+            .unwrap_or_default();
+        let last = opts.len().saturating_sub(1);
+        let new_index = match Command::from_key_event(event.as_key_press_event()?)? {
             Command::Esc => return Some(PauseOpt::Resume),
             Command::R => return Some(PauseOpt::Restart),
+            Command::F if self.favorite => return Some(PauseOpt::Favorite),
             Command::M => return Some(PauseOpt::MainMenu),
             Command::Q | Command::Quit => return Some(PauseOpt::Quit),
             Command::Enter => return Some(self.selection),
-            Command::Up => {
-                if let Some(opt) = self.selection.prev() {
-                    self.selection = opt;
-                }
-            }
-            Command::Down => {
-                if let Some(opt) = self.selection.next() {
-                    self.selection = opt;
-                }
-            }
-            Command::Next => self.selection = self.selection.next().unwrap_or_else(PauseOpt::min),
-            Command::Prev => self.selection = self.selection.prev().unwrap_or_else(PauseOpt::max),
-            Command::Home => self.selection = PauseOpt::min(),
-            Command::End => self.selection = PauseOpt::max(),
-            _ => (),
+            Command::Up => i.saturating_sub(1),
+            Command::Down => (i + 1).min(last),
+            Command::Next if i == last => 0,
+            Command::Next => i + 1,
+            Command::Prev if i == 0 => last,
+            Command::Prev => i - 1,
+            Command::Home => 0,
+            Command::End => last,
+            _ => i,
+        };
+        if let Some(&opt) = opts.get(new_index) {
+            self.selection = opt;
         }
         None
     }
@@ -74,6 +102,9 @@ pub(super) enum PauseOpt {
     /// Start the game over
     Restart,
 
+    /// Add the board to the player's favorites
+    Favorite,
+
     /// Return to the main menu
     MainMenu,
 
@@ -93,28 +124,33 @@ impl PauseOpt {
         }
         match self {
             PauseOpt::Resume => {
-                line.push_span("Resume (");
+                line.push_span(format!("{} (", tr(Msg::Resume)));
                 line.push_span(Span::styled("Esc", consts::KEY_STYLE));
                 line.push_span(")");
             }
             PauseOpt::Restart => {
-                line.push_span("Restart (");
+                line.push_span(format!("{} (", tr(Msg::Restart)));
                 line.push_span(Span::styled("r", consts::KEY_STYLE));
                 line.push_span(")");
             }
+            PauseOpt::Favorite => {
+                line.push_span(format!("{} (", tr(Msg::Favorite)));
+                line.push_span(Span::styled("f", consts::KEY_STYLE));
+                line.push_span(")");
+            }
             PauseOpt::MainMenu => {
-                line.push_span("Main Menu (");
+                line.push_span(format!("{} (", tr(Msg::MainMenu)));
                 line.push_span(Span::styled("m", consts::KEY_STYLE));
                 line.push_span(")");
             }
             PauseOpt::Quit => {
-                line.push_span("Quit (");
+                line.push_span(format!("{} (", tr(Msg::Quit)));
                 line.push_span(Span::styled("q", consts::KEY_STYLE));
                 line.push_span(")");
             }
         }
         if selected {
-            line = line.style(consts::MENU_SELECTION_STYLE);
+            line = line.style(theme::selection_style());
         }
         line
     }
@@ -125,6 +161,7 @@ impl Widget for Paused {
      * ┌──── PAUSED ─────┐
      * │ » Resume (Esc)  │
      * │   Restart (r)   │
+     * │   Favorite (f)  │
      * │   Main Menu (m) │
      * │   Quit (q)      │
      * └─────────────────┘
@@ -132,13 +169,13 @@ impl Widget for Paused {
 
     fn render(self, area: Rect, buf: &mut Buffer) {
         let block = Block::bordered()
-            .title(" PAUSED ")
+            .title(format!(" {} ", tr(Msg::PausedTitle)))
             .title_alignment(Alignment::Center)
             .padding(Padding::horizontal(1))
             .style(Style::reset());
         let inner = block.inner(area);
         block.render(area, buf);
-        for (opt, row) in PauseOpt::iter().zip(inner.rows()) {
+        for (opt, row) in self.options().zip(inner.rows()) {
             opt.to_line(self.selection == opt).render(row, buf);
         }
     }
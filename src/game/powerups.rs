@@ -0,0 +1,109 @@
+use crate::config::{Glyph, GlyphConfig};
+use crate::consts;
+use crate::i18n::{tr, Msg};
+use enum_map::Enum;
+use ratatui::layout::Position;
+use serde::Serialize;
+use std::time::Duration;
+
+/// A power-up lying in a level, waiting to be eaten
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct PowerUp {
+    /// The power-up's location in the level
+    pub(super) pos: Position,
+
+    /// What the power-up does when eaten
+    pub(super) kind: PowerUpKind,
+}
+
+/// The kinds of power-ups that can appear in a level.  Eating a power-up gives
+/// the snake the corresponding effect.
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum PowerUpKind {
+    /// Lets the snake plow through the next obstacle it hits, destroying the
+    /// obstacle
+    Plow,
+
+    /// Temporarily speeds up the snake
+    SpeedUp,
+
+    /// Temporarily slows down the snake
+    SlowDown,
+
+    /// Temporarily lets the snake pass through its own body and obstacles
+    Star,
+}
+
+impl PowerUpKind {
+    /// Return the glyph used for drawing the power-up in a level
+    pub(super) fn glyph(self, glyphs: &GlyphConfig) -> &Glyph {
+        match self {
+            PowerUpKind::Plow => &glyphs.plow,
+            PowerUpKind::SpeedUp => &glyphs.speed_up,
+            PowerUpKind::SlowDown => &glyphs.slow_down,
+            PowerUpKind::Star => &glyphs.star,
+        }
+    }
+
+    /// Return the name shown in the score bar while the effect is active
+    pub(super) fn label(self) -> &'static str {
+        match self {
+            PowerUpKind::Plow => tr(Msg::Plow),
+            PowerUpKind::SpeedUp => tr(Msg::SpeedUp),
+            PowerUpKind::SlowDown => tr(Msg::SlowDown),
+            PowerUpKind::Star => tr(Msg::Star),
+        }
+    }
+
+    /// Return the number of ticks for which the effect lasts, or `None` if it
+    /// lasts until used up
+    pub(super) fn duration(self) -> Option<u32> {
+        match self {
+            PowerUpKind::Plow => None,
+            PowerUpKind::SpeedUp => Some(consts::SPEED_UP_TICKS),
+            PowerUpKind::SlowDown => Some(consts::SLOW_DOWN_TICKS),
+            PowerUpKind::Star => Some(consts::STAR_TICKS),
+        }
+    }
+
+    /// Return how likely the power-up is to be chosen when placing a new
+    /// power-up, relative to the other kinds
+    pub(super) fn weight(self) -> u32 {
+        match self {
+            PowerUpKind::Plow | PowerUpKind::SpeedUp | PowerUpKind::SlowDown => 3,
+            PowerUpKind::Star => 1,
+        }
+    }
+
+    /// Return the time between movements of the snake while the effect is
+    /// active, given the time between movements without it
+    pub(super) fn adjust_period(self, period: Duration) -> Duration {
+        match self {
+            PowerUpKind::Plow | PowerUpKind::Star => period,
+            PowerUpKind::SpeedUp => period * 2 / 3,
+            PowerUpKind::SlowDown => period * 3 / 2,
+        }
+    }
+}
+
+/// The effect of a power-up that the snake has eaten
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct ActivePowerUp {
+    /// The kind of power-up eaten
+    pub(super) kind: PowerUpKind,
+
+    /// The number of ticks remaining until the effect wears off, or `None` if
+    /// the effect lasts until used up
+    pub(super) ticks_left: Option<u32>,
+}
+
+impl ActivePowerUp {
+    /// Create the effect of a newly-eaten power-up of the given kind
+    pub(super) fn new(kind: PowerUpKind) -> ActivePowerUp {
+        ActivePowerUp {
+            kind,
+            ticks_left: kind.duration(),
+        }
+    }
+}
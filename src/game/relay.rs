@@ -0,0 +1,128 @@
+//! Co-op relay mode, in which two players sharing a keyboard take turns
+//! steering the same snake
+use crate::consts;
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// Which of the two players is steering the snake and how long until the
+/// other player takes over
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct Relay {
+    /// The index (0 or 1) of the player currently steering
+    player: usize,
+
+    /// The number of ticks left in the current player's turn
+    ticks_left: u16,
+
+    /// The number of ticks left in the animation marking the most recent
+    /// hand-off, or 0 if it's over
+    handoff_ticks: u8,
+}
+
+impl Relay {
+    /// Start a relay with the first player steering
+    pub(super) fn new() -> Relay {
+        Relay {
+            player: 0,
+            ticks_left: consts::RELAY_TURN_TICKS,
+            handoff_ticks: 0,
+        }
+    }
+
+    /// Return the index (0 or 1) of the player currently steering
+    pub(super) fn player(&self) -> usize {
+        self.player
+    }
+
+    /// Is `ev` one of the steering keys of the player currently steering?
+    pub(super) fn steers(&self, ev: KeyEvent) -> bool {
+        key_owner(ev) == Some(self.player)
+    }
+
+    /// Return the 1-based number of the player currently steering, for
+    /// display
+    pub(super) fn player_number(&self) -> usize {
+        self.player + 1
+    }
+
+    /// Return the 1-based number of the player who will steer next, for
+    /// display
+    pub(super) fn next_player_number(&self) -> usize {
+        (self.player + 1) % 2 + 1
+    }
+
+    /// Advance the relay by one tick, passing control to the other player if
+    /// the current player's turn is over.  Returns `true` if control changed
+    /// hands.
+    pub(super) fn tick(&mut self) -> bool {
+        self.handoff_ticks = self.handoff_ticks.saturating_sub(1);
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+        if self.ticks_left == 0 {
+            self.player = (self.player + 1) % 2;
+            self.ticks_left = consts::RELAY_TURN_TICKS;
+            self.handoff_ticks = consts::HANDOFF_ANIMATION_TICKS;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// If the current player's turn is nearly over, return the number of
+    /// ticks left in it, for showing a countdown to the hand-off
+    pub(super) fn countdown(&self) -> Option<u16> {
+        (self.ticks_left <= consts::RELAY_WARNING_TICKS).then_some(self.ticks_left)
+    }
+
+    /// If a hand-off was just made, return the number of ticks left in the
+    /// animation marking it
+    pub(super) fn handoff(&self) -> Option<u8> {
+        (self.handoff_ticks > 0).then_some(self.handoff_ticks)
+    }
+}
+
+/// If `ev` is one of a player's steering keys, return the index of the
+/// player.  Player 1 steers with the arrow keys (or H, J, K, & L, or the
+/// number keys 8, 4, 2, & 6), and player 2 steers with W, A, S, & D.
+fn key_owner(ev: KeyEvent) -> Option<usize> {
+    match ev.code {
+        KeyCode::Up
+        | KeyCode::Left
+        | KeyCode::Down
+        | KeyCode::Right
+        | KeyCode::Char('h' | 'j' | 'k' | 'l' | '8' | '4' | '2' | '6') => Some(0),
+        KeyCode::Char('w' | 'a' | 's' | 'd') => Some(1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_turns() {
+        let mut relay = Relay::new();
+        assert_eq!(relay.player(), 0);
+        assert_eq!(relay.next_player_number(), 2);
+        for _ in 1..consts::RELAY_TURN_TICKS - consts::RELAY_WARNING_TICKS {
+            assert!(!relay.tick());
+            assert_eq!(relay.countdown(), None);
+        }
+        assert!(!relay.tick());
+        assert_eq!(relay.countdown(), Some(consts::RELAY_WARNING_TICKS));
+        for _ in 1..consts::RELAY_WARNING_TICKS {
+            assert!(!relay.tick());
+        }
+        assert_eq!(relay.countdown(), Some(1));
+        assert_eq!(relay.handoff(), None);
+        assert!(relay.tick());
+        assert_eq!(relay.player(), 1);
+        assert_eq!(relay.player_number(), 2);
+        assert_eq!(relay.next_player_number(), 1);
+        assert_eq!(relay.countdown(), None);
+        assert_eq!(relay.handoff(), Some(consts::HANDOFF_ANIMATION_TICKS));
+        for _ in 0..consts::HANDOFF_ANIMATION_TICKS {
+            assert!(!relay.tick());
+        }
+        assert_eq!(relay.handoff(), None);
+    }
+}
@@ -0,0 +1,53 @@
+use super::hydra::Hydra;
+use super::levels::LevelMap;
+use super::powerups::PowerUp;
+use super::snake::Snake;
+use super::stats::GameStats;
+use crate::consts;
+use ratatui::layout::Position;
+use std::collections::{HashSet, VecDeque};
+
+/// The parts of a game's state that change from tick to tick, saved at the
+/// start of a tick so that the game can later be rewound to that point
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct Checkpoint {
+    pub(super) score: u32,
+    pub(super) combo: u32,
+    pub(super) combo_ticks_left: u32,
+    pub(super) snake: Snake,
+    pub(super) hydras: Vec<Hydra>,
+    pub(super) fruits: HashSet<Position>,
+    pub(super) hydra_fruit: Option<Position>,
+    pub(super) power_ups: Vec<PowerUp>,
+    pub(super) map: LevelMap,
+    pub(super) stats: GameStats,
+    pub(super) ticks: u32,
+}
+
+/// A ring buffer of the [`Checkpoint`]s for the most recent
+/// [`REWIND_TICKS`][consts::REWIND_TICKS] ticks of a game
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct History(VecDeque<Checkpoint>);
+
+impl History {
+    /// Add a checkpoint to the end of the history, discarding the oldest
+    /// checkpoint if the history is full
+    pub(super) fn push(&mut self, checkpoint: Checkpoint) {
+        if self.0.len() >= consts::REWIND_TICKS {
+            let _ = self.0.pop_front();
+        }
+        self.0.push_back(checkpoint);
+    }
+
+    /// Returns `true` if there are no checkpoints in the history
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Remove & return the oldest checkpoint, clearing the rest of the history
+    pub(super) fn take_oldest(&mut self) -> Option<Checkpoint> {
+        let checkpoint = self.0.pop_front();
+        self.0.clear();
+        checkpoint
+    }
+}
@@ -1,8 +1,9 @@
+use super::powerups::{ActivePowerUp, PowerUpKind};
 use crate::consts;
 use crate::direction::Direction;
 use crate::util::Bounds;
 use ratatui::layout::Position;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 /// Snake state.  Snate.
 ///
@@ -15,13 +16,28 @@ pub(super) struct Snake {
 
     /// The positions of all of the cells in the snake's body, with the most
     /// recent at the end.
-    pub(super) body: VecDeque<Position>,
+    body: VecDeque<Position>,
+
+    /// The number of times each position occurs in `body`, for checking
+    /// whether a position is occupied without scanning the whole body.  (A
+    /// position can occur more than once if the snake passed through itself
+    /// while invincible.)
+    occupancy: HashMap<Position, u32>,
 
     /// The maximum length of the body
     pub(super) max_len: usize,
 
     /// The direction in which the snake is currently facing
     pub(super) direction: Direction,
+
+    /// The direction in which the snake last moved (or, before its first
+    /// move, the direction it started out facing).  This differs from
+    /// `direction` when the snake has been turned since it last moved.
+    heading: Direction,
+
+    /// The effects of any power-ups that the snake has eaten and not yet used
+    /// up, in the order in which they were eaten
+    pub(super) power_ups: Vec<ActivePowerUp>,
 }
 
 impl Snake {
@@ -32,8 +48,11 @@ impl Snake {
         Snake {
             head,
             body: VecDeque::new(),
+            occupancy: HashMap::new(),
             max_len: consts::INITIAL_SNAKE_LENGTH,
             direction,
+            heading: direction,
+            power_ups: Vec::new(),
         }
     }
 
@@ -47,28 +66,215 @@ impl Snake {
         &self.body
     }
 
+    /// Replace the snake's body with the given positions, ordered from tail to
+    /// neck
+    #[cfg(any(test, feature = "bench"))]
+    pub(super) fn set_body<I: IntoIterator<Item = Position>>(&mut self, body: I) {
+        self.body.clear();
+        self.occupancy.clear();
+        for pos in body {
+            self.push_body(pos);
+        }
+    }
+
+    /// Return `true` if any cell of the snake's body (not counting the head)
+    /// is at `pos`
+    pub(super) fn body_contains(&self, pos: Position) -> bool {
+        self.occupancy.contains_key(&pos)
+    }
+
+    /// Add a cell to the neck end of the body
+    fn push_body(&mut self, pos: Position) {
+        self.body.push_back(pos);
+        *self.occupancy.entry(pos).or_insert(0) += 1;
+    }
+
+    /// Remove the cell at the tail end of the body, if any
+    fn pop_tail(&mut self) {
+        if let Some(pos) = self.body.pop_front() {
+            if let Some(count) = self.occupancy.get_mut(&pos) {
+                *count -= 1;
+                if *count == 0 {
+                    self.occupancy.remove(&pos);
+                }
+            }
+        }
+    }
+
     /// Change the snake's direction to `direction`
     pub(super) fn turn(&mut self, direction: Direction) {
         self.direction = direction;
     }
 
+    /// Return the direction in which the snake last moved
+    pub(super) fn heading(&self) -> Direction {
+        self.heading
+    }
+
     /// Move the snake forwards one cell in the current direction within
     /// `bounds`.  Returns `false` if the snake was unable to advance due to
     /// hitting a non-wraparound edge.
     pub(super) fn advance(&mut self, bounds: Bounds) -> bool {
-        let Some(pos) = self.direction.advance(self.head, bounds) else {
+        self.advance_toward(self.direction, bounds)
+    }
+
+    /// Move the snake forwards one cell in the direction it last moved,
+    /// ignoring any turns made since then, which take effect on the next call
+    /// to [`Snake::advance()`] instead.  This is used when the snake's head
+    /// is on ice.  Returns `false` if the snake was unable to advance due to
+    /// hitting a non-wraparound edge.
+    pub(super) fn slide(&mut self, bounds: Bounds) -> bool {
+        self.advance_toward(self.heading, bounds)
+    }
+
+    /// Move the snake forwards one cell in `direction` within `bounds`.  The
+    /// heading is updated even if the snake hits an edge, so that it records
+    /// which way the snake crashed.
+    fn advance_toward(&mut self, direction: Direction, bounds: Bounds) -> bool {
+        self.heading = direction;
+        let Some(pos) = direction.advance(self.head, bounds) else {
             return false;
         };
-        self.body.push_back(self.head);
+        self.push_body(self.head);
         self.head = pos;
         while self.body.len() > self.max_len {
-            let _ = self.body.pop_front();
+            self.pop_tail();
         }
         true
     }
 
+    /// Give the snake the effect of a power-up of the given kind.  Effects
+    /// that last until used up stack, while eating a timed power-up whose
+    /// effect is already active restarts its timer.
+    pub(super) fn gain(&mut self, kind: PowerUpKind) {
+        let active = ActivePowerUp::new(kind);
+        match self
+            .power_ups
+            .iter_mut()
+            .find(|pu| pu.kind == kind && pu.ticks_left.is_some())
+        {
+            Some(pu) => *pu = active,
+            None => self.power_ups.push(active),
+        }
+    }
+
+    /// Return the number of ticks left until the snake stops being invincible,
+    /// or `None` if it is not currently invincible
+    pub(super) fn invincible_ticks(&self) -> Option<u32> {
+        self.power_ups
+            .iter()
+            .find(|pu| pu.kind == PowerUpKind::Star)
+            .and_then(|pu| pu.ticks_left)
+    }
+
+    /// If the snake has an active power-up of the given kind, use it up and
+    /// return `true`; otherwise, return `false`.
+    pub(super) fn consume(&mut self, kind: PowerUpKind) -> bool {
+        if let Some(i) = self.power_ups.iter().position(|pu| pu.kind == kind) {
+            self.power_ups.remove(i);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Extend the snake's maximum length in response to eating a fruit
     pub(super) fn grow(&mut self) {
         self.max_len += consts::SNAKE_GROWTH;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(wrap: bool) -> Bounds {
+        Bounds {
+            width: 5,
+            height: 5,
+            wrap,
+        }
+    }
+
+    #[test]
+    fn grow_and_trail() {
+        let mut snake = Snake::new(Position::new(0, 2), Direction::East);
+        snake.max_len = 2;
+        assert!(!snake.body_contains(Position::new(0, 2)));
+        assert!(snake.advance(bounds(false)));
+        assert!(snake.body_contains(Position::new(0, 2)));
+        assert!(!snake.body_contains(Position::new(1, 2)));
+        assert!(snake.advance(bounds(false)));
+        assert!(snake.advance(bounds(false)));
+        // The tail has moved off of the starting cell
+        assert_eq!(
+            snake.body(),
+            &VecDeque::from([Position::new(1, 2), Position::new(2, 2)])
+        );
+        assert!(!snake.body_contains(Position::new(0, 2)));
+        assert!(snake.body_contains(Position::new(1, 2)));
+        assert!(snake.body_contains(Position::new(2, 2)));
+        assert!(!snake.body_contains(snake.head()));
+        snake.grow();
+        assert!(snake.advance(bounds(false)));
+        assert_eq!(snake.body().len(), 3);
+        assert!(snake.body_contains(Position::new(1, 2)));
+    }
+
+    #[test]
+    fn wraparound() {
+        let mut snake = Snake::new(Position::new(4, 0), Direction::East);
+        snake.max_len = 3;
+        assert!(!snake.clone().advance(bounds(false)));
+        for _ in 0..3 {
+            assert!(snake.advance(bounds(true)));
+        }
+        assert_eq!(snake.head(), Position::new(2, 0));
+        for x in [4, 0, 1] {
+            assert!(snake.body_contains(Position::new(x, 0)));
+        }
+        assert!(snake.advance(bounds(true)));
+        assert!(!snake.body_contains(Position::new(4, 0)));
+        assert!(snake.body_contains(Position::new(2, 0)));
+    }
+
+    #[test]
+    fn slide() {
+        let mut snake = Snake::new(Position::new(0, 2), Direction::East);
+        assert!(snake.advance(bounds(false)));
+        snake.turn(Direction::North);
+        assert!(snake.slide(bounds(false)));
+        assert_eq!(snake.head(), Position::new(2, 2));
+        assert_eq!(snake.heading(), Direction::East);
+        // The turn is kept for the next move
+        assert!(snake.advance(bounds(false)));
+        assert_eq!(snake.head(), Position::new(2, 1));
+        assert_eq!(snake.heading(), Direction::North);
+    }
+
+    #[test]
+    fn overlapping_body() {
+        let mut snake = Snake::new(Position::new(3, 3), Direction::West);
+        snake.max_len = 4;
+        // A body that crosses itself, as can happen while invincible
+        snake.set_body([
+            Position::new(1, 1),
+            Position::new(2, 1),
+            Position::new(1, 1),
+            Position::new(1, 2),
+        ]);
+        assert!(snake.advance(bounds(false)));
+        // One occurrence of (1, 1) was dropped from the tail, but another
+        // remains
+        assert!(snake.body_contains(Position::new(1, 1)));
+        assert!(snake.advance(bounds(false)));
+        assert!(snake.body_contains(Position::new(1, 1)));
+        assert!(!snake.body_contains(Position::new(2, 1)));
+        assert!(snake.advance(bounds(false)));
+        assert!(!snake.body_contains(Position::new(1, 1)));
+        assert!(snake.body_contains(Position::new(1, 2)));
+        snake.set_body([]);
+        assert!(snake.body().is_empty());
+        assert!(!snake.body_contains(Position::new(1, 2)));
+    }
+}
@@ -0,0 +1,137 @@
+use super::powerups::PowerUpKind;
+use super::snake::Snake;
+use crate::direction::Direction;
+use ratatui::layout::Position;
+use serde::Serialize;
+
+/// A description of the state of a game, as sent to [bots][crate::bot::Bot]
+/// at the start of each tick and to broadcast clients whenever it changes.
+///
+/// All positions are relative to the top-left corner of the level, with `x`
+/// increasing to the east and `y` increasing to the south.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct Snapshot {
+    /// The number of ticks that have passed since the game started
+    pub(crate) tick: u32,
+
+    /// The width of the level in cells
+    pub(crate) width: u16,
+
+    /// The height of the level in cells
+    pub(crate) height: u16,
+
+    /// Whether the level's borders wrap around
+    pub(crate) wraparound: bool,
+
+    /// What the game is currently doing
+    pub(crate) state: SnapshotState,
+
+    /// The current score
+    pub(crate) score: u32,
+
+    /// The high score for the game's options at the time the game started, if
+    /// any
+    pub(crate) high_score: Option<u32>,
+
+    /// The state of the snake
+    pub(crate) snake: SnakeSnapshot,
+
+    /// The states of any extra snakes spawned in hydra mode
+    pub(crate) hydras: Vec<SnakeSnapshot>,
+
+    /// The positions of the fruits in the level
+    pub(crate) fruits: Vec<Point>,
+
+    /// The position of the hydra fruit in the level, if any
+    pub(crate) hydra_fruit: Option<Point>,
+
+    /// The positions of the obstacles & maze walls in the level
+    pub(crate) obstacles: Vec<Point>,
+
+    /// The positions of the mud tiles in the level
+    pub(crate) mud: Vec<Point>,
+
+    /// The positions of the ice tiles in the level
+    pub(crate) ice: Vec<Point>,
+
+    /// The one-way gates in the level
+    pub(crate) gates: Vec<GateSnapshot>,
+
+    /// The power-ups lying in the level
+    pub(crate) power_ups: Vec<PowerUpSnapshot>,
+}
+
+/// An enum of the states that a game in a [`Snapshot`] can be in
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SnapshotState {
+    /// The game is in progress
+    Running,
+
+    /// The game is paused
+    Paused,
+
+    /// The game is over
+    Over,
+}
+
+/// The state of the snake in a [`Snapshot`]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct SnakeSnapshot {
+    /// The position of the snake's head
+    pub(crate) head: Point,
+
+    /// The positions of the cells in the snake's body, starting at the end of
+    /// the tail
+    pub(crate) body: Vec<Point>,
+
+    /// The direction in which the snake is currently moving
+    pub(crate) direction: Direction,
+
+    /// The kinds of the power-ups whose effects the snake currently has
+    pub(crate) power_ups: Vec<PowerUpKind>,
+}
+
+impl From<&Snake> for SnakeSnapshot {
+    fn from(snake: &Snake) -> SnakeSnapshot {
+        SnakeSnapshot {
+            head: snake.head().into(),
+            body: snake.body().iter().copied().map(Into::into).collect(),
+            direction: snake.direction,
+            power_ups: snake.power_ups.iter().map(|pu| pu.kind).collect(),
+        }
+    }
+}
+
+/// A power-up lying in the level in a [`Snapshot`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct PowerUpSnapshot {
+    /// The power-up's position
+    pub(crate) pos: Point,
+
+    /// What the power-up does
+    pub(crate) kind: PowerUpKind,
+}
+
+/// A one-way gate in the level in a [`Snapshot`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct GateSnapshot {
+    /// The gate's position
+    pub(crate) pos: Point,
+
+    /// The direction in which the gate can be entered
+    pub(crate) direction: Direction,
+}
+
+/// A position in a [`Snapshot`]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub(crate) struct Point {
+    pub(crate) x: u16,
+    pub(crate) y: u16,
+}
+
+impl From<Position> for Point {
+    fn from(pos: Position) -> Point {
+        Point { x: pos.x, y: pos.y }
+    }
+}
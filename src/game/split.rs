@@ -0,0 +1,601 @@
+//! Split-board mode, in which two players sharing a keyboard each steer their
+//! own snake on their own half of the level, racing to be the first to reach
+//! a target score
+use super::levels::LevelMap;
+use super::paused::{PauseOpt, Paused};
+use super::{DottedBorder, Game, GameState, TickSource};
+use crate::app::Screen;
+use crate::command::Command;
+use crate::consts;
+use crate::direction::Direction;
+use crate::i18n::{tr, tr_args, Msg};
+use crate::input::EventSource;
+use crate::palette::{Palette, PaletteAction, PaletteEntry};
+use crate::util::{center_rect, get_display_area, Bounds, Globals, LayoutDirection};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Margin, Rect, Size},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Widget},
+    Frame,
+};
+use std::cmp::Ordering;
+use std::num::NonZeroU32;
+use std::time::Instant;
+
+/// Split-board game screen
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SplitGame<R = StdRng> {
+    /// The players' games, with player 1's on the left.  Each is an ordinary
+    /// game played on its own copy of the level, so all of the usual rules
+    /// apply to both, except that crashing costs a point instead of ending
+    /// the game.
+    games: [Game<R>; 2],
+
+    /// The score that a player must reach in order to win
+    target: NonZeroU32,
+
+    /// The current state of the match
+    state: SplitState,
+
+    /// Global data (options & high scores)
+    globals: Globals,
+}
+
+impl SplitGame<StdRng> {
+    /// Create a new split-board game to `target` points from the given
+    /// globals using an RNG seeded as directed by the `rng` setting
+    pub(crate) fn new(globals: Globals, target: NonZeroU32) -> Self {
+        let seed = globals.config.game.rng.seed();
+        SplitGame::new_with_rng(globals, target, StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng + SeedableRng> SplitGame<R> {
+    /// Create a new split-board game to `target` points from the given
+    /// globals using the given RNG.
+    ///
+    /// The level selected in the options is cut down to fit on the screen
+    /// and then split in two with a wall down the middle.  Both halves get
+    /// the same layout, obstacles, & terrain so that neither player has an
+    /// advantage.
+    pub(crate) fn new_with_rng(globals: Globals, target: NonZeroU32, mut rng: R) -> SplitGame<R> {
+        let level = globals.options.level_bounds(globals.config.cell_aspect);
+        let bounds = Bounds {
+            // One column goes to the wall between the halves
+            width: (level
+                .width
+                .min(consts::VIEWPORT_SIZE.width)
+                .saturating_sub(1)
+                / 2)
+            .max(1),
+            height: level.height.min(consts::VIEWPORT_SIZE.height),
+            wrap: level.wrap,
+        };
+        let options = globals.options;
+        let map = LevelMap::generate(
+            bounds,
+            options.layout,
+            options.obstacles,
+            options.terrain,
+            options.start,
+            &mut rng,
+        );
+        let left = Game::for_split(globals.clone(), map.clone(), R::from_rng(&mut rng));
+        let right = Game::for_split(globals.clone(), map, R::from_rng(&mut rng));
+        SplitGame {
+            games: [left, right],
+            target,
+            state: SplitState::Running,
+            globals,
+        }
+    }
+}
+
+impl<R: Rng> SplitGame<R> {
+    /// Receive & handle the next input event from `events`.  If the match is
+    /// currently running and no event is received before either game's next
+    /// tick, the snakes that are due to move advance and the method returns.
+    /// Each snake moves at its own speed, as set by slow motion, power-ups,
+    /// and adaptive difficulty.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn process_input(
+        &mut self,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<Option<Screen>> {
+        if self.state == SplitState::Running && self.globals.tick_source == TickSource::Timer {
+            let now = Instant::now();
+            for game in &mut self.games {
+                if game.next_tick.is_none() {
+                    game.next_tick = Some(now + game.move_period());
+                }
+            }
+            if let Some(when) = self.games.iter().filter_map(|game| game.next_tick).min() {
+                let wait = when.saturating_duration_since(Instant::now());
+                if wait.is_zero() || !events.poll(wait)? {
+                    let now = Instant::now();
+                    for game in &mut self.games {
+                        if game.next_tick.is_some_and(|t| t <= now) {
+                            game.advance();
+                            game.next_tick = None;
+                        }
+                    }
+                    self.check_winner();
+                    return Ok(None);
+                }
+            }
+        }
+        let event = events.read()?;
+        Ok(self.handle_event(event))
+    }
+
+    /// Move both snakes forwards (in step mode)
+    fn advance(&mut self) {
+        for game in &mut self.games {
+            game.advance();
+        }
+        self.check_winner();
+    }
+
+    /// End the match if either player has reached the target score or has
+    /// filled their half of the board.  If both players reach the target on
+    /// the same tick, or a board fills up, the higher score wins, and equal
+    /// scores are a tie.
+    fn check_winner(&mut self) {
+        let [left, right] = [self.games[0].score, self.games[1].score];
+        let filled = self
+            .games
+            .iter()
+            .any(|game| matches!(game.state, GameState::Exhausted(_)));
+        if filled || left.max(right) >= self.target.get() {
+            self.state = SplitState::Over(match left.cmp(&right) {
+                Ordering::Greater => Some(0),
+                Ordering::Less => Some(1),
+                Ordering::Equal => None,
+            });
+        }
+    }
+
+    /// Handle the given input event.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    fn handle_event(&mut self, event: Event) -> Option<Screen> {
+        match self.state {
+            SplitState::Running => {
+                if self.games[0].pauses_for(&event) {
+                    self.pause();
+                    return None;
+                }
+                let ev = event.as_key_press_event()?;
+                if self.globals.tick_source == TickSource::Step && consts::STEP_KEY.matches(ev) {
+                    self.advance();
+                } else if let Some((player, direction)) = steering(ev) {
+                    if let Some(game) = self.games.get_mut(player) {
+                        game.steer_by_key(direction, ev);
+                    }
+                } else if self.globals.config.controls.slow_motion.matches(ev) {
+                    // Slow motion slows both snakes so that neither player
+                    // gains an edge from it
+                    let slow_motion = !self.games[0].slow_motion;
+                    for game in &mut self.games {
+                        game.slow_motion = slow_motion;
+                        game.used_slow_motion |= slow_motion;
+                    }
+                } else {
+                    match Command::from_key_event(ev)? {
+                        Command::Quit => return Some(Screen::Quit),
+                        Command::Esc => self.pause(),
+                        _ => (),
+                    }
+                }
+            }
+            SplitState::Paused(ref mut paused) => match paused.handle_event(event)? {
+                PauseOpt::Resume => self.state = SplitState::Running,
+                PauseOpt::Restart => return Some(self.restart()),
+                // Not offered, as split-screen boards can't be replayed from
+                // the main menu
+                PauseOpt::Favorite => (),
+                PauseOpt::MainMenu => return Some(self.main_menu()),
+                PauseOpt::Quit => return Some(Screen::Quit),
+            },
+            SplitState::Over(_) => {
+                let ev = event.as_key_press_event()?;
+                if self.globals.kiosk {
+                    // As with a single-player game, a kiosk match always ends
+                    // with a return to the main menu
+                    return Some(self.main_menu());
+                }
+                match Command::from_key_event(ev)? {
+                    Command::R => return Some(self.restart()),
+                    Command::Esc | Command::M => return Some(self.main_menu()),
+                    Command::Quit | Command::Q => return Some(Screen::Quit),
+                    _ => (),
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R> SplitGame<R> {
+    /// Return a mutable reference to the screen's global data
+    pub(crate) fn globals_mut(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+
+    /// Draw the game on the given frame
+    pub(crate) fn draw(&self, frame: &mut Frame<'_>) {
+        frame.render_widget(self, frame.area());
+    }
+
+    /// Return a command palette listing the actions available in the game's
+    /// current state.  A running game is paused first.  In kiosk mode,
+    /// quitting is not offered.
+    pub(crate) fn palette(&mut self) -> Palette {
+        if self.state == SplitState::Running {
+            self.pause();
+        }
+        let actions: &[PaletteAction] = match self.state {
+            SplitState::Running | SplitState::Paused(_) => &[
+                PaletteAction::Resume,
+                PaletteAction::Restart,
+                PaletteAction::MainMenu,
+                PaletteAction::Quit,
+            ],
+            SplitState::Over(_) => &[
+                PaletteAction::Restart,
+                PaletteAction::MainMenu,
+                PaletteAction::Quit,
+            ],
+        };
+        Palette::new(
+            actions
+                .iter()
+                .copied()
+                .filter(|&action| action != PaletteAction::Quit || !self.globals.kiosk)
+                .map(PaletteEntry::new)
+                .collect(),
+        )
+    }
+
+    /// Carry out an action chosen from the command palette.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn run_palette_action(&mut self, action: PaletteAction) -> Option<Screen> {
+        match action {
+            PaletteAction::Resume => {
+                if matches!(self.state, SplitState::Paused(_)) {
+                    self.state = SplitState::Running;
+                }
+                None
+            }
+            PaletteAction::Restart => Some(self.restart()),
+            PaletteAction::MainMenu => Some(self.main_menu()),
+            PaletteAction::Quit => Some(Screen::Quit),
+            PaletteAction::Play
+            | PaletteAction::Toggle(_)
+            | PaletteAction::SetSeed(_)
+            | PaletteAction::HighScores
+            | PaletteAction::Progress
+            | PaletteAction::About
+            | PaletteAction::Favorites
+            | PaletteAction::Levels
+            | PaletteAction::AddFavorite => None,
+        }
+    }
+
+    /// Pause the game
+    fn pause(&mut self) {
+        self.state = SplitState::Paused(Paused::new());
+        for game in &mut self.games {
+            game.next_tick = None;
+        }
+    }
+
+    /// Return a screen containing a fresh game to the same target score
+    fn restart(&self) -> Screen {
+        Screen::Split(Box::new(SplitGame::new(self.globals.clone(), self.target)))
+    }
+
+    /// Return a main menu screen to switch to
+    fn main_menu(&self) -> Screen {
+        Screen::Main(crate::menu::MainMenu::new(self.globals.clone()))
+    }
+}
+
+impl<R> Widget for &SplitGame<R> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        let [score_area, main_area, msg1_area, msg2_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(display);
+
+        // Each player's score is shown above their own half of the board, so
+        // the score bar is laid out the same way in either text direction.
+        let player_score = |i: usize| {
+            let args: [(&str, &dyn std::fmt::Display); 2] =
+                [("player", &(i + 1)), ("score", &self.games[i].score)];
+            tr_args(Msg::PlayerScore, &args)
+        };
+        LayoutDirection::LeftToRight
+            .align_start(Line::styled(player_score(0), consts::SCORE_BAR_STYLE))
+            .render(score_area, buf);
+        Line::raw(tr_args(Msg::FirstTo, &[("points", &self.target)]))
+            .centered()
+            .render(score_area, buf);
+        LayoutDirection::LeftToRight
+            .align_end(Line::raw(player_score(1)))
+            .render(score_area, buf);
+
+        let bounds = self.games[0].map.bounds();
+        let block_size = Size {
+            width: bounds.width.saturating_mul(2).saturating_add(3),
+            height: bounds.height.saturating_add(2),
+        };
+        let block_area = center_rect(main_area, block_size);
+        if bounds.wrap {
+            DottedBorder.render(block_area, buf);
+        } else {
+            Block::bordered().render(block_area, buf);
+        }
+        let glyphs = &self.globals.config.glyphs;
+        let level_area = block_area.inner(Margin::new(1, 1));
+        let wall = Rect {
+            x: level_area.x.saturating_add(bounds.width),
+            width: 1,
+            ..level_area
+        };
+        for pos in wall.positions() {
+            if let Some(cell) = buf.cell_mut(pos) {
+                cell.set_symbol(glyphs.obstacle.symbol.as_ref());
+                cell.set_style(Style::reset().patch(glyphs.obstacle.style));
+            }
+        }
+        let halves = [
+            Rect {
+                width: bounds.width,
+                ..level_area
+            },
+            Rect {
+                x: wall.right(),
+                width: bounds.width,
+                ..level_area
+            },
+        ];
+        for (game, half_area) in self.games.iter().zip(halves) {
+            game.render_level(game.viewport(), half_area, buf);
+        }
+
+        let direction = self.globals.config.layout_direction;
+        match self.state {
+            SplitState::Running => {
+                let player = |i: usize| tr_args(Msg::RelayPlayer, &[("player", &i)]);
+                direction
+                    .align_start(Line::from_iter([
+                        Span::raw(format!("{} (", player(1))),
+                        Span::styled("W A S D", consts::KEY_STYLE),
+                        Span::raw(format!(") — {} (", player(2))),
+                        Span::styled("← ↑ → ↓", consts::KEY_STYLE),
+                        Span::raw(format!(") — {} (", tr(Msg::Pause))),
+                        Span::styled("Esc", consts::KEY_STYLE),
+                        Span::raw(")"),
+                    ]))
+                    .render(msg1_area, buf);
+            }
+            SplitState::Paused(paused) => {
+                let pause_area = center_rect(
+                    display,
+                    Size {
+                        width: Paused::WIDTH,
+                        height: paused.height(),
+                    },
+                );
+                paused.render(pause_area, buf);
+            }
+            SplitState::Over(winner) => {
+                let headline = match winner {
+                    Some(i) => tr_args(Msg::PlayerWins, &[("player", &(i + 1))]),
+                    None => String::from(tr(Msg::Tie)),
+                };
+                direction
+                    .align_start(Line::from(format!("— {headline} —")))
+                    .render(msg1_area, buf);
+                let choices = if self.globals.kiosk {
+                    Line::from(tr(Msg::AnyKeyMainMenu))
+                } else {
+                    Line::from_iter([
+                        Span::raw(format!("{} {} (", tr(Msg::ChooseOne), tr(Msg::Restart))),
+                        Span::styled("r", consts::KEY_STYLE),
+                        Span::raw(format!(") — {} (", tr(Msg::MainMenu))),
+                        Span::styled("m", consts::KEY_STYLE),
+                        Span::raw(format!(") — {} (", tr(Msg::Quit))),
+                        Span::styled("q", consts::KEY_STYLE),
+                        Span::raw(")"),
+                    ])
+                };
+                direction.align_start(choices).render(msg2_area, buf);
+            }
+        }
+    }
+}
+
+/// The states that a split-board game can be in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SplitState {
+    /// The snakes are moving
+    Running,
+
+    /// The game is paused, and the pause menu is shown
+    Paused(Paused),
+
+    /// A player has reached the target score.  The value is the index of the
+    /// winning player, or `None` if the game ended in a tie.
+    Over(Option<usize>),
+}
+
+/// If `ev` is one of a player's steering keys, return the index of the player
+/// and the direction to turn.  Player 1 steers with W, A, S, & D, and player
+/// 2 steers with the arrow keys.
+fn steering(ev: KeyEvent) -> Option<(usize, Direction)> {
+    match ev.code {
+        KeyCode::Char('w' | 'W') => Some((0, Direction::North)),
+        KeyCode::Char('a' | 'A') => Some((0, Direction::West)),
+        KeyCode::Char('s' | 'S') => Some((0, Direction::South)),
+        KeyCode::Char('d' | 'D') => Some((0, Direction::East)),
+        KeyCode::Up => Some((1, Direction::North)),
+        KeyCode::Left => Some((1, Direction::West)),
+        KeyCode::Down => Some((1, Direction::South)),
+        KeyCode::Right => Some((1, Direction::East)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::EndCause;
+    use crate::options::{LevelSize, Options};
+    use rand_chacha::ChaCha12Rng;
+    use ratatui::layout::Position;
+
+    const RNG_SEED: u64 = 0x0123456789ABCDEF;
+
+    fn new_game(options: Options, target: u32) -> SplitGame<ChaCha12Rng> {
+        SplitGame::new_with_rng(
+            Globals {
+                options,
+                ..Globals::default()
+            },
+            NonZeroU32::new(target).unwrap(),
+            ChaCha12Rng::seed_from_u64(RNG_SEED),
+        )
+    }
+
+    #[test]
+    fn halves() {
+        let game = new_game(
+            Options {
+                level_size: LevelSize::Large,
+                obstacles: true,
+                ..Options::default()
+            },
+            10,
+        );
+        let [left, right] = &game.games;
+        assert_eq!(left.map.size(), Size::new(37, 19));
+        assert_eq!(left.map, right.map);
+        assert!(!left.map.obstacles().is_empty());
+        let fruits = Options::default().fruits.get();
+        assert_eq!(left.fruits.len(), fruits);
+        assert_eq!(right.fruits.len(), fruits);
+        assert!(!left.eligible_for_high_score());
+        assert!(left.history_record(EndCause::Body).is_none());
+    }
+
+    #[test]
+    fn render() {
+        let game = new_game(Options::default(), 10);
+        let buffer = crate::snapshot::render(&game);
+        crate::snapshot::assert_snapshot("split/render", &buffer);
+    }
+
+    #[test]
+    fn independent_steering() {
+        let mut game = new_game(Options::default(), 10);
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('a').into()))
+            .is_none());
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Right.into()))
+            .is_none());
+        assert_eq!(game.games[0].snake.direction, Direction::West);
+        assert_eq!(game.games[1].snake.direction, Direction::East);
+    }
+
+    #[test]
+    fn first_to_target_wins() {
+        let mut game = new_game(Options::default(), 2);
+        game.games[1].score = 1;
+        let ahead = game.games[1]
+            .snake
+            .direction
+            .advance(game.games[1].snake.head(), game.games[1].map.bounds())
+            .unwrap();
+        game.games[1].fruits.insert(ahead);
+        game.advance();
+        assert_eq!(game.games[1].score, 2);
+        assert_eq!(
+            game.games[1].fruits.len(),
+            Options::default().fruits.get() + 1
+        );
+        assert_eq!(game.state, SplitState::Over(Some(1)));
+        assert!(matches!(
+            game.handle_event(Event::Key(KeyCode::Char('r').into())),
+            Some(Screen::Split(_))
+        ));
+    }
+
+    #[test]
+    fn crash_costs_a_point() {
+        let mut game = new_game(Options::default(), 10);
+        let start = game.games[0].snake.clone();
+        game.games[0].score = 3;
+        game.games[0].snake.head = Position::new(0, 0);
+        game.games[0].snake.direction = Direction::West;
+        game.advance();
+        assert_eq!(game.games[0].score, 2);
+        assert_eq!(game.games[0].snake, start);
+        assert!(game.games[0].running());
+        assert_eq!(game.state, SplitState::Running);
+    }
+
+    #[test]
+    fn slow_motion_slows_both() {
+        let mut game = new_game(Options::default(), 10);
+        let periods = game.games.each_ref().map(Game::move_period);
+        assert!(game
+            .handle_event(Event::Key(KeyCode::Char('z').into()))
+            .is_none());
+        for (g, period) in game.games.iter().zip(periods) {
+            assert!(g.slow_motion);
+            assert!(g.move_period() > period);
+        }
+    }
+
+    #[test]
+    fn focus_loss_pauses() {
+        let mut game = new_game(Options::default(), 10);
+        assert!(game.handle_event(Event::FocusLost).is_none());
+        assert_eq!(game.state, SplitState::Paused(Paused::new()));
+    }
+
+    #[test]
+    fn kiosk_over() {
+        let mut game = new_game(Options::default(), 10);
+        game.globals.kiosk = true;
+        game.state = SplitState::Over(None);
+        let mut palette = game.palette();
+        for c in "quit".chars() {
+            palette.handle_event(Event::Key(KeyCode::Char(c).into()));
+        }
+        assert_eq!(
+            palette.handle_event(Event::Key(KeyCode::Enter.into())),
+            None
+        );
+        assert!(matches!(
+            game.handle_event(Event::Key(KeyCode::Char('q').into())),
+            Some(Screen::Main(_))
+        ));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::direction::Direction;
+use crate::i18n::{self, tr, tr_args, Msg};
+use crate::popup::PopupFrame;
+use ratatui::{buffer::Buffer, layout::Rect, text::Text, widgets::Widget};
+use std::time::Duration;
+
+/// Counters accumulated over the course of a game, displayed in a pop-up
+/// panel when the game is over
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct GameStats {
+    /// Total time spent playing, not counting time spent paused
+    elapsed: Duration,
+
+    /// The number of fruits eaten
+    fruits_eaten: u32,
+
+    /// The greatest length (head included) that the snake has reached
+    max_length: usize,
+
+    /// The largest percentage of the level that the snake has filled
+    peak_fill: u8,
+
+    /// The number of times the snake has changed direction
+    turns: u32,
+
+    /// The direction in which the snake last moved
+    last_direction: Option<Direction>,
+
+    /// In co-op relay mode, the number of fruits eaten by each player
+    player_fruits: Option<[u32; 2]>,
+}
+
+impl GameStats {
+    /// The width of the text area
+    const TEXT_WIDTH: u16 = 26;
+
+    /// The width of the widget
+    const WIDTH: u16 = Self::TEXT_WIDTH + 4;
+
+    /// Return a new `GameStats` that also tallies the fruits eaten by each of
+    /// the two players in co-op relay mode
+    pub(super) fn for_relay() -> GameStats {
+        GameStats {
+            player_fruits: Some([0, 0]),
+            ..GameStats::default()
+        }
+    }
+
+    /// Record a tick lasting `period` in which the snake moved in `direction`
+    /// and ended up `length` cells long
+    pub(super) fn record_move(&mut self, period: Duration, direction: Direction, length: usize) {
+        self.elapsed = self.elapsed.saturating_add(period);
+        if self.last_direction.is_some_and(|d| d != direction) {
+            self.turns = self.turns.saturating_add(1);
+        }
+        self.last_direction = Some(direction);
+        self.max_length = self.max_length.max(length);
+    }
+
+    /// Record that the snake now fills `percent` percent of the level
+    pub(super) fn record_fill(&mut self, percent: u8) {
+        self.peak_fill = self.peak_fill.max(percent);
+    }
+
+    /// Record that a fruit was eaten
+    pub(super) fn record_fruit(&mut self) {
+        self.fruits_eaten = self.fruits_eaten.saturating_add(1);
+    }
+
+    /// In co-op relay mode, record that a fruit was eaten by the player with
+    /// index `player` (0 or 1).  Outside of relay mode, this does nothing.
+    pub(super) fn record_player_fruit(&mut self, player: usize) {
+        if let Some(n) = self
+            .player_fruits
+            .as_mut()
+            .and_then(|tallies| tallies.get_mut(player))
+        {
+            *n = n.saturating_add(1);
+        }
+    }
+
+    /// Return the total time spent playing
+    pub(super) fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Return the largest percentage of the level that the snake has filled
+    pub(super) fn peak_fill(&self) -> u8 {
+        self.peak_fill
+    }
+
+    /// Return the average time taken to eat each fruit, or `None` if no
+    /// fruits were eaten
+    fn time_per_fruit(&self) -> Option<Duration> {
+        self.elapsed.checked_div(self.fruits_eaten)
+    }
+}
+
+impl Widget for &GameStats {
+    /// Render a `GameStats` panel in the given area of `buf`.
+    ///
+    /// As with [`Warning`][crate::warning::Warning], `area` should be the area
+    /// within which the pop-up will be centered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = [
+            (tr(Msg::TimePlayed), i18n::duration(self.elapsed)),
+            (tr(Msg::FruitsEaten), i18n::number(self.fruits_eaten)),
+            (
+                tr(Msg::MaxLength),
+                i18n::number(u64::try_from(self.max_length).unwrap_or(u64::MAX)),
+            ),
+            (tr(Msg::PeakFill), format!("{}%", self.peak_fill)),
+            (tr(Msg::TurnsMade), i18n::number(self.turns)),
+            (
+                tr(Msg::TimePerFruit),
+                self.time_per_fruit()
+                    .map_or_else(|| String::from("-"), i18n::duration),
+            ),
+        ];
+        let player_rows = self.player_fruits.iter().flat_map(|tallies| {
+            tallies.iter().enumerate().map(|(i, &n)| {
+                (
+                    tr_args(Msg::PlayerFruits, &[("player", &(i + 1))]),
+                    i18n::number(n),
+                )
+            })
+        });
+        let rows = rows
+            .into_iter()
+            .map(|(label, value)| (String::from(label), value))
+            .chain(player_rows)
+            .collect::<Vec<_>>();
+        let [text_area, _] = PopupFrame {
+            title: tr(Msg::StatisticsTitle),
+            width: GameStats::WIDTH,
+            text_height: u16::try_from(rows.len()).unwrap_or(u16::MAX),
+            footer: false,
+        }
+        .render(area, buf);
+        let width = usize::from(GameStats::TEXT_WIDTH);
+        Text::from_iter(rows.into_iter().map(|(label, value)| {
+            format!(
+                "{label:<w$}{value}",
+                w = width.saturating_sub(value.chars().count())
+            )
+        }))
+        .render(text_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record() {
+        let mut stats = GameStats::default();
+        let period = Duration::from_millis(200);
+        stats.record_move(period, Direction::East, 1);
+        stats.record_move(period, Direction::East, 2);
+        stats.record_move(period, Direction::North, 3);
+        stats.record_fruit();
+        stats.record_move(period, Direction::West, 4);
+        stats.record_fill(12);
+        stats.record_move(period, Direction::West, 4);
+        stats.record_fill(10);
+        assert_eq!(stats.elapsed, Duration::from_secs(1));
+        assert_eq!(stats.fruits_eaten, 1);
+        assert_eq!(stats.max_length, 4);
+        assert_eq!(stats.peak_fill, 12);
+        assert_eq!(stats.turns, 2);
+        assert_eq!(stats.time_per_fruit(), Some(Duration::from_secs(1)));
+        assert_eq!(stats.player_fruits, None);
+    }
+
+    #[test]
+    fn record_relay() {
+        let mut stats = GameStats::for_relay();
+        stats.record_fruit();
+        stats.record_player_fruit(0);
+        stats.record_fruit();
+        stats.record_player_fruit(1);
+        stats.record_fruit();
+        stats.record_player_fruit(1);
+        assert_eq!(stats.fruits_eaten, 3);
+        assert_eq!(stats.player_fruits, Some([1, 2]));
+    }
+
+    #[test]
+    fn render() {
+        let stats = GameStats {
+            elapsed: Duration::from_millis(83_400),
+            fruits_eaten: 12,
+            max_length: 37,
+            peak_fill: 23,
+            turns: 58,
+            last_direction: Some(Direction::South),
+            player_fruits: None,
+        };
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        stats.render(area, &mut buffer);
+        let expected = Buffer::with_lines([
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "                         ┌──────── STATISTICS ────────┐                         ",
+            "                         │ Time Played         1:23.4 │                         ",
+            "                         │ Fruits Eaten            12 │                         ",
+            "                         │ Max Length              37 │                         ",
+            "                         │ Peak Fill              23% │                         ",
+            "                         │ Turns Made              58 │                         ",
+            "                         │ Time per Fruit      0:06.9 │                         ",
+            "                         └────────────────────────────┘                         ",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+        ]);
+        pretty_assertions::assert_eq!(buffer, expected);
+    }
+}
@@ -1,13 +1,62 @@
+use crate::consts;
+use crate::migrations::{self, SaveFile};
 use crate::options::Options;
 use crate::util::{data_dir, LoadError, SaveError};
+use jiff::Timestamp;
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
 use std::collections::HashMap;
 use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-/// A collection of the highest score achieved for various `Options` values
+/// A collection of the highest scores achieved for various `Options` values.
+/// For each `Options` value, up to [`consts::HIGH_SCORES_PER_OPTIONS`] scores
+/// are kept, sorted from highest to lowest.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub(crate) struct HighScores(HashMap<Options, NonZeroU32>);
+pub(crate) struct HighScores(HashMap<Options, Vec<HighScore>>);
+
+/// A single recorded high score
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct HighScore {
+    /// The score itself
+    pub(crate) score: NonZeroU32,
+
+    /// True if slow motion was used at any point during the game in which the
+    /// score was achieved
+    pub(crate) slow_motion: bool,
+
+    /// When the score was achieved.  This is `None` for scores recorded by
+    /// older versions of the program.
+    pub(crate) achieved: Option<Timestamp>,
+
+    /// How long the game in which the score was achieved was played for, not
+    /// counting time spent paused, in milliseconds.  This is `None` for scores
+    /// recorded by older versions of the program.
+    pub(crate) duration_ms: Option<u64>,
+
+    /// The name of the player who achieved the score, if they entered one
+    pub(crate) name: Option<String>,
+}
+
+impl HighScore {
+    /// Return the duration of the game in which the score was achieved, if
+    /// known
+    pub(crate) fn duration(&self) -> Option<Duration> {
+        self.duration_ms.map(Duration::from_millis)
+    }
+}
+
+impl From<NonZeroU32> for HighScore {
+    fn from(score: NonZeroU32) -> HighScore {
+        HighScore {
+            score,
+            slow_motion: false,
+            achieved: None,
+            duration_ms: None,
+            name: None,
+        }
+    }
+}
 
 impl HighScores {
     /// The name of the file within the high scores directory in which "arcade"
@@ -25,15 +74,15 @@ impl HighScores {
     ///
     /// # Errors
     ///
-    /// Returns `Err` if creating the file's parent directories failed, if
-    /// serializing the high scores failed, or if writing the serialized high
-    /// scores failed.
+    /// Returns `Err` if the existing file was written in a newer format, if
+    /// creating the file's parent directories failed, if serializing the high
+    /// scores failed, or if writing the serialized high scores failed.
     pub(crate) fn save(&self, path: &Path) -> Result<(), SaveError> {
         if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
             fs_err::create_dir_all(parent).map_err(|e| SaveError::mkdir("high scores", e))?;
         }
-        let mut src =
-            serde_json::to_string(self).map_err(|e| SaveError::serialize("high scores", e))?;
+        migrations::check_overwrite(SaveFile::HighScores, path)?;
+        let mut src = migrations::to_json(SaveFile::HighScores, &HighScoresFile { scores: self })?;
         src.push('\n');
         fs_err::write(path, &src).map_err(|e| SaveError::write("high scores", e))?;
         Ok(())
@@ -48,43 +97,148 @@ impl HighScores {
     /// could not be deserialized.
     pub(crate) fn load(path: &Path) -> Result<HighScores, LoadError> {
         match fs_err::read(path) {
-            Ok(src) => {
-                serde_json::from_slice(&src).map_err(|e| LoadError::deserialize("high scores", e))
-            }
+            Ok(src) => HighScores::from_json(&src),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HighScores::default()),
             Err(e) => Err(LoadError::read("high scores", e)),
         }
     }
 
-    /// Return the high score, if any, for the given [`Options`]
+    /// Deserialize high scores from the contents of a high scores file
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `src` could not be deserialized.
+    pub(crate) fn from_json(src: &[u8]) -> Result<HighScores, LoadError> {
+        migrations::from_json(SaveFile::HighScores, src)
+            .map(|file: HighScoresFile<HighScores>| file.scores)
+    }
+
+    /// Return the highest score, if any, for the given [`Options`]
     pub(crate) fn get(&self, opts: Options) -> Option<NonZeroU32> {
-        self.0.get(&opts).copied()
+        self.0
+            .get(&opts)
+            .and_then(|scores| scores.first())
+            .map(|hs| hs.score)
+    }
+
+    /// Returns `true` if `score` would be kept among the high scores for
+    /// `opts`
+    pub(crate) fn qualifies(&self, opts: Options, score: NonZeroU32) -> bool {
+        self.0.get(&opts).is_none_or(|scores| {
+            scores.len() < consts::HIGH_SCORES_PER_OPTIONS
+                || scores.last().is_some_and(|hs| hs.score < score)
+        })
+    }
+
+    /// Add `score` to the high scores for `opts`, below any equal scores,
+    /// dropping the lowest score if there are now too many.  Returns `false`
+    /// if `score` was too low to be kept.
+    pub(crate) fn insert(&mut self, opts: Options, score: HighScore) -> bool {
+        let scores = self.0.entry(opts).or_default();
+        let i = scores.partition_point(|hs| hs.score >= score.score);
+        if i >= consts::HIGH_SCORES_PER_OPTIONS {
+            if scores.is_empty() {
+                self.0.remove(&opts);
+            }
+            return false;
+        }
+        scores.insert(i, score);
+        scores.truncate(consts::HIGH_SCORES_PER_OPTIONS);
+        true
+    }
+
+    /// Record `name` as the name of the player who achieved the high score
+    /// `score` for `opts`.  Returns `false` if `score` is not among the high
+    /// scores for `opts`.
+    pub(crate) fn set_name(&mut self, opts: Options, score: &HighScore, name: String) -> bool {
+        if let Some(hs) = self
+            .0
+            .get_mut(&opts)
+            .and_then(|scores| scores.iter_mut().find(|hs| *hs == score))
+        {
+            hs.name = Some(name);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove the high score `score` for `opts`.  Returns `false` if `score`
+    /// is not among the high scores for `opts`.
+    pub(crate) fn remove(&mut self, opts: Options, score: &HighScore) -> bool {
+        let Some(scores) = self.0.get_mut(&opts) else {
+            return false;
+        };
+        let Some(i) = scores.iter().position(|hs| hs == score) else {
+            return false;
+        };
+        scores.remove(i);
+        if scores.is_empty() {
+            self.0.remove(&opts);
+        }
+        true
     }
 
-    /// Set the high score for `opts` to `score`.  No attempt is made to verify
-    /// that `score` is higher than the current high score.
-    pub(crate) fn set(&mut self, opts: Options, score: NonZeroU32) {
-        self.0.insert(opts, score);
+    /// Remove all high scores
+    pub(crate) fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Return all recorded high scores, sorted by descending score.  Entries
+    /// with the same score are sorted by their options and then by when they
+    /// were recorded.
+    pub(crate) fn ranked(&self) -> Vec<(Options, HighScore)> {
+        let mut entries = self.entries().collect::<Vec<_>>();
+        // The sort is stable so that equal scores for the same options stay
+        // in the order in which they were recorded
+        entries.sort_by(|(opts1, hs1), (opts2, hs2)| {
+            hs2.score.cmp(&hs1.score).then_with(|| opts1.cmp(opts2))
+        });
+        entries
+    }
+
+    /// Iterate over all recorded high scores and their options, in no
+    /// particular order apart from each option set's scores being in rank
+    /// order
+    fn entries(&self) -> impl Iterator<Item = (Options, HighScore)> + '_ {
+        self.0
+            .iter()
+            .flat_map(|(&opts, scores)| scores.iter().map(move |hs| (opts, hs.clone())))
     }
 
     /// Convert the high scores to a list of `{"options": ..., "score": ...}`
     /// objects that can then be serialized as JSON
     fn to_json_array(&self) -> Vec<HighScoreEntry> {
-        self.0
-            .iter()
-            .map(|(&options, &score)| HighScoreEntry { options, score })
+        self.entries()
+            .map(|(options, hs)| HighScoreEntry {
+                options,
+                score: hs.score,
+                slow_motion: hs.slow_motion,
+                achieved: hs.achieved,
+                duration_ms: hs.duration_ms,
+                name: hs.name,
+            })
             .collect()
     }
 
     /// Convert a list of `{"options": ..., "score": ...}` objects to a
-    /// `HighScores` instance
+    /// `HighScores` instance.  If there are too many scores for any options,
+    /// only the highest are kept.
     fn from_json_array(array: Vec<HighScoreEntry>) -> HighScores {
-        HighScores(
-            array
-                .into_iter()
-                .map(|hse| (hse.options, hse.score))
-                .collect(),
-        )
+        let mut scores = HighScores::default();
+        for hse in array {
+            scores.insert(
+                hse.options,
+                HighScore {
+                    score: hse.score,
+                    slow_motion: hse.slow_motion,
+                    achieved: hse.achieved,
+                    duration_ms: hse.duration_ms,
+                    name: hse.name,
+                },
+            );
+        }
+        scores
     }
 }
 
@@ -100,10 +254,206 @@ impl<'de> Deserialize<'de> for HighScores {
     }
 }
 
+/// The contents of a high scores file, apart from the format version
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct HighScoresFile<S> {
+    scores: S,
+}
+
 /// An intermediate type used for serializing & deserializing `HighScores` as
 /// JSON
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 struct HighScoreEntry {
     options: Options,
     score: NonZeroU32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    slow_motion: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    achieved: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{FruitQty, LevelSize};
+
+    #[test]
+    fn ranked() {
+        let small = Options {
+            level_size: LevelSize::Small,
+            ..Options::default()
+        };
+        let wrap = Options {
+            wraparound: true,
+            ..Options::default()
+        };
+        let fruity = Options {
+            fruits: FruitQty::new(5).unwrap(),
+            ..Options::default()
+        };
+        let mut scores = HighScores::default();
+        scores.insert(Options::default(), score(12));
+        scores.insert(small, score(30));
+        scores.insert(wrap, score(12));
+        scores.insert(fruity, score(7));
+        assert_eq!(
+            scores.ranked(),
+            vec![
+                (small, score(30)),
+                (Options::default(), score(12)),
+                (wrap, score(12)),
+                (fruity, score(7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn top_scores_per_options() {
+        let wrap = Options {
+            wraparound: true,
+            ..Options::default()
+        };
+        let mut scores = HighScores::default();
+        assert!(scores.insert(wrap, score(50)));
+        for n in 1..=10 {
+            assert!(scores.qualifies(Options::default(), NonZeroU32::new(n).unwrap()));
+            assert!(scores.insert(Options::default(), score(n)));
+        }
+        assert_eq!(scores.get(Options::default()), NonZeroU32::new(10));
+        // The table is full, so only scores above the lowest get in
+        assert!(!scores.qualifies(Options::default(), NonZeroU32::new(1).unwrap()));
+        assert!(!scores.insert(Options::default(), score(1)));
+        assert!(scores.qualifies(Options::default(), NonZeroU32::new(2).unwrap()));
+        let named = HighScore {
+            name: Some(String::from("Alice")),
+            ..score(5)
+        };
+        assert!(scores.insert(Options::default(), named.clone()));
+        let ranked = scores
+            .ranked()
+            .into_iter()
+            .map(|(opts, hs)| (opts == wrap, hs.score.get(), hs.name))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            [
+                (true, 50, None),
+                (false, 10, None),
+                (false, 9, None),
+                (false, 8, None),
+                (false, 7, None),
+                (false, 6, None),
+                (false, 5, None),
+                (false, 5, Some(String::from("Alice"))),
+                (false, 4, None),
+                (false, 3, None),
+                (false, 2, None),
+            ]
+        );
+        assert!(scores.remove(Options::default(), &named));
+        assert!(!scores.remove(Options::default(), &named));
+        assert!(scores.remove(wrap, &score(50)));
+        assert_eq!(scores.get(wrap), None);
+        assert_eq!(scores.ranked().len(), 9);
+    }
+
+    #[test]
+    fn load_too_many_scores() {
+        let entries = (1..=12)
+            .map(|n| format!(r#"{{"options": {{}}, "score": {n}}}"#))
+            .collect::<Vec<_>>();
+        let src = format!("[{}]", entries.join(", "));
+        let scores = serde_json::from_str::<HighScores>(&src).unwrap();
+        let ranked = scores
+            .ranked()
+            .into_iter()
+            .map(|(_, hs)| hs.score.get())
+            .collect::<Vec<_>>();
+        assert_eq!(ranked, [12, 11, 10, 9, 8, 7, 6, 5, 4, 3]);
+    }
+
+    #[test]
+    fn slow_motion_roundtrip() {
+        let mut scores = HighScores::default();
+        scores.insert(Options::default(), score(12));
+        scores.insert(
+            Options {
+                wraparound: true,
+                ..Options::default()
+            },
+            HighScore {
+                score: NonZeroU32::new(5).unwrap(),
+                slow_motion: true,
+                achieved: None,
+                duration_ms: None,
+                name: None,
+            },
+        );
+        let src = serde_json::to_string(&scores).unwrap();
+        assert_eq!(src.matches("slow_motion").count(), 1);
+        let scores2 = serde_json::from_str::<HighScores>(&src).unwrap();
+        assert_eq!(scores, scores2);
+    }
+
+    #[test]
+    fn timestamp_roundtrip() {
+        let mut scores = HighScores::default();
+        scores.insert(
+            Options::default(),
+            HighScore {
+                score: NonZeroU32::new(12).unwrap(),
+                slow_motion: false,
+                achieved: Some("2025-07-01T12:34:56Z".parse().unwrap()),
+                duration_ms: Some(83_456),
+                name: None,
+            },
+        );
+        let src = serde_json::to_string(&scores).unwrap();
+        assert!(src.contains(r#""achieved":"2025-07-01T12:34:56Z""#));
+        assert!(src.contains(r#""duration_ms":83456"#));
+        let scores2 = serde_json::from_str::<HighScores>(&src).unwrap();
+        assert_eq!(scores, scores2);
+    }
+
+    #[test]
+    fn name_roundtrip() {
+        let mut scores = HighScores::default();
+        scores.insert(Options::default(), score(12));
+        let wrap = Options {
+            wraparound: true,
+            ..Options::default()
+        };
+        assert!(!scores.set_name(wrap, &score(12), String::from("ZED")));
+        assert!(!scores.set_name(Options::default(), &score(5), String::from("ZED")));
+        assert!(scores.set_name(Options::default(), &score(12), String::from("Alice")));
+        let src = serde_json::to_string(&scores).unwrap();
+        assert!(src.contains(r#""name":"Alice""#));
+        let scores2 = serde_json::from_str::<HighScores>(&src).unwrap();
+        assert_eq!(scores, scores2);
+        assert_eq!(scores2.ranked()[0].1.name.as_deref(), Some("Alice"));
+    }
+
+    #[test]
+    fn load_without_timestamps() {
+        let src = r#"[{"options": {"wraparound": true}, "score": 7}]"#;
+        let scores = serde_json::from_str::<HighScores>(src).unwrap();
+        assert_eq!(
+            scores.ranked(),
+            vec![(
+                Options {
+                    wraparound: true,
+                    ..Options::default()
+                },
+                score(7)
+            )]
+        );
+    }
+
+    fn score(n: u32) -> HighScore {
+        HighScore::from(NonZeroU32::new(n).unwrap())
+    }
 }
@@ -0,0 +1,274 @@
+//! An append-only journal of completed games.
+//!
+//! Each time a game ends, a [`GameRecord`] describing it is appended to the
+//! history file as a single line of JSON.  The journal can be viewed with
+//! `ratsnake --history`.
+use crate::config::RngMode;
+use crate::i18n;
+use crate::migrations::{self, SaveFile};
+use crate::options::{OptKey, OptValue, Options};
+use crate::util::{data_dir, EnumExt, LoadError, SaveError};
+use jiff::{tz::TimeZone, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A record of a single completed game
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct GameRecord {
+    /// When the game ended
+    pub(crate) timestamp: Timestamp,
+
+    /// The options the game was played with
+    pub(crate) options: Options,
+
+    /// The seed for the random-number generator used to generate the level &
+    /// place fruits, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) seed: Option<u64>,
+
+    /// The `rng` setting in effect when the game was started, if recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rng: Option<RngMode>,
+
+    /// The final score
+    pub(crate) score: u32,
+
+    /// How long the game was played for, not counting time spent paused, in
+    /// milliseconds
+    pub(crate) duration_ms: u64,
+
+    /// How the game ended
+    pub(crate) cause: EndCause,
+
+    /// The largest percentage of the level that the snake filled during the
+    /// game, if recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) peak_fill: Option<u8>,
+}
+
+impl GameRecord {
+    /// Return the default filepath used for storing the game history
+    pub(crate) fn default_path() -> Option<PathBuf> {
+        data_dir().map(|p| p.join("history.jsonl"))
+    }
+
+    /// Return the game's duration
+    pub(crate) fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+
+    /// Append the record to the history file at `path` as a line of JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if creating the file's parent directories failed, if
+    /// serializing the record failed, or if opening or writing to the file
+    /// failed.
+    pub(crate) fn append(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs_err::create_dir_all(parent).map_err(|e| SaveError::mkdir("game history", e))?;
+        }
+        let mut line = migrations::to_json(SaveFile::History, self)?;
+        line.push('\n');
+        fs_err::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut fp| fp.write_all(line.as_bytes()))
+            .map_err(|e| SaveError::write("game history", e))?;
+        Ok(())
+    }
+
+    /// Read all records from the history file at `path`, oldest first.  If
+    /// the file does not exist, an empty `Vec` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file could not be read or if any line of it could
+    /// not be deserialized.
+    pub(crate) fn load_all(path: &Path) -> Result<Vec<GameRecord>, LoadError> {
+        match fs_err::read_to_string(path) {
+            Ok(src) => src
+                .lines()
+                .filter(|ln| !ln.trim().is_empty())
+                .map(|ln| migrations::from_json(SaveFile::History, ln.as_bytes()))
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(LoadError::read("game history", e)),
+        }
+    }
+}
+
+/// An enum of the ways in which a game can end
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum EndCause {
+    /// The snake ran into the level border
+    Wall,
+
+    /// The snake ran into its own body
+    Body,
+
+    /// The snake ran into an obstacle or maze wall
+    Obstacle,
+
+    /// The bot steering the snake failed to make a move
+    Forfeit,
+
+    /// The snake filled the level
+    BoardFull,
+
+    /// The snake filled every cell of the level that it could reach
+    Perfect,
+
+    /// The snake touched a cell that a custom level's objectives forbid
+    Forbidden,
+
+    /// The time limit of a custom level's objectives ran out before they
+    /// were met
+    TimeUp,
+
+    /// The objectives of a custom level were met
+    Objective,
+
+    /// The program exited without cleaning up (e.g., because it crashed or
+    /// the terminal was closed) while the game was in progress
+    Interrupted,
+}
+
+impl fmt::Display for EndCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EndCause::Wall => "wall",
+            EndCause::Body => "body",
+            EndCause::Obstacle => "obstacle",
+            EndCause::Forfeit => "forfeit",
+            EndCause::BoardFull => "board full",
+            EndCause::Perfect => "perfect",
+            EndCause::Forbidden => "forbidden",
+            EndCause::TimeUp => "time up",
+            EndCause::Objective => "objective",
+            EndCause::Interrupted => "interrupted",
+        };
+        f.pad(name)
+    }
+}
+
+/// Write a table of the given records to `out`, one per line
+pub(crate) fn print_table<W: Write>(mut out: W, records: &[GameRecord]) -> io::Result<()> {
+    if records.is_empty() {
+        return writeln!(out, "No games recorded yet");
+    }
+    let tz = TimeZone::system();
+    writeln!(
+        out,
+        "{:16}  {:>5}  {:>7}  {:10}  Options",
+        "Date", "Score", "Time", "Ended"
+    )?;
+    for rec in records {
+        writeln!(
+            out,
+            "{:16}  {:>5}  {:>7}  {:10}  {}",
+            rec.timestamp
+                .to_zoned(tz.clone())
+                .strftime("%Y-%m-%d %H:%M")
+                .to_string(),
+            rec.score,
+            i18n::duration(rec.duration()),
+            rec.cause,
+            describe_options(rec.options),
+        )?;
+    }
+    Ok(())
+}
+
+/// Return a short description of the options that differ from the defaults,
+/// e.g., "Wraparound, Fruits 3, Level Size Small"
+fn describe_options(options: Options) -> String {
+    let default = Options::default();
+    let changed = OptKey::iter()
+        .filter(|&key| options.get(key) != default.get(key))
+        .map(|key| match options.get(key) {
+            OptValue::Bool(_) => key.as_str().to_owned(),
+            OptValue::FruitQty(frq) => format!("{} {frq}", key.as_str()),
+            OptValue::LevelSize(sz) => format!("{} {sz}", key.as_str()),
+            OptValue::LevelLayout(lay) => format!("{} {lay}", key.as_str()),
+            OptValue::SpawnStrategy(spawn) => format!("{} {spawn}", key.as_str()),
+            OptValue::StartPosition(start) => format!("{} {start}", key.as_str()),
+        })
+        .collect::<Vec<_>>();
+    if changed.is_empty() {
+        String::from("default")
+    } else {
+        changed.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{FruitQty, LevelSize};
+
+    fn record(score: u32, cause: EndCause) -> GameRecord {
+        GameRecord {
+            timestamp: "2025-07-01T12:34:56Z".parse().unwrap(),
+            options: Options::default(),
+            seed: Some(0x0123456789ABCDEF),
+            rng: Some(RngMode::Thread),
+            score,
+            duration_ms: 83_456,
+            cause,
+            peak_fill: Some(12),
+        }
+    }
+
+    #[test]
+    fn append_and_load() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let path = tmpdir.path().join("sub").join("history.jsonl");
+        assert_eq!(GameRecord::load_all(&path).unwrap(), []);
+        let first = record(42, EndCause::Body);
+        let second = GameRecord {
+            seed: None,
+            rng: None,
+            peak_fill: None,
+            ..record(7, EndCause::BoardFull)
+        };
+        first.append(&path).unwrap();
+        second.append(&path).unwrap();
+        let src = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(src.lines().count(), 2);
+        assert!(src.lines().next().unwrap().contains(r#""cause":"body""#));
+        assert!(src.lines().next().unwrap().contains(r#""peak_fill":12"#));
+        assert!(!src.lines().nth(1).unwrap().contains("peak_fill"));
+        assert_eq!(GameRecord::load_all(&path).unwrap(), [first, second]);
+    }
+
+    #[test]
+    fn table() {
+        let records = [
+            record(42, EndCause::Wall),
+            GameRecord {
+                options: Options {
+                    wraparound: true,
+                    fruits: FruitQty::new(3).unwrap(),
+                    level_size: LevelSize::Small,
+                    ..Options::default()
+                },
+                ..record(1234, EndCause::Forfeit)
+            },
+        ];
+        let mut out = Vec::new();
+        print_table(&mut out, &records).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Date"));
+        assert!(lines[1].ends_with("   42   1:23.4  wall        default"));
+        assert!(lines[2]
+            .ends_with(" 1234   1:23.4  forfeit     Wraparound, Fruits 3, Level Size Small"));
+    }
+}
@@ -0,0 +1,574 @@
+//! Translating the text shown by the interface.
+//!
+//! Every piece of user-facing text is identified by a [`Msg`] and looked up in
+//! the active [`Catalog`].  English text is built in; catalogs for other
+//! languages are loaded from TOML files named `<lang>.toml` in the `lang`
+//! directory of the local data directory, selected via the `lang` setting in
+//! the configuration file.  Messages missing from a catalog fall back to
+//! English.
+//!
+//! The active catalog is chosen once at startup and is thereafter global to
+//! the process, as it is needed by nearly every widget.
+use crate::options::OptKey;
+use crate::util::{data_dir, EnumExt};
+use crate::widgets::SettingKey;
+use enum_map::{Enum, EnumMap};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use thiserror::Error;
+use unicode_width::UnicodeWidthStr;
+
+/// The catalog selected at startup
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Make `catalog` the catalog used for all text for the rest of the program's
+/// run.  If a catalog has already been set or used, this does nothing.
+pub(crate) fn init(catalog: Catalog) {
+    let _ = CATALOG.set(catalog);
+}
+
+/// Return the active catalog, defaulting to English if none was set
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(Catalog::default)
+}
+
+/// Return the text for `msg` in the active catalog
+pub(crate) fn tr(msg: Msg) -> &'static str {
+    catalog().get(msg)
+}
+
+/// Return the text for `msg` in the active catalog with each `{name}`
+/// placeholder replaced by the corresponding value in `args`
+pub(crate) fn tr_args(msg: Msg, args: &[(&str, &dyn fmt::Display)]) -> String {
+    fill(tr(msg), args)
+}
+
+/// The tips shown on the main menu, in order, before any extra tips from the
+/// active catalog
+const TIPS: [Msg; 4] = [
+    Msg::TipObstacles,
+    Msg::TipPause,
+    Msg::TipPalette,
+    Msg::TipFavorites,
+];
+
+/// Return the tips that the main menu cycles through: the built-in tips
+/// followed by any extra tips in the active catalog
+pub(crate) fn tips() -> Vec<&'static str> {
+    let catalog = catalog();
+    TIPS.iter()
+        .map(|&msg| catalog.get(msg))
+        .chain(catalog.tips.iter().map(String::as_str))
+        .collect()
+}
+
+/// Format `n` with the digit grouping of the active catalog
+pub(crate) fn number<N: Into<u64>>(n: N) -> String {
+    catalog().number(n.into())
+}
+
+/// Format a duration as minutes, seconds, and tenths of a second with the
+/// decimal separator of the active catalog
+pub(crate) fn duration(d: Duration) -> String {
+    catalog().duration(d)
+}
+
+/// Return the decimal separator of the active catalog
+pub(crate) fn decimal_separator() -> &'static str {
+    &catalog().decimal_separator
+}
+
+/// Replace each `{name}` placeholder in `template` with the corresponding
+/// value in `args`
+fn fill(template: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut s = template.to_owned();
+    for (name, value) in args {
+        s = s.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    s
+}
+
+/// A set of translations of the interface's text into one language
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Catalog {
+    /// The translated messages.  Messages that are `None` are shown in
+    /// English.
+    messages: EnumMap<Msg, Option<String>>,
+
+    /// Extra tips to show on the main menu after the built-in ones
+    tips: Vec<String>,
+
+    /// The string inserted between groups of three digits in large numbers
+    thousands_separator: String,
+
+    /// The string separating the whole & fractional parts of numbers
+    decimal_separator: String,
+}
+
+impl Catalog {
+    /// The name of the built-in language
+    pub(crate) const ENGLISH: &'static str = "en";
+
+    /// Return the path to the language file for `lang`
+    fn path(lang: &str) -> Option<PathBuf> {
+        data_dir().map(|p| p.join("lang").join(format!("{lang}.toml")))
+    }
+
+    /// Load the catalog for the language `lang` from the data directory.  If
+    /// `lang` is [`ENGLISH`][Catalog::ENGLISH], the built-in English catalog
+    /// is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the local data directory could not be determined, if
+    /// the language file could not be read, or if its contents were invalid.
+    pub(crate) fn load(lang: &str) -> Result<Catalog, CatalogError> {
+        if lang == Catalog::ENGLISH {
+            return Ok(Catalog::default());
+        }
+        let path = Catalog::path(lang).ok_or(CatalogError::NoPath)?;
+        Catalog::from_file(&path)
+    }
+
+    /// Load a catalog from the TOML file at `path`
+    fn from_file(path: &Path) -> Result<Catalog, CatalogError> {
+        let src = fs_err::read_to_string(path).map_err(CatalogError::Read)?;
+        Catalog::from_toml(&src)
+    }
+
+    /// Parse a catalog from TOML source.  The names of options are shown in a
+    /// column of fixed width in the options menu, so translations of them that
+    /// don't fit are rejected.
+    fn from_toml(src: &str) -> Result<Catalog, CatalogError> {
+        let raw = toml::from_str::<RawCatalog>(src)?;
+        let mut catalog = Catalog::default();
+        for (key, text) in raw.messages {
+            let msg = Msg::iter()
+                .find(|m| m.key() == key)
+                .ok_or(CatalogError::UnknownMessage(key))?;
+            catalog.messages[msg] = Some(text);
+        }
+        for key in OptKey::iter() {
+            let msg = key.msg();
+            if catalog.get(msg).width() > usize::from(OptKey::DISPLAY_WIDTH) {
+                return Err(CatalogError::TooWide(
+                    msg.key().to_owned(),
+                    OptKey::DISPLAY_WIDTH,
+                ));
+            }
+        }
+        catalog.tips = raw.tips;
+        if let Some(sep) = raw.thousands_separator {
+            catalog.thousands_separator = sep;
+        }
+        if let Some(sep) = raw.decimal_separator {
+            catalog.decimal_separator = sep;
+        }
+        Ok(catalog)
+    }
+
+    /// Return the text for `msg`
+    fn get(&self, msg: Msg) -> &str {
+        self.messages[msg]
+            .as_deref()
+            .unwrap_or_else(|| msg.english())
+    }
+
+    /// Format `n` with [`thousands_separator`][Catalog::thousands_separator]
+    /// between each group of three digits
+    fn number(&self, n: u64) -> String {
+        let digits = n.to_string();
+        let mut s = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                s.push_str(&self.thousands_separator);
+            }
+            let _ = s.write_char(c);
+        }
+        s
+    }
+
+    /// Format `d` as minutes, seconds, and tenths of a second, with
+    /// [`decimal_separator`][Catalog::decimal_separator] before the tenths
+    fn duration(&self, d: Duration) -> String {
+        let secs = d.as_secs();
+        format!(
+            "{}:{:02}{}{}",
+            secs / 60,
+            secs % 60,
+            self.decimal_separator,
+            d.subsec_millis() / 100
+        )
+    }
+}
+
+impl Default for Catalog {
+    /// Return the built-in English catalog
+    fn default() -> Catalog {
+        Catalog {
+            messages: EnumMap::default(),
+            tips: Vec::new(),
+            thousands_separator: String::from(","),
+            decimal_separator: String::from("."),
+        }
+    }
+}
+
+/// The contents of a language file
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct RawCatalog {
+    thousands_separator: Option<String>,
+    decimal_separator: Option<String>,
+    #[serde(default)]
+    tips: Vec<String>,
+    #[serde(default)]
+    messages: HashMap<String, String>,
+}
+
+/// Error returned by [`Catalog::load()`]
+#[derive(Debug, Error)]
+pub(crate) enum CatalogError {
+    #[error("failed to determine path to local data directory")]
+    NoPath,
+    #[error("failed to read language file")]
+    Read(#[source] std::io::Error),
+    #[error("failed to parse language file")]
+    Parse(#[from] toml::de::Error),
+    #[error("unknown message key in language file: {0:?}")]
+    UnknownMessage(String),
+    #[error("message {0:?} in language file is wider than {1} columns")]
+    TooWide(String, u16),
+}
+
+/// Define the [`Msg`] enum from a list of variant names, language file keys,
+/// and English text
+macro_rules! messages {
+    ($($name:ident = $key:literal => $english:literal,)*) => {
+        /// An enum of the pieces of text shown by the interface.
+        ///
+        /// Text containing placeholders of the form `{name}` should be
+        /// formatted with [`tr_args()`].
+        #[derive(Clone, Copy, Debug, Enum, Eq, Hash, PartialEq)]
+        pub(crate) enum Msg {
+            $($name,)*
+        }
+
+        impl Msg {
+            /// Return the key for the message in language files
+            pub(crate) fn key(self) -> &'static str {
+                match self {
+                    $(Msg::$name => $key,)*
+                }
+            }
+
+            /// Return the built-in English text for the message
+            pub(crate) fn english(self) -> &'static str {
+                match self {
+                    $(Msg::$name => $english,)*
+                }
+            }
+        }
+    };
+}
+
+messages! {
+    // Shared
+    Yes = "yes" => "Yes",
+    No = "no" => "No",
+    Ok = "ok" => "OK",
+    Quit = "quit" => "Quit",
+    Restart = "restart" => "Restart",
+    MainMenu = "main-menu" => "Main Menu",
+    ConfirmTitle = "confirm-title" => "CONFIRM",
+    WarningTitle = "warning-title" => "WARNING",
+    CausedBy = "caused-by" => "Caused by:",
+    ErrorLogSaved = "error-log-saved" => "Saved error details to {path}",
+    SaveLog = "save-log" => "Save Log",
+    Ignore = "ignore" => "Ignore",
+    ResetFile = "reset-file" => "Reset file",
+
+    // Main menu
+    Play = "play" => "Play",
+    HighScores = "high-scores" => "High Scores",
+    MoveTheSnake = "move-the-snake" => "Move the snake with:",
+    Or = "or" => "or:",
+    EatTheFruit = "eat-the-fruit" => "Eat the fruit, but",
+    DontHitYourself = "dont-hit-yourself" => "don't hit yourself!",
+    ConfirmSaveOptions = "confirm-save-options" => "Save your changes to the options before quitting?",
+    UseConfigDefaults = "use-config-defaults" => "Use config defaults",
+    SaveOptions = "save-options" => "Save",
+    HintWall = "hint-wall" => "Tip: turn before you reach the border, or try Wraparound",
+    HintBody = "hint-body" => "Tip: you can't reverse direction instantly",
+    HintObstacle = "hint-obstacle" => "Tip: plan your turns a few cells ahead of obstacles",
+    IgnoredErrors = "ignored-errors" => "⚠ Errors ignored while loading data files: {n}",
+    Details = "details" => "Details",
+    TipObstacles = "tip-obstacles" => "Tip: obstacles never spawn in front of you at the start",
+    TipPause = "tip-pause" => "Tip: press Esc to pause",
+    TipPalette = "tip-palette" => "Tip: press Ctrl+P to run any action by name",
+    TipFavorites = "tip-favorites" => "Tip: press f to replay your favorite boards",
+    FavoriteBoards = "favorite-boards" => "Favorite Boards",
+    FavoritesTitle = "favorites-title" => "FAVORITE BOARDS",
+    NoFavorites = "no-favorites" => "No favorite boards yet",
+    CustomLevels = "custom-levels" => "Custom Levels",
+    LevelsTitle = "levels-title" => "CUSTOM LEVELS",
+    NoLevelPacks = "no-level-packs" => "No level packs found",
+    PackAuthor = "pack-author" => "by {author}",
+    ExpandCollapse = "expand-collapse" => "Expand/Collapse",
+    Close = "close" => "Close",
+
+    // Options
+    OptionsTitle = "options-title" => "Options:",
+    Wraparound = "option-wraparound" => "Wraparound",
+    Obstacles = "option-obstacles" => "Obstacles",
+    Fruits = "option-fruits" => "Fruits",
+    LevelSize = "option-level-size" => "Level Size",
+    Layout = "option-layout" => "Layout",
+    Combos = "option-combos" => "Combos",
+    Spawn = "option-spawn" => "Spawn",
+    Strict = "option-strict" => "Strict",
+    PowerUps = "option-power-ups" => "Power-ups",
+    Hydra = "option-hydra" => "Hydra",
+    Start = "option-start" => "Start",
+    Adaptive = "option-adaptive" => "Adaptive",
+    Terrain = "option-terrain" => "Terrain",
+    Small = "size-small" => "Small",
+    Medium = "size-medium" => "Medium",
+    Large = "size-large" => "Large",
+    Custom = "size-custom" => "Custom",
+    Open = "layout-open" => "Open",
+    Maze = "layout-maze" => "Maze",
+    Random = "spawn-random" => "Random",
+    FarFromSnake = "spawn-far-from-snake" => "Far",
+    NearWalls = "spawn-near-walls" => "Walls",
+    Spread = "spawn-spread" => "Spread",
+    StartCenter = "start-center" => "Center",
+    StartCorner = "start-corner" => "Corner",
+    StartRandom = "start-random" => "Random",
+
+    // Game
+    Score = "score" => "Score: {score}",
+    ScoreWithCombo = "score-with-combo" => "Score: {score} (×{combo})",
+    HighScore = "high-score" => "High Score: {score}",
+    Pace = "pace" => "Pace {rate}/{target} per min",
+    AdaptiveLevel = "adaptive-level" => "Lv {level}",
+    Position = "position" => "@ {x},{y}",
+    Fill = "fill" => "Fill: {percent}%",
+    Arcade = "arcade" => "Arcade",
+    ArcadeWithSeed = "arcade-with-seed" => "Arcade #{seed}",
+    Goal = "goal" => "Goal: {score}/{target}",
+    TimeLeft = "time-left" => "Time: {secs}s",
+    GeneratingLevel = "generating-level" => "Generating level…",
+    SlowMotion = "slow-motion" => "Slow Motion",
+    StepMode = "step-mode" => "Step Mode",
+    Plow = "power-up-plow" => "Plow",
+    SpeedUp = "power-up-speed-up" => "Fast",
+    SlowDown = "power-up-slow-down" => "Slow",
+    Star = "power-up-star" => "Star",
+    GameOver = "game-over" => "GAME OVER",
+    NewHighScore = "new-high-score" => "NEW HIGH SCORE!",
+    PerfectGame = "perfect-game" => "PERFECT GAME!",
+    ObjectivesComplete = "objectives-complete" => "OBJECTIVES COMPLETE!",
+    ObjectivesFailed = "objectives-failed" => "OBJECTIVES FAILED",
+    TimeUp = "time-up" => "TIME'S UP!",
+    ResumingIn = "resuming-in" => "Resuming in {secs}…",
+    RelayPlayer = "relay-player" => "Player {player}",
+    HandoffIn = "handoff-in" => "Player {player} takes over in {secs}…",
+    YourTurn = "your-turn" => "Player {player}, your turn!",
+    YouWin = "you-win" => "YOU WIN!",
+    PlayerScore = "player-score" => "Player {player}: {score}",
+    FirstTo = "first-to" => "First to {points}",
+    PlayerWins = "player-wins" => "PLAYER {player} WINS!",
+    Tie = "tie" => "IT'S A TIE!",
+    NamePromptTitle = "name-prompt-title" => "NEW HIGH SCORE",
+    EnterName = "enter-name" => "Enter your name:",
+    Save = "save" => "Save",
+    Skip = "skip" => "Skip",
+    Favorite = "favorite" => "Favorite",
+    FavoritePromptTitle = "favorite-prompt-title" => "FAVORITE BOARD",
+    NameThisBoard = "name-this-board" => "Name this board:",
+    FavoriteSaved = "favorite-saved" => "Saved board as \"{name}\"",
+    ChooseOne = "choose-one" => "Choose One:",
+    AnyKeyMainMenu = "any-key-main-menu" => "Press any key to return to the main menu",
+    Rewind = "rewind" => "Rewind",
+    PausedTitle = "paused-title" => "PAUSED",
+    Resume = "resume" => "Resume",
+    Pause = "pause" => "Pause",
+    ConfirmRestart = "confirm-restart" => "Abandon this game and start a new one?",
+    ScreenshotSaved = "screenshot-saved" => "Saved screenshot to {path}",
+    HighScoreToBeat = "high-score-to-beat" => "High score to beat: {score}",
+    NoHighScoreYet = "no-high-score-yet" => "New category: no high score yet",
+    NewRecordPace = "new-record-pace" => "New record pace!",
+    StatisticsTitle = "statistics-title" => "STATISTICS",
+    TimePlayed = "time-played" => "Time Played",
+    FruitsEaten = "fruits-eaten" => "Fruits Eaten",
+    PlayerFruits = "player-fruits" => "Player {player} Fruits",
+    MaxLength = "max-length" => "Max Length",
+    PeakFill = "peak-fill" => "Peak Fill",
+    TurnsMade = "turns-made" => "Turns Made",
+    TimePerFruit = "time-per-fruit" => "Time per Fruit",
+
+    // High scores screen
+    HighScoresTitle = "high-scores-title" => "HIGH SCORES",
+    ExcludingSlowMotion = "excluding-slow-motion" => "(excluding slow motion)",
+    Sort = "sort" => "Sort:",
+    SortScore = "sort-score" => "Score",
+    SortDate = "sort-date" => "Date",
+    FilterColumn = "filter-column" => "Filter Column",
+    CycleFilter = "cycle-filter" => "Cycle Filter",
+    Delete = "delete" => "Delete",
+    ClearAll = "clear-all" => "Clear All",
+    FilterSlow = "filter-slow" => "Filter Slow",
+    ColumnWrap = "column-wraparound" => "Wrap",
+    ColumnObstacles = "column-obstacles" => "Obst",
+    ColumnFruits = "column-fruits" => "Frt",
+    ColumnSize = "column-level-size" => "Size",
+    ColumnLayout = "column-layout" => "Map",
+    ColumnStart = "column-start" => "Start",
+    ColumnFlags = "column-flags" => "Flags",
+    ColumnStartCenter = "column-start-center" => "Cntr",
+    ColumnStartCorner = "column-start-corner" => "Crnr",
+    ColumnStartRandom = "column-start-random" => "Rand",
+    ColumnScore = "column-score" => "Score",
+    ColumnSlow = "column-slow-motion" => "Slow",
+    ColumnAge = "column-age" => "Age",
+    ColumnName = "column-name" => "Name",
+    Any = "any" => "any",
+    NoMatchingScores = "no-matching-scores" => "No high scores match the current filters.",
+    NoFastScores = "no-fast-scores" => "No high scores without slow motion have been recorded yet.",
+    NoScores = "no-scores" => "No high scores have been recorded yet.",
+    JustNow = "just-now" => "just now",
+    MinutesAgo = "minutes-ago" => "{n}m ago",
+    HoursAgo = "hours-ago" => "{n}h ago",
+    DaysAgo = "days-ago" => "{n}d ago",
+    MonthsAgo = "months-ago" => "{n}mo ago",
+    YearsAgo = "years-ago" => "{n}y ago",
+    ConfirmDeleteScore = "confirm-delete-score" => "Delete the high score for the selected options?",
+    ConfirmDeleteAll = "confirm-delete-all" => "Delete all high scores?",
+    Progress = "progress" => "Progress",
+    Race = "race" => "Race",
+
+    // Progress screen
+    ProgressTitle = "progress-title" => "PERSONAL BESTS",
+    GroupBy = "group-by" => "Group by:",
+    PeriodWeek = "period-week" => "Week",
+    PeriodMonth = "period-month" => "Month",
+    ColumnPeriod = "column-period" => "Period",
+    ColumnGames = "column-games" => "Games",
+    ColumnBest = "column-best" => "Best",
+    ColumnAverage = "column-average" => "Average",
+    ColumnTrend = "column-trend" => "Trend",
+    ColumnPlayed = "column-played" => "Played",
+    NoHistory = "no-history" => "No games have been recorded yet.",
+
+    // About screen
+    About = "about" => "About",
+    AboutTitle = "about-title" => "ABOUT",
+    Version = "version" => "Version:",
+    ConfigFile = "config-file" => "Configuration file:",
+    OptionsFile = "options-file" => "Options file:",
+    HighScoresFile = "high-scores-file" => "High scores file:",
+    HistoryFile = "history-file" => "Game history file:",
+    SessionFile = "session-file" => "Session file:",
+    FavoritesFile = "favorites-file" => "Favorite boards file:",
+    ScreenshotsDir = "screenshots-dir" => "Screenshots directory:",
+    DataDir = "data-dir" => "Data directory:",
+    IgnoreErrors = "ignore-errors" => "Ignore file errors:",
+    PathNone = "path-none" => "(none)",
+    PathUnknown = "path-unknown" => "(unknown: no home directory)",
+    PathMissing = "path-missing" => "(does not exist yet)",
+
+    // Command palette
+    PaletteTitle = "palette-title" => "COMMANDS",
+    ToggleOption = "toggle-option" => "Toggle {option}",
+    SetSeed = "set-seed" => "Set seed…",
+    AddFavorite = "add-favorite" => "Add to Favorites",
+    EnterSeed = "enter-seed" => "Enter a seed for new games",
+    InvalidSeed = "invalid-seed" => "The seed must be a whole number",
+    NoMatchingCommands = "no-matching-commands" => "No matching commands",
+
+    // Diagnostics overlay
+    DiagnosticsTitle = "diagnostics-title" => "DIAGNOSTICS",
+    Jitter = "jitter" => "jitter",
+    Draw = "draw" => "draw",
+    Input = "input" => "input",
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "0")]
+    #[case(999, "999")]
+    #[case(1000, "1,000")]
+    #[case(123_456, "123,456")]
+    #[case(1_234_567, "1,234,567")]
+    fn english_number(#[case] n: u64, #[case] s: &str) {
+        assert_eq!(Catalog::default().number(n), s);
+    }
+
+    #[rstest]
+    #[case(Duration::ZERO, "0:00.0")]
+    #[case(Duration::from_millis(6950), "0:06.9")]
+    #[case(Duration::from_secs(60), "1:00.0")]
+    #[case(Duration::from_secs(3725), "62:05.0")]
+    fn english_duration(#[case] d: Duration, #[case] s: &str) {
+        assert_eq!(Catalog::default().duration(d), s);
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        let mut keys = Msg::iter().map(Msg::key).collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), Msg::LENGTH);
+    }
+
+    #[test]
+    fn from_toml() {
+        let catalog = Catalog::from_toml(concat!(
+            "thousands-separator = \".\"\n",
+            "decimal-separator = \",\"\n",
+            "tips = [\"Astuce : mangez les fruits\"]\n",
+            "\n",
+            "[messages]\n",
+            "game-over = \"PARTIE TERMINÉE\"\n",
+            "score = \"Points : {score}\"\n",
+        ))
+        .unwrap();
+        assert_eq!(catalog.get(Msg::GameOver), "PARTIE TERMINÉE");
+        assert_eq!(catalog.get(Msg::Quit), "Quit");
+        assert_eq!(
+            fill(
+                catalog.get(Msg::Score),
+                &[("score", &catalog.number(12345))]
+            ),
+            "Points : 12.345"
+        );
+        assert_eq!(catalog.decimal_separator, ",");
+        assert_eq!(catalog.tips, ["Astuce : mangez les fruits"]);
+    }
+
+    #[test]
+    fn unknown_message() {
+        let r = Catalog::from_toml("[messages]\ngame-overr = \"Oops\"\n");
+        assert!(matches!(r, Err(CatalogError::UnknownMessage(key)) if key == "game-overr"));
+    }
+
+    #[test]
+    fn option_name_too_wide() {
+        let r = Catalog::from_toml("[messages]\noption-fruits = \"Nombre de fruits\"\n");
+        assert!(matches!(r, Err(CatalogError::TooWide(key, 10)) if key == "option-fruits"));
+        // Widths are measured in terminal columns, not characters
+        let r = Catalog::from_toml("[messages]\noption-fruits = \"果物果物果物\"\n");
+        assert!(matches!(r, Err(CatalogError::TooWide(key, 10)) if key == "option-fruits"));
+        let catalog = Catalog::from_toml("[messages]\noption-fruits = \"Frutas\"\n").unwrap();
+        assert_eq!(catalog.get(Msg::Fruits), "Frutas");
+    }
+}
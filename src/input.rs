@@ -0,0 +1,249 @@
+//! Reading input events from the terminal and (if the `gamepad` feature is
+//! enabled) from any connected gamepads.
+//!
+//! Gamepad button presses are translated into the key events for the
+//! [`Command`][crate::command::Command]s that they are mapped to, so screens
+//! only ever need to deal with crossterm [`Event`]s.
+use crossterm::event::Event;
+use std::time::{Duration, Instant};
+
+/// Start listening for input from gamepads, mapping buttons to commands as
+/// specified in `config`.  If the `gamepad` feature is not enabled, this does
+/// nothing.
+///
+/// If gamepad support cannot be initialized, the program carries on with
+/// only terminal input.
+#[cfg_attr(not(feature = "gamepad"), allow(unused_variables))]
+pub(crate) fn init(config: &crate::config::GamepadConfig) {
+    #[cfg(feature = "gamepad")]
+    gamepad::init(config);
+}
+
+/// A source of input events.
+///
+/// The application reads all of its input through an `EventSource` so that
+/// tests can drive it with a scripted sequence of events instead of a real
+/// terminal.
+pub(crate) trait EventSource: std::fmt::Debug {
+    /// Wait up to `timeout` for an input event to become available.  Returns
+    /// `true` if an event is available.
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<bool>;
+
+    /// Block until an input event is available and return it
+    fn read(&mut self) -> std::io::Result<Event>;
+}
+
+/// An [`EventSource`] that reads events from the terminal and (if the
+/// `gamepad` feature is enabled) from any connected gamepads
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<bool> {
+        #[cfg(feature = "gamepad")]
+        {
+            gamepad::poll(timeout)
+        }
+        #[cfg(not(feature = "gamepad"))]
+        {
+            crossterm::event::poll(timeout)
+        }
+    }
+
+    fn read(&mut self) -> std::io::Result<Event> {
+        #[cfg(feature = "gamepad")]
+        let event = gamepad::read()?;
+        #[cfg(not(feature = "gamepad"))]
+        let event = crossterm::event::read()?;
+        Ok(event)
+    }
+}
+
+/// An [`EventSource`] that returns a fixed sequence of events, for use in
+/// tests.
+///
+/// An event is always available until the sequence is exhausted, after which
+/// [`poll()`][EventSource::poll] returns `false` (so that the snake advances
+/// in timer-driven games) and [`read()`][EventSource::read] returns an
+/// [`UnexpectedEof`][std::io::ErrorKind::UnexpectedEof] error.
+#[cfg(test)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct ScriptedEvents(std::collections::VecDeque<Event>);
+
+#[cfg(test)]
+impl ScriptedEvents {
+    /// Create a `ScriptedEvents` that returns the given events in order
+    pub(crate) fn new<I: IntoIterator<Item = Event>>(events: I) -> ScriptedEvents {
+        ScriptedEvents(events.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+impl EventSource for ScriptedEvents {
+    fn poll(&mut self, _timeout: Duration) -> std::io::Result<bool> {
+        Ok(!self.0.is_empty())
+    }
+
+    fn read(&mut self) -> std::io::Result<Event> {
+        match self.0.pop_front() {
+            Some(event) => Ok(event),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "scripted events exhausted",
+            )),
+        }
+    }
+}
+
+/// An [`EventSource`] that passes through the events from an inner source
+/// while keeping hold of the one most recently read.  This lets the
+/// application as a whole respond to & time events that are read by
+/// individual screens.
+#[derive(Debug)]
+pub(crate) struct TrackedEvents {
+    /// The source of the events
+    inner: Box<dyn EventSource>,
+
+    /// The event most recently returned by [`read()`][EventSource::read] and
+    /// the time at which it was returned, if it has not yet been taken
+    last_read: Option<(Instant, Event)>,
+}
+
+impl TrackedEvents {
+    /// Track the events read from `inner`
+    pub(crate) fn new<E: EventSource + 'static>(inner: E) -> TrackedEvents {
+        TrackedEvents {
+            inner: Box::new(inner),
+            last_read: None,
+        }
+    }
+
+    /// Return the event most recently read along with the time at which it
+    /// was read, or `None` if no event has been read since the last call to
+    /// this method
+    pub(crate) fn take_last_read(&mut self) -> Option<(Instant, Event)> {
+        self.last_read.take()
+    }
+}
+
+impl EventSource for TrackedEvents {
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<bool> {
+        self.inner.poll(timeout)
+    }
+
+    fn read(&mut self) -> std::io::Result<Event> {
+        let event = self.inner.read()?;
+        self.last_read = Some((Instant::now(), event));
+        Ok(event)
+    }
+}
+
+#[cfg(feature = "gamepad")]
+mod gamepad {
+    use crate::command::Command;
+    use crate::config::{GamepadButton, GamepadConfig};
+    use crossterm::event::Event;
+    use std::cell::RefCell;
+    use std::collections::{HashMap, VecDeque};
+    use std::time::{Duration, Instant};
+
+    /// How long to wait for terminal input before checking for gamepad input
+    /// again
+    const GAMEPAD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// The gamepad input state for the main thread
+    #[derive(Debug)]
+    struct Gamepads {
+        /// The gamepad library handle
+        gilrs: gilrs::Gilrs,
+
+        /// The commands that gamepad buttons are mapped to
+        buttons: HashMap<GamepadButton, Command>,
+
+        /// Events translated from gamepad input that have not yet been
+        /// returned by [`read()`]
+        pending: VecDeque<Event>,
+    }
+
+    impl Gamepads {
+        /// Translate any new gamepad events into terminal events and add them
+        /// to `pending`
+        fn pump(&mut self) {
+            while let Some(ev) = self.gilrs.next_event() {
+                if let gilrs::EventType::ButtonPressed(button, _) = ev.event {
+                    if let Some(cmd) = GamepadButton::from_gilrs(button)
+                        .and_then(|b| self.buttons.get(&b).copied())
+                    {
+                        self.pending.push_back(Event::Key(cmd.key_event()));
+                    }
+                }
+            }
+        }
+    }
+
+    thread_local! {
+        static GAMEPADS: RefCell<Option<Gamepads>> = const { RefCell::new(None) };
+    }
+
+    pub(super) fn init(config: &GamepadConfig) {
+        if !config.enabled {
+            return;
+        }
+        if let Ok(gilrs) = gilrs::Gilrs::new() {
+            GAMEPADS.with_borrow_mut(|gp| {
+                *gp = Some(Gamepads {
+                    gilrs,
+                    buttons: config.buttons.clone(),
+                    pending: VecDeque::new(),
+                });
+            });
+        }
+    }
+
+    /// Return `true` if a translated gamepad event is waiting to be read.
+    /// Returns `None` if gamepad support was not initialized.
+    fn gamepad_ready() -> Option<bool> {
+        GAMEPADS.with_borrow_mut(|gp| {
+            let gp = gp.as_mut()?;
+            gp.pump();
+            Some(!gp.pending.is_empty())
+        })
+    }
+
+    pub(super) fn poll(timeout: Duration) -> std::io::Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match gamepad_ready() {
+                None => return crossterm::event::poll(timeout),
+                Some(true) => return Ok(true),
+                Some(false) => (),
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if crossterm::event::poll(remaining.min(GAMEPAD_POLL_INTERVAL))? {
+                return Ok(true);
+            }
+            if remaining.is_zero() {
+                return Ok(false);
+            }
+        }
+    }
+
+    pub(super) fn read() -> std::io::Result<Event> {
+        loop {
+            match gamepad_ready() {
+                None => return crossterm::event::read(),
+                Some(true) => {
+                    if let Some(ev) =
+                        GAMEPADS.with_borrow_mut(|gp| gp.as_mut()?.pending.pop_front())
+                    {
+                        return Ok(ev);
+                    }
+                }
+                Some(false) => (),
+            }
+            if crossterm::event::poll(GAMEPAD_POLL_INTERVAL)? {
+                return crossterm::event::read();
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::command::KeyBinding;
+use crossterm::event::KeyEvent;
+
+/// The sequence of keys that must be typed in order to quit the application
+/// in kiosk mode, along with how much of it has been typed so far
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Passphrase {
+    /// The keys making up the passphrase
+    keys: Vec<KeyBinding>,
+
+    /// The number of keys at the start of the passphrase that were the most
+    /// recent keys typed
+    matched: usize,
+}
+
+impl Passphrase {
+    /// Create a new `Passphrase` from the given sequence of keys.  Returns
+    /// `None` if `keys` is empty.
+    pub(crate) fn new(keys: Vec<KeyBinding>) -> Option<Passphrase> {
+        (!keys.is_empty()).then_some(Passphrase { keys, matched: 0 })
+    }
+
+    /// Record a key press.  Returns `true` if it completed the passphrase.
+    ///
+    /// A key that breaks the sequence starts it over, though the key itself
+    /// may count as the start of the new attempt.
+    pub(crate) fn feed(&mut self, ev: KeyEvent) -> bool {
+        if self.keys.get(self.matched).is_some_and(|k| k.matches(ev)) {
+            self.matched += 1;
+        } else if self.keys.first().is_some_and(|k| k.matches(ev)) {
+            self.matched = 1;
+        } else {
+            self.matched = 0;
+        }
+        if self.matched == self.keys.len() {
+            self.matched = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    fn passphrase(keys: &[&str]) -> Passphrase {
+        Passphrase::new(keys.iter().map(|k| k.parse().unwrap()).collect()).unwrap()
+    }
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::from(KeyCode::Char(c))
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Passphrase::new(Vec::new()), None);
+    }
+
+    #[test]
+    fn typed_in_order() {
+        let mut pass = passphrase(&["ctrl-x", "b", "y", "e"]);
+        assert!(!pass.feed(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)));
+        assert!(!pass.feed(key('b')));
+        assert!(!pass.feed(key('y')));
+        assert!(pass.feed(key('e')));
+        // Typing the passphrase again requires starting from the beginning
+        assert!(!pass.feed(key('e')));
+    }
+
+    #[test]
+    fn interrupted() {
+        let mut pass = passphrase(&["a", "a", "b"]);
+        assert!(!pass.feed(key('a')));
+        assert!(!pass.feed(key('b')));
+        assert!(!pass.feed(key('a')));
+        assert!(!pass.feed(key('a')));
+        // A wrong key that is the first key of the passphrase restarts the
+        // match from there
+        assert!(!pass.feed(key('a')));
+        assert!(!pass.feed(key('a')));
+        assert!(pass.feed(key('b')));
+    }
+}
@@ -0,0 +1,419 @@
+//! Custom levels loaded from JSON files, optionally with objectives for the
+//! player to meet.
+//!
+//! A level file is a JSON object with the following fields:
+//!
+//! - `name` (optional) — the name of the level; defaults to the file's name
+//!   without its extension
+//! - `width` & `height` — the size of the level in cells
+//! - `wraparound` (optional, default `false`) — whether the edges of the
+//!   level wrap around
+//! - `obstacles` (optional) — a list of `[x, y]` positions of obstacles
+//! - `objectives` (optional) — an object with any of the following fields:
+//!     - `target-score` — the score that the player must reach to complete
+//!       the level
+//!     - `time-limit` — the number of seconds of play that the player has in
+//!       which to reach the target score, or, if there is no target score,
+//!       for which the snake must survive
+//!     - `forbidden` — a list of `[x, y]` positions of cells that the snake
+//!       must not touch
+//!
+//! The snake starts in the center of the level facing north.
+//!
+//! Levels can also be grouped into packs: subdirectories of the levels
+//! directory that contain a `pack.toml` manifest alongside the pack's level
+//! files.  The manifest may set the pack's `name` (defaulting to the name of
+//! the directory), its `author`, and a `levels` list of file names giving the
+//! order in which the levels are listed.  Any `*.json` files in the directory
+//! that the list omits are listed after it in order of file name.
+use crate::consts;
+use crate::util::Bounds;
+use ratatui::layout::Position;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::io;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+/// A level read from a level file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CustomLevel {
+    /// The name of the level
+    pub(crate) name: String,
+
+    /// The level's bounds (size and wraparound)
+    pub(crate) bounds: Bounds,
+
+    /// The locations of the obstacles in the level
+    pub(crate) obstacles: HashSet<Position>,
+
+    /// The goals that the player must meet to complete the level
+    pub(crate) objectives: Objectives,
+}
+
+impl CustomLevel {
+    /// Read & validate the level file at `path`.  If the file does not give
+    /// the level a name, the file's name without its extension is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file could not be read, could not be parsed, or
+    /// does not describe a valid level
+    pub(crate) fn load(path: &Path) -> Result<CustomLevel, LevelError> {
+        let src = fs_err::read_to_string(path)?;
+        let default_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        CustomLevel::from_json(&src, default_name)
+    }
+
+    /// Parse & validate a level file's contents, naming the level
+    /// `default_name` if the file does not give it a name
+    fn from_json(src: &str, default_name: String) -> Result<CustomLevel, LevelError> {
+        let raw = serde_json::from_str::<RawLevel>(src)?;
+        let size = ratatui::layout::Size {
+            width: raw.width,
+            height: raw.height,
+        };
+        let min = consts::MIN_CUSTOM_LEVEL_SIZE;
+        let max = consts::MAX_CUSTOM_LEVEL_SIZE;
+        if !(min.width..=max.width).contains(&size.width)
+            || !(min.height..=max.height).contains(&size.height)
+        {
+            return Err(LevelError::Size(size));
+        }
+        let bounds = Bounds::new(size, raw.wraparound);
+        let positions = |cells: Vec<[u16; 2]>| {
+            cells
+                .into_iter()
+                .map(|[x, y]| {
+                    if x < size.width && y < size.height {
+                        Ok(Position::new(x, y))
+                    } else {
+                        Err(LevelError::OutOfBounds(x, y))
+                    }
+                })
+                .collect::<Result<HashSet<_>, _>>()
+        };
+        let obstacles = positions(raw.obstacles)?;
+        let forbidden = positions(raw.objectives.forbidden)?;
+        let start = Position::new(size.width / 2, size.height / 2);
+        if obstacles.contains(&start) || forbidden.contains(&start) {
+            return Err(LevelError::StartBlocked(start.x, start.y));
+        }
+        Ok(CustomLevel {
+            name: raw.name.unwrap_or(default_name),
+            bounds,
+            obstacles,
+            objectives: Objectives {
+                target_score: raw.objectives.target_score,
+                time_limit: raw
+                    .objectives
+                    .time_limit
+                    .map(|secs| Duration::from_secs(secs.get())),
+                forbidden,
+            },
+        })
+    }
+}
+
+/// The goals of a custom level.  A level with no target score and no time
+/// limit is complete once the snake fills the level, as in a normal game.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Objectives {
+    /// The score that the player must reach to complete the level, if any
+    pub(crate) target_score: Option<NonZeroU32>,
+
+    /// The amount of play time within which the target score must be
+    /// reached, or, if there is no target score, for which the snake must
+    /// survive
+    pub(crate) time_limit: Option<Duration>,
+
+    /// Cells that the snake must not touch
+    pub(crate) forbidden: HashSet<Position>,
+}
+
+/// The contents of a level file as deserialized, before validation
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct RawLevel {
+    #[serde(default)]
+    name: Option<String>,
+    width: u16,
+    height: u16,
+    #[serde(default)]
+    wraparound: bool,
+    #[serde(default)]
+    obstacles: Vec<[u16; 2]>,
+    #[serde(default)]
+    objectives: RawObjectives,
+}
+
+/// The `objectives` field of a level file as deserialized
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct RawObjectives {
+    target_score: Option<NonZeroU32>,
+    time_limit: Option<NonZeroU64>,
+    forbidden: Vec<[u16; 2]>,
+}
+
+/// A named collection of custom levels, stored in a directory along with a
+/// `pack.toml` manifest
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct LevelPack {
+    /// The name of the pack
+    pub(crate) name: String,
+
+    /// The author of the pack, if the manifest names one
+    pub(crate) author: Option<String>,
+
+    /// The pack's levels, in order
+    pub(crate) levels: Vec<CustomLevel>,
+}
+
+impl LevelPack {
+    /// The name of the manifest file in a level pack's directory
+    const MANIFEST: &'static str = "pack.toml";
+
+    /// Load every level pack in the subdirectories of `dir`, sorted by
+    /// directory name.  Subdirectories without a manifest are skipped, and if
+    /// `dir` does not exist, no packs are returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `dir` could not be read or if any pack could not be
+    /// loaded
+    pub(crate) fn discover(dir: &Path) -> Result<Vec<LevelPack>, PackError> {
+        let entries = match fs_err::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut dirs = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.join(LevelPack::MANIFEST).is_file() {
+                dirs.push(path);
+            }
+        }
+        dirs.sort();
+        dirs.iter().map(|d| LevelPack::load(d)).collect()
+    }
+
+    /// Load the level pack in the directory `dir`
+    fn load(dir: &Path) -> Result<LevelPack, PackError> {
+        let manifest_path = dir.join(LevelPack::MANIFEST);
+        let src = fs_err::read_to_string(&manifest_path)?;
+        let manifest = toml::from_str::<Manifest>(&src).map_err(|source| PackError::Manifest {
+            path: manifest_path,
+            source,
+        })?;
+        let mut files = manifest
+            .levels
+            .iter()
+            .map(|name| dir.join(name))
+            .collect::<Vec<_>>();
+        let mut unlisted = Vec::new();
+        for entry in fs_err::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") && !files.contains(&path) {
+                unlisted.push(path);
+            }
+        }
+        unlisted.sort();
+        files.extend(unlisted);
+        let levels = files
+            .into_iter()
+            .map(|path| {
+                CustomLevel::load(&path).map_err(|source| PackError::Level { path, source })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = manifest.name.unwrap_or_else(|| {
+            dir.file_name()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        Ok(LevelPack {
+            name,
+            author: manifest.author,
+            levels,
+        })
+    }
+}
+
+/// The contents of a level pack's `pack.toml` manifest
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct Manifest {
+    name: Option<String>,
+    author: Option<String>,
+    levels: Vec<String>,
+}
+
+/// An error reading or validating a level file
+#[derive(Debug, Error)]
+pub(crate) enum LevelError {
+    #[error("failed to read level file")]
+    Read(#[from] io::Error),
+    #[error("failed to parse level file")]
+    Parse(#[from] serde_json::Error),
+    #[error(
+        "level size {}x{} is outside the allowed range of {}x{} to {}x{}",
+        .0.width,
+        .0.height,
+        consts::MIN_CUSTOM_LEVEL_SIZE.width,
+        consts::MIN_CUSTOM_LEVEL_SIZE.height,
+        consts::MAX_CUSTOM_LEVEL_SIZE.width,
+        consts::MAX_CUSTOM_LEVEL_SIZE.height
+    )]
+    Size(ratatui::layout::Size),
+    #[error("position [{0}, {1}] lies outside the level")]
+    OutOfBounds(u16, u16),
+    #[error("the snake's starting position at [{0}, {1}] is blocked")]
+    StartBlocked(u16, u16),
+}
+
+/// An error loading the level packs
+#[derive(Debug, Error)]
+pub(crate) enum PackError {
+    #[error("failed to read level packs")]
+    Read(#[from] io::Error),
+    #[error("failed to parse level pack manifest {}", .path.display())]
+    Manifest {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("failed to load level {}", .path.display())]
+    Level { path: PathBuf, source: LevelError },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let src = r#"{
+            "width": 20,
+            "height": 10,
+            "obstacles": [[2, 3], [2, 4]],
+            "objectives": {
+                "target-score": 15,
+                "time-limit": 90,
+                "forbidden": [[10, 2]]
+            }
+        }"#;
+        let level = CustomLevel::from_json(src, String::from("corridor")).unwrap();
+        assert_eq!(
+            level,
+            CustomLevel {
+                name: String::from("corridor"),
+                bounds: Bounds {
+                    width: 20,
+                    height: 10,
+                    wrap: false,
+                },
+                obstacles: HashSet::from([Position::new(2, 3), Position::new(2, 4)]),
+                objectives: Objectives {
+                    target_score: NonZeroU32::new(15),
+                    time_limit: Some(Duration::from_secs(90)),
+                    forbidden: HashSet::from([Position::new(10, 2)]),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_minimal() {
+        let src = r#"{"name": "Open field", "width": 10, "height": 5, "wraparound": true}"#;
+        let level = CustomLevel::from_json(src, String::from("field")).unwrap();
+        assert_eq!(level.name, "Open field");
+        assert!(level.bounds.wrap);
+        assert!(level.obstacles.is_empty());
+        assert_eq!(level.objectives, Objectives::default());
+    }
+
+    #[test]
+    fn invalid() {
+        let e = CustomLevel::from_json(r#"{"width": 5, "height": 5}"#, String::new()).unwrap_err();
+        assert!(matches!(e, LevelError::Size(_)));
+        let e = CustomLevel::from_json(
+            r#"{"width": 10, "height": 5, "objectives": {"forbidden": [[10, 0]]}}"#,
+            String::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(e, LevelError::OutOfBounds(10, 0)));
+        let e = CustomLevel::from_json(
+            r#"{"width": 10, "height": 5, "obstacles": [[5, 2]]}"#,
+            String::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(e, LevelError::StartBlocked(5, 2)));
+        let e = CustomLevel::from_json(
+            r#"{"width": 10, "height": 5, "objectives": {"time-limit": 0}}"#,
+            String::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(e, LevelError::Parse(_)));
+    }
+
+    #[test]
+    fn discover_packs() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let level = |name: &str| format!(r#"{{"name": "{name}", "width": 10, "height": 5}}"#);
+        let classic = tmpdir.path().join("classic");
+        fs_err::create_dir(&classic).unwrap();
+        fs_err::write(
+            classic.join("pack.toml"),
+            "name = \"Classics\"\nauthor = \"J. Doe\"\nlevels = [\"second.json\"]\n",
+        )
+        .unwrap();
+        fs_err::write(classic.join("first.json"), level("First")).unwrap();
+        fs_err::write(classic.join("second.json"), level("Second")).unwrap();
+        fs_err::write(classic.join("third.json"), level("Third")).unwrap();
+        fs_err::write(classic.join("notes.txt"), "not a level").unwrap();
+        let bonus = tmpdir.path().join("bonus");
+        fs_err::create_dir(&bonus).unwrap();
+        fs_err::write(bonus.join("pack.toml"), "").unwrap();
+        fs_err::write(bonus.join("extra.json"), r#"{"width": 10, "height": 5}"#).unwrap();
+        fs_err::create_dir(tmpdir.path().join("drafts")).unwrap();
+        fs_err::write(tmpdir.path().join("drafts").join("wip.json"), "{").unwrap();
+        let packs = LevelPack::discover(tmpdir.path()).unwrap();
+        let summary = packs
+            .iter()
+            .map(|p| {
+                let names = p.levels.iter().map(|l| l.name.as_str()).collect::<Vec<_>>();
+                (p.name.as_str(), p.author.as_deref(), names)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("bonus", None, vec!["extra"]),
+                ("Classics", Some("J. Doe"), vec!["Second", "First", "Third"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn discover_errors() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        assert!(LevelPack::discover(&tmpdir.path().join("nonexistent"))
+            .unwrap()
+            .is_empty());
+        let pack = tmpdir.path().join("pack");
+        fs_err::create_dir(&pack).unwrap();
+        fs_err::write(pack.join("pack.toml"), "title = \"Oops\"\n").unwrap();
+        let e = LevelPack::discover(tmpdir.path()).unwrap_err();
+        assert!(matches!(e, PackError::Manifest { .. }));
+        fs_err::write(pack.join("pack.toml"), "levels = [\"missing.json\"]\n").unwrap();
+        let e = LevelPack::discover(tmpdir.path()).unwrap_err();
+        assert!(
+            matches!(e, PackError::Level { ref path, .. } if path == &pack.join("missing.json"))
+        );
+    }
+}
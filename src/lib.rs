@@ -0,0 +1,652 @@
+//! The implementation of the `ratsnake` program.
+//!
+//! Everything is private to the crate apart from [`main()`], which the binary
+//! calls, and (with the `bench` feature enabled) the [`bench`] module, which
+//! exposes parts of the game engine to the benchmarks in `benches/`.
+mod about;
+mod app;
+mod backup;
+mod bot;
+#[cfg(feature = "broadcast")]
+mod broadcast;
+mod capabilities;
+mod command;
+mod config;
+mod confirm;
+mod consts;
+mod diagnostics;
+mod direction;
+mod favorites;
+mod game;
+mod highscores;
+mod history;
+mod i18n;
+mod input;
+mod kiosk;
+mod level;
+mod menu;
+mod migrations;
+mod options;
+mod outcome;
+mod palette;
+mod popup;
+mod progress;
+mod record;
+mod recovery;
+mod scoreboard;
+mod screenshot;
+mod script;
+mod session;
+#[cfg(test)]
+mod snapshot;
+mod table;
+mod terminal;
+mod textinput;
+mod theme;
+mod util;
+mod warning;
+mod widgets;
+use crate::app::App;
+use crate::bot::Bot;
+#[cfg(feature = "broadcast")]
+use crate::broadcast::Broadcaster;
+use crate::capabilities::Capabilities;
+use crate::config::{Config, ConfigWatcher};
+#[cfg(feature = "bench")]
+pub use crate::game::bench;
+use crate::game::{DeathLog, Difficulty, TickSource};
+use crate::highscores::HighScores;
+use crate::i18n::Catalog;
+use crate::input::TerminalEvents;
+use crate::kiosk::Passphrase;
+use crate::level::CustomLevel;
+use crate::migrations::SaveFile;
+use crate::options::SavedOptions;
+use crate::outcome::RunResult;
+use crate::record::Recorder;
+use crate::recovery::CorruptFile;
+use crate::script::{Script, ScriptPlayer};
+use crate::session::{Heartbeat, SessionFile};
+use crate::terminal::TerminalGuard;
+use crate::util::Globals;
+use crate::warning::Warning;
+use anyhow::Context;
+use lexopt::{Arg, Parser, ValueExt};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::io::{self, ErrorKind, Write};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Command {
+    Run {
+        cfg_src: ConfigSource,
+        record: Option<PathBuf>,
+        script: Option<PathBuf>,
+        bot: Option<String>,
+        step: bool,
+        relay: bool,
+        split: Option<NonZeroU32>,
+        level: Option<PathBuf>,
+        kiosk: bool,
+        json_result: bool,
+        #[cfg(feature = "broadcast")]
+        broadcast: Option<u16>,
+    },
+    ResetScores(ConfigSource),
+    Backup {
+        cfg_src: ConfigSource,
+        archive: PathBuf,
+    },
+    Restore {
+        cfg_src: ConfigSource,
+        archive: PathBuf,
+    },
+    History {
+        cfg_src: ConfigSource,
+        last: Option<usize>,
+    },
+    Help,
+    Version,
+}
+
+impl Command {
+    fn from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut cfg_src = ConfigSource::DefaultPath;
+        let mut reset_scores = false;
+        let mut history = false;
+        let mut backup = None;
+        let mut restore = None;
+        let mut last = None;
+        let mut record = None;
+        let mut script = None;
+        let mut bot = None;
+        let mut step = false;
+        let mut relay = false;
+        let mut split = None;
+        let mut level = None;
+        let mut kiosk_cfg = None;
+        let mut json_result = false;
+        #[cfg(feature = "broadcast")]
+        let mut broadcast = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::Help),
+                Arg::Short('V') | Arg::Long("version") => return Ok(Command::Version),
+                Arg::Short('c') | Arg::Long("config") => {
+                    cfg_src = ConfigSource::Path(parser.value()?.parse()?);
+                }
+                Arg::Long("reset-scores") => reset_scores = true,
+                Arg::Long("backup") => backup = Some(parser.value()?.parse()?),
+                Arg::Long("restore") => restore = Some(parser.value()?.parse()?),
+                Arg::Long("history") => history = true,
+                Arg::Long("last") => last = Some(parser.value()?.parse()?),
+                Arg::Long("record") => record = Some(parser.value()?.parse()?),
+                Arg::Long("script") => script = Some(parser.value()?.parse()?),
+                Arg::Long("bot") => bot = Some(parser.value()?.string()?),
+                Arg::Long("step") => step = true,
+                Arg::Long("relay") => relay = true,
+                Arg::Long("split") => split = Some(parser.value()?.parse()?),
+                Arg::Long("level") => level = Some(parser.value()?.parse()?),
+                Arg::Long("kiosk") => kiosk_cfg = Some(parser.value()?.parse()?),
+                Arg::Long("json-result") => json_result = true,
+                #[cfg(feature = "broadcast")]
+                Arg::Long("broadcast") => broadcast = Some(parser.value()?.parse()?),
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        let kiosk = if let Some(path) = kiosk_cfg {
+            // Kiosk mode never reads the user's own configuration file
+            if cfg_src != ConfigSource::DefaultPath {
+                return Err(String::from("--kiosk and --config are mutually exclusive").into());
+            }
+            cfg_src = ConfigSource::Path(path);
+            true
+        } else {
+            false
+        };
+        if bot.is_some() && relay {
+            return Err(String::from("--bot and --relay are mutually exclusive").into());
+        }
+        if split.is_some() && bot.is_some() {
+            return Err(String::from("--bot and --split are mutually exclusive").into());
+        }
+        if split.is_some() && relay {
+            return Err(String::from("--relay and --split are mutually exclusive").into());
+        }
+        if split.is_some() && json_result {
+            return Err(String::from("--json-result and --split are mutually exclusive").into());
+        }
+        if split.is_some() && level.is_some() {
+            return Err(String::from("--level and --split are mutually exclusive").into());
+        }
+        if level.is_some() && json_result {
+            return Err(String::from("--json-result and --level are mutually exclusive").into());
+        }
+        if backup.is_some() && restore.is_some() {
+            return Err(String::from("--backup and --restore are mutually exclusive").into());
+        }
+        if reset_scores && backup.is_some() {
+            return Err(String::from("--backup and --reset-scores are mutually exclusive").into());
+        }
+        if reset_scores && restore.is_some() {
+            return Err(String::from("--restore and --reset-scores are mutually exclusive").into());
+        }
+        if reset_scores {
+            Ok(Command::ResetScores(cfg_src))
+        } else if let Some(archive) = backup {
+            Ok(Command::Backup { cfg_src, archive })
+        } else if let Some(archive) = restore {
+            Ok(Command::Restore { cfg_src, archive })
+        } else if history {
+            Ok(Command::History { cfg_src, last })
+        } else {
+            Ok(Command::Run {
+                cfg_src,
+                record,
+                script,
+                bot,
+                step,
+                relay,
+                split,
+                level,
+                kiosk,
+                json_result,
+                #[cfg(feature = "broadcast")]
+                broadcast,
+            })
+        }
+    }
+
+    fn run(self) -> anyhow::Result<ExitCode> {
+        match self {
+            Command::Run {
+                cfg_src,
+                record,
+                script,
+                bot,
+                step,
+                relay,
+                split,
+                level,
+                kiosk,
+                json_result,
+                #[cfg(feature = "broadcast")]
+                broadcast,
+            } => {
+                let config = cfg_src.load()?;
+                let script = script
+                    .map(|path| {
+                        Script::load(&path)
+                            .with_context(|| format!("failed to load script {}", path.display()))
+                    })
+                    .transpose()?;
+                let level = level
+                    .map(|path| {
+                        CustomLevel::load(&path)
+                            .with_context(|| format!("failed to load level {}", path.display()))
+                    })
+                    .transpose()?;
+                let passphrase = if kiosk {
+                    Some(Passphrase::new(config.kiosk.passphrase.clone()).context(
+                        "kiosk mode requires a passphrase to be set in the configuration file",
+                    )?)
+                } else {
+                    None
+                };
+                let lang = config.lang.as_deref().unwrap_or(Catalog::ENGLISH);
+                let catalog = Catalog::load(lang).with_context(|| {
+                    format!("failed to load translations for language {lang:?}")
+                })?;
+                i18n::init(catalog);
+                theme::init(&config.theme);
+                options::set_max_fruits(config.game.max_fruits);
+                // Loaded after the catalog is set so that any pop-ups about
+                // corrupt files are translated.  Files written by a newer
+                // version are not loaded, and the defaults are used in their
+                // place; saving over them is refused later on.
+                let mut corrupt = Vec::new();
+                let mut ignored = Vec::new();
+                let mut warnings = config
+                    .unknown_skin_keys
+                    .iter()
+                    .cloned()
+                    .map(Warning::from)
+                    .collect::<Vec<_>>();
+                let saved_options = match config.load_options() {
+                    Ok(loaded) => {
+                        ignored.extend(loaded.ignored.into_iter().map(Warning::from));
+                        if let Some(replaced) = loaded.repaired {
+                            // The repaired options are written back so that
+                            // the replacements are only reported once.  If
+                            // that fails, they'll be reported again on the
+                            // next run, and the user can see why.
+                            if let Err(e) = config.save_options(loaded.value) {
+                                ignored.push(Warning::from(e));
+                            }
+                            warnings.push(Warning::from(replaced));
+                        }
+                        loaded.value
+                    }
+                    Err(e) if e.is_corrupt() => {
+                        corrupt.push(CorruptFile::new(SaveFile::Options, e));
+                        SavedOptions::from(config.options)
+                    }
+                    Err(e) if e.is_too_new() => {
+                        warnings.push(Warning::from(e));
+                        SavedOptions::from(config.options)
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                let high_scores = match config.load_high_scores() {
+                    Ok(loaded) => {
+                        ignored.extend(loaded.ignored.into_iter().map(Warning::from));
+                        loaded.value
+                    }
+                    Err(e) if e.is_corrupt() => {
+                        corrupt.push(CorruptFile::new(SaveFile::HighScores, e));
+                        HighScores::default()
+                    }
+                    Err(e) if e.is_too_new() => {
+                        warnings.push(Warning::from(e));
+                        HighScores::default()
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                // If the previous session ended abruptly in the middle of a
+                // game, record the game in the history.  The session file is
+                // replaced by the first heartbeat of this session, so there's
+                // no retrying on failure, and it shouldn't stop the program
+                // from starting.  If the session file was written to
+                // recently, it belongs to another instance that is still
+                // running, so it's left alone, and this session goes
+                // untracked.
+                let mut session = config.session_file().ok().map(SessionFile::new);
+                match session.as_ref().and_then(|s| s.previous().ok().flatten()) {
+                    Some(heartbeat) if heartbeat.is_live_at(jiff::Timestamp::now()) => {
+                        session = None;
+                    }
+                    Some(Heartbeat {
+                        game: Some(record), ..
+                    }) => {
+                        let _ = config.append_history(&record);
+                    }
+                    _ => (),
+                }
+                let recorder = match record {
+                    Some(path) => {
+                        let (width, height) =
+                            crossterm::terminal::size().context("failed to get terminal size")?;
+                        Some(
+                            Recorder::create(io::stdout(), &path, width, height)
+                                .context("failed to start recording")?,
+                        )
+                    }
+                    None => None,
+                };
+                #[cfg(feature = "broadcast")]
+                let broadcaster = broadcast
+                    .map(Broadcaster::start)
+                    .transpose()
+                    .context("failed to start broadcast server")?;
+                input::init(&config.gamepad);
+                let (guard, terminal) = TerminalGuard::init(config.game.mouse_steering)?;
+                let mut app = App::new(
+                    Globals {
+                        config,
+                        options: saved_options.options,
+                        use_config_defaults: saved_options.use_config_defaults,
+                        high_scores,
+                        bot: bot.map(Bot::new),
+                        tick_source: if step {
+                            TickSource::Step
+                        } else {
+                            TickSource::Timer
+                        },
+                        kiosk,
+                        capabilities: Capabilities::detect(),
+                        deaths: DeathLog::default(),
+                        difficulty: Difficulty::default(),
+                        relay,
+                        split,
+                        level,
+                        last_game: None,
+                    },
+                    cfg_src.watcher(),
+                )
+                .with_corrupt_files(corrupt)
+                .with_startup_warnings(warnings)
+                .with_ignored_errors(ignored);
+                if let Some(passphrase) = passphrase {
+                    app = app.with_kiosk(passphrase);
+                }
+                if let Some(session) = session {
+                    app = app.with_session(session);
+                }
+                if let Some(script) = script {
+                    app = app.with_events(ScriptPlayer::new(script, TerminalEvents));
+                }
+                #[cfg(feature = "broadcast")]
+                if let Some(b) = broadcaster {
+                    app = app.with_broadcaster(b);
+                }
+                let r = match recorder {
+                    Some(rec) => Terminal::new(CrosstermBackend::new(rec))
+                        .and_then(|terminal| app.run(terminal)),
+                    None => app.run(terminal),
+                }
+                .map_err(anyhow::Error::from);
+                let last_game = match guard.restore() {
+                    Ok(()) => r,
+                    Err(e) if r.is_ok() => Err(e),
+                    Err(e) => {
+                        errmsg(e);
+                        r
+                    }
+                }?;
+                // Printed after the terminal is restored so that the result
+                // isn't lost along with the alternate screen
+                if json_result {
+                    let result = RunResult::new(last_game);
+                    result.write(io::stdout().lock())?;
+                    Ok(result.outcome().exit_code())
+                } else {
+                    Ok(ExitCode::SUCCESS)
+                }
+            }
+            Command::ResetScores(cfg_src) => {
+                let config = cfg_src.load()?;
+                config.save_high_scores(&HighScores::default())?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Backup { cfg_src, archive } => {
+                let config = cfg_src.load()?;
+                let backed_up = backup::backup(&archive, &config, &cfg_src.path()?)
+                    .context("failed to back up data")?;
+                let mut stdout = io::stdout().lock();
+                for (file, path) in backed_up {
+                    writeln!(
+                        &mut stdout,
+                        "Backed up {} from {}",
+                        file.desc(),
+                        path.display()
+                    )?;
+                }
+                writeln!(&mut stdout, "Wrote backup to {}", archive.display())?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Restore { cfg_src, archive } => {
+                let config = cfg_src.load()?;
+                let restored = backup::restore(&archive, &config, &cfg_src.path()?)
+                    .with_context(|| format!("failed to restore {}", archive.display()))?;
+                let mut stdout = io::stdout().lock();
+                if restored.is_empty() {
+                    writeln!(&mut stdout, "Backup contains no data files")?;
+                }
+                for (file, path) in restored {
+                    match path {
+                        Some(p) => {
+                            writeln!(&mut stdout, "Restored {} to {}", file.desc(), p.display())?;
+                        }
+                        None => writeln!(
+                            &mut stdout,
+                            "Skipped {}, as saving it is disabled in the configuration",
+                            file.desc()
+                        )?,
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::History { cfg_src, last } => {
+                let config = cfg_src.load()?;
+                let records = config.load_history()?;
+                let start = last.map_or(0, |n| records.len().saturating_sub(n));
+                history::print_table(io::stdout().lock(), &records[start..])?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Help => {
+                let mut stdout = io::stdout().lock();
+                writeln!(&mut stdout, "Usage: ratsnake [<options>]")?;
+                writeln!(&mut stdout)?;
+                writeln!(&mut stdout, "Snake game in Rust+Ratatui")?;
+                writeln!(&mut stdout)?;
+                writeln!(
+                    &mut stdout,
+                    "Visit <https://github.com/jwodder/ratsnake> for more information."
+                )?;
+                writeln!(&mut stdout)?;
+                writeln!(&mut stdout, "Options:")?;
+                writeln!(&mut stdout, "  -c <file>, --config <file>")?;
+                writeln!(
+                    &mut stdout,
+                    "                    Read configuration settings from <file>."
+                )?;
+                writeln!(&mut stdout)?;
+                if let Ok(p) = Config::default_path() {
+                    writeln!(
+                        &mut stdout,
+                        "                    [Default configuration file: {}]",
+                        p.display()
+                    )?;
+                } else {
+                    writeln!(&mut stdout, "                    [Warning: could not determine default configuration file]")?;
+                }
+                writeln!(&mut stdout)?;
+                writeln!(
+                    &mut stdout,
+                    "  --backup <file>   Save all configuration & data to the archive <file>"
+                )?;
+                writeln!(&mut stdout, "                    and exit")?;
+                writeln!(
+                    &mut stdout,
+                    "  --bot <command>   Let <command> steer the snake"
+                )?;
+                #[cfg(feature = "broadcast")]
+                {
+                    writeln!(&mut stdout, "  --broadcast <port>")?;
+                    writeln!(
+                        &mut stdout,
+                        "                    Serve the game state over WebSocket on <port>"
+                    )?;
+                }
+                writeln!(
+                    &mut stdout,
+                    "  -h, --help        Display this help message and exit"
+                )?;
+                writeln!(&mut stdout, "  --history [--last <n>]")?;
+                writeln!(
+                    &mut stdout,
+                    "                    List completed games (only the last <n>) and exit"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --json-result     On exit, print the last game & its outcome as JSON"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "                    and exit with a status reflecting the outcome"
+                )?;
+                writeln!(&mut stdout, "  --kiosk <file>")?;
+                writeln!(
+                    &mut stdout,
+                    "                    Run in kiosk mode using the configuration in <file>"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --level <file>    Play the custom level described in <file>"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --record <file>   Record the session to <file> as an asciinema cast"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --relay           Let two players take turns steering the snake"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --reset-scores    Delete all saved high scores and exit"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --restore <file>  Restore configuration & data from the backup <file>"
+                )?;
+                writeln!(&mut stdout, "                    and exit")?;
+                writeln!(&mut stdout, "  --script <file>")?;
+                writeln!(
+                    &mut stdout,
+                    "                    Play back the timed key presses in <file> as input"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --split <points>  Let two players race on separate halves of the level,"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "                    the first to <points> points winning"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  --step            Only advance the snake when . is pressed"
+                )?;
+                writeln!(
+                    &mut stdout,
+                    "  -V, --version     Show the program version and exit"
+                )?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Version => {
+                writeln!(
+                    io::stdout().lock(),
+                    "{} {}",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION")
+                )?;
+                Ok(ExitCode::SUCCESS)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ConfigSource {
+    DefaultPath,
+    Path(PathBuf),
+}
+
+impl ConfigSource {
+    fn load(&self) -> anyhow::Result<Config> {
+        match self {
+            ConfigSource::DefaultPath => Config::load(&Config::default_path()?, true),
+            ConfigSource::Path(p) => Config::load(p, false),
+        }
+        .map_err(Into::into)
+    }
+
+    /// Return the path to the configuration file
+    fn path(&self) -> anyhow::Result<PathBuf> {
+        match self {
+            ConfigSource::DefaultPath => Ok(Config::default_path()?),
+            ConfigSource::Path(p) => Ok(p.clone()),
+        }
+    }
+
+    /// Return a watcher for changes to the configuration file, or `None` if
+    /// the default configuration path cannot be determined
+    fn watcher(&self) -> Option<ConfigWatcher> {
+        match self {
+            ConfigSource::DefaultPath => Config::default_path()
+                .ok()
+                .map(|p| ConfigWatcher::new(p, true)),
+            ConfigSource::Path(p) => Some(ConfigWatcher::new(p.clone(), false)),
+        }
+    }
+}
+
+/// Run the program with the command-line arguments of the current process
+/// and return its exit status
+pub fn main() -> ExitCode {
+    match Command::from_parser(Parser::from_env())
+        .map_err(anyhow::Error::from)
+        .and_then(Command::run)
+    {
+        Ok(code) => code,
+        Err(e) => {
+            for cause in e.chain() {
+                if let Some(ioerr) = cause.downcast_ref::<io::Error>() {
+                    if ioerr.kind() == ErrorKind::BrokenPipe {
+                        return ExitCode::SUCCESS;
+                    }
+                }
+            }
+            errmsg(e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Report an error to stderr, discarding any errors that occur in the process.
+fn errmsg(e: anyhow::Error) {
+    let _ = writeln!(io::stderr().lock(), "ratsnake: {e:?}");
+}
@@ -0,0 +1,230 @@
+use crate::command::Command;
+use crate::consts;
+use crate::favorites::{Favorite, Favorites};
+use crate::i18n::{tr, Msg};
+use crate::popup::PopupFrame;
+use crate::theme;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::Widget,
+};
+use unicode_width::UnicodeWidthStr;
+
+/// A pop-up listing the player's favorite boards, from which one can be
+/// chosen to play or deleted
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct FavoritesPicker {
+    /// The boards being listed
+    favorites: Favorites,
+
+    /// The index of the currently-selected board
+    selection: usize,
+}
+
+impl FavoritesPicker {
+    /// The width of the widget
+    const WIDTH: u16 = 48;
+
+    /// The maximum number of boards shown at once.  If there are more, the
+    /// list scrolls to keep the selection visible.
+    const VISIBLE_ROWS: usize = 8;
+
+    /// Create a new picker listing the given boards with the first one
+    /// selected
+    pub(super) fn new(favorites: Favorites) -> FavoritesPicker {
+        FavoritesPicker {
+            favorites,
+            selection: 0,
+        }
+    }
+
+    /// Return the boards currently listed
+    pub(super) fn favorites(&self) -> &Favorites {
+        &self.favorites
+    }
+
+    /// Process an input command.  Returns `Some` if the user chose a board,
+    /// deleted one, closed the picker, or quit the application.
+    pub(super) fn handle_command(&mut self, cmd: Command) -> Option<PickerOutcome> {
+        let last = self.favorites.boards().len().saturating_sub(1);
+        match cmd {
+            Command::Quit => return Some(PickerOutcome::Quit),
+            Command::Esc | Command::F => return Some(PickerOutcome::Close),
+            Command::Enter => {
+                let fav = self.favorites.boards().get(self.selection)?;
+                return Some(PickerOutcome::Play(fav.clone()));
+            }
+            Command::Delete => {
+                self.favorites.remove(self.selection)?;
+                self.selection = self
+                    .selection
+                    .min(self.favorites.boards().len().saturating_sub(1));
+                return Some(PickerOutcome::Deleted);
+            }
+            Command::Up => self.selection = self.selection.saturating_sub(1),
+            Command::Down => self.selection = (self.selection + 1).min(last),
+            Command::Next if self.selection >= last => self.selection = 0,
+            Command::Next => self.selection += 1,
+            Command::Prev if self.selection == 0 => self.selection = last,
+            Command::Prev => self.selection -= 1,
+            Command::Home => self.selection = 0,
+            Command::End => self.selection = last,
+            _ => (),
+        }
+        None
+    }
+}
+
+/// An enum of the ways that the user can finish working with a
+/// `FavoritesPicker`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum PickerOutcome {
+    /// The user chose a board to play
+    Play(Favorite),
+
+    /// The user deleted the selected board, and the remaining boards should
+    /// be saved
+    Deleted,
+
+    /// The user closed the picker
+    Close,
+
+    /// The user quit the application
+    Quit,
+}
+
+impl Widget for &FavoritesPicker {
+    /// Render a `FavoritesPicker` in the given area of `buf`.
+    ///
+    /// As with [`Warning`][crate::warning::Warning], `area` should be the area
+    /// of the entire display within which the pop-up will be rendered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let boards = self.favorites.boards();
+        let rows = boards.len().clamp(1, FavoritesPicker::VISIBLE_ROWS);
+        let [list_area, footer_area] = PopupFrame {
+            title: tr(Msg::FavoritesTitle),
+            width: FavoritesPicker::WIDTH,
+            text_height: u16::try_from(rows).unwrap_or(u16::MAX),
+            footer: true,
+        }
+        .render(area, buf);
+        if boards.is_empty() {
+            Line::from(tr(Msg::NoFavorites))
+                .centered()
+                .render(list_area, buf);
+        }
+        let name_width = usize::from(consts::FAVORITE_NAME_MAX_WIDTH);
+        let offset = (self.selection + 1).saturating_sub(FavoritesPicker::VISIBLE_ROWS);
+        for ((i, fav), row) in boards.iter().enumerate().skip(offset).zip(list_area.rows()) {
+            let selected = i == self.selection;
+            let style = if selected {
+                theme::selection_style()
+            } else {
+                Style::new()
+            };
+            let s = format!(
+                "{pointer:2}{name}{space:pad$}  {size} {layout}",
+                pointer = if selected { "»" } else { "" },
+                name = fav.name,
+                space = "",
+                pad = name_width.saturating_sub(fav.name.width()),
+                size = fav.options.level_size,
+                layout = fav.options.layout,
+            );
+            Span::styled(s, style).render(row, buf);
+        }
+        let mut footer = Line::default();
+        if !boards.is_empty() {
+            footer.extend([
+                Span::raw(format!("{} (", tr(Msg::Play))),
+                Span::styled("Enter", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::Delete))),
+                Span::styled("Del", consts::KEY_STYLE),
+                Span::raw(") — "),
+            ]);
+        }
+        footer.extend([
+            Span::raw(format!("{} (", tr(Msg::Close))),
+            Span::styled("Esc", consts::KEY_STYLE),
+            Span::raw(")"),
+        ]);
+        footer.centered().render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{LevelLayout, LevelSize, Options};
+
+    fn picker() -> FavoritesPicker {
+        let mut favorites = Favorites::default();
+        for (name, seed) in [("Spiral", 42), ("Donut", 1337), ("Gauntlet", 23)] {
+            favorites.add(Favorite {
+                name: name.to_owned(),
+                seed,
+                options: Options::default(),
+            });
+        }
+        FavoritesPicker::new(favorites)
+    }
+
+    #[test]
+    fn choose() {
+        let mut picker = picker();
+        assert_eq!(picker.handle_command(Command::Up), None);
+        assert_eq!(picker.handle_command(Command::Down), None);
+        assert_eq!(picker.handle_command(Command::End), None);
+        assert_eq!(picker.handle_command(Command::Down), None);
+        assert_eq!(picker.selection, 2);
+        assert_eq!(picker.handle_command(Command::Next), None);
+        assert_eq!(picker.selection, 0);
+        assert_eq!(picker.handle_command(Command::Prev), None);
+        let Some(PickerOutcome::Play(fav)) = picker.handle_command(Command::Enter) else {
+            panic!("Enter did not choose a board");
+        };
+        assert_eq!(fav.name, "Gauntlet");
+        assert_eq!(fav.seed, 23);
+    }
+
+    #[test]
+    fn delete() {
+        let mut picker = picker();
+        picker.handle_command(Command::End);
+        assert_eq!(
+            picker.handle_command(Command::Delete),
+            Some(PickerOutcome::Deleted)
+        );
+        assert_eq!(picker.selection, 1);
+        assert_eq!(picker.favorites().boards().len(), 2);
+        picker.handle_command(Command::Delete);
+        picker.handle_command(Command::Delete);
+        assert!(picker.favorites().boards().is_empty());
+        assert_eq!(picker.handle_command(Command::Delete), None);
+        assert_eq!(picker.handle_command(Command::Enter), None);
+        assert_eq!(
+            picker.handle_command(Command::Esc),
+            Some(PickerOutcome::Close)
+        );
+    }
+
+    #[test]
+    fn render() {
+        let mut picker = picker();
+        picker.favorites.add(Favorite {
+            name: String::from("Labyrinth"),
+            seed: 7,
+            options: Options {
+                level_size: LevelSize::Large,
+                layout: LevelLayout::Maze,
+                ..Options::default()
+            },
+        });
+        picker.handle_command(Command::Down);
+        let buffer = crate::snapshot::render(&picker);
+        crate::snapshot::assert_snapshot("menu/favorites", &buffer);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::command::Command;
+use crate::consts;
+use crate::i18n::{tr, tr_args, Msg};
+use crate::level::{CustomLevel, LevelPack};
+use crate::popup::PopupFrame;
+use crate::theme;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+/// A pop-up listing the custom levels in the player's level packs, grouped
+/// into collapsible sections by pack, from which a level can be chosen to
+/// play
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct LevelPicker {
+    /// The packs being listed
+    packs: Vec<LevelPack>,
+
+    /// Whether each pack's section is expanded to show its levels
+    expanded: Vec<bool>,
+
+    /// The index of the currently-selected row among the visible rows
+    selection: usize,
+}
+
+impl LevelPicker {
+    /// The width of the widget
+    const WIDTH: u16 = 56;
+
+    /// The maximum number of rows shown at once.  If there are more, the list
+    /// scrolls to keep the selection visible.
+    const VISIBLE_ROWS: usize = 12;
+
+    /// Create a new picker listing the given packs with every section
+    /// expanded and the first row selected
+    pub(super) fn new(packs: Vec<LevelPack>) -> LevelPicker {
+        let expanded = vec![true; packs.len()];
+        LevelPicker {
+            packs,
+            expanded,
+            selection: 0,
+        }
+    }
+
+    /// Return the rows currently visible: a header for each pack, followed
+    /// by the pack's levels if its section is expanded
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        for (p, pack) in self.packs.iter().enumerate() {
+            rows.push(Row::Pack(p));
+            if self.expanded[p] {
+                rows.extend((0..pack.levels.len()).map(|l| Row::Level(p, l)));
+            }
+        }
+        rows
+    }
+
+    /// Expand or collapse the section for the pack with index `pack`, keeping
+    /// the pack's header selected
+    fn set_expanded(&mut self, pack: usize, expanded: bool) {
+        self.expanded[pack] = expanded;
+        self.selection = self
+            .rows()
+            .iter()
+            .position(|&r| r == Row::Pack(pack))
+            .unwrap_or_default();
+    }
+
+    /// Process an input command.  Returns `Some` if the user chose a level,
+    /// closed the picker, or quit the application.
+    pub(super) fn handle_command(&mut self, cmd: Command) -> Option<PickerOutcome> {
+        let rows = self.rows();
+        let last = rows.len().saturating_sub(1);
+        let current = rows.get(self.selection).copied();
+        match (cmd, current) {
+            (Command::Quit, _) => return Some(PickerOutcome::Quit),
+            (Command::Esc | Command::C, _) => return Some(PickerOutcome::Close),
+            (Command::Enter, Some(Row::Level(p, l))) => {
+                let level = self.packs[p].levels[l].clone();
+                return Some(PickerOutcome::Play(level));
+            }
+            (Command::Enter | Command::Space, Some(Row::Pack(p))) => {
+                self.set_expanded(p, !self.expanded[p]);
+            }
+            (Command::Left, Some(Row::Pack(p) | Row::Level(p, _))) => self.set_expanded(p, false),
+            (Command::Right, Some(Row::Pack(p))) => self.set_expanded(p, true),
+            (Command::Up, _) => self.selection = self.selection.saturating_sub(1),
+            (Command::Down, _) => self.selection = (self.selection + 1).min(last),
+            (Command::Next, _) if self.selection >= last => self.selection = 0,
+            (Command::Next, _) => self.selection += 1,
+            (Command::Prev, _) if self.selection == 0 => self.selection = last,
+            (Command::Prev, _) => self.selection -= 1,
+            (Command::Home, _) => self.selection = 0,
+            (Command::End, _) => self.selection = last,
+            _ => (),
+        }
+        None
+    }
+}
+
+/// A row of a `LevelPicker`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Row {
+    /// The header of the pack with the given index
+    Pack(usize),
+
+    /// The level with the given index in the pack with the given index
+    Level(usize, usize),
+}
+
+/// An enum of the ways that the user can finish working with a `LevelPicker`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum PickerOutcome {
+    /// The user chose a level to play
+    Play(CustomLevel),
+
+    /// The user closed the picker
+    Close,
+
+    /// The user quit the application
+    Quit,
+}
+
+impl Widget for &LevelPicker {
+    /// Render a `LevelPicker` in the given area of `buf`.
+    ///
+    /// As with [`Warning`][crate::warning::Warning], `area` should be the area
+    /// of the entire display within which the pop-up will be rendered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self.rows();
+        let height = rows.len().clamp(1, LevelPicker::VISIBLE_ROWS);
+        let [list_area, footer_area] = PopupFrame {
+            title: tr(Msg::LevelsTitle),
+            width: LevelPicker::WIDTH,
+            text_height: u16::try_from(height).unwrap_or(u16::MAX),
+            footer: true,
+        }
+        .render(area, buf);
+        if rows.is_empty() {
+            Line::from(tr(Msg::NoLevelPacks))
+                .centered()
+                .render(list_area, buf);
+        }
+        let offset = (self.selection + 1).saturating_sub(LevelPicker::VISIBLE_ROWS);
+        for ((i, r), area) in rows.iter().enumerate().skip(offset).zip(list_area.rows()) {
+            let selected = i == self.selection;
+            let pointer = if selected { "»" } else { "" };
+            let (s, style) = match *r {
+                Row::Pack(p) => {
+                    let pack = &self.packs[p];
+                    let marker = if self.expanded[p] { '▾' } else { '▸' };
+                    let mut s = format!("{pointer:2}{marker} {}", pack.name);
+                    if let Some(ref author) = pack.author {
+                        s.push_str(" — ");
+                        s.push_str(&tr_args(Msg::PackAuthor, &[("author", author)]));
+                    }
+                    (s, Style::new().bold())
+                }
+                Row::Level(p, l) => (
+                    format!("{pointer:2}    {}", self.packs[p].levels[l].name),
+                    Style::new(),
+                ),
+            };
+            let style = if selected {
+                theme::selection_style()
+            } else {
+                style
+            };
+            Span::styled(s, style).render(area, buf);
+        }
+        let mut footer = Line::default();
+        if !rows.is_empty() {
+            footer.extend([
+                Span::raw(format!("{} (", tr(Msg::Play))),
+                Span::styled("Enter", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::ExpandCollapse))),
+                Span::styled("Space", consts::KEY_STYLE),
+                Span::raw(") — "),
+            ]);
+        }
+        footer.extend([
+            Span::raw(format!("{} (", tr(Msg::Close))),
+            Span::styled("Esc", consts::KEY_STYLE),
+            Span::raw(")"),
+        ]);
+        footer.centered().render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::Objectives;
+    use crate::util::Bounds;
+    use std::collections::HashSet;
+
+    fn level(name: &str) -> CustomLevel {
+        CustomLevel {
+            name: name.to_owned(),
+            bounds: Bounds {
+                width: 20,
+                height: 10,
+                wrap: false,
+            },
+            obstacles: HashSet::new(),
+            objectives: Objectives::default(),
+        }
+    }
+
+    fn picker() -> LevelPicker {
+        LevelPicker::new(vec![
+            LevelPack {
+                name: String::from("Classics"),
+                author: Some(String::from("J. Doe")),
+                levels: vec![level("Corridor"), level("Crossroads")],
+            },
+            LevelPack {
+                name: String::from("bonus"),
+                author: None,
+                levels: vec![level("Spiral")],
+            },
+        ])
+    }
+
+    #[test]
+    fn choose() {
+        let mut picker = picker();
+        assert_eq!(picker.handle_command(Command::Up), None);
+        assert_eq!(picker.handle_command(Command::End), None);
+        assert_eq!(picker.selection, 4);
+        assert_eq!(picker.handle_command(Command::Next), None);
+        assert_eq!(picker.selection, 0);
+        assert_eq!(picker.handle_command(Command::Down), None);
+        assert_eq!(picker.handle_command(Command::Down), None);
+        let Some(PickerOutcome::Play(level)) = picker.handle_command(Command::Enter) else {
+            panic!("Enter did not choose a level");
+        };
+        assert_eq!(level.name, "Crossroads");
+        assert_eq!(
+            picker.handle_command(Command::Esc),
+            Some(PickerOutcome::Close)
+        );
+    }
+
+    #[test]
+    fn collapse() {
+        let mut picker = picker();
+        picker.handle_command(Command::Down);
+        assert_eq!(picker.handle_command(Command::Left), None);
+        assert_eq!(
+            picker.rows(),
+            [Row::Pack(0), Row::Pack(1), Row::Level(1, 0)]
+        );
+        assert_eq!(picker.selection, 0);
+        picker.handle_command(Command::End);
+        picker.handle_command(Command::Prev);
+        assert_eq!(picker.handle_command(Command::Enter), None);
+        assert_eq!(picker.rows(), [Row::Pack(0), Row::Pack(1)]);
+        assert_eq!(picker.selection, 1);
+        picker.handle_command(Command::Up);
+        picker.handle_command(Command::Right);
+        assert_eq!(picker.selection, 0);
+        assert_eq!(picker.rows().len(), 4);
+        picker.handle_command(Command::Space);
+        assert_eq!(picker.rows().len(), 2);
+    }
+
+    #[test]
+    fn render() {
+        let mut picker = picker();
+        picker.handle_command(Command::End);
+        picker.handle_command(Command::Left);
+        picker.handle_command(Command::Home);
+        picker.handle_command(Command::Down);
+        let buffer = crate::snapshot::render(&picker);
+        crate::snapshot::assert_snapshot("menu/levels", &buffer);
+    }
+}
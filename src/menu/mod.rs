@@ -1,25 +1,40 @@
+mod favorites;
+mod levels;
 mod widgets;
+use self::favorites::{FavoritesPicker, PickerOutcome};
+use self::levels::LevelPicker;
 use self::widgets::{Instructions, Logo};
+use crate::about::About;
 use crate::app::Screen;
 use crate::command::Command;
+use crate::config::RngMode;
+use crate::confirm::{Confirm, ConfirmOutcome};
 use crate::consts;
-use crate::game::Game;
-use crate::options::{Adjustable, OptKey, OptValue, Options};
-use crate::util::{get_display_area, EnumExt, Globals};
-use crate::warning::{Warning, WarningOutcome};
-use crossterm::event::{read, Event};
-use enum_map::{Enum, EnumMap};
+use crate::favorites::Favorite;
+use crate::game::{Game, SplitGame};
+use crate::i18n::{self, tr, tr_args, Msg};
+use crate::input::EventSource;
+use crate::level::{CustomLevel, LevelPack};
+use crate::options::{OptKey, OptValue, Options};
+use crate::palette::{Palette, PaletteAction, PaletteEntry};
+use crate::recovery::{CorruptFile, Recovery};
+use crate::scoreboard::Scoreboard;
+use crate::theme;
+use crate::util::{get_display_area, EnumExt, Globals, LayoutDirection};
+use crate::warning::{ChoiceOutcome, Warning, WarningOutcome};
+use crate::widgets::SettingsList;
+use crossterm::event::Event;
+use enum_map::EnumMap;
+use rand::{rngs::StdRng, SeedableRng};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Flex, Layout, Rect},
     style::Style,
     text::{Line, Span},
-    widgets::{
-        block::{Block, Padding},
-        Widget,
-    },
+    widgets::{Paragraph, Widget, Wrap},
     Frame,
 };
+use std::time::Instant;
 
 /// The main menu/startup screen
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -33,32 +48,114 @@ pub(crate) struct MainMenu {
     /// The state that the menu is currently in
     state: MenuState,
 
+    /// Warnings about errors that were ignored while loading data files at
+    /// startup that the user has not yet viewed.  While this is nonempty, a
+    /// banner at the bottom of the menu offers to show them.
+    ignored: Vec<Warning>,
+
+    /// Warnings about problems encountered at startup that have yet to be
+    /// shown.  They are shown one at a time once the user has finished
+    /// dealing with any corrupt data files.
+    startup_warnings: Vec<Warning>,
+
+    /// The index into [`i18n::tips()`] of the tip currently shown under the
+    /// instructions
+    tip: usize,
+
+    /// When to move on to the next tip
+    next_tip: Instant,
+
     /// Global data (options & high scores)
     globals: Globals,
 }
 
 impl MainMenu {
+    /// The number of lines used for showing the current tip
+    const TIP_HEIGHT: u16 = 2;
+
     /// Create a new main menu from the given globals
     pub(crate) fn new(globals: Globals) -> Self {
         MainMenu {
             selection: Selection::default(),
-            opts_menu: OptionsMenu::new(globals.options),
+            opts_menu: OptionsMenu::from_options(globals.options),
             state: MenuState::Normal,
+            ignored: Vec::new(),
+            startup_warnings: Vec::new(),
+            tip: 0,
+            next_tip: Instant::now() + consts::MENU_TIP_PERIOD,
             globals,
         }
     }
 
+    /// Ask the user what to do about each of the given data files that could
+    /// not be parsed, one at a time, before the menu can be used
+    pub(crate) fn recover(&mut self, files: Vec<CorruptFile>) {
+        if files.is_empty() {
+            self.show_startup_warnings();
+        } else {
+            self.state = MenuState::Recovering(files);
+        }
+    }
+
+    /// Show each of the given warnings about problems encountered at startup,
+    /// one at a time, after any corrupt data files have been dealt with
+    pub(crate) fn warn(&mut self, warnings: Vec<Warning>) {
+        self.startup_warnings.extend(warnings);
+        if self.state == MenuState::Normal {
+            self.show_startup_warnings();
+        }
+    }
+
+    /// Start showing the pending startup warnings, if there are any
+    fn show_startup_warnings(&mut self) {
+        if !self.startup_warnings.is_empty() {
+            self.state = MenuState::StartupWarnings(std::mem::take(&mut self.startup_warnings));
+        }
+    }
+
+    /// Show a banner noting that the given errors were ignored while loading
+    /// data files, from which the user can view the errors' details
+    pub(crate) fn note_ignored(&mut self, errors: Vec<Warning>) {
+        self.ignored = errors;
+    }
+
+    /// Return a mutable reference to the screen's global data
+    pub(crate) fn globals_mut(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+
     /// Draw the main menu on the given frame
     pub(crate) fn draw(&self, frame: &mut Frame<'_>) {
         frame.render_widget(self, frame.area());
     }
 
-    /// Receive & handle the next input event.
+    /// Receive & handle the next input event from `events`.
     ///
     /// Returns `Some(screen)` if the application should switch to a different
     /// screen or quit.
-    pub(crate) fn process_input(&mut self) -> std::io::Result<Option<Screen>> {
-        Ok(self.handle_event(read()?))
+    pub(crate) fn process_input(
+        &mut self,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<Option<Screen>> {
+        // Wake up when the tip is due to change.  The tip only changes once
+        // its time is actually up, in case the event source stops waiting
+        // early.
+        if !events.poll(self.next_tip.saturating_duration_since(Instant::now()))?
+            && Instant::now() >= self.next_tip
+        {
+            self.advance_tip();
+            return Ok(None);
+        }
+        Ok(self.handle_event(events.read()?))
+    }
+
+    /// Move on to the next tip, going back to the first after the last
+    fn advance_tip(&mut self) {
+        self.tip += 1;
+        if self.tip >= i18n::tips().len() {
+            self.tip = 0;
+        }
+        self.next_tip = Instant::now() + consts::MENU_TIP_PERIOD;
     }
 
     /// Handle the given input event.
@@ -66,59 +163,414 @@ impl MainMenu {
     /// Returns `Some(screen)` if the application should switch to a different
     /// screen or quit.
     fn handle_event(&mut self, event: Event) -> Option<Screen> {
-        let cmd = Command::from_key_event(event.as_key_press_event()?)?;
+        let ev = event.as_key_press_event()?;
+        let cmd = self
+            .globals
+            .config
+            .layout_direction
+            .flip(Command::from_key_event(ev)?);
         if cmd == Command::Quit {
             return Some(Screen::Quit);
         }
         match self.state {
             MenuState::Normal => match (self.selection, cmd) {
                 (_, Command::Home) => self.select(Selection::PlayButton, None),
-                (_, Command::End) => self.select(Selection::QuitButton, None),
+                (_, Command::End) => self.select(self.last_button(), None),
                 (Selection::PlayButton, Command::Enter) | (_, Command::P) => {
-                    let options = self.opts_menu.to_options();
-                    self.globals.options = options;
-                    match self.globals.config.save_options(options) {
-                        Ok(()) => return Some(Screen::Game(self.play())),
-                        Err(e) => self.state = MenuState::SaveWarning(Warning::from(e)),
-                    }
+                    return self.save_and_play(None);
                 }
-                (Selection::PlayButton, Command::Prev) => self.select(Selection::QuitButton, None),
+                (Selection::PlayButton, Command::Prev) => self.select(self.last_button(), None),
                 (Selection::PlayButton, Command::Down | Command::Next) => {
                     self.select(Selection::Options, Some(true));
                 }
                 (Selection::Options, Command::Up | Command::Prev) => {
-                    if let Some(sel) = self.opts_menu.move_up() {
-                        self.select(sel, None);
+                    self.opts_menu.move_up();
+                    if self.opts_menu.selection().is_none() {
+                        self.select(Selection::PlayButton, None);
                     }
                 }
                 (Selection::Options, Command::Down | Command::Next) => {
-                    if let Some(sel) = self.opts_menu.move_down() {
-                        self.select(sel, None);
+                    self.opts_menu.move_down();
+                    if self.opts_menu.selection().is_none() {
+                        self.select(Selection::ConfigDefaults, None);
                     }
                 }
                 (Selection::Options, Command::Left) => self.opts_menu.move_left(),
                 (Selection::Options, Command::Right) => self.opts_menu.move_right(),
                 (Selection::Options, Command::Space | Command::Enter) => self.opts_menu.toggle(),
-                (Selection::QuitButton, Command::Enter) | (_, Command::Q) => {
-                    return Some(Screen::Quit);
+                (Selection::ConfigDefaults, Command::Space | Command::Enter) => {
+                    self.toggle_config_defaults();
+                }
+                (Selection::ConfigDefaults, Command::Up | Command::Prev) => {
+                    self.select(Selection::Options, Some(false));
+                }
+                (Selection::ConfigDefaults, Command::Down | Command::Next) => {
+                    self.select(Selection::HighScoresButton, None);
+                }
+                (_, Command::E) if !self.ignored.is_empty() => {
+                    self.state = MenuState::IgnoredWarnings(std::mem::take(&mut self.ignored));
+                }
+                (_, Command::U) => {
+                    if let Some(key) = self.opts_menu.undo() {
+                        self.selection = Selection::Options;
+                        self.opts_menu.select(Some(key));
+                    }
+                }
+                (_, Command::Redo) => {
+                    if let Some(key) = self.opts_menu.redo() {
+                        self.selection = Selection::Options;
+                        self.opts_menu.select(Some(key));
+                    }
+                }
+                (_, Command::Save) => self.save_options(),
+                (Selection::HighScoresButton, Command::Enter) | (_, Command::O) => {
+                    return Some(self.high_scores());
+                }
+                (Selection::HighScoresButton, Command::Up | Command::Prev) => {
+                    self.select(Selection::ConfigDefaults, None);
+                }
+                (Selection::HighScoresButton, Command::Down | Command::Next) => {
+                    self.select(Selection::AboutButton, None);
+                }
+                (_, Command::F) => self.open_favorites(),
+                (_, Command::C) => self.open_levels(),
+                (Selection::AboutButton, Command::Enter) | (_, Command::I) => {
+                    return Some(self.about());
+                }
+                (Selection::AboutButton, Command::Up | Command::Prev) => {
+                    self.select(Selection::HighScoresButton, None);
+                }
+                (Selection::AboutButton, Command::Next) if self.globals.kiosk => {
+                    self.select(Selection::PlayButton, None);
+                }
+                (Selection::AboutButton, Command::Down | Command::Next) => {
+                    self.select(self.last_button(), None);
                 }
+                (Selection::QuitButton, Command::Enter) | (_, Command::Q) => return self.quit(),
                 (Selection::QuitButton, Command::Next) => self.select(Selection::PlayButton, None),
                 (Selection::QuitButton, Command::Up | Command::Prev) => {
-                    self.select(Selection::Options, Some(false));
+                    self.select(Selection::AboutButton, None);
                 }
                 _ => (),
             },
-            MenuState::SaveWarning(ref mut warning) => match warning.handle_command(cmd)? {
-                WarningOutcome::Dismissed => return Some(Screen::Game(self.play())),
+            MenuState::SaveWarning(ref mut warning, after) => match warning.handle_key(ev)? {
+                WarningOutcome::Dismissed => match after {
+                    AfterSave::Play(seed) => return Some(self.play(seed)),
+                    AfterSave::Quit => return Some(Screen::Quit),
+                    AfterSave::Stay => self.state = MenuState::Normal,
+                },
                 WarningOutcome::Quit => return Some(Screen::Quit),
             },
+            MenuState::ConfirmQuit(ref mut confirm) => match confirm.handle_command(cmd)? {
+                ConfirmOutcome::Yes => {
+                    let options = self.opts_menu.to_options();
+                    self.globals.options = options;
+                    match self
+                        .globals
+                        .config
+                        .save_options(self.globals.saved_options())
+                    {
+                        Ok(()) => return Some(Screen::Quit),
+                        Err(e) => {
+                            self.state = MenuState::SaveWarning(Warning::from(e), AfterSave::Quit);
+                        }
+                    }
+                }
+                ConfirmOutcome::No | ConfirmOutcome::Quit => return Some(Screen::Quit),
+            },
+            MenuState::Recovering(ref mut files) => {
+                let corrupt = files.first_mut()?;
+                match corrupt.handle_command(cmd)? {
+                    ChoiceOutcome::Chosen(Recovery::Ignore) => self.next_recovery(None),
+                    ChoiceOutcome::Chosen(Recovery::Reset) => {
+                        let r = self.globals.config.reset_file(corrupt.file());
+                        self.next_recovery(r.err().map(Warning::from));
+                    }
+                    ChoiceOutcome::Chosen(Recovery::Quit) | ChoiceOutcome::Quit => {
+                        return Some(Screen::Quit)
+                    }
+                }
+            }
+            MenuState::ResetWarning(ref mut warning, _) => match warning.handle_key(ev)? {
+                WarningOutcome::Dismissed => self.next_recovery(None),
+                WarningOutcome::Quit => return Some(Screen::Quit),
+            },
+            MenuState::Favorites(ref mut picker) => match picker.handle_command(cmd)? {
+                PickerOutcome::Play(fav) => return self.play_favorite(fav),
+                PickerOutcome::Deleted => {
+                    if let Err(e) = self.globals.config.save_favorites(picker.favorites()) {
+                        self.state = MenuState::FavoritesWarning(Warning::from(e));
+                    }
+                }
+                PickerOutcome::Close => self.state = MenuState::Normal,
+                PickerOutcome::Quit => return Some(Screen::Quit),
+            },
+            MenuState::FavoritesWarning(ref mut warning)
+            | MenuState::LevelsWarning(ref mut warning) => match warning.handle_key(ev)? {
+                WarningOutcome::Dismissed => self.state = MenuState::Normal,
+                WarningOutcome::Quit => return Some(Screen::Quit),
+            },
+            MenuState::Levels(ref mut picker) => match picker.handle_command(cmd)? {
+                levels::PickerOutcome::Play(level) => return Some(self.play_level(level)),
+                levels::PickerOutcome::Close => self.state = MenuState::Normal,
+                levels::PickerOutcome::Quit => return Some(Screen::Quit),
+            },
+            MenuState::IgnoredWarnings(ref mut warnings)
+            | MenuState::StartupWarnings(ref mut warnings) => {
+                match warnings.first_mut()?.handle_key(ev)? {
+                    WarningOutcome::Dismissed => {
+                        warnings.remove(0);
+                        if warnings.is_empty() {
+                            self.state = MenuState::Normal;
+                        }
+                    }
+                    WarningOutcome::Quit => return Some(Screen::Quit),
+                }
+            }
         }
         None
     }
 
-    /// Create a new game
-    fn play(&self) -> Game {
-        Game::new(self.globals.clone())
+    /// Finish dealing with the current corrupt data file, showing `warning`
+    /// (if any) before moving on to the next file or to normal operation
+    fn next_recovery(&mut self, warning: Option<Warning>) {
+        let files = match std::mem::replace(&mut self.state, MenuState::Normal) {
+            MenuState::Recovering(mut files) => {
+                if !files.is_empty() {
+                    files.remove(0);
+                }
+                files
+            }
+            MenuState::ResetWarning(_, files) => files,
+            _ => Vec::new(),
+        };
+        match warning {
+            Some(warning) => self.state = MenuState::ResetWarning(warning, files),
+            None => self.recover(files),
+        }
+    }
+
+    /// Save the options selected in the menu and start a game with them,
+    /// seeded with `seed` if it is given.  If saving fails, a warning is
+    /// shown first.
+    fn save_and_play(&mut self, seed: Option<u64>) -> Option<Screen> {
+        let options = self.opts_menu.to_options();
+        self.globals.options = options;
+        match self
+            .globals
+            .config
+            .save_options(self.globals.saved_options())
+        {
+            Ok(()) => Some(self.play(seed)),
+            Err(e) => {
+                self.state = MenuState::SaveWarning(Warning::from(e), AfterSave::Play(seed));
+                None
+            }
+        }
+    }
+
+    /// Save the options selected in the menu without leaving the menu.  If
+    /// saving fails, a warning is shown.
+    fn save_options(&mut self) {
+        self.globals.options = self.opts_menu.to_options();
+        if let Err(e) = self
+            .globals
+            .config
+            .save_options(self.globals.saved_options())
+        {
+            self.state = MenuState::SaveWarning(Warning::from(e), AfterSave::Stay);
+        }
+    }
+
+    /// Switch to the high scores screen, which starts out showing the scores
+    /// for the options selected in the menu
+    fn high_scores(&mut self) -> Screen {
+        self.globals.options = self.opts_menu.to_options();
+        Screen::HighScores(Scoreboard::new(self.globals.clone()))
+    }
+
+    /// Open the list of favorite boards.  If they can't be loaded, a warning
+    /// is shown instead.
+    fn open_favorites(&mut self) {
+        self.state = match self.globals.config.load_favorites() {
+            Ok(favorites) => MenuState::Favorites(FavoritesPicker::new(favorites)),
+            Err(e) => MenuState::FavoritesWarning(Warning::from(e)),
+        };
+    }
+
+    /// Start a game on the given favorite board by setting the options menu
+    /// to the board's options and seeding the game with the board's seed.
+    /// Later games are seeded as directed by the `rng` setting as usual.
+    fn play_favorite(&mut self, fav: Favorite) -> Option<Screen> {
+        self.state = MenuState::Normal;
+        self.opts_menu = OptionsMenu::from_options(fav.options);
+        self.save_and_play(Some(fav.seed))
+    }
+
+    /// Open the list of custom levels in the level packs.  If they can't be
+    /// loaded, a warning is shown instead.
+    fn open_levels(&mut self) {
+        let packs = self
+            .globals
+            .config
+            .levels_dir()
+            .map_err(Warning::from)
+            .and_then(|dir| LevelPack::discover(&dir).map_err(Warning::from));
+        self.state = match packs {
+            Ok(packs) => MenuState::Levels(LevelPicker::new(packs)),
+            Err(warning) => MenuState::LevelsWarning(warning),
+        };
+    }
+
+    /// Start a game on the given custom level with the options selected in
+    /// the menu.  Later games started from the menu are played as usual.
+    fn play_level(&mut self, level: CustomLevel) -> Screen {
+        self.state = MenuState::Normal;
+        self.globals.options = self.opts_menu.to_options();
+        let game = Game::for_level(self.globals.clone(), level);
+        Screen::Game(Box::new(game))
+    }
+
+    /// Switch to the about screen
+    fn about(&mut self) -> Screen {
+        self.globals.options = self.opts_menu.to_options();
+        Screen::About(About::new(self.globals.clone()))
+    }
+
+    /// Return a command palette listing the actions available from the menu,
+    /// or `None` if a pop-up is being shown
+    pub(crate) fn palette(&self) -> Option<Palette> {
+        if !matches!(self.state, MenuState::Normal) {
+            return None;
+        }
+        let toggles = self
+            .opts_menu
+            .settings()
+            .iter()
+            .filter(|(_, value)| matches!(value, OptValue::Bool(_)))
+            .map(|(key, _)| PaletteEntry::new(PaletteAction::Toggle(key)));
+        let entries = std::iter::once(PaletteEntry::new(PaletteAction::Play))
+            .chain(toggles)
+            .chain([
+                PaletteEntry::set_seed(),
+                PaletteEntry::new(PaletteAction::HighScores),
+                PaletteEntry::new(PaletteAction::Favorites),
+                PaletteEntry::new(PaletteAction::Levels),
+                PaletteEntry::new(PaletteAction::About),
+            ])
+            // In kiosk mode, quitting requires the passphrase
+            .chain((!self.globals.kiosk).then(|| PaletteEntry::new(PaletteAction::Quit)))
+            .collect();
+        Some(Palette::new(entries))
+    }
+
+    /// Carry out an action chosen from the command palette.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn run_palette_action(&mut self, action: PaletteAction) -> Option<Screen> {
+        match action {
+            PaletteAction::Play => return self.save_and_play(None),
+            PaletteAction::Toggle(key) => {
+                // Toggle the option through the menu so that the change can
+                // be seen & undone
+                self.select(Selection::Options, None);
+                self.opts_menu.select(Some(key));
+                self.opts_menu.toggle();
+            }
+            PaletteAction::SetSeed(seed) => {
+                self.globals.config.game.rng = RngMode::Seeded(seed);
+            }
+            PaletteAction::HighScores => return Some(self.high_scores()),
+            PaletteAction::Favorites => self.open_favorites(),
+            PaletteAction::Levels => self.open_levels(),
+            PaletteAction::About => return Some(self.about()),
+            PaletteAction::Quit => return self.quit(),
+            PaletteAction::AddFavorite
+            | PaletteAction::Progress
+            | PaletteAction::MainMenu
+            | PaletteAction::Resume
+            | PaletteAction::Restart => (),
+        }
+        None
+    }
+
+    /// Quit the application, first asking whether to save the options if the
+    /// options menu has changes that haven't been written to disk yet.  In
+    /// kiosk mode, quitting requires the passphrase, so no question is asked.
+    fn quit(&mut self) -> Option<Screen> {
+        if !self.globals.kiosk && self.has_unsaved_options() {
+            self.state = MenuState::ConfirmQuit(Confirm::new(tr(Msg::ConfirmSaveOptions)));
+            None
+        } else {
+            Some(Screen::Quit)
+        }
+    }
+
+    /// Returns `true` if options are saved to disk, the "Use config defaults"
+    /// toggle is off, and the options shown in the options menu differ from
+    /// those currently saved
+    fn has_unsaved_options(&self) -> bool {
+        let config = &self.globals.config;
+        config.saves_options()
+            && !self.globals.use_config_defaults
+            && config
+                .load_options()
+                .ok()
+                .map(|loaded| loaded.value.options)
+                != Some(self.opts_menu.to_options())
+    }
+
+    /// Turn the "Use config defaults" toggle on or off and save the change.
+    /// Turning it on resets the options menu to the options in the
+    /// configuration file.
+    fn toggle_config_defaults(&mut self) {
+        self.globals.use_config_defaults = !self.globals.use_config_defaults;
+        if self.globals.use_config_defaults {
+            self.opts_menu = OptionsMenu::from_options(self.globals.config.options);
+        }
+        self.globals.options = self.opts_menu.to_options();
+        if let Err(e) = self
+            .globals
+            .config
+            .save_options(self.globals.saved_options())
+        {
+            self.state = MenuState::SaveWarning(Warning::from(e), AfterSave::Stay);
+        }
+    }
+
+    /// Return a screen containing a new game: a split-board game if one was
+    /// requested on the command line, or else a regular game announcing the
+    /// high score for its options.  If `seed` is given, the game is seeded
+    /// with it instead of as directed by the `rng` setting; otherwise, if a
+    /// custom level was given on the command line, the game is played on
+    /// that.
+    fn play(&self, seed: Option<u64>) -> Screen {
+        let globals = self.globals.clone();
+        if let Some(target) = self.globals.split {
+            let game = match seed {
+                Some(seed) => SplitGame::new_with_rng(globals, target, StdRng::seed_from_u64(seed)),
+                None => SplitGame::new(globals, target),
+            };
+            return Screen::Split(Box::new(game));
+        }
+        let mut game = match (seed, &self.globals.level) {
+            (Some(seed), _) => Game::new_with_seed(globals, seed),
+            (None, Some(level)) => Game::for_level(globals, level.clone()),
+            (None, None) => Game::new(globals),
+        };
+        game.announce_high_score();
+        Screen::Game(Box::new(game))
+    }
+
+    /// Return the last button on the menu: the "Quit" button, or the "About"
+    /// button in kiosk mode, where there is no "Quit" button
+    fn last_button(&self) -> Selection {
+        if self.globals.kiosk {
+            Selection::AboutButton
+        } else {
+            Selection::QuitButton
+        }
     }
 
     /// Select the given form element.  If `selection` is
@@ -129,13 +581,13 @@ impl MainMenu {
         self.selection = selection;
         if selection == Selection::Options {
             if let Some(first) = first_option {
-                self.opts_menu.selection = if first {
+                self.opts_menu.select(if first {
                     Some(OptKey::min())
                 } else {
                     Some(OptKey::max())
-                };
+                });
             } else {
-                self.opts_menu.selection = None;
+                self.opts_menu.select(None);
             }
         }
     }
@@ -148,70 +600,210 @@ impl Widget for &MainMenu {
             Layout::vertical([Constraint::Length(Logo::HEIGHT), Constraint::Fill(1)])
                 .spacing(1)
                 .areas(display);
+        let [main_area, banner_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(u16::from(!self.ignored.is_empty())),
+        ])
+        .areas(main_area);
 
         let [logo_area] = Layout::horizontal([Logo::WIDTH])
             .flex(Flex::Center)
             .areas(logo_area);
-        Logo.render(logo_area, buf);
+        Logo {
+            gradient: self
+                .globals
+                .config
+                .true_color
+                .resolve(self.globals.capabilities.true_color),
+        }
+        .render(logo_area, buf);
 
+        // If there isn't enough room for all of the options, the options menu
+        // is shrunk and scrolls instead
+        let opts_height = OptionsMenu::HEIGHT.min(main_area.height.saturating_sub(10));
         let [_, main_area, _] = Layout::vertical([
             Constraint::Fill(1),
-            Constraint::Length(OptionsMenu::HEIGHT + 4),
+            Constraint::Length(opts_height + 10),
             Constraint::Fill(2),
         ])
         .areas(main_area);
-        let [form_area, instructions_area] =
-            Layout::horizontal([OptionsMenu::WIDTH, Instructions::WIDTH])
+        let direction = self.globals.config.layout_direction;
+        let [form_area, instructions_area] = direction.arrange(
+            Layout::horizontal(direction.arrange([OptionsMenu::WIDTH, Instructions::width()]))
                 .flex(Flex::SpaceAround)
-                .areas(main_area);
+                .areas(main_area),
+        );
 
-        let [play_area, options_area, quit_area] = Layout::vertical([1, OptionsMenu::HEIGHT, 1])
-            .flex(Flex::Start)
-            .spacing(1)
-            .areas(form_area);
+        let [play_area, options_area, defaults_area, scores_area, about_area, quit_area] =
+            Layout::vertical([1, opts_height, 1, 1, 1, 1])
+                .flex(Flex::Start)
+                .spacing(1)
+                .areas(form_area);
 
-        let play_style = if self.selection == Selection::PlayButton {
-            consts::MENU_SELECTION_STYLE
-        } else {
-            Style::new()
-        };
-        Line::from_iter([
-            Span::styled("[Play (", play_style),
-            Span::styled("p", consts::KEY_STYLE.patch(play_style)),
-            Span::styled(")]", play_style),
-        ])
-        .centered()
-        .render(play_area, buf);
+        button(tr(Msg::Play), "p", self.selection == Selection::PlayButton).render(play_area, buf);
 
         let [options_area] = Layout::horizontal([OptionsMenu::WIDTH])
             .flex(Flex::Center)
             .areas(options_area);
-        (&self.opts_menu).render(options_area, buf);
-
-        let qstyle = if self.selection == Selection::QuitButton {
-            consts::MENU_SELECTION_STYLE
-        } else {
-            Style::new()
+        self.opts_menu.render(options_area, buf, direction);
+        // The key for saving the options without playing is shown on the
+        // bottom border of the options menu, on the same side as the title
+        let save_hint = Line::from_iter([
+            Span::raw(format!(" {} (", tr(Msg::SaveOptions))),
+            Span::styled("Ctrl-S", consts::KEY_STYLE),
+            Span::raw(") "),
+        ]);
+        let hint_width = u16::try_from(save_hint.width()).unwrap_or(u16::MAX);
+        let hint_x = match direction {
+            LayoutDirection::LeftToRight => options_area.x.saturating_add(1),
+            LayoutDirection::RightToLeft => options_area
+                .right()
+                .saturating_sub(hint_width.saturating_add(1)),
         };
-        Line::from_iter([
-            Span::styled("[Quit (", qstyle),
-            Span::styled("q", consts::KEY_STYLE.patch(qstyle)),
-            Span::styled(")]", qstyle),
-        ])
-        .centered()
-        .render(quit_area, buf);
+        save_hint.render(
+            Rect::new(
+                hint_x,
+                options_area.bottom().saturating_sub(1),
+                hint_width,
+                1,
+            )
+            .intersection(options_area),
+            buf,
+        );
+        checkbox(
+            tr(Msg::UseConfigDefaults),
+            self.globals.use_config_defaults,
+            self.selection == Selection::ConfigDefaults,
+        )
+        .render(defaults_area, buf);
 
-        let [instructions_area] = Layout::vertical([Instructions::HEIGHT])
-            .flex(Flex::Center)
-            .areas(instructions_area);
+        button(
+            tr(Msg::HighScores),
+            "o",
+            self.selection == Selection::HighScoresButton,
+        )
+        .render(scores_area, buf);
+        button(
+            tr(Msg::About),
+            "i",
+            self.selection == Selection::AboutButton,
+        )
+        .render(about_area, buf);
+        // In kiosk mode, quitting requires the passphrase, so there's no
+        // button for it
+        if !self.globals.kiosk {
+            button(tr(Msg::Quit), "q", self.selection == Selection::QuitButton)
+                .render(quit_area, buf);
+        }
+
+        let [instructions_area, tip_area] =
+            Layout::vertical([Instructions::HEIGHT, MainMenu::TIP_HEIGHT])
+                .flex(Flex::Center)
+                .spacing(1)
+                .areas(instructions_area);
         Instructions.render(instructions_area, buf);
 
-        if let MenuState::SaveWarning(warning) = &self.state {
-            warning.render(display, buf);
+        // Tips are wider than the instructions, so they get all of the space
+        // beside the form
+        let tip_area = match direction {
+            LayoutDirection::LeftToRight => Rect {
+                x: form_area.right(),
+                width: main_area.right().saturating_sub(form_area.right()),
+                ..tip_area
+            },
+            LayoutDirection::RightToLeft => Rect {
+                x: main_area.x,
+                width: form_area.x.saturating_sub(main_area.x),
+                ..tip_area
+            },
+        };
+        if let Some(tip) = i18n::tips().get(self.tip) {
+            Paragraph::new(Line::styled(*tip, consts::TIP_STYLE))
+                .wrap(Wrap { trim: true })
+                .centered()
+                .render(tip_area, buf);
+        }
+
+        if !self.ignored.is_empty() {
+            Line::from_iter([
+                Span::styled(
+                    tr_args(Msg::IgnoredErrors, &[("n", &self.ignored.len())]),
+                    consts::DANGER_STYLE,
+                ),
+                Span::raw(format!(" — {} (", tr(Msg::Details))),
+                Span::styled("e", consts::KEY_STYLE),
+                Span::raw(")"),
+            ])
+            .centered()
+            .render(banner_area, buf);
+        }
+
+        match self.state {
+            MenuState::Normal => (),
+            MenuState::SaveWarning(ref warning, _) | MenuState::ResetWarning(ref warning, _) => {
+                warning.render(display, buf);
+            }
+            MenuState::ConfirmQuit(ref confirm) => confirm.render(display, buf),
+            MenuState::Favorites(ref picker) => picker.render(display, buf),
+            MenuState::FavoritesWarning(ref warning) | MenuState::LevelsWarning(ref warning) => {
+                warning.render(display, buf);
+            }
+            MenuState::Levels(ref picker) => picker.render(display, buf),
+            MenuState::IgnoredWarnings(ref warnings) | MenuState::StartupWarnings(ref warnings) => {
+                if let Some(warning) = warnings.first() {
+                    warning.render(display, buf);
+                }
+            }
+            MenuState::Recovering(ref files) => {
+                if let Some(corrupt) = files.first() {
+                    corrupt.render(display, buf);
+                }
+            }
         }
     }
 }
 
+/// Return a centered `Line` for displaying a button with the given label and
+/// key.  If `selected` is `true`, the button is the currently-selected form
+/// element.
+fn button(label: &'static str, key: &'static str, selected: bool) -> Line<'static> {
+    let style = if selected {
+        theme::selection_style()
+    } else {
+        Style::new()
+    };
+    let [before, after] = theme::button_markers(selected);
+    Line::from_iter([
+        Span::raw(before),
+        Span::styled("[", style),
+        Span::styled(label, style),
+        Span::styled(" (", style),
+        Span::styled(key, consts::KEY_STYLE.patch(style)),
+        Span::styled(")]", style),
+        Span::raw(after),
+    ])
+    .centered()
+}
+
+/// Return a centered `Line` for displaying a checkbox with the given label.
+/// If `selected` is `true`, the checkbox is the currently-selected form
+/// element.
+fn checkbox(label: &'static str, checked: bool, selected: bool) -> Line<'static> {
+    let style = if selected {
+        theme::selection_style()
+    } else {
+        Style::new()
+    };
+    let [before, after] = theme::button_markers(selected);
+    Line::from_iter([
+        Span::raw(before),
+        Span::styled(if checked { "[x] " } else { "[ ] " }, style),
+        Span::styled(label, style),
+        Span::raw(after),
+    ])
+    .centered()
+}
+
 /// An enum of the states that the main menu can be in
 #[derive(Clone, Debug, Eq, PartialEq)]
 enum MenuState {
@@ -219,12 +811,61 @@ enum MenuState {
     Normal,
 
     /// A warning is being displayed about failure to save the chosen options
-    /// to a file.
-    ///
-    /// Because options are only saved when the user selects to play a game,
-    /// after this warning is dismissed, the application will transition to a
-    /// new game.
-    SaveWarning(Warning),
+    /// to a file.  After it is dismissed, the menu carries on with whatever
+    /// the save was done for.
+    SaveWarning(Warning, AfterSave),
+
+    /// The user is being asked whether to save changes to the options before
+    /// quitting
+    ConfirmQuit(Confirm),
+
+    /// The user is being asked what to do about data files that could not be
+    /// parsed at startup, starting with the first file in the `Vec`
+    Recovering(Vec<CorruptFile>),
+
+    /// A warning is being displayed about failure to reset a corrupt data
+    /// file.  After it is dismissed, the user will be asked about the
+    /// remaining files in the `Vec`, if any.
+    ResetWarning(Warning, Vec<CorruptFile>),
+
+    /// The list of favorite boards is being shown
+    Favorites(FavoritesPicker),
+
+    /// A warning is being displayed about failure to load or save the
+    /// favorite boards.  After it is dismissed, the menu returns to normal
+    /// operation.
+    FavoritesWarning(Warning),
+
+    /// The list of custom levels in the level packs is being shown
+    Levels(LevelPicker),
+
+    /// A warning is being displayed about failure to load the level packs.
+    /// After it is dismissed, the menu returns to normal operation.
+    LevelsWarning(Warning),
+
+    /// The user is viewing warnings about errors that were ignored while
+    /// loading data files at startup, starting with the first warning in the
+    /// `Vec`
+    IgnoredWarnings(Vec<Warning>),
+
+    /// The user is viewing warnings about problems encountered at startup,
+    /// starting with the first warning in the `Vec`
+    StartupWarnings(Vec<Warning>),
+}
+
+/// An enum of what the main menu does after an attempt to save the options
+/// fails and the resulting warning is dismissed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AfterSave {
+    /// Start a new game, as the options were saved for playing.  The value
+    /// is the seed of the favorite board being played, if any.
+    Play(Option<u64>),
+
+    /// Quit the application, as the options were saved on the way out
+    Quit,
+
+    /// Return to normal operation, as the user asked to save the options
+    Stay,
 }
 
 /// An enum of the form elements
@@ -237,127 +878,38 @@ enum Selection {
     /// The options sub-menu
     Options,
 
+    /// The "Use config defaults" checkbox
+    ConfigDefaults,
+
+    /// The "[High Scores (o)]" button
+    HighScoresButton,
+
+    /// The "[About (i)]" button
+    AboutButton,
+
     /// The "[Quit (q)]" button
     QuitButton,
 }
 
-/// State of the options sub-menu
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct OptionsMenu {
-    /// If the currently-selected main menu item is an element of this menu,
-    /// then `selection` is `Some(key)`, where `key` is the key of the selected
-    /// item within the `OptionsMenu`.
-    selection: Option<OptKey>,
-
-    /// Option values currently displayed in the submenu
-    settings: EnumMap<OptKey, OptValue>,
-}
+/// The options sub-menu
+type OptionsMenu = SettingsList<OptKey, OptValue>;
 
 impl OptionsMenu {
-    /// The height that should be used for the `Rect` passed to
-    /// `&OptionsMenu::render()`
-    #[allow(clippy::cast_possible_truncation)]
-    const HEIGHT: u16 = (OptKey::LENGTH as u16) + 2 /* for border */;
-
-    /// The width of the horizontal padding on each inner side of the menu
-    /// border
-    const HORIZONTAL_PADDING: u16 = 1;
-
-    /// The number of total display column cells used by the menu pointer
-    const POINTER_WIDTH: u16 = 2;
-
-    /// The number of display column cells between the option names and values
-    const LABEL_VALUE_GUTTER: u16 = 2;
-
-    /// The width that should be used for the `Rect` passed to
-    /// `&OptionsMenu::render()`
-    const WIDTH: u16 = 2 /* for border */ + 2 * Self::HORIZONTAL_PADDING + Self::POINTER_WIDTH + OptKey::DISPLAY_WIDTH + Self::LABEL_VALUE_GUTTER + OptValue::DISPLAY_WIDTH;
-
     /// Create a new `OptionsMenu` with the given `Options` as the initial
     /// values
-    fn new(options: Options) -> Self {
+    fn from_options(options: Options) -> Self {
         let settings = EnumMap::from_iter(OptKey::iter().map(|key| (key, options.get(key))));
-        OptionsMenu {
-            selection: None,
-            settings,
-        }
+        SettingsList::new(Msg::OptionsTitle, settings)
     }
 
     /// Return the `Options` currently selected in the menu
     fn to_options(&self) -> Options {
         let mut opts = Options::default();
-        for key in OptKey::iter() {
-            opts.set(key, self.settings[key]);
+        for (key, &value) in self.settings() {
+            opts.set(key, value);
         }
         opts
     }
-
-    /// Select the previous option in the submenu.  If there is no previous
-    /// item, return the form item to move the selection to instead.
-    fn move_up(&mut self) -> Option<Selection> {
-        self.selection = self.selection?.prev();
-        self.selection.is_none().then_some(Selection::PlayButton)
-    }
-
-    /// Select the next option in the submenu.  If there is no next item,
-    /// return the form item to move the selection to instead.
-    fn move_down(&mut self) -> Option<Selection> {
-        self.selection = self.selection?.next();
-        self.selection.is_none().then_some(Selection::QuitButton)
-    }
-
-    /// Respond to a "Left" input by decreasing or unsetting the current
-    /// option, if possible
-    fn move_left(&mut self) {
-        if let Some(sel) = self.selection {
-            self.settings[sel].decrease();
-        }
-    }
-
-    /// Respond to a "Right" input by increasing or setting the current
-    /// option, if possible
-    fn move_right(&mut self) {
-        if let Some(sel) = self.selection {
-            self.settings[sel].increase();
-        }
-    }
-
-    /// Toggle the current option, if possible
-    fn toggle(&mut self) {
-        if let Some(sel) = self.selection {
-            self.settings[sel].toggle();
-        }
-    }
-}
-
-impl Widget for &OptionsMenu {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let block = Block::bordered()
-            .title(" Options: ")
-            .padding(Padding::horizontal(OptionsMenu::HORIZONTAL_PADDING));
-        let menu_area = block.inner(area);
-        block.render(area, buf);
-        for ((key, value), row) in OptKey::iter()
-            .map(|key| (key, self.settings[key]))
-            .zip(menu_area.rows())
-        {
-            let selected = Some(key) == self.selection;
-            let style = if selected {
-                consts::MENU_SELECTION_STYLE
-            } else {
-                Style::new()
-            };
-            let s = format!(
-                "{pointer:pwidth$}{key:lwidth$}{space:gutter$}{value}",
-                pointer = if selected { "»" } else { "" },
-                pwidth = usize::from(OptionsMenu::POINTER_WIDTH),
-                lwidth = usize::from(OptKey::DISPLAY_WIDTH),
-                space = "",
-                gutter = usize::from(OptionsMenu::LABEL_VALUE_GUTTER),
-            );
-            Span::styled(s, style).render(row, buf);
-        }
-    }
 }
 
 #[cfg(test)]
@@ -366,7 +918,10 @@ mod tests {
 
     mod main_menu {
         use super::*;
+        use crate::migrations::SaveFile;
+        use crate::util::LoadError;
         use crossterm::event::KeyCode;
+        use enum_map::Enum;
         use ratatui::{buffer::Buffer, layout::Rect};
 
         #[test]
@@ -375,57 +930,21 @@ mod tests {
             let area = Rect::new(0, 0, 80, 24);
             let mut buffer = Buffer::empty(area);
             menu.render(area, &mut buffer);
-            #[rustfmt::skip]
-            let mut expected = Buffer::with_lines([
-                 "                    ____       _   ____              _                          ",
-                r"                   |  _ \ __ _| |_/ ___| _ __   __ _| | _____                   ",
-                r"                   | |_) / _` | __\___ \| '_ \ / _` | |/ / _ \                  ",
-                 "                   |  _ < (_| | |_ ___) | | | | (_| |   <  __/                  ",
-                r"                   |_| \_\__,_|\__|____/|_| |_|\__,_|_|\_\___|                  ",
-                 "                                                                                ",
-                 "                                 ⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬<  ●                               ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                    [Play (p)]                                                  ",
-                 "                                                                                ",
-                 "           ┌ Options: ────────────────┐          Move the snake with:           ",
-                 "           │   Wraparound     [ ]     │                 ← ↓ ↑ →                 ",
-                 "           │   Obstacles      [ ]     │             or: h j k l                 ",
-                 "           │   Fruits      ◁   1    ▶ │             or: a s w d                 ",
-                 "           │   Level Size  ◀ Large  ▷ │             or: 4 2 8 6                 ",
-                 "           └──────────────────────────┘          Eat the fruit, but             ",
-                 "                                                 don't hit yourself!            ",
-                 "                    [Quit (q)]                                                  ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-            ]);
-            expected.set_style(Rect::new(19, 0, 15, 5), consts::FRUIT_STYLE); // "Rat"
-            expected.set_style(Rect::new(34, 0, 28, 5), consts::SNAKE_STYLE); // "Snake"
-            expected.set_style(Rect::new(33, 6, 13, 1), consts::SNAKE_STYLE); // ⚬⚬…⚬<
-            expected.set_style(Rect::new(48, 6, 1, 1), consts::FRUIT_STYLE); // fruit in logo
-            expected.set_style(Rect::new(27, 10, 1, 1), consts::KEY_STYLE); // `p`
-            expected.set_style(Rect::new(20, 10, 10, 1), consts::MENU_SELECTION_STYLE); // Play button
-            expected.set_style(Rect::new(27, 19, 1, 1), consts::KEY_STYLE); // `q`
-            expected.set_style(Rect::new(56, 13, 1, 1), consts::KEY_STYLE); // `←`
-            expected.set_style(Rect::new(58, 13, 1, 1), consts::KEY_STYLE); // `↓`
-            expected.set_style(Rect::new(60, 13, 1, 1), consts::KEY_STYLE); // `↑`
-            expected.set_style(Rect::new(62, 13, 1, 1), consts::KEY_STYLE); // `→`
-            expected.set_style(Rect::new(56, 14, 1, 1), consts::KEY_STYLE); // `h`
-            expected.set_style(Rect::new(58, 14, 1, 1), consts::KEY_STYLE); // `j`
-            expected.set_style(Rect::new(60, 14, 1, 1), consts::KEY_STYLE); // `k`
-            expected.set_style(Rect::new(62, 14, 1, 1), consts::KEY_STYLE); // `l`
-            expected.set_style(Rect::new(56, 15, 1, 1), consts::KEY_STYLE); // `a`
-            expected.set_style(Rect::new(58, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(60, 15, 1, 1), consts::KEY_STYLE); // `w`
-            expected.set_style(Rect::new(62, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(56, 16, 1, 1), consts::KEY_STYLE); // `4`
-            expected.set_style(Rect::new(58, 16, 1, 1), consts::KEY_STYLE); // `2`
-            expected.set_style(Rect::new(60, 16, 1, 1), consts::KEY_STYLE); // `8`
-            expected.set_style(Rect::new(62, 16, 1, 1), consts::KEY_STYLE); // `6`
-            pretty_assertions::assert_eq!(buffer, expected);
+            crate::snapshot::assert_snapshot("menu/draw_initial", &buffer);
+        }
+
+        #[test]
+        fn scroll_options() {
+            let area = Rect::new(0, 0, 80, 24);
+            let mut menu = MainMenu::new(Globals::default());
+            for _ in 0..OptKey::LENGTH {
+                assert!(menu
+                    .handle_event(Event::Key(KeyCode::Down.into()))
+                    .is_none());
+            }
+            let mut buffer = Buffer::empty(area);
+            menu.render(area, &mut buffer);
+            crate::snapshot::assert_snapshot("menu/scroll_options", &buffer);
         }
 
         #[test]
@@ -437,114 +956,14 @@ mod tests {
                 .is_none());
             let mut buffer = Buffer::empty(area);
             menu.render(area, &mut buffer);
-            #[rustfmt::skip]
-            let mut expected = Buffer::with_lines([
-                 "                    ____       _   ____              _                          ",
-                r"                   |  _ \ __ _| |_/ ___| _ __   __ _| | _____                   ",
-                r"                   | |_) / _` | __\___ \| '_ \ / _` | |/ / _ \                  ",
-                 "                   |  _ < (_| | |_ ___) | | | | (_| |   <  __/                  ",
-                r"                   |_| \_\__,_|\__|____/|_| |_|\__,_|_|\_\___|                  ",
-                 "                                                                                ",
-                 "                                 ⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬<  ●                               ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                    [Play (p)]                                                  ",
-                 "                                                                                ",
-                 "           ┌ Options: ────────────────┐          Move the snake with:           ",
-                 "           │ » Wraparound     [ ]     │                 ← ↓ ↑ →                 ",
-                 "           │   Obstacles      [ ]     │             or: h j k l                 ",
-                 "           │   Fruits      ◁   1    ▶ │             or: a s w d                 ",
-                 "           │   Level Size  ◀ Large  ▷ │             or: 4 2 8 6                 ",
-                 "           └──────────────────────────┘          Eat the fruit, but             ",
-                 "                                                 don't hit yourself!            ",
-                 "                    [Quit (q)]                                                  ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-            ]);
-            expected.set_style(Rect::new(19, 0, 15, 5), consts::FRUIT_STYLE); // "Rat"
-            expected.set_style(Rect::new(34, 0, 28, 5), consts::SNAKE_STYLE); // "Snake"
-            expected.set_style(Rect::new(33, 6, 13, 1), consts::SNAKE_STYLE); // ⚬⚬…⚬<
-            expected.set_style(Rect::new(48, 6, 1, 1), consts::FRUIT_STYLE); // fruit in logo
-            expected.set_style(Rect::new(27, 10, 1, 1), consts::KEY_STYLE); // `p`
-            expected.set_style(Rect::new(13, 13, 24, 1), consts::MENU_SELECTION_STYLE); // "Wraparound" option
-            expected.set_style(Rect::new(27, 19, 1, 1), consts::KEY_STYLE); // `q`
-            expected.set_style(Rect::new(56, 13, 1, 1), consts::KEY_STYLE); // `←`
-            expected.set_style(Rect::new(58, 13, 1, 1), consts::KEY_STYLE); // `↓`
-            expected.set_style(Rect::new(60, 13, 1, 1), consts::KEY_STYLE); // `↑`
-            expected.set_style(Rect::new(62, 13, 1, 1), consts::KEY_STYLE); // `→`
-            expected.set_style(Rect::new(56, 14, 1, 1), consts::KEY_STYLE); // `h`
-            expected.set_style(Rect::new(58, 14, 1, 1), consts::KEY_STYLE); // `j`
-            expected.set_style(Rect::new(60, 14, 1, 1), consts::KEY_STYLE); // `k`
-            expected.set_style(Rect::new(62, 14, 1, 1), consts::KEY_STYLE); // `l`
-            expected.set_style(Rect::new(56, 15, 1, 1), consts::KEY_STYLE); // `a`
-            expected.set_style(Rect::new(58, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(60, 15, 1, 1), consts::KEY_STYLE); // `w`
-            expected.set_style(Rect::new(62, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(56, 16, 1, 1), consts::KEY_STYLE); // `4`
-            expected.set_style(Rect::new(58, 16, 1, 1), consts::KEY_STYLE); // `2`
-            expected.set_style(Rect::new(60, 16, 1, 1), consts::KEY_STYLE); // `8`
-            expected.set_style(Rect::new(62, 16, 1, 1), consts::KEY_STYLE); // `6`
-            pretty_assertions::assert_eq!(buffer, expected);
+            crate::snapshot::assert_snapshot("menu/interact_options-1", &buffer);
 
             assert!(menu
                 .handle_event(Event::Key(KeyCode::Char(' ').into()))
                 .is_none());
             let mut buffer = Buffer::empty(area);
             menu.render(area, &mut buffer);
-            #[rustfmt::skip]
-            let mut expected = Buffer::with_lines([
-                 "                    ____       _   ____              _                          ",
-                r"                   |  _ \ __ _| |_/ ___| _ __   __ _| | _____                   ",
-                r"                   | |_) / _` | __\___ \| '_ \ / _` | |/ / _ \                  ",
-                 "                   |  _ < (_| | |_ ___) | | | | (_| |   <  __/                  ",
-                r"                   |_| \_\__,_|\__|____/|_| |_|\__,_|_|\_\___|                  ",
-                 "                                                                                ",
-                 "                                 ⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬<  ●                               ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                    [Play (p)]                                                  ",
-                 "                                                                                ",
-                 "           ┌ Options: ────────────────┐          Move the snake with:           ",
-                 "           │ » Wraparound     [✓]     │                 ← ↓ ↑ →                 ",
-                 "           │   Obstacles      [ ]     │             or: h j k l                 ",
-                 "           │   Fruits      ◁   1    ▶ │             or: a s w d                 ",
-                 "           │   Level Size  ◀ Large  ▷ │             or: 4 2 8 6                 ",
-                 "           └──────────────────────────┘          Eat the fruit, but             ",
-                 "                                                 don't hit yourself!            ",
-                 "                    [Quit (q)]                                                  ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-            ]);
-            expected.set_style(Rect::new(19, 0, 15, 5), consts::FRUIT_STYLE); // "Rat"
-            expected.set_style(Rect::new(34, 0, 28, 5), consts::SNAKE_STYLE); // "Snake"
-            expected.set_style(Rect::new(33, 6, 13, 1), consts::SNAKE_STYLE); // ⚬⚬…⚬<
-            expected.set_style(Rect::new(48, 6, 1, 1), consts::FRUIT_STYLE); // fruit in logo
-            expected.set_style(Rect::new(27, 10, 1, 1), consts::KEY_STYLE); // `p`
-            expected.set_style(Rect::new(13, 13, 24, 1), consts::MENU_SELECTION_STYLE); // "Wraparound" option
-            expected.set_style(Rect::new(27, 19, 1, 1), consts::KEY_STYLE); // `q`
-            expected.set_style(Rect::new(56, 13, 1, 1), consts::KEY_STYLE); // `←`
-            expected.set_style(Rect::new(58, 13, 1, 1), consts::KEY_STYLE); // `↓`
-            expected.set_style(Rect::new(60, 13, 1, 1), consts::KEY_STYLE); // `↑`
-            expected.set_style(Rect::new(62, 13, 1, 1), consts::KEY_STYLE); // `→`
-            expected.set_style(Rect::new(56, 14, 1, 1), consts::KEY_STYLE); // `h`
-            expected.set_style(Rect::new(58, 14, 1, 1), consts::KEY_STYLE); // `j`
-            expected.set_style(Rect::new(60, 14, 1, 1), consts::KEY_STYLE); // `k`
-            expected.set_style(Rect::new(62, 14, 1, 1), consts::KEY_STYLE); // `l`
-            expected.set_style(Rect::new(56, 15, 1, 1), consts::KEY_STYLE); // `a`
-            expected.set_style(Rect::new(58, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(60, 15, 1, 1), consts::KEY_STYLE); // `w`
-            expected.set_style(Rect::new(62, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(56, 16, 1, 1), consts::KEY_STYLE); // `4`
-            expected.set_style(Rect::new(58, 16, 1, 1), consts::KEY_STYLE); // `2`
-            expected.set_style(Rect::new(60, 16, 1, 1), consts::KEY_STYLE); // `8`
-            expected.set_style(Rect::new(62, 16, 1, 1), consts::KEY_STYLE); // `6`
-            pretty_assertions::assert_eq!(buffer, expected);
+            crate::snapshot::assert_snapshot("menu/interact_options-2", &buffer);
 
             assert!(menu
                 .handle_event(Event::Key(KeyCode::Down.into()))
@@ -560,171 +979,21 @@ mod tests {
                 .is_none());
             let mut buffer = Buffer::empty(area);
             menu.render(area, &mut buffer);
-            #[rustfmt::skip]
-            let mut expected = Buffer::with_lines([
-                 "                    ____       _   ____              _                          ",
-                r"                   |  _ \ __ _| |_/ ___| _ __   __ _| | _____                   ",
-                r"                   | |_) / _` | __\___ \| '_ \ / _` | |/ / _ \                  ",
-                 "                   |  _ < (_| | |_ ___) | | | | (_| |   <  __/                  ",
-                r"                   |_| \_\__,_|\__|____/|_| |_|\__,_|_|\_\___|                  ",
-                 "                                                                                ",
-                 "                                 ⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬<  ●                               ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                    [Play (p)]                                                  ",
-                 "                                                                                ",
-                 "           ┌ Options: ────────────────┐          Move the snake with:           ",
-                 "           │   Wraparound     [✓]     │                 ← ↓ ↑ →                 ",
-                 "           │   Obstacles      [ ]     │             or: h j k l                 ",
-                 "           │   Fruits      ◁   1    ▶ │             or: a s w d                 ",
-                 "           │ » Level Size  ◀ Large  ▷ │             or: 4 2 8 6                 ",
-                 "           └──────────────────────────┘          Eat the fruit, but             ",
-                 "                                                 don't hit yourself!            ",
-                 "                    [Quit (q)]                                                  ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-            ]);
-            expected.set_style(Rect::new(19, 0, 15, 5), consts::FRUIT_STYLE); // "Rat"
-            expected.set_style(Rect::new(34, 0, 28, 5), consts::SNAKE_STYLE); // "Snake"
-            expected.set_style(Rect::new(33, 6, 13, 1), consts::SNAKE_STYLE); // ⚬⚬…⚬<
-            expected.set_style(Rect::new(48, 6, 1, 1), consts::FRUIT_STYLE); // fruit in logo
-            expected.set_style(Rect::new(27, 10, 1, 1), consts::KEY_STYLE); // `p`
-            expected.set_style(Rect::new(13, 16, 24, 1), consts::MENU_SELECTION_STYLE); // "Level Size" option
-            expected.set_style(Rect::new(27, 19, 1, 1), consts::KEY_STYLE); // `q`
-            expected.set_style(Rect::new(56, 13, 1, 1), consts::KEY_STYLE); // `←`
-            expected.set_style(Rect::new(58, 13, 1, 1), consts::KEY_STYLE); // `↓`
-            expected.set_style(Rect::new(60, 13, 1, 1), consts::KEY_STYLE); // `↑`
-            expected.set_style(Rect::new(62, 13, 1, 1), consts::KEY_STYLE); // `→`
-            expected.set_style(Rect::new(56, 14, 1, 1), consts::KEY_STYLE); // `h`
-            expected.set_style(Rect::new(58, 14, 1, 1), consts::KEY_STYLE); // `j`
-            expected.set_style(Rect::new(60, 14, 1, 1), consts::KEY_STYLE); // `k`
-            expected.set_style(Rect::new(62, 14, 1, 1), consts::KEY_STYLE); // `l`
-            expected.set_style(Rect::new(56, 15, 1, 1), consts::KEY_STYLE); // `a`
-            expected.set_style(Rect::new(58, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(60, 15, 1, 1), consts::KEY_STYLE); // `w`
-            expected.set_style(Rect::new(62, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(56, 16, 1, 1), consts::KEY_STYLE); // `4`
-            expected.set_style(Rect::new(58, 16, 1, 1), consts::KEY_STYLE); // `2`
-            expected.set_style(Rect::new(60, 16, 1, 1), consts::KEY_STYLE); // `8`
-            expected.set_style(Rect::new(62, 16, 1, 1), consts::KEY_STYLE); // `6`
-            pretty_assertions::assert_eq!(buffer, expected);
+            crate::snapshot::assert_snapshot("menu/interact_options-3", &buffer);
 
             assert!(menu
                 .handle_event(Event::Key(KeyCode::Left.into()))
                 .is_none());
             let mut buffer = Buffer::empty(area);
             menu.render(area, &mut buffer);
-            #[rustfmt::skip]
-            let mut expected = Buffer::with_lines([
-                 "                    ____       _   ____              _                          ",
-                r"                   |  _ \ __ _| |_/ ___| _ __   __ _| | _____                   ",
-                r"                   | |_) / _` | __\___ \| '_ \ / _` | |/ / _ \                  ",
-                 "                   |  _ < (_| | |_ ___) | | | | (_| |   <  __/                  ",
-                r"                   |_| \_\__,_|\__|____/|_| |_|\__,_|_|\_\___|                  ",
-                 "                                                                                ",
-                 "                                 ⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬<  ●                               ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                    [Play (p)]                                                  ",
-                 "                                                                                ",
-                 "           ┌ Options: ────────────────┐          Move the snake with:           ",
-                 "           │   Wraparound     [✓]     │                 ← ↓ ↑ →                 ",
-                 "           │   Obstacles      [ ]     │             or: h j k l                 ",
-                 "           │   Fruits      ◁   1    ▶ │             or: a s w d                 ",
-                 "           │ » Level Size  ◀ Medium ▶ │             or: 4 2 8 6                 ",
-                 "           └──────────────────────────┘          Eat the fruit, but             ",
-                 "                                                 don't hit yourself!            ",
-                 "                    [Quit (q)]                                                  ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-            ]);
-            expected.set_style(Rect::new(19, 0, 15, 5), consts::FRUIT_STYLE); // "Rat"
-            expected.set_style(Rect::new(34, 0, 28, 5), consts::SNAKE_STYLE); // "Snake"
-            expected.set_style(Rect::new(33, 6, 13, 1), consts::SNAKE_STYLE); // ⚬⚬…⚬<
-            expected.set_style(Rect::new(48, 6, 1, 1), consts::FRUIT_STYLE); // fruit in logo
-            expected.set_style(Rect::new(27, 10, 1, 1), consts::KEY_STYLE); // `p`
-            expected.set_style(Rect::new(13, 16, 24, 1), consts::MENU_SELECTION_STYLE); // "Level Size" option
-            expected.set_style(Rect::new(27, 19, 1, 1), consts::KEY_STYLE); // `q`
-            expected.set_style(Rect::new(56, 13, 1, 1), consts::KEY_STYLE); // `←`
-            expected.set_style(Rect::new(58, 13, 1, 1), consts::KEY_STYLE); // `↓`
-            expected.set_style(Rect::new(60, 13, 1, 1), consts::KEY_STYLE); // `↑`
-            expected.set_style(Rect::new(62, 13, 1, 1), consts::KEY_STYLE); // `→`
-            expected.set_style(Rect::new(56, 14, 1, 1), consts::KEY_STYLE); // `h`
-            expected.set_style(Rect::new(58, 14, 1, 1), consts::KEY_STYLE); // `j`
-            expected.set_style(Rect::new(60, 14, 1, 1), consts::KEY_STYLE); // `k`
-            expected.set_style(Rect::new(62, 14, 1, 1), consts::KEY_STYLE); // `l`
-            expected.set_style(Rect::new(56, 15, 1, 1), consts::KEY_STYLE); // `a`
-            expected.set_style(Rect::new(58, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(60, 15, 1, 1), consts::KEY_STYLE); // `w`
-            expected.set_style(Rect::new(62, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(56, 16, 1, 1), consts::KEY_STYLE); // `4`
-            expected.set_style(Rect::new(58, 16, 1, 1), consts::KEY_STYLE); // `2`
-            expected.set_style(Rect::new(60, 16, 1, 1), consts::KEY_STYLE); // `8`
-            expected.set_style(Rect::new(62, 16, 1, 1), consts::KEY_STYLE); // `6`
-            pretty_assertions::assert_eq!(buffer, expected);
+            crate::snapshot::assert_snapshot("menu/interact_options-4", &buffer);
 
             assert!(menu
                 .handle_event(Event::Key(KeyCode::Left.into()))
                 .is_none());
             let mut buffer = Buffer::empty(area);
             menu.render(area, &mut buffer);
-            #[rustfmt::skip]
-            let mut expected = Buffer::with_lines([
-                 "                    ____       _   ____              _                          ",
-                r"                   |  _ \ __ _| |_/ ___| _ __   __ _| | _____                   ",
-                r"                   | |_) / _` | __\___ \| '_ \ / _` | |/ / _ \                  ",
-                 "                   |  _ < (_| | |_ ___) | | | | (_| |   <  __/                  ",
-                r"                   |_| \_\__,_|\__|____/|_| |_|\__,_|_|\_\___|                  ",
-                 "                                                                                ",
-                 "                                 ⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬⚬<  ●                               ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                    [Play (p)]                                                  ",
-                 "                                                                                ",
-                 "           ┌ Options: ────────────────┐          Move the snake with:           ",
-                 "           │   Wraparound     [✓]     │                 ← ↓ ↑ →                 ",
-                 "           │   Obstacles      [ ]     │             or: h j k l                 ",
-                 "           │   Fruits      ◁   1    ▶ │             or: a s w d                 ",
-                 "           │ » Level Size  ◁ Small  ▶ │             or: 4 2 8 6                 ",
-                 "           └──────────────────────────┘          Eat the fruit, but             ",
-                 "                                                 don't hit yourself!            ",
-                 "                    [Quit (q)]                                                  ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-                 "                                                                                ",
-            ]);
-            expected.set_style(Rect::new(19, 0, 15, 5), consts::FRUIT_STYLE); // "Rat"
-            expected.set_style(Rect::new(34, 0, 28, 5), consts::SNAKE_STYLE); // "Snake"
-            expected.set_style(Rect::new(33, 6, 13, 1), consts::SNAKE_STYLE); // ⚬⚬…⚬<
-            expected.set_style(Rect::new(48, 6, 1, 1), consts::FRUIT_STYLE); // fruit in logo
-            expected.set_style(Rect::new(27, 10, 1, 1), consts::KEY_STYLE); // `p`
-            expected.set_style(Rect::new(13, 16, 24, 1), consts::MENU_SELECTION_STYLE); // "Level Size" option
-            expected.set_style(Rect::new(27, 19, 1, 1), consts::KEY_STYLE); // `q`
-            expected.set_style(Rect::new(56, 13, 1, 1), consts::KEY_STYLE); // `←`
-            expected.set_style(Rect::new(58, 13, 1, 1), consts::KEY_STYLE); // `↓`
-            expected.set_style(Rect::new(60, 13, 1, 1), consts::KEY_STYLE); // `↑`
-            expected.set_style(Rect::new(62, 13, 1, 1), consts::KEY_STYLE); // `→`
-            expected.set_style(Rect::new(56, 14, 1, 1), consts::KEY_STYLE); // `h`
-            expected.set_style(Rect::new(58, 14, 1, 1), consts::KEY_STYLE); // `j`
-            expected.set_style(Rect::new(60, 14, 1, 1), consts::KEY_STYLE); // `k`
-            expected.set_style(Rect::new(62, 14, 1, 1), consts::KEY_STYLE); // `l`
-            expected.set_style(Rect::new(56, 15, 1, 1), consts::KEY_STYLE); // `a`
-            expected.set_style(Rect::new(58, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(60, 15, 1, 1), consts::KEY_STYLE); // `w`
-            expected.set_style(Rect::new(62, 15, 1, 1), consts::KEY_STYLE); // `s`
-            expected.set_style(Rect::new(56, 16, 1, 1), consts::KEY_STYLE); // `4`
-            expected.set_style(Rect::new(58, 16, 1, 1), consts::KEY_STYLE); // `2`
-            expected.set_style(Rect::new(60, 16, 1, 1), consts::KEY_STYLE); // `8`
-            expected.set_style(Rect::new(62, 16, 1, 1), consts::KEY_STYLE); // `6`
-            pretty_assertions::assert_eq!(buffer, expected);
+            crate::snapshot::assert_snapshot("menu/interact_options-5", &buffer);
         }
 
         /// Test that tabbing to the end of the options menu and then tabbing
@@ -733,27 +1002,454 @@ mod tests {
         #[test]
         fn tab_wraparound() {
             let mut menu = MainMenu::new(Globals::default());
-            assert_eq!(menu.opts_menu.selection, None);
+            assert_eq!(menu.opts_menu.selection(), None);
             for _ in OptKey::iter() {
                 assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
             }
-            assert_eq!(menu.opts_menu.selection, Some(OptKey::max()));
+            assert_eq!(menu.opts_menu.selection(), Some(OptKey::max()));
+            assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
+            assert_eq!(menu.opts_menu.selection(), None);
+            assert_eq!(menu.selection, Selection::ConfigDefaults);
+            assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
+            assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
+            assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
             assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
-            assert_eq!(menu.opts_menu.selection, None);
             assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
+            assert_eq!(menu.opts_menu.selection(), Some(OptKey::min()));
+        }
+
+        #[test]
+        fn kiosk_no_quit_button() {
+            let mut menu = MainMenu::new(Globals {
+                kiosk: true,
+                ..Globals::default()
+            });
+            let buffer = crate::snapshot::render(&menu);
+            let text = (0..buffer.area.height)
+                .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+                .map(|pos| buffer[pos].symbol())
+                .collect::<String>();
+            assert!(text.contains("About"));
+            assert!(!text.contains("Quit"));
+            assert!(menu.handle_event(Event::Key(KeyCode::End.into())).is_none());
+            assert_eq!(menu.selection, Selection::AboutButton);
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Down.into()))
+                .is_none());
+            assert_eq!(menu.selection, Selection::AboutButton);
+            assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
+            assert_eq!(menu.selection, Selection::PlayButton);
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::BackTab.into()))
+                .is_none());
+            assert_eq!(menu.selection, Selection::AboutButton);
+            // Nor is quitting offered in the command palette
+            let mut palette = menu.palette().unwrap();
+            for c in "quit".chars() {
+                palette.handle_event(Event::Key(KeyCode::Char(c).into()));
+            }
+            assert_eq!(
+                palette.handle_event(Event::Key(KeyCode::Enter.into())),
+                None
+            );
+        }
+
+        #[test]
+        fn about_button() {
+            let mut menu = MainMenu::new(Globals::default());
+            menu.select(Selection::QuitButton, None);
+            assert!(menu.handle_event(Event::Key(KeyCode::Up.into())).is_none());
+            assert_eq!(menu.selection, Selection::AboutButton);
+            assert!(matches!(
+                menu.handle_event(Event::Key(KeyCode::Enter.into())),
+                Some(Screen::About(_))
+            ));
+            assert!(matches!(
+                menu.handle_event(Event::Key(KeyCode::Char('i').into())),
+                Some(Screen::About(_))
+            ));
+        }
+
+        #[test]
+        fn rotate_tips() {
+            let mut menu = MainMenu::new(Globals::default());
+            let tips = i18n::tips();
+            // The tip doesn't change when an event arrives before its time is
+            // up
+            let mut events = crate::input::ScriptedEvents::new([Event::FocusGained]);
+            assert!(menu.process_input(&mut events).unwrap().is_none());
+            assert_eq!(menu.tip, 0);
+            for i in 1..=tips.len() {
+                menu.next_tip = Instant::now();
+                assert!(menu.process_input(&mut events).unwrap().is_none());
+                assert_eq!(menu.tip, i % tips.len());
+                assert!(menu.next_tip > Instant::now());
+            }
+        }
+
+        #[test]
+        fn draw_corrupt_file() {
+            let mut menu = MainMenu::new(Globals::default());
+            let e = serde_json::from_str::<serde_json::Value>("{not json").unwrap_err();
+            menu.recover(vec![CorruptFile::new(
+                SaveFile::Options,
+                LoadError::deserialize("options", e),
+            )]);
+            let buffer = crate::snapshot::render(&menu);
+            crate::snapshot::assert_snapshot("menu/draw_corrupt_file", &buffer);
+        }
+
+        #[test]
+        fn view_ignored_errors() {
+            let mut menu = MainMenu::new(Globals::default());
+            let errors = ["{not json", "42"]
+                .into_iter()
+                .map(|s| {
+                    let e = serde_json::from_str::<Options>(s).unwrap_err();
+                    Warning::from(LoadError::deserialize("options", e))
+                })
+                .collect();
+            menu.note_ignored(errors);
+            let buffer = crate::snapshot::render(&menu);
+            crate::snapshot::assert_snapshot("menu/view_ignored_errors", &buffer);
+            // The menu can be used as normal while the banner is shown
             assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
-            assert_eq!(menu.opts_menu.selection, Some(OptKey::min()));
+            assert_eq!(menu.selection, Selection::Options);
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Char('e').into()))
+                .is_none());
+            assert!(menu.ignored.is_empty());
+            assert!(matches!(menu.state, MenuState::IgnoredWarnings(ref w) if w.len() == 2));
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Enter.into()))
+                .is_none());
+            assert!(matches!(menu.state, MenuState::IgnoredWarnings(ref w) if w.len() == 1));
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Enter.into()))
+                .is_none());
+            assert_eq!(menu.state, MenuState::Normal);
+            // Once viewed, the warnings can't be brought up again
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Char('e').into()))
+                .is_none());
+            assert_eq!(menu.state, MenuState::Normal);
+        }
+
+        #[test]
+        fn startup_warnings_after_recovery() {
+            let mut menu = MainMenu::new(Globals::default());
+            let e = serde_json::from_str::<serde_json::Value>("{not json").unwrap_err();
+            menu.recover(vec![CorruptFile::new(
+                SaveFile::Options,
+                LoadError::deserialize("options", e),
+            )]);
+            let src = br#"{"version": 99, "scores": []}"#;
+            let e = crate::migrations::from_json::<serde_json::Value>(SaveFile::HighScores, src)
+                .unwrap_err();
+            menu.warn(vec![Warning::from(e)]);
+            assert!(matches!(menu.state, MenuState::Recovering(_)));
+            assert!(menu.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
+            assert!(matches!(menu.state, MenuState::StartupWarnings(ref w) if w.len() == 1));
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Enter.into()))
+                .is_none());
+            assert_eq!(menu.state, MenuState::Normal);
+        }
+
+        #[test]
+        fn reset_corrupt_files() {
+            let tmpdir = tempfile::TempDir::new().unwrap();
+            let globals = Globals::in_tempdir(&tmpdir, "");
+            let options_file = tmpdir.path().join("options.json");
+            let scores_dir = tmpdir.path().join("highscores");
+            std::fs::write(&options_file, "{not json").unwrap();
+            std::fs::create_dir(&scores_dir).unwrap();
+            let scores_file = scores_dir.join(crate::highscores::HighScores::ARCADE_FILE_NAME);
+            std::fs::write(&scores_file, "42").unwrap();
+            let options_err = globals.config.load_options().unwrap_err();
+            assert!(options_err.is_corrupt());
+            let scores_err = globals.config.load_high_scores().unwrap_err();
+            assert!(scores_err.is_corrupt());
+            let mut menu = MainMenu::new(globals);
+            menu.recover(vec![
+                CorruptFile::new(SaveFile::Options, options_err),
+                CorruptFile::new(SaveFile::HighScores, scores_err),
+            ]);
+            // Keys are directed at the pop-up rather than the menu
+            assert!(menu.handle_event(Event::Key(KeyCode::Tab.into())).is_none());
+            assert_eq!(menu.selection, Selection::PlayButton);
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Char('r').into()))
+                .is_none());
+            assert!(!options_file.exists());
+            assert_eq!(
+                std::fs::read_to_string(tmpdir.path().join("options.json.corrupt")).unwrap(),
+                "{not json"
+            );
+            assert!(matches!(menu.state, MenuState::Recovering(ref files) if files.len() == 1));
+            assert!(menu.handle_event(Event::Key(KeyCode::Esc.into())).is_none());
+            assert_eq!(menu.state, MenuState::Normal);
+            assert!(scores_file.exists());
+        }
+
+        #[test]
+        fn palette_actions() {
+            let mut menu = MainMenu::new(Globals::default());
+            assert!(menu.palette().is_some());
+            assert!(menu
+                .run_palette_action(PaletteAction::Toggle(OptKey::Wraparound))
+                .is_none());
+            assert_eq!(menu.selection, Selection::Options);
+            assert_eq!(menu.opts_menu.selection(), Some(OptKey::Wraparound));
+            assert!(menu.opts_menu.to_options().wraparound);
+            // The toggle can be undone like any other change in the menu
+            assert!(menu
+                .handle_event(Event::Key(KeyCode::Char('u').into()))
+                .is_none());
+            assert!(!menu.opts_menu.to_options().wraparound);
+            assert!(menu
+                .run_palette_action(PaletteAction::SetSeed(42))
+                .is_none());
+            assert_eq!(menu.globals.config.game.rng, RngMode::Seeded(42));
+            menu.state = MenuState::ConfirmQuit(Confirm::new("Quit?"));
+            assert!(menu.palette().is_none());
+        }
+
+        #[test]
+        fn play_favorite() {
+            let tmpdir = tempfile::TempDir::new().unwrap();
+            let mut menu = MainMenu::new(Globals::in_tempdir(&tmpdir, ""));
+            let options = Options {
+                wraparound: true,
+                ..Options::default()
+            };
+            let Some(Screen::Game(game)) = menu.play_favorite(Favorite {
+                name: String::from("Loops"),
+                seed: 42,
+                options,
+            }) else {
+                panic!("Favorite did not start a game");
+            };
+            let record = game.interrupted_record().unwrap();
+            assert_eq!(record.seed, Some(42));
+            assert_eq!(record.rng, Some(RngMode::Seeded(42)));
+            assert_eq!(record.options, options);
+            // Later games are seeded as usual
+            assert_eq!(menu.globals.config.game.rng, RngMode::Thread);
+        }
+    }
+
+    mod undo {
+        use super::*;
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        fn press(menu: &mut MainMenu, code: KeyCode) {
+            assert!(menu.handle_event(Event::Key(code.into())).is_none());
+        }
+
+        fn redo(menu: &mut MainMenu) {
+            assert!(menu
+                .handle_event(Event::Key(KeyEvent::new(
+                    KeyCode::Char('r'),
+                    KeyModifiers::CONTROL
+                )))
+                .is_none());
+        }
+
+        #[test]
+        fn undo_redo() {
+            let mut menu = MainMenu::new(Globals::default());
+            press(&mut menu, KeyCode::Tab);
+            press(&mut menu, KeyCode::Char(' '));
+            // Inputs that don't change anything aren't recorded
+            press(&mut menu, KeyCode::Right);
+            press(&mut menu, KeyCode::Tab);
+            press(&mut menu, KeyCode::Tab);
+            press(&mut menu, KeyCode::Right);
+            press(&mut menu, KeyCode::Right);
+            // Undoing works even when the options menu isn't selected
+            press(&mut menu, KeyCode::Home);
+            let opts = menu.opts_menu.to_options();
+            assert!(opts.wraparound);
+            assert_eq!(opts.fruits.get(), 3);
+
+            press(&mut menu, KeyCode::Char('u'));
+            assert_eq!(menu.selection, Selection::Options);
+            assert_eq!(menu.opts_menu.selection(), Some(OptKey::Fruits));
+            assert_eq!(menu.opts_menu.to_options().fruits.get(), 2);
+            press(&mut menu, KeyCode::Char('u'));
+            press(&mut menu, KeyCode::Char('u'));
+            assert_eq!(menu.opts_menu.selection(), Some(OptKey::Wraparound));
+            assert_eq!(menu.opts_menu.to_options(), Options::default());
+            // Nothing left to undo
+            press(&mut menu, KeyCode::Char('u'));
+            assert_eq!(menu.opts_menu.to_options(), Options::default());
+
+            redo(&mut menu);
+            assert!(menu.opts_menu.to_options().wraparound);
+            redo(&mut menu);
+            assert_eq!(menu.opts_menu.selection(), Some(OptKey::Fruits));
+            assert_eq!(menu.opts_menu.to_options().fruits.get(), 2);
+
+            // A new change discards the changes that could be redone
+            press(&mut menu, KeyCode::Left);
+            redo(&mut menu);
+            let opts = menu.opts_menu.to_options();
+            assert!(opts.wraparound);
+            assert_eq!(opts.fruits.get(), 1);
+        }
+
+        #[test]
+        fn undo_limit() {
+            let mut menu = MainMenu::new(Globals::default());
+            press(&mut menu, KeyCode::Tab);
+            for _ in 0..=OptionsMenu::UNDO_LIMIT {
+                press(&mut menu, KeyCode::Char(' '));
+            }
+            assert!(menu.opts_menu.to_options().wraparound);
+            for _ in 0..=OptionsMenu::UNDO_LIMIT {
+                press(&mut menu, KeyCode::Char('u'));
+            }
+            // The oldest change can no longer be undone
+            assert!(menu.opts_menu.to_options().wraparound);
+        }
+    }
+
+    mod unsaved {
+        use super::*;
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        use std::path::PathBuf;
+        use tempfile::TempDir;
+
+        /// Create a main menu whose data files are saved in `dir`, and return
+        /// the path to the options file
+        fn menu_in(dir: &TempDir) -> (MainMenu, PathBuf) {
+            let menu = MainMenu::new(Globals::in_tempdir(dir, ""));
+            (menu, dir.path().join("options.json"))
+        }
+
+        fn press(menu: &mut MainMenu, code: KeyCode) -> Option<Screen> {
+            menu.handle_event(Event::Key(code.into()))
+        }
+
+        #[test]
+        fn quit_unchanged() {
+            let tmpdir = TempDir::new().unwrap();
+            let (mut menu, _) = menu_in(&tmpdir);
+            assert!(matches!(
+                press(&mut menu, KeyCode::Char('q')),
+                Some(Screen::Quit)
+            ));
+        }
+
+        #[test]
+        fn quit_and_save() {
+            let tmpdir = TempDir::new().unwrap();
+            let (mut menu, options_file) = menu_in(&tmpdir);
+            assert!(press(&mut menu, KeyCode::Tab).is_none());
+            assert!(press(&mut menu, KeyCode::Char(' ')).is_none());
+            assert!(press(&mut menu, KeyCode::Char('q')).is_none());
+            assert!(matches!(menu.state, MenuState::ConfirmQuit(_)));
+            assert!(!options_file.exists());
+            assert!(matches!(
+                press(&mut menu, KeyCode::Char('y')),
+                Some(Screen::Quit)
+            ));
+            let saved = menu.globals.config.load_options().unwrap().value;
+            assert!(saved.options.wraparound);
+        }
+
+        #[test]
+        fn quit_and_discard() {
+            let tmpdir = TempDir::new().unwrap();
+            let (mut menu, options_file) = menu_in(&tmpdir);
+            assert!(press(&mut menu, KeyCode::Tab).is_none());
+            assert!(press(&mut menu, KeyCode::Char(' ')).is_none());
+            assert!(press(&mut menu, KeyCode::Char('q')).is_none());
+            assert!(matches!(
+                press(&mut menu, KeyCode::Char('n')),
+                Some(Screen::Quit)
+            ));
+            assert!(!options_file.exists());
+        }
+
+        #[test]
+        fn save_directly() {
+            let tmpdir = TempDir::new().unwrap();
+            let (mut menu, _) = menu_in(&tmpdir);
+            assert!(press(&mut menu, KeyCode::Tab).is_none());
+            assert!(press(&mut menu, KeyCode::Char(' ')).is_none());
+            let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+            assert!(menu.handle_event(Event::Key(ctrl_s)).is_none());
+            assert_eq!(menu.state, MenuState::Normal);
+            assert!(
+                menu.globals
+                    .config
+                    .load_options()
+                    .unwrap()
+                    .value
+                    .options
+                    .wraparound
+            );
+            // Now that the changes are saved, quitting doesn't ask about them
+            assert!(matches!(
+                press(&mut menu, KeyCode::Char('q')),
+                Some(Screen::Quit)
+            ));
+        }
+
+        #[test]
+        fn kiosk_quit_unsaved() {
+            let tmpdir = TempDir::new().unwrap();
+            let (mut menu, _) = menu_in(&tmpdir);
+            menu.globals.kiosk = true;
+            assert!(press(&mut menu, KeyCode::Tab).is_none());
+            assert!(press(&mut menu, KeyCode::Char(' ')).is_none());
+            // The app ignores the request to quit, and the menu isn't stuck
+            // on a question that can't be dismissed
+            assert!(matches!(
+                press(&mut menu, KeyCode::Char('q')),
+                Some(Screen::Quit)
+            ));
+            assert_eq!(menu.state, MenuState::Normal);
+        }
+
+        #[test]
+        fn use_config_defaults() {
+            let tmpdir = TempDir::new().unwrap();
+            let (mut menu, _) = menu_in(&tmpdir);
+            assert!(press(&mut menu, KeyCode::Tab).is_none());
+            assert!(press(&mut menu, KeyCode::Char(' ')).is_none());
+            assert!(menu.opts_menu.to_options().wraparound);
+            menu.select(Selection::ConfigDefaults, None);
+            assert!(press(&mut menu, KeyCode::Enter).is_none());
+            assert_eq!(menu.state, MenuState::Normal);
+            assert!(menu.globals.use_config_defaults);
+            assert!(!menu.opts_menu.to_options().wraparound);
+            let saved = menu.globals.config.load_options().unwrap().value;
+            assert!(saved.use_config_defaults);
+            assert_eq!(saved.options, Options::default());
+            // Changes made while the toggle is on are never saved
+            menu.select(Selection::Options, Some(true));
+            assert!(press(&mut menu, KeyCode::Char(' ')).is_none());
+            assert!(menu.opts_menu.to_options().wraparound);
+            assert!(matches!(
+                press(&mut menu, KeyCode::Char('q')),
+                Some(Screen::Quit)
+            ));
+            let saved = menu.globals.config.load_options().unwrap().value;
+            assert_eq!(saved.options, Options::default());
         }
     }
 
     mod options_menu {
         use super::*;
-        use crate::options::{FruitQty, LevelSize};
+        use crate::options::{FruitQty, LevelLayout, LevelSize, SpawnStrategy, StartPosition};
 
         #[test]
         fn roundtrip_defaults() {
             let opts = Options::default();
-            let optmenu = OptionsMenu::new(opts);
+            let optmenu = OptionsMenu::from_options(opts);
             assert_eq!(optmenu.to_options(), opts);
         }
 
@@ -764,8 +1460,17 @@ mod tests {
                 obstacles: true,
                 fruits: FruitQty::new(4).unwrap(),
                 level_size: LevelSize::Small,
+                layout: LevelLayout::Maze,
+                combos: true,
+                spawn: SpawnStrategy::FarFromSnake,
+                strict_collisions: true,
+                power_ups: true,
+                hydra: true,
+                adaptive: true,
+                terrain: true,
+                start: StartPosition::Random,
             };
-            let optmenu = OptionsMenu::new(opts);
+            let optmenu = OptionsMenu::from_options(opts);
             assert_eq!(optmenu.to_options(), opts);
         }
     }
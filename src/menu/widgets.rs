@@ -1,14 +1,21 @@
 use crate::consts;
+use crate::i18n::{tr, Msg};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Flex, Layout, Rect},
+    style::Color,
     text::{Line, Span, Text},
     widgets::Widget,
 };
 
 /// A widget for drawing the `ratsnake` logo
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub(super) struct Logo;
+pub(super) struct Logo {
+    /// If true, the "`RatSnake`" text is colored with a 24-bit color gradient
+    /// running from the color of the fruit to that of the snake.  Otherwise,
+    /// "Rat" is drawn in the fruit's color and "Snake" in the snake's.
+    pub(super) gradient: bool,
+}
 
 impl Logo {
     /// The width of the "Rat" portion
@@ -73,6 +80,23 @@ impl Widget for Logo {
         Text::from_iter(Self::SNAKE)
             .style(consts::SNAKE_STYLE)
             .render(snake_area, buf);
+        if self.gradient {
+            let steps = words_area.width.saturating_sub(1).max(1);
+            for (i, x) in (words_area.left()..words_area.right()).enumerate() {
+                let i = u16::try_from(i).unwrap_or(u16::MAX);
+                let color = blend(
+                    consts::LOGO_GRADIENT_START,
+                    consts::LOGO_GRADIENT_END,
+                    i,
+                    steps,
+                );
+                for y in words_area.top()..words_area.bottom() {
+                    if let Some(cell) = buf.cell_mut((x, y)) {
+                        cell.set_fg(color);
+                    }
+                }
+            }
+        }
         let [body_area, head_area, _, fruit_area] = Layout::horizontal([
             Constraint::Length(Self::SNAKE_BODY_LENGTH),
             Constraint::Length(1),
@@ -111,15 +135,17 @@ impl Instructions {
     /// `Instructions::render()`
     pub(super) const HEIGHT: u16 = 7;
 
-    /// The width that should be used for the `Rect` passed to
-    /// `Instructions::render()`
-    pub(super) const WIDTH: u16 = 20;
-}
+    /// Return the width that should be used for the `Rect` passed to
+    /// `Instructions::render()`.  This is measured from the text in the
+    /// current language so that longer translations aren't cut off.
+    pub(super) fn width() -> u16 {
+        u16::try_from(Self::text().width()).unwrap_or(u16::MAX)
+    }
 
-impl Widget for Instructions {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let text = Text::from_iter([
-            Line::from("Move the snake with:"),
+    /// Return the text of the instructions
+    fn text() -> Text<'static> {
+        Text::from_iter([
+            Line::from(tr(Msg::MoveTheSnake)),
             Line::from_iter([
                 Span::raw("       "),
                 Span::styled("←", consts::KEY_STYLE),
@@ -131,7 +157,7 @@ impl Widget for Instructions {
                 Span::styled("→", consts::KEY_STYLE),
             ]),
             Line::from_iter([
-                Span::raw("   or: "),
+                Span::raw(format!("{:>6} ", tr(Msg::Or))),
                 Span::styled("h", consts::KEY_STYLE),
                 Span::raw(" "),
                 Span::styled("j", consts::KEY_STYLE),
@@ -141,7 +167,7 @@ impl Widget for Instructions {
                 Span::styled("l", consts::KEY_STYLE),
             ]),
             Line::from_iter([
-                Span::raw("   or: "),
+                Span::raw(format!("{:>6} ", tr(Msg::Or))),
                 Span::styled("a", consts::KEY_STYLE),
                 Span::raw(" "),
                 Span::styled("s", consts::KEY_STYLE),
@@ -151,7 +177,7 @@ impl Widget for Instructions {
                 Span::styled("d", consts::KEY_STYLE),
             ]),
             Line::from_iter([
-                Span::raw("   or: "),
+                Span::raw(format!("{:>6} ", tr(Msg::Or))),
                 Span::styled("4", consts::KEY_STYLE),
                 Span::raw(" "),
                 Span::styled("2", consts::KEY_STYLE),
@@ -160,34 +186,57 @@ impl Widget for Instructions {
                 Span::raw(" "),
                 Span::styled("6", consts::KEY_STYLE),
             ]),
-            Line::from("Eat the fruit, but"),
-            Line::from("don't hit yourself!"),
-        ]);
+            Line::from(tr(Msg::EatTheFruit)),
+            Line::from(tr(Msg::DontHitYourself)),
+        ])
+    }
+}
+
+impl Widget for Instructions {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let text = Instructions::text();
         debug_assert_eq!(
             text.height(),
             usize::from(Self::HEIGHT),
             "Instructions::HEIGHT is wrong"
         );
-        debug_assert_eq!(
-            text.width(),
-            usize::from(Self::WIDTH),
-            "Instructions::WIDTH is wrong"
-        );
         text.render(area, buf);
     }
 }
 
+/// Return the color `step / steps` of the way from `start` to `end`
+fn blend(start: [u8; 3], end: [u8; 3], step: u16, steps: u16) -> Color {
+    let channel = |a: u8, b: u8| {
+        let (a, b) = (i32::from(a), i32::from(b));
+        let value = a + (b - a) * i32::from(step) / i32::from(steps.max(1));
+        u8::try_from(value.clamp(0, 255)).unwrap_or(u8::MAX)
+    };
+    Color::Rgb(
+        channel(start[0], end[0]),
+        channel(start[1], end[1]),
+        channel(start[2], end[2]),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn instructions_width() {
+        // The width of the English text
+        assert_eq!(Instructions::width(), 20);
+    }
+
     mod logo {
         use super::*;
+        use ratatui::style::Modifier;
 
         #[test]
         fn test_render() {
             let mut buffer = Buffer::empty(Rect::new(0, 0, 50, 10));
-            Logo.render(Rect::new(3, 1, Logo::WIDTH, Logo::HEIGHT), &mut buffer);
+            Logo { gradient: false }
+                .render(Rect::new(3, 1, Logo::WIDTH, Logo::HEIGHT), &mut buffer);
             #[rustfmt::skip]
             let mut expected = Buffer::with_lines([
                  "",
@@ -211,7 +260,7 @@ mod tests {
         #[test]
         fn test_render_too_big() {
             let mut buffer = Buffer::empty(Rect::new(0, 0, 50, 10));
-            Logo.render(Rect::new(3, 1, 50, 10), &mut buffer);
+            Logo { gradient: false }.render(Rect::new(3, 1, 50, 10), &mut buffer);
             #[rustfmt::skip]
             let mut expected = Buffer::with_lines([
                  "",
@@ -232,6 +281,38 @@ mod tests {
             pretty_assertions::assert_eq!(buffer, expected);
         }
 
+        #[test]
+        fn test_render_gradient() {
+            let mut buffer = Buffer::empty(Rect::new(0, 0, Logo::WIDTH, Logo::HEIGHT));
+            Logo { gradient: true }.render(buffer.area, &mut buffer);
+            let first = &buffer[(0, 1)];
+            assert_eq!(first.symbol(), "|");
+            assert_eq!(first.fg, Color::Rgb(255, 85, 85));
+            assert!(first.modifier.is_empty());
+            let last = &buffer[(Logo::WIDTH - 1, 2)];
+            assert_eq!(last.symbol(), "\\");
+            assert_eq!(last.fg, Color::Rgb(0, 205, 0));
+            assert!(last.modifier.contains(Modifier::BOLD));
+            // The snake under the text keeps its usual colors
+            assert_eq!(buffer[(14, 6)].fg, Color::Green);
+        }
+
+        #[test]
+        fn test_blend() {
+            assert_eq!(
+                blend([0, 100, 200], [200, 100, 0], 0, 4),
+                Color::Rgb(0, 100, 200)
+            );
+            assert_eq!(
+                blend([0, 100, 200], [200, 100, 0], 1, 4),
+                Color::Rgb(50, 100, 150)
+            );
+            assert_eq!(
+                blend([0, 100, 200], [200, 100, 0], 4, 4),
+                Color::Rgb(200, 100, 0)
+            );
+        }
+
         #[test]
         fn rat_width() {
             assert!(Logo::RAT
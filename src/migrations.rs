@@ -0,0 +1,273 @@
+//! Versioning of the JSON files in which data is saved.
+//!
+//! Every JSON object written to a save file carries a `"version"` field giving
+//! the version of that file's format.  Files written before versioning was
+//! introduced lack the field and are treated as version 0.  When a file is
+//! loaded, it is upgraded to the current format by applying each migration
+//! between its version and the current one in turn; files written by a newer
+//! version of the program are refused rather than misread.
+use crate::util::{LoadError, SaveError};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use thiserror::Error;
+
+/// The name of the field holding the format version
+const VERSION_FIELD: &str = "version";
+
+/// A function upgrading the contents of a save file (without the version
+/// field) from one version of its format to the next
+type Migration = fn(Value) -> Result<Value, MigrationError>;
+
+/// An enum of the kinds of files in which the program saves data
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SaveFile {
+    /// The gameplay options file
+    Options,
+
+    /// A high scores file
+    HighScores,
+
+    /// A line of the game history file
+    History,
+
+    /// The session file
+    Session,
+
+    /// The favorite boards file
+    Favorites,
+}
+
+impl SaveFile {
+    /// Return a description of the file for use in error messages
+    pub(crate) fn desc(self) -> &'static str {
+        match self {
+            SaveFile::Options => "options",
+            SaveFile::HighScores => "high scores",
+            SaveFile::History => "game history",
+            SaveFile::Session => "session",
+            SaveFile::Favorites => "favorites",
+        }
+    }
+
+    /// Return the migrations for the file's format.  The migration at index
+    /// `i` upgrades version `i` to version `i + 1`.
+    fn migrations(self) -> &'static [Migration] {
+        match self {
+            // Version 1: Added the version field
+            SaveFile::Options | SaveFile::History | SaveFile::Session => &[Ok],
+            // Version 1: Wrapped the array of scores in an object under
+            // "scores"
+            // Version 2: Allowed more than one score per set of options,
+            // which older versions would silently discard
+            SaveFile::HighScores => &[wrap_scores, Ok],
+            // Introduced after versioning, so there's nothing to migrate yet
+            SaveFile::Favorites => &[],
+        }
+    }
+
+    /// Return the current version of the file's format
+    pub(crate) fn current_version(self) -> u64 {
+        u64::try_from(self.migrations().len()).unwrap_or(u64::MAX)
+    }
+
+    /// Upgrade the contents of a file in the given format version to the
+    /// current version, returning the contents without the version field
+    fn upgrade(self, version: u64, mut value: Value) -> Result<Value, MigrationError> {
+        let current = self.current_version();
+        if version > current {
+            return Err(MigrationError::TooNew { version, current });
+        }
+        let start = usize::try_from(version).unwrap_or(usize::MAX);
+        for migrate in self.migrations().iter().skip(start) {
+            value = migrate(value)?;
+        }
+        Ok(value)
+    }
+}
+
+/// Serialize `payload`, which must serialize to a JSON object, as the contents
+/// of the given kind of file in the current format version
+///
+/// # Errors
+///
+/// Returns `Err` if serializing `payload` failed.
+pub(crate) fn to_json<T: Serialize>(file: SaveFile, payload: &T) -> Result<String, SaveError> {
+    let mut value =
+        serde_json::to_value(payload).map_err(|e| SaveError::serialize(file.desc(), e))?;
+    if let Value::Object(ref mut map) = value {
+        map.insert(VERSION_FIELD.into(), file.current_version().into());
+    }
+    serde_json::to_string(&value).map_err(|e| SaveError::serialize(file.desc(), e))
+}
+
+/// Deserialize the contents of the given kind of file, upgrading them from
+/// whatever format version they are in
+///
+/// # Errors
+///
+/// Returns `Err` if `src` is not valid JSON, if its version field is invalid
+/// or newer than the current version, if a migration failed, or if the
+/// upgraded contents could not be deserialized.
+pub(crate) fn from_json<T: DeserializeOwned>(file: SaveFile, src: &[u8]) -> Result<T, LoadError> {
+    let mut value =
+        serde_json::from_slice::<Value>(src).map_err(|e| LoadError::deserialize(file.desc(), e))?;
+    let upgraded = match value {
+        Value::Object(ref mut map) => take_version(map),
+        _ => Ok(0),
+    }
+    .and_then(|version| file.upgrade(version, value))
+    .map_err(|e| LoadError::migrate(file.desc(), e))?;
+    serde_json::from_value(upgraded).map_err(|e| LoadError::deserialize(file.desc(), e))
+}
+
+/// Check that the given kind of file at `path` can be saved over, i.e., that
+/// it was not written by a newer version of the program in a format that this
+/// version does not support.  A file that does not exist or cannot be read or
+/// parsed is left for the save itself to deal with.
+///
+/// # Errors
+///
+/// Returns `Err` if the existing file's format version is newer than the
+/// current version.
+pub(crate) fn check_overwrite(file: SaveFile, path: &Path) -> Result<(), SaveError> {
+    let Ok(src) = fs_err::read(path) else {
+        return Ok(());
+    };
+    let Ok(Value::Object(mut map)) = serde_json::from_slice::<Value>(&src) else {
+        return Ok(());
+    };
+    match take_version(&mut map) {
+        Ok(version) if version > file.current_version() => Err(SaveError::too_new(
+            file.desc(),
+            MigrationError::TooNew {
+                version,
+                current: file.current_version(),
+            },
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Remove the version field from `map` and return its value, or 0 if it is
+/// not present
+fn take_version(map: &mut Map<String, Value>) -> Result<u64, MigrationError> {
+    match map.remove(VERSION_FIELD) {
+        Some(v) => v.as_u64().ok_or(MigrationError::BadVersion(v)),
+        None => Ok(0),
+    }
+}
+
+/// Migration for high scores files from version 0 (a bare array of scores) to
+/// version 1 (an object with the array under `"scores"`)
+fn wrap_scores(value: Value) -> Result<Value, MigrationError> {
+    match value {
+        Value::Array(scores) => Ok(Value::Object(Map::from_iter([(
+            String::from("scores"),
+            Value::Array(scores),
+        )]))),
+        _ => Err(MigrationError::Malformed {
+            version: 0,
+            expected: "an array",
+        }),
+    }
+}
+
+/// Error returned when the contents of a save file could not be upgraded to
+/// the current format version
+#[derive(Debug, Error)]
+pub(crate) enum MigrationError {
+    #[error("file was written in format version {version}, but this version of ratsnake only supports versions up to {current}; upgrade ratsnake to load it")]
+    TooNew { version: u64, current: u64 },
+    #[error("invalid format version: {0}")]
+    BadVersion(Value),
+    #[error("format version {version} file is not {expected}")]
+    Malformed {
+        version: u64,
+        expected: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highscores::HighScores;
+    use crate::options::{LevelSize, Options};
+
+    #[test]
+    fn options_roundtrip() {
+        let opts = Options {
+            wraparound: true,
+            level_size: LevelSize::Small,
+            ..Options::default()
+        };
+        let src = to_json(SaveFile::Options, &opts).unwrap();
+        assert!(src.contains(r#""version":1"#));
+        assert_eq!(
+            from_json::<Options>(SaveFile::Options, src.as_bytes()).unwrap(),
+            opts
+        );
+    }
+
+    #[test]
+    fn unversioned_options() {
+        let src = br#"{"wraparound": true}"#;
+        let opts = from_json::<Options>(SaveFile::Options, src).unwrap();
+        assert!(opts.wraparound);
+    }
+
+    #[test]
+    fn unversioned_high_scores() {
+        let src = br#"[{"options": {"wraparound": true}, "score": 7}]"#;
+        let value = from_json::<Value>(SaveFile::HighScores, src).unwrap();
+        let scores = serde_json::from_value::<HighScores>(value["scores"].clone()).unwrap();
+        assert_eq!(scores.ranked().len(), 1);
+    }
+
+    #[test]
+    fn too_new() {
+        let src = br#"{"version": 99, "scores": []}"#;
+        let e = from_json::<Value>(SaveFile::HighScores, src).unwrap_err();
+        assert!(e.is_too_new());
+        let err: &dyn std::error::Error = &e;
+        let msgs = std::iter::successors(Some(err), |e| e.source())
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            msgs,
+            [
+                "Failed to load high scores from disk",
+                "failed to upgrade file format",
+                "file was written in format version 99, but this version of ratsnake only supports versions up to 2; upgrade ratsnake to load it",
+            ]
+        );
+    }
+
+    #[test]
+    fn overwrite_too_new() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("arcade.json");
+        assert!(check_overwrite(SaveFile::HighScores, &path).is_ok());
+        fs_err::write(&path, r#"{"version": 1, "scores": []}"#).unwrap();
+        assert!(check_overwrite(SaveFile::HighScores, &path).is_ok());
+        fs_err::write(&path, "not JSON").unwrap();
+        assert!(check_overwrite(SaveFile::HighScores, &path).is_ok());
+        fs_err::write(&path, r#"{"version": 99, "scores": []}"#).unwrap();
+        let e = check_overwrite(SaveFile::HighScores, &path).unwrap_err();
+        assert!(e.is_too_new());
+        assert!(HighScores::default().save(&path).unwrap_err().is_too_new());
+    }
+
+    #[test]
+    fn bad_version() {
+        let src = br#"{"version": "two"}"#;
+        let e = from_json::<Options>(SaveFile::Options, src).unwrap_err();
+        assert!(!e.is_too_new());
+    }
+
+    #[test]
+    fn malformed() {
+        let src = br#"{"scores": []}"#;
+        assert!(from_json::<Value>(SaveFile::HighScores, src).is_err());
+    }
+}
@@ -1,5 +1,9 @@
+use crate::config::Loaded;
 use crate::consts;
-use crate::util::{data_dir, Bounds, LoadError, SaveError};
+use crate::i18n::{tr, Msg};
+use crate::migrations::{self, SaveFile};
+use crate::util::{data_dir, Bounds, CellAspect, LoadError, SaveError};
+use crate::widgets::{SettingKey, SettingValue};
 use enum_dispatch::enum_dispatch;
 use enum_map::Enum;
 use ratatui::layout::Size;
@@ -8,11 +12,16 @@ use serde::{
     ser::Serializer,
     Deserialize, Serialize,
 };
+use serde_json::{Map, Value};
+use std::cell::Cell;
 use std::fmt;
 use std::path::{Path, PathBuf};
+use thiserror::Error;
 
 /// Gameplay options
-#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub(crate) struct Options {
     /// Should levels wrap around at the borders?
     #[serde(default)]
@@ -29,49 +38,227 @@ pub(crate) struct Options {
     /// Size of levels
     #[serde(default)]
     pub(crate) level_size: LevelSize,
+
+    /// Arrangement of walls in levels
+    #[serde(default)]
+    pub(crate) layout: LevelLayout,
+
+    /// Should eating fruits in quick succession build up a score multiplier?
+    #[serde(default)]
+    pub(crate) combos: bool,
+
+    /// Where new fruits are placed
+    #[serde(default)]
+    pub(crate) spawn: SpawnStrategy,
+
+    /// Should moving into the cell just vacated by the end of the snake's
+    /// tail count as a collision?
+    #[serde(default)]
+    pub(crate) strict_collisions: bool,
+
+    /// Should power-ups occasionally appear in levels?
+    #[serde(default)]
+    pub(crate) power_ups: bool,
+
+    /// Should fruits that spawn extra, mirrored snakes occasionally appear in
+    /// levels?
+    #[serde(default)]
+    pub(crate) hydra: bool,
+
+    /// Where the snake starts in a level
+    #[serde(default)]
+    pub(crate) start: StartPosition,
+
+    /// Should the snake's speed and the placement of new obstacles adapt to
+    /// how well the player is doing?
+    #[serde(default)]
+    pub(crate) adaptive: bool,
+
+    /// Should patches of mud & ice be placed in levels?
+    #[serde(default)]
+    pub(crate) terrain: bool,
 }
 
-impl Options {
-    /// Return the default filepath used for storing gameplay options
-    pub(crate) fn default_path() -> Option<PathBuf> {
-        data_dir().map(|p| p.join("options.json"))
-    }
+/// The contents of the options file: the gameplay options last chosen in the
+/// options menu, along with the state of the menu's "Use config defaults"
+/// toggle
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct SavedOptions {
+    /// The gameplay options
+    #[serde(flatten)]
+    pub(crate) options: Options,
+
+    /// Should the options menu always start from the options in the
+    /// configuration file, without saving changes made in the menu?
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) use_config_defaults: bool,
+}
 
-    /// Save the options to a file on disk
+impl SavedOptions {
+    /// Save the options & toggle to a file on disk
     ///
     /// # Errors
     ///
-    /// Returns `Err` if creating the file's parent directories failed, if
-    /// serializing the options failed, or if writing the serialized options
-    /// failed.
+    /// Returns `Err` if the existing file was written in a newer format, if
+    /// creating the file's parent directories failed, if serializing the
+    /// options failed, or if writing the serialized options failed.
     pub(crate) fn save(&self, path: &Path) -> Result<(), SaveError> {
         if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
             fs_err::create_dir_all(parent).map_err(|e| SaveError::mkdir("options", e))?;
         }
-        let mut src =
-            serde_json::to_string(self).map_err(|e| SaveError::serialize("options", e))?;
+        migrations::check_overwrite(SaveFile::Options, path)?;
+        let mut src = migrations::to_json(SaveFile::Options, self)?;
         src.push('\n');
         fs_err::write(path, &src).map_err(|e| SaveError::write("options", e))?;
         Ok(())
     }
 
-    /// Read options from a file on disk.  If the file does not exist, `None`
-    /// is returned.
+    /// Read options & the toggle from a file on disk.  If the file does not
+    /// exist, `None` is returned.  Any option values that had to be replaced
+    /// are reported as for [`SavedOptions::from_json()`]; the file itself is
+    /// left as it is.
     ///
     /// # Errors
     ///
     /// Returns `Err` if the file could not be read or if the file's contents
     /// could not be deserialized.
-    pub(crate) fn load(path: &Path) -> Result<Option<Options>, LoadError> {
+    pub(crate) fn load(path: &Path) -> Result<Option<Loaded<SavedOptions>>, LoadError> {
         match fs_err::read(path) {
-            Ok(src) => serde_json::from_slice(&src)
-                .map(Some)
-                .map_err(|e| LoadError::deserialize("options", e)),
+            Ok(src) => SavedOptions::from_json(&src).map(Some),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(e) => Err(LoadError::read("options", e)),
         }
     }
 
+    /// Deserialize options & the toggle from the contents of an options file.
+    ///
+    /// Option values that cannot be understood — such as values written by a
+    /// version of the program in which an option's choices were named
+    /// differently, or choices that have since been removed — do not cause
+    /// an error.  Instead, each such value is mapped to the closest current
+    /// value (see [`repair_value()`]) or else reset to its default, and the
+    /// replacements are described by the `repaired` field of the returned
+    /// [`Loaded`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `src` is not valid JSON, if its version field is
+    /// invalid or newer than the current version, if a migration failed, or
+    /// if the file's contents are not a JSON object.
+    pub(crate) fn from_json(src: &[u8]) -> Result<Loaded<SavedOptions>, LoadError> {
+        let mut value = migrations::from_json::<Value>(SaveFile::Options, src)?;
+        let mut replaced = Vec::new();
+        if let Value::Object(ref mut map) = value {
+            let defaults = serde_json::to_value(SavedOptions::default()).unwrap_or_default();
+            let keys = map.keys().cloned().collect::<Vec<_>>();
+            for key in keys {
+                let Some(old) = map.get(&key).filter(|v| !is_valid_value(&key, v)).cloned() else {
+                    continue;
+                };
+                if let Some(new) = repair_value(&key, &old).or_else(|| defaults.get(&key).cloned())
+                {
+                    replaced.push(format!("{key} {old} → {new}"));
+                    map.insert(key, new);
+                } else {
+                    replaced.push(format!("{key} {old} → default"));
+                    map.remove(&key);
+                }
+            }
+        }
+        let saved = serde_json::from_value(value)
+            .map_err(|e| LoadError::deserialize(SaveFile::Options.desc(), e))?;
+        if replaced.is_empty() {
+            Ok(Loaded::new(saved))
+        } else {
+            Ok(Loaded {
+                value: saved,
+                ignored: None,
+                repaired: Some(ReplacedValues(replaced)),
+            })
+        }
+    }
+}
+
+/// Report of values in the options file that could not be understood and were
+/// replaced when the file was loaded.  The report lists each replacement as
+/// the option's name followed by the old & new values.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Replaced unrecognized values in the options file: {}", .0.join(", "))]
+pub(crate) struct ReplacedValues(Vec<String>);
+
+/// Returns `true` if `value` is a valid value for the field `key` of a
+/// [`SavedOptions`].  Unknown fields are always valid, as they are ignored
+/// when deserializing.
+fn is_valid_value(key: &str, value: &Value) -> bool {
+    let map = Map::from_iter([(key.to_owned(), value.clone())]);
+    serde_json::from_value::<SavedOptions>(Value::Object(map)).is_ok()
+}
+
+/// Try to map an invalid value for the field `key` of a [`SavedOptions`] to
+/// a valid one:
+///
+/// - Strings are compared case-insensitively, with underscores & spaces
+///   treated as hyphens.
+///
+/// - Numbers of fruits outside the allowed range are clamped to it.
+///
+/// - Custom level sizes written with "×" are accepted, and dimensions outside
+///   the allowed range are clamped to it.
+///
+/// Returns `None` if no valid value could be found.
+fn repair_value(key: &str, value: &Value) -> Option<Value> {
+    let mut candidates = Vec::new();
+    if let Some(s) = value.as_str() {
+        let s = s.trim().to_lowercase();
+        candidates.push(Value::from(s.replace(['_', ' '], "-")));
+        candidates.push(Value::from(s));
+    }
+    match key {
+        "fruits" => {
+            if let Some(n) = value.as_u64() {
+                let n = usize::try_from(n).unwrap_or(usize::MAX);
+                candidates.push(Value::from(n.clamp(1, consts::FRUIT_CAPACITY)));
+            }
+        }
+        "level_size" => {
+            let (min, max) = (consts::MIN_CUSTOM_LEVEL_SIZE, consts::MAX_CUSTOM_LEVEL_SIZE);
+            let dims = value.as_str().and_then(|s| {
+                s.to_lowercase()
+                    .split_once(['x', '×'])
+                    .map(|(w, h)| (w.trim().parse::<u32>().ok(), h.trim().parse::<u32>().ok()))
+            });
+            if let Some((Some(w), Some(h))) = dims {
+                let w = u16::try_from(w)
+                    .unwrap_or(u16::MAX)
+                    .clamp(min.width, max.width);
+                let h = u16::try_from(h)
+                    .unwrap_or(u16::MAX)
+                    .clamp(min.height, max.height);
+                candidates.push(Value::from(format!("{w}x{h}")));
+            }
+        }
+        _ => (),
+    }
+    candidates
+        .into_iter()
+        .find(|v| v != value && is_valid_value(key, v))
+}
+
+impl From<Options> for SavedOptions {
+    fn from(options: Options) -> SavedOptions {
+        SavedOptions {
+            options,
+            use_config_defaults: false,
+        }
+    }
+}
+
+impl Options {
+    /// Return the default filepath used for storing gameplay options
+    pub(crate) fn default_path() -> Option<PathBuf> {
+        data_dir().map(|p| p.join("options.json"))
+    }
+
     /// Retrieve the value of the given option as an [`OptValue`]
     pub(crate) fn get(&self, key: OptKey) -> OptValue {
         match key {
@@ -79,6 +266,15 @@ impl Options {
             OptKey::Obstacles => self.obstacles.into(),
             OptKey::Fruits => self.fruits.into(),
             OptKey::LevelSize => self.level_size.into(),
+            OptKey::Layout => self.layout.into(),
+            OptKey::Combos => self.combos.into(),
+            OptKey::Spawn => self.spawn.into(),
+            OptKey::StrictCollisions => self.strict_collisions.into(),
+            OptKey::PowerUps => self.power_ups.into(),
+            OptKey::Hydra => self.hydra.into(),
+            OptKey::Start => self.start.into(),
+            OptKey::Adaptive => self.adaptive.into(),
+            OptKey::Terrain => self.terrain.into(),
         }
     }
 
@@ -110,12 +306,65 @@ impl Options {
                     .try_into()
                     .expect("Options::set(LevelSize, value) called with non-LevelSize value");
             }
+            OptKey::Layout => {
+                self.layout = value
+                    .try_into()
+                    .expect("Options::set(Layout, value) called with non-LevelLayout value");
+            }
+            OptKey::Combos => {
+                self.combos = value
+                    .try_into()
+                    .expect("Options::set(Combos, value) called with non-Bool value");
+            }
+            OptKey::Spawn => {
+                self.spawn = value
+                    .try_into()
+                    .expect("Options::set(Spawn, value) called with non-SpawnStrategy value");
+            }
+            OptKey::StrictCollisions => {
+                self.strict_collisions = value
+                    .try_into()
+                    .expect("Options::set(StrictCollisions, value) called with non-Bool value");
+            }
+            OptKey::PowerUps => {
+                self.power_ups = value
+                    .try_into()
+                    .expect("Options::set(PowerUps, value) called with non-Bool value");
+            }
+            OptKey::Hydra => {
+                self.hydra = value
+                    .try_into()
+                    .expect("Options::set(Hydra, value) called with non-Bool value");
+            }
+            OptKey::Start => {
+                self.start = value
+                    .try_into()
+                    .expect("Options::set(Start, value) called with non-StartPosition value");
+            }
+            OptKey::Adaptive => {
+                self.adaptive = value
+                    .try_into()
+                    .expect("Options::set(Adaptive, value) called with non-Bool value");
+            }
+            OptKey::Terrain => {
+                self.terrain = value
+                    .try_into()
+                    .expect("Options::set(Terrain, value) called with non-Bool value");
+            }
         }
     }
 
-    /// Return level bounds as configured by the options
-    pub(crate) fn level_bounds(&self) -> Bounds {
-        Bounds::new(self.level_size.as_size(), self.wraparound)
+    /// Change the value of the option `key` by applying `adjust` to it
+    pub(crate) fn modify<F: FnOnce(&mut OptValue)>(&mut self, key: OptKey, adjust: F) {
+        let mut value = self.get(key);
+        adjust(&mut value);
+        self.set(key, value);
+    }
+
+    /// Return level bounds as configured by the options, with the level's
+    /// shape adjusted for terminal cells of the given shape
+    pub(crate) fn level_bounds(&self, aspect: Option<CellAspect>) -> Bounds {
+        Bounds::new(self.level_size.as_size(aspect), self.wraparound)
     }
 }
 
@@ -126,20 +375,39 @@ pub(crate) enum OptKey {
     Obstacles,
     Fruits,
     LevelSize,
+    Layout,
+    Combos,
+    Spawn,
+    StrictCollisions,
+    PowerUps,
+    Hydra,
+    Start,
+    Adaptive,
+    Terrain,
 }
 
 impl OptKey {
-    /// The maximum display column width of `opt_key.to_string()` for `opt_key:
-    /// OptKey`
-    pub(crate) const DISPLAY_WIDTH: u16 = 10;
-
     /// Return a human-readable name for the option
     pub(crate) fn as_str(&self) -> &'static str {
+        tr(self.msg())
+    }
+
+    /// Return the message containing the option's name
+    pub(crate) fn msg(self) -> Msg {
         match self {
-            OptKey::Wraparound => "Wraparound",
-            OptKey::Obstacles => "Obstacles",
-            OptKey::Fruits => "Fruits",
-            OptKey::LevelSize => "Level Size",
+            OptKey::Wraparound => Msg::Wraparound,
+            OptKey::Obstacles => Msg::Obstacles,
+            OptKey::Fruits => Msg::Fruits,
+            OptKey::LevelSize => Msg::LevelSize,
+            OptKey::Layout => Msg::Layout,
+            OptKey::Combos => Msg::Combos,
+            OptKey::Spawn => Msg::Spawn,
+            OptKey::StrictCollisions => Msg::Strict,
+            OptKey::PowerUps => Msg::PowerUps,
+            OptKey::Hydra => Msg::Hydra,
+            OptKey::Start => Msg::Start,
+            OptKey::Adaptive => Msg::Adaptive,
+            OptKey::Terrain => Msg::Terrain,
         }
     }
 }
@@ -150,6 +418,10 @@ impl fmt::Display for OptKey {
     }
 }
 
+impl SettingKey for OptKey {
+    const DISPLAY_WIDTH: u16 = 10;
+}
+
 /// A trait for modifiable option values
 #[enum_dispatch]
 pub(crate) trait Adjustable {
@@ -176,12 +448,35 @@ pub(crate) enum OptValue {
     Bool(bool),
     FruitQty,
     LevelSize,
+    LevelLayout,
+    SpawnStrategy,
+    StartPosition,
 }
 
-impl OptValue {
-    /// The display column width of `opt_value.to_string()` for all `opt_value:
-    /// OptValue`
-    pub(crate) const DISPLAY_WIDTH: u16 = 10;
+impl SettingValue for OptValue {
+    const DISPLAY_WIDTH: u16 = 10;
+
+    /// Return the value as displayed in a right-to-left options menu.  This
+    /// is the same as the `Display` output, but with the arrows on either
+    /// side swapped & turned around.
+    fn mirrored(self) -> String {
+        let mut chars = self.to_string().chars().collect::<Vec<_>>();
+        let mirror = |c| match c {
+            '◀' => Some('▶'),
+            '◁' => Some('▷'),
+            '▶' => Some('◀'),
+            '▷' => Some('◁'),
+            _ => None,
+        };
+        if let (Some(&first), Some(&last)) = (chars.first(), chars.last()) {
+            if let (Some(first), Some(last)) = (mirror(first), mirror(last)) {
+                let n = chars.len();
+                chars[0] = last;
+                chars[n - 1] = first;
+            }
+        }
+        chars.into_iter().collect()
+    }
 }
 
 // This is needed for EnumMap to be convenient to construct.
@@ -204,6 +499,16 @@ impl fmt::Display for OptValue {
                     right = if frq.can_increase() { '▶' } else { '▷' }
                 )
             }
+            OptValue::LevelSize(sz @ LevelSize::Custom { .. }) => {
+                // Custom sizes can be up to seven characters wide, so the
+                // usual padding is dropped
+                write!(
+                    f,
+                    "{left}{sz:^8}{right}",
+                    left = if sz.can_decrease() { '◀' } else { '◁' },
+                    right = if sz.can_increase() { '▶' } else { '▷' }
+                )
+            }
             OptValue::LevelSize(sz) => {
                 write!(
                     f,
@@ -212,6 +517,30 @@ impl fmt::Display for OptValue {
                     right = if sz.can_increase() { '▶' } else { '▷' }
                 )
             }
+            OptValue::LevelLayout(layout) => {
+                write!(
+                    f,
+                    "{left} {layout:6} {right}",
+                    left = if layout.can_decrease() { '◀' } else { '◁' },
+                    right = if layout.can_increase() { '▶' } else { '▷' }
+                )
+            }
+            OptValue::SpawnStrategy(spawn) => {
+                write!(
+                    f,
+                    "{left} {spawn:6} {right}",
+                    left = if spawn.can_decrease() { '◀' } else { '◁' },
+                    right = if spawn.can_increase() { '▶' } else { '▷' }
+                )
+            }
+            OptValue::StartPosition(start) => {
+                write!(
+                    f,
+                    "{left} {start:6} {right}",
+                    left = if start.can_decrease() { '◀' } else { '◁' },
+                    right = if start.can_increase() { '▶' } else { '▷' }
+                )
+            }
         }
     }
 }
@@ -239,8 +568,7 @@ impl Adjustable for bool {
 }
 
 /// Possible level sizes that the user can choose from
-#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub(crate) enum LevelSize {
     /// A 38×8 level
     Small,
@@ -251,15 +579,32 @@ pub(crate) enum LevelSize {
     /// A 76×19 level
     #[default]
     Large,
+
+    /// A level of a custom size, which can only be selected via the
+    /// configuration file or the options file.  Levels larger than
+    /// [`VIEWPORT_SIZE`][consts::VIEWPORT_SIZE] scroll to follow the snake.
+    Custom { width: u16, height: u16 },
 }
 
 impl LevelSize {
     pub(crate) const MINIMUM: LevelSize = LevelSize::Small;
-    pub(crate) const MAXIMUM: LevelSize = LevelSize::Large;
 
-    /// Return the actual size for the level size choice
-    pub(crate) fn as_size(self) -> Size {
-        match self {
+    /// Return a custom level size of the given dimensions, or `None` if
+    /// either is outside the range from
+    /// [`MIN_CUSTOM_LEVEL_SIZE`][consts::MIN_CUSTOM_LEVEL_SIZE] to
+    /// [`MAX_CUSTOM_LEVEL_SIZE`][consts::MAX_CUSTOM_LEVEL_SIZE]
+    pub(crate) fn custom(width: u16, height: u16) -> Option<LevelSize> {
+        let (min, max) = (consts::MIN_CUSTOM_LEVEL_SIZE, consts::MAX_CUSTOM_LEVEL_SIZE);
+        ((min.width..=max.width).contains(&width) && (min.height..=max.height).contains(&height))
+            .then_some(LevelSize::Custom { width, height })
+    }
+
+    /// Return the actual size for the level size choice.  If `aspect` is
+    /// given, the non-custom sizes are adjusted so that they have the same
+    /// shape on screen when drawn with terminal cells of that shape as they
+    /// do with the cells they were designed for.
+    pub(crate) fn as_size(self, aspect: Option<CellAspect>) -> Size {
+        let size = match self {
             LevelSize::Small => Size {
                 width: 38,
                 height: 8,
@@ -272,18 +617,69 @@ impl LevelSize {
                 width: 76,
                 height: 19,
             },
-        }
+            LevelSize::Custom { width, height } => return Size { width, height },
+        };
+        aspect.map_or(size, |a| a.reshape(size))
     }
 }
 
 impl fmt::Display for LevelSize {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let name = match self {
-            LevelSize::Small => "Small",
-            LevelSize::Medium => "Medium",
-            LevelSize::Large => "Large",
-        };
-        f.pad(name)
+        match self {
+            LevelSize::Small => f.pad(tr(Msg::Small)),
+            LevelSize::Medium => f.pad(tr(Msg::Medium)),
+            LevelSize::Large => f.pad(tr(Msg::Large)),
+            LevelSize::Custom { width, height } => f.pad(&format!("{width}×{height}")),
+        }
+    }
+}
+
+impl Serialize for LevelSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            LevelSize::Small => serializer.serialize_str("small"),
+            LevelSize::Medium => serializer.serialize_str("medium"),
+            LevelSize::Large => serializer.serialize_str("large"),
+            LevelSize::Custom { width, height } => {
+                serializer.collect_str(&format_args!("{width}x{height}"))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LevelSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = LevelSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let (min, max) = (consts::MIN_CUSTOM_LEVEL_SIZE, consts::MAX_CUSTOM_LEVEL_SIZE);
+                write!(
+                    f,
+                    r#""small", "medium", "large", or a custom size from "{}x{}" to "{}x{}""#,
+                    min.width, min.height, max.width, max.height
+                )
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match value {
+                    "small" => Ok(LevelSize::Small),
+                    "medium" => Ok(LevelSize::Medium),
+                    "large" => Ok(LevelSize::Large),
+                    _ => value
+                        .split_once('x')
+                        .and_then(|(w, h)| LevelSize::custom(w.parse().ok()?, h.parse().ok()?))
+                        .ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
     }
 }
 
@@ -292,7 +688,7 @@ impl Adjustable for LevelSize {
         match self {
             LevelSize::Small => *self = LevelSize::Medium,
             LevelSize::Medium => *self = LevelSize::Large,
-            LevelSize::Large => (),
+            LevelSize::Large | LevelSize::Custom { .. } => (),
         }
     }
 
@@ -301,6 +697,194 @@ impl Adjustable for LevelSize {
             LevelSize::Small => (),
             LevelSize::Medium => *self = LevelSize::Small,
             LevelSize::Large => *self = LevelSize::Medium,
+            LevelSize::Custom { .. } => *self = LevelSize::Large,
+        }
+    }
+
+    fn toggle(&mut self) {}
+
+    fn can_increase(&self) -> bool {
+        matches!(self, LevelSize::Small | LevelSize::Medium)
+    }
+
+    fn can_decrease(&self) -> bool {
+        *self != Self::MINIMUM
+    }
+}
+
+/// Possible arrangements of walls in a level
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LevelLayout {
+    /// No walls other than the level border
+    #[default]
+    Open,
+
+    /// A randomly-generated maze
+    Maze,
+}
+
+impl LevelLayout {
+    pub(crate) const MINIMUM: LevelLayout = LevelLayout::Open;
+    pub(crate) const MAXIMUM: LevelLayout = LevelLayout::Maze;
+}
+
+impl fmt::Display for LevelLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LevelLayout::Open => tr(Msg::Open),
+            LevelLayout::Maze => tr(Msg::Maze),
+        };
+        f.pad(name)
+    }
+}
+
+impl Adjustable for LevelLayout {
+    fn increase(&mut self) {
+        *self = LevelLayout::Maze;
+    }
+
+    fn decrease(&mut self) {
+        *self = LevelLayout::Open;
+    }
+
+    fn toggle(&mut self) {
+        *self = match self {
+            LevelLayout::Open => LevelLayout::Maze,
+            LevelLayout::Maze => LevelLayout::Open,
+        };
+    }
+
+    fn can_increase(&self) -> bool {
+        *self != Self::MAXIMUM
+    }
+
+    fn can_decrease(&self) -> bool {
+        *self != Self::MINIMUM
+    }
+}
+
+/// Strategies for choosing where to place new fruits
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SpawnStrategy {
+    /// Place fruits in uniformly random free cells
+    #[default]
+    Random,
+
+    /// Place fruits in the free cells farthest from the snake's head
+    FarFromSnake,
+
+    /// Place fruits in free cells on the edge of the level or next to an
+    /// obstacle
+    NearWalls,
+
+    /// Divide the level into one region per fruit, and place each fruit in
+    /// a free cell of a region that doesn't already have one
+    Spread,
+}
+
+impl SpawnStrategy {
+    pub(crate) const MINIMUM: SpawnStrategy = SpawnStrategy::Random;
+    pub(crate) const MAXIMUM: SpawnStrategy = SpawnStrategy::Spread;
+}
+
+impl fmt::Display for SpawnStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SpawnStrategy::Random => tr(Msg::Random),
+            SpawnStrategy::FarFromSnake => tr(Msg::FarFromSnake),
+            SpawnStrategy::NearWalls => tr(Msg::NearWalls),
+            SpawnStrategy::Spread => tr(Msg::Spread),
+        };
+        f.pad(name)
+    }
+}
+
+impl Adjustable for SpawnStrategy {
+    fn increase(&mut self) {
+        match self {
+            SpawnStrategy::Random => *self = SpawnStrategy::FarFromSnake,
+            SpawnStrategy::FarFromSnake => *self = SpawnStrategy::NearWalls,
+            SpawnStrategy::NearWalls => *self = SpawnStrategy::Spread,
+            SpawnStrategy::Spread => (),
+        }
+    }
+
+    fn decrease(&mut self) {
+        match self {
+            SpawnStrategy::Random => (),
+            SpawnStrategy::FarFromSnake => *self = SpawnStrategy::Random,
+            SpawnStrategy::NearWalls => *self = SpawnStrategy::FarFromSnake,
+            SpawnStrategy::Spread => *self = SpawnStrategy::NearWalls,
+        }
+    }
+
+    fn toggle(&mut self) {}
+
+    fn can_increase(&self) -> bool {
+        *self != Self::MAXIMUM
+    }
+
+    fn can_decrease(&self) -> bool {
+        *self != Self::MINIMUM
+    }
+}
+
+/// Places where the snake can start in a level
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum StartPosition {
+    /// In the center of the level, facing north (or, in a maze, in the room
+    /// nearest the center)
+    #[default]
+    Center,
+
+    /// In the top-left corner of the level, facing east (or, in a maze, in the
+    /// top-left room)
+    Corner,
+
+    /// At a random location facing a random direction with room to move (or,
+    /// in a maze, in a random room)
+    Random,
+}
+
+impl StartPosition {
+    pub(crate) const MINIMUM: StartPosition = StartPosition::Center;
+    pub(crate) const MAXIMUM: StartPosition = StartPosition::Random;
+}
+
+impl fmt::Display for StartPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StartPosition::Center => tr(Msg::StartCenter),
+            StartPosition::Corner => tr(Msg::StartCorner),
+            StartPosition::Random => tr(Msg::StartRandom),
+        };
+        f.pad(name)
+    }
+}
+
+impl Adjustable for StartPosition {
+    fn increase(&mut self) {
+        match self {
+            StartPosition::Center => *self = StartPosition::Corner,
+            StartPosition::Corner => *self = StartPosition::Random,
+            StartPosition::Random => (),
+        }
+    }
+
+    fn decrease(&mut self) {
+        match self {
+            StartPosition::Center => (),
+            StartPosition::Corner => *self = StartPosition::Center,
+            StartPosition::Random => *self = StartPosition::Corner,
         }
     }
 
@@ -315,16 +899,36 @@ impl Adjustable for LevelSize {
     }
 }
 
+thread_local! {
+    /// The largest value that the Fruits option can be set to, as set by
+    /// `game.max-fruits`.  This is thread-local rather than global so that
+    /// tests can each use their own limit.
+    static MAX_FRUITS: Cell<usize> = const { Cell::new(consts::DEFAULT_MAX_FRUITS) };
+}
+
+/// Set the largest value that the Fruits option can be set to
+pub(crate) fn set_max_fruits(max: usize) {
+    MAX_FRUITS.set(max);
+}
+
+/// Return the largest value that the Fruits option can be set to
+pub(crate) fn max_fruits() -> usize {
+    MAX_FRUITS.get()
+}
+
 /// The number of fruits present in a level at one time.  The value is
-/// restricted to between 1 and [`crate::consts::MAX_FRUITS`], inclusive.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// restricted to between 1 and [`crate::consts::FRUIT_CAPACITY`], inclusive,
+/// and the options menu only lets it be raised as far as [`max_fruits()`].
+/// Values above `max_fruits()` are still accepted so that lowering
+/// `game.max-fruits` doesn't invalidate saved options & high scores.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub(crate) struct FruitQty(usize);
 
 impl FruitQty {
     /// Create a new `FruitQty` with the given value.  Returns `None` if `qty`
     /// is out of bounds.
     pub(crate) fn new(qty: usize) -> Option<FruitQty> {
-        (1..=consts::MAX_FRUITS)
+        (1..=consts::FRUIT_CAPACITY)
             .contains(&qty)
             .then_some(FruitQty(qty))
     }
@@ -390,7 +994,7 @@ impl<'de> Deserialize<'de> for FruitQty {
             type Value = FruitQty;
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "a number from 1 to {}", consts::MAX_FRUITS)
+                write!(f, "a number from 1 to {}", consts::FRUIT_CAPACITY)
             }
 
             try_visit_int!(i8, visit_i8, i16, visit_i16, i32, visit_i32, i64, visit_i64);
@@ -417,7 +1021,7 @@ impl Adjustable for FruitQty {
     fn toggle(&mut self) {}
 
     fn can_increase(&self) -> bool {
-        self.0 < consts::MAX_FRUITS
+        self.0 < max_fruits()
     }
 
     fn can_decrease(&self) -> bool {
@@ -429,6 +1033,103 @@ impl Adjustable for FruitQty {
 mod tests {
     use super::*;
 
+    mod saved_options {
+        use super::*;
+        use rstest::rstest;
+        use tempfile::TempDir;
+
+        #[rstest]
+        #[case(false)]
+        #[case(true)]
+        fn roundtrip(#[case] use_config_defaults: bool) {
+            let tmpdir = TempDir::new().unwrap();
+            let path = tmpdir.path().join("options.json");
+            let saved = SavedOptions {
+                options: Options {
+                    wraparound: true,
+                    ..Options::default()
+                },
+                use_config_defaults,
+            };
+            saved.save(&path).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(&path)
+                    .unwrap()
+                    .contains("use_config_defaults"),
+                use_config_defaults
+            );
+            let loaded = SavedOptions::load(&path).unwrap().unwrap();
+            assert_eq!(loaded.value, saved);
+            assert!(loaded.ignored.is_none());
+        }
+
+        #[test]
+        fn missing() {
+            let tmpdir = TempDir::new().unwrap();
+            let path = tmpdir.path().join("options.json");
+            assert!(SavedOptions::load(&path).unwrap().is_none());
+        }
+
+        #[rstest]
+        #[case(r#""Medium""#, LevelSize::Medium)]
+        #[case(r#"" small ""#, LevelSize::Small)]
+        #[case(r#""60×15""#, LevelSize::Custom { width: 60, height: 15 })]
+        #[case(r#""9999x1""#, LevelSize::Custom { width: consts::MAX_CUSTOM_LEVEL_SIZE.width, height: consts::MIN_CUSTOM_LEVEL_SIZE.height })]
+        #[case(r#""huge""#, LevelSize::Large)]
+        #[case("42", LevelSize::Large)]
+        fn repair_level_size(#[case] value: &str, #[case] level_size: LevelSize) {
+            let src = format!(r#"{{"version": 1, "level_size": {value}, "wraparound": true}}"#);
+            let loaded = SavedOptions::from_json(src.as_bytes()).unwrap();
+            assert_eq!(
+                loaded.value.options,
+                Options {
+                    level_size,
+                    wraparound: true,
+                    ..Options::default()
+                }
+            );
+            assert!(loaded.ignored.is_none());
+            let replaced = loaded.repaired.unwrap().0;
+            assert_eq!(replaced.len(), 1);
+            assert!(replaced[0].starts_with(&format!("level_size {value} → ")));
+        }
+
+        #[test]
+        fn repair_without_rewrite() {
+            let tmpdir = TempDir::new().unwrap();
+            let path = tmpdir.path().join("options.json");
+            let src = r#"{"version": 1, "fruits": 999, "spawn": "Far_From_Snake", "obstacles": "yes", "use_config_defaults": 1}"#;
+            std::fs::write(&path, src).unwrap();
+            let loaded = SavedOptions::load(&path).unwrap().unwrap();
+            let expected = SavedOptions {
+                options: Options {
+                    fruits: FruitQty(consts::FRUIT_CAPACITY),
+                    spawn: SpawnStrategy::FarFromSnake,
+                    ..Options::default()
+                },
+                use_config_defaults: false,
+            };
+            assert_eq!(loaded.value, expected);
+            assert!(loaded.ignored.is_none());
+            assert_eq!(
+                loaded.repaired.unwrap().to_string(),
+                format!(
+                    r#"Replaced unrecognized values in the options file: fruits 999 → {}, obstacles "yes" → false, spawn "Far_From_Snake" → "far-from-snake", use_config_defaults 1 → default"#,
+                    consts::FRUIT_CAPACITY
+                )
+            );
+            // Loading is read-only; writing back the repairs is up to the
+            // caller
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), src);
+        }
+
+        #[test]
+        fn not_an_object() {
+            let err = SavedOptions::from_json(b"[1, 2, 3]").unwrap_err();
+            assert!(err.is_corrupt());
+        }
+    }
+
     mod opt_key {
         use super::*;
         use crate::util::EnumExt;
@@ -465,16 +1166,49 @@ mod tests {
                     OptValue::Bool(false),
                     OptValue::Bool(true),
                     OptValue::FruitQty(FruitQty(1)),
-                    OptValue::FruitQty(FruitQty(consts::MAX_FRUITS)),
+                    OptValue::FruitQty(FruitQty(consts::FRUIT_CAPACITY)),
                     OptValue::LevelSize(LevelSize::Small),
                     OptValue::LevelSize(LevelSize::Medium),
                     OptValue::LevelSize(LevelSize::Large),
+                    OptValue::LevelSize(LevelSize::Custom {
+                        width: 10,
+                        height: 5,
+                    }),
+                    OptValue::LevelSize(LevelSize::Custom {
+                        width: 500,
+                        height: 200,
+                    }),
+                    OptValue::LevelLayout(LevelLayout::Open),
+                    OptValue::LevelLayout(LevelLayout::Maze),
+                    OptValue::SpawnStrategy(SpawnStrategy::Random),
+                    OptValue::SpawnStrategy(SpawnStrategy::FarFromSnake),
+                    OptValue::SpawnStrategy(SpawnStrategy::NearWalls),
+                    OptValue::SpawnStrategy(SpawnStrategy::Spread),
+                    OptValue::StartPosition(StartPosition::Center),
+                    OptValue::StartPosition(StartPosition::Corner),
+                    OptValue::StartPosition(StartPosition::Random),
                 ]
                 .iter()
                 .all(|value| value.to_string().chars().count()
                     == usize::from(OptValue::DISPLAY_WIDTH))
             );
         }
+
+        #[test]
+        fn mirrored() {
+            assert_eq!(
+                OptValue::LevelSize(LevelSize::Small).to_string(),
+                "◁ Small  ▶"
+            );
+            assert_eq!(
+                OptValue::LevelSize(LevelSize::Small).mirrored(),
+                "◀ Small  ▷"
+            );
+            assert_eq!(
+                OptValue::Bool(true).mirrored(),
+                OptValue::Bool(true).to_string()
+            );
+        }
     }
 
     mod fruit_qty {
@@ -490,6 +1224,8 @@ mod tests {
         #[case(1)]
         #[case(5)]
         #[case(10)]
+        #[case(15)]
+        #[case(49)]
         fn deserialize_good_json(#[case] qty: usize) {
             let src = format!(r#"{{"fruits": {qty}}}"#);
             let value = serde_json::from_str::<FruitStruct>(&src).unwrap();
@@ -499,19 +1235,86 @@ mod tests {
         #[rstest]
         #[case(-1)]
         #[case(0)]
-        #[case(15)]
+        #[case(50)]
         fn deserialize_bad_json(#[case] qty: isize) {
             let src = format!(r#"{{"fruits": {qty}}}"#);
             assert!(serde_json::from_str::<FruitStruct>(&src).is_err());
         }
+
+        #[test]
+        fn adjust_within_limit() {
+            set_max_fruits(12);
+            let mut qty = FruitQty::new(10).unwrap();
+            qty.increase();
+            qty.increase();
+            assert_eq!(qty.get(), 12);
+            assert!(!qty.can_increase());
+            qty.increase();
+            assert_eq!(qty.get(), 12);
+            // Values above a lowered limit can still be decreased
+            set_max_fruits(5);
+            assert!(!qty.can_increase());
+            qty.decrease();
+            assert_eq!(qty.get(), 11);
+        }
     }
 
     mod level_size {
         use super::*;
 
+        use rstest::rstest;
+
         #[test]
         fn fmt_width() {
             assert_eq!(format!("{:6}", LevelSize::Small), "Small ");
         }
+
+        #[rstest]
+        #[case(r#""medium""#, LevelSize::Medium)]
+        #[case(r#""500x200""#, LevelSize::Custom { width: 500, height: 200 })]
+        #[case(r#""80x10""#, LevelSize::Custom { width: 80, height: 10 })]
+        fn roundtrip_json(#[case] src: &str, #[case] size: LevelSize) {
+            assert_eq!(serde_json::from_str::<LevelSize>(src).unwrap(), size);
+            assert_eq!(serde_json::to_string(&size).unwrap(), src);
+        }
+
+        #[rstest]
+        #[case(r#""huge""#)]
+        #[case(r#""501x200""#)]
+        #[case(r#""9x10""#)]
+        #[case(r#""80×10""#)]
+        #[case(r#""80x""#)]
+        #[case("80")]
+        fn deserialize_bad_json(#[case] src: &str) {
+            assert!(serde_json::from_str::<LevelSize>(src).is_err());
+        }
+
+        #[test]
+        fn adjust_custom() {
+            let mut size = LevelSize::custom(200, 100).unwrap();
+            assert_eq!(OptValue::LevelSize(size).to_string(), "◀200×100 ▷");
+            assert!(!size.can_increase());
+            size.decrease();
+            assert_eq!(size, LevelSize::Large);
+            assert!(!size.can_increase());
+        }
+
+        #[rstest]
+        #[case(LevelSize::Large, None, 76, 19)]
+        #[case(LevelSize::Large, Some((1, 2)), 76, 19)]
+        #[case(LevelSize::Large, Some((1, 1)), 38, 19)]
+        #[case(LevelSize::Large, Some((9, 20)), 76, 17)]
+        #[case(LevelSize::Small, Some((1, 4)), 38, 5)]
+        #[case(LevelSize::Small, Some((2, 1)), 10, 8)]
+        #[case(LevelSize::Custom { width: 80, height: 10 }, Some((1, 1)), 80, 10)]
+        fn as_size(
+            #[case] size: LevelSize,
+            #[case] aspect: Option<(u16, u16)>,
+            #[case] width: u16,
+            #[case] height: u16,
+        ) {
+            let aspect = aspect.map(|(w, h)| CellAspect::new(w, h).unwrap());
+            assert_eq!(size.as_size(aspect), Size { width, height });
+        }
     }
 }
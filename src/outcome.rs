@@ -0,0 +1,160 @@
+//! The machine-readable summary of a run printed by `ratsnake --json-result`
+//! on exit, for use by scripts that run the program non-interactively
+use crate::history::{EndCause, GameRecord};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+/// How the last game of a run ended, as far as a script running the program
+/// is concerned
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Outcome {
+    /// The snake crashed or the bot forfeited
+    Died,
+
+    /// The snake filled the level (or every cell it could reach)
+    Won,
+
+    /// The program quit in the middle of a game or before any game was
+    /// played
+    Aborted,
+}
+
+impl Outcome {
+    /// The exit status used when the snake died
+    const DIED_CODE: u8 = 0;
+
+    /// The exit status used when the snake filled the level.  Statuses 1 and
+    /// 2 are left for errors.
+    const WON_CODE: u8 = 3;
+
+    /// The exit status used when no game was finished
+    const ABORTED_CODE: u8 = 4;
+
+    /// Determine the outcome of a run from its last game, if any
+    pub(crate) fn of(game: Option<&GameRecord>) -> Outcome {
+        match game.map(|record| record.cause) {
+            Some(
+                EndCause::Wall
+                | EndCause::Body
+                | EndCause::Obstacle
+                | EndCause::Forfeit
+                | EndCause::Forbidden
+                | EndCause::TimeUp,
+            ) => Outcome::Died,
+            Some(EndCause::BoardFull | EndCause::Perfect | EndCause::Objective) => Outcome::Won,
+            Some(EndCause::Interrupted) | None => Outcome::Aborted,
+        }
+    }
+
+    /// Return the exit status for the program to exit with
+    pub(crate) fn exit_code(self) -> ExitCode {
+        ExitCode::from(self.code())
+    }
+
+    /// Return the numeric exit status for the outcome
+    fn code(self) -> u8 {
+        match self {
+            Outcome::Died => Self::DIED_CODE,
+            Outcome::Won => Self::WON_CODE,
+            Outcome::Aborted => Self::ABORTED_CODE,
+        }
+    }
+}
+
+/// The summary of a run, serialized as a single JSON object consisting of
+/// the outcome and the fields of the last game's [`GameRecord`], if any
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct RunResult {
+    /// How the run ended
+    outcome: Outcome,
+
+    /// A record of the last game played, if any
+    #[serde(flatten)]
+    game: Option<GameRecord>,
+}
+
+impl RunResult {
+    /// Create a summary of a run whose last game is `game`
+    pub(crate) fn new(game: Option<GameRecord>) -> RunResult {
+        RunResult {
+            outcome: Outcome::of(game.as_ref()),
+            game,
+        }
+    }
+
+    /// Return how the run ended
+    pub(crate) fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    /// Write the summary to `writer` as a line of JSON
+    pub(crate) fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        serde_json::to_writer(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+    use rstest::rstest;
+
+    fn record(cause: EndCause) -> GameRecord {
+        GameRecord {
+            timestamp: "2025-06-01T12:34:56Z".parse().unwrap(),
+            options: Options::default(),
+            seed: Some(42),
+            rng: None,
+            score: 17,
+            duration_ms: 61_500,
+            cause,
+            peak_fill: Some(8),
+        }
+    }
+
+    #[rstest]
+    #[case(Some(EndCause::Wall), Outcome::Died, 0)]
+    #[case(Some(EndCause::Body), Outcome::Died, 0)]
+    #[case(Some(EndCause::Obstacle), Outcome::Died, 0)]
+    #[case(Some(EndCause::Forfeit), Outcome::Died, 0)]
+    #[case(Some(EndCause::BoardFull), Outcome::Won, 3)]
+    #[case(Some(EndCause::Perfect), Outcome::Won, 3)]
+    #[case(Some(EndCause::Interrupted), Outcome::Aborted, 4)]
+    #[case(None, Outcome::Aborted, 4)]
+    fn outcome_of_cause(
+        #[case] cause: Option<EndCause>,
+        #[case] outcome: Outcome,
+        #[case] code: u8,
+    ) {
+        let game = cause.map(record);
+        assert_eq!(Outcome::of(game.as_ref()), outcome);
+        assert_eq!(outcome.code(), code);
+    }
+
+    #[test]
+    fn write_json() {
+        let mut buf = Vec::new();
+        RunResult::new(Some(record(EndCause::Body)))
+            .write(&mut buf)
+            .unwrap();
+        let value = serde_json::from_slice::<serde_json::Value>(&buf).unwrap();
+        assert_eq!(value["outcome"], "died");
+        assert_eq!(value["score"], 17);
+        assert_eq!(value["seed"], 42);
+        assert_eq!(value["cause"], "body");
+        assert_eq!(value["peak_fill"], 8);
+        assert!(value["options"].is_object());
+        assert!(buf.ends_with(b"}\n"));
+    }
+
+    #[test]
+    fn write_json_no_game() {
+        let mut buf = Vec::new();
+        RunResult::new(None).write(&mut buf).unwrap();
+        assert_eq!(buf, b"{\"outcome\":\"aborted\"}\n");
+    }
+}
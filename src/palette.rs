@@ -0,0 +1,442 @@
+//! The command palette: a pop-up, opened with <kbd>:</kbd> or
+//! <kbd>Ctrl</kbd>+<kbd>P</kbd>, that lists the actions available on the
+//! current screen and runs the one the user picks, narrowing the list as the
+//! user types.
+//!
+//! Each screen contributes its own entries to the palette (see
+//! [`Screen::palette()`][crate::app::Screen::palette]) and carries out the
+//! chosen [`PaletteAction`].
+use crate::i18n::{tr, tr_args, Msg};
+use crate::options::OptKey;
+use crate::popup::PopupFrame;
+use crate::textinput::TextInput;
+use crate::theme;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+/// An action that can be run from the command palette
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum PaletteAction {
+    /// Start a game
+    Play,
+
+    /// Turn the given boolean option on or off
+    Toggle(OptKey),
+
+    /// Use the given seed for the random-number generators of new games
+    SetSeed(u64),
+
+    /// Open the high scores screen
+    HighScores,
+
+    /// Open the progress screen
+    Progress,
+
+    /// Open the about screen
+    About,
+
+    /// Open the list of favorite boards
+    Favorites,
+
+    /// Open the list of custom levels in the level packs
+    Levels,
+
+    /// Save the current board to the player's favorites
+    AddFavorite,
+
+    /// Return to the main menu
+    MainMenu,
+
+    /// Resume a paused game
+    Resume,
+
+    /// Start a new game with the same options
+    Restart,
+
+    /// Quit the program
+    Quit,
+}
+
+impl PaletteAction {
+    /// Return the name under which the action is listed in the palette
+    fn label(self) -> String {
+        match self {
+            PaletteAction::Play => tr(Msg::Play).to_owned(),
+            PaletteAction::Toggle(key) => tr_args(Msg::ToggleOption, &[("option", &key)]),
+            PaletteAction::SetSeed(_) => tr(Msg::SetSeed).to_owned(),
+            PaletteAction::HighScores => tr(Msg::HighScores).to_owned(),
+            PaletteAction::Progress => tr(Msg::Progress).to_owned(),
+            PaletteAction::About => tr(Msg::About).to_owned(),
+            PaletteAction::Favorites => tr(Msg::FavoriteBoards).to_owned(),
+            PaletteAction::Levels => tr(Msg::CustomLevels).to_owned(),
+            PaletteAction::AddFavorite => tr(Msg::AddFavorite).to_owned(),
+            PaletteAction::MainMenu => tr(Msg::MainMenu).to_owned(),
+            PaletteAction::Resume => tr(Msg::Resume).to_owned(),
+            PaletteAction::Restart => tr(Msg::Restart).to_owned(),
+            PaletteAction::Quit => tr(Msg::Quit).to_owned(),
+        }
+    }
+}
+
+/// An entry in the command palette
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct PaletteEntry {
+    /// The name under which the entry is listed
+    label: String,
+
+    /// What happens when the entry is chosen
+    kind: EntryKind,
+}
+
+impl PaletteEntry {
+    /// Create an entry that runs `action` when chosen
+    pub(crate) fn new(action: PaletteAction) -> PaletteEntry {
+        PaletteEntry {
+            label: action.label(),
+            kind: EntryKind::Run(action),
+        }
+    }
+
+    /// Create an entry that asks the user for a seed and then runs
+    /// [`PaletteAction::SetSeed`] with it
+    pub(crate) fn set_seed() -> PaletteEntry {
+        PaletteEntry {
+            label: tr(Msg::SetSeed).to_owned(),
+            kind: EntryKind::AskSeed,
+        }
+    }
+}
+
+/// An enum of what happens when a [`PaletteEntry`] is chosen
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum EntryKind {
+    /// Run the given action
+    Run(PaletteAction),
+
+    /// Ask the user for a seed
+    AskSeed,
+}
+
+/// The command palette pop-up
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Palette {
+    /// The entries contributed by the current screen, in the order that they
+    /// are listed when nothing has been typed
+    entries: Vec<PaletteEntry>,
+
+    /// The text typed to filter the entries
+    query: TextInput,
+
+    /// The indices in `entries` of the entries matching `query`, best match
+    /// first
+    matches: Vec<usize>,
+
+    /// The index in `matches` of the selected entry
+    selection: usize,
+
+    /// If the user chose to set the seed, the field in which they are
+    /// entering it and whether the last attempt to submit it was invalid
+    seed: Option<(TextInput, bool)>,
+}
+
+impl Palette {
+    /// The width of the pop-up
+    const WIDTH: u16 = 40;
+
+    /// The maximum number of entries shown at once
+    const VISIBLE_ENTRIES: u16 = 8;
+
+    /// The width of the prompt drawn before the text fields
+    const PROMPT_WIDTH: u16 = 2;
+
+    /// The maximum width of the text typed into the pop-up
+    const INPUT_WIDTH: u16 = Self::WIDTH - 4 /* border & padding */ - Self::PROMPT_WIDTH - 1 /* cursor */;
+
+    /// Create a palette listing the given entries
+    pub(crate) fn new(entries: Vec<PaletteEntry>) -> Palette {
+        let matches = (0..entries.len()).collect();
+        Palette {
+            entries,
+            query: TextInput::new(Self::INPUT_WIDTH),
+            matches,
+            selection: 0,
+            seed: None,
+        }
+    }
+
+    /// Handle the given input event.  Returns `Some` if the user chose an
+    /// action or closed the palette.
+    pub(crate) fn handle_event(&mut self, event: Event) -> Option<PaletteOutcome> {
+        let ev = event.as_key_press_event()?;
+        if ev.modifiers == KeyModifiers::CONTROL && ev.code == KeyCode::Char('c') {
+            return Some(PaletteOutcome::Run(PaletteAction::Quit));
+        }
+        if let Some((ref mut input, ref mut invalid)) = self.seed {
+            match ev.code {
+                KeyCode::Esc => self.seed = None,
+                KeyCode::Enter => match input.value().trim().parse::<u64>() {
+                    Ok(seed) => return Some(PaletteOutcome::Run(PaletteAction::SetSeed(seed))),
+                    Err(_) => *invalid = true,
+                },
+                _ => {
+                    if input.handle_key(ev) {
+                        *invalid = false;
+                    }
+                }
+            }
+            return None;
+        }
+        match ev.code {
+            KeyCode::Esc => return Some(PaletteOutcome::Close),
+            KeyCode::Enter => {
+                let &i = self.matches.get(self.selection)?;
+                match self.entries[i].kind {
+                    EntryKind::Run(action) => return Some(PaletteOutcome::Run(action)),
+                    EntryKind::AskSeed => {
+                        self.seed = Some((TextInput::new(Self::INPUT_WIDTH), false));
+                    }
+                }
+            }
+            KeyCode::Up | KeyCode::BackTab => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => {
+                if self.selection + 1 < self.matches.len() {
+                    self.selection += 1;
+                }
+            }
+            _ => {
+                if self.query.handle_key(ev) {
+                    self.refilter();
+                }
+            }
+        }
+        None
+    }
+
+    /// Recompute the entries matching the query, and select the best match
+    fn refilter(&mut self) {
+        let query = self.query.value();
+        let mut scored = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| fuzzy_score(query, &entry.label).map(|score| (score, i)))
+            .collect::<Vec<_>>();
+        // Sorting is stable, so equally good matches stay in registry order
+        scored.sort_by_key(|&(score, _)| score);
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selection = 0;
+    }
+}
+
+/// An enum of the ways that the user can finish working with the command
+/// palette
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum PaletteOutcome {
+    /// The user chose the given action
+    Run(PaletteAction),
+
+    /// The user closed the palette without choosing anything
+    Close,
+}
+
+impl Widget for &Palette {
+    /// Render the palette in the given area of `buf`.
+    ///
+    /// As with [`Warning`][crate::warning::Warning], `area` should be the area
+    /// of the entire display within which the pop-up will be rendered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [text_area, _] = PopupFrame {
+            title: tr(Msg::PaletteTitle),
+            width: Palette::WIDTH,
+            text_height: Palette::VISIBLE_ENTRIES + 2,
+            footer: false,
+        }
+        .render(area, buf);
+        let [input_area, _, list_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .areas(text_area);
+        let [prompt_area, field_area] = Layout::horizontal([
+            Constraint::Length(Palette::PROMPT_WIDTH),
+            Constraint::Fill(1),
+        ])
+        .areas(input_area);
+        if let Some((ref input, invalid)) = self.seed {
+            Span::raw("#").render(prompt_area, buf);
+            input.render(field_area, buf);
+            let message = if invalid {
+                tr(Msg::InvalidSeed)
+            } else {
+                tr(Msg::EnterSeed)
+            };
+            Line::from(message).render(list_area, buf);
+            return;
+        }
+        Span::raw(":").render(prompt_area, buf);
+        self.query.render(field_area, buf);
+        if self.matches.is_empty() {
+            Line::from(tr(Msg::NoMatchingCommands)).render(list_area, buf);
+            return;
+        }
+        let visible = usize::from(list_area.height);
+        let offset = (self.selection + 1).saturating_sub(visible);
+        for ((pos, &i), row) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .zip(list_area.rows())
+        {
+            let selected = pos == self.selection;
+            let style = if selected {
+                theme::selection_style()
+            } else {
+                Style::new()
+            };
+            let pointer = if selected { "»" } else { "" };
+            Span::styled(format!("{pointer:2}{}", self.entries[i].label), style).render(row, buf);
+        }
+    }
+}
+
+/// Return how well `query` matches `label`, with lower scores being better,
+/// or `None` if it doesn't match.  A query matches if its characters all
+/// appear in `label` in order, ignoring case; the score is the number of
+/// characters of `label` skipped over before & between the matched
+/// characters.
+fn fuzzy_score(query: &str, label: &str) -> Option<usize> {
+    let mut label_chars = label.chars().flat_map(char::to_lowercase);
+    let mut score = 0;
+    for qc in query.chars().flat_map(char::to_lowercase) {
+        loop {
+            if label_chars.next()? == qc {
+                break;
+            }
+            score += 1;
+        }
+    }
+    Some(score)
+}
+
+/// Is `ev` one of the keys that open the command palette?
+pub(crate) fn opens_palette(ev: KeyEvent) -> bool {
+    matches!(
+        (ev.modifiers, ev.code),
+        (KeyModifiers::CONTROL, KeyCode::Char('p'))
+            | (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(':'))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn sample() -> Palette {
+        Palette::new(vec![
+            PaletteEntry::new(PaletteAction::Play),
+            PaletteEntry::new(PaletteAction::Toggle(OptKey::Wraparound)),
+            PaletteEntry::new(PaletteAction::HighScores),
+            PaletteEntry::set_seed(),
+            PaletteEntry::new(PaletteAction::Quit),
+        ])
+    }
+
+    fn type_keys(palette: &mut Palette, keys: &str) -> Option<PaletteOutcome> {
+        let mut outcome = None;
+        for c in keys.chars() {
+            outcome = palette.handle_event(Event::Key(KeyCode::Char(c).into()));
+        }
+        outcome
+    }
+
+    fn press(palette: &mut Palette, code: KeyCode) -> Option<PaletteOutcome> {
+        palette.handle_event(Event::Key(code.into()))
+    }
+
+    #[rstest]
+    #[case("", "Play", Some(0))]
+    #[case("play", "Play", Some(0))]
+    #[case("hs", "High Scores", Some(4))]
+    #[case("wrap", "Toggle Wraparound", Some(7))]
+    #[case("tw", "Toggle Wraparound", Some(6))]
+    #[case("qp", "Quit", None)]
+    #[case("playing", "Play", None)]
+    fn fuzzy(#[case] query: &str, #[case] label: &str, #[case] score: Option<usize>) {
+        assert_eq!(fuzzy_score(query, label), score);
+    }
+
+    #[test]
+    fn filter_and_run() {
+        let mut palette = sample();
+        assert_eq!(press(&mut palette, KeyCode::Down), None);
+        assert_eq!(
+            press(&mut palette, KeyCode::Enter),
+            Some(PaletteOutcome::Run(PaletteAction::Toggle(
+                OptKey::Wraparound
+            )))
+        );
+        let mut palette = sample();
+        assert_eq!(type_keys(&mut palette, "hi"), None);
+        assert_eq!(palette.matches, [2]);
+        assert_eq!(
+            press(&mut palette, KeyCode::Enter),
+            Some(PaletteOutcome::Run(PaletteAction::HighScores))
+        );
+        let mut palette = sample();
+        type_keys(&mut palette, "xyz");
+        assert!(palette.matches.is_empty());
+        assert_eq!(press(&mut palette, KeyCode::Enter), None);
+        press(&mut palette, KeyCode::Backspace);
+        press(&mut palette, KeyCode::Backspace);
+        press(&mut palette, KeyCode::Backspace);
+        assert_eq!(palette.matches.len(), 5);
+        assert_eq!(
+            press(&mut palette, KeyCode::Esc),
+            Some(PaletteOutcome::Close)
+        );
+    }
+
+    #[test]
+    fn set_seed() {
+        let mut palette = sample();
+        type_keys(&mut palette, "seed");
+        assert_eq!(press(&mut palette, KeyCode::Enter), None);
+        assert!(palette.seed.is_some());
+        type_keys(&mut palette, "4x");
+        assert_eq!(press(&mut palette, KeyCode::Enter), None);
+        assert_eq!(
+            palette.seed.as_ref().map(|&(_, invalid)| invalid),
+            Some(true)
+        );
+        press(&mut palette, KeyCode::Backspace);
+        assert_eq!(type_keys(&mut palette, "2"), None);
+        assert_eq!(
+            press(&mut palette, KeyCode::Enter),
+            Some(PaletteOutcome::Run(PaletteAction::SetSeed(42)))
+        );
+        // Esc goes back to the list of commands
+        let mut palette = sample();
+        type_keys(&mut palette, "seed");
+        press(&mut palette, KeyCode::Enter);
+        assert_eq!(press(&mut palette, KeyCode::Esc), None);
+        assert!(palette.seed.is_none());
+    }
+
+    #[rstest]
+    #[case(KeyEvent::from(KeyCode::Char(':')), true)]
+    #[case(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::SHIFT), true)]
+    #[case(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL), true)]
+    #[case(KeyEvent::from(KeyCode::Char('p')), false)]
+    fn palette_keys(#[case] ev: KeyEvent, #[case] opens: bool) {
+        assert_eq!(opens_palette(ev), opens);
+    }
+}
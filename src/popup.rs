@@ -0,0 +1,71 @@
+use crate::util::center_rect;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Flex, Layout, Rect, Size},
+    widgets::{
+        block::{Block, Padding},
+        Clear, Widget,
+    },
+};
+
+/// The frame shared by all pop-up windows: a bordered box with a centered
+/// title, drawn over a cleared area in the center of the display.  The inside
+/// of the box is divided into a text area and an optional one-line footer
+/// (e.g., for buttons) separated from the text by a blank line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct PopupFrame {
+    /// The title to display in the top border, without surrounding padding
+    pub(crate) title: &'static str,
+
+    /// The total width of the pop-up, including borders
+    pub(crate) width: u16,
+
+    /// The number of lines of text to make room for
+    pub(crate) text_height: u16,
+
+    /// Should space be reserved for a footer line?
+    pub(crate) footer: bool,
+}
+
+impl PopupFrame {
+    /// Draw the frame centered within `area` of `buf` and return the areas
+    /// for the text and the footer.  If the frame has no footer, the footer
+    /// area will have a height of zero.
+    ///
+    /// As with the pop-up widgets themselves, `area` should be the area of the
+    /// entire display within which the pop-up will be rendered.
+    pub(crate) fn render(self, area: Rect, buf: &mut Buffer) -> [Rect; 2] {
+        let height = self
+            .text_height
+            .saturating_add(if self.footer { 4 } else { 2 });
+        let block_area = center_rect(
+            area,
+            Size {
+                width: self.width,
+                height,
+            },
+        );
+        let block = Block::bordered()
+            .title(format!(" {} ", self.title))
+            .title_alignment(Alignment::Center)
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(block_area);
+        Clear.render(block_area, buf);
+        block.render(block_area, buf);
+        if self.footer {
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+                .flex(Flex::Start)
+                .spacing(1)
+                .areas(inner)
+        } else {
+            [
+                inner,
+                Rect {
+                    y: inner.bottom(),
+                    height: 0,
+                    ..inner
+                },
+            ]
+        }
+    }
+}
@@ -0,0 +1,526 @@
+//! The progress screen, summarizing the game history journal by week or
+//! month so that players can see how their play has improved over time
+use crate::app::Screen;
+use crate::command::{ChordReader, Command};
+use crate::consts;
+use crate::history::GameRecord;
+use crate::i18n::{self, tr, Msg};
+use crate::input::EventSource;
+use crate::palette::{Palette, PaletteAction, PaletteEntry};
+use crate::scoreboard::Scoreboard;
+use crate::table::{self, TableRows};
+use crate::util::{get_display_area, Globals};
+use crate::warning::{Warning, WarningOutcome};
+use crossterm::event::Event;
+use jiff::tz::TimeZone;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+    Frame,
+};
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+/// The progress screen, listing the best & average scores for each week or
+/// month in which games were played
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Progress {
+    /// All recorded games, oldest first
+    records: Vec<GameRecord>,
+
+    /// The length of the periods into which the games are grouped
+    period: Period,
+
+    /// The summaries of the periods, most recent first
+    summaries: Vec<PeriodSummary>,
+
+    /// The index into `summaries` of the currently-selected entry
+    selection: usize,
+
+    /// The state that the screen is currently in
+    state: ProgressState,
+
+    /// Recognizes key chords such as `g g`
+    chords: ChordReader,
+
+    /// Global data (options & high scores)
+    globals: Globals,
+}
+
+impl Progress {
+    /// The width of the table of periods
+    const TABLE_WIDTH: u16 = 48;
+
+    /// Create a new progress screen from the given globals, loading the game
+    /// history from disk.  If loading fails, the screen starts out showing a
+    /// warning and no games.
+    pub(crate) fn new(globals: Globals) -> Self {
+        let (records, state) = match globals.config.load_history() {
+            Ok(records) => (records, ProgressState::Normal),
+            Err(e) => (Vec::new(), ProgressState::LoadWarning(Warning::from(e))),
+        };
+        let mut progress = Progress {
+            records,
+            period: Period::Week,
+            summaries: Vec::new(),
+            selection: 0,
+            state,
+            chords: ChordReader::default(),
+            globals,
+        };
+        progress.refresh();
+        progress
+    }
+
+    /// Recompute the period summaries for the current period length and reset
+    /// the selection to the most recent period
+    fn refresh(&mut self) {
+        self.summaries = summarize(&self.records, self.period, &TimeZone::system());
+        self.selection = 0;
+    }
+
+    /// Return a mutable reference to the screen's global data
+    pub(crate) fn globals_mut(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+
+    /// Draw the progress screen on the given frame
+    pub(crate) fn draw(&self, frame: &mut Frame<'_>) {
+        frame.render_widget(self, frame.area());
+    }
+
+    /// Receive & handle the next input event from `events`.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn process_input(
+        &mut self,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<Option<Screen>> {
+        if let Some(deadline) = self.chords.deadline() {
+            // Wake up when a pending chord times out so that its indicator
+            // goes away
+            if !events.poll(deadline.saturating_duration_since(Instant::now()))? {
+                self.chords.cancel();
+                return Ok(None);
+            }
+        }
+        Ok(self.handle_event(events.read()?))
+    }
+
+    /// Return a command palette listing the actions available from the
+    /// progress screen, or `None` if a pop-up is being shown
+    pub(crate) fn palette(&self) -> Option<Palette> {
+        matches!(self.state, ProgressState::Normal).then(|| {
+            let mut entries = vec![
+                PaletteEntry::new(PaletteAction::HighScores),
+                PaletteEntry::new(PaletteAction::MainMenu),
+            ];
+            if !self.globals.kiosk {
+                entries.push(PaletteEntry::new(PaletteAction::Quit));
+            }
+            Palette::new(entries)
+        })
+    }
+
+    /// Carry out an action chosen from the command palette.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn run_palette_action(&self, action: PaletteAction) -> Option<Screen> {
+        match action {
+            PaletteAction::HighScores => {
+                Some(Screen::HighScores(Scoreboard::new(self.globals.clone())))
+            }
+            PaletteAction::MainMenu => Some(Screen::Main(crate::menu::MainMenu::new(
+                self.globals.clone(),
+            ))),
+            PaletteAction::Quit => Some(Screen::Quit),
+            PaletteAction::Play
+            | PaletteAction::Toggle(_)
+            | PaletteAction::SetSeed(_)
+            | PaletteAction::Progress
+            | PaletteAction::About
+            | PaletteAction::Favorites
+            | PaletteAction::Levels
+            | PaletteAction::AddFavorite
+            | PaletteAction::Resume
+            | PaletteAction::Restart => None,
+        }
+    }
+
+    /// Handle the given input event.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    fn handle_event(&mut self, event: Event) -> Option<Screen> {
+        let ev = event.as_key_press_event()?;
+        let cmd = if matches!(self.state, ProgressState::Normal) {
+            self.chords.command(ev, Instant::now())?
+        } else {
+            Command::from_key_event(ev)?
+        };
+        match self.state {
+            ProgressState::Normal => match cmd {
+                Command::Quit | Command::Q => return Some(Screen::Quit),
+                Command::Esc => {
+                    return Some(Screen::HighScores(Scoreboard::new(self.globals.clone())))
+                }
+                Command::M => {
+                    return Some(Screen::Main(crate::menu::MainMenu::new(
+                        self.globals.clone(),
+                    )))
+                }
+                Command::Up | Command::Prev => self.selection = self.selection.saturating_sub(1),
+                Command::Down | Command::Next if self.selection + 1 < self.summaries.len() => {
+                    self.selection += 1;
+                }
+                Command::Home => self.selection = 0,
+                Command::End => self.selection = self.summaries.len().saturating_sub(1),
+                Command::O => {
+                    self.period = self.period.toggled();
+                    self.refresh();
+                }
+                _ => (),
+            },
+            ProgressState::LoadWarning(ref mut warning) => match warning.handle_key(ev)? {
+                WarningOutcome::Dismissed => self.state = ProgressState::Normal,
+                WarningOutcome::Quit => return Some(Screen::Quit),
+            },
+        }
+        None
+    }
+}
+
+impl Widget for &Progress {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        let direction = self.globals.config.layout_direction;
+        let [title_area, controls_area, table_area, _, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(display);
+
+        direction
+            .align_start(Line::raw(tr(Msg::ProgressTitle)))
+            .style(consts::SCORE_BAR_STYLE)
+            .render(title_area, buf);
+        if let Some(indicator) = self.chords.indicator() {
+            direction.align_end(indicator).render(title_area, buf);
+        }
+
+        direction
+            .align_start(Line::from_iter([
+                Span::raw(format!("{} {} (", tr(Msg::GroupBy), self.period.as_str())),
+                Span::styled("o", consts::KEY_STYLE),
+                Span::raw(")"),
+            ]))
+            .render(controls_area, buf);
+
+        let [table_area] = Layout::horizontal([Progress::TABLE_WIDTH])
+            .flex(Flex::Center)
+            .areas(table_area);
+        let [header_area, rows_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(table_area);
+        table::header([
+            (tr(Msg::ColumnPeriod), 8),
+            (tr(Msg::ColumnGames), 5),
+            (tr(Msg::ColumnBest), 6),
+            (tr(Msg::ColumnAverage), 8),
+            (tr(Msg::ColumnTrend), 5),
+            (tr(Msg::ColumnPlayed), 9),
+        ])
+        .render(header_area, buf);
+
+        if self.summaries.is_empty() {
+            Line::from(tr(Msg::NoHistory))
+                .centered()
+                .render(rows_area, buf);
+        } else {
+            let rows = self.summaries.iter().map(|sm| {
+                let (whole, tenths) = sm.average_tenths();
+                format!(
+                    "{:^8} {:>5} {:>6} {:>8} {:^5} {:>9}",
+                    sm.label,
+                    sm.games,
+                    i18n::number(sm.best),
+                    format!(
+                        "{}{}{tenths}",
+                        i18n::number(whole),
+                        i18n::decimal_separator()
+                    ),
+                    match sm.trend {
+                        Some(Ordering::Greater) => "▲",
+                        Some(Ordering::Less) => "▼",
+                        Some(Ordering::Equal) => "=",
+                        None => "",
+                    },
+                    i18n::duration(sm.played),
+                )
+            });
+            TableRows::new(rows, self.selection).render(rows_area, buf);
+        }
+
+        direction
+            .align_start(Line::from_iter([
+                Span::raw(format!("{} (", tr(Msg::HighScores))),
+                Span::styled("Esc", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::MainMenu))),
+                Span::styled("m", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::Quit))),
+                Span::styled("q", consts::KEY_STYLE),
+                Span::raw(")"),
+            ]))
+            .render(help_area, buf);
+
+        match self.state {
+            ProgressState::Normal => (),
+            ProgressState::LoadWarning(ref warning) => warning.render(display, buf),
+        }
+    }
+}
+
+/// The lengths of time by which the progress screen can group games
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Period {
+    /// ISO 8601 weeks, starting on Monday
+    Week,
+
+    /// Calendar months
+    Month,
+}
+
+impl Period {
+    /// Return the other period length
+    fn toggled(self) -> Period {
+        match self {
+            Period::Week => Period::Month,
+            Period::Month => Period::Week,
+        }
+    }
+
+    /// Return a human-readable name for the period length
+    fn as_str(self) -> &'static str {
+        match self {
+            Period::Week => tr(Msg::PeriodWeek),
+            Period::Month => tr(Msg::PeriodMonth),
+        }
+    }
+}
+
+/// Statistics on the games played during a single week or month
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct PeriodSummary {
+    /// The name of the period, e.g., "2025-W27" or "2025-07"
+    label: String,
+
+    /// The number of games played
+    games: u64,
+
+    /// The highest score achieved
+    best: u32,
+
+    /// The sum of the scores of all games played
+    total: u64,
+
+    /// How the average score compares to that of the previous period in
+    /// which games were played, if any
+    trend: Option<Ordering>,
+
+    /// The total time spent playing
+    played: Duration,
+}
+
+impl PeriodSummary {
+    /// Return the average score, rounded to the nearest tenth, as a pair of
+    /// the whole part and the tenths digit
+    fn average_tenths(&self) -> (u64, u64) {
+        let tenths = (self.total * 10 + self.games / 2)
+            .checked_div(self.games)
+            .unwrap_or(0);
+        (tenths / 10, tenths % 10)
+    }
+}
+
+/// Group `records` (which must be sorted oldest first) by the period in which
+/// they were played in the time zone `tz`, and return a summary of each
+/// period, most recent first
+fn summarize(records: &[GameRecord], period: Period, tz: &TimeZone) -> Vec<PeriodSummary> {
+    let mut summaries: Vec<PeriodSummary> = Vec::new();
+    for rec in records {
+        let date = rec.timestamp.to_zoned(tz.clone()).date();
+        let label = match period {
+            Period::Week => {
+                let week = date.iso_week_date();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => format!("{}-{:02}", date.year(), date.month()),
+        };
+        match summaries.last_mut() {
+            Some(sm) if sm.label == label => {
+                sm.games += 1;
+                sm.best = sm.best.max(rec.score);
+                sm.total += u64::from(rec.score);
+                sm.played += rec.duration();
+            }
+            _ => summaries.push(PeriodSummary {
+                label,
+                games: 1,
+                best: rec.score,
+                total: u64::from(rec.score),
+                trend: None,
+                played: rec.duration(),
+            }),
+        }
+    }
+    for i in 1..summaries.len() {
+        let prev = summaries[i - 1].average_tenths();
+        summaries[i].trend = Some(summaries[i].average_tenths().cmp(&prev));
+    }
+    summaries.reverse();
+    summaries
+}
+
+/// An enum of the states that the progress screen can be in
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ProgressState {
+    /// Normal operation
+    Normal,
+
+    /// The game history could not be loaded, and the user is being shown a
+    /// warning about it
+    LoadWarning(Warning),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::EndCause;
+    use crate::options::Options;
+    use crossterm::event::KeyCode;
+    use tempfile::TempDir;
+
+    fn record(timestamp: &str, score: u32) -> GameRecord {
+        GameRecord {
+            timestamp: timestamp.parse().unwrap(),
+            options: Options::default(),
+            seed: None,
+            rng: None,
+            score,
+            duration_ms: 60_000,
+            cause: EndCause::Wall,
+            peak_fill: None,
+        }
+    }
+
+    fn records() -> Vec<GameRecord> {
+        vec![
+            record("2025-06-30T09:00:00Z", 10),
+            record("2025-07-02T09:00:00Z", 25),
+            record("2025-07-06T23:00:00Z", 4),
+            record("2025-07-07T00:30:00Z", 30),
+            record("2025-08-01T12:00:00Z", 13),
+        ]
+    }
+
+    #[test]
+    fn summarize_weeks() {
+        let summaries = summarize(&records(), Period::Week, &TimeZone::UTC);
+        assert_eq!(
+            summaries,
+            [
+                PeriodSummary {
+                    label: String::from("2025-W31"),
+                    games: 1,
+                    best: 13,
+                    total: 13,
+                    trend: Some(Ordering::Less),
+                    played: Duration::from_secs(60),
+                },
+                PeriodSummary {
+                    label: String::from("2025-W28"),
+                    games: 1,
+                    best: 30,
+                    total: 30,
+                    trend: Some(Ordering::Greater),
+                    played: Duration::from_secs(60),
+                },
+                PeriodSummary {
+                    label: String::from("2025-W27"),
+                    games: 3,
+                    best: 25,
+                    total: 39,
+                    trend: None,
+                    played: Duration::from_secs(180),
+                },
+            ]
+        );
+        assert_eq!(summaries[2].average_tenths(), (13, 0));
+    }
+
+    #[test]
+    fn summarize_months() {
+        let summaries = summarize(&records(), Period::Month, &TimeZone::UTC);
+        assert_eq!(
+            summaries
+                .iter()
+                .map(|sm| (sm.label.as_str(), sm.games, sm.best, sm.average_tenths()))
+                .collect::<Vec<_>>(),
+            [
+                ("2025-08", 1, 13, (13, 0)),
+                ("2025-07", 3, 30, (19, 7)),
+                ("2025-06", 1, 10, (10, 0)),
+            ]
+        );
+        assert_eq!(summaries[1].trend, Some(Ordering::Greater));
+        assert_eq!(summaries[0].trend, Some(Ordering::Less));
+    }
+
+    #[test]
+    fn render_months() {
+        let records = records();
+        let summaries = summarize(&records, Period::Month, &TimeZone::UTC);
+        let progress = Progress {
+            records,
+            period: Period::Month,
+            summaries,
+            selection: 1,
+            state: ProgressState::Normal,
+            chords: ChordReader::default(),
+            globals: Globals::default(),
+        };
+        let buffer = crate::snapshot::render(&progress);
+        crate::snapshot::assert_snapshot("progress/render_months", &buffer);
+    }
+
+    #[test]
+    fn toggle_period() {
+        let tmpdir = TempDir::new().unwrap();
+        let history = tmpdir.path().join("history.jsonl");
+        for rec in records() {
+            rec.append(&history).unwrap();
+        }
+        let mut progress = Progress::new(Globals::in_tempdir(&tmpdir, ""));
+        assert_eq!(progress.state, ProgressState::Normal);
+        assert_eq!(progress.records.len(), 5);
+        assert!(progress
+            .handle_event(Event::Key(KeyCode::End.into()))
+            .is_none());
+        assert_eq!(progress.selection, progress.summaries.len() - 1);
+        assert!(progress
+            .handle_event(Event::Key(KeyCode::Char('o').into()))
+            .is_none());
+        assert_eq!(progress.period, Period::Month);
+        assert_eq!(progress.selection, 0);
+        assert!(matches!(
+            progress.handle_event(Event::Key(KeyCode::Esc.into())),
+            Some(Screen::HighScores(_))
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+use serde::Serialize;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime};
+
+/// A writer that passes everything written to it through to an inner writer
+/// (normally stdout) while also recording it, along with timing information,
+/// as an [asciinema v2 cast][spec].
+///
+/// Output is accumulated until the writer is flushed, at which point it is
+/// written to the cast as a single output event.  As the terminal backend
+/// flushes after drawing each frame, this results in one event per frame.
+///
+/// [spec]: https://docs.asciinema.org/manual/asciicast/v2/
+#[derive(Debug)]
+pub(crate) struct Recorder<W, C = BufWriter<fs_err::File>> {
+    /// The writer to pass output through to
+    inner: W,
+
+    /// The writer to which the cast is written
+    cast: C,
+
+    /// The time at which recording started
+    start: Instant,
+
+    /// Output written since the last flush
+    pending: Vec<u8>,
+}
+
+impl<W> Recorder<W> {
+    /// Create a `Recorder` that writes a cast to a newly-created file at
+    /// `path`.  `width` and `height` are the dimensions of the terminal.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file could not be created or if writing the cast
+    /// header failed.
+    pub(crate) fn create(inner: W, path: &Path, width: u16, height: u16) -> io::Result<Self> {
+        let cast = BufWriter::new(fs_err::File::create(path)?);
+        Recorder::new(inner, cast, width, height)
+    }
+}
+
+impl<W, C: Write> Recorder<W, C> {
+    /// Create a `Recorder` that writes a cast to `cast`.  `width` and `height`
+    /// are the dimensions of the terminal.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if writing the cast header failed.
+    pub(crate) fn new(inner: W, mut cast: C, width: u16, height: u16) -> io::Result<Self> {
+        let header = CastHeader {
+            version: 2,
+            width,
+            height,
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_secs()),
+        };
+        serde_json::to_writer(&mut cast, &header)?;
+        cast.write_all(b"\n")?;
+        cast.flush()?;
+        Ok(Recorder {
+            inner,
+            cast,
+            start: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+}
+
+impl<W: Write, C: Write> Write for Recorder<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.pending.extend_from_slice(buf.get(..n).unwrap_or(buf));
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if !self.pending.is_empty() {
+            let elapsed = self.start.elapsed().as_secs_f64();
+            let data = String::from_utf8_lossy(&self.pending);
+            serde_json::to_writer(&mut self.cast, &(elapsed, "o", data))?;
+            self.cast.write_all(b"\n")?;
+            self.cast.flush()?;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+}
+
+/// The header line of an asciinema v2 cast
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+struct CastHeader {
+    version: u32,
+    width: u16,
+    height: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn record_frames() {
+        let mut output = Vec::new();
+        let mut cast = Vec::new();
+        {
+            let mut rec = Recorder::new(&mut output, &mut cast, 80, 24).unwrap();
+            rec.write_all(b"\x1b[1;1H").unwrap();
+            rec.write_all("●⚬⚬".as_bytes()).unwrap();
+            rec.flush().unwrap();
+            rec.flush().unwrap();
+            rec.write_all(b"\x1b[2;1Hx").unwrap();
+            rec.flush().unwrap();
+        }
+        assert_eq!(output, "\x1b[1;1H●⚬⚬\x1b[2;1Hx".as_bytes());
+        let cast = String::from_utf8(cast).unwrap();
+        let lines = cast
+            .lines()
+            .map(|ln| serde_json::from_str::<Value>(ln).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["height"], 24);
+        assert!(lines[1][0].is_f64());
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "\x1b[1;1H●⚬⚬");
+        assert!(lines[2][0].as_f64().unwrap() >= lines[1][0].as_f64().unwrap());
+        assert_eq!(lines[2][1], "o");
+        assert_eq!(lines[2][2], "\x1b[2;1Hx");
+    }
+}
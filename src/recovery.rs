@@ -0,0 +1,88 @@
+//! Recovering from options & high scores files that could not be parsed.
+//!
+//! When such a file fails to load at startup, the program starts with default
+//! values in its place, and the main menu shows a pop-up for each such file
+//! letting the user ignore the problem (in which case the file will be
+//! overwritten the next time it is saved), reset the file (after backing it up
+//! to `<name>.corrupt`), or quit.
+use crate::command::Command;
+use crate::i18n::{tr, Msg};
+use crate::migrations::SaveFile;
+use crate::util::LoadError;
+use crate::warning::{Button, Choice, ChoiceOutcome, Warning};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+/// A pop-up asking the user what to do about a data file that could not be
+/// parsed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CorruptFile {
+    /// The kind of file that could not be parsed
+    file: SaveFile,
+
+    /// The pop-up itself
+    choice: Choice<Recovery>,
+}
+
+impl CorruptFile {
+    /// Create a pop-up reporting the given error in loading `file`
+    pub(crate) fn new(file: SaveFile, e: LoadError) -> CorruptFile {
+        let buttons = vec![
+            Button {
+                label: tr(Msg::Ignore),
+                command: Command::Esc,
+                key: "Esc",
+                value: Recovery::Ignore,
+            },
+            Button {
+                label: tr(Msg::ResetFile),
+                command: Command::R,
+                key: "r",
+                value: Recovery::Reset,
+            },
+            Button {
+                label: tr(Msg::Quit),
+                command: Command::Q,
+                key: "q",
+                value: Recovery::Quit,
+            },
+        ];
+        CorruptFile {
+            file,
+            choice: Choice::new(Warning::from(e), buttons),
+        }
+    }
+
+    /// Return the kind of file that could not be parsed
+    pub(crate) fn file(&self) -> SaveFile {
+        self.file
+    }
+
+    /// Process an input command.  Returns `Some` if the user makes a choice
+    /// or quits the application.
+    pub(crate) fn handle_command(&mut self, cmd: Command) -> Option<ChoiceOutcome<Recovery>> {
+        self.choice.handle_command(cmd)
+    }
+}
+
+impl Widget for &CorruptFile {
+    /// Render the pop-up in the given area of `buf`.
+    ///
+    /// As with [`Warning`], `area` should be the area of the entire display
+    /// within which the pop-up will be rendered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.choice.render(area, buf);
+    }
+}
+
+/// An enum of the actions the user can take regarding a corrupt data file
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Recovery {
+    /// Carry on using default values
+    Ignore,
+
+    /// Back up the file and carry on using default values
+    Reset,
+
+    /// Quit the application
+    Quit,
+}
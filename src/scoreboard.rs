@@ -0,0 +1,1114 @@
+use crate::app::Screen;
+use crate::command::{ChordReader, Command};
+use crate::confirm::{Confirm, ConfirmOutcome};
+use crate::consts;
+use crate::game::{Game, Rival};
+use crate::highscores::HighScore;
+use crate::i18n::{self, tr, tr_args, Msg};
+use crate::input::EventSource;
+use crate::options::{OptKey, OptValue, Options, StartPosition};
+use crate::palette::{Palette, PaletteAction, PaletteEntry};
+use crate::progress::Progress;
+use crate::table::{self, TableRows};
+use crate::theme;
+use crate::util::{get_display_area, EnumExt, Globals};
+use crate::warning::{Warning, WarningOutcome};
+use crossterm::event::Event;
+use enum_map::EnumMap;
+use jiff::Timestamp;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+    Frame,
+};
+use std::fmt::Write as _;
+use std::time::Instant;
+
+/// The high scores screen, listing the high score for each combination of
+/// options and allowing the user to delete them
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Scoreboard {
+    /// The high scores, in the order in which they are displayed
+    entries: Vec<(Options, HighScore)>,
+
+    /// The index into `entries` of the currently-selected entry
+    selection: usize,
+
+    /// Are high scores achieved using slow motion currently hidden?
+    hide_slow_motion: bool,
+
+    /// The order in which the high scores are listed
+    sort: SortOrder,
+
+    /// For each option, the value that displayed high scores must have for
+    /// that option, if any
+    filters: EnumMap<OptKey, Option<OptValue>>,
+
+    /// The option column whose filter is currently selected
+    filter_column: OptKey,
+
+    /// The time at which the screen was opened, used for displaying the ages
+    /// of high scores
+    now: Timestamp,
+
+    /// The state that the screen is currently in
+    state: ScoreboardState,
+
+    /// Recognizes key chords such as `g g`
+    chords: ChordReader,
+
+    /// Global data (options & high scores)
+    globals: Globals,
+}
+
+impl Scoreboard {
+    /// The width of the table of high scores
+    const TABLE_WIDTH: u16 = 80;
+
+    /// Create a new high scores screen from the given globals
+    pub(crate) fn new(globals: Globals) -> Self {
+        Scoreboard {
+            entries: globals.high_scores.ranked(),
+            selection: 0,
+            hide_slow_motion: false,
+            sort: SortOrder::Score,
+            filters: EnumMap::default(),
+            filter_column: OptKey::min(),
+            now: Timestamp::now(),
+            state: ScoreboardState::Normal,
+            chords: ChordReader::default(),
+            globals,
+        }
+    }
+
+    /// Recompute the entries to display, taking the sort order & filters
+    /// into account, and reset the selection to the first entry
+    fn refresh(&mut self) {
+        let mut entries = self.globals.high_scores.ranked();
+        if self.sort == SortOrder::Date {
+            // Scores without dates sort after all scores with dates
+            entries.sort_by_key(|(_, hs)| std::cmp::Reverse(hs.achieved));
+        }
+        entries.retain(|(opts, hs)| {
+            !(self.hide_slow_motion && hs.slow_motion)
+                && self
+                    .filters
+                    .iter()
+                    .all(|(key, value)| value.is_none_or(|v| opts.get(key) == v))
+        });
+        self.entries = entries;
+        self.selection = 0;
+    }
+
+    /// Set the filter for the currently-selected option column to the next
+    /// value for that option that occurs among the high scores, or clear the
+    /// filter if there are no more values
+    fn cycle_filter(&mut self) {
+        let key = self.filter_column;
+        let mut values = Vec::new();
+        for (opts, _) in self.globals.high_scores.ranked() {
+            let v = opts.get(key);
+            if !values.contains(&v) {
+                values.push(v);
+            }
+        }
+        let next = match self.filters[key] {
+            Some(current) => values
+                .iter()
+                .position(|&v| v == current)
+                .and_then(|i| values.get(i + 1)),
+            None => values.first(),
+        };
+        self.filters[key] = next.copied();
+        self.refresh();
+    }
+
+    /// Returns `true` if any option filters are set
+    fn filtering(&self) -> bool {
+        self.filters.values().any(Option::is_some)
+    }
+
+    /// Return a mutable reference to the screen's global data
+    pub(crate) fn globals_mut(&mut self) -> &mut Globals {
+        &mut self.globals
+    }
+
+    /// Draw the high scores screen on the given frame
+    pub(crate) fn draw(&self, frame: &mut Frame<'_>) {
+        frame.render_widget(self, frame.area());
+    }
+
+    /// Receive & handle the next input event from `events`.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn process_input(
+        &mut self,
+        events: &mut dyn EventSource,
+    ) -> std::io::Result<Option<Screen>> {
+        if let Some(deadline) = self.chords.deadline() {
+            // Wake up when a pending chord times out so that its indicator
+            // goes away
+            if !events.poll(deadline.saturating_duration_since(Instant::now()))? {
+                self.chords.cancel();
+                return Ok(None);
+            }
+        }
+        Ok(self.handle_event(events.read()?))
+    }
+
+    /// Return a command palette listing the actions available from the high
+    /// scores screen, or `None` if a pop-up is being shown
+    pub(crate) fn palette(&self) -> Option<Palette> {
+        matches!(self.state, ScoreboardState::Normal).then(|| {
+            let mut entries = vec![
+                PaletteEntry::new(PaletteAction::MainMenu),
+                PaletteEntry::new(PaletteAction::Progress),
+            ];
+            if !self.globals.kiosk {
+                entries.push(PaletteEntry::new(PaletteAction::Quit));
+            }
+            Palette::new(entries)
+        })
+    }
+
+    /// Carry out an action chosen from the command palette.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    pub(crate) fn run_palette_action(&self, action: PaletteAction) -> Option<Screen> {
+        match action {
+            PaletteAction::MainMenu => Some(Screen::Main(crate::menu::MainMenu::new(
+                self.globals.clone(),
+            ))),
+            PaletteAction::Progress => Some(Screen::Progress(Progress::new(self.globals.clone()))),
+            PaletteAction::Quit => Some(Screen::Quit),
+            PaletteAction::Play
+            | PaletteAction::Toggle(_)
+            | PaletteAction::SetSeed(_)
+            | PaletteAction::HighScores
+            | PaletteAction::About
+            | PaletteAction::Favorites
+            | PaletteAction::Levels
+            | PaletteAction::AddFavorite
+            | PaletteAction::Resume
+            | PaletteAction::Restart => None,
+        }
+    }
+
+    /// Handle the given input event.
+    ///
+    /// Returns `Some(screen)` if the application should switch to a different
+    /// screen or quit.
+    fn handle_event(&mut self, event: Event) -> Option<Screen> {
+        let ev = event.as_key_press_event()?;
+        let cmd = if matches!(self.state, ScoreboardState::Normal) {
+            self.chords.command(ev, Instant::now())?
+        } else {
+            Command::from_key_event(ev)?
+        };
+        match self.state {
+            // The option columns are mirrored in right-to-left layouts
+            ScoreboardState::Normal => match self.globals.config.layout_direction.flip(cmd) {
+                Command::Quit | Command::Q => return Some(Screen::Quit),
+                Command::Esc | Command::M => {
+                    return Some(Screen::Main(crate::menu::MainMenu::new(
+                        self.globals.clone(),
+                    )))
+                }
+                Command::Up | Command::Prev => self.selection = self.selection.saturating_sub(1),
+                Command::Down | Command::Next if self.selection + 1 < self.entries.len() => {
+                    self.selection += 1;
+                }
+                Command::Home => self.selection = 0,
+                Command::End => self.selection = self.entries.len().saturating_sub(1),
+                Command::Delete if self.selection < self.entries.len() => {
+                    self.state = ScoreboardState::Confirm(
+                        Confirm::new(tr(Msg::ConfirmDeleteScore)),
+                        Deletion::Selected,
+                    );
+                }
+                Command::F => {
+                    self.hide_slow_motion = !self.hide_slow_motion;
+                    self.refresh();
+                }
+                Command::O => {
+                    self.sort = self.sort.toggled();
+                    self.refresh();
+                }
+                Command::Left => {
+                    let order = filter_order();
+                    if let Some(i) = order.iter().position(|&k| k == self.filter_column) {
+                        self.filter_column = order[i.saturating_sub(1)];
+                    }
+                }
+                Command::Right => {
+                    let order = filter_order();
+                    if let Some(&key) = order
+                        .iter()
+                        .position(|&k| k == self.filter_column)
+                        .and_then(|i| order.get(i + 1))
+                    {
+                        self.filter_column = key;
+                    }
+                }
+                Command::Enter | Command::Space => self.cycle_filter(),
+                Command::P => return Some(Screen::Progress(Progress::new(self.globals.clone()))),
+                Command::R => {
+                    if let Some(game) = self.race() {
+                        return Some(Screen::Game(Box::new(game)));
+                    }
+                }
+                Command::C if !self.entries.is_empty() => {
+                    self.state = ScoreboardState::Confirm(
+                        Confirm::new(tr(Msg::ConfirmDeleteAll)),
+                        Deletion::All,
+                    );
+                }
+                _ => (),
+            },
+            ScoreboardState::Confirm(ref mut confirm, deletion) => {
+                match confirm.handle_command(cmd)? {
+                    ConfirmOutcome::Yes => self.delete(deletion),
+                    ConfirmOutcome::No => self.state = ScoreboardState::Normal,
+                    ConfirmOutcome::Quit => return Some(Screen::Quit),
+                }
+            }
+            ScoreboardState::SaveWarning(ref mut warning) => match warning.handle_key(ev)? {
+                WarningOutcome::Dismissed => self.state = ScoreboardState::Normal,
+                WarningOutcome::Quit => return Some(Screen::Quit),
+            },
+        }
+        None
+    }
+
+    /// If the selected high score's game duration is known, create a new game
+    /// with the same options that races against it
+    fn race(&mut self) -> Option<Game> {
+        let &(opts, ref hs) = self.entries.get(self.selection)?;
+        let rival = Rival::new(hs)?;
+        self.globals.options = opts;
+        let mut game = Game::new(self.globals.clone());
+        game.race(rival);
+        game.announce_high_score();
+        Some(game)
+    }
+
+    /// Perform the given deletion, save the high scores to disk, and update
+    /// the state accordingly
+    fn delete(&mut self, deletion: Deletion) {
+        match deletion {
+            Deletion::Selected => {
+                if self.selection < self.entries.len() {
+                    let (opts, hs) = self.entries.remove(self.selection);
+                    self.globals.high_scores.remove(opts, &hs);
+                }
+            }
+            Deletion::All => {
+                self.entries.clear();
+                self.globals.high_scores.clear();
+            }
+        }
+        self.selection = self.selection.min(self.entries.len().saturating_sub(1));
+        self.state = match self
+            .globals
+            .config
+            .save_high_scores(&self.globals.high_scores)
+        {
+            Ok(()) => ScoreboardState::Normal,
+            Err(e) => ScoreboardState::SaveWarning(Warning::from(e)),
+        };
+    }
+}
+
+impl Widget for &Scoreboard {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        let direction = self.globals.config.layout_direction;
+        let [title_area, controls_area, table_area, detail_area, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ])
+        .areas(display);
+
+        direction
+            .align_start(Line::raw(if self.hide_slow_motion {
+                format!(
+                    "{} {}",
+                    tr(Msg::HighScoresTitle),
+                    tr(Msg::ExcludingSlowMotion)
+                )
+            } else {
+                String::from(tr(Msg::HighScoresTitle))
+            }))
+            .style(consts::SCORE_BAR_STYLE)
+            .render(title_area, buf);
+        if let Some(indicator) = self.chords.indicator() {
+            direction.align_end(indicator).render(title_area, buf);
+        }
+
+        direction
+            .align_start(Line::from_iter([
+                Span::raw(format!("{} {} (", tr(Msg::Sort), self.sort.as_str())),
+                Span::styled("o", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::FilterColumn))),
+                Span::styled("◀/▶", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::CycleFilter))),
+                Span::styled("Enter", consts::KEY_STYLE),
+                Span::raw(")"),
+            ]))
+            .render(controls_area, buf);
+
+        let [table_area] = Layout::horizontal([Scoreboard::TABLE_WIDTH])
+            .flex(Flex::Center)
+            .areas(table_area);
+        let [header_area, filter_area, rows_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .areas(table_area);
+        // On shared machines, the name of each high score's player is more
+        // useful than its age
+        let show_names = self.globals.config.game.ask_name;
+        let last_column = tr(if show_names {
+            Msg::ColumnName
+        } else {
+            Msg::ColumnAge
+        });
+        let columns = direction.arrange(Column::all());
+        let flags = direction.arrange(flag_keys());
+        table::header(
+            columns
+                .iter()
+                .map(|column| match *column {
+                    Column::Single(key) => (column_header(key), column_width(key)),
+                    Column::Flags => (tr(Msg::ColumnFlags), flags.len()),
+                })
+                .chain([
+                    (tr(Msg::ColumnScore), 5),
+                    (tr(Msg::ColumnSlow), 4),
+                    (last_column, 8),
+                ]),
+        )
+        .render(header_area, buf);
+
+        let mut filter_spans = vec![Span::raw("  ")];
+        for column in &columns {
+            match *column {
+                Column::Single(key) => {
+                    let text = format!(
+                        "{:^width$.width$}",
+                        self.filters[key].map_or_else(|| String::from(tr(Msg::Any)), cell_text),
+                        width = column_width(key)
+                    );
+                    if key == self.filter_column {
+                        filter_spans.push(Span::styled(text, theme::selection_style()));
+                    } else {
+                        filter_spans.push(Span::raw(text));
+                    }
+                }
+                Column::Flags => {
+                    // Each flag's filter is shown in the flag's position
+                    for &key in &flags {
+                        let text = match self.filters[key] {
+                            None => String::from("·"),
+                            Some(value) => flag_cell(key, value).to_string(),
+                        };
+                        if key == self.filter_column {
+                            filter_spans.push(Span::styled(text, theme::selection_style()));
+                        } else {
+                            filter_spans.push(Span::raw(text));
+                        }
+                    }
+                }
+            }
+            filter_spans.push(Span::raw(" "));
+        }
+        Line::from(filter_spans).render(filter_area, buf);
+
+        if self.entries.is_empty() {
+            Line::from(tr(if self.filtering() {
+                Msg::NoMatchingScores
+            } else if self.hide_slow_motion {
+                Msg::NoFastScores
+            } else {
+                Msg::NoScores
+            }))
+            .centered()
+            .render(rows_area, buf);
+        } else {
+            let rows = self.entries.iter().map(|(opts, hs)| {
+                let mut s = String::new();
+                for column in &columns {
+                    match *column {
+                        Column::Single(key) => {
+                            let _ = write!(
+                                s,
+                                "{:^width$.width$}",
+                                cell_text(opts.get(key)),
+                                width = column_width(key)
+                            );
+                        }
+                        Column::Flags => {
+                            s.extend(flags.iter().map(|&key| flag_cell(key, opts.get(key))));
+                        }
+                    }
+                    s.push(' ');
+                }
+                let last = if show_names {
+                    hs.name.clone().unwrap_or_else(|| String::from("-"))
+                } else {
+                    hs.achieved
+                        .map_or_else(|| String::from("-"), |t| relative_age(t, self.now))
+                };
+                let _ = write!(
+                    s,
+                    "{:>5} {:^4} {:^8.8}",
+                    i18n::number(hs.score.get()),
+                    checkmark(hs.slow_motion),
+                    last,
+                );
+                s
+            });
+            TableRows::new(rows, self.selection).render(rows_area, buf);
+        }
+
+        // Spell out the flags set for the selected high score
+        if let Some((opts, _)) = self.entries.get(self.selection) {
+            let names = flag_keys()
+                .into_iter()
+                .filter(|&key| opts.get(key) == OptValue::Bool(true))
+                .map(|key| key.as_str())
+                .collect::<Vec<_>>();
+            if !names.is_empty() {
+                direction
+                    .align_start(Line::raw(format!(
+                        "{}: {}",
+                        tr(Msg::ColumnFlags),
+                        names.join(", ")
+                    )))
+                    .render(detail_area, buf);
+            }
+        }
+
+        let [help1_area, help2_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(help_area);
+        direction
+            .align_start(Line::from_iter([
+                Span::raw(format!("{} (", tr(Msg::Race))),
+                Span::styled("r", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::Delete))),
+                Span::styled("Del", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::ClearAll))),
+                Span::styled("c", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::FilterSlow))),
+                Span::styled("f", consts::KEY_STYLE),
+                Span::raw(")"),
+            ]))
+            .render(help1_area, buf);
+        direction
+            .align_start(Line::from_iter([
+                Span::raw(format!("{} (", tr(Msg::Progress))),
+                Span::styled("p", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::MainMenu))),
+                Span::styled("m", consts::KEY_STYLE),
+                Span::raw(format!(") — {} (", tr(Msg::Quit))),
+                Span::styled("q", consts::KEY_STYLE),
+                Span::raw(")"),
+            ]))
+            .render(help2_area, buf);
+
+        match self.state {
+            ScoreboardState::Normal => (),
+            ScoreboardState::Confirm(ref confirm, _) => confirm.render(display, buf),
+            ScoreboardState::SaveWarning(ref warning) => warning.render(display, buf),
+        }
+    }
+}
+
+/// A column of option values in the high scores table
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Column {
+    /// A column showing the values of a single option
+    Single(OptKey),
+
+    /// A column showing each of the options for which [`is_flag()`] is true
+    /// as a single character
+    Flags,
+}
+
+impl Column {
+    /// Return the option columns in left-to-right order
+    fn all() -> Vec<Column> {
+        OptKey::iter()
+            .filter(|&key| !is_flag(key))
+            .map(Column::Single)
+            .chain([Column::Flags])
+            .collect()
+    }
+}
+
+/// Is the given option shown as a single character in the "Flags" column of
+/// the high scores table rather than in a column of its own?
+fn is_flag(key: OptKey) -> bool {
+    match key {
+        OptKey::Combos
+        | OptKey::StrictCollisions
+        | OptKey::PowerUps
+        | OptKey::Hydra
+        | OptKey::Adaptive
+        | OptKey::Terrain => true,
+        OptKey::Wraparound
+        | OptKey::Obstacles
+        | OptKey::Fruits
+        | OptKey::LevelSize
+        | OptKey::Layout
+        | OptKey::Spawn
+        | OptKey::Start => false,
+    }
+}
+
+/// Return the options shown in the "Flags" column, in left-to-right order
+fn flag_keys() -> Vec<OptKey> {
+    OptKey::iter().filter(|&key| is_flag(key)).collect()
+}
+
+/// Return the options in the order in which their filters are selected with
+/// the left & right arrow keys: the options with their own columns, followed
+/// by the flags
+fn filter_order() -> Vec<OptKey> {
+    let (flags, singles): (Vec<_>, Vec<_>) = OptKey::iter().partition(|&key| is_flag(key));
+    singles.into_iter().chain(flags).collect()
+}
+
+/// Return the character shown for the flag `key` with value `value` in the
+/// "Flags" column: the first letter of the option's name if it is set, a
+/// hyphen otherwise
+fn flag_cell(key: OptKey, value: OptValue) -> char {
+    if value == OptValue::Bool(true) {
+        key.as_str().chars().next().unwrap_or('✓')
+    } else {
+        '-'
+    }
+}
+
+/// Return the header for the column for the given option in the high scores
+/// table.  Flags share the "Flags" column.
+fn column_header(key: OptKey) -> &'static str {
+    match key {
+        OptKey::Wraparound => tr(Msg::ColumnWrap),
+        OptKey::Obstacles => tr(Msg::ColumnObstacles),
+        OptKey::LevelSize => tr(Msg::ColumnSize),
+        OptKey::Fruits => tr(Msg::ColumnFruits),
+        OptKey::Layout => tr(Msg::ColumnLayout),
+        OptKey::Start => tr(Msg::ColumnStart),
+        OptKey::Spawn => key.as_str(),
+        OptKey::Combos
+        | OptKey::StrictCollisions
+        | OptKey::PowerUps
+        | OptKey::Hydra
+        | OptKey::Adaptive
+        | OptKey::Terrain => tr(Msg::ColumnFlags),
+    }
+}
+
+/// Return the width of the column for the given option in the high scores
+/// table.  Each flag takes up one character of the "Flags" column.
+fn column_width(key: OptKey) -> usize {
+    match key {
+        OptKey::Fruits => 3,
+        OptKey::Wraparound | OptKey::Obstacles | OptKey::Layout => 4,
+        OptKey::Start => 5,
+        OptKey::LevelSize | OptKey::Spawn => 6,
+        OptKey::Combos
+        | OptKey::StrictCollisions
+        | OptKey::PowerUps
+        | OptKey::Hydra
+        | OptKey::Adaptive
+        | OptKey::Terrain => 1,
+    }
+}
+
+/// Return a short description of how long before `now` the time `then` was,
+/// e.g., "3d ago"
+fn relative_age(then: Timestamp, now: Timestamp) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+    let secs = now.duration_since(then).as_secs();
+    let (msg, n) = if secs < MINUTE {
+        return String::from(tr(Msg::JustNow));
+    } else if secs < HOUR {
+        (Msg::MinutesAgo, secs / MINUTE)
+    } else if secs < DAY {
+        (Msg::HoursAgo, secs / HOUR)
+    } else if secs < MONTH {
+        (Msg::DaysAgo, secs / DAY)
+    } else if secs < YEAR {
+        (Msg::MonthsAgo, secs / MONTH)
+    } else {
+        (Msg::YearsAgo, secs / YEAR)
+    };
+    tr_args(msg, &[("n", &n)])
+}
+
+/// Return the text used to display an option value in the high scores table
+fn cell_text(value: OptValue) -> String {
+    match value {
+        OptValue::Bool(b) => checkmark(b).to_owned(),
+        OptValue::FruitQty(frq) => frq.to_string(),
+        OptValue::LevelSize(sz) => {
+            // Custom sizes that don't fit in the column are just labelled as
+            // custom
+            let text = sz.to_string();
+            if text.chars().count() > column_width(OptKey::LevelSize) {
+                tr(Msg::Custom).to_owned()
+            } else {
+                text
+            }
+        }
+        OptValue::LevelLayout(lay) => lay.to_string(),
+        OptValue::SpawnStrategy(spawn) => spawn.to_string(),
+        OptValue::StartPosition(start) => String::from(tr(match start {
+            StartPosition::Center => Msg::ColumnStartCenter,
+            StartPosition::Corner => Msg::ColumnStartCorner,
+            StartPosition::Random => Msg::ColumnStartRandom,
+        })),
+    }
+}
+
+/// Return the symbol used to display a boolean option in the high scores table
+fn checkmark(b: bool) -> &'static str {
+    if b {
+        "✓"
+    } else {
+        "-"
+    }
+}
+
+/// The orders in which the high scores screen can list high scores
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SortOrder {
+    /// Highest scores first
+    Score,
+
+    /// Most recently achieved scores first
+    Date,
+}
+
+impl SortOrder {
+    /// Return the other sort order
+    fn toggled(self) -> SortOrder {
+        match self {
+            SortOrder::Score => SortOrder::Date,
+            SortOrder::Date => SortOrder::Score,
+        }
+    }
+
+    /// Return a human-readable name for the sort order
+    fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Score => tr(Msg::SortScore),
+            SortOrder::Date => tr(Msg::SortDate),
+        }
+    }
+}
+
+/// An enum of the states that the high scores screen can be in
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ScoreboardState {
+    /// Normal operation
+    Normal,
+
+    /// The user is being asked to confirm a deletion
+    Confirm(Confirm, Deletion),
+
+    /// A warning is being displayed about failure to save the high scores to
+    /// disk after a deletion
+    SaveWarning(Warning),
+}
+
+/// The high scores that the user has asked to delete
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Deletion {
+    /// The currently-selected high score
+    Selected,
+
+    /// All high scores
+    All,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highscores::HighScores;
+    use crate::options::{FruitQty, LevelLayout, LevelSize, SpawnStrategy};
+    use crate::util::LayoutDirection;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use jiff::Timestamp;
+    use rstest::rstest;
+    use std::num::NonZeroU32;
+    use tempfile::TempDir;
+
+    /// Return a `Globals` with a few high scores and a configuration that
+    /// stores data files in `tmpdir`
+    fn globals(tmpdir: &TempDir) -> Globals {
+        let mut globals = Globals::in_tempdir(tmpdir, "");
+        globals
+            .high_scores
+            .insert(Options::default(), NonZeroU32::new(23).unwrap().into());
+        globals.high_scores.insert(
+            Options {
+                wraparound: true,
+                obstacles: true,
+                fruits: FruitQty::new(3).unwrap(),
+                level_size: LevelSize::Small,
+                layout: LevelLayout::Maze,
+                combos: true,
+                spawn: SpawnStrategy::NearWalls,
+                strict_collisions: true,
+                power_ups: true,
+                hydra: true,
+                start: StartPosition::Corner,
+                adaptive: true,
+                terrain: true,
+            },
+            NonZeroU32::new(42).unwrap().into(),
+        );
+        globals.high_scores.insert(
+            Options {
+                level_size: LevelSize::Medium,
+                ..Options::default()
+            },
+            HighScore {
+                score: NonZeroU32::new(17).unwrap(),
+                slow_motion: true,
+                achieved: Some("2025-06-01T00:00:00Z".parse().unwrap()),
+                duration_ms: Some(60_000),
+                name: None,
+            },
+        );
+        globals
+    }
+
+    #[test]
+    fn render() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        board.now = "2025-06-04T01:00:00Z".parse().unwrap();
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        board.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("scoreboard/render", &buffer);
+    }
+
+    #[test]
+    fn render_names() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut globals = globals(&tmpdir);
+        globals.config.game.ask_name = true;
+        assert!(globals.high_scores.set_name(
+            Options::default(),
+            &NonZeroU32::new(23).unwrap().into(),
+            String::from("Alice")
+        ));
+        let mut board = Scoreboard::new(globals);
+        board.now = "2025-06-04T01:00:00Z".parse().unwrap();
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        board.render(area, &mut buffer);
+        crate::snapshot::assert_snapshot("scoreboard/render_names", &buffer);
+    }
+
+    #[test]
+    fn custom_size_cell() {
+        let fits = LevelSize::custom(80, 10).unwrap();
+        assert_eq!(cell_text(OptValue::LevelSize(fits)), "80×10");
+        let too_wide = LevelSize::custom(500, 200).unwrap();
+        assert_eq!(cell_text(OptValue::LevelSize(too_wide)), "Custom");
+    }
+
+    #[test]
+    fn delete_selected() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Delete.into()))
+            .is_none());
+        assert!(matches!(board.state, ScoreboardState::Confirm(..)));
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('y').into()))
+            .is_none());
+        assert_eq!(board.state, ScoreboardState::Normal);
+        assert_eq!(board.entries.len(), 2);
+        assert_eq!(
+            board.entries[0],
+            (Options::default(), NonZeroU32::new(23).unwrap().into())
+        );
+        let saved = board.globals.config.load_high_scores().unwrap().value;
+        assert_eq!(saved, board.globals.high_scores);
+        assert_eq!(saved.ranked(), board.entries);
+    }
+
+    #[test]
+    fn race_selected() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        // The top score's game duration is unknown
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('r').into()))
+            .is_none());
+        assert!(board
+            .handle_event(Event::Key(KeyCode::End.into()))
+            .is_none());
+        let Some(Screen::Game(_)) = board.handle_event(Event::Key(KeyCode::Char('r').into()))
+        else {
+            panic!("Racing a score with a known duration did not start a game");
+        };
+        assert_eq!(board.globals.options.level_size, LevelSize::Medium);
+    }
+
+    #[test]
+    fn chords() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        assert!(board
+            .handle_event(Event::Key(KeyCode::End.into()))
+            .is_none());
+        assert_eq!(board.selection, 2);
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('g').into()))
+            .is_none());
+        assert_eq!(board.selection, 2);
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        board.render(area, &mut buffer);
+        let title = (0..80).map(|x| buffer[(x, 0)].symbol()).collect::<String>();
+        assert!(title.ends_with(" g… "), "{title:?}");
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('g').into()))
+            .is_none());
+        assert_eq!(board.selection, 0);
+        assert!(board
+            .handle_event(Event::Key(KeyEvent::new(
+                KeyCode::Char('Z'),
+                KeyModifiers::SHIFT
+            )))
+            .is_none());
+        assert!(matches!(
+            board.handle_event(Event::Key(KeyEvent::new(
+                KeyCode::Char('Z'),
+                KeyModifiers::SHIFT
+            ))),
+            Some(Screen::Quit)
+        ));
+    }
+
+    #[test]
+    fn cancel_clear_all() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('c').into()))
+            .is_none());
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('n').into()))
+            .is_none());
+        assert_eq!(board.state, ScoreboardState::Normal);
+        assert_eq!(board.entries.len(), 3);
+        assert!(!tmpdir.path().join("highscores").exists());
+    }
+
+    #[test]
+    fn filter_slow_motion() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        assert_eq!(board.entries.len(), 3);
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('f').into()))
+            .is_none());
+        assert!(board.hide_slow_motion);
+        assert_eq!(board.entries.len(), 2);
+        assert!(board.entries.iter().all(|(_, hs)| !hs.slow_motion));
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('f').into()))
+            .is_none());
+        assert_eq!(board.entries.len(), 3);
+    }
+
+    #[test]
+    fn sort_by_date() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut globals = globals(&tmpdir);
+        let wrap = Options {
+            wraparound: true,
+            ..Options::default()
+        };
+        let stamp = "2025-07-01T12:00:00Z".parse::<Timestamp>().unwrap();
+        globals.high_scores.insert(
+            wrap,
+            HighScore {
+                score: NonZeroU32::new(5).unwrap(),
+                slow_motion: false,
+                achieved: Some(stamp),
+                duration_ms: None,
+                name: None,
+            },
+        );
+        globals.high_scores.insert(
+            Options::default(),
+            HighScore {
+                score: NonZeroU32::new(23).unwrap(),
+                slow_motion: false,
+                achieved: Some(stamp - jiff::SignedDuration::from_hours(24)),
+                duration_ms: None,
+                name: None,
+            },
+        );
+        let mut board = Scoreboard::new(globals);
+        assert_eq!(board.entries[0].1.score.get(), 42);
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('o').into()))
+            .is_none());
+        assert_eq!(board.sort, SortOrder::Date);
+        assert_eq!(
+            board
+                .entries
+                .iter()
+                .map(|(_, hs)| hs.score.get())
+                .collect::<Vec<_>>(),
+            // The undated score from `globals()` comes last
+            [5, 23, 17, 42, 23]
+        );
+    }
+
+    #[test]
+    fn filter_columns_right_to_left() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut globals = globals(&tmpdir);
+        globals.config.layout_direction = LayoutDirection::RightToLeft;
+        let mut board = Scoreboard::new(globals);
+        assert_eq!(board.filter_column, OptKey::min());
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Right.into()))
+            .is_none());
+        assert_eq!(board.filter_column, OptKey::min());
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Left.into()))
+            .is_none());
+        assert_eq!(board.filter_column.prev(), Some(OptKey::min()));
+        let buffer = crate::snapshot::render(&board);
+        let first = column_header(OptKey::min());
+        let last = column_header(OptKey::max());
+        let header = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .find(|row| row.contains(first) && row.contains(last))
+            .unwrap();
+        assert!(header.find(last) < header.find(first), "{header:?}");
+    }
+
+    #[test]
+    fn filter_options() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        // Select the "Size" column
+        for _ in 0..3 {
+            assert!(board
+                .handle_event(Event::Key(KeyCode::Right.into()))
+                .is_none());
+        }
+        assert_eq!(board.filter_column, OptKey::LevelSize);
+        let mut sizes = Vec::new();
+        for _ in 0..3 {
+            assert!(board
+                .handle_event(Event::Key(KeyCode::Enter.into()))
+                .is_none());
+            let Some(OptValue::LevelSize(size)) = board.filters[OptKey::LevelSize] else {
+                panic!("Size filter not set");
+            };
+            assert_eq!(board.entries.len(), 1);
+            assert_eq!(board.entries[0].0.level_size, size);
+            sizes.push(size);
+        }
+        assert_eq!(
+            sizes,
+            [LevelSize::Small, LevelSize::Large, LevelSize::Medium]
+        );
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Enter.into()))
+            .is_none());
+        assert_eq!(board.filters[OptKey::LevelSize], None);
+        assert_eq!(board.entries.len(), 3);
+    }
+
+    #[test]
+    fn filter_flags() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        // The flags' filters come after those of the other columns
+        for _ in 0..7 {
+            assert!(board
+                .handle_event(Event::Key(KeyCode::Right.into()))
+                .is_none());
+        }
+        assert_eq!(board.filter_column, OptKey::Combos);
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Enter.into()))
+            .is_none());
+        assert_eq!(board.filters[OptKey::Combos], Some(OptValue::Bool(true)));
+        assert_eq!(board.entries.len(), 1);
+        let buffer = crate::snapshot::render(&board);
+        let filter_row = (0..buffer.area.width)
+            .map(|x| buffer[(x, 3)].symbol())
+            .collect::<String>();
+        assert!(filter_row.contains(" C····· "), "{filter_row:?}");
+        for _ in 0..10 {
+            assert!(board
+                .handle_event(Event::Key(KeyCode::Right.into()))
+                .is_none());
+        }
+        assert_eq!(board.filter_column, OptKey::Terrain);
+    }
+
+    #[rstest]
+    #[case("2025-07-01T11:59:30Z", "just now")]
+    #[case("2025-07-01T11:15:00Z", "45m ago")]
+    #[case("2025-06-30T13:00:00Z", "23h ago")]
+    #[case("2025-06-28T12:00:00Z", "3d ago")]
+    #[case("2025-03-01T00:00:00Z", "4mo ago")]
+    #[case("2022-01-01T00:00:00Z", "3y ago")]
+    #[case("2025-07-02T00:00:00Z", "just now")]
+    fn test_relative_age(#[case] then: Timestamp, #[case] age: &str) {
+        let now = "2025-07-01T12:00:00Z".parse::<Timestamp>().unwrap();
+        assert_eq!(relative_age(then, now), age);
+    }
+
+    #[test]
+    fn clear_all() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut board = Scoreboard::new(globals(&tmpdir));
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('c').into()))
+            .is_none());
+        assert!(board
+            .handle_event(Event::Key(KeyCode::Char('y').into()))
+            .is_none());
+        assert!(board.entries.is_empty());
+        assert_eq!(
+            board.globals.config.load_high_scores().unwrap().value,
+            HighScores::default()
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::util::SaveError;
+// Use ratatui's crossterm so that its colors can be converted
+use ratatui::crossterm::{
+    style::{SetBackgroundColor, SetForegroundColor},
+    Command,
+};
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use std::fmt::Write as _;
+use std::io::{ErrorKind, Write as _};
+use std::path::{Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
+
+/// Write the contents of `buf` as text to a new file in `dir` named after the
+/// current time and return the file's path.  If `ansi` is true, the text
+/// will include ANSI escape sequences reproducing the buffer's styling.
+///
+/// # Errors
+///
+/// Returns `Err` if creating `dir` failed or if writing the file failed.
+pub(crate) fn save_screenshot(buf: &Buffer, dir: &Path, ansi: bool) -> Result<PathBuf, SaveError> {
+    fs_err::create_dir_all(dir).map_err(|e| SaveError::mkdir("screenshot", e))?;
+    let stamp = jiff::Zoned::now().strftime("%Y-%m-%dT%H-%M-%S").to_string();
+    let text = buffer_to_text(buf, ansi);
+    let mut n = 1;
+    loop {
+        let path = if n == 1 {
+            dir.join(format!("{stamp}.txt"))
+        } else {
+            dir.join(format!("{stamp}-{n}.txt"))
+        };
+        match fs_err::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut fp) => {
+                fp.write_all(text.as_bytes())
+                    .map_err(|e| SaveError::write("screenshot", e))?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(SaveError::write("screenshot", e)),
+        }
+    }
+}
+
+/// Convert the contents of `buf` to a string, one line per row, with trailing
+/// whitespace removed from each line.  If `ansi` is true, ANSI escape
+/// sequences are inserted to reproduce the styling of the cells.
+pub(crate) fn buffer_to_text(buf: &Buffer, ansi: bool) -> String {
+    let mut s = String::new();
+    for y in buf.area.top()..buf.area.bottom() {
+        let mut line = String::new();
+        let mut current = CellStyle::PLAIN;
+        // The length of `line` up to the end of the last visible cell, and
+        // the style in effect at that point
+        let mut content_len = 0;
+        let mut content_style = CellStyle::PLAIN;
+        let mut skip = 0;
+        for x in buf.area.left()..buf.area.right() {
+            let Some(cell) = buf.cell((x, y)) else {
+                continue;
+            };
+            // Skip cells hidden by a preceding wide character
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let symbol = cell.symbol();
+            skip = symbol.width().saturating_sub(1);
+            let style = CellStyle {
+                fg: cell.fg,
+                bg: cell.bg,
+                modifier: cell.modifier,
+            };
+            if ansi && style != current {
+                line.push_str(&style.sgr());
+                current = style;
+            }
+            line.push_str(symbol);
+            if !symbol.trim().is_empty() || (ansi && style != CellStyle::PLAIN) {
+                content_len = line.len();
+                content_style = style;
+            }
+        }
+        line.truncate(content_len);
+        s.push_str(&line);
+        if ansi && content_style != CellStyle::PLAIN {
+            s.push_str("\x1b[0m");
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// The styling of a single buffer cell
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct CellStyle {
+    fg: Color,
+    bg: Color,
+    modifier: Modifier,
+}
+
+impl CellStyle {
+    /// The style of a cell with no styling
+    const PLAIN: CellStyle = CellStyle {
+        fg: Color::Reset,
+        bg: Color::Reset,
+        modifier: Modifier::empty(),
+    };
+
+    /// Return an ANSI escape sequence that resets the terminal's styling and
+    /// then applies this style
+    fn sgr(self) -> String {
+        let mut s = String::from("\x1b[0m");
+        if self.fg != Color::Reset {
+            let _ = SetForegroundColor(self.fg.into()).write_ansi(&mut s);
+        }
+        if self.bg != Color::Reset {
+            let _ = SetBackgroundColor(self.bg.into()).write_ansi(&mut s);
+        }
+        for (modifier, code) in [
+            (Modifier::BOLD, 1),
+            (Modifier::DIM, 2),
+            (Modifier::ITALIC, 3),
+            (Modifier::UNDERLINED, 4),
+            (Modifier::SLOW_BLINK, 5),
+            (Modifier::RAPID_BLINK, 6),
+            (Modifier::REVERSED, 7),
+            (Modifier::HIDDEN, 8),
+            (Modifier::CROSSED_OUT, 9),
+        ] {
+            if self.modifier.contains(modifier) {
+                let _ = write!(s, "\x1b[{code}m");
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{
+        layout::Rect,
+        style::{Style, Stylize},
+    };
+    use tempfile::TempDir;
+
+    fn sample() -> Buffer {
+        let mut buf = Buffer::with_lines([" Score: 3 ", "", " ●⚬⚬  ×   ", "全角 x"]);
+        buf.set_style(Rect::new(0, 0, 10, 1), Style::new().reversed());
+        buf.set_style(Rect::new(1, 2, 3, 1), Style::new().green().bold());
+        buf
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(
+            buffer_to_text(&sample(), false),
+            " Score: 3\n\n ●⚬⚬  ×\n全角 x\n"
+        );
+    }
+
+    #[test]
+    fn ansi_text() {
+        assert_eq!(
+            buffer_to_text(&sample(), true),
+            concat!(
+                "\x1b[0m\x1b[7m Score: 3 \x1b[0m\n",
+                "\n",
+                " \x1b[0m\x1b[38;5;2m\x1b[1m●⚬⚬\x1b[0m  ×\n",
+                "全角 x\n",
+            )
+        );
+    }
+
+    #[test]
+    fn save_twice() {
+        let tmpdir = TempDir::new().unwrap();
+        let dir = tmpdir.path().join("screenshots");
+        let buf = sample();
+        let path1 = save_screenshot(&buf, &dir, false).unwrap();
+        let path2 = save_screenshot(&buf, &dir, false).unwrap();
+        assert_ne!(path1, path2);
+        assert_eq!(path1.parent(), Some(&*dir));
+        assert_eq!(
+            std::fs::read_to_string(&path1).unwrap(),
+            buffer_to_text(&buf, false)
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Scripts of timed key presses, for recording demos and reproducing bug
+//! reports.
+//!
+//! A script is a text file with one key press per line, each written as a
+//! delay followed by a key, e.g., `200ms down` or `1s ctrl-r`.  The delay is
+//! measured from the previous key press in the script (or, for the first key,
+//! from the start of the program) and is given as a whole number of
+//! milliseconds (`ms`) or seconds (`s`).  Keys are written in the same format
+//! as key bindings in the configuration file.  Blank lines and lines starting
+//! with `#` are ignored.
+use crate::command::{KeyBinding, ParseKeyBindingError};
+use crate::input::EventSource;
+use crossterm::event::Event;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// A parsed script: a sequence of key presses, each preceded by a delay
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Script(VecDeque<(Duration, Event)>);
+
+impl Script {
+    /// Read & parse the script at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file could not be read or could not be parsed
+    pub(crate) fn load(path: &Path) -> Result<Script, ScriptError> {
+        fs_err::read_to_string(path)?.parse()
+    }
+}
+
+impl std::str::FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Script, ScriptError> {
+        let mut steps = VecDeque::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_err = |kind| ScriptError::Parse { line: i + 1, kind };
+            let Some((delay, key)) = line.split_once(char::is_whitespace) else {
+                return Err(parse_err(ParseStepError::MissingKey));
+            };
+            let delay = parse_delay(delay).ok_or_else(|| parse_err(ParseStepError::Delay))?;
+            let key = key
+                .trim()
+                .parse::<KeyBinding>()
+                .map_err(|e| parse_err(ParseStepError::Key(e)))?;
+            steps.push_back((delay, Event::Key(key.key_event())));
+        }
+        Ok(Script(steps))
+    }
+}
+
+/// Parse a delay of the form `<n>ms` or `<n>s`
+fn parse_delay(s: &str) -> Option<Duration> {
+    if let Some(ms) = s.strip_suffix("ms") {
+        ms.parse::<u64>().ok().map(Duration::from_millis)
+    } else if let Some(secs) = s.strip_suffix('s') {
+        secs.parse::<u64>().ok().map(Duration::from_secs)
+    } else {
+        None
+    }
+}
+
+/// An [`EventSource`] that plays back a [`Script`] as though its keys were
+/// typed at the given times, while still passing through events from an inner
+/// source (normally the terminal) so that the program can be interrupted.
+/// Once the script is exhausted, events come solely from the inner source.
+#[derive(Debug)]
+pub(crate) struct ScriptPlayer<E> {
+    /// The key presses remaining to be played back
+    script: Script,
+
+    /// The time at which the previous key press in the script was due (or,
+    /// before any have been played back, when playback started)
+    last_due: Instant,
+
+    /// The source of events that are not part of the script
+    inner: E,
+}
+
+impl<E: EventSource> ScriptPlayer<E> {
+    /// Start playing back `script`, with the first delay measured from now
+    pub(crate) fn new(script: Script, inner: E) -> ScriptPlayer<E> {
+        ScriptPlayer {
+            script,
+            last_due: Instant::now(),
+            inner,
+        }
+    }
+
+    /// Return the time at which the next key press in the script is due, if
+    /// any
+    fn next_due(&self) -> Option<Instant> {
+        self.script
+            .0
+            .front()
+            .map(|&(delay, _)| self.last_due + delay)
+    }
+}
+
+impl<E: EventSource> EventSource for ScriptPlayer<E> {
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<bool> {
+        let Some(due) = self.next_due() else {
+            return self.inner.poll(timeout);
+        };
+        let until_due = due.saturating_duration_since(Instant::now());
+        if self.inner.poll(timeout.min(until_due))? {
+            Ok(true)
+        } else {
+            Ok(timeout >= until_due)
+        }
+    }
+
+    fn read(&mut self) -> std::io::Result<Event> {
+        loop {
+            let Some(due) = self.next_due() else {
+                return self.inner.read();
+            };
+            let until_due = due.saturating_duration_since(Instant::now());
+            if until_due.is_zero() {
+                if let Some((_, event)) = self.script.0.pop_front() {
+                    self.last_due = due;
+                    return Ok(event);
+                }
+            } else if self.inner.poll(until_due)? {
+                return self.inner.read();
+            }
+        }
+    }
+}
+
+/// An error reading or parsing a script
+#[derive(Debug, Error)]
+pub(crate) enum ScriptError {
+    #[error("failed to read script file")]
+    Read(#[from] std::io::Error),
+    #[error("invalid script line {line}")]
+    Parse {
+        line: usize,
+        #[source]
+        kind: ParseStepError,
+    },
+}
+
+/// An error parsing a line of a script
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub(crate) enum ParseStepError {
+    #[error("expected a delay followed by a key")]
+    MissingKey,
+    #[error("invalid delay; expected a whole number followed by \"ms\" or \"s\"")]
+    Delay,
+    #[error("invalid key")]
+    Key(#[source] ParseKeyBindingError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ScriptedEvents;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn parse() {
+        let script = "# A demo\n200ms Down\n\n  1s ctrl-r\n0ms q\n"
+            .parse::<Script>()
+            .unwrap();
+        assert_eq!(
+            script,
+            Script(VecDeque::from([
+                (
+                    Duration::from_millis(200),
+                    Event::Key(KeyEvent::from(KeyCode::Down))
+                ),
+                (
+                    Duration::from_secs(1),
+                    Event::Key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL))
+                ),
+                (
+                    Duration::ZERO,
+                    Event::Key(KeyEvent::from(KeyCode::Char('q')))
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let e = "100ms up\nup\n".parse::<Script>().unwrap_err();
+        assert!(matches!(
+            e,
+            ScriptError::Parse {
+                line: 2,
+                kind: ParseStepError::MissingKey
+            }
+        ));
+        let e = "1.5s up".parse::<Script>().unwrap_err();
+        assert!(matches!(
+            e,
+            ScriptError::Parse {
+                line: 1,
+                kind: ParseStepError::Delay
+            }
+        ));
+        let e = "\n\n5ms upward".parse::<Script>().unwrap_err();
+        assert!(matches!(
+            e,
+            ScriptError::Parse {
+                line: 3,
+                kind: ParseStepError::Key(_)
+            }
+        ));
+    }
+
+    #[test]
+    fn playback() {
+        let script = "0ms up\n30ms down\n".parse::<Script>().unwrap();
+        let inner = ScriptedEvents::new([Event::Key(KeyEvent::from(KeyCode::Char('x')))]);
+        let mut player = ScriptPlayer::new(script, inner);
+        let start = Instant::now();
+        // Events from the inner source are passed through
+        assert!(player.poll(Duration::ZERO).unwrap());
+        assert_eq!(
+            player.read().unwrap(),
+            Event::Key(KeyEvent::from(KeyCode::Up))
+        );
+        assert_eq!(
+            player.read().unwrap(),
+            Event::Key(KeyEvent::from(KeyCode::Char('x')))
+        );
+        // The next scripted key isn't available until its delay has passed
+        assert!(!player.poll(Duration::ZERO).unwrap());
+        assert_eq!(
+            player.read().unwrap(),
+            Event::Key(KeyEvent::from(KeyCode::Down))
+        );
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        assert!(!player.poll(Duration::from_millis(10)).unwrap());
+    }
+}
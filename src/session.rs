@@ -0,0 +1,282 @@
+//! Noticing sessions that ended without cleaning up.
+//!
+//! While the program is running, it periodically writes a "heartbeat" to the
+//! session file describing the current screen and the game in progress, if
+//! any, and it deletes the file when it quits normally.  If the file is
+//! still present at the next startup, the previous session ended abruptly
+//! (e.g., the program crashed or the terminal was closed), and any game that
+//! was in progress is recorded in the game history so that it isn't lost
+//! without a trace.
+//!
+//! Heartbeats are also written by a background thread whenever the program
+//! has been waiting for input for longer than [`consts::HEARTBEAT_INTERVAL`],
+//! so a session file whose latest heartbeat is recent belongs to another
+//! instance of the program that is still running rather than to one that
+//! crashed.
+use crate::consts;
+use crate::history::{EndCause, GameRecord};
+use crate::migrations::{self, SaveFile};
+use crate::util::{data_dir, temp_path, LoadError, SaveError};
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// The state of the program as of its most recent heartbeat
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct Heartbeat {
+    /// When the heartbeat was written
+    pub(crate) timestamp: Timestamp,
+
+    /// The screen being shown
+    pub(crate) screen: ScreenName,
+
+    /// A record of the game in progress as it would be if it ended now, or
+    /// `None` if no game was in progress
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) game: Option<GameRecord>,
+}
+
+impl Heartbeat {
+    /// Was this heartbeat written recently enough as of `now` that the
+    /// session that wrote it is presumably still running?
+    pub(crate) fn is_live_at(&self, now: Timestamp) -> bool {
+        let window =
+            SignedDuration::try_from(consts::HEARTBEAT_INTERVAL * 3).unwrap_or(SignedDuration::MAX);
+        now.duration_since(self.timestamp) < window
+    }
+
+    /// Return the parts of the heartbeat that, when they change, cause it to
+    /// be written immediately rather than waiting for the next interval.  A
+    /// game's seed stands in for the game itself, so that restarting a game
+    /// counts as a change.
+    fn milestone(&self) -> (ScreenName, Option<(Option<u64>, EndCause)>) {
+        (self.screen, self.game.as_ref().map(|g| (g.seed, g.cause)))
+    }
+}
+
+/// An enum of the screens named in [`Heartbeat`]s
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ScreenName {
+    Menu,
+    Game,
+    HighScores,
+    Progress,
+    About,
+}
+
+/// The session file, the last heartbeat written to it, and the background
+/// thread that keeps it fresh
+#[derive(Debug)]
+pub(crate) struct SessionFile {
+    /// The path to the file
+    path: PathBuf,
+
+    /// The last heartbeat written and when it was written, if one has been
+    /// written yet.  The mutex is held while writing to the file so that
+    /// the background thread and the main thread don't write at once.
+    latest: Arc<Mutex<Option<(Heartbeat, Instant)>>>,
+
+    /// The background thread that rewrites the last heartbeat with a fresh
+    /// timestamp while no new ones are coming in, along with the channel
+    /// whose closing tells it to stop.  This is `None` until the first
+    /// heartbeat is written.
+    keeper: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+impl SessionFile {
+    /// Track the session using the file at `path`
+    pub(crate) fn new(path: PathBuf) -> SessionFile {
+        SessionFile {
+            path,
+            latest: Arc::new(Mutex::new(None)),
+            keeper: None,
+        }
+    }
+
+    /// Return the default filepath used for the session file
+    pub(crate) fn default_path() -> Option<PathBuf> {
+        data_dir().map(|p| p.join("session.json"))
+    }
+
+    /// Read the last heartbeat left behind by a previous session.  If the
+    /// file does not exist (i.e., the previous session quit normally),
+    /// `None` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file could not be read or if its contents could
+    /// not be deserialized.
+    pub(crate) fn previous(&self) -> Result<Option<Heartbeat>, LoadError> {
+        match fs_err::read(&self.path) {
+            Ok(src) => migrations::from_json(SaveFile::Session, &src).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(LoadError::read("session", e)),
+        }
+    }
+
+    /// Write `heartbeat` to the file if at least
+    /// [`consts::HEARTBEAT_INTERVAL`] has passed since the last one was
+    /// written or if it shows a different screen, a different game, or the
+    /// end of a game compared to the last one
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if creating the file's parent directories failed, if
+    /// serializing the heartbeat failed, or if writing the file failed.
+    pub(crate) fn beat(&mut self, heartbeat: Heartbeat) -> Result<(), SaveError> {
+        {
+            let Ok(mut latest) = self.latest.lock() else {
+                return Ok(());
+            };
+            if latest.as_ref().is_some_and(|(last, written)| {
+                last.milestone() == heartbeat.milestone()
+                    && written.elapsed() < consts::HEARTBEAT_INTERVAL
+            }) {
+                return Ok(());
+            }
+            let r = write_heartbeat(&self.path, &heartbeat);
+            *latest = Some((heartbeat, Instant::now()));
+            r?;
+        }
+        if self.keeper.is_none() {
+            let (sender, receiver) = mpsc::channel();
+            let path = self.path.clone();
+            let latest = Arc::clone(&self.latest);
+            let handle = std::thread::spawn(move || loop {
+                match receiver.recv_timeout(consts::HEARTBEAT_INTERVAL) {
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+                }
+                let Ok(mut latest) = latest.lock() else {
+                    return;
+                };
+                if let Some((heartbeat, written)) = latest.as_mut() {
+                    if written.elapsed() >= consts::HEARTBEAT_INTERVAL {
+                        heartbeat.timestamp = Timestamp::now();
+                        *written = Instant::now();
+                        let _ = write_heartbeat(&path, heartbeat);
+                    }
+                }
+            });
+            self.keeper = Some((sender, handle));
+        }
+        Ok(())
+    }
+
+    /// Stop the background thread and delete the file at the end of a
+    /// session that is quitting normally
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file exists but could not be deleted
+    pub(crate) fn end(mut self) -> std::io::Result<()> {
+        if let Some((sender, handle)) = self.keeper.take() {
+            drop(sender);
+            let _ = handle.join();
+        }
+        match fs_err::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Write `heartbeat` to the file at `path`, replacing its contents.  The
+/// heartbeat is first written to a temporary file that is then renamed over
+/// `path` so that a crash in the middle of writing can't leave behind a
+/// truncated file.
+fn write_heartbeat(path: &Path, heartbeat: &Heartbeat) -> Result<(), SaveError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs_err::create_dir_all(parent).map_err(|e| SaveError::mkdir("session", e))?;
+    }
+    let mut src = migrations::to_json(SaveFile::Session, heartbeat)?;
+    src.push('\n');
+    let tmp = temp_path(path);
+    fs_err::write(&tmp, &src).map_err(|e| SaveError::write("session", e))?;
+    fs_err::rename(&tmp, path).map_err(|e| SaveError::write("session", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::EndCause;
+    use crate::options::Options;
+    use tempfile::TempDir;
+
+    #[test]
+    fn lifecycle() {
+        let tmpdir = TempDir::new().unwrap();
+        let path = tmpdir.path().join("state").join("session.json");
+        let mut session = SessionFile::new(path.clone());
+        assert_eq!(session.previous().unwrap(), None);
+        let heartbeat = Heartbeat {
+            timestamp: "2025-07-01T12:34:56Z".parse().unwrap(),
+            screen: ScreenName::Game,
+            game: Some(GameRecord {
+                timestamp: "2025-07-01T12:34:56Z".parse().unwrap(),
+                options: Options::default(),
+                seed: Some(42),
+                rng: None,
+                score: 17,
+                duration_ms: 61_000,
+                cause: EndCause::Interrupted,
+                peak_fill: None,
+            }),
+        };
+        session.beat(heartbeat.clone()).unwrap();
+        // Heartbeats less than the interval apart are skipped if they're
+        // still for the same game ...
+        let mut later = heartbeat.clone();
+        if let Some(game) = later.game.as_mut() {
+            game.score = 23;
+        }
+        session.beat(later).unwrap();
+        assert_eq!(session.previous().unwrap(), Some(heartbeat.clone()));
+        // ... but not if the game has been restarted, has ended, or the
+        // screen has changed
+        let mut restarted = heartbeat.clone();
+        if let Some(game) = restarted.game.as_mut() {
+            game.seed = Some(43);
+            game.score = 0;
+        }
+        session.beat(restarted.clone()).unwrap();
+        assert_eq!(session.previous().unwrap(), Some(restarted));
+        let mut ended = heartbeat.clone();
+        if let Some(game) = ended.game.as_mut() {
+            game.cause = EndCause::Wall;
+        }
+        session.beat(ended.clone()).unwrap();
+        assert_eq!(session.previous().unwrap(), Some(ended));
+        let menu = Heartbeat {
+            screen: ScreenName::Menu,
+            game: None,
+            ..heartbeat
+        };
+        session.beat(menu.clone()).unwrap();
+        assert_eq!(session.previous().unwrap(), Some(menu));
+        assert!(!path.with_extension("json.tmp").exists());
+        session.end().unwrap();
+        assert!(!path.exists());
+        // Ending a session whose file is already gone is not an error
+        SessionFile::new(path).end().unwrap();
+    }
+
+    #[test]
+    fn is_live_at() {
+        let heartbeat = Heartbeat {
+            timestamp: "2025-07-01T12:34:56Z".parse().unwrap(),
+            screen: ScreenName::Menu,
+            game: None,
+        };
+        assert!(heartbeat.is_live_at("2025-07-01T12:34:56Z".parse().unwrap()));
+        assert!(heartbeat.is_live_at("2025-07-01T12:35:06Z".parse().unwrap()));
+        assert!(!heartbeat.is_live_at("2025-07-01T12:35:56Z".parse().unwrap()));
+        assert!(!heartbeat.is_live_at("2025-07-02T12:34:56Z".parse().unwrap()));
+    }
+}
@@ -0,0 +1,50 @@
+//! Golden-file tests of rendered screens.
+//!
+//! A snapshot is the [`Debug`] representation of a [`Buffer`], which lists
+//! both the text of each line and the positions at which the style changes.
+//! Snapshots are stored under `src/snapshots/`, one file per snapshot.  After
+//! an intentional change to how something is drawn, run the tests with the
+//! `RATSNAKE_UPDATE_SNAPSHOTS` environment variable set to `1` to regenerate
+//! the affected files, and then review the changes to them before committing.
+use crate::consts;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use std::path::PathBuf;
+
+/// The environment variable that causes snapshots to be written instead of
+/// compared
+const UPDATE_VAR: &str = "RATSNAKE_UPDATE_SNAPSHOTS";
+
+/// Render `widget` in a buffer the size of the game's display
+pub(crate) fn render<W: Widget>(widget: W) -> Buffer {
+    let mut buffer = Buffer::empty(Rect::from((
+        ratatui::layout::Position::ORIGIN,
+        consts::DISPLAY_SIZE,
+    )));
+    widget.render(buffer.area, &mut buffer);
+    buffer
+}
+
+/// Assert that `buffer` matches the snapshot stored at
+/// `src/snapshots/{name}.txt`, or (re)write the snapshot if
+/// `RATSNAKE_UPDATE_SNAPSHOTS=1` is set
+#[track_caller]
+pub(crate) fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path = PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "src", "snapshots"])
+        .join(format!("{name}.txt"));
+    let actual = format!("{buffer:?}\n");
+    if std::env::var_os(UPDATE_VAR).is_some_and(|v| v == "1") {
+        if let Some(parent) = path.parent() {
+            fs_err::create_dir_all(parent).unwrap();
+        }
+        fs_err::write(&path, actual).unwrap();
+        return;
+    }
+    match fs_err::read_to_string(&path) {
+        Ok(expected) => pretty_assertions::assert_str_eq!(
+            actual,
+            expected,
+            "rendering does not match snapshot {name:?}; if the change is intentional, rerun the tests with {UPDATE_VAR}=1"
+        ),
+        Err(e) => panic!("failed to read snapshot {name:?} ({e}); run the tests with {UPDATE_VAR}=1 to create it"),
+    }
+}
@@ -0,0 +1,88 @@
+//! Helpers for drawing the fixed-width tables shown on the high scores &
+//! progress screens.
+use crate::consts;
+use crate::theme;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::Widget,
+};
+use std::fmt::Write as _;
+
+/// Return a header line for a table whose columns have the given titles &
+/// widths.  Each title is centered (and truncated if necessary) within its
+/// column, and the columns are separated by single spaces.  Room is left at
+/// the start of the line for the selection marker drawn by [`TableRows`].
+pub(crate) fn header<'a, I>(columns: I) -> Line<'static>
+where
+    I: IntoIterator<Item = (&'a str, usize)>,
+{
+    let mut s = String::from("  ");
+    for (i, (title, width)) in columns.into_iter().enumerate() {
+        if i > 0 {
+            s.push(' ');
+        }
+        let _ = write!(s, "{title:^width$.width$}");
+    }
+    Line::styled(s, consts::SCORE_BAR_STYLE)
+}
+
+/// A widget for drawing the body of a table: a list of preformatted rows, one
+/// of which is selected.  If there are more rows than fit in the area, the
+/// rows are scrolled so that the selected row is visible.
+#[derive(Clone, Debug)]
+pub(crate) struct TableRows<I> {
+    /// The rows to draw
+    rows: I,
+
+    /// The index of the selected row
+    selection: usize,
+}
+
+impl<I> TableRows<I> {
+    /// Create a new `TableRows` for the given rows and selected row index
+    pub(crate) fn new(rows: I, selection: usize) -> Self {
+        TableRows { rows, selection }
+    }
+}
+
+impl<I: IntoIterator<Item = String>> Widget for TableRows<I> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let visible = usize::from(area.height);
+        let offset = (self.selection + 1).saturating_sub(visible);
+        for ((i, text), row) in self
+            .rows
+            .into_iter()
+            .enumerate()
+            .skip(offset)
+            .zip(area.rows())
+        {
+            let selected = i == self.selection;
+            let style = if selected {
+                theme::selection_style()
+            } else {
+                Style::new()
+            };
+            let s = format!("{:2}{text}", if selected { "»" } else { "" });
+            Span::styled(s, style).render(row, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrolled_rows() {
+        let area = Rect::new(0, 0, 7, 3);
+        let mut buffer = Buffer::empty(area);
+        let rows = (1..=5).map(|i| format!("row {i}"));
+        TableRows::new(rows, 3).render(area, &mut buffer);
+        let mut expected = Buffer::with_lines(["  row 2", "  row 3", "» row 4"]);
+        expected.set_style(Rect::new(0, 2, 7, 1), theme::selection_style());
+        pretty_assertions::assert_eq!(buffer, expected);
+    }
+}
@@ -0,0 +1,130 @@
+//! Setting up the terminal for the application and restoring it afterwards.
+//!
+//! Restoration is guaranteed by [`TerminalGuard`], which restores the
+//! terminal when dropped, and by a panic hook that restores the terminal
+//! before the panic message is printed, so that a crash doesn't leave the
+//! terminal in raw mode or on the alternate screen.
+use anyhow::Context;
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, DefaultTerminal, Terminal};
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the terminal is currently set up for the application and thus
+/// needs to be restored
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// A guard that keeps the terminal set up for the application for as long as
+/// it exists
+#[derive(Debug)]
+pub(crate) struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    /// Set up the terminal — raw mode, the alternate screen, focus events, a
+    /// hidden cursor, no bracketed paste, and (if `mouse` is `true`) mouse
+    /// capture — and install a panic hook that restores it.  Returns the
+    /// guard along with a `Terminal` for drawing on stdout.
+    pub(crate) fn init(mouse: bool) -> anyhow::Result<(TerminalGuard, DefaultTerminal)> {
+        set_panic_hook();
+        ACTIVE.store(true, Ordering::SeqCst);
+        // If anything below fails, dropping the guard undoes whatever
+        // succeeded
+        let guard = TerminalGuard { _private: () };
+        enable_raw_mode().context("failed to set up terminal")?;
+        execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            EnableFocusChange,
+            DisableBracketedPaste,
+            Hide
+        )
+        .context("failed to set up terminal")?;
+        if mouse {
+            execute!(io::stdout(), EnableMouseCapture).context("failed to set up terminal")?;
+        }
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))
+            .context("failed to set up terminal")?;
+        Ok((guard, terminal))
+    }
+
+    /// Restore the terminal, reporting any errors that occur
+    pub(crate) fn restore(self) -> anyhow::Result<()> {
+        reset().context("failed to clean up terminal")
+    }
+}
+
+/// Enable or disable mouse capture, e.g., after the `mouse-steering` setting
+/// has been changed in the configuration file.  This does nothing if the
+/// terminal isn't set up for the application (as when testing).
+pub(crate) fn set_mouse_capture(enabled: bool) -> io::Result<()> {
+    if !ACTIVE.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    if enabled {
+        execute!(io::stdout(), EnableMouseCapture)
+    } else {
+        execute!(io::stdout(), DisableMouseCapture)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = reset();
+    }
+}
+
+/// A command that turns off bracketed paste mode, in case it was left on by a
+/// previous program.  The application has no use for pasted text, and
+/// crossterm's own command for this requires making input events non-`Copy`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct DisableBracketedPaste;
+
+impl crossterm::Command for DisableBracketedPaste {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B[?2004l")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Replace the panic hook with one that restores the terminal before calling
+/// the original hook, which prints the panic message
+fn set_panic_hook() {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = reset();
+        hook(info);
+    }));
+}
+
+/// Undo the changes made by [`TerminalGuard::init()`], if they haven't been
+/// undone already.  All of the changes are undone even if some fail, in which
+/// case the first error is returned.
+fn reset() -> io::Result<()> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    // Disabling raw mode first is important, as it has more side effects
+    // than leaving the alternate screen.
+    let r = disable_raw_mode();
+    let r2 = execute!(
+        io::stdout(),
+        DisableBracketedPaste,
+        DisableFocusChange,
+        DisableMouseCapture,
+        Show,
+        LeaveAlternateScreen
+    );
+    r.and(r2)
+}
@@ -0,0 +1,134 @@
+use crate::consts;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::Widget,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// A single-line text field that the user can type into.  Text can only be
+/// added or removed at the end.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct TextInput {
+    /// The text entered so far
+    value: String,
+
+    /// The maximum display width of the text
+    max_width: u16,
+}
+
+impl TextInput {
+    /// Create an empty text field that accepts text up to `max_width` columns
+    /// wide
+    pub(crate) fn new(max_width: u16) -> TextInput {
+        TextInput {
+            value: String::new(),
+            max_width,
+        }
+    }
+
+    /// Return the text entered so far
+    pub(crate) fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Return the display width of the field, including room for the cursor
+    pub(crate) fn width(&self) -> u16 {
+        self.max_width.saturating_add(1)
+    }
+
+    /// Update the text in response to a key press.  Printable characters are
+    /// appended (if there is room for them), and Backspace deletes the last
+    /// character.  Returns `false` if the key is not one that the field
+    /// responds to.
+    pub(crate) fn handle_key(&mut self, ev: KeyEvent) -> bool {
+        match ev.code {
+            KeyCode::Char(c)
+                if !c.is_control()
+                    && !ev
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                let width = self.value.width() + c.width().unwrap_or(0);
+                if width <= usize::from(self.max_width) {
+                    self.value.push(c);
+                }
+                true
+            }
+            KeyCode::Backspace => {
+                if let Some((i, _)) = self.value.grapheme_indices(true).next_back() {
+                    self.value.truncate(i);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Widget for &TextInput {
+    /// Render the text followed by a cursor in the given area of `buf`, with
+    /// the rest of the field underlined
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let used = u16::try_from(self.value.width()).unwrap_or(u16::MAX);
+        let padding = usize::from(self.max_width.saturating_sub(used));
+        Line::from_iter([
+            Span::styled(self.value.as_str(), consts::TEXT_INPUT_STYLE),
+            Span::styled(
+                " ",
+                consts::TEXT_INPUT_STYLE.patch(consts::TEXT_CURSOR_STYLE),
+            ),
+            Span::styled(" ".repeat(padding), consts::TEXT_INPUT_STYLE),
+        ])
+        .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_keys(input: &mut TextInput, keys: &str) {
+        for c in keys.chars() {
+            assert!(input.handle_key(KeyEvent::from(KeyCode::Char(c))));
+        }
+    }
+
+    #[test]
+    fn typing() {
+        let mut input = TextInput::new(5);
+        type_keys(&mut input, "Bob");
+        assert_eq!(input.value(), "Bob");
+        assert!(input.handle_key(KeyEvent::from(KeyCode::Backspace)));
+        assert_eq!(input.value(), "Bo");
+        type_keys(&mut input, "nnie!");
+        assert_eq!(input.value(), "Bonni");
+        assert!(!input.handle_key(KeyEvent::from(KeyCode::Enter)));
+        assert!(!input.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL)));
+        assert_eq!(input.value(), "Bonni");
+    }
+
+    #[test]
+    fn wide_characters() {
+        let mut input = TextInput::new(5);
+        type_keys(&mut input, "日本語");
+        assert_eq!(input.value(), "日本");
+        assert!(input.handle_key(KeyEvent::from(KeyCode::Backspace)));
+        assert_eq!(input.value(), "日");
+    }
+
+    #[test]
+    fn render() {
+        let mut input = TextInput::new(5);
+        type_keys(&mut input, "Al");
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 1));
+        input.render(Rect::new(1, 0, input.width(), 1), &mut buffer);
+        let mut expected = Buffer::with_lines([" Al     "]);
+        expected.set_style(Rect::new(1, 0, 6, 1), consts::TEXT_INPUT_STYLE);
+        expected.set_style(Rect::new(3, 0, 1, 1), consts::TEXT_CURSOR_STYLE);
+        pretty_assertions::assert_eq!(buffer, expected);
+    }
+}
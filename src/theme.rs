@@ -0,0 +1,189 @@
+//! Display settings that apply throughout the interface.
+//!
+//! Like the message catalog in [`crate::i18n`], the theme is set once at
+//! startup and then consulted by widgets as they are drawn.
+use crate::config::{Glyph, SelectionIndicator, ThemeConfig};
+use crate::util::get_display_area;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::Widget,
+};
+use std::sync::OnceLock;
+
+/// The theme in use, set by [`init()`]
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Make the theme described by `config` the theme used for the rest of the
+/// program's run.  If a theme has already been set or used, this does
+/// nothing.
+pub(crate) fn init(config: &ThemeConfig) {
+    let _ = THEME.set(Theme::from(config));
+}
+
+/// Return the active theme, defaulting to that of the default configuration
+/// if none was set
+fn theme() -> &'static Theme {
+    THEME.get_or_init(|| Theme::from(&ThemeConfig::default()))
+}
+
+/// Return the style for the currently-selected item in a menu, list, or row
+/// of buttons
+pub(crate) fn selection_style() -> Style {
+    theme().selection_style
+}
+
+/// Return the style to apply to the score bar once the score has overtaken
+/// the high score
+pub(crate) fn record_style() -> Style {
+    theme().record_style
+}
+
+/// Return the text to place before & after a button.  If bracket markers are
+/// enabled, a selected button is surrounded by pointers and other buttons by
+/// spaces of the same width; otherwise, both strings are empty.
+pub(crate) fn button_markers(selected: bool) -> [&'static str; 2] {
+    match (theme().markers, selected) {
+        (true, true) => ["»", "«"],
+        (true, false) => [" ", " "],
+        (false, _) => ["", ""],
+    }
+}
+
+/// Return a widget for filling the parts of the terminal outside of the
+/// display area, if the theme has a backdrop
+pub(crate) fn backdrop() -> Option<Backdrop<'static>> {
+    theme().backdrop.as_ref().map(Backdrop)
+}
+
+/// A widget that fills every cell of an area outside of the centered display
+/// area (as returned by [`get_display_area()`]) with a glyph
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Backdrop<'a>(&'a Glyph);
+
+impl Widget for Backdrop<'_> {
+    /// Fill the parts of `area` outside of `get_display_area(area)`.  `area`
+    /// should be the area of the entire terminal.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let display = get_display_area(area);
+        for pos in area.positions() {
+            if !display.contains(pos) {
+                if let Some(cell) = buf.cell_mut(pos) {
+                    cell.set_symbol(self.0.symbol.as_ref());
+                    cell.set_style(self.0.style);
+                }
+            }
+        }
+    }
+}
+
+/// The resolved form of a [`ThemeConfig`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Theme {
+    /// The style for selected items
+    selection_style: Style,
+
+    /// Whether to draw markers around selected buttons
+    markers: bool,
+
+    /// The glyph to fill the area outside of the display with, if any
+    backdrop: Option<Glyph>,
+
+    /// The style for the score bar when on pace for a new high score
+    record_style: Style,
+}
+
+impl From<&ThemeConfig> for Theme {
+    fn from(config: &ThemeConfig) -> Theme {
+        let indicators = if config.selection.is_empty() {
+            ThemeConfig::default().selection
+        } else {
+            config.selection.clone()
+        };
+        let mut theme = Theme {
+            selection_style: Style::new(),
+            markers: false,
+            backdrop: config.backdrop.clone(),
+            record_style: config.record,
+        };
+        for ind in indicators {
+            match ind {
+                SelectionIndicator::Underline => {
+                    theme.selection_style =
+                        theme.selection_style.add_modifier(Modifier::UNDERLINED);
+                }
+                SelectionIndicator::Bold => {
+                    theme.selection_style = theme.selection_style.add_modifier(Modifier::BOLD);
+                }
+                SelectionIndicator::Reverse => {
+                    theme.selection_style = theme.selection_style.add_modifier(Modifier::REVERSED);
+                }
+                SelectionIndicator::Brackets => theme.markers = true,
+            }
+        }
+        theme
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn default_theme() {
+        let theme = Theme::from(&ThemeConfig::default());
+        assert_eq!(
+            theme.selection_style,
+            Style::new().add_modifier(Modifier::UNDERLINED)
+        );
+        assert!(!theme.markers);
+        assert_eq!(
+            Theme::from(&ThemeConfig {
+                selection: Vec::new(),
+                ..ThemeConfig::default()
+            }),
+            theme
+        );
+    }
+
+    #[test]
+    fn combined_indicators() {
+        let theme = Theme::from(&ThemeConfig {
+            selection: vec![
+                SelectionIndicator::Bold,
+                SelectionIndicator::Reverse,
+                SelectionIndicator::Brackets,
+            ],
+            ..ThemeConfig::default()
+        });
+        assert_eq!(
+            theme.selection_style,
+            Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        );
+        assert!(theme.markers);
+    }
+
+    #[test]
+    fn fill_backdrop() {
+        let glyph = Glyph {
+            symbol: "░".parse().unwrap(),
+            style: Style::new().fg(Color::Blue),
+        };
+        let area = Rect::new(0, 0, 84, 26);
+        let mut buffer = Buffer::empty(area);
+        Backdrop(&glyph).render(area, &mut buffer);
+        let display = get_display_area(area);
+        assert_eq!(display, Rect::new(2, 1, 80, 24));
+        for pos in area.positions() {
+            let cell = &buffer[pos];
+            if display.contains(pos) {
+                assert_eq!(cell.symbol(), " ", "{pos:?}");
+            } else {
+                assert_eq!(cell.symbol(), "░", "{pos:?}");
+                assert_eq!(cell.fg, Color::Blue, "{pos:?}");
+            }
+        }
+    }
+}
@@ -1,7 +1,14 @@
+use crate::command::Command;
 use crate::consts;
+use crate::migrations::MigrationError;
 use enum_map::Enum;
-use ratatui::layout::{Flex, Layout, Position, Positions, Rect, Size};
+use ratatui::{
+    layout::{Alignment, Constraint, Flex, Layout, Position, Positions, Rect, Size},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Deserializer};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
 /// Values that would be global state if it weren't so evil.
@@ -16,8 +23,96 @@ pub(crate) struct Globals {
     /// Gameplay options
     pub(crate) options: crate::options::Options,
 
+    /// Is the options menu's "Use config defaults" toggle on?  If so, the
+    /// options menu starts from the options in the configuration file, and
+    /// changes to the options are not saved.
+    pub(crate) use_config_defaults: bool,
+
     /// High score records
     pub(crate) high_scores: crate::highscores::HighScores,
+
+    /// The bot steering the snake, if any
+    pub(crate) bot: Option<crate::bot::Bot>,
+
+    /// What causes the snake to advance during games
+    pub(crate) tick_source: crate::game::TickSource,
+
+    /// Is the application running in kiosk mode, in which quitting requires
+    /// a passphrase and every game ends with a return to the main menu?
+    pub(crate) kiosk: bool,
+
+    /// The optional features supported by the terminal
+    pub(crate) capabilities: crate::capabilities::Capabilities,
+
+    /// When the snake recently crashed, for deciding when to show tips
+    pub(crate) deaths: crate::game::DeathLog,
+
+    /// The adaptive difficulty level, used when the Adaptive option is set
+    pub(crate) difficulty: crate::game::Difficulty,
+
+    /// Are games played in co-op relay mode, in which two players take turns
+    /// steering the snake?
+    pub(crate) relay: bool,
+
+    /// If set, games are played on a split board, with two players each
+    /// steering their own snake on their own half of the level, and the
+    /// first to this many points wins
+    pub(crate) split: Option<std::num::NonZeroU32>,
+
+    /// If set, games started from the main menu are played on this custom
+    /// level instead of on a generated one
+    pub(crate) level: Option<crate::level::CustomLevel>,
+
+    /// A record of the most recently finished game, if any, for reporting
+    /// with `--json-result`
+    pub(crate) last_game: Option<crate::history::GameRecord>,
+}
+
+impl Globals {
+    /// Return the gameplay options and the "Use config defaults" toggle in
+    /// the form in which they are saved to the options file
+    pub(crate) fn saved_options(&self) -> crate::options::SavedOptions {
+        crate::options::SavedOptions {
+            options: self.options,
+            use_config_defaults: self.use_config_defaults,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Globals {
+    /// Return a `Globals` whose configuration is read from a `config.toml`
+    /// file written to `dir` that stores all data files in `dir`.  `extra` is
+    /// added to the configuration file; any `[files]` settings in it take
+    /// precedence over the paths in `dir`.
+    pub(crate) fn in_tempdir(dir: &tempfile::TempDir, extra: &str) -> Globals {
+        let mut table = extra.parse::<toml::Table>().unwrap();
+        let toml::Value::Table(files) = table
+            .entry("files")
+            .or_insert_with(|| toml::Table::new().into())
+        else {
+            panic!("[files] in extra config is not a table");
+        };
+        for (key, name) in [
+            ("options-file", "options.json"),
+            ("high-scores-dir", "highscores"),
+            ("screenshots-dir", "screenshots"),
+            ("history-file", "history.jsonl"),
+            ("session-file", "session.json"),
+            ("favorites-file", "favorites.json"),
+            ("levels-dir", "levels"),
+        ] {
+            files
+                .entry(key)
+                .or_insert_with(|| dir.path().join(name).display().to_string().into());
+        }
+        let cfgfile = dir.path().join("config.toml");
+        fs_err::write(&cfgfile, table.to_string()).unwrap();
+        Globals {
+            config: crate::config::Config::load(&cfgfile, false).unwrap(),
+            ..Globals::default()
+        }
+    }
 }
 
 /// The bounds of a game level: size and wraparound
@@ -55,8 +150,233 @@ impl Bounds {
     pub(crate) fn positions(self) -> Positions {
         Rect::from((Position::ORIGIN, self.size())).positions()
     }
+
+    /// Return the number of orthogonal steps needed to get from `a` to `b`,
+    /// taking wraparound into account
+    pub(crate) fn distance(self, a: Position, b: Position) -> u32 {
+        let axis = |p: u16, q: u16, len: u16| {
+            let d = p.abs_diff(q);
+            if self.wrap {
+                d.min(len.saturating_sub(d))
+            } else {
+                d
+            }
+        };
+        u32::from(axis(a.x, b.x, self.width)) + u32::from(axis(a.y, b.y, self.height))
+    }
+
+    /// Return the horizontal & vertical displacement along the shortest route
+    /// from `a` to `b`, taking wraparound into account
+    pub(crate) fn offset(self, a: Position, b: Position) -> (i32, i32) {
+        let axis = |p: u16, q: u16, len: u16| {
+            let d = i32::from(q) - i32::from(p);
+            let len = i32::from(len);
+            if self.wrap && d.abs() * 2 > len {
+                d - d.signum() * len
+            } else {
+                d
+            }
+        };
+        (axis(a.x, b.x, self.width), axis(a.y, b.y, self.height))
+    }
+
+    /// Returns `true` if `pos` lies on the outer edge of the level
+    pub(crate) fn is_edge(self, pos: Position) -> bool {
+        pos.x == 0
+            || pos.y == 0
+            || pos.x == self.width.saturating_sub(1)
+            || pos.y == self.height.saturating_sub(1)
+    }
+
+    /// Divide the level into `n` rectangular regions of roughly equal size.
+    /// The regions are arranged in `⌊√n⌋` rows, with any regions that don't
+    /// fit evenly going to the upper rows.
+    pub(crate) fn regions(self, n: usize) -> Vec<Rect> {
+        let n = n.max(1);
+        let rows = (1..n).take_while(|r| (r + 1) * (r + 1) <= n).count() + 1;
+        let area = Rect::from((Position::ORIGIN, self.size()));
+        Layout::vertical(vec![Constraint::Fill(1); rows])
+            .split(area)
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &row)| {
+                let cols = n / rows + usize::from(i < n % rows);
+                Layout::horizontal(vec![Constraint::Fill(1); cols])
+                    .split(row)
+                    .to_vec()
+            })
+            .collect()
+    }
 }
 
+/// The direction in which the interface is laid out horizontally.
+///
+/// This affects which sides of the screen things like the score and the
+/// high score are drawn on, the arrangement of the main menu, and which of
+/// the left & right keys increases option values.  The game level itself is
+/// never mirrored.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+pub(crate) enum LayoutDirection {
+    /// Lay things out from left to right
+    #[default]
+    #[serde(rename = "ltr")]
+    LeftToRight,
+
+    /// Lay things out from right to left
+    #[serde(rename = "rtl")]
+    RightToLeft,
+}
+
+impl LayoutDirection {
+    /// Return the alignment for text at the start of a line
+    pub(crate) fn start(self) -> Alignment {
+        match self {
+            LayoutDirection::LeftToRight => Alignment::Left,
+            LayoutDirection::RightToLeft => Alignment::Right,
+        }
+    }
+
+    /// Return the alignment for text at the end of a line
+    pub(crate) fn end(self) -> Alignment {
+        match self {
+            LayoutDirection::LeftToRight => Alignment::Right,
+            LayoutDirection::RightToLeft => Alignment::Left,
+        }
+    }
+
+    /// Align `line` to the start of its area, separated from the edge by one
+    /// space
+    pub(crate) fn align_start(self, line: Line<'_>) -> Line<'_> {
+        self.pad(line, self.start())
+    }
+
+    /// Align `line` to the end of its area, separated from the edge by one
+    /// space
+    pub(crate) fn align_end(self, line: Line<'_>) -> Line<'_> {
+        self.pad(line, self.end())
+    }
+
+    /// Align `line` as given, separated from the edge by one space
+    fn pad(self, mut line: Line<'_>, alignment: Alignment) -> Line<'_> {
+        if alignment == Alignment::Right {
+            line.push_span(Span::raw(" "));
+        } else {
+            line.spans.insert(0, Span::raw(" "));
+        }
+        line.alignment(alignment)
+    }
+
+    /// Given items in left-to-right order, return them in the order in which
+    /// they should be laid out from left to right in this direction
+    pub(crate) fn arrange<C: AsMut<[T]>, T>(self, mut items: C) -> C {
+        if self == LayoutDirection::RightToLeft {
+            items.as_mut().reverse();
+        }
+        items
+    }
+
+    /// Swap left & right commands if this direction is right-to-left, so
+    /// that code written for left-to-right layouts responds to them in a
+    /// mirrored fashion
+    pub(crate) fn flip(self, cmd: Command) -> Command {
+        match (self, cmd) {
+            (LayoutDirection::RightToLeft, Command::Left) => Command::Right,
+            (LayoutDirection::RightToLeft, Command::Right) => Command::Left,
+            (_, cmd) => cmd,
+        }
+    }
+}
+
+/// The shape of a terminal cell, given as the ratio of its width to its
+/// height, written in configuration files as `"W:H"`, e.g., `"1:2"` for the
+/// common case of cells twice as tall as they are wide
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct CellAspect {
+    width: u16,
+    height: u16,
+}
+
+impl CellAspect {
+    /// The cell shape for which the built-in level sizes were designed
+    pub(crate) const DESIGNED: CellAspect = CellAspect {
+        width: 1,
+        height: 2,
+    };
+
+    /// Create a new `CellAspect` for cells `width` wide & `height` tall, in
+    /// arbitrary units.  Returns `None` if either value is zero or if a cell
+    /// would be more than four times as tall as it is wide or more than twice
+    /// as wide as it is tall.
+    pub(crate) fn new(width: u16, height: u16) -> Option<CellAspect> {
+        let (w, h) = (u32::from(width), u32::from(height));
+        (w > 0 && h > 0 && h <= w * 4 && w <= h * 2).then_some(CellAspect { width, height })
+    }
+
+    /// Scale a level size designed for [`CellAspect::DESIGNED`] so that it
+    /// has the same physical shape when drawn with cells of this shape.  One
+    /// dimension is left as-is while the other is shrunk, so that the result
+    /// is never larger than `size` in either dimension (except as needed to
+    /// reach [`MIN_CUSTOM_LEVEL_SIZE`][consts::MIN_CUSTOM_LEVEL_SIZE]).
+    pub(crate) fn reshape(self, size: Size) -> Size {
+        let (w, h) = (u32::from(self.width), u32::from(self.height));
+        let (dw, dh) = (
+            u32::from(Self::DESIGNED.width),
+            u32::from(Self::DESIGNED.height),
+        );
+        let min = consts::MIN_CUSTOM_LEVEL_SIZE;
+        if h * dw < dh * w {
+            // Cells are squatter than designed, so fewer columns are needed
+            let width = scale(size.width, h * dw, dh * w).max(min.width);
+            Size { width, ..size }
+        } else {
+            // Cells are at least as tall as designed, so fewer rows are
+            // needed
+            let height = scale(size.height, dh * w, h * dw).max(min.height);
+            Size { height, ..size }
+        }
+    }
+
+    /// Scale the time taken by a horizontal move to the time that a vertical
+    /// move should take so that both cover the same physical distance
+    pub(crate) fn vertical_period(self, period: Duration) -> Duration {
+        period * u32::from(self.height) / u32::from(self.width)
+    }
+}
+
+/// Return `value * num / den`, rounded to the nearest integer
+fn scale(value: u16, num: u32, den: u32) -> u16 {
+    let scaled = (u32::from(value) * num + den / 2) / den;
+    u16::try_from(scaled).unwrap_or(u16::MAX)
+}
+
+impl std::str::FromStr for CellAspect {
+    type Err = ParseCellAspectError;
+
+    fn from_str(s: &str) -> Result<CellAspect, ParseCellAspectError> {
+        let (width, height) = s.split_once(':').ok_or(ParseCellAspectError)?;
+        let width = width
+            .trim()
+            .parse::<u16>()
+            .map_err(|_| ParseCellAspectError)?;
+        let height = height
+            .trim()
+            .parse::<u16>()
+            .map_err(|_| ParseCellAspectError)?;
+        CellAspect::new(width, height).ok_or(ParseCellAspectError)
+    }
+}
+
+impl<'de> Deserialize<'de> for CellAspect {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<CellAspect>().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+#[error("invalid cell aspect ratio: expected \"W:H\" with each cell between one quarter & two times as wide as it is tall")]
+pub(crate) struct ParseCellAspectError;
+
 /// An extension trait on [`enum_map::Enum`]
 pub(crate) trait EnumExt: Enum {
     /// Returns an iterator over all values of the enum
@@ -182,6 +502,27 @@ impl SaveError {
             source: SaveErrorSource::Write(e),
         }
     }
+
+    pub(crate) fn backup(desc: &'static str, e: std::io::Error) -> Self {
+        SaveError {
+            desc,
+            source: SaveErrorSource::Backup(e),
+        }
+    }
+
+    pub(crate) fn too_new(desc: &'static str, e: MigrationError) -> Self {
+        SaveError {
+            desc,
+            source: SaveErrorSource::TooNew(e),
+        }
+    }
+
+    /// Returns `true` if the file was not saved because the existing file was
+    /// written in a newer format than this version of the program supports.
+    /// Such errors are never ignored.
+    pub(crate) fn is_too_new(&self) -> bool {
+        matches!(self.source, SaveErrorSource::TooNew(_))
+    }
 }
 
 /// Source error of [`SaveError`].
@@ -207,6 +548,10 @@ enum SaveErrorSource {
     Serialize(#[source] serde_json::Error),
     #[error("failed to write data to disk")]
     Write(#[source] std::io::Error),
+    #[error("failed to move corrupt file aside")]
+    Backup(#[source] std::io::Error),
+    #[error("refusing to overwrite file")]
+    TooNew(#[source] MigrationError),
 }
 
 /// Error returned by [`Options::load()`][crate::options::Options::load] and
@@ -243,6 +588,33 @@ impl LoadError {
             source: LoadErrorSource::Deserialize(e),
         }
     }
+
+    pub(crate) fn migrate(desc: &'static str, e: MigrationError) -> Self {
+        LoadError {
+            desc,
+            source: LoadErrorSource::Migrate(e),
+        }
+    }
+
+    /// Returns `true` if the file was read but its contents could not be
+    /// understood, in which case the user may choose to reset it
+    pub(crate) fn is_corrupt(&self) -> bool {
+        match self.source {
+            LoadErrorSource::Deserialize(_) => true,
+            LoadErrorSource::Migrate(_) => !self.is_too_new(),
+            LoadErrorSource::NoPath | LoadErrorSource::Read(_) => false,
+        }
+    }
+
+    /// Returns `true` if the file could not be loaded because it was written
+    /// in a newer format than this version of the program supports.  Such
+    /// errors are never ignored, as saving over the file would lose data.
+    pub(crate) fn is_too_new(&self) -> bool {
+        matches!(
+            self.source,
+            LoadErrorSource::Migrate(MigrationError::TooNew { .. })
+        )
+    }
 }
 
 /// Source error of [`LoadError`].
@@ -266,6 +638,29 @@ enum LoadErrorSource {
     Read(#[source] std::io::Error),
     #[error("failed to deserialize file contents")]
     Deserialize(#[source] serde_json::Error),
+    #[error("failed to upgrade file format")]
+    Migrate(#[source] MigrationError),
+}
+
+/// Return a description of `e` followed by descriptions of each of its
+/// sources, separated by colons, for display in a one-line message
+pub(crate) fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut text = e.to_string();
+    let mut source = e.source();
+    while let Some(src) = source {
+        text.push_str(": ");
+        text.push_str(&src.to_string());
+        source = src.source();
+    }
+    text
+}
+
+/// Return the path of the temporary file to which the file at `path` is
+/// written before being renamed into place
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let mut p = path.as_os_str().to_owned();
+    p.push(".tmp");
+    PathBuf::from(p)
 }
 
 /// Produce a [`Rect`] of the given size that is centered both vertically &
@@ -1,21 +1,38 @@
 use crate::command::Command;
-use crate::util::center_rect;
+use crate::consts;
+use crate::i18n::{tr, tr_args, Msg};
+use crate::popup::PopupFrame;
+use crate::theme;
+use crate::util::{data_dir, SaveError};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Constraint, Flex, Layout, Rect, Size},
-    text::{Line, Text},
-    widgets::{
-        block::{Block, Padding},
-        Clear, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget,
-    },
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
 };
 use std::borrow::Cow;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// A widget for displaying a warning about an error (including its source
 /// traceback messages) in a pop-up window
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct Warning {
+    /// The unwrapped messages of the error and its chain of sources, for
+    /// saving to the error log
+    messages: Vec<String>,
+
     lines: Vec<String>,
+
+    /// The number of entries at the start of `lines` that display the error
+    /// itself rather than the result of saving it to the error log
+    error_lines: usize,
+
+    /// The directory in which to save the error log, if one is defined
+    log_dir: Option<PathBuf>,
+
     scroll_offset: usize,
     max_scroll: usize,
 }
@@ -32,25 +49,64 @@ impl Warning {
     /// between it & the text)
     const WIDTH: u16 = Self::TEXT_WIDTH + 4;
 
-    /// Process an input command.  Returns `Some` if the user dismisses the
-    /// pop-up or quits the application.
-    pub(crate) fn handle_command(&mut self, cmd: Command) -> Option<WarningOutcome> {
-        match (cmd, self.scrolling()) {
-            (Command::Enter, _) => return Some(WarningOutcome::Dismissed),
-            (Command::Quit, _) => return Some(WarningOutcome::Quit),
-            (Command::Up, true) => {
-                if self.scroll_offset > 0 {
-                    self.scroll_offset -= 1;
-                }
+    /// Process a key press.  Returns `Some` if the user dismisses the pop-up
+    /// or quits the application.
+    ///
+    /// This takes a raw key event rather than a [`Command`], as the key for
+    /// saving the error log, <kbd>s</kbd>, is otherwise read as
+    /// [`Command::Down`].
+    pub(crate) fn handle_key(&mut self, ev: KeyEvent) -> Option<WarningOutcome> {
+        if (ev.modifiers, ev.code) == (KeyModifiers::NONE, KeyCode::Char('s')) {
+            self.save_log(self.log_dir.clone().as_deref());
+            return None;
+        }
+        match Command::from_key_event(ev)? {
+            Command::Enter => Some(WarningOutcome::Dismissed),
+            Command::Quit => Some(WarningOutcome::Quit),
+            cmd => {
+                self.scroll(cmd);
+                None
             }
-            (Command::Down, true) => {
-                if self.scroll_offset < self.max_scroll.saturating_sub(1) {
-                    self.scroll_offset += 1;
-                }
+        }
+    }
+
+    /// Scroll the text up or down in response to an input command, if
+    /// scrolling is in effect
+    fn scroll(&mut self, cmd: Command) {
+        match (cmd, self.scrolling()) {
+            (Command::Up, true) if self.scroll_offset > 0 => self.scroll_offset -= 1,
+            (Command::Down, true) if self.scroll_offset < self.max_scroll.saturating_sub(1) => {
+                self.scroll_offset += 1;
             }
             _ => (),
         }
-        None
+    }
+
+    /// Append the error's messages to the error log in `dir` and show the
+    /// log's path — or the reason it could not be written — below the error,
+    /// replacing the result of any previous save
+    fn save_log(&mut self, dir: Option<&Path>) {
+        let note = match dir
+            .ok_or_else(|| SaveError::no_path("error log"))
+            .and_then(|dir| append_error_log(dir, &self.messages))
+        {
+            Ok(path) => tr_args(Msg::ErrorLogSaved, &[("path", &path.display())]),
+            Err(e) => format!("{:#}", anyhow::Error::from(e)),
+        };
+        self.lines.truncate(self.error_lines);
+        self.lines.push(String::new());
+        let opts = textwrap::Options::new(usize::from(Warning::TEXT_WIDTH)).break_words(true);
+        self.lines
+            .extend(textwrap::wrap(&note, opts).into_iter().map(Cow::into_owned));
+        self.max_scroll = Warning::max_scroll(self.lines.len());
+        if self.scrolling() {
+            self.scroll_offset = self.max_scroll.saturating_sub(1);
+        }
+    }
+
+    /// Return the `max_scroll` value for text with `len` lines
+    fn max_scroll(len: usize) -> usize {
+        len.saturating_sub(usize::from(Warning::MAX_LINES) - 1)
     }
 
     /// Does the text not fit in [`MAX_LINES`][Self::MAX_LINES], necessitating
@@ -74,6 +130,7 @@ impl Warning {
             !msgs.is_empty(),
             "Empty Vec passed to Warning::from_error_messages()"
         );
+        let messages = msgs.clone();
         let mut lines = Vec::new();
         let opts = textwrap::Options::new(usize::from(Warning::TEXT_WIDTH)).break_words(true);
         lines.extend(
@@ -83,7 +140,7 @@ impl Warning {
         );
         if msgs.len() > 1 {
             lines.push(String::new());
-            lines.push(String::from("Caused by:"));
+            lines.push(String::from(tr(Msg::CausedBy)));
             if msgs.len() > 2 {
                 for (i, m) in msgs.into_iter().skip(1).enumerate() {
                     let init_indent = format!("{i:>5}: ");
@@ -105,17 +162,45 @@ impl Warning {
                 );
             }
         }
-        let max_scroll = lines
-            .len()
-            .saturating_sub(usize::from(Warning::MAX_LINES) - 1);
+        let max_scroll = Warning::max_scroll(lines.len());
         Warning {
+            messages,
+            error_lines: lines.len(),
             lines,
+            log_dir: data_dir(),
             scroll_offset: 0,
             max_scroll,
         }
     }
 }
 
+/// Append a timestamped entry containing `messages` (an error message
+/// followed by the messages of its chain of sources) to the `errors.log` file
+/// in `dir` and return the file's path
+///
+/// # Errors
+///
+/// Returns `Err` if creating `dir` failed or if writing to the file failed.
+fn append_error_log(dir: &Path, messages: &[String]) -> Result<PathBuf, SaveError> {
+    fs_err::create_dir_all(dir).map_err(|e| SaveError::mkdir("error log", e))?;
+    let path = dir.join("errors.log");
+    let mut entry = format!("[{}] ", jiff::Timestamp::now());
+    for (i, m) in messages.iter().enumerate() {
+        if i > 0 {
+            entry.push_str("    caused by: ");
+        }
+        entry.push_str(m);
+        entry.push('\n');
+    }
+    fs_err::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)
+        .and_then(|mut fp| fp.write_all(entry.as_bytes()))
+        .map_err(|e| SaveError::write("error log", e))?;
+    Ok(path)
+}
+
 /// An enum of the ways that the user can finish working with a `Warning`
 /// pop-up
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -147,27 +232,30 @@ impl Widget for &Warning {
     /// `Rect` for the actual area inside `area` on which the drawing will
     /// occur.
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let height = u16::try_from(self.lines.len())
-            .unwrap_or(u16::MAX)
-            .min(Warning::MAX_LINES)
-            .saturating_add(4);
-        let block_area = center_rect(
-            area,
-            Size {
-                width: Warning::WIDTH.saturating_add(u16::from(self.scrolling()) * 2),
-                height,
-            },
-        );
-        let block = Block::bordered()
-            .title(" WARNING ")
-            .title_alignment(Alignment::Center)
-            .padding(Padding::horizontal(1));
-        let [text_area, ok_area] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
-            .flex(Flex::Start)
-            .spacing(1)
-            .areas(block.inner(block_area));
-        Clear.render(block_area, buf);
-        block.render(block_area, buf);
+        let footer_area = self.render_frame(area, buf);
+        Line::from(vec![
+            Span::raw(format!("[{}]   [{} (", tr(Msg::Ok), tr(Msg::SaveLog))),
+            Span::styled("s", consts::KEY_STYLE),
+            Span::raw(")]"),
+        ])
+        .centered()
+        .render(footer_area, buf);
+    }
+}
+
+impl Warning {
+    /// Draw the pop-up's frame & text centered within `area` of `buf` and
+    /// return the area for the footer
+    fn render_frame(&self, area: Rect, buf: &mut Buffer) -> Rect {
+        let [text_area, footer_area] = PopupFrame {
+            title: tr(Msg::WarningTitle),
+            width: Warning::WIDTH.saturating_add(u16::from(self.scrolling()) * 2),
+            text_height: u16::try_from(self.lines.len())
+                .unwrap_or(u16::MAX)
+                .min(Warning::MAX_LINES),
+            footer: true,
+        }
+        .render(area, buf);
         if self.scrolling() {
             let [text_area, scrollbar_area] =
                 Layout::horizontal([Constraint::Fill(1), Constraint::Length(1)])
@@ -190,7 +278,124 @@ impl Widget for &Warning {
         } else {
             Text::from_iter(self.lines.iter().map(String::as_str)).render(text_area, buf);
         }
-        Line::from("[OK]").centered().render(ok_area, buf);
+        footer_area
+    }
+}
+
+/// A [`Warning`] pop-up that offers the user a choice of actions in place of
+/// the usual "OK" button
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Choice<T> {
+    /// The warning text to display
+    warning: Warning,
+
+    /// The buttons for the available actions, from left to right
+    buttons: Vec<Button<T>>,
+
+    /// The index into `buttons` of the currently-selected button
+    selected: usize,
+}
+
+/// A button in a [`Choice`] pop-up
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Button<T> {
+    /// The text of the button
+    pub(crate) label: &'static str,
+
+    /// The command that activates the button
+    pub(crate) command: Command,
+
+    /// The name of the key that activates the button
+    pub(crate) key: &'static str,
+
+    /// The value returned when the button is activated
+    pub(crate) value: T,
+}
+
+impl<T: Copy> Choice<T> {
+    /// Create a new `Choice` pop-up displaying `warning` and offering the
+    /// given buttons, with the first button selected
+    pub(crate) fn new(warning: Warning, buttons: Vec<Button<T>>) -> Choice<T> {
+        Choice {
+            warning,
+            buttons,
+            selected: 0,
+        }
+    }
+
+    /// Process an input command.  Returns `Some` if the user activates a
+    /// button or quits the application.
+    pub(crate) fn handle_command(&mut self, cmd: Command) -> Option<ChoiceOutcome<T>> {
+        let last = self.buttons.len().saturating_sub(1);
+        match cmd {
+            Command::Quit => return Some(ChoiceOutcome::Quit),
+            Command::Enter => {
+                return self
+                    .buttons
+                    .get(self.selected)
+                    .map(|b| ChoiceOutcome::Chosen(b.value));
+            }
+            Command::Left => self.selected = self.selected.saturating_sub(1),
+            Command::Right => self.selected = (self.selected + 1).min(last),
+            Command::Home => self.selected = 0,
+            Command::End => self.selected = last,
+            Command::Next => {
+                self.selected = if self.selected >= last {
+                    0
+                } else {
+                    self.selected + 1
+                };
+            }
+            Command::Prev => {
+                self.selected = self.selected.checked_sub(1).unwrap_or(last);
+            }
+            _ => {
+                if let Some(b) = self.buttons.iter().find(|b| b.command == cmd) {
+                    return Some(ChoiceOutcome::Chosen(b.value));
+                }
+                self.warning.scroll(cmd);
+            }
+        }
+        None
+    }
+}
+
+/// An enum of the ways that the user can finish working with a `Choice`
+/// pop-up
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ChoiceOutcome<T> {
+    /// The user activated the button with the given value
+    Chosen(T),
+
+    /// The user quit the application
+    Quit,
+}
+
+impl<T> Widget for &Choice<T> {
+    /// Render a `Choice` in the given area of `buf`.
+    ///
+    /// As with [`Warning`], `area` should be the area of the entire display
+    /// within which the pop-up will be rendered.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let buttons_area = self.warning.render_frame(area, buf);
+        let mut line = Line::default();
+        for (i, b) in self.buttons.iter().enumerate() {
+            if i > 0 {
+                line.push_span(Span::raw("   "));
+            }
+            let style = if i == self.selected {
+                theme::selection_style()
+            } else {
+                Style::new()
+            };
+            let [before, after] = theme::button_markers(i == self.selected);
+            line.push_span(Span::raw(before));
+            line.push_span(Span::styled(format!("[{} (", b.label), style));
+            line.push_span(Span::styled(b.key, consts::KEY_STYLE.patch(style)));
+            line.push_span(Span::styled(")]", style));
+            line.push_span(Span::raw(after));
+        }
+        line.centered().render(buttons_area, buf);
     }
 }
 
@@ -199,13 +404,84 @@ mod tests {
     use super::*;
     use ratatui::{buffer::Buffer, layout::Rect};
 
+    #[test]
+    fn choice() {
+        let buttons = vec![
+            Button {
+                label: "Keep",
+                command: Command::Esc,
+                key: "Esc",
+                value: 1,
+            },
+            Button {
+                label: "Drop",
+                command: Command::R,
+                key: "r",
+                value: 2,
+            },
+        ];
+        let mut choice = Choice::new(
+            Warning::from_error_messages(vec![String::from("Save file is corrupt")]),
+            buttons,
+        );
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buffer = Buffer::empty(area);
+        choice.render(area, &mut buffer);
+        let mut expected = Buffer::with_lines([
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "              ┌──────────────────── WARNING ─────────────────────┐              ",
+            "              │ Save file is corrupt                             │              ",
+            "              │                                                  │              ",
+            "              │            [Keep (Esc)]   [Drop (r)]             │              ",
+            "              └──────────────────────────────────────────────────┘              ",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+        ]);
+        expected.set_style(Rect::new(27, 13, 12, 1), theme::selection_style());
+        expected.set_style(
+            Rect::new(34, 13, 3, 1),
+            consts::KEY_STYLE.patch(theme::selection_style()),
+        );
+        expected.set_style(Rect::new(49, 13, 1, 1), consts::KEY_STYLE);
+        pretty_assertions::assert_eq!(buffer, expected);
+        assert_eq!(choice.handle_command(Command::Right), None);
+        assert_eq!(
+            choice.handle_command(Command::Enter),
+            Some(ChoiceOutcome::Chosen(2))
+        );
+        assert_eq!(
+            choice.handle_command(Command::Esc),
+            Some(ChoiceOutcome::Chosen(1))
+        );
+        assert_eq!(
+            choice.handle_command(Command::Quit),
+            Some(ChoiceOutcome::Quit)
+        );
+    }
+
     #[test]
     fn render_no_cause() {
         let warning = Warning::from_error_messages(vec![String::from("Terminal broke")]);
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "",
@@ -219,7 +495,7 @@ mod tests {
             "              ┌──────────────────── WARNING ─────────────────────┐              ",
             "              │ Terminal broke                                   │              ",
             "              │                                                  │              ",
-            "              │                       [OK]                       │              ",
+            "              │              [OK]   [Save Log (s)]               │              ",
             "              └──────────────────────────────────────────────────┘              ",
             "",
             "",
@@ -231,6 +507,7 @@ mod tests {
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 13, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
     }
 
@@ -243,7 +520,7 @@ mod tests {
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "",
@@ -258,7 +535,7 @@ mod tests {
             "              │ Caused by:                                       │              ",
             "              │     not a tty                                    │              ",
             "              │                                                  │              ",
-            "              │                       [OK]                       │              ",
+            "              │              [OK]   [Save Log (s)]               │              ",
             "              └──────────────────────────────────────────────────┘              ",
             "",
             "",
@@ -269,6 +546,7 @@ mod tests {
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 14, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
     }
 
@@ -282,7 +560,7 @@ mod tests {
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "",
@@ -298,7 +576,7 @@ mod tests {
             "              │     0: terminal broke                            │              ",
             "              │     1: not a tty                                 │              ",
             "              │                                                  │              ",
-            "              │                       [OK]                       │              ",
+            "              │              [OK]   [Save Log (s)]               │              ",
             "              └──────────────────────────────────────────────────┘              ",
             "",
             "",
@@ -308,6 +586,7 @@ mod tests {
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 15, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
     }
 
@@ -320,7 +599,7 @@ mod tests {
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "",
@@ -336,7 +615,7 @@ mod tests {
             "              │     All mimsy were the borogoves, and the mome   │              ",
             "              │     raths outgrabe                               │              ",
             "              │                                                  │              ",
-            "              │                       [OK]                       │              ",
+            "              │              [OK]   [Save Log (s)]               │              ",
             "              └──────────────────────────────────────────────────┘              ",
             "",
             "",
@@ -346,6 +625,7 @@ mod tests {
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 15, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
     }
 
@@ -361,7 +641,7 @@ mod tests {
         let area = Rect::new(0, 0, 80, 24);
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "",
@@ -378,7 +658,7 @@ mod tests {
             "              │     1: Beware the Jabberwock, my son!  The jaws  │              ",
             "              │        that bite, the claws that catch!          │              ",
             "              │                                                  │              ",
-            "              │                       [OK]                       │              ",
+            "              │              [OK]   [Save Log (s)]               │              ",
             "              └──────────────────────────────────────────────────┘              ",
             "",
             "",
@@ -387,6 +667,7 @@ mod tests {
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 16, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
     }
 
@@ -418,7 +699,7 @@ mod tests {
 
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "             ┌───────────────────── WARNING ──────────────────────┐             ",
@@ -439,17 +720,18 @@ mod tests {
             "             │    11: And as in uffish thought he stood,        ▒ │             ",
             "             │    12: The Jabberwock, with eyes of flame,       ▼ │             ",
             "             │                                                    │             ",
-            "             │                        [OK]                        │             ",
+            "             │               [OK]   [Save Log (s)]                │             ",
             "             └────────────────────────────────────────────────────┘             ",
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 20, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
 
-        assert!(warning.handle_command(Command::Down).is_none());
+        assert!(warning.handle_key(KeyEvent::from(KeyCode::Down)).is_none());
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "             ┌───────────────────── WARNING ──────────────────────┐             ",
@@ -470,19 +752,20 @@ mod tests {
             "             │    12: The Jabberwock, with eyes of flame,       ▒ │             ",
             "             │    13: Came whiffling through the tulgey wood,   ▼ │             ",
             "             │                                                    │             ",
-            "             │                        [OK]                        │             ",
+            "             │               [OK]   [Save Log (s)]                │             ",
             "             └────────────────────────────────────────────────────┘             ",
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 20, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
 
         for _ in 0..8 {
-            assert!(warning.handle_command(Command::Down).is_none());
+            assert!(warning.handle_key(KeyEvent::from(KeyCode::Down)).is_none());
         }
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
-        let expected = Buffer::with_lines([
+        let mut expected = Buffer::with_lines([
             "",
             "",
             "             ┌───────────────────── WARNING ──────────────────────┐             ",
@@ -503,16 +786,98 @@ mod tests {
             "             │    17: He left it dead, and with its head        █ │             ",
             "             │    18: He went galumping back.                   ▼ │             ",
             "             │                                                    │             ",
-            "             │                        [OK]                        │             ",
+            "             │               [OK]   [Save Log (s)]                │             ",
             "             └────────────────────────────────────────────────────┘             ",
             "",
             "",
         ]);
+        expected.set_style(Rect::new(47, 20, 1, 1), consts::KEY_STYLE);
         pretty_assertions::assert_eq!(buffer, expected);
 
-        assert!(warning.handle_command(Command::Down).is_none());
+        assert!(warning.handle_key(KeyEvent::from(KeyCode::Down)).is_none());
         let mut buffer = Buffer::empty(area);
         warning.render(area, &mut buffer);
         pretty_assertions::assert_eq!(buffer, expected);
     }
+
+    #[test]
+    fn save_log() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let dir = tmpdir.path().join("data");
+        let mut warning = Warning::from_error_messages(vec![
+            String::from("Failed to save high scores to disk"),
+            String::from("failed to write data to disk"),
+            String::from("No space left on device"),
+        ]);
+        let error_lines = warning.lines.clone();
+        warning.save_log(Some(&dir));
+        warning.save_log(Some(&dir));
+        let path = dir.join("errors.log");
+        let log = fs_err::read_to_string(&path).unwrap();
+        let entries = log.lines().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 6);
+        for entry in entries.chunks(3) {
+            let [head, cause1, cause2] = entry else {
+                panic!("Log entry has wrong number of lines: {entry:?}");
+            };
+            let (stamp, msg) = head.split_once("] ").unwrap();
+            assert!(stamp
+                .strip_prefix('[')
+                .unwrap()
+                .parse::<jiff::Timestamp>()
+                .is_ok());
+            assert_eq!(msg, "Failed to save high scores to disk");
+            assert_eq!(*cause1, "    caused by: failed to write data to disk");
+            assert_eq!(*cause2, "    caused by: No space left on device");
+        }
+        // Saving again replaces the previous note rather than adding another
+        let note = textwrap::wrap(
+            &format!("Saved error details to {}", path.display()),
+            usize::from(Warning::TEXT_WIDTH),
+        )
+        .into_iter()
+        .map(Cow::into_owned)
+        .collect::<Vec<_>>();
+        let mut expected = error_lines;
+        expected.push(String::new());
+        expected.extend(note);
+        assert_eq!(warning.lines, expected);
+    }
+
+    #[test]
+    fn save_log_no_dir() {
+        let mut warning =
+            Warning::from_error_messages(vec![String::from("Failed to load options from disk")]);
+        warning.save_log(None);
+        assert_eq!(
+            warning.lines[1..],
+            [
+                String::new(),
+                String::from("Failed to save error log to disk: failed to"),
+                String::from("determine path to local data directory"),
+            ]
+        );
+    }
+
+    #[test]
+    fn save_key() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let mut warning = Warning::from_error_messages(vec![String::from("Terminal broke")]);
+        warning.log_dir = Some(tmpdir.path().to_owned());
+        assert_eq!(warning.handle_key(KeyEvent::from(KeyCode::Char('s'))), None);
+        let path = tmpdir.path().join("errors.log");
+        assert!(fs_err::read_to_string(&path)
+            .unwrap()
+            .ends_with("Terminal broke\n"));
+        assert_ne!(warning.lines.len(), warning.error_lines);
+        // Only a plain `s` saves the log
+        let mut warning = Warning::from_error_messages(vec![String::from("Terminal broke")]);
+        let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(warning.handle_key(ctrl_s), None);
+        assert_eq!(warning.lines.len(), warning.error_lines);
+        assert_eq!(
+            warning.handle_key(KeyEvent::from(KeyCode::Enter)),
+            Some(WarningOutcome::Dismissed)
+        );
+    }
 }
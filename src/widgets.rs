@@ -0,0 +1,309 @@
+//! Widgets shared between screens
+use crate::i18n::{tr, Msg};
+use crate::options::Adjustable;
+use crate::theme;
+use crate::util::{EnumExt, LayoutDirection};
+use enum_map::{Enum, EnumArray, EnumMap};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Span,
+    widgets::{
+        block::{Block, Padding},
+        Widget,
+    },
+};
+use std::fmt;
+
+/// A trait for the keys naming the items in a [`SettingsList`]
+pub(crate) trait SettingKey: Enum + Copy + Eq + fmt::Display {
+    /// The maximum display column width of `key.to_string()` for all keys
+    const DISPLAY_WIDTH: u16;
+}
+
+/// A trait for the values of the items in a [`SettingsList`]
+pub(crate) trait SettingValue: Adjustable + Copy + Eq + fmt::Display {
+    /// The display column width of `value.to_string()` for all values
+    const DISPLAY_WIDTH: u16;
+
+    /// Return the value as displayed in a right-to-left list, in which the
+    /// left key increases values and the right key decreases them
+    fn mirrored(self) -> String;
+}
+
+/// A bordered list of settings, one per key of `K`, that can be navigated,
+/// adjusted, and toggled, with support for undoing & redoing changes
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct SettingsList<K: EnumArray<V>, V> {
+    /// The title drawn on the list's border
+    title: Msg,
+
+    /// If the currently-selected item of the enclosing screen is an element
+    /// of this list, then `selection` is `Some(key)`, where `key` is the key
+    /// of the selected item within the list.
+    selection: Option<K>,
+
+    /// Values currently displayed in the list
+    settings: EnumMap<K, V>,
+
+    /// Changes made to the settings, most recent last, that can be undone
+    undo_stack: Vec<Change<K, V>>,
+
+    /// Changes that have been undone, most recently undone last, that can be
+    /// redone
+    redo_stack: Vec<Change<K, V>>,
+}
+
+// This can't be derived, as cloning an `EnumMap` requires a bound on its
+// underlying array type.
+impl<K: EnumArray<V> + Clone, V: Clone> Clone for SettingsList<K, V>
+where
+    K::Array: Clone,
+{
+    fn clone(&self) -> Self {
+        SettingsList {
+            title: self.title,
+            selection: self.selection.clone(),
+            settings: self.settings.clone(),
+            undo_stack: self.undo_stack.clone(),
+            redo_stack: self.redo_stack.clone(),
+        }
+    }
+}
+
+impl<K: SettingKey + EnumArray<V>, V: SettingValue> SettingsList<K, V> {
+    /// The height that should be used for the `Rect` passed to
+    /// `&SettingsList::render()` in order to show all of the settings at
+    /// once.  If the `Rect` is shorter, the settings scroll to keep the
+    /// selection visible.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) const HEIGHT: u16 = (K::LENGTH as u16) + 2 /* for border */;
+
+    /// The width of the horizontal padding on each inner side of the list
+    /// border
+    const HORIZONTAL_PADDING: u16 = 1;
+
+    /// The number of total display column cells used by the selection pointer
+    const POINTER_WIDTH: u16 = 2;
+
+    /// The number of display column cells between the keys and values
+    const LABEL_VALUE_GUTTER: u16 = 2;
+
+    /// The width that should be used for the `Rect` passed to
+    /// `&SettingsList::render()`
+    pub(crate) const WIDTH: u16 = 2 /* for border */ + 2 * Self::HORIZONTAL_PADDING + Self::POINTER_WIDTH + K::DISPLAY_WIDTH + Self::LABEL_VALUE_GUTTER + V::DISPLAY_WIDTH;
+
+    /// The maximum number of changes that can be undone
+    pub(crate) const UNDO_LIMIT: usize = 20;
+
+    /// Create a new `SettingsList` titled `title` with the given initial
+    /// values and nothing selected
+    pub(crate) fn new(title: Msg, settings: EnumMap<K, V>) -> Self {
+        SettingsList {
+            title,
+            selection: None,
+            settings,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Return the values currently displayed in the list
+    pub(crate) fn settings(&self) -> &EnumMap<K, V> {
+        &self.settings
+    }
+
+    /// Return the key of the selected item, if any
+    pub(crate) fn selection(&self) -> Option<K> {
+        self.selection
+    }
+
+    /// Select the item with the given key, or deselect the list if `None`
+    pub(crate) fn select(&mut self, key: Option<K>) {
+        self.selection = key;
+    }
+
+    /// Select the previous item in the list.  If the first item was
+    /// selected, the list is deselected instead, and the enclosing screen
+    /// should move its selection elsewhere.
+    pub(crate) fn move_up(&mut self) {
+        self.selection = self.selection.and_then(K::prev);
+    }
+
+    /// Select the next item in the list.  If the last item was selected, the
+    /// list is deselected instead, and the enclosing screen should move its
+    /// selection elsewhere.
+    pub(crate) fn move_down(&mut self) {
+        self.selection = self.selection.and_then(K::next);
+    }
+
+    /// Respond to a "Left" input by decreasing or unsetting the current
+    /// setting, if possible
+    pub(crate) fn move_left(&mut self) {
+        self.adjust(V::decrease);
+    }
+
+    /// Respond to a "Right" input by increasing or setting the current
+    /// setting, if possible
+    pub(crate) fn move_right(&mut self) {
+        self.adjust(V::increase);
+    }
+
+    /// Toggle the current setting, if possible
+    pub(crate) fn toggle(&mut self) {
+        self.adjust(V::toggle);
+    }
+
+    /// Apply `f` to the value of the current setting, if any, and record the
+    /// change (if the value changed) so that it can be undone
+    fn adjust<F: FnOnce(&mut V)>(&mut self, f: F) {
+        if let Some(key) = self.selection {
+            let old = self.settings[key];
+            f(&mut self.settings[key]);
+            let new = self.settings[key];
+            if new != old {
+                if self.undo_stack.len() >= Self::UNDO_LIMIT {
+                    self.undo_stack.remove(0);
+                }
+                self.undo_stack.push(Change { key, old, new });
+                self.redo_stack.clear();
+            }
+        }
+    }
+
+    /// Revert the most recent change that has not been undone, if any, and
+    /// return the key of the setting that was changed
+    pub(crate) fn undo(&mut self) -> Option<K> {
+        let change = self.undo_stack.pop()?;
+        self.settings[change.key] = change.old;
+        self.redo_stack.push(change);
+        Some(change.key)
+    }
+
+    /// Reapply the most recently undone change, if any, and return the key of
+    /// the setting that was changed
+    pub(crate) fn redo(&mut self) -> Option<K> {
+        let change = self.redo_stack.pop()?;
+        self.settings[change.key] = change.new;
+        self.undo_stack.push(change);
+        Some(change.key)
+    }
+
+    /// Render the list in `area` of `buf`, laid out in `direction`
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer, direction: LayoutDirection) {
+        let block = Block::bordered()
+            .title(format!(" {} ", tr(self.title)))
+            .title_alignment(direction.start())
+            .padding(Padding::horizontal(Self::HORIZONTAL_PADDING));
+        let list_area = block.inner(area);
+        block.render(area, buf);
+        let visible = usize::from(list_area.height);
+        let offset = self
+            .selection
+            .map_or(0, |key| (key.into_usize() + 1).saturating_sub(visible));
+        // Scroll indicators go in the corners opposite the title
+        let indicator_x = match direction {
+            LayoutDirection::LeftToRight => area.right().saturating_sub(3),
+            LayoutDirection::RightToLeft => area.x.saturating_add(2),
+        };
+        if offset > 0 {
+            Span::raw("▲").render(Rect::new(indicator_x, area.y, 1, 1), buf);
+        }
+        if offset + visible < K::LENGTH {
+            Span::raw("▼").render(
+                Rect::new(indicator_x, area.bottom().saturating_sub(1), 1, 1),
+                buf,
+            );
+        }
+        for ((key, value), row) in K::iter()
+            .map(|key| (key, self.settings[key]))
+            .skip(offset)
+            .zip(list_area.rows())
+        {
+            let selected = Some(key) == self.selection;
+            let style = if selected {
+                theme::selection_style()
+            } else {
+                Style::new()
+            };
+            let pwidth = usize::from(Self::POINTER_WIDTH);
+            let lwidth = usize::from(K::DISPLAY_WIDTH);
+            let gutter = usize::from(Self::LABEL_VALUE_GUTTER);
+            let s = match direction {
+                LayoutDirection::LeftToRight => format!(
+                    "{pointer:pwidth$}{key:lwidth$}{space:gutter$}{value}",
+                    pointer = if selected { "»" } else { "" },
+                    space = "",
+                ),
+                LayoutDirection::RightToLeft => format!(
+                    "{value}{space:gutter$}{key:>lwidth$}{pointer:>pwidth$}",
+                    value = value.mirrored(),
+                    pointer = if selected { "«" } else { "" },
+                    space = "",
+                ),
+            };
+            Span::styled(s, style).render(row, buf);
+        }
+    }
+}
+
+/// A change made to a setting in a [`SettingsList`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Change<K, V> {
+    /// The setting that was changed
+    key: K,
+
+    /// The setting's value before the change
+    old: V,
+
+    /// The setting's value after the change
+    new: V,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{OptKey, OptValue, Options};
+
+    fn options_list() -> SettingsList<OptKey, OptValue> {
+        let options = Options::default();
+        SettingsList::new(
+            Msg::OptionsTitle,
+            EnumMap::from_iter(OptKey::iter().map(|key| (key, options.get(key)))),
+        )
+    }
+
+    #[test]
+    fn navigate() {
+        let mut list = options_list();
+        list.move_down();
+        assert_eq!(list.selection(), None);
+        list.select(Some(OptKey::min()));
+        list.move_down();
+        assert_eq!(list.selection(), Some(OptKey::Obstacles));
+        list.move_up();
+        list.move_up();
+        assert_eq!(list.selection(), None);
+        list.select(Some(OptKey::max()));
+        list.move_down();
+        assert_eq!(list.selection(), None);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut list = options_list();
+        // Nothing changes without a selection
+        list.toggle();
+        assert_eq!(list.undo(), None);
+        list.select(Some(OptKey::Wraparound));
+        list.toggle();
+        assert_eq!(list.settings()[OptKey::Wraparound], OptValue::Bool(true));
+        list.select(None);
+        assert_eq!(list.undo(), Some(OptKey::Wraparound));
+        assert_eq!(list.settings()[OptKey::Wraparound], OptValue::Bool(false));
+        assert_eq!(list.redo(), Some(OptKey::Wraparound));
+        assert_eq!(list.settings()[OptKey::Wraparound], OptValue::Bool(true));
+        assert_eq!(list.redo(), None);
+    }
+}